@@ -91,6 +91,20 @@ pub struct Certificates {
     pub refresh_after: DateTime<Utc>,
 }
 
+impl Certificates {
+    /// Whether the server will no longer accept messages signed with these
+    /// certificates.
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+
+    /// Whether new certificates should be requested from Mojang. This happens
+    /// a while before they actually expire.
+    pub fn should_refresh(&self) -> bool {
+        Utc::now() >= self.refresh_after
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CertificatesResponse {
     #[serde(rename = "keyPair")]
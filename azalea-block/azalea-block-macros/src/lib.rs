@@ -17,7 +17,7 @@ use syn::{
     punctuated::Punctuated,
     token, Expr, Ident, LitStr, Token,
 };
-use utils::{combinations_of, to_pascal_case};
+use utils::{combinations_of, to_pascal_case, to_snake_case};
 
 // must be the same as the type in `azalea-block/src/lib.rs`
 type BlockStateIntegerRepr = u16;
@@ -382,6 +382,8 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
     let mut from_registry_block_to_block_match = quote! {};
     let mut from_registry_block_to_blockstate_match = quote! {};
     let mut from_registry_block_to_blockstates_match = quote! {};
+    let mut from_properties_match = quote! {};
+    let mut to_properties_match = quote! {};
 
     // {
     //     Waterlogged: [
@@ -390,6 +392,13 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
     //     }
     // }
     let mut properties_to_state_ids: HashMap<String, Vec<PropertyVariantData>> = HashMap::new();
+    // {
+    //     Waterlogged: [
+    //         7035..=7058 => id - (id - 7035) / 1 % 2 * 1 + index * 1,
+    //         ...
+    //     ]
+    // }
+    let mut properties_to_set_arms: HashMap<String, proc_macro2::TokenStream> = HashMap::new();
 
     for block in &input.block_definitions.blocks {
         let block_property_names = &block
@@ -585,6 +594,11 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
         //     }
         // }
         let mut from_state_to_block_inner = quote! {};
+        // if a block has the same property more than once, only the first one can be
+        // set. the properties are in reverse order here, so the first one overwrites
+        // the others.
+        let mut set_arms = HashMap::new();
+        let last_state_id = state_id - 1;
         let mut division: BlockStateIntegerRepr = 1;
         for i in (0..properties_with_name.len()).rev() {
             let PropertyWithNameAndDefault {
@@ -609,10 +623,119 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
                 #property_name_ident: #conversion_code,
             });
 
+            set_arms.insert(
+                property_struct_name_ident.to_string(),
+                quote! {
+                    #first_state_id..=#last_state_id => {
+                        id - (id - #first_state_id) / #division % #property_variants_count * #division
+                            + index * #division
+                    },
+                },
+            );
+
             division *= property_variants_count;
         }
+        for (property_struct_name, set_arm) in set_arms {
+            properties_to_set_arms
+                .entry(property_struct_name)
+                .or_default()
+                .extend(set_arm);
+        }
 
-        let last_state_id = state_id - 1;
+        // azalea_registry::Block::AcaciaButton => {
+        //     let mut id = 7035;
+        //     id += match get("powered") {
+        //         Some("true") => 0,
+        //         Some("false") => 1,
+        //         _ => 1,
+        //     } * 1;
+        //     ...
+        //     BlockState { id }
+        // }
+        // 7035..=7058 => {
+        //     let b = b - 7035;
+        //     vec![
+        //         ("face", match (b / 8) % 3 { 0 => "floor", 1 => "wall", _ =>
+        // "ceiling" }),         ...
+        //     ]
+        // }
+        let mut from_properties_inner = quote! {};
+        let mut to_properties_inner = Vec::new();
+        let mut division: BlockStateIntegerRepr = 1;
+        for i in (0..block.properties_and_defaults.len()).rev() {
+            let property = &block.properties_and_defaults[i];
+            let property_name = &property.name;
+            let property_variants = &block_properties_vec[i];
+            let property_variants_count = property_variants.len() as BlockStateIntegerRepr;
+
+            let property_default_as_string = if let TokenTree::Ident(ident) =
+                property.default.clone().into_iter().last().unwrap()
+            {
+                ident.to_string()
+            } else {
+                panic!()
+            };
+            let default_index = property_variants
+                .iter()
+                .position(|v| *v == property_default_as_string)
+                .unwrap() as BlockStateIntegerRepr;
+
+            let mut value_match_inner = quote! {};
+            let mut index_match_inner = quote! {};
+            for (index, variant) in property_variants.iter().enumerate() {
+                let value = to_snake_case(variant);
+                let index = index as BlockStateIntegerRepr;
+                value_match_inner.extend(quote! {
+                    Some(#value) => #index,
+                });
+                index_match_inner.extend(if index == property_variants_count - 1 {
+                    quote! { _ => #value, }
+                } else {
+                    quote! { #index => #value, }
+                });
+            }
+            from_properties_inner.extend(quote! {
+                id += match get(#property_name) {
+                    #value_match_inner
+                    _ => #default_index,
+                } * #division;
+            });
+            to_properties_inner.push(quote! {
+                (#property_name, match (b / #division) % #property_variants_count {
+                    #index_match_inner
+                }),
+            });
+
+            division *= property_variants_count;
+        }
+        // the properties were added in reverse order
+        to_properties_inner.reverse();
+
+        from_properties_match.extend(if block.properties_and_defaults.is_empty() {
+            quote! {
+                azalea_registry::Block::#block_name_pascal_case => BlockState { id: #first_state_id },
+            }
+        } else {
+            quote! {
+                azalea_registry::Block::#block_name_pascal_case => {
+                    let mut id = #first_state_id;
+                    #from_properties_inner
+                    BlockState { id }
+                },
+            }
+        });
+        to_properties_match.extend(if block.properties_and_defaults.is_empty() {
+            quote! {
+                #first_state_id => Vec::new(),
+            }
+        } else {
+            quote! {
+                #first_state_id..=#last_state_id => {
+                    let b = b - #first_state_id;
+                    vec![#(#to_properties_inner)*]
+                },
+            }
+        });
         from_state_to_block_match.extend(quote! {
             #first_state_id..=#last_state_id => {
                 let b = b - #first_state_id;
@@ -709,6 +832,66 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
             pub fn property<P: Property>(self) -> Option<P::Value> {
                 P::try_from_block_state(self)
             }
+
+            /// Get this block state with a property changed to the given value. Will be `None`
+            /// if the block can't have the property, or if the property doesn't implement
+            /// [`Property::try_set_in_block_state`].
+            ///
+            /// If the block has the property more than once (like the bottles in a brewing
+            /// stand), only the first one is changed.
+            ///
+            /// ```
+            /// # use azalea_block::{BlockState, properties::{FacingCardinal, Open}};
+            /// let door = BlockState::from(azalea_registry::Block::OakDoor);
+            /// let door = door.with_property::<Open>(true).unwrap();
+            /// let door = door.with_property::<FacingCardinal>(FacingCardinal::East).unwrap();
+            /// assert_eq!(door.property::<Open>(), Some(true));
+            /// assert_eq!(door.property::<FacingCardinal>(), Some(FacingCardinal::East));
+            ///
+            /// let stone = BlockState::from(azalea_registry::Block::Stone);
+            /// assert_eq!(stone.with_property::<Open>(true), None);
+            /// ```
+            pub fn with_property<P: Property>(self, value: P::Value) -> Option<BlockState> {
+                P::try_set_in_block_state(self, value)
+            }
+
+            /// Get the block state for a block with the given properties, like the ones
+            /// in structure and world files. Properties that are missing or have invalid
+            /// values are set to their defaults.
+            ///
+            /// ```
+            /// # use azalea_block::{BlockState, properties::Axis};
+            /// let state = BlockState::from_properties(azalea_registry::Block::OakLog, &[("axis", "x")]);
+            /// assert_eq!(state.property::<Axis>(), Some(Axis::X));
+            /// ```
+            pub fn from_properties(block: azalea_registry::Block, properties: &[(&str, &str)]) -> BlockState {
+                let get = |name: &str| {
+                    properties
+                        .iter()
+                        .find(|(property_name, _)| *property_name == name)
+                        .map(|(_, value)| *value)
+                };
+                match block {
+                    #from_properties_match
+                    _ => unreachable!("There should always be a block state for every azalea_registry::Block variant")
+                }
+            }
+
+            /// Get the names and values of this block state's properties, in the same
+            /// format that [`Self::from_properties`] takes.
+            ///
+            /// ```
+            /// # use azalea_block::BlockState;
+            /// let state = BlockState::from_properties(azalea_registry::Block::OakLog, &[("axis", "x")]);
+            /// assert_eq!(state.properties(), vec![("axis", "x")]);
+            /// ```
+            pub fn properties(self) -> Vec<(&'static str, &'static str)> {
+                let b = self.id;
+                match b {
+                    #to_properties_match
+                    _ => Vec::new(),
+                }
+            }
         }
     };
 
@@ -755,6 +938,15 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
         } else {
             quote! { bool }
         };
+        // the variants of bool properties start with true
+        let value_index = if is_enum {
+            quote! { value as crate::block_state::BlockStateIntegerRepr }
+        } else {
+            quote! { crate::block_state::BlockStateIntegerRepr::from(!value) }
+        };
+        let set_arms = properties_to_set_arms
+            .remove(&property_struct_name.to_string())
+            .unwrap_or_default();
 
         let property_impl = quote! {
             impl Property for #property_struct_name {
@@ -766,6 +958,16 @@ pub fn make_block_states(input: TokenStream) -> TokenStream {
                         _ => None
                     }
                 }
+
+                fn try_set_in_block_state(block_state: BlockState, value: Self::Value) -> Option<BlockState> {
+                    let index = #value_index;
+                    let id = block_state.id;
+                    let id = match id {
+                        #set_arms
+                        _ => return None
+                    };
+                    Some(BlockState { id })
+                }
             }
         };
         property_impls.extend(property_impl);
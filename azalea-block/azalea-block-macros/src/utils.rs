@@ -46,3 +46,20 @@ pub fn to_pascal_case(s: &str) -> String {
     }
     result
 }
+
+/// The opposite of [`to_pascal_case`], for getting the original names of
+/// property values back.
+pub fn to_snake_case(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.trim_start_matches('_').chars() {
+        if c.is_ascii_uppercase() {
+            if !result.is_empty() {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
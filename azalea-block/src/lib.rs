@@ -14,7 +14,7 @@ pub use behavior::BlockBehavior;
 // re-exported for convenience
 pub use block_state::BlockState;
 pub use generated::{blocks, properties};
-pub use range::BlockStates;
+pub use range::{BlockSetFilter, BlockStates};
 
 pub trait Block: Debug + Any {
     fn behavior(&self) -> BlockBehavior;
@@ -38,4 +38,12 @@ pub trait Property {
     type Value;
 
     fn try_from_block_state(state: BlockState) -> Option<Self::Value>;
+    /// Change the value of this property in the block state, or return `None`
+    /// if the block can't have the property.
+    ///
+    /// The properties that are generated by azalea-block implement this, but
+    /// it returns `None` by default so other implementations don't have to.
+    fn try_set_in_block_state(_state: BlockState, _value: Self::Value) -> Option<BlockState> {
+        None
+    }
 }
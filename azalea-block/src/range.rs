@@ -35,6 +35,39 @@ impl BlockStates {
     }
 }
 
+/// Something that decides which block states to look for, like in
+/// `Instance::find_blocks`.
+///
+/// This is implemented for [`BlockStates`], single blocks, and closures that
+/// take a [`BlockState`].
+pub trait BlockSetFilter {
+    fn contains_state(&self, state: BlockState) -> bool;
+}
+
+impl BlockSetFilter for BlockStates {
+    fn contains_state(&self, state: BlockState) -> bool {
+        self.contains(&state)
+    }
+}
+
+impl BlockSetFilter for &BlockStates {
+    fn contains_state(&self, state: BlockState) -> bool {
+        self.contains(&state)
+    }
+}
+
+impl BlockSetFilter for azalea_registry::Block {
+    fn contains_state(&self, state: BlockState) -> bool {
+        azalea_registry::Block::from(state) == *self
+    }
+}
+
+impl<F: Fn(BlockState) -> bool> BlockSetFilter for F {
+    fn contains_state(&self, state: BlockState) -> bool {
+        self(state)
+    }
+}
+
 impl Add for BlockStates {
     type Output = Self;
 
@@ -0,0 +1,194 @@
+use std::{any::Any, sync::Arc};
+
+use super::ArgumentType;
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+/// One coordinate of a position, which is either absolute or relative to the
+/// position of whoever ran the command (with `~`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldCoordinate {
+    pub value: f64,
+    pub relative: bool,
+}
+
+impl WorldCoordinate {
+    pub fn resolve(&self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+
+    fn parse_int(reader: &mut StringReader) -> Result<WorldCoordinate, CommandSyntaxException> {
+        if reader.can_read() && reader.peek() == '^' {
+            return Err(BuiltInExceptions::PosMixed.create_with_context(reader));
+        }
+        if !reader.can_read() {
+            return Err(BuiltInExceptions::ReaderExpectedInt.create_with_context(reader));
+        }
+        if reader.peek() == '~' {
+            reader.skip();
+            return Ok(WorldCoordinate {
+                value: read_offset(reader)?,
+                relative: true,
+            });
+        }
+        Ok(WorldCoordinate {
+            value: reader.read_int()? as f64,
+            relative: false,
+        })
+    }
+}
+
+/// A position from a command, like `~ ~1 ~` or `^ ^ ^2`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinates {
+    World {
+        x: WorldCoordinate,
+        y: WorldCoordinate,
+        z: WorldCoordinate,
+    },
+    /// Coordinates with `^`, which are relative to where the player is
+    /// looking.
+    Local { left: f64, up: f64, forwards: f64 },
+}
+
+impl Coordinates {
+    /// Get the position that these coordinates point to.
+    ///
+    /// `origin` is the position of whoever ran the command (usually their eye
+    /// position for local coordinates), and `y_rot` and `x_rot` are the
+    /// direction that they're looking in.
+    pub fn resolve(&self, origin: [f64; 3], y_rot: f32, x_rot: f32) -> [f64; 3] {
+        match self {
+            Coordinates::World { x, y, z } => [
+                x.resolve(origin[0]),
+                y.resolve(origin[1]),
+                z.resolve(origin[2]),
+            ],
+            Coordinates::Local { left, up, forwards } => {
+                let yaw = (y_rot as f64 + 90.).to_radians();
+                let pitch = (-x_rot as f64).to_radians();
+                let pitch_up = (-x_rot as f64 + 90.).to_radians();
+                let forward = [
+                    yaw.cos() * pitch.cos(),
+                    pitch.sin(),
+                    yaw.sin() * pitch.cos(),
+                ];
+                let up_vec = [
+                    yaw.cos() * pitch_up.cos(),
+                    pitch_up.sin(),
+                    yaw.sin() * pitch_up.cos(),
+                ];
+                // left is the cross product of forward and up, flipped
+                let left_vec = [
+                    -(forward[1] * up_vec[2] - forward[2] * up_vec[1]),
+                    -(forward[2] * up_vec[0] - forward[0] * up_vec[2]),
+                    -(forward[0] * up_vec[1] - forward[1] * up_vec[0]),
+                ];
+                [0, 1, 2].map(|axis| {
+                    origin[axis]
+                        + forward[axis] * forwards
+                        + up_vec[axis] * up
+                        + left_vec[axis] * left
+                })
+            }
+        }
+    }
+
+    /// Like [`Self::resolve`], but rounded down to a block position.
+    pub fn block_pos(&self, origin: [f64; 3], y_rot: f32, x_rot: f32) -> [i32; 3] {
+        self.resolve(origin, y_rot, x_rot)
+            .map(|coordinate| coordinate.floor() as i32)
+    }
+
+    /// Whether the position depends on where the player that ran the command
+    /// is.
+    pub fn is_relative(&self) -> bool {
+        match self {
+            Coordinates::World { x, y, z } => x.relative || y.relative || z.relative,
+            Coordinates::Local { .. } => true,
+        }
+    }
+
+    fn parse_local(reader: &mut StringReader) -> Result<Coordinates, CommandSyntaxException> {
+        let left = read_local_coordinate(reader)?;
+        expect_separator(reader)?;
+        let up = read_local_coordinate(reader)?;
+        expect_separator(reader)?;
+        let forwards = read_local_coordinate(reader)?;
+        Ok(Coordinates::Local { left, up, forwards })
+    }
+
+    fn parse_world(reader: &mut StringReader) -> Result<Coordinates, CommandSyntaxException> {
+        let x = WorldCoordinate::parse_int(reader)?;
+        expect_separator(reader)?;
+        let y = WorldCoordinate::parse_int(reader)?;
+        expect_separator(reader)?;
+        let z = WorldCoordinate::parse_int(reader)?;
+        Ok(Coordinates::World { x, y, z })
+    }
+}
+
+fn expect_separator(reader: &mut StringReader) -> Result<(), CommandSyntaxException> {
+    if !reader.can_read() || reader.peek() != ' ' {
+        return Err(BuiltInExceptions::PosIncomplete.create_with_context(reader));
+    }
+    reader.skip();
+    Ok(())
+}
+
+/// Read the number after a `~` or `^`, which defaults to 0.
+fn read_offset(reader: &mut StringReader) -> Result<f64, CommandSyntaxException> {
+    if reader.can_read() && reader.peek() != ' ' {
+        reader.read_double()
+    } else {
+        Ok(0.)
+    }
+}
+
+fn read_local_coordinate(reader: &mut StringReader) -> Result<f64, CommandSyntaxException> {
+    if !reader.can_read() || reader.peek() != '^' {
+        return Err(BuiltInExceptions::PosMixed.create_with_context(reader));
+    }
+    reader.skip();
+    read_offset(reader)
+}
+
+#[derive(Default)]
+struct BlockPos;
+
+impl ArgumentType for BlockPos {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let coordinates = if reader.can_read() && reader.peek() == '^' {
+            Coordinates::parse_local(reader)?
+        } else {
+            Coordinates::parse_world(reader)?
+        };
+        Ok(Arc::new(coordinates))
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["0 0 0", "~ ~ ~", "^ ^ ^", "^1 ^ ^-5", "~0.5 ~1 ~-5"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+/// Three block coordinates, which can be relative with `~` or local with `^`.
+pub fn block_pos() -> impl ArgumentType {
+    BlockPos
+}
+pub fn get_block_pos<S>(context: &CommandContext<S>, name: &str) -> Option<Coordinates> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<Coordinates>()
+        .copied()
+}
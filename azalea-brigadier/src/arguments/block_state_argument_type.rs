@@ -0,0 +1,125 @@
+use std::{any::Any, sync::Arc};
+
+use super::{resource_location_argument_type::read_resource_location, ArgumentType};
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+/// A block state from a command, like `minecraft:oak_stairs[facing=east]`.
+///
+/// The id and properties aren't checked against the block registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockInput {
+    /// The block id, including the namespace.
+    pub id: String,
+    /// The properties in the order that they were written.
+    pub properties: Vec<(String, String)>,
+    /// The block entity data after the properties, including the braces.
+    pub nbt: Option<String>,
+}
+
+impl BlockInput {
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn parse_properties(
+        reader: &mut StringReader,
+        block: &str,
+    ) -> Result<Vec<(String, String)>, CommandSyntaxException> {
+        let mut properties: Vec<(String, String)> = Vec::new();
+        reader.skip();
+        reader.skip_whitespace();
+        while reader.can_read() && reader.peek() != ']' {
+            reader.skip_whitespace();
+            let start = reader.cursor;
+            let property = reader.read_string()?;
+            if properties.iter().any(|(key, _)| *key == property) {
+                reader.cursor = start;
+                return Err(BuiltInExceptions::BlockPropertyDuplicate {
+                    property,
+                    block: block.to_string(),
+                }
+                .create_with_context(reader));
+            }
+
+            reader.skip_whitespace();
+            if !reader.can_read() || reader.peek() != '=' {
+                return Err(BuiltInExceptions::BlockPropertyNoValue {
+                    property,
+                    block: block.to_string(),
+                }
+                .create_with_context(reader));
+            }
+            reader.skip();
+            reader.skip_whitespace();
+            let value = reader.read_string()?;
+            properties.push((property, value));
+
+            reader.skip_whitespace();
+            if !reader.can_read() {
+                break;
+            }
+            match reader.peek() {
+                ',' => reader.skip(),
+                ']' => {}
+                _ => {
+                    return Err(BuiltInExceptions::BlockPropertyUnclosed.create_with_context(reader))
+                }
+            }
+        }
+
+        if !reader.can_read() {
+            return Err(BuiltInExceptions::BlockPropertyUnclosed.create_with_context(reader));
+        }
+        reader.skip();
+        Ok(properties)
+    }
+}
+
+#[derive(Default)]
+struct BlockState;
+
+impl ArgumentType for BlockState {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let id = read_resource_location(reader)?;
+        let properties = if reader.can_read() && reader.peek() == '[' {
+            BlockInput::parse_properties(reader, &id)?
+        } else {
+            Vec::new()
+        };
+        let nbt = if reader.can_read() && reader.peek() == '{' {
+            Some(reader.read_bracketed()?)
+        } else {
+            None
+        };
+        Ok(Arc::new(BlockInput {
+            id,
+            properties,
+            nbt,
+        }))
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["stone", "minecraft:stone", "stone[foo=bar]", "foo{bar=baz}"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+pub fn block_state() -> impl ArgumentType {
+    BlockState
+}
+pub fn get_block_state<S>(context: &CommandContext<S>, name: &str) -> Option<BlockInput> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<BlockInput>()
+        .cloned()
+}
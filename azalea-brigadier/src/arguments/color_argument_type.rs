@@ -0,0 +1,144 @@
+use std::{any::Any, sync::Arc};
+
+use super::ArgumentType;
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
+};
+
+/// One of the named chat colors, like the ones that are used for team colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamedColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Reset,
+}
+
+impl NamedColor {
+    pub const ALL: [NamedColor; 17] = [
+        NamedColor::Black,
+        NamedColor::DarkBlue,
+        NamedColor::DarkGreen,
+        NamedColor::DarkAqua,
+        NamedColor::DarkRed,
+        NamedColor::DarkPurple,
+        NamedColor::Gold,
+        NamedColor::Gray,
+        NamedColor::DarkGray,
+        NamedColor::Blue,
+        NamedColor::Green,
+        NamedColor::Aqua,
+        NamedColor::Red,
+        NamedColor::LightPurple,
+        NamedColor::Yellow,
+        NamedColor::White,
+        NamedColor::Reset,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedColor::Black => "black",
+            NamedColor::DarkBlue => "dark_blue",
+            NamedColor::DarkGreen => "dark_green",
+            NamedColor::DarkAqua => "dark_aqua",
+            NamedColor::DarkRed => "dark_red",
+            NamedColor::DarkPurple => "dark_purple",
+            NamedColor::Gold => "gold",
+            NamedColor::Gray => "gray",
+            NamedColor::DarkGray => "dark_gray",
+            NamedColor::Blue => "blue",
+            NamedColor::Green => "green",
+            NamedColor::Aqua => "aqua",
+            NamedColor::Red => "red",
+            NamedColor::LightPurple => "light_purple",
+            NamedColor::Yellow => "yellow",
+            NamedColor::White => "white",
+            NamedColor::Reset => "reset",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NamedColor> {
+        let name = name.to_lowercase();
+        NamedColor::ALL
+            .into_iter()
+            .find(|color| color.name() == name)
+    }
+
+    /// The RGB value of the color, or `None` for [`NamedColor::Reset`].
+    pub fn rgb(&self) -> Option<u32> {
+        Some(match self {
+            NamedColor::Black => 0x000000,
+            NamedColor::DarkBlue => 0x0000AA,
+            NamedColor::DarkGreen => 0x00AA00,
+            NamedColor::DarkAqua => 0x00AAAA,
+            NamedColor::DarkRed => 0xAA0000,
+            NamedColor::DarkPurple => 0xAA00AA,
+            NamedColor::Gold => 0xFFAA00,
+            NamedColor::Gray => 0xAAAAAA,
+            NamedColor::DarkGray => 0x555555,
+            NamedColor::Blue => 0x5555FF,
+            NamedColor::Green => 0x55FF55,
+            NamedColor::Aqua => 0x55FFFF,
+            NamedColor::Red => 0xFF5555,
+            NamedColor::LightPurple => 0xFF55FF,
+            NamedColor::Yellow => 0xFFFF55,
+            NamedColor::White => 0xFFFFFF,
+            NamedColor::Reset => return None,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Color;
+
+impl ArgumentType for Color {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor;
+        let name = reader.read_unquoted_string().to_string();
+        let Some(color) = NamedColor::from_name(&name) else {
+            reader.cursor = start;
+            return Err(BuiltInExceptions::ColorInvalid { color: name }.create_with_context(reader));
+        };
+        Ok(Arc::new(color))
+    }
+
+    fn list_suggestions(&self, mut builder: SuggestionsBuilder) -> Suggestions {
+        for color in NamedColor::ALL {
+            if color.name().starts_with(builder.remaining_lowercase()) {
+                builder = builder.suggest(color.name());
+            }
+        }
+        builder.build()
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["red".to_string(), "green".to_string()]
+    }
+}
+
+pub fn color() -> impl ArgumentType {
+    Color
+}
+pub fn get_color<S>(context: &CommandContext<S>, name: &str) -> Option<NamedColor> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<NamedColor>()
+        .copied()
+}
@@ -0,0 +1,379 @@
+use std::{any::Any, sync::Arc};
+
+use super::ArgumentType;
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
+};
+
+/// The options that vanilla allows in the brackets after a selector, like the
+/// `type` in `@e[type=pig]`.
+const SELECTOR_OPTIONS: &[&str] = &[
+    "name",
+    "distance",
+    "level",
+    "x",
+    "y",
+    "z",
+    "dx",
+    "dy",
+    "dz",
+    "x_rotation",
+    "y_rotation",
+    "limit",
+    "sort",
+    "gamemode",
+    "team",
+    "type",
+    "tag",
+    "nbt",
+    "scores",
+    "advancements",
+    "predicate",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@p`
+    NearestPlayer,
+    /// `@r`
+    RandomPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@e`
+    AllEntities,
+    /// `@s`
+    SelfEntity,
+    /// `@n`
+    NearestEntity,
+}
+
+impl SelectorKind {
+    pub fn from_char(c: char) -> Option<SelectorKind> {
+        match c {
+            'p' => Some(SelectorKind::NearestPlayer),
+            'r' => Some(SelectorKind::RandomPlayer),
+            'a' => Some(SelectorKind::AllPlayers),
+            'e' => Some(SelectorKind::AllEntities),
+            's' => Some(SelectorKind::SelfEntity),
+            'n' => Some(SelectorKind::NearestEntity),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(&self) -> char {
+        match self {
+            SelectorKind::NearestPlayer => 'p',
+            SelectorKind::RandomPlayer => 'r',
+            SelectorKind::AllPlayers => 'a',
+            SelectorKind::AllEntities => 'e',
+            SelectorKind::SelfEntity => 's',
+            SelectorKind::NearestEntity => 'n',
+        }
+    }
+}
+
+/// An option in a selector, like `type=!pig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorOption {
+    pub key: String,
+    /// The value without the `!`. Quoted strings are unquoted, and the
+    /// contents of brackets (like for `scores` or `nbt`) are kept as-is.
+    pub value: String,
+    /// Whether the value started with `!`.
+    pub negated: bool,
+}
+
+/// A parsed target selector, player name, or UUID.
+///
+/// The options aren't checked beyond making sure that vanilla knows about
+/// them, so it's up to you to decide what they mean.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntitySelector {
+    Name(String),
+    Uuid(u128),
+    Selector {
+        kind: SelectorKind,
+        options: Vec<SelectorOption>,
+    },
+}
+
+impl EntitySelector {
+    /// Get the first option with the given key, if the selector has it.
+    pub fn option(&self, key: &str) -> Option<&SelectorOption> {
+        match self {
+            EntitySelector::Selector { options, .. } => {
+                options.iter().find(|option| option.key == key)
+            }
+            _ => None,
+        }
+    }
+
+    /// The most entities that this selector can match.
+    pub fn max_results(&self) -> usize {
+        let EntitySelector::Selector { kind, .. } = self else {
+            return 1;
+        };
+        if let Some(limit) = self
+            .option("limit")
+            .and_then(|option| option.value.parse().ok())
+        {
+            return limit;
+        }
+        match kind {
+            SelectorKind::AllPlayers | SelectorKind::AllEntities => usize::MAX,
+            _ => 1,
+        }
+    }
+
+    /// Whether the selector can match entities that aren't players.
+    pub fn includes_entities(&self) -> bool {
+        match self {
+            EntitySelector::Name(_) => false,
+            EntitySelector::Uuid(_) => true,
+            EntitySelector::Selector { kind, .. } => match kind {
+                SelectorKind::NearestPlayer
+                | SelectorKind::RandomPlayer
+                | SelectorKind::AllPlayers => false,
+                SelectorKind::SelfEntity => true,
+                SelectorKind::AllEntities | SelectorKind::NearestEntity => {
+                    !self.option("type").is_some_and(|option| {
+                        !option.negated
+                            && (option.value == "player" || option.value == "minecraft:player")
+                    })
+                }
+            },
+        }
+    }
+
+    pub fn is_self_selector(&self) -> bool {
+        matches!(
+            self,
+            EntitySelector::Selector {
+                kind: SelectorKind::SelfEntity,
+                ..
+            }
+        )
+    }
+
+    pub fn parse(reader: &mut StringReader) -> Result<EntitySelector, CommandSyntaxException> {
+        if reader.can_read() && reader.peek() == '@' {
+            reader.skip();
+            return Self::parse_selector(reader);
+        }
+
+        let start = reader.cursor;
+        let text = reader.read_string()?;
+        if let Some(uuid) = parse_uuid(&text) {
+            return Ok(EntitySelector::Uuid(uuid));
+        }
+        if text.is_empty() || text.len() > 16 {
+            reader.cursor = start;
+            return Err(BuiltInExceptions::EntityInvalidNameOrUuid.create_with_context(reader));
+        }
+        Ok(EntitySelector::Name(text))
+    }
+
+    fn parse_selector(reader: &mut StringReader) -> Result<EntitySelector, CommandSyntaxException> {
+        if !reader.can_read() {
+            return Err(BuiltInExceptions::EntitySelectorMissing.create_with_context(reader));
+        }
+        let start = reader.cursor;
+        let c = reader.read();
+        let Some(kind) = SelectorKind::from_char(c) else {
+            reader.cursor = start;
+            return Err(BuiltInExceptions::EntitySelectorUnknown {
+                selector: format!("@{c}"),
+            }
+            .create_with_context(reader));
+        };
+
+        let mut options = Vec::new();
+        if reader.can_read() && reader.peek() == '[' {
+            reader.skip();
+            options = Self::parse_options(reader)?;
+        }
+        Ok(EntitySelector::Selector { kind, options })
+    }
+
+    fn parse_options(
+        reader: &mut StringReader,
+    ) -> Result<Vec<SelectorOption>, CommandSyntaxException> {
+        let mut options = Vec::new();
+        reader.skip_whitespace();
+        while reader.can_read() && reader.peek() != ']' {
+            reader.skip_whitespace();
+            let start = reader.cursor;
+            let key = reader.read_string()?;
+            if !SELECTOR_OPTIONS.contains(&key.as_str()) {
+                reader.cursor = start;
+                return Err(
+                    BuiltInExceptions::EntitySelectorOptionUnknown { option: key }
+                        .create_with_context(reader),
+                );
+            }
+
+            reader.skip_whitespace();
+            if !reader.can_read() || reader.peek() != '=' {
+                reader.cursor = start;
+                return Err(
+                    BuiltInExceptions::EntitySelectorOptionValueless { option: key }
+                        .create_with_context(reader),
+                );
+            }
+            reader.skip();
+            reader.skip_whitespace();
+
+            let negated = reader.can_read() && reader.peek() == '!';
+            if negated {
+                reader.skip();
+            }
+            let value = read_option_value(reader)?;
+            options.push(SelectorOption {
+                key,
+                value,
+                negated,
+            });
+
+            reader.skip_whitespace();
+            if !reader.can_read() {
+                break;
+            }
+            match reader.peek() {
+                ',' => reader.skip(),
+                ']' => {}
+                _ => {
+                    return Err(BuiltInExceptions::EntitySelectorOptionsUnterminated
+                        .create_with_context(reader))
+                }
+            }
+        }
+
+        if !reader.can_read() {
+            return Err(
+                BuiltInExceptions::EntitySelectorOptionsUnterminated.create_with_context(reader)
+            );
+        }
+        reader.skip();
+        Ok(options)
+    }
+}
+
+fn read_option_value(reader: &mut StringReader) -> Result<String, CommandSyntaxException> {
+    if !reader.can_read() {
+        return Ok(String::new());
+    }
+    let next = reader.peek();
+    if StringReader::is_quoted_string_start(next) {
+        return reader.read_string();
+    }
+    if next == '{' || next == '[' {
+        return reader.read_bracketed();
+    }
+    let start = reader.cursor;
+    while reader.can_read() && !matches!(reader.peek(), ',' | ']') && !reader.peek().is_whitespace()
+    {
+        reader.skip();
+    }
+    Ok(reader.string()[start..reader.cursor].to_string())
+}
+
+/// Parse a hyphenated UUID like `dd12be42-52a9-4a91-a8a1-11c01849e498`.
+fn parse_uuid(text: &str) -> Option<u128> {
+    let parts = text.split('-').collect::<Vec<_>>();
+    if parts.len() != 5
+        || parts
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .any(|(part, len)| part.len() != len)
+    {
+        return None;
+    }
+    u128::from_str_radix(&parts.concat(), 16).ok()
+}
+
+struct Entity {
+    single: bool,
+    players_only: bool,
+}
+
+impl ArgumentType for Entity {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor;
+        let selector = EntitySelector::parse(reader)?;
+        if self.single && selector.max_results() > 1 {
+            reader.cursor = start;
+            let exception = if self.players_only {
+                BuiltInExceptions::PlayerTooMany
+            } else {
+                BuiltInExceptions::EntityTooMany
+            };
+            return Err(exception.create_with_context(reader));
+        }
+        if self.players_only && selector.includes_entities() && !selector.is_self_selector() {
+            reader.cursor = start;
+            return Err(BuiltInExceptions::PlayerOnlyPlayersAllowed.create_with_context(reader));
+        }
+        Ok(Arc::new(selector))
+    }
+
+    fn list_suggestions(&self, mut builder: SuggestionsBuilder) -> Suggestions {
+        for selector in ["@p", "@a", "@r", "@s", "@e", "@n"] {
+            if selector.starts_with(builder.remaining_lowercase()) {
+                builder = builder.suggest(selector);
+            }
+        }
+        builder.build()
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec![
+            "Player",
+            "0123",
+            "@e",
+            "@e[type=foo]",
+            "dd12be42-52a9-4a91-a8a1-11c01849e498",
+        ]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+}
+
+/// A single entity, like `@s`, `@e[limit=1]`, or a player name.
+pub fn entity() -> impl ArgumentType {
+    Entity {
+        single: true,
+        players_only: false,
+    }
+}
+/// Any number of entities, like `@e[type=pig]`.
+pub fn entities() -> impl ArgumentType {
+    Entity {
+        single: false,
+        players_only: false,
+    }
+}
+/// A single player, like `@p` or a player name.
+pub fn player() -> impl ArgumentType {
+    Entity {
+        single: true,
+        players_only: true,
+    }
+}
+/// Any number of players, like `@a`.
+pub fn players() -> impl ArgumentType {
+    Entity {
+        single: false,
+        players_only: true,
+    }
+}
+pub fn get_entity_selector<S>(context: &CommandContext<S>, name: &str) -> Option<EntitySelector> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<EntitySelector>()
+        .cloned()
+}
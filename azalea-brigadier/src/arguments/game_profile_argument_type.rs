@@ -0,0 +1,64 @@
+use std::{any::Any, sync::Arc};
+
+use super::{entity_argument_type::EntitySelector, ArgumentType};
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+/// One or more players from a command, which don't have to be online.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameProfileInput {
+    /// A player name or UUID, which isn't checked.
+    Name(String),
+    /// A selector that only matches players.
+    Selector(EntitySelector),
+}
+
+#[derive(Default)]
+struct GameProfile;
+
+impl ArgumentType for GameProfile {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        if reader.can_read() && reader.peek() == '@' {
+            let start = reader.cursor;
+            let selector = EntitySelector::parse(reader)?;
+            if selector.includes_entities() {
+                reader.cursor = start;
+                return Err(BuiltInExceptions::PlayerOnlyPlayersAllowed.create_with_context(reader));
+            }
+            return Ok(Arc::new(GameProfileInput::Selector(selector)));
+        }
+
+        let start = reader.cursor;
+        while reader.can_read() && reader.peek() != ' ' {
+            reader.skip();
+        }
+        let name = reader.string()[start..reader.cursor].to_string();
+        Ok(Arc::new(GameProfileInput::Name(name)))
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec![
+            "Player",
+            "0123",
+            "dd12be42-52a9-4a91-a8a1-11c01849e498",
+            "@e",
+        ]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+}
+
+pub fn game_profile() -> impl ArgumentType {
+    GameProfile
+}
+pub fn get_game_profile<S>(context: &CommandContext<S>, name: &str) -> Option<GameProfileInput> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<GameProfileInput>()
+        .cloned()
+}
@@ -0,0 +1,51 @@
+use std::{any::Any, sync::Arc};
+
+use super::{resource_location_argument_type::read_resource_location, ArgumentType};
+use crate::{
+    context::CommandContext, exceptions::CommandSyntaxException, string_reader::StringReader,
+};
+
+/// An item from a command, like `minecraft:stick[custom_name='"hi"']`.
+///
+/// The id isn't checked against the item registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemInput {
+    /// The item id, including the namespace.
+    pub id: String,
+    /// The data components in brackets after the id, including the brackets.
+    /// They're kept as they were written in the command.
+    pub components: Option<String>,
+}
+
+#[derive(Default)]
+struct Item;
+
+impl ArgumentType for Item {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let id = read_resource_location(reader)?;
+        let components = if reader.can_read() && reader.peek() == '[' {
+            Some(reader.read_bracketed()?)
+        } else {
+            None
+        };
+        Ok(Arc::new(ItemInput { id, components }))
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["stick", "minecraft:stick", "stick[custom_data={foo=bar}]"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+pub fn item() -> impl ArgumentType {
+    Item
+}
+pub fn get_item<S>(context: &CommandContext<S>, name: &str) -> Option<ItemInput> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<ItemInput>()
+        .cloned()
+}
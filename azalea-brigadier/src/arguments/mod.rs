@@ -1,9 +1,17 @@
 mod argument_type;
+pub mod block_pos_argument_type;
+pub mod block_state_argument_type;
 pub mod bool_argument_type;
+pub mod color_argument_type;
 pub mod double_argument_type;
+pub mod entity_argument_type;
 pub mod float_argument_type;
+pub mod game_profile_argument_type;
 pub mod integer_argument_type;
+pub mod item_argument_type;
 pub mod long_argument_type;
+pub mod resource_location_argument_type;
 pub mod string_argument_type;
+pub mod time_argument_type;
 
 pub use argument_type::ArgumentType;
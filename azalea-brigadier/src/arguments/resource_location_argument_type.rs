@@ -0,0 +1,69 @@
+use std::{any::Any, sync::Arc};
+
+use super::ArgumentType;
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+#[derive(Default)]
+struct ResourceLocation;
+
+impl ArgumentType for ResourceLocation {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        Ok(Arc::new(read_resource_location(reader)?))
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["foo", "foo:bar", "012"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+fn is_allowed_in_resource_location(c: char) -> bool {
+    c.is_ascii_digit()
+        || c.is_ascii_lowercase()
+        || c == '_'
+        || c == ':'
+        || c == '/'
+        || c == '.'
+        || c == '-'
+}
+
+/// Read an identifier like `minecraft:stone`, adding the `minecraft`
+/// namespace if it's missing.
+pub fn read_resource_location(reader: &mut StringReader) -> Result<String, CommandSyntaxException> {
+    let start = reader.cursor;
+    while reader.can_read() && is_allowed_in_resource_location(reader.peek()) {
+        reader.skip();
+    }
+    let text = &reader.string()[start..reader.cursor];
+
+    let (namespace, path) = text.split_once(':').unwrap_or(("minecraft", text));
+    let namespace = if namespace.is_empty() {
+        "minecraft"
+    } else {
+        namespace
+    };
+    // the path can have slashes but the namespace can't, and neither of them can
+    // have another colon
+    if path.is_empty() || namespace.contains('/') || path.contains(':') {
+        reader.cursor = start;
+        return Err(BuiltInExceptions::IdInvalid.create_with_context(reader));
+    }
+    Ok(format!("{namespace}:{path}"))
+}
+
+pub fn resource_location() -> impl ArgumentType {
+    ResourceLocation
+}
+pub fn get_resource_location<S>(context: &CommandContext<S>, name: &str) -> Option<String> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<String>()
+        .cloned()
+}
@@ -0,0 +1,73 @@
+use std::{any::Any, sync::Arc};
+
+use super::ArgumentType;
+use crate::{
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
+};
+
+/// How many ticks are in each unit that can come after the number.
+const UNITS: &[(&str, i32)] = &[("d", 24000), ("s", 20), ("t", 1), ("", 1)];
+
+#[derive(Default)]
+struct Time {
+    pub minimum: i32,
+}
+
+impl ArgumentType for Time {
+    fn parse(&self, reader: &mut StringReader) -> Result<Arc<dyn Any>, CommandSyntaxException> {
+        let value = reader.read_float()?;
+        let unit = reader.read_unquoted_string();
+        let Some(&(_, ticks_per_unit)) = UNITS.iter().find(|(name, _)| *name == unit) else {
+            return Err(BuiltInExceptions::TimeInvalidUnit.create_with_context(reader));
+        };
+        let ticks = (value * ticks_per_unit as f32).round() as i32;
+        if ticks < self.minimum {
+            return Err(BuiltInExceptions::TimeTickCountTooLow {
+                found: ticks,
+                min: self.minimum,
+            }
+            .create_with_context(reader));
+        }
+        Ok(Arc::new(ticks))
+    }
+
+    fn list_suggestions(&self, mut builder: SuggestionsBuilder) -> Suggestions {
+        // suggest units after the number that was typed
+        let remaining = builder.remaining();
+        if remaining.is_empty() || !remaining.chars().all(StringReader::is_allowed_number) {
+            return builder.build();
+        }
+        let number = remaining.to_string();
+        for (unit, _) in UNITS.iter().filter(|(unit, _)| !unit.is_empty()) {
+            builder = builder.suggest(&format!("{number}{unit}"));
+        }
+        builder.build()
+    }
+
+    fn examples(&self) -> Vec<String> {
+        vec!["0d", "0s", "0t", "0"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+/// A duration in ticks, which can have a `d` (days), `s` (seconds) or `t`
+/// (ticks) suffix. It can't be negative.
+pub fn time() -> impl ArgumentType {
+    Time::default()
+}
+/// Like [`time`], but with a custom minimum number of ticks.
+pub fn time_with_minimum(minimum: i32) -> impl ArgumentType {
+    Time { minimum }
+}
+pub fn get_time<S>(context: &CommandContext<S>, name: &str) -> Option<i32> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<i32>()
+        .copied()
+}
@@ -1,12 +1,13 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, future::Future, sync::Arc};
 
 use parking_lot::RwLock;
 
 use super::{literal_argument_builder::Literal, required_argument_builder::Argument};
 use crate::{
     context::CommandContext,
-    modifier::RedirectModifier,
-    tree::{Command, CommandNode},
+    modifier::{RedirectModifier, SingleRedirectModifier},
+    suggestion::{SuggestionProvider, Suggestions, SuggestionsBuilder},
+    tree::{Command, CommandExecutor, CommandFuture, CommandNode},
 };
 
 #[derive(Debug, Clone)]
@@ -26,6 +27,7 @@ pub struct ArgumentBuilder<S> {
 
     forks: bool,
     modifier: Option<Arc<RedirectModifier<S>>>,
+    suggestions: Option<Arc<SuggestionProvider<S>>>,
 }
 
 /// A node that isn't yet built.
@@ -41,6 +43,7 @@ impl<S> ArgumentBuilder<S> {
             forks: false,
             modifier: None,
             target: None,
+            suggestions: None,
         }
     }
 
@@ -80,7 +83,70 @@ impl<S> ArgumentBuilder<S> {
     where
         F: Fn(&CommandContext<S>) -> i32 + Send + Sync + 'static,
     {
-        self.command = Some(Arc::new(f));
+        self.command = Some(Arc::new(CommandExecutor::Sync(Box::new(f))));
+        self
+    }
+
+    /// Like [`Self::executes`], but the function returns a future. These
+    /// commands have to be run with [`CommandDispatcher::execute_async`].
+    ///
+    /// The context can't be used inside of the future, so get the arguments
+    /// that you need before it.
+    ///
+    /// ```
+    /// # use azalea_brigadier::prelude::*;
+    /// # let mut subject = CommandDispatcher::<()>::new();
+    /// # subject.register(
+    /// literal("wait").then(argument("seconds", integer()).executes_async(
+    ///     |ctx: &CommandContext<()>| {
+    ///         let seconds = get_integer(ctx, "seconds").unwrap();
+    ///         async move {
+    ///             // do something that takes a while here
+    ///             seconds
+    ///         }
+    ///     },
+    /// ))
+    /// # );
+    /// ```
+    ///
+    /// [`CommandDispatcher::execute_async`]: crate::command_dispatcher::CommandDispatcher::execute_async
+    pub fn executes_async<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(&CommandContext<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = i32> + Send + 'static,
+    {
+        let executor = move |ctx: &CommandContext<S>| -> CommandFuture { Box::pin(f(ctx)) };
+        self.command = Some(Arc::new(CommandExecutor::Async(Box::new(executor))));
+        self
+    }
+
+    /// Set a function that gives suggestions for this argument, instead of
+    /// the default suggestions from its argument type. This does nothing for
+    /// literals.
+    ///
+    /// ```
+    /// # use azalea_brigadier::prelude::*;
+    /// # let mut subject = CommandDispatcher::<()>::new();
+    /// # subject.register(
+    /// literal("warp").then(
+    ///     argument("name", word())
+    ///         .suggests(|_ctx: &CommandContext<()>, mut builder| {
+    ///             for warp in ["spawn", "shop"] {
+    ///                 if warp.starts_with(builder.remaining_lowercase()) {
+    ///                     builder = builder.suggest(warp);
+    ///                 }
+    ///             }
+    ///             builder.build()
+    ///         })
+    ///         .executes(|_| 1),
+    /// )
+    /// # );
+    /// ```
+    pub fn suggests<F>(mut self, provider: F) -> Self
+    where
+        F: Fn(&CommandContext<S>, SuggestionsBuilder) -> Suggestions + Send + Sync + 'static,
+    {
+        self.suggestions = Some(Arc::new(provider));
         self
     }
 
@@ -108,10 +174,45 @@ impl<S> ArgumentBuilder<S> {
         self
     }
 
+    /// Continue parsing the command from another node, which is useful for
+    /// aliases.
+    ///
+    /// ```
+    /// # use azalea_brigadier::prelude::*;
+    /// # let mut subject = CommandDispatcher::<()>::new();
+    /// let teleport = subject.register(
+    ///     literal("teleport").then(literal("spawn").executes(|ctx: &CommandContext<()>| 42)),
+    /// );
+    /// subject.register(literal("tp").redirect(teleport));
+    ///
+    /// assert_eq!(subject.execute("tp spawn", ()).unwrap(), 42);
+    /// ```
     pub fn redirect(self, target: Arc<RwLock<CommandNode<S>>>) -> Self {
         self.forward(target, None, false)
     }
 
+    /// Like [`Self::redirect`], but the rest of the command is run with the
+    /// source that's returned by the modifier.
+    pub fn redirect_modified(
+        self,
+        target: Arc<RwLock<CommandNode<S>>>,
+        modifier: Arc<SingleRedirectModifier<S>>,
+    ) -> Self
+    where
+        S: 'static,
+    {
+        let modifier: Arc<RedirectModifier<S>> =
+            Arc::new(move |context: &CommandContext<S>| Ok(vec![modifier(context)?]));
+        self.forward(target, Some(modifier), false)
+    }
+
+    /// Continue parsing the command from another node, and run the rest of the
+    /// command once for every source that's returned by the modifier. This is
+    /// how commands like vanilla's `/execute as` work.
+    ///
+    /// When a command forks, its result is the number of times that the
+    /// command ran successfully, and errors from the modifier don't stop the
+    /// other forks from running.
     pub fn fork(
         self,
         target: Arc<RwLock<CommandNode<S>>>,
@@ -149,6 +250,7 @@ impl<S> ArgumentBuilder<S> {
             redirect: self.target,
             modifier: self.modifier,
             forks: self.forks,
+            suggestions: self.suggestions,
             arguments: Default::default(),
             children: Default::default(),
             literals: Default::default(),
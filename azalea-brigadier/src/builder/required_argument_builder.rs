@@ -28,9 +28,6 @@ impl Argument {
     }
 
     pub fn list_suggestions(&self, builder: SuggestionsBuilder) -> Suggestions {
-        // TODO: custom suggestions
-        // https://github.com/Mojang/brigadier/blob/master/src/main/java/com/mojang/brigadier/tree/ArgumentCommandNode.java#L71
-
         self.parser.list_suggestions(builder)
     }
 
@@ -1,6 +1,7 @@
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
+    future::Future,
     mem,
     rc::Rc,
     sync::Arc,
@@ -15,7 +16,7 @@ use crate::{
     parse_results::ParseResults,
     string_reader::StringReader,
     suggestion::{Suggestions, SuggestionsBuilder},
-    tree::CommandNode,
+    tree::{CommandExecutor, CommandNode},
 };
 
 /// The root of the command tree. You need to make this to register commands.
@@ -225,7 +226,80 @@ impl<S> CommandDispatcher<S> {
     }
 
     /// Executes a given pre-parsed command.
+    ///
+    /// This returns an error if the command is async, use
+    /// [`Self::execute_parsed_async`] for those.
     pub fn execute_parsed(parse: ParseResults<S>) -> Result<i32, CommandSyntaxException> {
+        let mut result = 0i32;
+        let mut successful_forks = 0;
+        let forked = Self::run_contexts(parse, |context, executor| {
+            let CommandExecutor::Sync(command) = executor else {
+                return Err(BuiltInExceptions::DispatcherAsyncCommand.create());
+            };
+            result += command(context);
+            // consumer.on_command_complete(context, true, value);
+            successful_forks += 1;
+
+            // TODO: allow context_command to error and handle those
+            // errors
+            Ok(())
+        })?;
+
+        Ok(if forked { successful_forks } else { result })
+    }
+
+    /// Like [`Self::execute`], but async commands are awaited.
+    ///
+    /// Commands are run in order, so a command's future will finish before
+    /// the next forked command's future starts. If you don't want to wait for
+    /// the command to finish, you can spawn the returned future with your
+    /// async runtime.
+    pub fn execute_async(
+        &self,
+        input: impl Into<StringReader>,
+        source: S,
+    ) -> impl Future<Output = Result<i32, CommandSyntaxException>> + Send {
+        let input = input.into();
+
+        let parse = self.parse(input, source);
+        Self::execute_parsed_async(parse)
+    }
+
+    /// Like [`Self::execute_parsed`], but async commands are awaited.
+    ///
+    /// Parsing and the non-async parts of the commands happen immediately, and
+    /// only the futures from async commands are awaited.
+    pub fn execute_parsed_async(
+        parse: ParseResults<S>,
+    ) -> impl Future<Output = Result<i32, CommandSyntaxException>> + Send {
+        let mut futures = Vec::new();
+        let run_result = Self::run_contexts(parse, |context, executor| {
+            futures.push(executor.call(context));
+            Ok(())
+        });
+
+        async move {
+            let forked = run_result?;
+            let successful_forks = futures.len() as i32;
+            let mut result = 0i32;
+            for future in futures {
+                result += future.await;
+            }
+            Ok(if forked { successful_forks } else { result })
+        }
+    }
+
+    /// Follow the redirects and forks in the parsed command, calling `run` for
+    /// every command that should be executed.
+    ///
+    /// Returns whether the command forked.
+    fn run_contexts(
+        parse: ParseResults<S>,
+        mut run: impl FnMut(
+            &CommandContext<S>,
+            &CommandExecutor<S>,
+        ) -> Result<(), CommandSyntaxException>,
+    ) -> Result<bool, CommandSyntaxException> {
         if parse.reader.can_read() {
             if parse.exceptions.len() == 1 {
                 return Err(parse.exceptions.values().next().unwrap().clone());
@@ -239,8 +313,6 @@ impl<S> CommandDispatcher<S> {
                 BuiltInExceptions::DispatcherUnknownArgument.create_with_context(&parse.reader)
             );
         }
-        let mut result = 0i32;
-        let mut successful_forks = 0;
         let mut forked = false;
         let mut found_command = false;
         let command = parse.reader.string();
@@ -252,7 +324,7 @@ impl<S> CommandDispatcher<S> {
             for context in &contexts {
                 let child = &context.child;
                 if let Some(child) = child {
-                    forked |= child.forks;
+                    forked |= context.forks;
                     if child.has_nodes() {
                         found_command = true;
                         let modifier = &context.modifier;
@@ -275,14 +347,7 @@ impl<S> CommandDispatcher<S> {
                     }
                 } else if let Some(context_command) = &context.command {
                     found_command = true;
-
-                    let value = context_command(context);
-                    result += value;
-                    // consumer.on_command_complete(context, true, value);
-                    successful_forks += 1;
-
-                    // TODO: allow context_command to error and handle those
-                    // errors
+                    run(context, context_command)?;
                 }
             }
 
@@ -297,14 +362,7 @@ impl<S> CommandDispatcher<S> {
                 BuiltInExceptions::DispatcherUnknownCommand.create_with_context(&parse.reader)
             );
         }
-
-        // TODO: this is not how vanilla does it but it works
-        Ok(if successful_forks >= 2 {
-            successful_forks
-        } else {
-            result
-        })
-        // Ok(if forked { successful_forks } else { result })
+        Ok(forked)
     }
 
     pub fn get_all_usage(
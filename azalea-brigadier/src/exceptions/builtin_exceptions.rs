@@ -19,6 +19,30 @@ pub enum BuiltInExceptions {
 
     LiteralIncorrect { expected: String },
 
+    EntityInvalidNameOrUuid,
+    EntitySelectorMissing,
+    EntitySelectorUnknown { selector: String },
+    EntitySelectorOptionUnknown { option: String },
+    EntitySelectorOptionsUnterminated,
+    EntitySelectorOptionValueless { option: String },
+    EntityTooMany,
+    PlayerTooMany,
+    PlayerOnlyPlayersAllowed,
+
+    PosMixed,
+    PosIncomplete,
+
+    IdInvalid,
+
+    BlockPropertyUnclosed,
+    BlockPropertyNoValue { property: String, block: String },
+    BlockPropertyDuplicate { property: String, block: String },
+
+    TimeInvalidUnit,
+    TimeTickCountTooLow { found: i32, min: i32 },
+
+    ColorInvalid { color: String },
+
     ReaderExpectedStartOfQuote,
     ReaderExpectedEndOfQuote,
     ReaderInvalidEscape { character: char },
@@ -38,6 +62,7 @@ pub enum BuiltInExceptions {
     DispatcherUnknownArgument,
     DispatcherExpectedArgumentSeparator,
     DispatcherParseException { message: String },
+    DispatcherAsyncCommand,
 }
 
 impl fmt::Debug for BuiltInExceptions {
@@ -75,6 +100,87 @@ impl fmt::Debug for BuiltInExceptions {
                 write!(f, "Expected literal {expected}")
             }
 
+            BuiltInExceptions::EntityInvalidNameOrUuid => {
+                write!(f, "Invalid name or UUID")
+            }
+            BuiltInExceptions::EntitySelectorMissing => {
+                write!(f, "Missing selector type")
+            }
+            BuiltInExceptions::EntitySelectorUnknown { selector } => {
+                write!(f, "Unknown selector type '{selector}'")
+            }
+            BuiltInExceptions::EntitySelectorOptionUnknown { option } => {
+                write!(f, "Unknown option '{option}'")
+            }
+            BuiltInExceptions::EntitySelectorOptionsUnterminated => {
+                write!(f, "Expected end of options")
+            }
+            BuiltInExceptions::EntitySelectorOptionValueless { option } => {
+                write!(f, "Expected value for option '{option}'")
+            }
+            BuiltInExceptions::EntityTooMany => {
+                write!(
+                    f,
+                    "Only one entity is allowed, but the provided selector allows more than one"
+                )
+            }
+            BuiltInExceptions::PlayerTooMany => {
+                write!(
+                    f,
+                    "Only one player is allowed, but the provided selector allows more than one"
+                )
+            }
+            BuiltInExceptions::PlayerOnlyPlayersAllowed => {
+                write!(
+                    f,
+                    "Only players may be affected by this command, but the provided selector includes entities"
+                )
+            }
+
+            BuiltInExceptions::PosMixed => {
+                write!(
+                    f,
+                    "Cannot mix world & local coordinates (everything must either use ^ or not)"
+                )
+            }
+            BuiltInExceptions::PosIncomplete => {
+                write!(f, "Incomplete (expected 3 coordinates)")
+            }
+
+            BuiltInExceptions::IdInvalid => {
+                write!(f, "Invalid ID")
+            }
+
+            BuiltInExceptions::BlockPropertyUnclosed => {
+                write!(f, "Expected closing ] for block state properties")
+            }
+            BuiltInExceptions::BlockPropertyNoValue { property, block } => {
+                write!(
+                    f,
+                    "Expected value for property '{property}' on block {block}"
+                )
+            }
+            BuiltInExceptions::BlockPropertyDuplicate { property, block } => {
+                write!(
+                    f,
+                    "Property '{property}' can only be set once for block {block}"
+                )
+            }
+
+            BuiltInExceptions::TimeInvalidUnit => {
+                write!(f, "Invalid unit")
+            }
+            BuiltInExceptions::TimeTickCountTooLow { found, min } => {
+                write!(
+                    f,
+                    "The tick count must not be less than {min}, found {found}"
+                )
+            }
+
+            BuiltInExceptions::ColorInvalid { color } => {
+                write!(f, "Unknown color '{color}'")
+            }
+
             BuiltInExceptions::ReaderExpectedStartOfQuote => {
                 write!(f, "Expected quote to start a string")
             }
@@ -136,6 +242,9 @@ impl fmt::Debug for BuiltInExceptions {
             BuiltInExceptions::DispatcherParseException { message } => {
                 write!(f, "Could not parse command: {message}")
             }
+            BuiltInExceptions::DispatcherAsyncCommand => {
+                write!(f, "Async commands have to be executed with execute_async")
+            }
         }
     }
 }
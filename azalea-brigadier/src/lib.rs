@@ -14,12 +14,20 @@ pub mod tree;
 pub mod prelude {
     pub use crate::{
         arguments::{
+            block_pos_argument_type::{block_pos, get_block_pos},
+            block_state_argument_type::{block_state, get_block_state},
             bool_argument_type::{bool, get_bool},
+            color_argument_type::{color, get_color},
             double_argument_type::{double, get_double},
+            entity_argument_type::{entities, entity, get_entity_selector, player, players},
             float_argument_type::{float, get_float},
+            game_profile_argument_type::{game_profile, get_game_profile},
             integer_argument_type::{get_integer, integer},
+            item_argument_type::{get_item, item},
             long_argument_type::{get_long, long},
+            resource_location_argument_type::{get_resource_location, resource_location},
             string_argument_type::{get_string, greedy_string, string, word},
+            time_argument_type::{get_time, time},
         },
         builder::{literal_argument_builder::literal, required_argument_builder::argument},
         command_dispatcher::CommandDispatcher,
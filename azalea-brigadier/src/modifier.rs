@@ -2,5 +2,12 @@ use std::sync::Arc;
 
 use crate::{context::CommandContext, exceptions::CommandSyntaxException};
 
+/// A function that decides which sources the rest of a forked command is run
+/// with. The rest of the command is run once for every source returned.
 pub type RedirectModifier<S> =
     dyn Fn(&CommandContext<S>) -> Result<Vec<Arc<S>>, CommandSyntaxException> + Send + Sync;
+
+/// Like [`RedirectModifier`], but for redirects that don't fork and so always
+/// have exactly one source.
+pub type SingleRedirectModifier<S> =
+    dyn Fn(&CommandContext<S>) -> Result<Arc<S>, CommandSyntaxException> + Send + Sync;
@@ -272,4 +272,59 @@ impl StringReader {
         self.skip();
         Ok(())
     }
+
+    /// Read a section that starts with `{` or `[` up to and including its
+    /// matching closing bracket, like an NBT compound or a list of item
+    /// components.
+    ///
+    /// The contents aren't parsed, but nested brackets have to match and
+    /// brackets inside of quoted strings are ignored.
+    pub fn read_bracketed(&mut self) -> Result<String, CommandSyntaxException> {
+        let start = self.cursor;
+        if !self.can_read() || !matches!(self.peek(), '{' | '[') {
+            return Err(
+                BuiltInExceptions::ReaderExpectedSymbol { symbol: '{' }.create_with_context(self)
+            );
+        }
+
+        let mut closing = Vec::new();
+        let mut quote = None;
+        while self.can_read() {
+            let c = self.read();
+            if let Some(terminator) = quote {
+                if c == SYNTAX_ESCAPE {
+                    if self.can_read() {
+                        self.skip();
+                    }
+                } else if c == terminator {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '{' => closing.push('}'),
+                '[' => closing.push(']'),
+                '}' | ']' => {
+                    let expected = closing.pop();
+                    if expected != Some(c) {
+                        self.cursor -= 1;
+                        return Err(BuiltInExceptions::ReaderExpectedSymbol {
+                            symbol: expected.unwrap_or(c),
+                        }
+                        .create_with_context(self));
+                    }
+                    if closing.is_empty() {
+                        return Ok(self.string[start..self.cursor].to_string());
+                    }
+                }
+                c if StringReader::is_quoted_string_start(c) => quote = Some(c),
+                _ => {}
+            }
+        }
+
+        Err(BuiltInExceptions::ReaderExpectedSymbol {
+            symbol: closing.last().copied().unwrap_or('}'),
+        }
+        .create_with_context(self))
+    }
 }
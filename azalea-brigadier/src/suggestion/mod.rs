@@ -15,7 +15,14 @@ use azalea_chat::FormattedText;
 pub use suggestions::Suggestions;
 pub use suggestions_builder::SuggestionsBuilder;
 
-use crate::context::StringRange;
+use crate::context::{CommandContext, StringRange};
+
+/// A function that gives custom suggestions for an argument. It's set with
+/// [`ArgumentBuilder::suggests`].
+///
+/// [`ArgumentBuilder::suggests`]: crate::builder::argument_builder::ArgumentBuilder::suggests
+pub type SuggestionProvider<S> =
+    dyn Fn(&CommandContext<S>, SuggestionsBuilder) -> Suggestions + Send + Sync;
 
 /// A suggestion given to the user for what they might want to type next.
 ///
@@ -1,7 +1,9 @@
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::Debug,
+    future::{self, Future},
     hash::Hash,
+    pin::Pin,
     ptr,
     sync::Arc,
 };
@@ -17,10 +19,45 @@ use crate::{
     exceptions::{BuiltInExceptions, CommandSyntaxException},
     modifier::RedirectModifier,
     string_reader::StringReader,
-    suggestion::{Suggestions, SuggestionsBuilder},
+    suggestion::{SuggestionProvider, Suggestions, SuggestionsBuilder},
 };
 
-pub type Command<S> = Option<Arc<dyn Fn(&CommandContext<S>) -> i32 + Send + Sync>>;
+pub type Command<S> = Option<Arc<CommandExecutor<S>>>;
+
+/// The future that's returned by async commands.
+pub type CommandFuture = Pin<Box<dyn Future<Output = i32> + Send>>;
+
+pub type SyncCommandFn<S> = dyn Fn(&CommandContext<S>) -> i32 + Send + Sync;
+pub type AsyncCommandFn<S> = dyn Fn(&CommandContext<S>) -> CommandFuture + Send + Sync;
+
+/// The function that's run when a command is executed.
+pub enum CommandExecutor<S> {
+    Sync(Box<SyncCommandFn<S>>),
+    /// A command that returns a future, which is awaited by
+    /// [`CommandDispatcher::execute_async`].
+    ///
+    /// The function is called while the command is being executed, so it
+    /// should get everything that it needs from the context before returning
+    /// the future.
+    ///
+    /// [`CommandDispatcher::execute_async`]: crate::command_dispatcher::CommandDispatcher::execute_async
+    Async(Box<AsyncCommandFn<S>>),
+}
+
+impl<S> CommandExecutor<S> {
+    pub fn is_async(&self) -> bool {
+        matches!(self, CommandExecutor::Async(_))
+    }
+
+    /// Run the command, returning a future that's already finished if the
+    /// command isn't async.
+    pub fn call(&self, context: &CommandContext<S>) -> CommandFuture {
+        match self {
+            CommandExecutor::Sync(f) => Box::pin(future::ready(f(context))),
+            CommandExecutor::Async(f) => f(context),
+        }
+    }
+}
 
 /// An ArgumentBuilder that has been built.
 #[non_exhaustive]
@@ -37,6 +74,9 @@ pub struct CommandNode<S> {
     pub redirect: Option<Arc<RwLock<CommandNode<S>>>>,
     pub forks: bool,
     pub modifier: Option<Arc<RedirectModifier<S>>>,
+    /// A custom suggestion provider for argument nodes, which is used instead
+    /// of the suggestions from the argument type.
+    pub suggestions: Option<Arc<SuggestionProvider<S>>>,
 }
 
 impl<S> Clone for CommandNode<S> {
@@ -51,6 +91,7 @@ impl<S> Clone for CommandNode<S> {
             redirect: self.redirect.clone(),
             forks: self.forks,
             modifier: self.modifier.clone(),
+            suggestions: self.suggestions.clone(),
         }
     }
 }
@@ -214,9 +255,7 @@ impl<S> CommandNode<S> {
 
     pub fn list_suggestions(
         &self,
-        // context is here because that's how it is in mojang's brigadier, but we haven't
-        // implemented custom suggestions yet so this is unused rn
-        _context: CommandContext<S>,
+        context: CommandContext<S>,
         builder: SuggestionsBuilder,
     ) -> Suggestions {
         match &self.value {
@@ -231,7 +270,13 @@ impl<S> CommandNode<S> {
                     Suggestions::default()
                 }
             }
-            ArgumentBuilderType::Argument(argument) => argument.list_suggestions(builder),
+            ArgumentBuilderType::Argument(argument) => {
+                if let Some(suggestions) = &self.suggestions {
+                    suggestions(&context, builder)
+                } else {
+                    argument.list_suggestions(builder)
+                }
+            }
         }
     }
 }
@@ -243,7 +288,14 @@ impl<S> Debug for CommandNode<S> {
             .field("children", &self.children)
             .field("command", &self.command.is_some())
             // .field("requirement", &self.requirement)
-            .field("redirect", &self.redirect)
+            // only the name, since redirects can loop back to this node
+            .field(
+                "redirect",
+                &self
+                    .redirect
+                    .as_ref()
+                    .map(|node| node.read().name().to_owned()),
+            )
             .field("forks", &self.forks)
             // .field("modifier", &self.modifier)
             .finish()
@@ -264,6 +316,7 @@ impl<S> Default for CommandNode<S> {
             redirect: None,
             forks: false,
             modifier: None,
+            suggestions: None,
         }
     }
 }
@@ -295,7 +348,6 @@ impl<S> PartialEq for CommandNode<S> {
         if let Some(selfexecutes) = &self.command {
             // idk how to do this better since we can't compare `dyn Fn`s
             if let Some(otherexecutes) = &other.command {
-                #[allow(ambiguous_wide_pointer_comparisons)]
                 if !Arc::ptr_eq(selfexecutes, otherexecutes) {
                     return false;
                 }
@@ -0,0 +1,115 @@
+use azalea_brigadier::{
+    arguments::{
+        block_pos_argument_type::{block_pos, get_block_pos, Coordinates, WorldCoordinate},
+        ArgumentType,
+    },
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    command_dispatcher::CommandDispatcher,
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+fn parse(input: &str) -> Result<Coordinates, CommandSyntaxException> {
+    let mut reader = StringReader::from(input);
+    let result = block_pos().parse(&mut reader)?;
+    Ok(*result.downcast_ref::<Coordinates>().unwrap())
+}
+
+#[test]
+fn parse_absolute() {
+    let coordinates = parse("1 -2 3").unwrap();
+    assert!(!coordinates.is_relative());
+    assert_eq!(coordinates.block_pos([0., 0., 0.], 0., 0.), [1, -2, 3]);
+}
+
+#[test]
+fn parse_relative() {
+    let coordinates = parse("~ ~1 ~-0.5").unwrap();
+    assert_eq!(
+        coordinates,
+        Coordinates::World {
+            x: WorldCoordinate {
+                value: 0.,
+                relative: true
+            },
+            y: WorldCoordinate {
+                value: 1.,
+                relative: true
+            },
+            z: WorldCoordinate {
+                value: -0.5,
+                relative: true
+            },
+        }
+    );
+    assert_eq!(coordinates.block_pos([10.5, 64., 0.], 0., 0.), [10, 65, -1]);
+}
+
+#[test]
+fn parse_local() {
+    let coordinates = parse("^ ^ ^2").unwrap();
+    // looking towards positive z
+    let [x, y, z] = coordinates.resolve([0., 0., 0.], 0., 0.);
+    assert!(x.abs() < 1e-6);
+    assert!(y.abs() < 1e-6);
+    assert!((z - 2.).abs() < 1e-6);
+}
+
+#[test]
+fn mixed_coordinates() {
+    assert_eq!(
+        parse("^ ~ ^").unwrap_err().type_,
+        BuiltInExceptions::PosMixed
+    );
+    assert_eq!(
+        parse("~ ^ ~").unwrap_err().type_,
+        BuiltInExceptions::PosMixed
+    );
+}
+
+#[test]
+fn incomplete() {
+    assert_eq!(
+        parse("1 2").unwrap_err().type_,
+        BuiltInExceptions::PosIncomplete
+    );
+}
+
+#[test]
+fn relative_coordinates_in_command() {
+    struct CommandSource {
+        position: [f64; 3],
+    }
+
+    let source = CommandSource {
+        position: [10.5, 64., 2.9],
+    };
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("setblock").then(argument("pos", block_pos()).executes(
+            |context: &CommandContext<&CommandSource>| {
+                let coordinates = get_block_pos(context, "pos").unwrap();
+                let [x, y, z] = coordinates.block_pos(context.source.position, 0., 0.);
+                x * 10000 + y * 100 + z
+            },
+        )),
+    );
+
+    assert_eq!(subject.execute("setblock ~ ~ ~", &source).unwrap(), 106402);
+    assert_eq!(
+        subject.execute("setblock ~-1 ~1.5 5", &source).unwrap(),
+        96505
+    );
+
+    let parse_error = |input: &str| {
+        let parse = subject.parse(input.into(), &source);
+        match parse.exceptions.into_values().next().unwrap().type_ {
+            BuiltInExceptions::DispatcherParseException { message } => message,
+            other => panic!("unexpected error {other:?}"),
+        }
+    };
+    // the coordinates have to be separated by spaces, like in vanilla
+    assert!(parse_error("setblock ~~~").starts_with("Expected double"));
+    assert!(parse_error("setblock ~ ~").starts_with("Incomplete (expected 3 coordinates)"));
+}
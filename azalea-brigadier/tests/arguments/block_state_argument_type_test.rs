@@ -0,0 +1,99 @@
+use azalea_brigadier::{
+    arguments::{
+        block_pos_argument_type::block_pos,
+        block_state_argument_type::{block_state, get_block_state, BlockInput},
+        item_argument_type::{item, ItemInput},
+        ArgumentType,
+    },
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    command_dispatcher::CommandDispatcher,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+fn parse(input: &str) -> Result<BlockInput, CommandSyntaxException> {
+    let mut reader = StringReader::from(input);
+    let result = block_state().parse(&mut reader)?;
+    Ok(result.downcast_ref::<BlockInput>().unwrap().clone())
+}
+
+#[test]
+fn parse_block_state() {
+    let block = parse("oak_stairs[facing=east, half=top]{foo:\"]\"}").unwrap();
+    assert_eq!(block.id, "minecraft:oak_stairs");
+    assert_eq!(block.property("facing"), Some("east"));
+    assert_eq!(block.property("half"), Some("top"));
+    assert_eq!(block.nbt.as_deref(), Some("{foo:\"]\"}"));
+}
+
+#[test]
+fn duplicate_property() {
+    assert_eq!(
+        parse("stone[a=b,a=c]").unwrap_err().type_,
+        BuiltInExceptions::BlockPropertyDuplicate {
+            property: "a".to_string(),
+            block: "minecraft:stone".to_string()
+        }
+    );
+}
+
+#[test]
+fn unclosed_properties() {
+    assert_eq!(
+        parse("stone[a=b").unwrap_err().type_,
+        BuiltInExceptions::BlockPropertyUnclosed
+    );
+}
+
+#[test]
+fn invalid_id() {
+    assert_eq!(
+        parse("a:b:c").unwrap_err().type_,
+        BuiltInExceptions::IdInvalid
+    );
+}
+
+#[test]
+fn parse_item() {
+    let mut reader = StringReader::from("foo:stick[custom_data={a:[1,2]}] 5");
+    let result = item().parse(&mut reader).unwrap();
+    assert_eq!(
+        result.downcast_ref::<ItemInput>().unwrap(),
+        &ItemInput {
+            id: "foo:stick".to_string(),
+            components: Some("[custom_data={a:[1,2]}]".to_string()),
+        }
+    );
+    assert_eq!(reader.remaining(), " 5");
+}
+
+#[test]
+fn block_state_in_command() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("setblock").then(argument("pos", block_pos()).then(
+        argument("block", block_state()).executes(|context| {
+            let block = get_block_state(context, "block").unwrap();
+            assert_eq!(block.id, "minecraft:oak_stairs");
+            block.properties.len() as i32
+        }),
+    )));
+
+    assert_eq!(
+        subject.execute("setblock ~ ~ ~ oak_stairs", &()).unwrap(),
+        0
+    );
+    assert_eq!(
+        subject
+            .execute(
+                "setblock ~ ~1 ~ minecraft:oak_stairs[facing=east,half=top]",
+                &()
+            )
+            .unwrap(),
+        2
+    );
+    let parse = subject.parse("setblock ~ ~ ~ oak_stairs[facing=east".into(), &());
+    let error = parse.exceptions.into_values().next().unwrap();
+    assert!(error
+        .message()
+        .contains("Expected closing ] for block state properties"));
+}
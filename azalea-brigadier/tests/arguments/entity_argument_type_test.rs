@@ -0,0 +1,143 @@
+use azalea_brigadier::{
+    arguments::{
+        entity_argument_type::{
+            entities, entity, get_entity_selector, player, players, EntitySelector, SelectorKind,
+            SelectorOption,
+        },
+        integer_argument_type::{get_integer, integer},
+        ArgumentType,
+    },
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    command_dispatcher::CommandDispatcher,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+
+fn parse(
+    argument: impl ArgumentType,
+    input: &str,
+) -> Result<EntitySelector, CommandSyntaxException> {
+    let mut reader = StringReader::from(input);
+    let result = argument.parse(&mut reader)?;
+    Ok(result.downcast_ref::<EntitySelector>().unwrap().clone())
+}
+
+#[test]
+fn parse_name() {
+    assert_eq!(
+        parse(player(), "py5").unwrap(),
+        EntitySelector::Name("py5".to_string())
+    );
+}
+
+#[test]
+fn parse_uuid() {
+    assert_eq!(
+        parse(entity(), "dd12be42-52a9-4a91-a8a1-11c01849e498").unwrap(),
+        EntitySelector::Uuid(0xdd12be4252a94a91a8a111c01849e498)
+    );
+}
+
+#[test]
+fn parse_selector_with_options() {
+    let selector = parse(entities(), "@e[type=!pig, tag=\"a b\",scores={foo=1..}]").unwrap();
+    assert_eq!(
+        selector,
+        EntitySelector::Selector {
+            kind: SelectorKind::AllEntities,
+            options: vec![
+                SelectorOption {
+                    key: "type".to_string(),
+                    value: "pig".to_string(),
+                    negated: true,
+                },
+                SelectorOption {
+                    key: "tag".to_string(),
+                    value: "a b".to_string(),
+                    negated: false,
+                },
+                SelectorOption {
+                    key: "scores".to_string(),
+                    value: "{foo=1..}".to_string(),
+                    negated: false,
+                },
+            ],
+        }
+    );
+    assert!(selector.includes_entities());
+}
+
+#[test]
+fn single_entity_with_limit() {
+    assert!(parse(entity(), "@e[limit=1]").is_ok());
+    assert_eq!(
+        parse(entity(), "@e").unwrap_err().type_,
+        BuiltInExceptions::EntityTooMany
+    );
+    assert_eq!(
+        parse(player(), "@a").unwrap_err().type_,
+        BuiltInExceptions::PlayerTooMany
+    );
+}
+
+#[test]
+fn players_only() {
+    assert!(parse(players(), "@e[type=player]").is_ok());
+    assert!(parse(player(), "@s").is_ok());
+    assert_eq!(
+        parse(players(), "@e").unwrap_err().type_,
+        BuiltInExceptions::PlayerOnlyPlayersAllowed
+    );
+}
+
+#[test]
+fn unknown_option() {
+    assert_eq!(
+        parse(entities(), "@e[foo=bar]").unwrap_err().type_,
+        BuiltInExceptions::EntitySelectorOptionUnknown {
+            option: "foo".to_string()
+        }
+    );
+}
+
+#[test]
+fn unterminated_options() {
+    assert_eq!(
+        parse(entities(), "@e[type=pig").unwrap_err().type_,
+        BuiltInExceptions::EntitySelectorOptionsUnterminated
+    );
+}
+
+#[test]
+fn selectors_in_command() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("give").then(argument("targets", players()).then(
+        argument("count", integer()).executes(|context| {
+            let targets = get_entity_selector(context, "targets").unwrap();
+            let count = get_integer(context, "count").unwrap();
+            match targets {
+                EntitySelector::Selector { options, .. } => options.len() as i32 * count,
+                _ => count,
+            }
+        }),
+    )));
+
+    assert_eq!(subject.execute("give py5 3", &()).unwrap(), 3);
+    assert_eq!(subject.execute("give @a 3", &()).unwrap(), 0);
+    // the spaces inside of the options don't end the argument
+    assert_eq!(
+        subject
+            .execute("give @a[ tag=a , distance=..5 ] 3", &())
+            .unwrap(),
+        6
+    );
+    let parse_error = |input: &str| {
+        let parse = subject.parse(input.into(), &());
+        match parse.exceptions.into_values().next().unwrap().type_ {
+            BuiltInExceptions::DispatcherParseException { message } => message,
+            other => panic!("unexpected error {other:?}"),
+        }
+    };
+    assert!(parse_error("give @e 3").starts_with("Only players may be affected by this command"));
+    assert!(parse_error("give @q 3").starts_with("Unknown selector type '@q'"));
+}
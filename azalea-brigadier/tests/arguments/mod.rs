@@ -1,6 +1,10 @@
+mod block_pos_argument_type_test;
+mod block_state_argument_type_test;
 mod bool_argument_type_test;
 mod double_argument_type_test;
+mod entity_argument_type_test;
 mod float_argument_type_test;
 mod integer_argument_type_test;
 mod long_argument_type_test;
 mod string_argument_type_test;
+mod time_argument_type_test;
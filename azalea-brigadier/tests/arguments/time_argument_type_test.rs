@@ -0,0 +1,43 @@
+use azalea_brigadier::{
+    arguments::{color_argument_type::color, time_argument_type::time, ArgumentType},
+    exceptions::BuiltInExceptions,
+    string_reader::StringReader,
+};
+
+fn parse_time(input: &str) -> Result<i32, BuiltInExceptions> {
+    let mut reader = StringReader::from(input);
+    let result = time().parse(&mut reader).map_err(|e| e.type_)?;
+    Ok(*result.downcast_ref::<i32>().unwrap())
+}
+
+#[test]
+fn parse_units() {
+    assert_eq!(parse_time("5"), Ok(5));
+    assert_eq!(parse_time("5t"), Ok(5));
+    assert_eq!(parse_time("1.5s"), Ok(30));
+    assert_eq!(parse_time("2d"), Ok(48000));
+}
+
+#[test]
+fn invalid_unit() {
+    assert_eq!(parse_time("5m"), Err(BuiltInExceptions::TimeInvalidUnit));
+}
+
+#[test]
+fn negative_time() {
+    assert_eq!(
+        parse_time("-1s"),
+        Err(BuiltInExceptions::TimeTickCountTooLow { found: -20, min: 0 })
+    );
+}
+
+#[test]
+fn unknown_color() {
+    let mut reader = StringReader::from("purple");
+    assert_eq!(
+        color().parse(&mut reader).unwrap_err().type_,
+        BuiltInExceptions::ColorInvalid {
+            color: "purple".to_string()
+        }
+    );
+}
@@ -0,0 +1,69 @@
+use azalea_brigadier::{
+    arguments::integer_argument_type::{get_integer, integer},
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    command_dispatcher::CommandDispatcher,
+    context::CommandContext,
+    exceptions::BuiltInExceptions,
+};
+use futures_lite::future::block_on;
+
+#[derive(Debug, PartialEq)]
+struct CommandSource {}
+
+#[test]
+fn execute_async_command() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("foo").then(argument("bar", integer()).executes_async(
+            |ctx: &CommandContext<CommandSource>| {
+                let bar = get_integer(ctx, "bar").unwrap();
+                async move { bar * 2 }
+            },
+        )),
+    );
+
+    assert_eq!(
+        block_on(subject.execute_async("foo 21", CommandSource {})).unwrap(),
+        42
+    );
+}
+
+#[test]
+fn execute_async_runs_sync_commands() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo").executes(|_| 42));
+
+    assert_eq!(
+        block_on(subject.execute_async("foo", CommandSource {})).unwrap(),
+        42
+    );
+}
+
+#[test]
+fn execute_async_command_synchronously() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo").executes_async(|_| async { 42 }));
+
+    assert_eq!(
+        subject
+            .execute("foo", CommandSource {})
+            .err()
+            .unwrap()
+            .type_,
+        BuiltInExceptions::DispatcherAsyncCommand
+    );
+}
+
+#[test]
+fn execute_async_unknown_command() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo").executes_async(|_| async { 42 }));
+
+    assert_eq!(
+        block_on(subject.execute_async("bar", CommandSource {}))
+            .err()
+            .unwrap()
+            .type_,
+        BuiltInExceptions::DispatcherUnknownCommand
+    );
+}
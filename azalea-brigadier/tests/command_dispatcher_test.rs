@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use azalea_brigadier::{
-    arguments::integer_argument_type::integer,
+    arguments::integer_argument_type::{get_integer, integer},
     builder::{literal_argument_builder::literal, required_argument_builder::argument},
     command_dispatcher::CommandDispatcher,
     context::CommandContext,
@@ -284,6 +284,90 @@ fn execute_redirected() {
     assert_eq!(CommandDispatcher::execute_parsed(parse).unwrap(), 2);
 }
 
+#[test]
+fn execute_redirected_with_modifier() {
+    let mut subject = CommandDispatcher::<i32>::new();
+
+    subject.register(literal("actual").executes(|ctx: &CommandContext<i32>| *ctx.source));
+    subject.register(literal("double").redirect_modified(
+        subject.root.clone(),
+        Arc::new(|ctx: &CommandContext<i32>| Ok(Arc::new(*ctx.source * 2))),
+    ));
+
+    assert_eq!(subject.execute("actual", 21).unwrap(), 21);
+    assert_eq!(subject.execute("double actual", 21).unwrap(), 42);
+    assert_eq!(subject.execute("double double actual", 21).unwrap(), 84);
+}
+
+#[test]
+fn execute_forked_into_own_parent() {
+    let mut subject = CommandDispatcher::new();
+
+    subject.register(literal("actual").executes(|_| 42));
+    let execute = subject.register(literal("execute"));
+    let root = subject.root.clone();
+    subject.register(literal("execute").then(literal("run").redirect(root)).then(
+        literal("as").then(argument("count", integer()).fork(
+            execute,
+            Arc::new(|ctx: &CommandContext<CommandSource>| {
+                let count = get_integer(ctx, "count").unwrap();
+                Ok((0..count).map(|_| Arc::new(CommandSource {})).collect())
+            }),
+        )),
+    ));
+
+    // the result of a forked command is the number of times it ran
+    assert_eq!(
+        subject
+            .execute("execute as 3 run actual", CommandSource {})
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        subject
+            .execute("execute as 2 as 3 run actual", CommandSource {})
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        subject
+            .execute("execute as 1 run actual", CommandSource {})
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        subject
+            .execute("execute as 0 run actual", CommandSource {})
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        subject
+            .execute("execute run actual", CommandSource {})
+            .unwrap(),
+        42
+    );
+
+    // make sure that the loop doesn't make it recurse forever
+    let _ = format!("{:?}", subject.root.read());
+}
+
+#[test]
+fn execute_forked_with_failing_modifier() {
+    let mut subject = CommandDispatcher::new();
+
+    subject.register(literal("actual").executes(|_| 42));
+    subject.register(literal("fail").fork(
+        subject.root.clone(),
+        Arc::new(|_: &CommandContext<CommandSource>| {
+            Err(BuiltInExceptions::DispatcherUnknownCommand.create())
+        }),
+    ));
+
+    // forked commands don't fail when the modifier fails, they just don't run
+    assert_eq!(subject.execute("fail actual", CommandSource {}).unwrap(), 0);
+}
+
 #[test]
 fn execute_orphaned_subcommand() {
     let mut subject = CommandDispatcher::new();
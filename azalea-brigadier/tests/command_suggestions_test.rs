@@ -444,3 +444,30 @@ fn get_completion_suggestions_execute_simulation_partial() {
         ]
     );
 }
+
+#[test]
+fn get_completion_suggestions_custom_provider() {
+    let mut subject = CommandDispatcher::<()>::new();
+    subject.register(
+        literal("warp").then(
+            argument("name", word())
+                .suggests(|_, mut builder| {
+                    for warp in ["spawn", "shop", "mine"] {
+                        if warp.starts_with(builder.remaining_lowercase()) {
+                            builder = builder.suggest(warp);
+                        }
+                    }
+                    builder.build()
+                })
+                .executes(|_| 1),
+        ),
+    );
+
+    test_suggestions(
+        &subject,
+        "warp s",
+        6,
+        StringRange::between(5, 6),
+        vec!["shop", "spawn"],
+    );
+}
@@ -9,6 +9,7 @@ use tracing::{debug, trace, warn};
 
 use crate::{
     base_component::BaseComponent,
+    events::{ClickAction, ClickEvent},
     style::{ChatFormatting, Style},
     text_component::TextComponent,
     translatable_component::{StringOrComponent, TranslatableComponent},
@@ -125,6 +126,288 @@ impl FormattedText {
 
         built_string
     }
+
+    /// Get the text of every component along with the style that it ends up
+    /// with after inheriting from its parents. Runs with the same style are
+    /// merged and empty runs are skipped.
+    pub fn styled_runs(&self) -> Vec<(String, Style)> {
+        let mut runs: Vec<(String, Style)> = Vec::new();
+        self.visit_styled(&Style::default(), &mut |text, style| {
+            if text.is_empty() {
+                return;
+            }
+            match runs.last_mut() {
+                Some((last_text, last_style)) if last_style == style => last_text.push_str(text),
+                _ => runs.push((text.to_string(), style.clone())),
+            }
+        });
+        runs
+    }
+
+    fn visit_styled(&self, parent_style: &Style, f: &mut impl FnMut(&str, &Style)) {
+        let own_style = &self.get_base().style;
+        // legacy formatting codes can reset the style, which makes it not inherit
+        // anything
+        let mut style = if own_style.reset {
+            Style::default()
+        } else {
+            parent_style.clone()
+        };
+        style.apply(own_style);
+
+        let text = match self {
+            Self::Text(c) => c.text.clone(),
+            Self::Translatable(c) => match c.read() {
+                Ok(c) => c.to_string(),
+                Err(_) => c.key.clone(),
+            },
+        };
+        f(&text, &style);
+
+        for sibling in &self.get_base().siblings {
+            sibling.visit_styled(&style, f);
+        }
+    }
+
+    /// Convert this component into HTML, so it can be shown on a web page.
+    ///
+    /// Every run of text is put in a `<span>` with inline CSS for the color
+    /// and formatting, and the text of `show_text` hover events becomes the
+    /// `title`. Click events that open an `http(s)` URL become links, and
+    /// other click events are kept in `data-click-action` and
+    /// `data-click-value` attributes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azalea_chat::FormattedText;
+    /// use serde::de::Deserialize;
+    ///
+    /// let component = FormattedText::deserialize(&serde_json::json!({
+    ///    "text": "Hello, world!",
+    ///    "color": "red",
+    ///    "bold": true,
+    /// })).unwrap();
+    ///
+    /// assert_eq!(
+    ///     component.to_html(),
+    ///     r#"<span style="color:#FF5555;font-weight:bold">Hello, world!</span>"#
+    /// );
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+
+        for (text, style) in self.styled_runs() {
+            let mut css = Vec::new();
+            if let Some(color) = &style.color {
+                css.push(format!("color:{}", color.format_value()));
+            }
+            if style.bold == Some(true) {
+                css.push("font-weight:bold".to_string());
+            }
+            if style.italic == Some(true) {
+                css.push("font-style:italic".to_string());
+            }
+            let decorations = [
+                (style.underlined, "underline"),
+                (style.strikethrough, "line-through"),
+            ]
+            .into_iter()
+            .filter(|(enabled, _)| *enabled == Some(true))
+            .map(|(_, decoration)| decoration)
+            .collect::<Vec<_>>();
+            if !decorations.is_empty() {
+                css.push(format!("text-decoration:{}", decorations.join(" ")));
+            }
+
+            let mut attributes = String::new();
+            if !css.is_empty() {
+                attributes.push_str(&format!(" style=\"{}\"", css.join(";")));
+            }
+            if let Some(hover_event) = &style.hover_event {
+                attributes.push_str(&format!(
+                    " title=\"{}\"",
+                    escape_html(&hover_event.to_string())
+                ));
+            }
+            let link = style.click_event.as_ref().and_then(web_link);
+            if let (Some(click_event), None) = (&style.click_event, link) {
+                attributes.push_str(&format!(
+                    " data-click-action=\"{}\" data-click-value=\"{}\"",
+                    click_event.action.name(),
+                    escape_html(&click_event.value)
+                ));
+            }
+
+            let text = escape_html(&text).replace('\n', "<br>");
+            let span = if attributes.is_empty() {
+                text
+            } else {
+                format!("<span{attributes}>{text}</span>")
+            };
+            if let Some(url) = link {
+                html.push_str(&format!("<a href=\"{}\">{span}</a>", escape_html(url)));
+            } else {
+                html.push_str(&span);
+            }
+        }
+
+        html
+    }
+
+    /// Convert this component into Markdown, like what Discord uses.
+    ///
+    /// Markdown doesn't have colors, so they're lost, but bold, italic,
+    /// underlined (`__`), strikethrough, and obfuscated (as spoilers) text is
+    /// kept. Click events that open an `http(s)` URL become links, with the
+    /// text of the hover event as the title.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azalea_chat::FormattedText;
+    /// use serde::de::Deserialize;
+    ///
+    /// let component = FormattedText::deserialize(&serde_json::json!({
+    ///    "text": "Hello, ",
+    ///    "extra": [{ "text": "world", "bold": true }, "!"],
+    /// })).unwrap();
+    ///
+    /// assert_eq!(component.to_markdown(), "Hello, **world**!");
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        // the formatting that markdown can show, so runs that only differ in
+        // other ways (like color) can be merged
+        let mut runs: Vec<(String, MarkdownStyle)> = Vec::new();
+        for (text, style) in self.styled_runs() {
+            let markdown_style = MarkdownStyle::from(&style);
+            match runs.last_mut() {
+                Some((last_text, last_style)) if *last_style == markdown_style => {
+                    last_text.push_str(&text)
+                }
+                _ => runs.push((text, markdown_style)),
+            }
+        }
+
+        let mut markdown = String::new();
+        for (text, style) in runs {
+            // markdown doesn't allow whitespace right inside of the markers
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                markdown.push_str(&text);
+                continue;
+            }
+            let leading = &text[..text.len() - text.trim_start().len()];
+            let trailing = &text[text.trim_end().len()..];
+
+            let markers = [
+                (style.obfuscated, "||"),
+                (style.strikethrough, "~~"),
+                (style.underlined, "__"),
+                (style.bold, "**"),
+                (style.italic, "*"),
+            ]
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, marker)| marker)
+            .collect::<Vec<_>>();
+
+            let mut formatted = markers.concat();
+            formatted.push_str(&escape_markdown(trimmed));
+            formatted.push_str(&markers.iter().rev().copied().collect::<String>());
+
+            markdown.push_str(leading);
+            if let Some(url) = &style.link {
+                let url = url
+                    .replace(' ', "%20")
+                    .replace('(', "%28")
+                    .replace(')', "%29");
+                if let Some(title) = &style.title {
+                    let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+                    markdown.push_str(&format!("[{formatted}]({url} \"{title}\")"));
+                } else {
+                    markdown.push_str(&format!("[{formatted}]({url})"));
+                }
+            } else {
+                markdown.push_str(&formatted);
+            }
+            markdown.push_str(trailing);
+        }
+
+        markdown
+    }
+}
+
+/// The parts of a [`Style`] that can be shown in Markdown.
+#[derive(PartialEq)]
+struct MarkdownStyle {
+    bold: bool,
+    italic: bool,
+    underlined: bool,
+    strikethrough: bool,
+    obfuscated: bool,
+    link: Option<String>,
+    title: Option<String>,
+}
+
+impl From<&Style> for MarkdownStyle {
+    fn from(style: &Style) -> Self {
+        let link = style
+            .click_event
+            .as_ref()
+            .and_then(web_link)
+            .map(str::to_string);
+        Self {
+            bold: style.bold.unwrap_or(false),
+            italic: style.italic.unwrap_or(false),
+            underlined: style.underlined.unwrap_or(false),
+            strikethrough: style.strikethrough.unwrap_or(false),
+            obfuscated: style.obfuscated.unwrap_or(false),
+            // titles can only be shown on links
+            title: link
+                .as_ref()
+                .and(style.hover_event.as_ref())
+                .map(|hover_event| hover_event.to_string()),
+            link,
+        }
+    }
+}
+
+/// The URL that a click event opens, if it's a web page. Other URLs (like
+/// `javascript:`) aren't allowed since the text might come from anyone.
+fn web_link(click_event: &ClickEvent) -> Option<&str> {
+    let url = click_event.value.as_str();
+    let is_web_url = url.starts_with("https://") || url.starts_with("http://");
+    (click_event.action == ClickAction::OpenUrl && is_web_url).then_some(url)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '|' | '`' | '[' | ']' | '<' | '>' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 impl IntoIterator for FormattedText {
@@ -1,26 +1,325 @@
-enum ClickAction {
-    OPEN_URL = Action::new("open_url", true),
-    OPEN_FILE = Action::new("open_file", false),
-    RUN_COMMAND = Action::new("run_command", true),
-    SUGGEST_COMMAND = Action::new("suggest_command", true),
-    CHANGE_PAGE = Action::new("change_page", true),
-    COPY_TO_CLIPBOARD = Action::new("copy_to_clipboard", true),
-}
+//! The things that can happen when a player clicks or hovers over a chat
+//! component.
+
+use std::fmt;
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::Value;
+#[cfg(feature = "simdnbt")]
+use simdnbt::{
+    owned::{NbtCompound, NbtTag},
+    FromNbtTag as _, Serialize as _,
+};
+
+use crate::FormattedText;
 
-struct ClickAction {
-    pub name: String,
-    pub allow_from_server: bool,
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClickAction {
+    OpenUrl,
+    OpenFile,
+    RunCommand,
+    SuggestCommand,
+    ChangePage,
+    CopyToClipboard,
 }
 
 impl ClickAction {
-    fn new(name: &str, allow_from_server: bool) -> Self {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClickAction::OpenUrl => "open_url",
+            ClickAction::OpenFile => "open_file",
+            ClickAction::RunCommand => "run_command",
+            ClickAction::SuggestCommand => "suggest_command",
+            ClickAction::ChangePage => "change_page",
+            ClickAction::CopyToClipboard => "copy_to_clipboard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ClickAction> {
+        match name {
+            "open_url" => Some(ClickAction::OpenUrl),
+            "open_file" => Some(ClickAction::OpenFile),
+            "run_command" => Some(ClickAction::RunCommand),
+            "suggest_command" => Some(ClickAction::SuggestCommand),
+            "change_page" => Some(ClickAction::ChangePage),
+            "copy_to_clipboard" => Some(ClickAction::CopyToClipboard),
+            _ => None,
+        }
+    }
+
+    /// Whether vanilla clients allow this action in components that came from
+    /// the server.
+    pub fn allow_from_server(&self) -> bool {
+        !matches!(self, ClickAction::OpenFile)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClickEvent {
+    pub action: ClickAction,
+    /// The URL, command, page number, or text to copy, depending on the
+    /// action.
+    pub value: String,
+}
+
+impl ClickEvent {
+    pub fn new(action: ClickAction, value: impl Into<String>) -> Self {
         Self {
-            name: name.to_string(),
-            allow_from_server,
+            action,
+            value: value.into(),
+        }
+    }
+
+    pub fn deserialize(json: &Value) -> Option<ClickEvent> {
+        let action = ClickAction::from_name(json.get("action")?.as_str()?)?;
+        let value = match json.get("value")? {
+            // change_page can be a number
+            Value::Number(n) => n.to_string(),
+            value => value.as_str()?.to_string(),
+        };
+        Some(ClickEvent { action, value })
+    }
+}
+
+impl Serialize for ClickEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(2))?;
+        state.serialize_entry("action", self.action.name())?;
+        state.serialize_entry("value", &self.value)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "simdnbt")]
+impl simdnbt::ToNbtTag for ClickEvent {
+    fn to_nbt_tag(self) -> NbtTag {
+        let mut compound = NbtCompound::new();
+        compound.insert("action", self.action.name());
+        compound.insert("value", self.value);
+        NbtTag::Compound(compound)
+    }
+}
+
+#[cfg(feature = "simdnbt")]
+impl simdnbt::FromNbtTag for ClickEvent {
+    fn from_nbt_tag(tag: simdnbt::borrow::NbtTag) -> Option<Self> {
+        let compound = tag.compound()?;
+        let action = ClickAction::from_name(&compound.string("action")?.to_str())?;
+        let value = if let Some(value) = compound.string("value") {
+            value.to_string()
+        } else {
+            compound.int("value")?.to_string()
+        };
+        Some(ClickEvent { action, value })
+    }
+}
+
+/// What's shown when a player hovers over a chat component.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HoverEvent {
+    ShowText(Box<FormattedText>),
+    /// Show the tooltip of an item. The item's data components aren't kept.
+    ShowItem {
+        id: String,
+        count: i32,
+    },
+    ShowEntity {
+        /// The entity type, like `minecraft:pig`.
+        kind: String,
+        /// The UUID of the entity, as a hyphenated string.
+        id: String,
+        name: Option<Box<FormattedText>>,
+    },
+}
+
+impl HoverEvent {
+    pub fn action_name(&self) -> &'static str {
+        match self {
+            HoverEvent::ShowText(_) => "show_text",
+            HoverEvent::ShowItem { .. } => "show_item",
+            HoverEvent::ShowEntity { .. } => "show_entity",
+        }
+    }
+
+    pub fn deserialize(json: &Value) -> Option<HoverEvent> {
+        let action = json.get("action")?.as_str()?;
+        // "value" is the old name for "contents"
+        let contents = json.get("contents").or_else(|| json.get("value"))?;
+        match action {
+            "show_text" => {
+                let text = <FormattedText as serde::Deserialize>::deserialize(contents).ok()?;
+                Some(HoverEvent::ShowText(Box::new(text)))
+            }
+            "show_item" => {
+                // the contents can also be just the item id
+                if let Some(id) = contents.as_str() {
+                    return Some(HoverEvent::ShowItem {
+                        id: id.to_string(),
+                        count: 1,
+                    });
+                }
+                Some(HoverEvent::ShowItem {
+                    id: contents.get("id")?.as_str()?.to_string(),
+                    count: contents.get("count").and_then(|v| v.as_i64()).unwrap_or(1) as i32,
+                })
+            }
+            "show_entity" => Some(HoverEvent::ShowEntity {
+                kind: contents.get("type")?.as_str()?.to_string(),
+                id: contents.get("id")?.as_str()?.to_string(),
+                name: contents
+                    .get("name")
+                    .and_then(|v| <FormattedText as serde::Deserialize>::deserialize(v).ok())
+                    .map(Box::new),
+            }),
+            _ => None,
         }
     }
 }
 
-struct ClickEvent {
-    action: ClickAction,
+/// The text of the tooltip, without any styling.
+impl fmt::Display for HoverEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoverEvent::ShowText(text) => write!(f, "{text}"),
+            HoverEvent::ShowItem { id, count } => {
+                if *count == 1 {
+                    write!(f, "{id}")
+                } else {
+                    write!(f, "{id} x{count}")
+                }
+            }
+            HoverEvent::ShowEntity { kind, id, name } => {
+                if let Some(name) = name {
+                    writeln!(f, "{name}")?;
+                }
+                write!(f, "{kind}\n{id}")
+            }
+        }
+    }
+}
+
+impl Serialize for HoverEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(2))?;
+        state.serialize_entry("action", self.action_name())?;
+        match self {
+            HoverEvent::ShowText(text) => {
+                state.serialize_entry("contents", text)?;
+            }
+            HoverEvent::ShowItem { id, count } => {
+                state.serialize_entry(
+                    "contents",
+                    &serde_json::json!({ "id": id, "count": count }),
+                )?;
+            }
+            HoverEvent::ShowEntity { kind, id, name } => {
+                let mut contents = serde_json::json!({ "type": kind, "id": id });
+                if let Some(name) = name {
+                    contents["name"] =
+                        serde_json::to_value(name).map_err(serde::ser::Error::custom)?;
+                }
+                state.serialize_entry("contents", &contents)?;
+            }
+        }
+        state.end()
+    }
+}
+
+#[cfg(feature = "simdnbt")]
+impl simdnbt::ToNbtTag for HoverEvent {
+    fn to_nbt_tag(self) -> NbtTag {
+        let mut compound = NbtCompound::new();
+        compound.insert("action", self.action_name());
+        let contents = match self {
+            HoverEvent::ShowText(text) => NbtTag::Compound(text.to_compound()),
+            HoverEvent::ShowItem { id, count } => {
+                let mut contents = NbtCompound::new();
+                contents.insert("id", id);
+                contents.insert("count", count);
+                NbtTag::Compound(contents)
+            }
+            HoverEvent::ShowEntity { kind, id, name } => {
+                let mut contents = NbtCompound::new();
+                contents.insert("type", kind);
+                contents.insert("id", id);
+                if let Some(name) = name {
+                    contents.insert("name", NbtTag::Compound(name.to_compound()));
+                }
+                NbtTag::Compound(contents)
+            }
+        };
+        compound.insert("contents", contents);
+        NbtTag::Compound(compound)
+    }
+}
+
+#[cfg(feature = "simdnbt")]
+impl simdnbt::FromNbtTag for HoverEvent {
+    fn from_nbt_tag(tag: simdnbt::borrow::NbtTag) -> Option<Self> {
+        let compound = tag.compound()?;
+        let action = compound.string("action")?.to_str();
+        let contents = compound.get("contents").or_else(|| compound.get("value"))?;
+        match action.as_ref() {
+            "show_text" => Some(HoverEvent::ShowText(Box::new(FormattedText::from_nbt_tag(
+                contents,
+            )?))),
+            "show_item" => {
+                if let Some(id) = contents.string() {
+                    return Some(HoverEvent::ShowItem {
+                        id: id.to_string(),
+                        count: 1,
+                    });
+                }
+                let contents = contents.compound()?;
+                Some(HoverEvent::ShowItem {
+                    id: contents.string("id")?.to_string(),
+                    count: contents.int("count").unwrap_or(1),
+                })
+            }
+            "show_entity" => {
+                let contents = contents.compound()?;
+                Some(HoverEvent::ShowEntity {
+                    kind: contents.string("type")?.to_string(),
+                    // uuids that are stored as int arrays aren't supported
+                    id: contents.string("id")?.to_string(),
+                    name: contents
+                        .get("name")
+                        .and_then(FormattedText::from_nbt_tag)
+                        .map(Box::new),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_event_round_trip() {
+        let json = serde_json::json!({ "action": "run_command", "value": "/help" });
+        let click_event = ClickEvent::deserialize(&json).unwrap();
+        assert_eq!(
+            click_event,
+            ClickEvent::new(ClickAction::RunCommand, "/help")
+        );
+        assert_eq!(serde_json::to_value(&click_event).unwrap(), json);
+    }
+
+    #[test]
+    fn test_hover_event_legacy_value() {
+        let json = serde_json::json!({ "action": "show_text", "value": "hi" });
+        assert_eq!(
+            HoverEvent::deserialize(&json).unwrap(),
+            HoverEvent::ShowText(Box::new(FormattedText::from("hi")))
+        );
+    }
 }
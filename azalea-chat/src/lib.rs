@@ -2,10 +2,12 @@
 
 pub mod base_component;
 mod component;
+pub mod events;
 #[cfg(feature = "numbers")]
 pub mod numbers;
 pub mod style;
 pub mod text_component;
 pub mod translatable_component;
 
+pub use azalea_language::{set_language, Language};
 pub use component::FormattedText;
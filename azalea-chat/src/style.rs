@@ -5,7 +5,12 @@ use azalea_buf::AzBuf;
 use serde::{ser::SerializeStruct, Serialize, Serializer};
 use serde_json::Value;
 #[cfg(feature = "simdnbt")]
-use simdnbt::owned::{NbtCompound, NbtTag};
+use simdnbt::{
+    owned::{NbtCompound, NbtTag},
+    FromNbtTag as _,
+};
+
+use crate::events::{ClickEvent, HoverEvent};
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct TextColor {
@@ -307,6 +312,8 @@ pub struct Style {
     pub underlined: Option<bool>,
     pub strikethrough: Option<bool>,
     pub obfuscated: Option<bool>,
+    pub click_event: Option<ClickEvent>,
+    pub hover_event: Option<HoverEvent>,
     /// Whether formatting should be reset before applying these styles
     pub reset: bool,
 }
@@ -356,6 +363,8 @@ impl Serialize for Style {
                 + usize::from(self.strikethrough.is_some())
                 + usize::from(self.obfuscated.is_some())
         };
+        let len =
+            len + usize::from(self.click_event.is_some()) + usize::from(self.hover_event.is_some());
         let mut state = serializer.serialize_struct("Style", len)?;
 
         serde_serialize_field(&mut state, "color", &self.color, "white", self.reset)?;
@@ -382,6 +391,12 @@ impl Serialize for Style {
             &false,
             self.reset,
         )?;
+        if let Some(click_event) = &self.click_event {
+            state.serialize_field("clickEvent", click_event)?;
+        }
+        if let Some(hover_event) = &self.hover_event {
+            state.serialize_field("hoverEvent", hover_event)?;
+        }
 
         state.end()
     }
@@ -416,6 +431,12 @@ impl simdnbt::Serialize for Style {
             false,
             self.reset,
         );
+        if let Some(click_event) = self.click_event {
+            compound.insert("clickEvent", click_event);
+        }
+        if let Some(hover_event) = self.hover_event {
+            compound.insert("hoverEvent", hover_event);
+        }
 
         compound
     }
@@ -439,6 +460,12 @@ impl Style {
             .get("color")
             .and_then(|v| v.as_str())
             .and_then(|v| TextColor::parse(v.to_string()));
+        let click_event = json_object
+            .get("clickEvent")
+            .and_then(ClickEvent::deserialize);
+        let hover_event = json_object
+            .get("hoverEvent")
+            .and_then(HoverEvent::deserialize);
         Style {
             color,
             bold,
@@ -446,6 +473,8 @@ impl Style {
             underlined,
             strikethrough,
             obfuscated,
+            click_event,
+            hover_event,
             ..Style::default()
         }
     }
@@ -458,6 +487,8 @@ impl Style {
             && self.underlined.is_none()
             && self.strikethrough.is_none()
             && self.obfuscated.is_none()
+            && self.click_event.is_none()
+            && self.hover_event.is_none()
     }
 
     /// find the necessary ansi code to get from this style to another
@@ -552,6 +583,12 @@ impl Style {
         if let Some(obfuscated) = &style.obfuscated {
             self.obfuscated = Some(*obfuscated);
         }
+        if let Some(click_event) = &style.click_event {
+            self.click_event = Some(click_event.clone());
+        }
+        if let Some(hover_event) = &style.hover_event {
+            self.hover_event = Some(hover_event.clone());
+        }
     }
 
     /// Apply a ChatFormatting to this style
@@ -586,6 +623,12 @@ impl simdnbt::Deserialize for Style {
         let color: Option<TextColor> = compound
             .string("color")
             .and_then(|v| TextColor::parse(v.to_string()));
+        let click_event = compound
+            .get("clickEvent")
+            .and_then(ClickEvent::from_nbt_tag);
+        let hover_event = compound
+            .get("hoverEvent")
+            .and_then(HoverEvent::from_nbt_tag);
         Ok(Style {
             color,
             bold,
@@ -593,6 +636,8 @@ impl simdnbt::Deserialize for Style {
             underlined,
             strikethrough,
             obfuscated,
+            click_event,
+            hover_event,
             ..Style::default()
         })
     }
@@ -105,7 +105,8 @@ impl TranslatableComponent {
 
     /// Convert the key and args to a FormattedText.
     pub fn read(&self) -> Result<TextComponent, fmt::Error> {
-        let template = azalea_language::get(&self.key).unwrap_or(&self.key);
+        let language = azalea_language::current_language();
+        let template = language.get(&self.key).unwrap_or(&self.key);
         // decode the % things
 
         let mut i = 0;
@@ -228,6 +229,7 @@ impl From<StringOrComponent> for TextComponent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Language;
 
     #[test]
     fn test_none() {
@@ -290,4 +292,22 @@ mod tests {
         );
         assert_eq!(c.read().unwrap().to_string(), "hi %  s".to_string());
     }
+    #[test]
+    fn test_custom_language() {
+        // other tests might be running, so only add keys that they don't use
+        let language = Language::from_json(
+            r#"{"translation.test.custom_language": "Hallo, %s!"}"#,
+        )
+        .unwrap();
+        crate::set_language(language);
+
+        let c = TranslatableComponent::new(
+            "translation.test.custom_language".to_string(),
+            vec![StringOrComponent::String("Welt".to_string())],
+        );
+        assert_eq!(c.read().unwrap().to_string(), "Hallo, Welt!".to_string());
+        // missing keys fall back to en_us
+        let c = TranslatableComponent::new("translation.test.none".to_string(), vec![]);
+        assert_eq!(c.read().unwrap().to_string(), "Hello, world!".to_string());
+    }
 }
@@ -1,5 +1,6 @@
 use azalea_chat::{
     style::{Ansi, ChatFormatting, TextColor},
+    text_component::TextComponent,
     FormattedText,
 };
 use serde::Deserialize;
@@ -73,3 +74,73 @@ fn component_from_string() {
     let component = FormattedText::deserialize(&j).unwrap();
     assert_eq!(component.to_ansi(), "foo");
 }
+
+#[test]
+fn html_test() {
+    let j: Value = serde_json::from_str(
+        r##"{
+    "text": "click <here>",
+    "color": "#abcdef",
+    "underlined": true,
+    "clickEvent": {"action": "open_url", "value": "https://example.com/?a=1&b=2"},
+    "hoverEvent": {"action": "show_text", "contents": {"text": "a \"link\""}},
+    "extra": [
+        {"text": " or run ", "underlined": false, "clickEvent": {"action": "run_command", "value": "/help"}},
+        {"text": "this\nplease", "color": "red", "italic": true, "strikethrough": true}
+    ]
+}"##,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(
+        component.to_html(),
+        concat!(
+            r#"<a href="https://example.com/?a=1&amp;b=2"><span style="color:#ABCDEF;text-decoration:underline" title="a &quot;link&quot;">click &lt;here&gt;</span></a>"#,
+            r#"<span style="color:#ABCDEF" title="a &quot;link&quot;" data-click-action="run_command" data-click-value="/help"> or run </span>"#,
+            r#"<a href="https://example.com/?a=1&amp;b=2"><span style="color:#FF5555;font-style:italic;text-decoration:underline line-through" title="a &quot;link&quot;">this<br>please</span></a>"#,
+        )
+    );
+}
+
+#[test]
+fn html_ignores_non_web_links() {
+    let j: Value = serde_json::from_str(
+        r#"{"text": "hi", "clickEvent": {"action": "open_url", "value": "javascript:alert(1)"}}"#,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(
+        component.to_html(),
+        r#"<span data-click-action="open_url" data-click-value="javascript:alert(1)">hi</span>"#
+    );
+}
+
+#[test]
+fn markdown_test() {
+    let j: Value = serde_json::from_str(
+        r##"[
+    {"text": "<bot0> ", "color": "gray"},
+    {"text": "hello ", "bold": true, "italic": true},
+    {"text": "*world*", "color": "red", "bold": true, "italic": true},
+    {"text": " secret", "obfuscated": true},
+    {
+        "text": "docs",
+        "underlined": true,
+        "clickEvent": {"action": "open_url", "value": "https://example.com/a (b)"},
+        "hoverEvent": {"action": "show_text", "contents": "open the \"docs\""}
+    }
+]"##,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(
+        component.to_markdown(),
+        r#"\<bot0\> ***hello \*world\**** ||secret||[__docs__](https://example.com/a%20%28b%29 "open the \"docs\"")"#
+    );
+}
+
+#[test]
+fn markdown_legacy_reset() {
+    let component = FormattedText::Text(TextComponent::new("§lHello §rworld".to_string()));
+    assert_eq!(component.to_markdown(), "**Hello** world");
+}
@@ -51,8 +51,8 @@ pub struct Account {
 
     /// The certificates used for chat signing.
     ///
-    /// This is set when you call [`Self::request_certs`], but you only
-    /// need to if the servers you're joining require it.
+    /// This is set automatically when joining a server with an online-mode
+    /// account, or when you call [`Self::request_certs`].
     pub certs: Option<Certificates>,
 }
 
@@ -264,4 +264,20 @@ impl Account {
 
         Ok(())
     }
+
+    /// Request new certificates for chat signing if we don't have any yet or
+    /// if the ones we have are about to expire.
+    ///
+    /// This is called automatically when joining a server with an online-mode
+    /// account.
+    pub async fn request_certs_if_needed(&mut self) -> Result<(), RequestCertError> {
+        if self
+            .certs
+            .as_ref()
+            .is_none_or(|certs| certs.should_refresh())
+        {
+            self.request_certs().await?;
+        }
+        Ok(())
+    }
 }
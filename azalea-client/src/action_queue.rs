@@ -0,0 +1,268 @@
+//! Spread world interactions out over multiple ticks.
+//!
+//! Servers (and anticheats) usually kick players that send too many packets in
+//! a single tick, which is easy to do by accident when you're placing or
+//! breaking a lot of blocks at once. Actions that are queued with
+//! [`Client::queue_action`] are run a few at a time instead, based on the
+//! client's [`ActionQueue::actions_per_tick`].
+
+use std::collections::VecDeque;
+
+use azalea_core::{position::BlockPos, tick::GameTick};
+use azalea_inventory::operations::ClickOperation;
+use azalea_physics::PhysicsSet;
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    interact::BlockInteractEvent,
+    inventory::ContainerClickEvent,
+    mining::{Mining, StartMiningBlockEvent},
+    Client,
+};
+
+/// The number of queued actions that are run every tick by default.
+pub const DEFAULT_ACTIONS_PER_TICK: u32 = 4;
+
+pub struct ActionQueuePlugin;
+impl Plugin for ActionQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(GameTick, run_queued_actions.before(PhysicsSet));
+    }
+}
+
+/// A component for local players that contains the actions that are waiting to
+/// be run.
+///
+/// This is only present after an action is queued or the budget is changed.
+#[derive(Component, Clone, Debug)]
+pub struct ActionQueue {
+    /// The maximum number of actions that can be run in a single tick.
+    pub actions_per_tick: u32,
+    pub actions: VecDeque<QueuedAction>,
+}
+impl Default for ActionQueue {
+    fn default() -> Self {
+        Self {
+            actions_per_tick: DEFAULT_ACTIONS_PER_TICK,
+            actions: VecDeque::new(),
+        }
+    }
+}
+
+/// An interaction with the world that can be added to the [`ActionQueue`].
+#[derive(Clone, Debug)]
+pub enum QueuedAction {
+    /// Right click a block, like [`Client::block_interact`].
+    BlockInteract(BlockPos),
+    /// Start mining a block, like [`Client::start_mining`].
+    ///
+    /// Blocks that can't be broken instantly take multiple ticks to mine, so
+    /// the queue will wait until we're done mining before running the next
+    /// action.
+    Mine(BlockPos),
+    /// Click a slot in the container that we have open.
+    ContainerClick {
+        window_id: i32,
+        operation: ClickOperation,
+    },
+}
+
+impl Client {
+    /// Add an action to the end of our [`ActionQueue`], so it gets run once
+    /// the actions before it are done and there's enough budget in the
+    /// current tick.
+    ///
+    /// ```
+    /// # use azalea_client::{Client, action_queue::QueuedAction};
+    /// # use azalea_core::position::BlockPos;
+    /// # fn example(bot: &Client) {
+    /// // this would probably get us kicked if we did it all in one tick
+    /// for x in 0..64 {
+    ///     bot.queue_action(QueuedAction::BlockInteract(BlockPos::new(x, 64, 0)));
+    /// }
+    /// # }
+    /// ```
+    pub fn queue_action(&self, action: QueuedAction) {
+        let mut ecs = self.ecs.lock();
+        let mut entity = ecs.entity_mut(self.entity);
+        if let Some(mut queue) = entity.get_mut::<ActionQueue>() {
+            queue.actions.push_back(action);
+        } else {
+            let mut queue = ActionQueue::default();
+            queue.actions.push_back(action);
+            entity.insert(queue);
+        }
+    }
+
+    /// Set the maximum number of queued actions that can be run in a single
+    /// tick. This defaults to [`DEFAULT_ACTIONS_PER_TICK`].
+    pub fn set_actions_per_tick(&self, actions_per_tick: u32) {
+        let mut ecs = self.ecs.lock();
+        let mut entity = ecs.entity_mut(self.entity);
+        if let Some(mut queue) = entity.get_mut::<ActionQueue>() {
+            queue.actions_per_tick = actions_per_tick;
+        } else {
+            entity.insert(ActionQueue {
+                actions_per_tick,
+                ..Default::default()
+            });
+        }
+    }
+
+    /// The number of actions that are waiting to be run.
+    pub fn queued_action_count(&self) -> usize {
+        self.get_component::<ActionQueue>()
+            .map(|queue| queue.actions.len())
+            .unwrap_or_default()
+    }
+
+    /// Remove all the actions that haven't been run yet.
+    pub fn clear_queued_actions(&self) {
+        let mut ecs = self.ecs.lock();
+        if let Some(mut queue) = ecs.get_mut::<ActionQueue>(self.entity) {
+            queue.actions.clear();
+        }
+    }
+}
+
+pub fn run_queued_actions(
+    mut query: Query<(Entity, &mut ActionQueue, Option<&Mining>)>,
+    mut block_interact_events: EventWriter<BlockInteractEvent>,
+    mut start_mining_events: EventWriter<StartMiningBlockEvent>,
+    mut container_click_events: EventWriter<ContainerClickEvent>,
+) {
+    for (entity, mut queue, mining) in &mut query {
+        if queue.actions.is_empty() {
+            continue;
+        }
+        let mut is_mining = mining.is_some();
+
+        for _ in 0..queue.actions_per_tick {
+            if is_mining && matches!(queue.actions.front(), Some(QueuedAction::Mine(_))) {
+                // starting to mine a different block would cancel the one we're mining
+                break;
+            }
+            let Some(action) = queue.actions.pop_front() else {
+                break;
+            };
+            match action {
+                QueuedAction::BlockInteract(position) => {
+                    block_interact_events.send(BlockInteractEvent { entity, position });
+                }
+                QueuedAction::Mine(position) => {
+                    start_mining_events.send(StartMiningBlockEvent { entity, position });
+                    is_mining = true;
+                }
+                QueuedAction::ContainerClick {
+                    window_id,
+                    operation,
+                } => {
+                    container_click_events.send(ContainerClickEvent {
+                        entity,
+                        window_id,
+                        operation,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::direction::Direction;
+    use azalea_inventory::operations::QuickMoveClick;
+    use bevy_ecs::schedule::Schedule;
+
+    use super::*;
+
+    fn world_with_events() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<BlockInteractEvent>>();
+        world.init_resource::<Events<StartMiningBlockEvent>>();
+        world.init_resource::<Events<ContainerClickEvent>>();
+        world
+    }
+
+    /// Run the system for one tick and return the number of each kind of event
+    /// that was sent, as `(interactions, mines, clicks)`.
+    fn tick(world: &mut World) -> (usize, usize, usize) {
+        let mut schedule = Schedule::default();
+        schedule.add_systems(run_queued_actions);
+        schedule.run(world);
+
+        let counts = (
+            world.resource::<Events<BlockInteractEvent>>().len(),
+            world.resource::<Events<StartMiningBlockEvent>>().len(),
+            world.resource::<Events<ContainerClickEvent>>().len(),
+        );
+        world.resource_mut::<Events<BlockInteractEvent>>().clear();
+        world
+            .resource_mut::<Events<StartMiningBlockEvent>>()
+            .clear();
+        world.resource_mut::<Events<ContainerClickEvent>>().clear();
+        counts
+    }
+
+    #[test]
+    fn test_actions_are_spread_across_ticks() {
+        let mut world = world_with_events();
+        let mut queue = ActionQueue {
+            actions_per_tick: 3,
+            ..Default::default()
+        };
+        for x in 0..7 {
+            queue
+                .actions
+                .push_back(QueuedAction::BlockInteract(BlockPos::new(x, 0, 0)));
+        }
+        queue.actions.push_back(QueuedAction::ContainerClick {
+            window_id: 1,
+            operation: ClickOperation::QuickMove(QuickMoveClick::Left { slot: 0 }),
+        });
+        let entity = world.spawn(queue).id();
+
+        assert_eq!(tick(&mut world), (3, 0, 0));
+        assert_eq!(tick(&mut world), (3, 0, 0));
+        assert_eq!(tick(&mut world), (1, 0, 1));
+        assert_eq!(tick(&mut world), (0, 0, 0));
+        assert!(world.get::<ActionQueue>(entity).unwrap().actions.is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_mining() {
+        let mut world = world_with_events();
+        let mut queue = ActionQueue::default();
+        queue
+            .actions
+            .push_back(QueuedAction::Mine(BlockPos::new(0, 0, 0)));
+        queue
+            .actions
+            .push_back(QueuedAction::Mine(BlockPos::new(1, 0, 0)));
+        queue
+            .actions
+            .push_back(QueuedAction::BlockInteract(BlockPos::new(2, 0, 0)));
+        let entity = world.spawn(queue).id();
+
+        // only one block can be mined at a time, even though we have the budget for
+        // more
+        assert_eq!(tick(&mut world), (0, 1, 0));
+        world.entity_mut(entity).insert(Mining {
+            pos: BlockPos::new(0, 0, 0),
+            dir: Direction::Up,
+        });
+        assert_eq!(tick(&mut world), (0, 0, 0));
+        assert_eq!(tick(&mut world), (0, 0, 0));
+
+        // we're done mining the first block, so the rest can run
+        world.entity_mut(entity).remove::<Mining>();
+        assert_eq!(tick(&mut world), (0, 1, 0));
+        world.entity_mut(entity).insert(Mining {
+            pos: BlockPos::new(1, 0, 0),
+            dir: Direction::Up,
+        });
+        // interacting doesn't have to wait for mining to finish
+        assert_eq!(tick(&mut world), (1, 0, 0));
+    }
+}
@@ -1,5 +1,6 @@
 use azalea_core::{game_type::GameMode, tick::GameTick};
 use azalea_entity::{
+    indexing::{EntityIdError, EntityIdIndex, EntityIdRef},
     metadata::{ShiftKeyDown, Sprinting},
     update_bounding_box, Attributes, Physics,
 };
@@ -39,6 +40,16 @@ impl Plugin for AttackPlugin {
 
 impl Client {
     /// Attack the entity with the given id.
+    ///
+    /// The server can give an id to a different entity as soon as the old one
+    /// is removed, so this might attack the wrong entity if the id was saved
+    /// from earlier. To migrate, get an [`EntityIdRef`] with
+    /// [`Self::entity_id_ref`] and pass it to [`Self::attack_ref`], which
+    /// returns an error instead of attacking if that happened.
+    #[deprecated(
+        since = "0.12.0",
+        note = "use `attack_ref` with an `EntityIdRef` from `entity_id_ref` instead"
+    )]
     pub fn attack(&mut self, entity_id: MinecraftEntityId) {
         self.ecs.lock().send_event(AttackEvent {
             entity: self.entity,
@@ -46,6 +57,39 @@ impl Client {
         });
     }
 
+    /// Attack the entity that an [`EntityIdRef`] refers to, or return an error
+    /// without attacking if the server has given its ID to a different entity
+    /// since the reference was created.
+    ///
+    /// You can get an `EntityIdRef` with [`Self::entity_id_ref`] or
+    /// [`EntityIdIndex::get_ref`].
+    pub fn attack_ref(&mut self, target: EntityIdRef) -> Result<(), EntityIdError> {
+        let mut ecs = self.ecs.lock();
+        ecs.get::<EntityIdIndex>(self.entity)
+            .ok_or(EntityIdError::NotFound(target.id))?
+            .resolve(target)?;
+        ecs.send_event(AttackEvent {
+            entity: self.entity,
+            target: target.id,
+        });
+        Ok(())
+    }
+
+    /// Get an [`EntityIdRef`] for an entity that we can see, which can be
+    /// passed to [`Self::attack_ref`].
+    ///
+    /// Returns `None` if the entity doesn't exist or if it's not in our
+    /// [`EntityIdIndex`].
+    pub fn entity_id_ref(&self, entity: Entity) -> Option<EntityIdRef> {
+        let ecs = self.ecs.lock();
+        let id = *ecs.get::<MinecraftEntityId>(entity)?;
+        let entity_id_index = ecs.get::<EntityIdIndex>(self.entity)?;
+        if entity_id_index.get(id) != Some(entity) {
+            return None;
+        }
+        entity_id_index.get_ref(id)
+    }
+
     /// Whether the player has an attack cooldown.
     pub fn has_attack_cooldown(&self) -> bool {
         let Some(AttackStrengthScale(ticks_since_last_attack)) =
@@ -77,8 +121,11 @@ pub fn handle_attack_event(
     mut swing_arm_event: EventWriter<SwingArmEvent>,
 ) {
     for event in events.read() {
-        let (game_mode, mut ticks_since_last_attack, mut physics, mut sprinting, sneaking) =
-            query.get_mut(event.entity).unwrap();
+        let Ok((game_mode, mut ticks_since_last_attack, mut physics, mut sprinting, sneaking)) =
+            query.get_mut(event.entity)
+        else {
+            continue;
+        };
 
         swing_arm_event.send(SwingArmEvent {
             entity: event.entity,
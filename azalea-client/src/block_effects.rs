@@ -0,0 +1,513 @@
+//! Events for the sounds and particles that happen when blocks are broken or
+//! placed, so things like external visualizers can show what the bot is doing.
+//!
+//! The server doesn't send these effects to the player that caused them since
+//! vanilla clients play them on their own, so the events for our own actions
+//! are sent by Azalea instead.
+
+use azalea_block::BlockState;
+use azalea_core::position::BlockPos;
+use azalea_protocol::packets::game::{c_level_event::ClientboundLevelEvent, ClientboundGamePacket};
+use azalea_world::{InstanceContainer, InstanceName, MinecraftEntityId};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    interact::{handle_block_interact_event, BlockInteractEvent, CurrentSequenceNumber},
+    inventory::Inventory,
+    packet_handling::game::PacketEvent,
+};
+
+/// The level event type that vanilla uses for the particles and sound of a
+/// block being broken.
+pub const DESTROY_BLOCK_LEVEL_EVENT: u32 = 2001;
+
+pub struct BlockEffectsPlugin;
+impl Plugin for BlockEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LevelEffectEvent>()
+            .add_event::<BlockBreakEffectEvent>()
+            .add_event::<BlockPlaceEffectEvent>()
+            .add_event::<BlockDestructionProgressEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_block_effect_packets,
+                    track_block_placements.after(handle_block_interact_event),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The server sent a level event, which is used for sounds and particles that
+/// come from something happening in the world.
+///
+/// See [`BlockBreakEffectEvent`] for a more convenient way to handle blocks
+/// being broken.
+#[derive(Event, Debug, Clone)]
+pub struct LevelEffectEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub packet: ClientboundLevelEvent,
+}
+
+/// Where a block effect came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockEffectSource {
+    /// The server told us about it, so it was caused by another player or
+    /// something else in the world.
+    Server,
+    /// It was caused by our own client.
+    Local,
+}
+
+/// A block was broken, which is when vanilla clients show the block breaking
+/// particles and play the break sound.
+#[derive(Event, Debug, Clone)]
+pub struct BlockBreakEffectEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub position: BlockPos,
+    /// The block that was broken.
+    pub block_state: BlockState,
+    pub source: BlockEffectSource,
+}
+
+/// We placed a block, which is when vanilla clients play the place sound.
+///
+/// This is sent when the server confirms that the block is there, since we
+/// don't know what a click will do until then.
+#[derive(Event, Debug, Clone)]
+pub struct BlockPlaceEffectEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub position: BlockPos,
+    pub block_state: BlockState,
+}
+
+/// Another entity's progress on mining a block changed, which is when vanilla
+/// clients update the cracks on the block.
+///
+/// Our own progress is sent in [`MineBlockProgressEvent`].
+///
+/// [`MineBlockProgressEvent`]: crate::mining::MineBlockProgressEvent
+#[derive(Event, Debug, Clone)]
+pub struct BlockDestructionProgressEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    /// The ID of the entity that's mining the block.
+    pub breaker: MinecraftEntityId,
+    pub position: BlockPos,
+    /// A number from 0 to 9, or `None` if they stopped mining.
+    pub destroy_stage: Option<u32>,
+}
+
+/// The blocks around where we clicked, so we can tell when the server places a
+/// block there.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PendingBlockPlacements {
+    pub blocks: Vec<PendingBlockPlacement>,
+}
+
+/// A block that might be replaced by the block that we were holding when we
+/// clicked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingBlockPlacement {
+    /// The sequence number of the click.
+    pub sequence: u32,
+    pub position: BlockPos,
+    /// The state that the block had when we clicked.
+    pub old_state: BlockState,
+    /// The item that we were holding when we clicked.
+    pub item: azalea_registry::Item,
+}
+
+impl PendingBlockPlacements {
+    /// Check whether a block update from the server is us placing a block,
+    /// and stop tracking the position if it is.
+    ///
+    /// Only blocks that could've been placed by the item we were holding count,
+    /// so things like water flowing into the space next to where we clicked or
+    /// bone meal growing grass aren't mistaken for placements.
+    pub fn take_placement(&mut self, position: BlockPos, new_state: BlockState) -> bool {
+        let Some(index) = self.blocks.iter().position(|pending| {
+            pending.position == position
+                && pending.old_state != new_state
+                && is_replaceable(pending.old_state)
+                && item_places_block(pending.item, new_state)
+        }) else {
+            return false;
+        };
+        // if the same click could've placed the block in multiple spots, it can only
+        // be in one of them
+        let sequence = self.blocks[index].sequence;
+        self.blocks.retain(|pending| pending.sequence != sequence);
+        true
+    }
+}
+
+/// Whether holding the item and right clicking could place the block.
+///
+/// This uses the names of the item and block, since almost every block item
+/// has the same name as its block. Wall signs, wall torches and buckets place
+/// a block with a slightly different name, which is handled too.
+pub fn item_places_block(item: azalea_registry::Item, block_state: BlockState) -> bool {
+    if block_state.is_air() {
+        return false;
+    }
+    let block = azalea_registry::Block::from(block_state);
+    let item_name = item.to_string();
+    let block_name = block.to_string();
+    if block_name == item_name
+        || block_name.replacen("wall_", "", 1) == item_name
+        || item_name.strip_suffix("_bucket") == Some(&block_name)
+    {
+        return true;
+    }
+    matches!(
+        (item, block),
+        (
+            azalea_registry::Item::Redstone,
+            azalea_registry::Block::RedstoneWire
+        ) | (
+            azalea_registry::Item::String,
+            azalea_registry::Block::Tripwire
+        )
+    )
+}
+
+pub fn handle_block_effect_packets(
+    mut commands: Commands,
+    mut events: EventReader<PacketEvent>,
+    mut query: Query<Option<&mut PendingBlockPlacements>>,
+    mut level_effect_events: EventWriter<LevelEffectEvent>,
+    mut block_break_effect_events: EventWriter<BlockBreakEffectEvent>,
+    mut block_place_effect_events: EventWriter<BlockPlaceEffectEvent>,
+    mut block_destruction_progress_events: EventWriter<BlockDestructionProgressEvent>,
+) {
+    for event in events.read() {
+        let Ok(pending_placements) = query.get_mut(event.entity) else {
+            continue;
+        };
+        match event.packet.as_ref() {
+            ClientboundGamePacket::Login(_) => {
+                commands
+                    .entity(event.entity)
+                    .insert(PendingBlockPlacements::default());
+            }
+            ClientboundGamePacket::LevelEvent(p) => {
+                if p.event_type == DESTROY_BLOCK_LEVEL_EVENT {
+                    if let Ok(block_state) = BlockState::try_from(p.data) {
+                        block_break_effect_events.send(BlockBreakEffectEvent {
+                            entity: event.entity,
+                            position: p.pos,
+                            block_state,
+                            source: BlockEffectSource::Server,
+                        });
+                    }
+                }
+                level_effect_events.send(LevelEffectEvent {
+                    entity: event.entity,
+                    packet: p.clone(),
+                });
+            }
+            ClientboundGamePacket::BlockDestruction(p) => {
+                block_destruction_progress_events.send(BlockDestructionProgressEvent {
+                    entity: event.entity,
+                    breaker: MinecraftEntityId(p.id),
+                    position: p.pos,
+                    destroy_stage: (p.progress < 10).then_some(p.progress as u32),
+                });
+            }
+            ClientboundGamePacket::BlockUpdate(p) => {
+                let Some(mut pending_placements) = pending_placements else {
+                    continue;
+                };
+                if pending_placements.take_placement(p.pos, p.block_state) {
+                    block_place_effect_events.send(BlockPlaceEffectEvent {
+                        entity: event.entity,
+                        position: p.pos,
+                        block_state: p.block_state,
+                    });
+                }
+            }
+            ClientboundGamePacket::SectionBlocksUpdate(p) => {
+                let Some(mut pending_placements) = pending_placements else {
+                    continue;
+                };
+                for state in &p.states {
+                    let position = p.section_pos + state.pos;
+                    if pending_placements.take_placement(position, state.state) {
+                        block_place_effect_events.send(BlockPlaceEffectEvent {
+                            entity: event.entity,
+                            position,
+                            block_state: state.state,
+                        });
+                    }
+                }
+            }
+            ClientboundGamePacket::BlockChangedAck(p) => {
+                let Some(mut pending_placements) = pending_placements else {
+                    continue;
+                };
+                // the server always sends the block updates before it acknowledges the click
+                pending_placements
+                    .blocks
+                    .retain(|pending| pending.sequence as i32 > p.sequence);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_replaceable(block_state: BlockState) -> bool {
+    let block = azalea_registry::Block::from(block_state);
+    azalea_registry::tags::blocks::REPLACEABLE.contains(&block)
+}
+
+/// Remember the blocks around where we clicked, since the block we're holding
+/// could be placed on the clicked block or in it if it's replaceable.
+pub fn track_block_placements(
+    mut events: EventReader<BlockInteractEvent>,
+    mut query: Query<(
+        &CurrentSequenceNumber,
+        &InstanceName,
+        &Inventory,
+        &mut PendingBlockPlacements,
+    )>,
+    instances: Res<InstanceContainer>,
+) {
+    for event in events.read() {
+        let Ok((sequence_number, instance_name, inventory, mut pending_placements)) =
+            query.get_mut(event.entity)
+        else {
+            continue;
+        };
+        let item = inventory.held_item().kind();
+        if item == azalea_registry::Item::Air {
+            continue;
+        }
+        let Some(instance_lock) = instances.get(instance_name) else {
+            continue;
+        };
+        let instance = instance_lock.read();
+
+        let neighbors = [
+            event.position,
+            event.position.up(1),
+            event.position.down(1),
+            event.position.north(1),
+            event.position.south(1),
+            event.position.east(1),
+            event.position.west(1),
+        ];
+        for pos in neighbors {
+            if let Some(old_state) = instance.get_block_state(&pos) {
+                pending_placements.blocks.push(PendingBlockPlacement {
+                    sequence: **sequence_number,
+                    position: pos,
+                    old_state,
+                    item,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_block::{blocks, properties::WaterLevel};
+    use azalea_core::position::{ChunkSectionBlockPos, ChunkSectionPos};
+    use azalea_protocol::packets::{
+        game::{
+            c_block_changed_ack::ClientboundBlockChangedAck,
+            c_block_update::ClientboundBlockUpdate,
+            c_section_blocks_update::{BlockStateWithPosition, ClientboundSectionBlocksUpdate},
+        },
+        Packet,
+    };
+    use azalea_registry::Item;
+    use bevy_ecs::event::Events;
+
+    use super::*;
+
+    fn pending(
+        sequence: u32,
+        position: BlockPos,
+        old_state: BlockState,
+        item: Item,
+    ) -> PendingBlockPlacement {
+        PendingBlockPlacement {
+            sequence,
+            position,
+            old_state,
+            item,
+        }
+    }
+
+    #[test]
+    fn test_item_places_block() {
+        assert!(item_places_block(Item::Stone, blocks::Stone {}.into()));
+        assert!(item_places_block(
+            Item::Torch,
+            BlockState::from(azalea_registry::Block::WallTorch)
+        ));
+        assert!(item_places_block(
+            Item::OakSign,
+            BlockState::from(azalea_registry::Block::OakWallSign)
+        ));
+        assert!(item_places_block(
+            Item::Redstone,
+            BlockState::from(azalea_registry::Block::RedstoneWire)
+        ));
+        assert!(item_places_block(
+            Item::WaterBucket,
+            blocks::Water {
+                level: WaterLevel::from(0)
+            }
+            .into()
+        ));
+
+        assert!(!item_places_block(Item::Stone, blocks::Dirt {}.into()));
+        assert!(!item_places_block(Item::Stone, BlockState::AIR));
+        assert!(!item_places_block(
+            Item::BoneMeal,
+            blocks::ShortGrass {}.into()
+        ));
+    }
+
+    #[test]
+    fn test_take_placement() {
+        let mut placements = PendingBlockPlacements {
+            blocks: vec![
+                pending(1, BlockPos::new(0, 1, 0), BlockState::AIR, Item::Stone),
+                pending(1, BlockPos::new(1, 0, 0), BlockState::AIR, Item::Stone),
+                pending(
+                    1,
+                    BlockPos::new(0, 0, 0),
+                    blocks::Dirt {}.into(),
+                    Item::Stone,
+                ),
+            ],
+        };
+
+        // water flowing next to where we clicked isn't us placing a block
+        let water = blocks::Water {
+            level: WaterLevel::from(1),
+        }
+        .into();
+        assert!(!placements.take_placement(BlockPos::new(1, 0, 0), water));
+        // and neither is a block that wasn't replaceable being changed
+        assert!(!placements.take_placement(BlockPos::new(0, 0, 0), blocks::Stone {}.into()));
+
+        assert!(placements.take_placement(BlockPos::new(0, 1, 0), blocks::Stone {}.into()));
+        // a click can only place one block
+        assert!(placements.blocks.is_empty());
+        assert!(!placements.take_placement(BlockPos::new(1, 0, 0), blocks::Stone {}.into()));
+    }
+
+    fn run_packets(
+        ecs: &mut World,
+        entity: Entity,
+        packets: Vec<ClientboundGamePacket>,
+    ) -> Vec<BlockPlaceEffectEvent> {
+        for packet in packets {
+            ecs.send_event(PacketEvent {
+                entity,
+                packet: Arc::new(packet),
+            });
+        }
+        Schedule::default()
+            .add_systems(handle_block_effect_packets)
+            .run(ecs);
+        ecs.resource_mut::<Events<BlockPlaceEffectEvent>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn test_block_place_effects_from_packets() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<PacketEvent>>();
+        ecs.init_resource::<Events<LevelEffectEvent>>();
+        ecs.init_resource::<Events<BlockBreakEffectEvent>>();
+        ecs.init_resource::<Events<BlockPlaceEffectEvent>>();
+        ecs.init_resource::<Events<BlockDestructionProgressEvent>>();
+
+        let entity = ecs
+            .spawn(PendingBlockPlacements {
+                blocks: vec![
+                    pending(1, BlockPos::new(0, 65, 0), BlockState::AIR, Item::Stone),
+                    pending(2, BlockPos::new(17, 65, 1), BlockState::AIR, Item::Stone),
+                    pending(
+                        3,
+                        BlockPos::new(5, 65, 5),
+                        blocks::ShortGrass {}.into(),
+                        Item::Stone,
+                    ),
+                ],
+            })
+            .id();
+
+        let events = run_packets(
+            &mut ecs,
+            entity,
+            vec![
+                // bone meal growing grass shouldn't count
+                ClientboundBlockUpdate {
+                    pos: BlockPos::new(5, 65, 5),
+                    block_state: azalea_registry::Block::TallGrass.into(),
+                }
+                .into_variant(),
+                ClientboundBlockUpdate {
+                    pos: BlockPos::new(0, 65, 0),
+                    block_state: blocks::Stone {}.into(),
+                }
+                .into_variant(),
+            ],
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].position, BlockPos::new(0, 65, 0));
+
+        // placements are also sent in section updates when several blocks change in
+        // the same tick
+        let events = run_packets(
+            &mut ecs,
+            entity,
+            vec![ClientboundSectionBlocksUpdate {
+                section_pos: ChunkSectionPos::new(1, 4, 0),
+                states: vec![
+                    BlockStateWithPosition {
+                        pos: ChunkSectionBlockPos::new(0, 0, 0),
+                        state: blocks::Water {
+                            level: WaterLevel::from(1),
+                        }
+                        .into(),
+                    },
+                    BlockStateWithPosition {
+                        pos: ChunkSectionBlockPos::new(1, 1, 1),
+                        state: blocks::Stone {}.into(),
+                    },
+                ],
+            }
+            .into_variant()],
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].position, BlockPos::new(17, 65, 1));
+
+        let events = run_packets(
+            &mut ecs,
+            entity,
+            vec![ClientboundBlockChangedAck { sequence: 3 }.into_variant()],
+        );
+        assert!(events.is_empty());
+        assert!(ecs
+            .get::<PendingBlockPlacements>(entity)
+            .unwrap()
+            .blocks
+            .is_empty());
+    }
+}
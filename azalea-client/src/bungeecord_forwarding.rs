@@ -0,0 +1,92 @@
+//! Join backend servers that expect BungeeCord's legacy IP forwarding.
+//!
+//! BungeeCord tells backend servers who's joining by putting the player's IP
+//! address, UUID, and profile properties in the hostname of the handshake
+//! packet. The backend can't check where that came from, so anything that can
+//! connect to it directly can claim to be anyone. Only use this on servers
+//! where that's fine, like on a private network or for testing.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use azalea_auth::game_profile::SerializableProfilePropertyValue;
+use uuid::Uuid;
+
+/// The player info that's appended to the hostname in the handshake, the same
+/// way that BungeeCord does it.
+#[derive(Clone, Debug)]
+pub struct BungeeCordForwarding {
+    /// The IP address that the backend server will think we're connecting
+    /// from.
+    pub client_address: IpAddr,
+    /// The profile properties to send, like the `textures` property for our
+    /// skin. This is empty by default.
+    pub properties: Vec<SerializableProfilePropertyValue>,
+}
+
+impl Default for BungeeCordForwarding {
+    fn default() -> Self {
+        Self {
+            client_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            properties: Vec::new(),
+        }
+    }
+}
+
+impl BungeeCordForwarding {
+    pub fn new(client_address: IpAddr) -> Self {
+        Self {
+            client_address,
+            properties: Vec::new(),
+        }
+    }
+
+    /// The hostname to send in the handshake packet instead of the real one.
+    ///
+    /// ```
+    /// # use azalea_client::bungeecord_forwarding::BungeeCordForwarding;
+    /// let forwarding = BungeeCordForwarding::new("10.0.0.2".parse().unwrap());
+    /// let hostname = forwarding.hostname("localhost", uuid::Uuid::nil()).unwrap();
+    /// assert_eq!(
+    ///     hostname,
+    ///     "localhost\u{0}10.0.0.2\u{0}00000000000000000000000000000000\u{0}[]"
+    /// );
+    /// ```
+    pub fn hostname(&self, host: &str, uuid: Uuid) -> Result<String, serde_json::Error> {
+        let properties = serde_json::to_string(&self.properties)?;
+        Ok(format!(
+            "{host}\u{0}{}\u{0}{}\u{0}{properties}",
+            self.client_address,
+            uuid.simple()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hostname_with_properties() {
+        let forwarding = BungeeCordForwarding {
+            client_address: "2001:db8::1".parse().unwrap(),
+            properties: vec![SerializableProfilePropertyValue {
+                name: "textures".to_owned(),
+                value: "abc".to_owned(),
+                signature: None,
+            }],
+        };
+        let hostname = forwarding
+            .hostname("play.example.com", Uuid::from_u128(1))
+            .unwrap();
+        let parts = hostname.split('\u{0}').collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            [
+                "play.example.com",
+                "2001:db8::1",
+                "00000000000000000000000000000001",
+                r#"[{"name":"textures","value":"abc","signature":null}]"#,
+            ]
+        );
+    }
+}
@@ -1,33 +1,36 @@
 //! Implementations of chat-related features.
 
-use std::{
-    sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::{collections::VecDeque, sync::Arc};
 
 use azalea_chat::FormattedText;
+use azalea_core::tick::GameTick;
 use azalea_protocol::packets::{
     game::{
         c_disguised_chat::ClientboundDisguisedChat,
         c_player_chat::ClientboundPlayerChat,
         c_system_chat::ClientboundSystemChat,
-        s_chat::{LastSeenMessagesUpdate, ServerboundChat},
+        s_chat::ServerboundChat,
         s_chat_command::ServerboundChatCommand,
+        s_chat_command_signed::{ArgumentSignature, ServerboundChatCommandSigned},
     },
     Packet,
 };
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::{
+    component::Component,
     entity::Entity,
     event::{EventReader, EventWriter},
     prelude::Event,
     schedule::IntoSystemConfigs,
+    system::{Query, Res, Resource},
 };
 use uuid::Uuid;
 
 use crate::{
+    chat_signing::{chat_timestamp, ChatSigningSession, LastSeenMessagesTracker},
     client::Client,
     packet_handling::game::{handle_send_packet_event, SendPacketEvent},
+    server_commands::ServerCommands,
 };
 
 /// A chat packet, either a system message or a chat message.
@@ -186,14 +189,17 @@ impl Plugin for ChatPlugin {
         app.add_event::<SendChatEvent>()
             .add_event::<SendChatKindEvent>()
             .add_event::<ChatReceivedEvent>()
+            .init_resource::<ChatRateLimit>()
             .add_systems(
                 Update,
                 (
                     handle_send_chat_event,
+                    send_queued_chat,
                     handle_send_chat_kind_event.after(handle_send_packet_event),
                 )
                     .chain(),
-            );
+            )
+            .add_systems(GameTick, tick_chat_rate_limit);
     }
 }
 
@@ -205,6 +211,10 @@ pub struct ChatReceivedEvent {
 }
 
 /// Send a chat message (or command, if it starts with a slash) to the server.
+///
+/// Messages that are too long are split into multiple messages, and if we've
+/// been sending messages too quickly then they're queued in [`ChatQueue`] so
+/// we don't get kicked for spamming.
 #[derive(Event)]
 pub struct SendChatEvent {
     pub entity: Entity,
@@ -213,25 +223,150 @@ pub struct SendChatEvent {
 
 pub fn handle_send_chat_event(
     mut events: EventReader<SendChatEvent>,
+    mut query: Query<&mut ChatQueue>,
     mut send_chat_kind_events: EventWriter<SendChatKindEvent>,
 ) {
     for event in events.read() {
-        if event.content.starts_with('/') {
-            send_chat_kind_events.send(SendChatKindEvent {
-                entity: event.entity,
-                content: event.content[1..].to_string(),
-                kind: ChatKind::Command,
-            });
+        let messages = if let Some(command) = event.content.strip_prefix('/') {
+            vec![(command.to_string(), ChatKind::Command)]
         } else {
+            split_message(&event.content, MAX_MESSAGE_LENGTH)
+                .into_iter()
+                .map(|message| (message, ChatKind::Message))
+                .collect()
+        };
+
+        if let Ok(mut chat_queue) = query.get_mut(event.entity) {
+            chat_queue.queue.extend(messages);
+        } else {
+            // we're not in the game yet, so there's nothing to rate limit
+            for (content, kind) in messages {
+                send_chat_kind_events.send(SendChatKindEvent {
+                    entity: event.entity,
+                    content,
+                    kind,
+                });
+            }
+        }
+    }
+}
+
+/// The maximum number of characters that can be in a chat message.
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// Split a message into parts that are at most `max_length` characters long.
+///
+/// Messages are split at spaces when possible, and words that are too long to
+/// fit in one message are split in the middle.
+///
+/// # Panics
+///
+/// This will panic if `max_length` is 0, since nothing could fit in a message.
+pub fn split_message(message: &str, max_length: usize) -> Vec<String> {
+    assert!(max_length > 0, "max_length must be greater than 0");
+    if message.chars().count() <= max_length {
+        return vec![message.to_owned()];
+    }
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut current_length = 0;
+    for mut word in message.split(' ') {
+        loop {
+            let word_length = word.chars().count();
+            let separator_length = if current.is_empty() { 0 } else { 1 };
+            if current_length + separator_length + word_length <= max_length {
+                if separator_length > 0 {
+                    current.push(' ');
+                }
+                current.push_str(word);
+                current_length += separator_length + word_length;
+                break;
+            }
+
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+                current_length = 0;
+                continue;
+            }
+
+            // the word can't fit in a message by itself
+            let (split_at, _) = word
+                .char_indices()
+                .nth(max_length)
+                .expect("the word is longer than max_length");
+            parts.push(word[..split_at].to_string());
+            word = &word[split_at..];
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// How quickly we're allowed to send chat messages and commands.
+///
+/// Vanilla servers keep a counter for each player that goes up by 20 for every
+/// message and down by 1 every tick, and kick the player if it goes above 200.
+/// The defaults here do the same thing on our side, so messages are queued
+/// instead of getting us kicked.
+#[derive(Resource, Clone, Debug)]
+pub struct ChatRateLimit {
+    /// How much the counter goes up for every message.
+    pub ticks_per_message: u32,
+    /// The highest that the counter is allowed to go.
+    pub max_ticks: u32,
+}
+impl Default for ChatRateLimit {
+    fn default() -> Self {
+        Self {
+            ticks_per_message: 20,
+            max_ticks: 200,
+        }
+    }
+}
+
+/// The chat messages and commands that are waiting to be sent because of the
+/// [`ChatRateLimit`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct ChatQueue {
+    pub queue: VecDeque<(String, ChatKind)>,
+    /// The same counter that the server uses for kicking players that spam,
+    /// see [`ChatRateLimit`].
+    pub spam_ticks: u32,
+}
+
+/// Send the messages from the [`ChatQueue`] that we can send without going
+/// over the [`ChatRateLimit`].
+pub fn send_queued_chat(
+    mut query: Query<(Entity, &mut ChatQueue)>,
+    rate_limit: Res<ChatRateLimit>,
+    mut send_chat_kind_events: EventWriter<SendChatKindEvent>,
+) {
+    for (entity, mut chat_queue) in &mut query {
+        while !chat_queue.queue.is_empty()
+            && chat_queue.spam_ticks + rate_limit.ticks_per_message <= rate_limit.max_ticks
+        {
+            let Some((content, kind)) = chat_queue.queue.pop_front() else {
+                break;
+            };
+            chat_queue.spam_ticks += rate_limit.ticks_per_message;
             send_chat_kind_events.send(SendChatKindEvent {
-                entity: event.entity,
-                content: event.content.clone(),
-                kind: ChatKind::Message,
+                entity,
+                content,
+                kind,
             });
         }
     }
 }
 
+pub fn tick_chat_rate_limit(mut query: Query<&mut ChatQueue>) {
+    for mut chat_queue in &mut query {
+        chat_queue.spam_ticks = chat_queue.spam_ticks.saturating_sub(1);
+    }
+}
+
 /// Send a chat packet to the server of a specific kind (chat message or
 /// command). Usually you just want [`SendChatEvent`] instead.
 ///
@@ -249,6 +384,7 @@ pub struct SendChatKindEvent {
 }
 
 /// A kind of chat packet, either a chat message or a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ChatKind {
     Message,
     Command,
@@ -256,41 +392,217 @@ pub enum ChatKind {
 
 pub fn handle_send_chat_kind_event(
     mut events: EventReader<SendChatKindEvent>,
+    mut query: Query<(
+        Option<&mut ChatSigningSession>,
+        Option<&mut LastSeenMessagesTracker>,
+        Option<&ServerCommands>,
+    )>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
     for event in events.read() {
+        let Ok((mut session, mut last_seen_tracker, server_commands)) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
         let content = event
             .content
             .chars()
             .filter(|c| !matches!(c, '\x00'..='\x1F' | '\x7F' | '§'))
-            .take(256)
+            .take(MAX_MESSAGE_LENGTH)
             .collect::<String>();
+
+        let signed_arguments = match event.kind {
+            ChatKind::Message => Vec::new(),
+            ChatKind::Command => server_commands
+                .map(|commands| commands.signed_arguments(&content))
+                .unwrap_or_default(),
+        };
+        if matches!(event.kind, ChatKind::Command) && signed_arguments.is_empty() {
+            send_packet_events.send(SendPacketEvent::new(
+                event.entity,
+                ServerboundChatCommand { command: content },
+            ));
+            continue;
+        }
+
+        let (timestamp, timestamp_millis) = chat_timestamp();
+        let salt = azalea_crypto::make_salt();
+        let (last_seen, last_seen_messages) = last_seen_tracker
+            .as_mut()
+            .map(|tracker| tracker.generate_update())
+            .unwrap_or_default();
+        let mut sign = |message: &str| {
+            session
+                .as_mut()
+                .map(|session| session.sign(message, timestamp, salt, last_seen.clone()))
+        };
+
         let packet = match event.kind {
             ChatKind::Message => ServerboundChat {
+                signature: sign(&content),
                 message: content,
-                timestamp: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Time shouldn't be before epoch")
-                    .as_millis()
-                    .try_into()
-                    .expect("Instant should fit into a u64"),
-                salt: azalea_crypto::make_salt(),
-                signature: None,
-                last_seen_messages: LastSeenMessagesUpdate::default(),
+                timestamp: timestamp_millis,
+                salt,
+                last_seen_messages,
             }
             .into_variant(),
-            ChatKind::Command => {
-                // TODO: chat signing
-                ServerboundChatCommand { command: content }.into_variant()
+            ChatKind::Command => ServerboundChatCommandSigned {
+                argument_signatures: signed_arguments
+                    .into_iter()
+                    .filter_map(|(name, value)| {
+                        Some(ArgumentSignature {
+                            name,
+                            signature: sign(value)?,
+                        })
+                    })
+                    .collect(),
+                command: content,
+                timestamp: timestamp_millis,
+                salt,
+                last_seen_messages,
             }
+            .into_variant(),
         };
 
         send_packet_events.send(SendPacketEvent::new(event.entity, packet));
     }
 }
 
-// TODO
-// MessageSigner, ChatMessageContent, LastSeenMessages
-// fn sign_message() -> MessageSignature {
-//     MessageSignature::default()
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_short_message() {
+        assert_eq!(split_message("hello world", 256), ["hello world"]);
+        assert_eq!(split_message("", 256), [""]);
+    }
+
+    #[test]
+    fn test_split_at_spaces() {
+        assert_eq!(split_message("aaa bbb ccc ddd", 7), ["aaa bbb", "ccc ddd"]);
+        assert_eq!(split_message("aaa bbb ccc", 8), ["aaa bbb", "ccc"]);
+    }
+
+    #[test]
+    fn test_split_long_word() {
+        assert_eq!(
+            split_message("a bbbbbbbbbb c", 4),
+            ["a", "bbbb", "bbbb", "bb c"]
+        );
+    }
+
+    #[test]
+    fn test_split_into_single_chars() {
+        assert_eq!(split_message("ab c", 1), ["a", "b", "c"]);
+        assert_eq!(split_message("éé", 1), ["é", "é"]);
+        assert_eq!(split_message("a", 1), ["a"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_with_zero_max_length() {
+        split_message("hello", 0);
+    }
+
+    #[test]
+    fn test_split_counts_chars_instead_of_bytes() {
+        let message = "é".repeat(300);
+        let parts = split_message(&message, MAX_MESSAGE_LENGTH);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].chars().count(), MAX_MESSAGE_LENGTH);
+        assert_eq!(parts[1].chars().count(), 300 - MAX_MESSAGE_LENGTH);
+        assert_eq!(parts.concat(), message);
+    }
+
+    fn sent_chat(ecs: &mut World) -> Vec<(String, ChatKind)> {
+        ecs.resource_mut::<Events<SendChatKindEvent>>()
+            .drain()
+            .map(|e| (e.content, e.kind))
+            .collect()
+    }
+
+    fn chat_world() -> (World, Schedule, Schedule) {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<SendChatEvent>>();
+        ecs.init_resource::<Events<SendChatKindEvent>>();
+        ecs.init_resource::<ChatRateLimit>();
+        let mut update = Schedule::default();
+        update.add_systems((handle_send_chat_event, send_queued_chat).chain());
+        let mut game_tick = Schedule::default();
+        game_tick.add_systems(tick_chat_rate_limit);
+        (ecs, update, game_tick)
+    }
+
+    #[test]
+    fn test_long_messages_are_split_before_sending() {
+        let (mut ecs, mut update, _) = chat_world();
+        let entity = ecs.spawn(ChatQueue::default()).id();
+
+        ecs.send_event(SendChatEvent {
+            entity,
+            content: "a ".repeat(200),
+        });
+        ecs.send_event(SendChatEvent {
+            entity,
+            content: format!("/say {}", "a ".repeat(200)),
+        });
+        update.run(&mut ecs);
+
+        let sent = sent_chat(&mut ecs);
+        assert_eq!(sent.len(), 3);
+        assert!(sent[..2].iter().all(|(content, kind)| {
+            content.len() <= MAX_MESSAGE_LENGTH && *kind == ChatKind::Message
+        }));
+        // commands aren't split
+        assert_eq!(sent[2].1, ChatKind::Command);
+        assert!(sent[2].0.starts_with("say "));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (mut ecs, mut update, mut game_tick) = chat_world();
+        let entity = ecs.spawn(ChatQueue::default()).id();
+
+        for i in 0..15 {
+            ecs.send_event(SendChatEvent {
+                entity,
+                content: format!("message {i}"),
+            });
+        }
+        update.run(&mut ecs);
+        // 200 / 20 = 10 messages can be sent right away
+        let sent = sent_chat(&mut ecs);
+        assert_eq!(sent.len(), 10);
+        assert_eq!(sent[9].0, "message 9");
+        assert_eq!(ecs.get::<ChatQueue>(entity).unwrap().queue.len(), 5);
+
+        for _ in 0..19 {
+            game_tick.run(&mut ecs);
+            update.run(&mut ecs);
+        }
+        assert!(sent_chat(&mut ecs).is_empty());
+
+        game_tick.run(&mut ecs);
+        update.run(&mut ecs);
+        assert_eq!(
+            sent_chat(&mut ecs),
+            [("message 10".to_owned(), ChatKind::Message)]
+        );
+    }
+
+    #[test]
+    fn test_no_rate_limit_before_joining() {
+        let (mut ecs, mut update, _) = chat_world();
+        // no ChatQueue since we're not in the game yet
+        let entity = ecs.spawn_empty().id();
+        for _ in 0..15 {
+            ecs.send_event(SendChatEvent {
+                entity,
+                content: "hi".to_owned(),
+            });
+        }
+        update.run(&mut ecs);
+        assert_eq!(sent_chat(&mut ecs).len(), 15);
+    }
+}
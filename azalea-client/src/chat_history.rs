@@ -0,0 +1,324 @@
+//! Remembering the chat messages that a client received recently.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use azalea_entity::LocalEntity;
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    chat::{ChatPacket, ChatReceivedEvent},
+    Client,
+};
+
+/// The number of messages that are kept in a [`ChatHistory`] by default.
+pub const DEFAULT_CHAT_HISTORY_CAPACITY: usize = 100;
+
+pub struct ChatHistoryPlugin;
+impl Plugin for ChatHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (insert_chat_history, record_chat_history).chain());
+    }
+}
+
+/// The most recent chat messages that this client received, oldest first.
+///
+/// Once it's full, the oldest messages are forgotten when new ones come in.
+/// You can insert your own `ChatHistory` with a different capacity and it'll
+/// be used instead of the default one.
+#[derive(Component, Clone, Debug)]
+pub struct ChatHistory {
+    entries: VecDeque<ChatHistoryEntry>,
+    capacity: usize,
+}
+
+/// A chat message in a [`ChatHistory`].
+#[derive(Clone, Debug)]
+pub struct ChatHistoryEntry {
+    pub packet: ChatPacket,
+    /// When we received the message.
+    pub received_at: Instant,
+    /// The username of the sender, from [`ChatPacket::username`].
+    pub sender: Option<String>,
+    /// The UUID of the sender, from [`ChatPacket::uuid`].
+    pub sender_uuid: Option<Uuid>,
+    /// The message without the sender, from [`ChatPacket::content`].
+    pub content: String,
+}
+
+impl ChatHistoryEntry {
+    pub fn new(packet: ChatPacket, received_at: Instant) -> Self {
+        let (sender, content) = packet.split_sender_and_content();
+        Self {
+            sender_uuid: packet.uuid(),
+            packet,
+            received_at,
+            sender,
+            content,
+        }
+    }
+
+    /// How long ago we received the message.
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+}
+
+impl Default for ChatHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHAT_HISTORY_CAPACITY)
+    }
+}
+
+impl ChatHistory {
+    /// Create an empty chat history that remembers up to `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the number of messages that are remembered, forgetting the
+    /// oldest ones if there's too many.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Add a message to the history.
+    pub fn push(&mut self, entry: ChatHistoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Iterate over the messages from oldest to newest. Use `.rev()` to start
+    /// from the newest message instead.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ChatHistoryEntry> {
+        self.entries.iter()
+    }
+
+    /// The most recent message that we received.
+    pub fn latest(&self) -> Option<&ChatHistoryEntry> {
+        self.entries.back()
+    }
+
+    /// The messages that match the predicate, from newest to oldest.
+    pub fn filter<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&ChatHistoryEntry) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a ChatHistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(move |entry| predicate(entry))
+    }
+
+    /// The messages that were sent by the player with the given username, from
+    /// newest to oldest.
+    pub fn from_sender<'a>(
+        &'a self,
+        username: &'a str,
+    ) -> impl Iterator<Item = &'a ChatHistoryEntry> {
+        self.filter(move |entry| entry.sender.as_deref() == Some(username))
+    }
+
+    /// The messages that were sent by the player with the given UUID, from
+    /// newest to oldest.
+    pub fn from_uuid(&self, uuid: Uuid) -> impl Iterator<Item = &ChatHistoryEntry> {
+        self.filter(move |entry| entry.sender_uuid == Some(uuid))
+    }
+
+    /// The messages that contain the given text, from newest to oldest.
+    pub fn search<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a ChatHistoryEntry> {
+        self.filter(move |entry| entry.content.contains(text))
+    }
+
+    /// The messages that we received in the last `duration`, from newest to
+    /// oldest.
+    pub fn since(&self, duration: Duration) -> impl Iterator<Item = &ChatHistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .take_while(move |entry| entry.age() <= duration)
+    }
+
+    /// The last message that the player sent at least `ago` before now, which
+    /// is useful for answering questions like "what did they say 5 seconds
+    /// ago?"
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use azalea_client::chat_history::ChatHistory;
+    /// # fn example(history: &ChatHistory) {
+    /// if let Some(entry) = history.said_before("py5", Duration::from_secs(5)) {
+    ///     println!("py5 said {}", entry.content);
+    /// }
+    /// # }
+    /// ```
+    pub fn said_before(&self, username: &str, ago: Duration) -> Option<&ChatHistoryEntry> {
+        self.from_sender(username).find(|entry| entry.age() >= ago)
+    }
+}
+
+fn insert_chat_history(
+    mut commands: Commands,
+    query: Query<Entity, (With<LocalEntity>, Without<ChatHistory>)>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert(ChatHistory::default());
+    }
+}
+
+pub fn record_chat_history(
+    mut events: EventReader<ChatReceivedEvent>,
+    mut query: Query<&mut ChatHistory>,
+) {
+    for event in events.read() {
+        let Ok(mut history) = query.get_mut(event.entity) else {
+            continue;
+        };
+        history.push(ChatHistoryEntry::new(event.packet.clone(), Instant::now()));
+    }
+}
+
+impl Client {
+    /// Look at the chat messages that this client received recently. See
+    /// [`ChatHistory`] for more details.
+    ///
+    /// The history isn't cloned, so the ECS is locked while `f` runs.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// let last_message =
+    ///     bot.with_chat_history(|history| history.latest().map(|e| e.content.clone()));
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the client isn't in the game yet.
+    pub fn with_chat_history<R>(&self, f: impl FnOnce(&ChatHistory) -> R) -> R {
+        self.map_component::<ChatHistory, R>(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::event::Events;
+
+    use super::*;
+
+    fn entry(message: &str, secs_ago: u64) -> ChatHistoryEntry {
+        ChatHistoryEntry::new(
+            ChatPacket::new(message),
+            Instant::now() - Duration::from_secs(secs_ago),
+        )
+    }
+
+    fn contents<'a>(entries: impl Iterator<Item = &'a ChatHistoryEntry>) -> Vec<&'a str> {
+        entries.map(|entry| entry.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut history = ChatHistory::new(2);
+        history.push(entry("<a> 1", 0));
+        history.push(entry("<a> 2", 0));
+        history.push(entry("<a> 3", 0));
+        assert_eq!(contents(history.iter()), ["2", "3"]);
+
+        history.set_capacity(1);
+        assert_eq!(contents(history.iter()), ["3"]);
+
+        history.set_capacity(0);
+        history.push(entry("<a> 4", 0));
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut history = ChatHistory::default();
+        history.push(entry("<py5> hello", 0));
+        history.push(entry("<bot> hello py5", 0));
+        history.push(entry("<py5> goodbye", 0));
+        history.push(entry("Server restarting", 0));
+
+        assert_eq!(contents(history.from_sender("py5")), ["goodbye", "hello"]);
+        assert_eq!(contents(history.search("hello")), ["hello py5", "hello"]);
+        assert_eq!(history.latest().unwrap().sender, None);
+        assert_eq!(history.latest().unwrap().content, "Server restarting");
+    }
+
+    #[test]
+    fn test_said_before() {
+        let mut history = ChatHistory::default();
+        history.push(entry("<py5> old", 30));
+        history.push(entry("<py5> recent", 6));
+        history.push(entry("<other> unrelated", 5));
+        history.push(entry("<py5> now", 0));
+
+        let said = history.said_before("py5", Duration::from_secs(5)).unwrap();
+        assert_eq!(said.content, "recent");
+        assert!(history
+            .said_before("py5", Duration::from_secs(60))
+            .is_none());
+
+        assert_eq!(
+            contents(history.since(Duration::from_secs(10))),
+            ["now", "unrelated", "recent"]
+        );
+    }
+
+    #[test]
+    fn test_record_chat_history() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ChatReceivedEvent>>();
+        let entity = ecs.spawn(LocalEntity).id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((insert_chat_history, record_chat_history).chain());
+
+        ecs.send_event(ChatReceivedEvent {
+            entity,
+            packet: ChatPacket::new("<py5> hi"),
+        });
+        schedule.run(&mut ecs);
+        ecs.send_event(ChatReceivedEvent {
+            entity,
+            packet: ChatPacket::new("<py5> hi again"),
+        });
+        schedule.run(&mut ecs);
+
+        let history = ecs.get::<ChatHistory>(entity).unwrap();
+        assert_eq!(contents(history.iter()), ["hi", "hi again"]);
+        assert_eq!(history.latest().unwrap().sender.as_deref(), Some("py5"));
+    }
+}
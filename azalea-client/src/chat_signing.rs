@@ -0,0 +1,418 @@
+//! Cryptographically signing chat messages, which is required by servers that
+//! have `enforce-secure-chat` enabled.
+//!
+//! Chat messages are only signed if the account has [`Account::certs`], which
+//! are requested from Mojang automatically when joining with an online-mode
+//! account.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use azalea_auth::certs::Certificates;
+use azalea_core::bitset::FixedBitSet;
+use azalea_crypto::{sign_chat_message, MessageSignature, SignChatMessageOptions};
+use azalea_protocol::packets::game::{
+    s_chat::LastSeenMessagesUpdate,
+    s_chat_session_update::{ProfilePublicKeyData, RemoteChatSessionData},
+    ClientboundGamePacket, ServerboundChatAck, ServerboundChatSessionUpdate,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+    chat::handle_send_chat_kind_event,
+    packet_handling::game::{PacketEvent, SendPacketEvent},
+    Account,
+};
+
+/// The number of messages that the server remembers for each player when
+/// checking which messages they've seen.
+pub const LAST_SEEN_MESSAGES_SIZE: usize = 20;
+
+/// How many messages we can receive before we have to tell the server that
+/// we've seen them, if we haven't sent any chat messages in the meantime.
+pub const MAX_UNACKNOWLEDGED_MESSAGES: u32 = 64;
+
+pub struct ChatSigningPlugin;
+impl Plugin for ChatSigningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            handle_chat_signing_packets.before(handle_send_chat_kind_event),
+        );
+    }
+}
+
+/// The chat session that we told the server about, which is used for signing
+/// our chat messages.
+///
+/// This is only present if the account has certificates.
+#[derive(Component, Clone, Debug)]
+pub struct ChatSigningSession {
+    pub session_id: Uuid,
+    /// The UUID of the player that's sending the messages.
+    pub sender: Uuid,
+    pub certs: Certificates,
+    /// The number of messages that were signed in this session, which is
+    /// included in the next signature so messages can't be reordered.
+    pub message_index: u32,
+}
+
+impl ChatSigningSession {
+    pub fn new(sender: Uuid, certs: Certificates) -> Self {
+        Self {
+            session_id: Uuid::new_v4(),
+            sender,
+            certs,
+            message_index: 0,
+        }
+    }
+
+    /// The data that the server needs to verify our signatures.
+    pub fn remote_data(&self) -> RemoteChatSessionData {
+        RemoteChatSessionData {
+            session_id: self.session_id,
+            profile_public_key: ProfilePublicKeyData {
+                expires_at: self
+                    .certs
+                    .expires_at
+                    .timestamp_millis()
+                    .try_into()
+                    .unwrap_or_default(),
+                key: self.certs.public_key_der.clone(),
+                key_signature: self.certs.signature_v2.clone(),
+            },
+        }
+    }
+
+    /// Sign a chat message or a command argument.
+    pub fn sign(
+        &mut self,
+        message: &str,
+        timestamp: SystemTime,
+        salt: u64,
+        last_seen_messages: Vec<MessageSignature>,
+    ) -> MessageSignature {
+        let signature = sign_chat_message(&SignChatMessageOptions {
+            account_uuid: self.sender,
+            chat_session_uuid: self.session_id,
+            message_index: self.message_index,
+            salt,
+            timestamp,
+            message: message.to_owned(),
+            last_seen_messages,
+            private_key: self.certs.private_key.clone(),
+        });
+        self.message_index += 1;
+        signature
+    }
+}
+
+/// Keeps track of the signed chat messages that we've received, so we can
+/// tell the server which ones we've seen.
+///
+/// This works the same way as vanilla's `LastSeenMessagesTracker`.
+#[derive(Component, Clone, Debug, Default)]
+pub struct LastSeenMessagesTracker {
+    tracked_messages: [Option<MessageSignature>; LAST_SEEN_MESSAGES_SIZE],
+    tail: usize,
+    /// The number of messages that we've received since we last told the
+    /// server about them.
+    offset: u32,
+    last_tracked_message: Option<MessageSignature>,
+}
+
+impl LastSeenMessagesTracker {
+    /// Start tracking a message that we received. Returns false if it was a
+    /// duplicate of the last message and so it wasn't added.
+    pub fn add_pending(&mut self, signature: MessageSignature) -> bool {
+        if self.last_tracked_message.as_ref() == Some(&signature) {
+            return false;
+        }
+        self.last_tracked_message = Some(signature.clone());
+
+        let index = self.tail;
+        self.tail = (index + 1) % LAST_SEEN_MESSAGES_SIZE;
+        self.offset += 1;
+        self.tracked_messages[index] = Some(signature);
+        true
+    }
+
+    /// The number of messages that we've received since the last time we
+    /// acknowledged them.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Return the number of messages that we haven't acknowledged yet, and
+    /// reset it to 0.
+    pub fn take_offset(&mut self) -> u32 {
+        std::mem::take(&mut self.offset)
+    }
+
+    /// Return the signatures of the messages that have to be included when
+    /// signing our next message, along with the update that tells the server
+    /// which messages we've seen.
+    pub fn generate_update(&mut self) -> (Vec<MessageSignature>, LastSeenMessagesUpdate) {
+        let messages = self.take_offset();
+        let mut acknowledged = FixedBitSet::new();
+        let mut last_seen = Vec::with_capacity(LAST_SEEN_MESSAGES_SIZE);
+        for i in 0..LAST_SEEN_MESSAGES_SIZE {
+            let index = (self.tail + i) % LAST_SEEN_MESSAGES_SIZE;
+            if let Some(signature) = &self.tracked_messages[index] {
+                acknowledged.set(i);
+                last_seen.push(signature.clone());
+            }
+        }
+        (
+            last_seen,
+            LastSeenMessagesUpdate {
+                messages,
+                acknowledged,
+            },
+        )
+    }
+}
+
+/// The current time and the milliseconds since the Unix epoch, which is what
+/// chat packets use for their timestamp.
+pub(crate) fn chat_timestamp() -> (SystemTime, u64) {
+    let now = SystemTime::now();
+    let millis = now
+        .duration_since(UNIX_EPOCH)
+        .expect("Time shouldn't be before epoch")
+        .as_millis()
+        .try_into()
+        .expect("Instant should fit into a u64");
+    (now, millis)
+}
+
+/// Start a new chat session when we join the game, and acknowledge the signed
+/// messages that we receive.
+pub fn handle_chat_signing_packets(
+    mut commands: Commands,
+    mut events: EventReader<PacketEvent>,
+    mut query: Query<(&Account, Option<&mut LastSeenMessagesTracker>)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok((account, tracker)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        match event.packet.as_ref() {
+            ClientboundGamePacket::Login(_) => {
+                let mut entity_commands = commands.entity(event.entity);
+                entity_commands.insert(LastSeenMessagesTracker::default());
+
+                let certs = account.certs.clone().filter(|certs| !certs.is_expired());
+                let Some(certs) = certs else {
+                    if account.certs.is_some() {
+                        warn!("Our chat signing certificates expired, so chat won't be signed");
+                    }
+                    entity_commands.remove::<ChatSigningSession>();
+                    continue;
+                };
+                let session = ChatSigningSession::new(account.uuid_or_offline(), certs);
+                debug!("Starting chat session {}", session.session_id);
+                send_packet_events.send(SendPacketEvent::new(
+                    event.entity,
+                    ServerboundChatSessionUpdate {
+                        chat_session: session.remote_data(),
+                    },
+                ));
+                entity_commands.insert(session);
+            }
+            ClientboundGamePacket::PlayerChat(p) => {
+                let (Some(mut tracker), Some(signature)) = (tracker, &p.signature) else {
+                    continue;
+                };
+                if tracker.add_pending(signature.clone())
+                    && tracker.offset() > MAX_UNACKNOWLEDGED_MESSAGES
+                {
+                    let messages = tracker.take_offset();
+                    send_packet_events.send(SendPacketEvent::new(
+                        event.entity,
+                        ServerboundChatAck { messages },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_chat::FormattedText;
+    use azalea_core::{
+        game_type::{GameMode, OptionalGameType},
+        resource_location::ResourceLocation,
+    };
+    use azalea_protocol::packets::{
+        common::CommonPlayerSpawnInfo,
+        game::{
+            c_player_chat::{
+                ChatTypeBound, FilterMask, PackedLastSeenMessages, PackedSignedMessageBody,
+            },
+            ClientboundLogin, ClientboundPlayerChat, ServerboundGamePacket,
+        },
+    };
+    use azalea_registry::ChatType;
+
+    use super::*;
+
+    fn signature(n: u8) -> MessageSignature {
+        MessageSignature { bytes: [n; 256] }
+    }
+
+    #[test]
+    fn test_last_seen_messages_are_oldest_first() {
+        let mut tracker = LastSeenMessagesTracker::default();
+        for i in 0..3 {
+            assert!(tracker.add_pending(signature(i)));
+        }
+        assert_eq!(tracker.offset(), 3);
+
+        let (last_seen, update) = tracker.generate_update();
+        assert_eq!(last_seen, [signature(0), signature(1), signature(2)]);
+        assert_eq!(update.messages, 3);
+        for i in 0..LAST_SEEN_MESSAGES_SIZE {
+            assert_eq!(update.acknowledged.index(i), i >= 17);
+        }
+        // the messages are still included after they were acknowledged
+        assert_eq!(tracker.offset(), 0);
+        assert_eq!(tracker.generate_update().0.len(), 3);
+    }
+
+    #[test]
+    fn test_last_seen_messages_wrap_around() {
+        let mut tracker = LastSeenMessagesTracker::default();
+        for i in 0..25 {
+            tracker.add_pending(signature(i));
+        }
+        let (last_seen, update) = tracker.generate_update();
+        assert_eq!(last_seen, (5..25).map(signature).collect::<Vec<_>>());
+        assert_eq!(update.messages, 25);
+        assert!((0..LAST_SEEN_MESSAGES_SIZE).all(|i| update.acknowledged.index(i)));
+    }
+
+    #[test]
+    fn test_duplicate_message_isnt_tracked() {
+        let mut tracker = LastSeenMessagesTracker::default();
+        assert!(tracker.add_pending(signature(1)));
+        assert!(!tracker.add_pending(signature(1)));
+        assert_eq!(tracker.offset(), 1);
+        assert!(tracker.add_pending(signature(2)));
+        assert!(tracker.add_pending(signature(1)));
+        assert_eq!(tracker.offset(), 3);
+    }
+
+    fn login_packet() -> ClientboundGamePacket {
+        ClientboundGamePacket::Login(ClientboundLogin {
+            player_id: 0,
+            hardcore: false,
+            levels: vec![],
+            max_players: 20,
+            chunk_radius: 8,
+            simulation_distance: 8,
+            reduced_debug_info: false,
+            show_death_screen: true,
+            do_limited_crafting: false,
+            common: CommonPlayerSpawnInfo {
+                dimension_type: 0,
+                dimension: ResourceLocation::new("minecraft:overworld"),
+                seed: 0,
+                game_type: GameMode::Survival,
+                previous_game_type: OptionalGameType(None),
+                is_debug: false,
+                is_flat: false,
+                last_death_location: None,
+                portal_cooldown: 0,
+                sea_level: 63,
+            },
+            enforces_secure_chat: true,
+        })
+    }
+
+    fn chat_packet(signature: Option<MessageSignature>) -> ClientboundGamePacket {
+        ClientboundGamePacket::PlayerChat(ClientboundPlayerChat {
+            sender: Uuid::nil(),
+            index: 0,
+            signature,
+            body: PackedSignedMessageBody {
+                content: "hello".to_owned(),
+                timestamp: 0,
+                salt: 0,
+                last_seen: PackedLastSeenMessages { entries: vec![] },
+            },
+            unsigned_content: None,
+            filter_mask: FilterMask::PassThrough,
+            chat_type: ChatTypeBound {
+                chat_type: ChatType::Chat,
+                name: FormattedText::default(),
+                target_name: None,
+            },
+        })
+    }
+
+    fn receive(ecs: &mut World, entity: Entity, packet: ClientboundGamePacket) {
+        ecs.send_event(PacketEvent {
+            entity,
+            packet: Arc::new(packet),
+        });
+    }
+
+    fn sent_packets(ecs: &mut World) -> Vec<ServerboundGamePacket> {
+        ecs.resource_mut::<Events<SendPacketEvent>>()
+            .drain()
+            .map(|e| e.packet)
+            .collect()
+    }
+
+    #[test]
+    fn test_acknowledge_received_messages() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<PacketEvent>>();
+        ecs.init_resource::<Events<SendPacketEvent>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(handle_chat_signing_packets);
+
+        let entity = ecs.spawn(Account::offline("bot")).id();
+        receive(&mut ecs, entity, login_packet());
+        schedule.run(&mut ecs);
+        // offline accounts can't sign messages, but they still have to acknowledge the
+        // ones that they receive
+        assert!(ecs.get::<ChatSigningSession>(entity).is_none());
+        assert!(ecs.get::<LastSeenMessagesTracker>(entity).is_some());
+        assert!(sent_packets(&mut ecs).is_empty());
+
+        // unsigned messages aren't tracked
+        receive(&mut ecs, entity, chat_packet(None));
+        schedule.run(&mut ecs);
+        assert_eq!(
+            ecs.get::<LastSeenMessagesTracker>(entity).unwrap().offset(),
+            0
+        );
+
+        for i in 0..MAX_UNACKNOWLEDGED_MESSAGES {
+            receive(&mut ecs, entity, chat_packet(Some(signature(i as u8))));
+        }
+        schedule.run(&mut ecs);
+        assert!(sent_packets(&mut ecs).is_empty());
+
+        receive(&mut ecs, entity, chat_packet(Some(signature(255))));
+        schedule.run(&mut ecs);
+        let sent = sent_packets(&mut ecs);
+        let [ServerboundGamePacket::ChatAck(ack)] = &sent[..] else {
+            panic!("expected a chat ack, got {sent:?}");
+        };
+        assert_eq!(ack.messages, MAX_UNACKNOWLEDGED_MESSAGES + 1);
+        assert_eq!(
+            ecs.get::<LastSeenMessagesTracker>(entity).unwrap().offset(),
+            0
+        );
+    }
+}
@@ -1,29 +1,55 @@
 //! Used for Minecraft's chunk batching introduced in 23w31a (1.20.2). It's used
 //! for making the server spread out how often it sends us chunk packets
 //! depending on our receiving speed.
+//!
+//! Chunks are decoded in the [`AsyncComputeTaskPool`] while the rest of the
+//! packets are handled, so lots of chunks arriving at once are decoded in
+//! parallel instead of one after another. The tasks are checked without
+//! blocking at the end of [`process_packet_events`] and in every update after
+//! that, and chunks are added to the world as soon as they're done, which
+//! sends a [`ChunkLoadedEvent`]. Packets that depend on a chunk that's still
+//! being decoded are kept until it's added, however many frames that takes.
+//!
+//! This also stores the light that the server sends for chunks, see
+//! [`azalea_world::light`].
+//!
+//! [`process_packet_events`]: crate::packet_handling::game::process_packet_events
 
 use std::{
+    collections::{HashMap, HashSet},
     io::Cursor,
     ops::Deref,
+    sync::{Arc, Weak},
     time::{Duration, Instant},
 };
 
-use azalea_core::position::ChunkPos;
+use azalea_buf::BufReadError;
+use azalea_core::{position::ChunkPos, tick::GameTick};
 use azalea_protocol::packets::game::{
     c_level_chunk_with_light::ClientboundLevelChunkWithLight,
+    c_light_update::ClientboundLightUpdatePacketData,
     s_chunk_batch_received::ServerboundChunkBatchReceived,
+    s_client_information::ServerboundClientInformation, ClientboundGamePacket,
+};
+use azalea_world::{
+    chunk_storage::{in_range_for_view_center_and_radius, PartialChunkStorage},
+    light::{ChunkLight, LightKind},
+    Chunk, Instance,
 };
 use bevy_app::{App, Plugin, Update};
-use bevy_ecs::prelude::*;
+use bevy_ecs::{prelude::*, system::SystemState};
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use parking_lot::RwLock;
 use simdnbt::owned::BaseNbt;
-use tracing::{error, trace};
+use tracing::{debug, error, trace, warn};
 
 use crate::{
     interact::handle_block_interact_event,
     inventory::InventorySet,
-    packet_handling::game::{handle_send_packet_event, SendPacketEvent},
+    packet_handling::game::{self, handle_send_packet_event, InstanceLoadedEvent, SendPacketEvent},
     respawn::perform_respawn,
-    InstanceHolder,
+    ClientInformation, InstanceHolder,
 };
 
 pub struct ChunkPlugin;
@@ -34,7 +60,9 @@ impl Plugin for ChunkPlugin {
             (
                 handle_chunk_batch_start_event,
                 handle_receive_chunk_events,
+                handle_decoded_chunks,
                 handle_chunk_batch_finished_event,
+                handle_request_chunk_resend_event,
             )
                 .chain()
                 .before(handle_send_packet_event)
@@ -42,18 +70,154 @@ impl Plugin for ChunkPlugin {
                 .before(handle_block_interact_event)
                 .before(perform_respawn),
         )
+        .add_systems(GameTick, update_chunk_resends)
         .add_event::<ReceiveChunkEvent>()
+        .add_event::<ChunkLoadedEvent>()
         .add_event::<ChunkBatchStartEvent>()
-        .add_event::<ChunkBatchFinishedEvent>();
+        .add_event::<ChunkBatchFinishedEvent>()
+        .add_event::<RequestChunkResendEvent>();
+    }
+}
+
+/// A plugin that keeps chunks in memory for a while after the server unloads
+/// them, so we still know what's there after we walk away. See
+/// [`azalea_world::chunk_cache`].
+///
+/// This isn't part of the default plugins, so you have to add it yourself.
+///
+/// ```no_run
+/// # use azalea_client::chunks::ChunkCachePlugin;
+/// # fn example(app: &mut bevy_app::App) {
+/// // keep up to 256 MiB of chunks
+/// app.add_plugins(ChunkCachePlugin::new(256 * 1024 * 1024));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ChunkCachePlugin {
+    /// About how many bytes of chunks are kept for each instance.
+    pub max_memory: usize,
+}
+
+impl ChunkCachePlugin {
+    pub fn new(max_memory: usize) -> Self {
+        Self { max_memory }
+    }
+}
+
+impl Plugin for ChunkCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChunkCacheMaxMemory(self.max_memory))
+            .add_systems(Update, set_chunk_cache_max_memory);
     }
 }
 
+/// The memory budget for the chunk cache of every instance, which is set by
+/// the [`ChunkCachePlugin`].
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ChunkCacheMaxMemory(pub usize);
+
+pub fn set_chunk_cache_max_memory(
+    mut events: EventReader<InstanceLoadedEvent>,
+    max_memory: Res<ChunkCacheMaxMemory>,
+) {
+    for event in events.read() {
+        if let Some(instance) = event.instance.upgrade() {
+            instance.write().chunks.cache.set_max_memory(max_memory.0);
+        }
+    }
+}
+
+/// The smallest view distance that vanilla servers allow.
+pub const MIN_VIEW_DISTANCE: u8 = 2;
+
+/// Load a chunk for a client as if the server sent it.
+///
+/// Chunks that the server sends don't go through this event, since they have
+/// to start decoding before the packets after them are handled.
 #[derive(Event)]
 pub struct ReceiveChunkEvent {
     pub entity: Entity,
     pub packet: ClientboundLevelChunkWithLight,
 }
 
+/// An event sent when a chunk that the server sent us was decoded and added to
+/// the world.
+#[derive(Event, Debug, Clone)]
+pub struct ChunkLoadedEvent {
+    pub entity: Entity,
+    pub pos: ChunkPos,
+}
+
+/// The chunks that are being decoded in the background for a client.
+#[derive(Component, Default)]
+pub struct PendingChunks {
+    chunks: HashMap<ChunkPos, PendingChunk>,
+}
+
+struct PendingChunk {
+    task: Task<Result<Chunk, BufReadError>>,
+    /// The instance that the chunk is for, so it's not added to a different
+    /// one if we changed dimensions while it was being decoded.
+    instance: Weak<RwLock<Instance>>,
+    /// Packets that the server sent for the chunk while it was being decoded,
+    /// in the order that they were received. They're handled after the chunk
+    /// is added to the world.
+    deferred_packets: Vec<Arc<ClientboundGamePacket>>,
+}
+
+impl PendingChunks {
+    /// Whether the chunk at the given position is still being decoded.
+    pub fn is_pending(&self, pos: &ChunkPos) -> bool {
+        self.chunks.contains_key(pos)
+    }
+
+    /// The number of chunks that are being decoded.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Remember a packet that depends on a chunk that's still being decoded,
+    /// so it can be handled when the chunk is added to the world.
+    ///
+    /// Returns `false` if the packet doesn't depend on a chunk that's being
+    /// decoded, which means that it should be handled now.
+    pub fn defer_packet(&mut self, packet: &Arc<ClientboundGamePacket>) -> bool {
+        let Some(pos) = packet_chunk_pos(packet) else {
+            return false;
+        };
+        match self.chunks.get_mut(&pos) {
+            Some(pending_chunk) => {
+                pending_chunk.deferred_packets.push(packet.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop decoding the chunk, like if the server told us to forget it.
+    pub fn cancel(&mut self, pos: &ChunkPos) {
+        self.chunks.remove(pos);
+    }
+}
+
+/// The chunk that a packet changes, if it has to wait for the chunk to be in
+/// the world before it can be handled.
+pub fn packet_chunk_pos(packet: &ClientboundGamePacket) -> Option<ChunkPos> {
+    match packet {
+        ClientboundGamePacket::BlockUpdate(p) => Some(ChunkPos::from(&p.pos)),
+        ClientboundGamePacket::SectionBlocksUpdate(p) => Some(ChunkPos::from(p.section_pos)),
+        ClientboundGamePacket::LightUpdate(p) => Some(ChunkPos::new(p.x, p.z)),
+        ClientboundGamePacket::BlockEntityData(p) => Some(ChunkPos::from(&p.pos)),
+        ClientboundGamePacket::BlockEvent(p) => Some(ChunkPos::from(&p.pos)),
+        ClientboundGamePacket::BlockDestruction(p) => Some(ChunkPos::from(&p.pos)),
+        _ => None,
+    }
+}
+
 #[derive(Component, Clone, Debug)]
 pub struct ChunkBatchInfo {
     pub start_time: Instant,
@@ -72,51 +236,196 @@ pub struct ChunkBatchFinishedEvent {
 }
 
 pub fn handle_receive_chunk_events(
-    mut events: EventReader<ReceiveChunkEvent>,
-    mut query: Query<&mut InstanceHolder>,
+    mut events: ResMut<Events<ReceiveChunkEvent>>,
+    mut query: Query<(&InstanceHolder, &mut PendingChunks)>,
 ) {
-    for event in events.read() {
-        let pos = ChunkPos::new(event.packet.x, event.packet.z);
-
-        let local_player = query.get_mut(event.entity).unwrap();
-
-        let mut instance = local_player.instance.write();
-        let mut partial_instance = local_player.partial_instance.write();
-
-        // OPTIMIZATION: if we already know about the chunk from the shared world (and
-        // not ourselves), then we don't need to parse it again. This is only used when
-        // we have a shared world, since we check that the chunk isn't currently owned
-        // by this client.
-        let shared_chunk = instance.chunks.get(&pos);
-        let this_client_has_chunk = partial_instance.chunks.limited_get(&pos).is_some();
-
-        if !this_client_has_chunk {
-            if let Some(shared_chunk) = shared_chunk {
-                trace!("Skipping parsing chunk {pos:?} because we already know about it");
-                partial_instance
-                    .chunks
-                    .limited_set(&pos, Some(shared_chunk));
-                continue;
-            }
+    // the events are drained so the packets can be moved into the decoding tasks
+    for event in events.drain() {
+        if let Ok((local_player, mut pending_chunks)) = query.get_mut(event.entity) {
+            receive_chunk(
+                local_player,
+                &mut pending_chunks,
+                Arc::new(ClientboundGamePacket::LevelChunkWithLight(event.packet)),
+            );
         }
+    }
+}
 
-        let heightmaps_nbt = &event.packet.chunk_data.heightmaps;
+/// Start decoding a chunk that the server sent us, or use the one from the
+/// shared world if another client already has it.
+///
+/// The packet is moved into the task that decodes it so the chunk data doesn't
+/// have to be copied. Packets other than
+/// [`ClientboundGamePacket::LevelChunkWithLight`] are ignored.
+pub fn receive_chunk(
+    local_player: &InstanceHolder,
+    pending_chunks: &mut PendingChunks,
+    packet: Arc<ClientboundGamePacket>,
+) {
+    let ClientboundGamePacket::LevelChunkWithLight(p) = packet.as_ref() else {
+        return;
+    };
+    let pos = ChunkPos::new(p.x, p.z);
+
+    let instance = local_player.instance.read();
+    let mut partial_instance = local_player.partial_instance.write();
+
+    // OPTIMIZATION: if we already know about the chunk from the shared world (and
+    // not ourselves), then we don't need to parse it again. This is only used when
+    // we have a shared world, since we check that the chunk isn't currently owned
+    // by this client.
+    let shared_chunk = instance.chunks.get(&pos);
+    let this_client_has_chunk = partial_instance.chunks.limited_get(&pos).is_some();
+
+    if !this_client_has_chunk {
+        if let Some(shared_chunk) = shared_chunk {
+            trace!("Skipping parsing chunk {pos:?} because we already know about it");
+            partial_instance
+                .chunks
+                .limited_set(&pos, Some(shared_chunk));
+            // the server sent the chunk again, so any older version that's
+            // still being decoded is out of date
+            pending_chunks.cancel(&pos);
+            return;
+        }
+    }
+
+    if !partial_instance.chunks.in_range(&pos) {
+        debug!("Ignoring chunk since it's not in the view range: {pos:?}");
+        return;
+    }
+
+    let height = instance.chunks.height;
+    let min_y = instance.chunks.min_y;
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let ClientboundGamePacket::LevelChunkWithLight(p) = packet.as_ref() else {
+            unreachable!("the packet was checked before the task was spawned");
+        };
         // necessary to make the unwrap_or work
         let empty_nbt = BaseNbt::default();
-        let heightmaps = heightmaps_nbt.unwrap_or(&empty_nbt).deref();
-
-        if let Err(e) = partial_instance.chunks.replace_with_packet_data(
-            &pos,
-            &mut Cursor::new(&event.packet.chunk_data.data),
-            heightmaps,
-            &mut instance.chunks,
-        ) {
-            error!(
-                "Couldn't set chunk data: {e}. World height: {}",
-                instance.chunks.height
+        let heightmaps = p.chunk_data.heightmaps.unwrap_or(&empty_nbt);
+        let mut chunk = Chunk::read_with_dimension_height(
+            &mut Cursor::new(&p.chunk_data.data),
+            height,
+            min_y,
+            heightmaps.deref(),
+        )?;
+        update_light(&mut chunk.light, &p.light_data);
+        Ok::<_, BufReadError>(chunk)
+    });
+    // replacing a chunk that's still being decoded drops (and cancels) the old
+    // task, and the packets for it are out of date too
+    pending_chunks.chunks.insert(
+        pos,
+        PendingChunk {
+            task,
+            instance: Arc::downgrade(&local_player.instance),
+            deferred_packets: Vec::new(),
+        },
+    );
+}
+
+/// Add the chunks that finished decoding to the world and handle the packets
+/// that were waiting for them.
+pub fn handle_decoded_chunks(ecs: &mut World) {
+    let deferred_packets = load_decoded_chunks(ecs);
+    game::process_packets(ecs, deferred_packets);
+}
+
+/// Add the chunks that finished decoding to the world, without waiting for the
+/// ones that are still being decoded.
+///
+/// This returns the packets that were deferred until the chunks were loaded,
+/// which should be handled right after. Chunks that aren't done yet keep their
+/// deferred packets and are checked again the next time this is called.
+pub fn load_decoded_chunks(ecs: &mut World) -> Vec<(Entity, Arc<ClientboundGamePacket>)> {
+    let mut system_state: SystemState<(
+        Query<(Entity, &InstanceHolder, &mut PendingChunks)>,
+        EventWriter<ChunkLoadedEvent>,
+    )> = SystemState::new(ecs);
+    let (mut query, mut chunk_loaded_events) = system_state.get_mut(ecs);
+
+    let mut deferred_packets = Vec::new();
+    for (entity, local_player, mut pending_chunks) in &mut query {
+        if pending_chunks.is_empty() {
+            continue;
+        }
+
+        let mut finished = Vec::new();
+        for (pos, pending_chunk) in pending_chunks.chunks.iter_mut() {
+            if let Some(result) = future::block_on(future::poll_once(&mut pending_chunk.task)) {
+                finished.push((*pos, result));
+            }
+        }
+
+        for (pos, result) in finished {
+            // a finished task can't be polled again, so it has to be removed now
+            let pending_chunk = pending_chunks
+                .chunks
+                .remove(&pos)
+                .expect("the chunk was just in the map");
+
+            let is_same_instance = pending_chunk
+                .instance
+                .upgrade()
+                .is_some_and(|chunk_instance| Arc::ptr_eq(&chunk_instance, &local_player.instance));
+            if !is_same_instance {
+                trace!("Dropping chunk {pos:?} since it was for a different instance");
+                continue;
+            }
+
+            let mut instance = local_player.instance.write();
+            let mut partial_instance = local_player.partial_instance.write();
+
+            let chunk = match result {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    error!(
+                        "Couldn't set chunk data: {e}. World height: {}",
+                        instance.chunks.height
+                    );
+                    continue;
+                }
+            };
+            // we might've moved while the chunk was being decoded
+            if !partial_instance.chunks.in_range(&pos) {
+                debug!("Ignoring chunk since it's not in the view range anymore: {pos:?}");
+                continue;
+            }
+
+            partial_instance
+                .chunks
+                .set(&pos, Some(chunk), &mut instance.chunks);
+            trace!("Loaded chunk {pos:?}");
+
+            chunk_loaded_events.send(ChunkLoadedEvent { entity, pos });
+            deferred_packets.extend(
+                pending_chunk
+                    .deferred_packets
+                    .into_iter()
+                    .map(|packet| (entity, packet)),
             );
         }
     }
+
+    deferred_packets
+}
+
+/// Update the light in a chunk from a light update or chunk packet.
+pub fn update_light(light: &mut ChunkLight, light_data: &ClientboundLightUpdatePacketData) {
+    light.update(
+        LightKind::Sky,
+        &light_data.sky_y_mask,
+        &light_data.empty_sky_y_mask,
+        &light_data.sky_updates,
+    );
+    light.update(
+        LightKind::Block,
+        &light_data.block_y_mask,
+        &light_data.empty_block_y_mask,
+        &light_data.block_updates,
+    );
 }
 
 impl ChunkBatchInfo {
@@ -223,3 +532,361 @@ impl Default for ChunkBatchInfo {
         }
     }
 }
+
+/// Ask the server to send some chunks again, which is useful if we're missing
+/// chunks that we should have.
+///
+/// There's no packet for this, so it works by telling the server that our view
+/// distance is [`MIN_VIEW_DISTANCE`], waiting for it to forget the chunks that
+/// are further away than that, and then setting the view distance back so it
+/// sends them again. See [`ChunkResend`].
+///
+/// The distance is measured from the chunk cache center that the server sent
+/// us in `ClientboundSetChunkCacheCenter`, and chunks that are within
+/// [`MIN_VIEW_DISTANCE`] of it can't be resent this way, so they're ignored.
+#[derive(Event, Debug, Clone)]
+pub struct RequestChunkResendEvent {
+    pub entity: Entity,
+    /// The chunks that we're missing.
+    pub chunks: Vec<ChunkPos>,
+}
+
+/// A chunk resend that's in progress for a client, which is removed once all
+/// of the chunks arrived or after [`CHUNK_RESEND_TIMEOUT_TICKS`].
+///
+/// You can check for this component to avoid requesting a resend while one is
+/// already happening.
+#[derive(Component, Clone, Debug)]
+pub struct ChunkResend {
+    /// The chunks that were requested and haven't been received yet.
+    pub missing: HashSet<ChunkPos>,
+    pub stage: ChunkResendStage,
+    /// The number of game ticks since the current stage started.
+    pub ticks: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkResendStage {
+    /// We told the server that our view distance is [`MIN_VIEW_DISTANCE`] and
+    /// are waiting for it to forget the chunks that are further away.
+    LoweredViewDistance,
+    /// We set our view distance back and are waiting for the chunks to be sent
+    /// again.
+    Restored,
+}
+
+/// How long to wait for each stage of a [`ChunkResend`] before giving up on it.
+pub const CHUNK_RESEND_TIMEOUT_TICKS: u32 = 20 * 10;
+
+pub fn handle_request_chunk_resend_event(
+    mut commands: Commands,
+    mut events: EventReader<RequestChunkResendEvent>,
+    mut query: Query<(
+        &ClientInformation,
+        &InstanceHolder,
+        Option<&mut ChunkResend>,
+    )>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok((client_information, instance_holder, chunk_resend)) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
+        if client_information.view_distance <= MIN_VIEW_DISTANCE {
+            debug!("Can't ask the server to resend chunks since our view distance is too low");
+            continue;
+        }
+        let view_center = instance_holder.partial_instance.read().chunks.view_center();
+        let resendable = event
+            .chunks
+            .iter()
+            .filter(|pos| {
+                !in_range_for_view_center_and_radius(pos, view_center, MIN_VIEW_DISTANCE.into())
+            })
+            .copied();
+
+        if let Some(mut chunk_resend) = chunk_resend {
+            // a resend is already happening, so the chunks will be sent with it if they
+            // haven't been forgotten yet
+            if chunk_resend.stage == ChunkResendStage::LoweredViewDistance {
+                chunk_resend.missing.extend(resendable);
+            }
+            continue;
+        }
+        let missing = resendable.collect::<HashSet<_>>();
+        if missing.is_empty() {
+            debug!("The missing chunks are too close to the view center to be resent");
+            continue;
+        }
+
+        debug!(
+            "Asking the server to resend {} chunks for {:?}",
+            missing.len(),
+            event.entity
+        );
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundClientInformation {
+                information: ClientInformation {
+                    view_distance: MIN_VIEW_DISTANCE,
+                    ..client_information.clone()
+                },
+            },
+        ));
+        commands.entity(event.entity).insert(ChunkResend {
+            missing,
+            stage: ChunkResendStage::LoweredViewDistance,
+            ticks: 0,
+        });
+    }
+}
+
+/// Set the view distance back once the server forgot the chunks, and stop
+/// tracking the resend once the chunks arrive.
+pub fn update_chunk_resends(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut ChunkResend,
+        &ClientInformation,
+        &InstanceHolder,
+    )>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, mut chunk_resend, client_information, instance_holder) in &mut query {
+        chunk_resend.ticks += 1;
+        let partial_instance = instance_holder.partial_instance.read();
+
+        match chunk_resend.stage {
+            ChunkResendStage::LoweredViewDistance => {
+                let forgotten =
+                    !has_chunks_further_than(&partial_instance.chunks, MIN_VIEW_DISTANCE.into());
+                if !forgotten && chunk_resend.ticks < CHUNK_RESEND_TIMEOUT_TICKS {
+                    continue;
+                }
+                // the client information might've been changed since we lowered it, so the
+                // current one is sent instead of remembering the old one
+                send_packet_events.send(SendPacketEvent::new(
+                    entity,
+                    ServerboundClientInformation {
+                        information: client_information.clone(),
+                    },
+                ));
+                chunk_resend.stage = ChunkResendStage::Restored;
+                chunk_resend.ticks = 0;
+            }
+            ChunkResendStage::Restored => {
+                chunk_resend
+                    .missing
+                    .retain(|pos| partial_instance.chunks.limited_get(pos).is_none());
+                if chunk_resend.missing.is_empty() {
+                    debug!("Got all of the chunks that we asked the server to resend");
+                    commands.entity(entity).remove::<ChunkResend>();
+                } else if chunk_resend.ticks >= CHUNK_RESEND_TIMEOUT_TICKS {
+                    warn!(
+                        "The server didn't resend {} chunks that we asked for",
+                        chunk_resend.missing.len()
+                    );
+                    commands.entity(entity).remove::<ChunkResend>();
+                }
+            }
+        }
+    }
+}
+
+/// Whether any chunks that are further than `distance` from the view center are
+/// loaded.
+fn has_chunks_further_than(chunks: &PartialChunkStorage, distance: u32) -> bool {
+    let center = chunks.view_center();
+    let radius = (chunks.view_range() / 2) as i32;
+    (-radius..=radius)
+        .flat_map(|x| (-radius..=radius).map(move |z| ChunkPos::new(center.x + x, center.z + z)))
+        .filter(|pos| !in_range_for_view_center_and_radius(pos, center, distance))
+        .any(|pos| chunks.limited_get(&pos).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_block::BlockState;
+    use azalea_core::position::BlockPos;
+    use azalea_protocol::packets::game::{
+        c_block_update::ClientboundBlockUpdate, ServerboundGamePacket,
+    };
+    use azalea_world::ChunkStorage;
+    use bevy_ecs::event::Events;
+    use bevy_tasks::TaskPool;
+
+    use super::*;
+
+    fn sent_view_distances(ecs: &mut World) -> Vec<u8> {
+        ecs.resource_mut::<Events<SendPacketEvent>>()
+            .drain()
+            .filter_map(|event| match event.packet {
+                ServerboundGamePacket::ClientInformation(p) => Some(p.information.view_distance),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_resend() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<RequestChunkResendEvent>>();
+        ecs.init_resource::<Events<SendPacketEvent>>();
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+        let entity = ecs.spawn(ClientInformation::default()).id();
+        let instance_holder = InstanceHolder::new(entity, instance.clone());
+        let partial_instance = instance_holder.partial_instance.clone();
+        ecs.entity_mut(entity).insert(instance_holder);
+
+        // a chunk that the server will make us forget when we lower our view distance
+        partial_instance.write().chunks.set(
+            &ChunkPos::new(4, 0),
+            Some(Chunk::default()),
+            &mut instance.write().chunks,
+        );
+
+        let mut request_schedule = Schedule::default();
+        request_schedule.add_systems(handle_request_chunk_resend_event);
+        let mut update_schedule = Schedule::default();
+        update_schedule.add_systems(update_chunk_resends);
+
+        ecs.send_event(RequestChunkResendEvent {
+            entity,
+            chunks: vec![ChunkPos::new(1, 0), ChunkPos::new(5, 0)],
+        });
+        request_schedule.run(&mut ecs);
+        assert_eq!(sent_view_distances(&mut ecs), [MIN_VIEW_DISTANCE]);
+        let chunk_resend = ecs.get::<ChunkResend>(entity).unwrap();
+        // the chunk next to the view center won't be resent, so we don't wait for it
+        assert_eq!(chunk_resend.missing, HashSet::from([ChunkPos::new(5, 0)]));
+
+        // the view distance isn't restored until the server forgets the chunk
+        update_schedule.run(&mut ecs);
+        assert!(sent_view_distances(&mut ecs).is_empty());
+        assert_eq!(
+            ecs.get::<ChunkResend>(entity).unwrap().stage,
+            ChunkResendStage::LoweredViewDistance
+        );
+
+        let forgotten = partial_instance
+            .write()
+            .chunks
+            .limited_get_mut(&ChunkPos::new(4, 0))
+            .and_then(Option::take);
+        assert!(forgotten.is_some());
+        update_schedule.run(&mut ecs);
+        assert_eq!(sent_view_distances(&mut ecs), [8]);
+        assert_eq!(
+            ecs.get::<ChunkResend>(entity).unwrap().stage,
+            ChunkResendStage::Restored
+        );
+
+        // requesting again while a resend is happening doesn't send anything
+        ecs.send_event(RequestChunkResendEvent {
+            entity,
+            chunks: vec![ChunkPos::new(5, 0)],
+        });
+        request_schedule.run(&mut ecs);
+        assert!(sent_view_distances(&mut ecs).is_empty());
+
+        partial_instance.write().chunks.set(
+            &ChunkPos::new(5, 0),
+            Some(Chunk::default()),
+            &mut instance.write().chunks,
+        );
+        update_schedule.run(&mut ecs);
+        assert!(ecs.get::<ChunkResend>(entity).is_none());
+    }
+
+    #[test]
+    fn test_chunk_resend_ignores_nearby_chunks() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<RequestChunkResendEvent>>();
+        ecs.init_resource::<Events<SendPacketEvent>>();
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+        let entity = ecs.spawn(ClientInformation::default()).id();
+        ecs.entity_mut(entity)
+            .insert(InstanceHolder::new(entity, instance));
+
+        ecs.send_event(RequestChunkResendEvent {
+            entity,
+            chunks: vec![ChunkPos::new(0, 0), ChunkPos::new(-2, 2)],
+        });
+        Schedule::default()
+            .add_systems(handle_request_chunk_resend_event)
+            .run(&mut ecs);
+        assert!(sent_view_distances(&mut ecs).is_empty());
+        assert!(ecs.get::<ChunkResend>(entity).is_none());
+    }
+
+    fn block_update_at(x: i32, z: i32) -> Arc<ClientboundGamePacket> {
+        Arc::new(ClientboundGamePacket::BlockUpdate(ClientboundBlockUpdate {
+            pos: BlockPos::new(x, 0, z),
+            block_state: BlockState::AIR,
+        }))
+    }
+
+    #[test]
+    fn test_unfinished_chunks_keep_deferred_packets() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ChunkLoadedEvent>>();
+        AsyncComputeTaskPool::get_or_init(TaskPool::default);
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+        let entity = ecs.spawn(PendingChunks::default()).id();
+        ecs.entity_mut(entity)
+            .insert(InstanceHolder::new(entity, instance.clone()));
+
+        let slow_pos = ChunkPos::new(0, 0);
+        let fast_pos = ChunkPos::new(1, 0);
+        let slow_packet = block_update_at(0, 0);
+        let fast_packet = block_update_at(16, 0);
+        {
+            let mut pending_chunks = ecs.get_mut::<PendingChunks>(entity).unwrap();
+            pending_chunks.chunks.insert(
+                slow_pos,
+                PendingChunk {
+                    task: AsyncComputeTaskPool::get().spawn(future::pending()),
+                    instance: Arc::downgrade(&instance),
+                    deferred_packets: Vec::new(),
+                },
+            );
+            pending_chunks.chunks.insert(
+                fast_pos,
+                PendingChunk {
+                    task: AsyncComputeTaskPool::get().spawn(async { Ok(Chunk::default()) }),
+                    instance: Arc::downgrade(&instance),
+                    deferred_packets: Vec::new(),
+                },
+            );
+            assert!(pending_chunks.defer_packet(&slow_packet));
+            assert!(pending_chunks.defer_packet(&fast_packet));
+        }
+
+        // the fast chunk might be decoded on another thread, so it can take a few
+        // frames
+        let mut deferred_packets = Vec::new();
+        for _ in 0..1000 {
+            deferred_packets = load_decoded_chunks(&mut ecs);
+            if !deferred_packets.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(deferred_packets.len(), 1);
+        assert_eq!(deferred_packets[0].0, entity);
+        assert!(Arc::ptr_eq(&deferred_packets[0].1, &fast_packet));
+        assert!(instance.read().chunks.get(&fast_pos).is_some());
+        assert_eq!(ecs.resource::<Events<ChunkLoadedEvent>>().len(), 1);
+
+        // the slow chunk is still being decoded, so packets for it keep waiting
+        assert!(instance.read().chunks.get(&slow_pos).is_none());
+        let mut pending_chunks = ecs.get_mut::<PendingChunks>(entity).unwrap();
+        assert!(pending_chunks.is_pending(&slow_pos));
+        assert!(!pending_chunks.is_pending(&fast_pos));
+        assert!(pending_chunks.defer_packet(&block_update_at(1, 1)));
+        assert_eq!(pending_chunks.chunks[&slow_pos].deferred_packets.len(), 2);
+        assert!(load_decoded_chunks(&mut ecs).is_empty());
+    }
+}
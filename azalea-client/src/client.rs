@@ -8,20 +8,21 @@ use std::{
 };
 
 use azalea_auth::{game_profile::GameProfile, sessionserver::ClientSessionServerError};
+use azalea_buf::AzaleaWriteVar;
 use azalea_chat::FormattedText;
 use azalea_core::{
     data_registry::ResolvableDataRegistry, position::Vec3, resource_location::ResourceLocation,
     tick::GameTick,
 };
 use azalea_entity::{
-    indexing::{EntityIdIndex, EntityUuidIndex},
+    indexing::{EntityIdIndex, LocalPlayerUuidIndex},
     metadata::Health,
     EntityPlugin, EntityUpdateSet, EyeHeight, LocalEntity, Position,
 };
 use azalea_physics::PhysicsPlugin;
 use azalea_protocol::{
     common::client_information::ClientInformation,
-    connect::{Connection, ConnectionError, Proxy},
+    connect::{Connection, ConnectionError, Proxy, SocketOptions},
     packets::{
         self,
         config::{ClientboundConfigPacket, ServerboundConfigPacket},
@@ -33,6 +34,7 @@ use azalea_protocol::{
         login::{
             s_hello::ServerboundHello, s_key::ServerboundKey,
             s_login_acknowledged::ServerboundLoginAcknowledged, ClientboundLoginPacket,
+            ServerboundLoginPacket,
         },
         ClientIntention, ConnectionProtocol, Packet, PROTOCOL_VERSION,
     },
@@ -54,36 +56,58 @@ use parking_lot::{Mutex, RwLock};
 use simdnbt::owned::NbtCompound;
 use thiserror::Error;
 use tokio::{
+    io::AsyncWriteExt,
     sync::{broadcast, mpsc},
     time,
 };
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use crate::{
+    action_queue::ActionQueuePlugin,
     attack::{self, AttackPlugin},
-    chat::ChatPlugin,
-    chunks::{ChunkBatchInfo, ChunkPlugin},
+    block_effects::BlockEffectsPlugin,
+    bungeecord_forwarding::BungeeCordForwarding,
+    chat::{ChatPlugin, ChatQueue},
+    chat_history::ChatHistoryPlugin,
+    chat_signing::ChatSigningPlugin,
+    chunks::{ChunkBatchInfo, ChunkPlugin, PendingChunks},
+    component_watch::ComponentWatchPlugin,
     configuration::ConfigurationPlugin,
-    disconnect::{DisconnectEvent, DisconnectPlugin},
+    disconnect::{DisconnectEvent, DisconnectPlugin, ManualDisconnect},
+    elytra::ElytraPlugin,
+    ender_chest::EnderChestPlugin,
+    error::ClientError,
     events::{Event, EventPlugin, LocalPlayerEvents},
     interact::{CurrentSequenceNumber, InteractPlugin},
     inventory::{Inventory, InventoryPlugin},
     local_player::{
-        death_event, GameProfileComponent, Hunger, InstanceHolder, PermissionLevel,
-        PlayerAbilities, TabList,
+        death_event, Experience, GameProfileComponent, GameTime, Hunger, InstanceHolder,
+        PermissionLevel, Ping, PlayerAbilities, ShareInstances, TabList, TabListHeader, Weather,
     },
+    maps::Maps,
     mining::{self, MinePlugin},
     movement::{LastSentLookDirection, PhysicsState, PlayerMovePlugin},
     packet_handling::{
         login::{self, LoginSendPacketQueue},
         PacketHandlerPlugin,
     },
+    packet_logger::JoinPacketLog,
+    packet_middleware::DelayedPacketsPlugin,
     player::retroactively_add_game_profile_component,
-    raw_connection::RawConnection,
+    plugin_channels::PluginChannelsPlugin,
+    proxy_protocol::ProxyProtocolHeader,
+    raw_connection::{PacketFlow, RawConnection},
     respawn::RespawnPlugin,
     send_client_end::TickEndPlugin,
+    server_commands::ServerCommandsPlugin,
+    session_stats::{SessionStats, SessionStatsPlugin},
+    spectator::SpectatorPlugin,
     task_pool::TaskPoolPlugin,
+    tps::TpsPlugin,
+    transfer::{Cookies, PendingTransfer, TransferPlugin},
+    vehicle::VehiclePlugin,
+    world_audit::WorldAuditPlugin,
     Account, PlayerInfo,
 };
 
@@ -138,6 +162,10 @@ pub enum JoinError {
     Auth(#[from] azalea_auth::AuthError),
     #[error("Disconnected: {reason}")]
     Disconnect { reason: FormattedText },
+    #[error("Couldn't serialize the BungeeCord forwarding properties: {0}")]
+    BungeeCordForwarding(serde_json::Error),
+    #[error("The client doesn't have an Account to join with")]
+    MissingAccount,
 }
 
 pub struct StartClientOpts<'a> {
@@ -146,9 +174,23 @@ pub struct StartClientOpts<'a> {
     pub address: &'a ServerAddress,
     pub resolved_address: &'a SocketAddr,
     pub proxy: Option<Proxy>,
+    /// The options for the TCP socket, like the local address to connect
+    /// from. If there's a proxy, these are used for connecting to it.
+    pub socket_options: SocketOptions,
+    /// Pretend to be coming from a BungeeCord proxy by adding our info to the
+    /// hostname in the handshake.
+    pub bungeecord_forwarding: Option<BungeeCordForwarding>,
+    /// Send a PROXY protocol header as soon as we connect, so we can join
+    /// servers that are behind HAProxy without going through it.
+    pub proxy_protocol: Option<ProxyProtocolHeader>,
     pub run_schedule_sender: mpsc::UnboundedSender<()>,
 }
 
+/// The [`SocketOptions`] that a client connected with, so they can be used
+/// again when it's transferred to another server.
+#[derive(Component, Clone, Debug, Deref)]
+pub struct ClientSocketOptions(pub SocketOptions);
+
 impl<'a> StartClientOpts<'a> {
     pub fn new(
         account: &'a Account,
@@ -169,6 +211,9 @@ impl<'a> StartClientOpts<'a> {
             address,
             resolved_address,
             proxy: None,
+            socket_options: SocketOptions::default(),
+            bungeecord_forwarding: None,
+            proxy_protocol: None,
             run_schedule_sender,
         }
     }
@@ -177,6 +222,21 @@ impl<'a> StartClientOpts<'a> {
         self.proxy = Some(proxy);
         self
     }
+
+    pub fn socket_options(mut self, socket_options: SocketOptions) -> Self {
+        self.socket_options = socket_options;
+        self
+    }
+
+    pub fn bungeecord_forwarding(mut self, forwarding: BungeeCordForwarding) -> Self {
+        self.bungeecord_forwarding = Some(forwarding);
+        self
+    }
+
+    pub fn proxy_protocol(mut self, header: ProxyProtocolHeader) -> Self {
+        self.proxy_protocol = Some(header);
+        self
+    }
 }
 
 impl Client {
@@ -228,7 +288,10 @@ impl Client {
         let address: ServerAddress = address.try_into().map_err(|_| JoinError::InvalidAddress)?;
         let resolved_address = resolver::resolve_address(&address).await?;
 
-        Self::start_client(StartClientOpts::new(account, &address, &resolved_address)).await
+        let (client, rx) =
+            Self::start_client(StartClientOpts::new(account, &address, &resolved_address)).await?;
+        crate::transfer::follow_transfers(&client, None);
+        Ok((client, rx))
     }
 
     pub async fn join_with_proxy(
@@ -239,8 +302,12 @@ impl Client {
         let address: ServerAddress = address.try_into().map_err(|_| JoinError::InvalidAddress)?;
         let resolved_address = resolver::resolve_address(&address).await?;
 
-        Self::start_client(StartClientOpts::new(account, &address, &resolved_address).proxy(proxy))
-            .await
+        let (client, rx) = Self::start_client(
+            StartClientOpts::new(account, &address, &resolved_address).proxy(proxy.clone()),
+        )
+        .await?;
+        crate::transfer::follow_transfers(&client, Some(proxy));
+        Ok((client, rx))
     }
 
     /// Create a [`Client`] when you already have the ECS made with
@@ -252,15 +319,25 @@ impl Client {
             address,
             resolved_address,
             proxy,
+            socket_options,
+            bungeecord_forwarding,
+            proxy_protocol,
             run_schedule_sender,
         }: StartClientOpts<'_>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<Event>), JoinError> {
+        let mut account = account.to_owned();
+        if account.access_token.is_some() {
+            if let Err(e) = account.request_certs_if_needed().await {
+                warn!("Couldn't get certificates for chat signing: {e}");
+            }
+        }
+
         // check if an entity with our uuid already exists in the ecs and if so then
         // just use that
         let entity = {
             let mut ecs = ecs_lock.lock();
 
-            let entity_uuid_index = ecs.resource::<EntityUuidIndex>();
+            let entity_uuid_index = ecs.resource::<LocalPlayerUuidIndex>();
             let uuid = account.uuid_or_offline();
             let entity = if let Some(entity) = entity_uuid_index.get(&account.uuid_or_offline()) {
                 debug!("Reusing entity {entity:?} for client");
@@ -269,24 +346,40 @@ impl Client {
                 let entity = ecs.spawn_empty().id();
                 debug!("Created new entity {entity:?} for client");
                 // add to the uuid index
-                let mut entity_uuid_index = ecs.resource_mut::<EntityUuidIndex>();
+                let mut entity_uuid_index = ecs.resource_mut::<LocalPlayerUuidIndex>();
                 entity_uuid_index.insert(uuid, entity);
                 entity
             };
 
             // add the Account to the entity now so plugins can access it earlier
-            ecs.entity_mut(entity).insert(account.to_owned());
+            ecs.entity_mut(entity)
+                .insert((account.clone(), ClientSocketOptions(socket_options.clone())))
+                .remove::<ManualDisconnect>();
 
             entity
         };
 
-        let conn = if let Some(proxy) = proxy {
-            Connection::new_with_proxy(resolved_address, proxy).await?
-        } else {
-            Connection::new(resolved_address).await?
+        let mut conn =
+            Connection::new_with_options(resolved_address, proxy, &socket_options).await?;
+        if let Some(header) = &proxy_protocol {
+            // this has to be sent before anything else
+            conn.writer
+                .raw
+                .write_stream
+                .write_all(&header.encode(*resolved_address))
+                .await?;
+        }
+        let address = match &bungeecord_forwarding {
+            Some(forwarding) => ServerAddress {
+                host: forwarding
+                    .hostname(&address.host, account.uuid_or_offline())
+                    .map_err(JoinError::BungeeCordForwarding)?,
+                port: address.port,
+            },
+            None => address.clone(),
         };
         let (conn, game_profile) =
-            Self::handshake(ecs_lock.clone(), entity, conn, account, address).await?;
+            Self::handshake(ecs_lock.clone(), entity, conn, &account, &address).await?;
 
         // note that we send the proper packets in
         // crate::configuration::handle_in_configuration_state
@@ -355,15 +448,40 @@ impl Client {
         ),
         JoinError,
     > {
+        // cookies are only kept if we're being transferred from another server
+        let (is_transfer, packet_log) = {
+            let mut ecs = ecs_lock.lock();
+            let mut entity_mut = ecs.entity_mut(entity);
+            let is_transfer = entity_mut.take::<PendingTransfer>().is_some();
+            if !is_transfer || !entity_mut.contains::<Cookies>() {
+                entity_mut.insert(Cookies::default());
+            }
+            (is_transfer, JoinPacketLog::new(&mut ecs, entity))
+        };
+
         // handshake
-        conn.write(ServerboundIntention {
+        let intention = ServerboundIntention {
             protocol_version: PROTOCOL_VERSION,
             hostname: address.host.clone(),
             port: address.port,
-            intention: ClientIntention::Login,
-        })
-        .await?;
+            intention: if is_transfer {
+                ClientIntention::Transfer
+            } else {
+                ClientIntention::Login
+            },
+        }
+        .into_variant();
+        packet_log.record(
+            PacketFlow::Serverbound,
+            ConnectionProtocol::Handshake,
+            &intention,
+        );
+        conn.write(intention).await?;
         let mut conn = conn.login();
+        let send = |packet: ServerboundLoginPacket| {
+            packet_log.record(PacketFlow::Serverbound, ConnectionProtocol::Login, &packet);
+            packet
+        };
 
         // this makes it so plugins can send an `SendLoginPacketEvent` event to the ecs
         // and we'll send it to the server
@@ -374,12 +492,15 @@ impl Client {
         ));
 
         // login
-        conn.write(ServerboundHello {
-            name: account.username.clone(),
-            // TODO: pretty sure this should generate an offline-mode uuid instead of just
-            // Uuid::default()
-            profile_id: account.uuid.unwrap_or_default(),
-        })
+        conn.write(send(
+            ServerboundHello {
+                name: account.username.clone(),
+                // TODO: pretty sure this should generate an offline-mode uuid instead of just
+                // Uuid::default()
+                profile_id: account.uuid.unwrap_or_default(),
+            }
+            .into_variant(),
+        ))
         .await?;
 
         let (conn, profile) = loop {
@@ -387,10 +508,11 @@ impl Client {
                 packet = conn.read() => packet?,
                 Some(packet) = ecs_packets_rx.recv() => {
                     // write this packet to the server
-                    conn.write(packet).await?;
+                    conn.write(send(packet)).await?;
                     continue;
                 }
             };
+            packet_log.record(PacketFlow::Clientbound, ConnectionProtocol::Login, &packet);
 
             ecs_lock.lock().send_event(login::LoginPacketEvent {
                 entity,
@@ -439,10 +561,13 @@ impl Client {
                         }
                     }
 
-                    conn.write(ServerboundKey {
-                        key_bytes: e.encrypted_public_key,
-                        encrypted_challenge: e.encrypted_challenge,
-                    })
+                    conn.write(send(
+                        ServerboundKey {
+                            key_bytes: e.encrypted_public_key,
+                            encrypted_challenge: e.encrypted_challenge,
+                        }
+                        .into_variant(),
+                    ))
                     .await?;
 
                     conn.set_encryption_key(e.secret_key);
@@ -456,7 +581,8 @@ impl Client {
                         "Got profile {:?}. handshake is finished and we're now switching to the configuration state",
                         p.game_profile
                     );
-                    conn.write(ServerboundLoginAcknowledged {}).await?;
+                    conn.write(send(ServerboundLoginAcknowledged {}.into_variant()))
+                        .await?;
                     break (conn.config(), p.game_profile);
                 }
                 ClientboundLoginPacket::LoginDisconnect(p) => {
@@ -471,11 +597,17 @@ impl Client {
                 ClientboundLoginPacket::CookieRequest(p) => {
                     debug!("Got cookie request {:?}", p);
 
-                    conn.write(packets::login::ServerboundCookieResponse {
-                        key: p.key,
-                        // cookies aren't implemented
-                        payload: None,
-                    })
+                    let payload = ecs_lock
+                        .lock()
+                        .get::<Cookies>(entity)
+                        .and_then(|cookies| cookies.get(&p.key).cloned());
+                    conn.write(send(
+                        packets::login::ServerboundCookieResponse {
+                            key: p.key,
+                            payload,
+                        }
+                        .into_variant(),
+                    ))
                     .await?;
                 }
             }
@@ -500,17 +632,70 @@ impl Client {
             .write_packet(packet)
     }
 
+    /// Write a packet to the server that Azalea doesn't have a type for, like
+    /// one that's added by a mod.
+    ///
+    /// The packet is made from the id and the bytes after it, and it's only
+    /// sent if the connection is in the given state.
+    ///
+    /// ```no_run
+    /// # use azalea_client::Client;
+    /// # use azalea_protocol::packets::ConnectionProtocol;
+    /// # fn example(bot: &Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// bot.write_raw_packet(ConnectionProtocol::Game, 0x80, &[1, 2, 3])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_raw_packet(
+        &self,
+        state: ConnectionProtocol,
+        id: u32,
+        bytes: &[u8],
+    ) -> Result<(), crate::raw_connection::WritePacketError> {
+        let mut ecs = self.ecs.lock();
+        let raw_connection = self.raw_connection(&mut ecs);
+        if raw_connection.connection_protocol != state {
+            return Err(crate::raw_connection::WritePacketError::WrongState {
+                expected: state,
+                got: raw_connection.connection_protocol,
+            });
+        }
+
+        let mut raw_packet = Vec::with_capacity(bytes.len() + 5);
+        id.azalea_write_var(&mut raw_packet)
+            .expect("writing to a vec can't fail");
+        raw_packet.extend_from_slice(bytes);
+        raw_connection.write_raw_packet(raw_packet.into_boxed_slice())
+    }
+
     /// Disconnect this client from the server by ending all tasks.
     ///
     /// The OwnedReadHalf for the TCP connection is in one of the tasks, so it
     /// automatically closes the connection when that's dropped.
     pub fn disconnect(&self) {
-        self.ecs.lock().send_event(DisconnectEvent {
+        let mut ecs = self.ecs.lock();
+        ecs.entity_mut(self.entity).insert(ManualDisconnect);
+        ecs.send_event(DisconnectEvent {
             entity: self.entity,
             reason: None,
         });
     }
 
+    /// Temporarily take the connection away from the client so you can read
+    /// and write raw packets yourself.
+    ///
+    /// The connection is given back when the [`TakenOverConnection`] is
+    /// dropped. See [`RawConnection::take_over`] for more details.
+    ///
+    /// [`TakenOverConnection`]: crate::raw_connection::TakenOverConnection
+    pub async fn take_over_connection(
+        &self,
+    ) -> Result<crate::raw_connection::TakenOverConnection, crate::raw_connection::TakeOverError>
+    {
+        let take_over = self.raw_connection(&mut self.ecs.lock()).take_over();
+        take_over.await
+    }
+
     pub fn raw_connection<'a>(&'a self, ecs: &'a mut World) -> &'a RawConnection {
         self.query::<&RawConnection>(ecs)
     }
@@ -534,7 +719,8 @@ impl Client {
     ///
     /// # Panics
     ///
-    /// This will panic if the component doesn't exist on the client.
+    /// This will panic if the component doesn't exist on the client. Use
+    /// [`Self::try_component`] or [`Self::get_component`] if it might not.
     ///
     /// # Examples
     ///
@@ -547,6 +733,22 @@ impl Client {
         self.query::<&T>(&mut self.ecs.lock()).clone()
     }
 
+    /// Get a component from this client, or a [`ClientError`] that explains
+    /// why it's missing.
+    ///
+    /// ```
+    /// # use azalea_client::{Client, Hunger};
+    /// # fn example(bot: &Client) {
+    /// match bot.try_component::<Hunger>() {
+    ///     Ok(hunger) => println!("food: {}", hunger.food),
+    ///     Err(err) => println!("couldn't get hunger: {err}"),
+    /// }
+    /// # }
+    /// ```
+    pub fn try_component<T: Component + Clone>(&self) -> Result<T, ClientError> {
+        self.try_query::<&T>(&mut self.ecs.lock()).cloned()
+    }
+
     /// Get a component from this client, or `None` if it doesn't exist.
     ///
     /// If the component can't be cloned, try [`Self::map_component`] instead.
@@ -580,6 +782,17 @@ impl Client {
         f(value)
     }
 
+    /// Like [`Self::map_component`], but returns a [`ClientError`] instead of
+    /// panicking if the component doesn't exist.
+    pub fn try_map_component<T: Component, R>(
+        &self,
+        f: impl FnOnce(&T) -> R,
+    ) -> Result<R, ClientError> {
+        let mut ecs = self.ecs.lock();
+        let value = self.try_query::<&T>(&mut ecs)?;
+        Ok(f(value))
+    }
+
     /// Optionally get a component for this client and call the given function.
     ///
     /// Similar to [`Self::get_component`], but doesn't clone the component
@@ -609,6 +822,12 @@ impl Client {
         instance_holder.instance.clone()
     }
 
+    /// Like [`Self::world`], but returns a [`ClientError`] instead of panicking
+    /// if we don't have a world.
+    pub fn try_world(&self) -> Result<Arc<RwLock<Instance>>, ClientError> {
+        self.try_map_component::<InstanceHolder, _>(|holder| holder.instance.clone())
+    }
+
     /// Get an `RwLock` with a reference to the world that this client has
     /// loaded.
     ///
@@ -675,6 +894,12 @@ impl Client {
         Vec3::from(&self.component::<Position>())
     }
 
+    /// Like [`Self::position`], but returns a [`ClientError`] instead of
+    /// panicking if we don't have a position (like if we disconnected).
+    pub fn try_position(&self) -> Result<Vec3, ClientError> {
+        self.try_map_component::<Position, _>(|position| **position)
+    }
+
     /// Get the position of this client's eyes.
     ///
     /// This is a shortcut for
@@ -698,6 +923,13 @@ impl Client {
         self.component::<Hunger>().to_owned()
     }
 
+    /// Get the experience level and progress of this client.
+    ///
+    /// This is a shortcut for `self.component::<Experience>().to_owned()`.
+    pub fn experience(&self) -> Experience {
+        self.component::<Experience>().to_owned()
+    }
+
     /// Get the username of this client.
     ///
     /// This is a shortcut for
@@ -720,6 +952,53 @@ impl Client {
         self.component::<TabList>().deref().clone()
     }
 
+    /// Get the header and footer that are shown above and below the tab list.
+    ///
+    /// This is a shortcut for getting `header` and `footer` from the
+    /// [`TabListHeader`] component.
+    pub fn tab_list_header_and_footer(&self) -> (FormattedText, FormattedText) {
+        let tab_list_header = self.component::<TabListHeader>();
+        (
+            tab_list_header.header.clone(),
+            tab_list_header.footer.clone(),
+        )
+    }
+
+    /// Get our round trip time to the server, or zero if we don't know it yet.
+    ///
+    /// This is a shortcut for [`Ping::get`] on our [`Ping`] component. By
+    /// default it's the latency that the server shows in the tab list, insert
+    /// the [`MeasurePing`] component to measure it ourselves.
+    ///
+    /// [`MeasurePing`]: crate::MeasurePing
+    pub fn ping(&self) -> Duration {
+        self.get_component::<Ping>()
+            .and_then(|ping| ping.get())
+            .unwrap_or_default()
+    }
+
+    /// Get the time in the world that we're in, or `None` if the server hasn't
+    /// told us yet.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// let is_night = bot.game_time().is_some_and(|time| time.can_sleep());
+    /// // you can also sleep during thunderstorms
+    /// if is_night || bot.weather().is_thundering() {
+    ///     println!("Time to go to bed");
+    /// }
+    /// # }
+    /// ```
+    pub fn game_time(&self) -> Option<GameTime> {
+        self.get_component::<GameTime>()
+    }
+
+    /// Get the current weather in the world that we're in.
+    pub fn weather(&self) -> Weather {
+        self.get_component::<Weather>().unwrap_or_default()
+    }
+
     /// Call the given function with the client's [`RegistryHolder`].
     ///
     /// The player's instance (aka world) will be locked during this time, which
@@ -792,12 +1071,19 @@ pub struct JoinedClientBundle {
     pub physics_state: PhysicsState,
     pub inventory: Inventory,
     pub tab_list: TabList,
+    pub tab_list_header: TabListHeader,
+    pub ping: Ping,
+    pub maps: Maps,
     pub current_sequence_number: CurrentSequenceNumber,
     pub last_sent_direction: LastSentLookDirection,
     pub abilities: PlayerAbilities,
     pub permission_level: PermissionLevel,
     pub chunk_batch_info: ChunkBatchInfo,
+    pub pending_chunks: PendingChunks,
     pub hunger: Hunger,
+    pub experience: Experience,
+    pub chat_queue: ChatQueue,
+    pub session_stats: SessionStats,
 
     pub entity_id_index: EntityIdIndex,
 
@@ -806,6 +1092,35 @@ pub struct JoinedClientBundle {
 
     pub _local_entity: LocalEntity,
 }
+impl Default for JoinedClientBundle {
+    fn default() -> Self {
+        Self {
+            physics_state: PhysicsState::default(),
+            inventory: Inventory::default(),
+            tab_list: TabList::default(),
+            tab_list_header: TabListHeader::default(),
+            ping: Ping::default(),
+            maps: Maps::default(),
+            current_sequence_number: CurrentSequenceNumber::default(),
+            last_sent_direction: LastSentLookDirection::default(),
+            abilities: PlayerAbilities::default(),
+            permission_level: PermissionLevel::default(),
+            chunk_batch_info: ChunkBatchInfo::default(),
+            pending_chunks: PendingChunks::default(),
+            hunger: Hunger::default(),
+            experience: Experience::default(),
+            chat_queue: ChatQueue::default(),
+            session_stats: SessionStats::default(),
+
+            entity_id_index: EntityIdIndex::default(),
+
+            mining: mining::MineBundle::default(),
+            attack: attack::AttackBundle::default(),
+
+            _local_entity: LocalEntity,
+        }
+    }
+}
 
 /// A marker component for local players that are currently in the
 /// `configuration` state.
@@ -825,6 +1140,7 @@ impl Plugin for AzaleaPlugin {
             ),
         )
         .init_resource::<InstanceContainer>()
+        .init_resource::<ShareInstances>()
         .init_resource::<TabList>();
     }
 }
@@ -972,17 +1288,33 @@ impl PluginGroup for DefaultPlugins {
             .add(EventPlugin)
             .add(TaskPoolPlugin::default())
             .add(InventoryPlugin)
+            .add(EnderChestPlugin)
             .add(ChatPlugin)
+            .add(ChatSigningPlugin)
+            .add(ChatHistoryPlugin)
             .add(DisconnectPlugin)
             .add(PlayerMovePlugin)
             .add(InteractPlugin)
+            .add(ActionQueuePlugin)
             .add(RespawnPlugin)
             .add(MinePlugin)
+            .add(BlockEffectsPlugin)
+            .add(SessionStatsPlugin)
             .add(AttackPlugin)
             .add(ChunkPlugin)
+            .add(VehiclePlugin)
+            .add(SpectatorPlugin)
+            .add(ElytraPlugin)
             .add(TickEndPlugin)
             .add(ConfigurationPlugin)
-            .add(TickBroadcastPlugin);
+            .add(TickBroadcastPlugin)
+            .add(ComponentWatchPlugin)
+            .add(TpsPlugin)
+            .add(ServerCommandsPlugin)
+            .add(WorldAuditPlugin)
+            .add(PluginChannelsPlugin)
+            .add(DelayedPacketsPlugin)
+            .add(TransferPlugin);
         #[cfg(feature = "log")]
         {
             group = group.add(bevy_log::LogPlugin::default());
@@ -990,3 +1322,43 @@ impl PluginGroup for DefaultPlugins {
         group
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_raw_packet() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+
+        let mut ecs = World::new();
+        let (raw_connection, mut outgoing) =
+            RawConnection::new_headless(mpsc::unbounded_channel().0, ConnectionProtocol::Game);
+        let entity = ecs.spawn(raw_connection).id();
+        let client = Client::new(
+            GameProfile::new(Uuid::nil(), "bot".to_owned()),
+            entity,
+            Arc::new(Mutex::new(ecs)),
+            mpsc::unbounded_channel().0,
+        );
+
+        client
+            .write_raw_packet(ConnectionProtocol::Game, 0x80, &[1, 2, 3])
+            .unwrap();
+        // the id is written as a varint before the bytes
+        assert_eq!(&*outgoing.try_recv().unwrap(), &[0x80, 0x01, 1, 2, 3]);
+
+        let err = client
+            .write_raw_packet(ConnectionProtocol::Configuration, 0, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::raw_connection::WritePacketError::WrongState {
+                expected: ConnectionProtocol::Configuration,
+                got: ConnectionProtocol::Game,
+            }
+        ));
+        assert!(outgoing.try_recv().is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Wait for a component on an entity to change, instead of checking it every
+//! tick.
+//!
+//! See [`Client::watch_component`].
+
+use std::{any::TypeId, collections::HashMap, ops::Deref};
+
+use azalea_core::position::Vec3;
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::{prelude::*, system::SystemId};
+use tokio::sync::mpsc;
+
+use crate::Client;
+
+pub struct ComponentWatchPlugin;
+impl Plugin for ComponentWatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ComponentWatchSystems>()
+            .add_systems(Last, run_component_watch_systems);
+    }
+}
+
+/// The systems that check for changes, one for each component type that's
+/// being watched.
+///
+/// They're registered the first time a component type is watched, since we
+/// can't know ahead of time which components people will want to watch.
+#[derive(Resource, Default)]
+pub struct ComponentWatchSystems {
+    systems: HashMap<TypeId, SystemId>,
+}
+
+/// The entities that are being watched for changes to `T`, and where to send
+/// the new values.
+#[derive(Resource)]
+struct ComponentWatchers<T> {
+    watchers: Vec<(Entity, mpsc::UnboundedSender<T>)>,
+}
+
+impl Client {
+    /// Get a [`ComponentWatcher`] that yields whenever the given component on
+    /// the entity changes.
+    ///
+    /// This is checked once every time the ECS runs, so even if the component
+    /// changes multiple times in a tick you'll only get the last value. The
+    /// watcher ends when the entity is despawned.
+    ///
+    /// ```
+    /// # use azalea_entity::Position;
+    /// # async fn example(bot: azalea_client::Client, entity: bevy_ecs::entity::Entity) {
+    /// // only wake up when the entity moves at least 2 blocks
+    /// let mut watcher = bot.watch_component::<Position>(entity).min_distance(2.);
+    /// while let Some(position) = watcher.changed().await {
+    ///     println!("the entity moved to {position:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_component<T: Component + Clone>(&self, entity: Entity) -> ComponentWatcher<T> {
+        watch_component(&mut self.ecs.lock(), entity)
+    }
+}
+
+/// Start watching the component on the entity. This is what
+/// [`Client::watch_component`] uses.
+pub fn watch_component<T: Component + Clone>(
+    ecs: &mut World,
+    entity: Entity,
+) -> ComponentWatcher<T> {
+    let type_id = TypeId::of::<T>();
+    if !ecs
+        .resource::<ComponentWatchSystems>()
+        .systems
+        .contains_key(&type_id)
+    {
+        ecs.insert_resource(ComponentWatchers::<T> {
+            watchers: Vec::new(),
+        });
+        let system = ecs.register_system(send_component_changes::<T>);
+        // run it once so the components that already exist don't count as
+        // changed the next time it runs
+        let _ = ecs.run_system(system);
+        ecs.resource_mut::<ComponentWatchSystems>()
+            .systems
+            .insert(type_id, system);
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    ecs.resource_mut::<ComponentWatchers<T>>()
+        .watchers
+        .push((entity, sender));
+
+    ComponentWatcher {
+        receiver,
+        last: ecs.get::<T>(entity).cloned(),
+        is_significant: Box::new(|_, _| true),
+    }
+}
+
+/// Something that yields the new value of a component whenever it changes.
+///
+/// Create one with [`Client::watch_component`].
+pub struct ComponentWatcher<T> {
+    receiver: mpsc::UnboundedReceiver<T>,
+    /// The last value that was yielded, or the value when the watcher was
+    /// created.
+    last: Option<T>,
+    is_significant: Box<SignificantChangeFn<T>>,
+}
+
+pub type SignificantChangeFn<T> = dyn Fn(&T, &T) -> bool + Send + Sync;
+
+impl<T: Clone> ComponentWatcher<T> {
+    /// Only yield when the given function returns true. It's called with the
+    /// last value that was yielded and the new value.
+    ///
+    /// Small changes don't get lost, since they're compared to the last value
+    /// that was yielded rather than the previous value of the component.
+    pub fn threshold(
+        mut self,
+        is_significant: impl Fn(&T, &T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.is_significant = Box::new(is_significant);
+        self
+    }
+
+    /// Wait until the component changes, and return the new value.
+    ///
+    /// Returns `None` if the entity was despawned.
+    pub async fn changed(&mut self) -> Option<T> {
+        loop {
+            let value = self.receiver.recv().await?;
+            if let Some(last) = &self.last {
+                if !(self.is_significant)(last, &value) {
+                    continue;
+                }
+            }
+            self.last = Some(value.clone());
+            return Some(value);
+        }
+    }
+}
+
+impl<T: Clone + Deref<Target = Vec3>> ComponentWatcher<T> {
+    /// Only yield when the position is at least `distance` blocks away from
+    /// the last one that was yielded.
+    pub fn min_distance(self, distance: f64) -> Self {
+        self.threshold(move |last, new| last.distance_to(new) >= distance)
+    }
+}
+
+pub fn run_component_watch_systems(world: &mut World) {
+    let systems = world
+        .resource::<ComponentWatchSystems>()
+        .systems
+        .values()
+        .copied()
+        .collect::<Vec<_>>();
+    for system in systems {
+        let _ = world.run_system(system);
+    }
+}
+
+fn send_component_changes<T: Component + Clone>(
+    query: Query<Option<Ref<T>>>,
+    mut watchers: ResMut<ComponentWatchers<T>>,
+) {
+    watchers.watchers.retain(|(entity, sender)| {
+        if sender.is_closed() {
+            return false;
+        }
+        match query.get(*entity) {
+            Ok(Some(component)) => {
+                if component.is_changed() {
+                    let _ = sender.send(T::clone(&component));
+                }
+                true
+            }
+            // the entity doesn't have the component yet
+            Ok(None) => true,
+            // the entity was despawned, so dropping the sender ends the watcher
+            Err(_) => false,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_entity::Position;
+    use futures_lite::future;
+
+    use super::*;
+
+    fn setup() -> (World, Entity) {
+        let mut ecs = World::new();
+        ecs.init_resource::<ComponentWatchSystems>();
+        let entity = ecs.spawn(Position::new(Vec3::new(0., 0., 0.))).id();
+        (ecs, entity)
+    }
+
+    fn move_to(ecs: &mut World, entity: Entity, x: f64) {
+        **ecs.get_mut::<Position>(entity).unwrap() = Vec3::new(x, 0., 0.);
+        run_component_watch_systems(ecs);
+    }
+
+    /// Get the next value from the watcher without waiting, or `None` if
+    /// there isn't one yet.
+    fn poll<T: Clone>(watcher: &mut ComponentWatcher<T>) -> Option<Option<T>> {
+        future::block_on(future::poll_once(watcher.changed()))
+    }
+
+    #[test]
+    fn test_yields_on_change() {
+        let (mut ecs, entity) = setup();
+        let mut watcher = watch_component::<Position>(&mut ecs, entity);
+
+        // nothing changed yet
+        run_component_watch_systems(&mut ecs);
+        assert_eq!(poll(&mut watcher), None);
+
+        move_to(&mut ecs, entity, 1.);
+        assert_eq!(
+            poll(&mut watcher),
+            Some(Some(Position::new(Vec3::new(1., 0., 0.))))
+        );
+        assert_eq!(poll(&mut watcher), None);
+    }
+
+    #[test]
+    fn test_min_distance_compares_to_last_yielded() {
+        let (mut ecs, entity) = setup();
+        let mut watcher = watch_component::<Position>(&mut ecs, entity).min_distance(2.);
+
+        // each step is too small on its own, but they add up
+        move_to(&mut ecs, entity, 1.);
+        assert_eq!(poll(&mut watcher), None);
+        move_to(&mut ecs, entity, 2.);
+        assert_eq!(
+            poll(&mut watcher),
+            Some(Some(Position::new(Vec3::new(2., 0., 0.))))
+        );
+        move_to(&mut ecs, entity, 3.);
+        assert_eq!(poll(&mut watcher), None);
+    }
+
+    #[test]
+    fn test_ends_when_entity_despawned() {
+        let (mut ecs, entity) = setup();
+        let mut watcher = watch_component::<Position>(&mut ecs, entity);
+
+        ecs.despawn(entity);
+        run_component_watch_systems(&mut ecs);
+        assert_eq!(poll(&mut watcher), Some(None));
+        assert!(ecs
+            .resource::<ComponentWatchers<Position>>()
+            .watchers
+            .is_empty());
+    }
+
+    #[test]
+    fn test_dropped_watchers_are_removed() {
+        let (mut ecs, entity) = setup();
+        let watcher = watch_component::<Position>(&mut ecs, entity);
+        let _other = watch_component::<Position>(&mut ecs, entity);
+        // only one system is registered per component type
+        assert_eq!(ecs.resource::<ComponentWatchSystems>().systems.len(), 1);
+
+        drop(watcher);
+        move_to(&mut ecs, entity, 1.);
+        assert_eq!(
+            ecs.resource::<ComponentWatchers<Position>>().watchers.len(),
+            1
+        );
+    }
+}
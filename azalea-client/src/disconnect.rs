@@ -62,6 +62,15 @@ pub fn remove_components_from_disconnected_players(
 #[derive(Component, Clone, Copy, Debug, Deref)]
 pub struct IsConnectionAlive(bool);
 
+/// A marker component for clients that were disconnected on purpose with
+/// [`Client::disconnect`], as opposed to being kicked or losing connection.
+///
+/// This is removed when the client joins again.
+///
+/// [`Client::disconnect`]: crate::Client::disconnect
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ManualDisconnect;
+
 fn update_read_packets_task_running_component(
     query: Query<(Entity, &RawConnection)>,
     mut commands: Commands,
@@ -73,7 +82,7 @@ fn update_read_packets_task_running_component(
 }
 
 #[allow(clippy::type_complexity)]
-fn disconnect_on_connection_dead(
+pub fn disconnect_on_connection_dead(
     query: Query<(Entity, &IsConnectionAlive), (Changed<IsConnectionAlive>, With<LocalEntity>)>,
     mut disconnect_events: EventWriter<DisconnectEvent>,
 ) {
@@ -0,0 +1,180 @@
+//! Gliding with elytras and boosting with firework rockets.
+
+use azalea_core::tick::GameTick;
+use azalea_entity::{metadata::FallFlying, Jumping, LocalEntity, Physics};
+use azalea_inventory::ItemStack;
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::{
+    s_interact::InteractionHand, s_player_command, ServerboundPlayerCommand,
+};
+use azalea_world::MinecraftEntityId;
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tracing::warn;
+
+use crate::{
+    interact::{handle_start_use_item_event, StartUseItemEvent},
+    inventory::{Inventory, InventorySet},
+    movement::MoveEventsSet,
+    packet_handling::game::SendPacketEvent,
+    Client,
+};
+
+pub struct ElytraPlugin;
+impl Plugin for ElytraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartGlidingEvent>()
+            .add_event::<UseFireworkEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_start_gliding_event.before(MoveEventsSet),
+                    handle_use_firework_event
+                        .after(InventorySet)
+                        .before(handle_start_use_item_event),
+                ),
+            )
+            .add_systems(GameTick, try_start_gliding.before(PhysicsSet));
+    }
+}
+
+impl Client {
+    /// Start gliding with the elytra that we're wearing.
+    ///
+    /// If we're on the ground, this will jump first and then start gliding
+    /// once we're in the air. You can check whether it worked with
+    /// [`Client::is_gliding`].
+    pub fn start_gliding(&mut self) {
+        self.ecs.lock().send_event(StartGlidingEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Use the firework rocket that we're holding to boost ourselves forward
+    /// while gliding.
+    ///
+    /// This does nothing if we're not holding a firework rocket.
+    pub fn use_firework(&mut self) {
+        self.ecs.lock().send_event(UseFireworkEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Whether we're currently gliding with an elytra.
+    pub fn is_gliding(&self) -> bool {
+        self.get_component::<FallFlying>()
+            .is_some_and(|fall_flying| *fall_flying)
+    }
+}
+
+/// A marker component for local players that are going to start gliding as
+/// soon as they're in the air.
+#[derive(Component, Clone, Debug, Default)]
+pub struct TryingToGlide;
+
+/// Start gliding with an elytra. See [`Client::start_gliding`].
+#[derive(Event)]
+pub struct StartGlidingEvent {
+    pub entity: Entity,
+}
+pub fn handle_start_gliding_event(
+    mut events: EventReader<StartGlidingEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        commands.entity(event.entity).insert(TryingToGlide);
+    }
+}
+
+/// Returns whether the given item can be used to glide.
+pub fn is_glider(item: &ItemStack) -> bool {
+    let ItemStack::Present(item) = item else {
+        return false;
+    };
+    item.kind == azalea_registry::Item::Elytra
+        || item
+            .components
+            .has::<azalea_inventory::components::Glider>()
+}
+
+#[allow(clippy::type_complexity)]
+pub fn try_start_gliding(
+    mut query: Query<
+        (
+            Entity,
+            &MinecraftEntityId,
+            &Physics,
+            &Inventory,
+            &mut Jumping,
+            &mut FallFlying,
+        ),
+        (With<TryingToGlide>, With<LocalEntity>),
+    >,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut commands: Commands,
+) {
+    for (entity, minecraft_entity_id, physics, inventory, mut jumping, mut fall_flying) in
+        &mut query
+    {
+        if **fall_flying {
+            commands.entity(entity).remove::<TryingToGlide>();
+            continue;
+        }
+
+        let chestplate = &inventory.inventory_menu.as_player().armor[1];
+        if !is_glider(chestplate) {
+            warn!("Tried to start gliding without wearing an elytra");
+            commands.entity(entity).remove::<TryingToGlide>();
+            continue;
+        }
+
+        if physics.is_in_water() || physics.is_in_lava() {
+            commands.entity(entity).remove::<TryingToGlide>();
+            continue;
+        }
+
+        if physics.on_ground() {
+            // we have to jump first
+            **jumping = true;
+            continue;
+        }
+
+        **jumping = false;
+        // vanilla also sets this client-side before the server confirms it
+        **fall_flying = true;
+        send_packet_events.send(SendPacketEvent::new(
+            entity,
+            ServerboundPlayerCommand {
+                id: **minecraft_entity_id,
+                action: s_player_command::Action::StartFallFlying,
+                data: 0,
+            },
+        ));
+        commands.entity(entity).remove::<TryingToGlide>();
+    }
+}
+
+/// Use the firework rocket that we're holding. See [`Client::use_firework`].
+#[derive(Event)]
+pub struct UseFireworkEvent {
+    pub entity: Entity,
+}
+pub fn handle_use_firework_event(
+    mut events: EventReader<UseFireworkEvent>,
+    query: Query<&Inventory>,
+    mut start_use_item_events: EventWriter<StartUseItemEvent>,
+) {
+    for event in events.read() {
+        let Ok(inventory) = query.get(event.entity) else {
+            continue;
+        };
+        if inventory.held_item().kind() != azalea_registry::Item::FireworkRocket {
+            warn!("Tried to use a firework rocket without holding one");
+            continue;
+        }
+        start_use_item_events.send(StartUseItemEvent {
+            entity: event.entity,
+            hand: InteractionHand::MainHand,
+        });
+    }
+}
@@ -0,0 +1,162 @@
+//! Remember what's in each account's ender chest.
+//!
+//! Ender chests have the same contents no matter which one you open, so once
+//! we've seen the contents we can keep track of them without having to open
+//! the chest again.
+
+use std::collections::HashMap;
+
+use azalea_chat::FormattedText;
+use azalea_inventory::{ItemStack, Menu};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{
+    interact::{handle_block_interact_event, BlockInteractEvent},
+    inventory::{Inventory, InventorySet, MenuOpenedEvent},
+    local_player::{GameProfileComponent, InstanceHolder},
+    Client,
+};
+
+pub struct EnderChestPlugin;
+impl Plugin for EnderChestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnderChests>().add_systems(
+            Update,
+            (
+                remember_ender_chest_interaction.before(handle_block_interact_event),
+                (detect_ender_chest_opened, update_ender_chest_contents)
+                    .chain()
+                    .after(InventorySet),
+            ),
+        );
+    }
+}
+
+/// The last-known contents of the ender chests of every account, indexed by
+/// the account's UUID.
+///
+/// This is a resource so it stays around when a bot reconnects, or when a
+/// different bot in the swarm logs in with the same account.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EnderChests(pub HashMap<Uuid, Vec<ItemStack>>);
+
+/// A component that's present on local players whose currently open container
+/// is their ender chest.
+#[derive(Component, Clone, Debug)]
+pub struct EnderChestOpen {
+    pub window_id: i32,
+}
+
+/// A component that's present on local players that just right clicked an
+/// ender chest, so the next container that's opened is probably going to be
+/// the ender chest.
+#[derive(Component, Clone, Debug)]
+pub struct InteractedWithEnderChest;
+
+impl Client {
+    /// Get the last-known contents of our ender chest, or `None` if we haven't
+    /// opened it yet.
+    ///
+    /// The contents are updated every time the ender chest is opened, and
+    /// while it stays open.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// if let Some(ender_chest) = bot.ender_chest() {
+    ///     let diamonds = ender_chest
+    ///         .iter()
+    ///         .filter(|item| item.kind() == azalea_registry::Item::Diamond)
+    ///         .map(|item| item.count())
+    ///         .sum::<i32>();
+    ///     println!("We have {diamonds} diamonds in our ender chest");
+    /// }
+    /// # }
+    /// ```
+    pub fn ender_chest(&self) -> Option<Vec<ItemStack>> {
+        let uuid = self.uuid();
+        let ecs = self.ecs.lock();
+        ecs.resource::<EnderChests>().0.get(&uuid).cloned()
+    }
+}
+
+pub fn remember_ender_chest_interaction(
+    mut events: EventReader<BlockInteractEvent>,
+    query: Query<&InstanceHolder>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(instance_holder) = query.get(event.entity) else {
+            continue;
+        };
+        let block_state = instance_holder
+            .instance
+            .read()
+            .get_block_state(&event.position)
+            .unwrap_or_default();
+        if azalea_registry::Block::from(block_state) == azalea_registry::Block::EnderChest {
+            commands
+                .entity(event.entity)
+                .insert(InteractedWithEnderChest);
+        } else {
+            commands
+                .entity(event.entity)
+                .remove::<InteractedWithEnderChest>();
+        }
+    }
+}
+
+/// Returns whether the title of a container is the one that vanilla uses for
+/// ender chests.
+fn is_ender_chest_title(title: &FormattedText) -> bool {
+    match title {
+        FormattedText::Translatable(translatable) => translatable.key == "container.enderchest",
+        FormattedText::Text(_) => false,
+    }
+}
+
+pub fn detect_ender_chest_opened(
+    mut events: EventReader<MenuOpenedEvent>,
+    query: Query<Option<&InteractedWithEnderChest>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(interacted_with_ender_chest) = query.get(event.entity) else {
+            continue;
+        };
+        let mut entity_commands = commands.entity(event.entity);
+        entity_commands.remove::<InteractedWithEnderChest>();
+
+        let is_ender_chest = matches!(event.menu_type, azalea_registry::MenuKind::Generic9x3)
+            && (interacted_with_ender_chest.is_some() || is_ender_chest_title(&event.title));
+        if is_ender_chest {
+            debug!("Opened ender chest with window id {}", event.window_id);
+            entity_commands.insert(EnderChestOpen {
+                window_id: event.window_id,
+            });
+        } else {
+            entity_commands.remove::<EnderChestOpen>();
+        }
+    }
+}
+
+pub fn update_ender_chest_contents(
+    query: Query<(Entity, &Inventory, &GameProfileComponent, &EnderChestOpen), Changed<Inventory>>,
+    mut ender_chests: ResMut<EnderChests>,
+    mut commands: Commands,
+) {
+    for (entity, inventory, game_profile, ender_chest_open) in &query {
+        if inventory.id != ender_chest_open.window_id {
+            // the ender chest was closed
+            commands.entity(entity).remove::<EnderChestOpen>();
+            continue;
+        }
+        let Some(menu @ Menu::Generic9x3 { .. }) = &inventory.container_menu else {
+            continue;
+        };
+        ender_chests.0.insert(game_profile.uuid, menu.contents());
+    }
+}
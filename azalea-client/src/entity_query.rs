@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use azalea_core::{aabb::AABB, position::Vec3};
+use azalea_entity::Position;
 use bevy_ecs::{
     component::Component,
     entity::Entity,
@@ -9,11 +11,17 @@ use bevy_ecs::{
 };
 use parking_lot::Mutex;
 
-use crate::Client;
+use crate::{error::ClientError, Client};
 
 impl Client {
     /// A convenience function for getting components of our player's entity.
     ///
+    /// # Panics
+    ///
+    /// This will panic if our entity doesn't match the query, for example
+    /// because a component is missing or because we disconnected. Use
+    /// [`Self::try_query`] if you'd like to handle that instead.
+    ///
     /// # Examples
     /// ```
     /// # use azalea_world::InstanceName;
@@ -24,14 +32,23 @@ impl Client {
     /// # }
     /// ```
     pub fn query<'w, D: QueryData>(&self, ecs: &'w mut World) -> D::Item<'w> {
-        ecs.query::<D>()
+        self.try_query::<D>(ecs)
+            .unwrap_or_else(|err| panic!("Couldn't query our client: {err}"))
+    }
+
+    /// Like [`Self::query`], but returns a [`ClientError`] instead of
+    /// panicking if our entity doesn't match the query.
+    pub fn try_query<'w, D: QueryData>(
+        &self,
+        ecs: &'w mut World,
+    ) -> Result<D::Item<'w>, ClientError> {
+        let mut query = ecs.query::<D>();
+        if query.get_mut(ecs, self.entity).is_err() {
+            return Err(ClientError::diagnose::<D>(ecs, self.entity));
+        }
+        Ok(query
             .get_mut(ecs, self.entity)
-            .unwrap_or_else(|_| {
-                panic!(
-                    "Our client is missing a required component {:?}",
-                    std::any::type_name::<D>()
-                )
-            })
+            .expect("we just checked that the entity matches the query"))
     }
 
     /// Return a lightweight [`Entity`] for the entity that matches the given
@@ -66,21 +83,105 @@ impl Client {
         predicate.find(self.ecs.clone())
     }
 
+    /// Get the closest entity to our player that's at most `max_distance`
+    /// blocks away and matches the given predicate function, or `None` if
+    /// there isn't one. The distance is measured from our eyes (like the
+    /// reach for attacking) to the other entity's position (at its feet), and
+    /// `max_distance` can be [`f64::INFINITY`].
+    ///
+    /// Only the entities in the chunks around us are checked until one is
+    /// found (see [`Instance::nearest_entity`]), so this is much faster than
+    /// going through every entity when there are lots of them.
+    ///
+    /// # Example
+    /// ```
+    /// use azalea_client::Client;
+    /// use azalea_entity::metadata::{AbstractMonster, Health};
+    /// use bevy_ecs::query::With;
+    ///
+    /// # fn example(mut bot: Client) {
+    /// let target = bot.nearest_entity::<With<AbstractMonster>, (&Health,)>(
+    ///     16.,
+    ///     |(health,): &(&Health,)| **health > 0.,
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`Instance::nearest_entity`]: azalea_world::Instance::nearest_entity
+    pub fn nearest_entity<F: QueryFilter, Q: QueryData>(
+        &self,
+        max_distance: f64,
+        predicate: impl Fn(&ROQueryItem<Q>) -> bool,
+    ) -> Option<Entity> {
+        let instance = self.world();
+        let center = self.eye_position();
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query_filtered::<(&Position, Q), F>();
+        let instance = instance.read();
+        instance.nearest_entity(center, max_distance, |entity| {
+            if entity == self.entity {
+                return None;
+            }
+            let (position, q) = query.get(&ecs, entity).ok()?;
+            predicate(&q).then_some(**position)
+        })
+    }
+
+    /// Get the entities in our world whose positions are within `radius`
+    /// blocks of `center`. This includes our player.
+    ///
+    /// Only the entities in nearby chunks are checked, see
+    /// [`Instance::entities_in_radius`].
+    ///
+    /// [`Instance::entities_in_radius`]: azalea_world::Instance::entities_in_radius
+    pub fn entities_in_radius(&self, center: Vec3, radius: f64) -> Vec<Entity> {
+        let instance = self.world();
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query::<&Position>();
+        let instance = instance.read();
+        instance.entities_in_radius(center, radius, |entity| {
+            query.get(&ecs, entity).ok().map(|position| **position)
+        })
+    }
+
+    /// Get the entities in our world whose positions are in the bounding box.
+    /// This includes our player.
+    ///
+    /// Only the entities in nearby chunks are checked, see
+    /// [`Instance::entities_in_aabb`].
+    ///
+    /// [`Instance::entities_in_aabb`]: azalea_world::Instance::entities_in_aabb
+    pub fn entities_in_aabb(&self, aabb: &AABB) -> Vec<Entity> {
+        let instance = self.world();
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query::<&Position>();
+        let instance = instance.read();
+        instance.entities_in_aabb(aabb, |entity| {
+            query.get(&ecs, entity).ok().map(|position| **position)
+        })
+    }
+
     /// Get a component from an entity. Note that this will return an owned type
     /// (i.e. not a reference) so it may be expensive for larger types.
     ///
     /// If you're trying to get a component for this client, use
     /// [`Self::component`].
     pub fn entity_component<Q: Component + Clone>(&mut self, entity: Entity) -> Q {
-        let mut ecs = self.ecs.lock();
-        let mut q = ecs.query::<&Q>();
-        let components = q.get(&ecs, entity).unwrap_or_else(|_| {
-            panic!(
-                "Entity is missing a required component {:?}",
-                std::any::type_name::<Q>()
-            )
-        });
-        components.clone()
+        self.try_entity_component(entity)
+            .unwrap_or_else(|err| panic!("Couldn't get entity component: {err}"))
+    }
+
+    /// Like [`Self::entity_component`], but returns a [`ClientError`] instead
+    /// of panicking if the entity doesn't exist or doesn't have the component.
+    pub fn try_entity_component<Q: Component + Clone>(
+        &self,
+        entity: Entity,
+    ) -> Result<Q, ClientError> {
+        let ecs = self.ecs.lock();
+        match ecs.get::<Q>(entity) {
+            Some(component) => Ok(component.clone()),
+            None => Err(ClientError::diagnose_other::<Q>(&ecs, entity)),
+        }
     }
 
     /// Get a component from an entity, if it exists. This is similar to
@@ -129,3 +230,84 @@ where
 //         entity
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use azalea_auth::game_profile::GameProfile;
+    use azalea_core::position::ChunkPos;
+    use azalea_entity::EyeHeight;
+    use azalea_world::{ChunkStorage, Instance};
+    use bevy_ecs::query::With;
+    use parking_lot::RwLock;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::InstanceHolder;
+
+    #[derive(Component)]
+    struct Target;
+
+    #[test]
+    fn test_nearest_entity() {
+        let mut ecs = World::new();
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+        let entity = ecs
+            .spawn((Position::new(Vec3::new(0., 64., 0.)), EyeHeight::new(2.)))
+            .id();
+        ecs.entity_mut(entity)
+            .insert(InstanceHolder::new(entity, instance.clone()));
+        let near = ecs
+            .spawn((Position::new(Vec3::new(3., 66., 0.)), Target))
+            .id();
+        let far = ecs
+            .spawn((Position::new(Vec3::new(20., 66., 0.)), Target))
+            .id();
+        let not_a_target = ecs.spawn(Position::new(Vec3::new(1., 66., 0.))).id();
+        let mut instance_lock = instance.write();
+        instance_lock
+            .entities_by_chunk
+            .entry(ChunkPos::new(0, 0))
+            .or_default()
+            .extend([entity, near, not_a_target]);
+        instance_lock
+            .entities_by_chunk
+            .entry(ChunkPos::new(1, 0))
+            .or_default()
+            .insert(far);
+        drop(instance_lock);
+
+        let client = Client::new(
+            GameProfile::new(Uuid::nil(), "bot".to_owned()),
+            entity,
+            Arc::new(Mutex::new(ecs)),
+            mpsc::unbounded_channel().0,
+        );
+
+        assert_eq!(
+            client.nearest_entity::<With<Target>, ()>(f64::INFINITY, |_: &()| true),
+            Some(near)
+        );
+        assert_eq!(
+            client.nearest_entity::<With<Target>, ()>(2., |_: &()| true),
+            None
+        );
+        // the distance is measured from our eyes, and the target would be too far
+        // away from our feet
+        assert_eq!(
+            client.nearest_entity::<With<Target>, ()>(3., |_: &()| true),
+            Some(near)
+        );
+        assert_eq!(
+            client.nearest_entity::<With<Target>, &Position>(f64::INFINITY, |position| {
+                position.x > 10.
+            }),
+            Some(far)
+        );
+        // our own entity is never the nearest one
+        assert_eq!(
+            client.nearest_entity::<(), ()>(2., |_: &()| true),
+            Some(not_a_target)
+        );
+    }
+}
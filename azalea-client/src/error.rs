@@ -0,0 +1,176 @@
+//! Errors that can be returned by the non-panicking methods on [`Client`].
+
+use azalea_world::InstanceName;
+use bevy_ecs::{
+    entity::Entity,
+    query::{QueryData, QueryEntityError, QueryFilter},
+    system::Query,
+    world::World,
+};
+use thiserror::Error;
+
+use crate::{Client, InConfigState};
+
+/// An error that happened while trying to access the state of a [`Client`].
+///
+/// Most of the methods on `Client` panic when something is missing since it
+/// usually means there's a bug, but that can take down a whole swarm. The
+/// `try_` variants of those methods (like [`Client::try_component`]) return
+/// this instead.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// Our entity was removed from the ECS, which happens after we disconnect.
+    #[error("The client's entity {0} doesn't exist anymore, it probably disconnected")]
+    Despawned(Entity),
+    /// We're in the configuration state, so we don't have any of the
+    /// components from [`JoinedClientBundle`] yet.
+    ///
+    /// [`JoinedClientBundle`]: crate::JoinedClientBundle
+    #[error("The client is in the configuration state, so it isn't in the game yet")]
+    InConfigState,
+    /// We haven't received the login packet yet, so we don't have a world.
+    #[error("The client isn't in a world yet")]
+    NotInWorld,
+    /// Another entity that we were trying to access doesn't exist anymore.
+    #[error("The entity {0} doesn't exist anymore")]
+    EntityDespawned(Entity),
+    /// The component isn't present on the entity, even though the entity
+    /// exists and is in the right state.
+    #[error("The entity is missing the component {component}")]
+    MissingComponent { component: &'static str },
+}
+
+impl ClientError {
+    /// Figure out why a query for our client failed.
+    pub(crate) fn diagnose<D>(ecs: &World, entity: Entity) -> Self {
+        state_error(ecs, entity).unwrap_or(Self::MissingComponent {
+            component: std::any::type_name::<D>(),
+        })
+    }
+
+    /// Figure out why a query for an entity that isn't our client failed.
+    pub(crate) fn diagnose_other<D>(ecs: &World, entity: Entity) -> Self {
+        if ecs.entities().contains(entity) {
+            Self::MissingComponent {
+                component: std::any::type_name::<D>(),
+            }
+        } else {
+            Self::EntityDespawned(entity)
+        }
+    }
+}
+
+/// Get the item for our client from a query, returning a [`ClientError`]
+/// instead of panicking if our entity doesn't match it.
+///
+/// This is mostly meant for packet handlers, since they run for every client
+/// and one of them being in a weird state shouldn't take down the others.
+pub(crate) fn get_client<'a, D: QueryData, F: QueryFilter>(
+    query: &'a mut Query<'_, '_, D, F>,
+    entity: Entity,
+) -> Result<D::Item<'a>, ClientError> {
+    query.get_mut(entity).map_err(|err| match err {
+        QueryEntityError::NoSuchEntity(entity, ..) => ClientError::Despawned(entity),
+        _ => ClientError::MissingComponent {
+            component: std::any::type_name::<D>(),
+        },
+    })
+}
+
+/// Returns the error for our client not being fully in the game, or `None` if
+/// it is.
+fn state_error(ecs: &World, entity: Entity) -> Option<ClientError> {
+    if !ecs.entities().contains(entity) {
+        Some(ClientError::Despawned(entity))
+    } else if ecs.get::<InConfigState>(entity).is_some() {
+        Some(ClientError::InConfigState)
+    } else if ecs.get::<InstanceName>(entity).is_none() {
+        Some(ClientError::NotInWorld)
+    } else {
+        None
+    }
+}
+
+impl Client {
+    /// Returns an error if our client isn't fully in the game, like if it
+    /// disconnected or is in the configuration state.
+    pub fn check_in_game(&self) -> Result<(), ClientError> {
+        match state_error(&self.ecs.lock(), self.entity) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::resource_location::ResourceLocation;
+    use azalea_entity::Position;
+    use bevy_ecs::system::SystemState;
+
+    use super::*;
+
+    #[test]
+    fn test_get_client_despawned() {
+        let mut ecs = World::new();
+        let entity = ecs.spawn(Position::default()).id();
+        ecs.despawn(entity);
+
+        let mut system_state = SystemState::<Query<&Position>>::new(&mut ecs);
+        let mut query = system_state.get_mut(&mut ecs);
+        assert_eq!(
+            get_client(&mut query, entity).unwrap_err(),
+            ClientError::Despawned(entity)
+        );
+    }
+
+    #[test]
+    fn test_get_client_missing_component() {
+        let mut ecs = World::new();
+        let entity = ecs
+            .spawn(InstanceName(ResourceLocation::new("minecraft:overworld")))
+            .id();
+
+        let mut system_state = SystemState::<Query<&Position>>::new(&mut ecs);
+        let mut query = system_state.get_mut(&mut ecs);
+        assert!(matches!(
+            get_client(&mut query, entity),
+            Err(ClientError::MissingComponent { .. })
+        ));
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let mut ecs = World::new();
+        let entity = ecs.spawn(InConfigState).id();
+        assert_eq!(
+            ClientError::diagnose::<&Position>(&ecs, entity),
+            ClientError::InConfigState
+        );
+
+        ecs.entity_mut(entity).remove::<InConfigState>();
+        assert_eq!(
+            ClientError::diagnose::<&Position>(&ecs, entity),
+            ClientError::NotInWorld
+        );
+
+        ecs.entity_mut(entity)
+            .insert(InstanceName(ResourceLocation::new("minecraft:overworld")));
+        assert_eq!(
+            ClientError::diagnose::<&Position>(&ecs, entity),
+            ClientError::MissingComponent {
+                component: std::any::type_name::<&Position>()
+            }
+        );
+
+        ecs.despawn(entity);
+        assert_eq!(
+            ClientError::diagnose::<&Position>(&ecs, entity),
+            ClientError::Despawned(entity)
+        );
+        assert_eq!(
+            ClientError::diagnose_other::<&Position>(&ecs, entity),
+            ClientError::EntityDespawned(entity)
+        );
+    }
+}
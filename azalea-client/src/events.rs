@@ -1,17 +1,25 @@
 //! Defines the [`Event`] enum and makes those events trigger when they're sent
 //! in the ECS.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use azalea_chat::FormattedText;
-use azalea_core::tick::GameTick;
+use azalea_core::{position::Vec3, tick::GameTick};
+use azalea_entity::Position;
 use azalea_protocol::packets::game::{
-    c_player_combat_kill::ClientboundPlayerCombatKill, ClientboundGamePacket,
+    c_level_particles::ClientboundLevelParticles,
+    c_player_combat_kill::ClientboundPlayerCombatKill,
+    c_sound::{CustomSound, SoundSource},
+    ClientboundGamePacket,
 };
+use azalea_protocol::packets::ConnectionProtocol;
+use azalea_protocol::ServerAddress;
+use azalea_registry::{Holder, SoundEvent};
 use azalea_world::{InstanceName, MinecraftEntityId};
-use bevy_app::{App, Plugin, PreUpdate, Update};
+use bevy_app::{App, Plugin, PostUpdate, PreUpdate, Update};
 use bevy_ecs::{
     component::Component,
+    entity::Entity,
     event::EventReader,
     query::{Added, With},
     schedule::IntoSystemConfigs,
@@ -22,10 +30,17 @@ use tokio::sync::mpsc;
 
 use crate::{
     chat::{ChatPacket, ChatReceivedEvent},
-    disconnect::DisconnectEvent,
-    packet_handling::game::{
-        AddPlayerEvent, DeathEvent, KeepAliveEvent, PacketEvent, RemovePlayerEvent,
-        UpdatePlayerEvent,
+    disconnect::{
+        disconnect_on_connection_dead, remove_components_from_disconnected_players, DisconnectEvent,
+    },
+    local_player::{GameTime, LastDamageSource, Weather},
+    packet_handling::{
+        game::{
+            AddPlayerEvent, DeathEvent, GameStateChange, GameStateChangeEvent, KeepAliveEvent,
+            PacketEvent, ParticleEvent, PlaySoundEvent, RemovePlayerEvent, TimeUpdateEvent,
+            UpdatePlayerEvent, WeatherChangeEvent,
+        },
+        UnknownPacketEvent,
     },
     PlayerInfo,
 };
@@ -96,11 +111,119 @@ pub enum Event {
     /// name, or latency changed).
     UpdatePlayer(PlayerInfo),
     /// The client player died in-game.
+    ///
+    /// This is followed by an [`Event::DeathInfo`] with more information about
+    /// how and where we died.
     Death(Option<Arc<ClientboundPlayerCombatKill>>),
+    /// Information about how and where the client player died. This is sent
+    /// right after [`Event::Death`].
+    DeathInfo(DeathInfo),
+    /// A sound was played near us. This includes sounds that were played by
+    /// an entity, like mob noises.
+    Sound(SoundInfo),
+    /// Particles were spawned near us.
+    Particle(ClientboundLevelParticles),
+    /// The server told us what time it is in the world. This happens about
+    /// once a second.
+    TimeUpdate(GameTime),
+    /// The rain or thunder level changed. This happens every tick while it's
+    /// starting or stopping raining, so you might want to check
+    /// [`Weather::is_raining`] to see if it actually started or stopped.
+    WeatherChange(Weather),
+    /// The server sent us a game event, like the credits being shown or one
+    /// of our arrows hitting a player.
+    GameStateChange(GameStateChange),
     /// A `KeepAlive` packet was sent by the server.
     KeepAlive(u64),
+    /// The server sent us a packet with an id that Azalea doesn't know about,
+    /// which usually means that the server is modded.
+    ///
+    /// You can send your own packets with [`Client::write_raw_packet`].
+    ///
+    /// [`Client::write_raw_packet`]: crate::Client::write_raw_packet
+    UnknownPacket {
+        /// The state that the connection was in, which is either
+        /// configuration or game.
+        state: ConnectionProtocol,
+        id: u32,
+        /// The contents of the packet, not including the id.
+        bytes: Box<[u8]>,
+    },
     /// The client disconnected from the server.
+    ///
+    /// This contains the reason that the server gave if we were kicked, which
+    /// you can use to tell apart things like the server restarting and being
+    /// banned. It's `None` if the connection was lost without a reason, or if
+    /// we disconnected ourselves.
     Disconnect(Option<FormattedText>),
+    /// We're about to try rejoining the server after getting disconnected.
+    ///
+    /// This is only sent if a reconnect policy was set on the `ClientBuilder`
+    /// or `SwarmBuilder`.
+    Reconnecting {
+        /// The number of times we've already failed to rejoin, starting at 0.
+        attempt: u32,
+        /// How long we're waiting before trying to join.
+        delay: Duration,
+    },
+    /// The server sent us to another server, and we joined it.
+    ///
+    /// This is sent after the [`Event::Disconnect`] from the old server.
+    /// Swarms and clients made with `Client::join` follow transfers, see
+    /// [`crate::transfer`] for more details.
+    Transferred {
+        /// The address of the server that we were sent to.
+        address: ServerAddress,
+    },
+}
+
+/// A sound that was played, sent in [`Event::Sound`].
+#[derive(Debug, Clone)]
+pub struct SoundInfo {
+    /// The sound that was played. This is usually a [`SoundEvent`], but the
+    /// server can also send custom sounds from resource packs.
+    pub sound: Holder<SoundEvent, CustomSound>,
+    /// The category of the sound, which is used for volume sliders in vanilla.
+    pub source: SoundSource,
+    /// Where the sound was played.
+    pub position: Vec3,
+    pub volume: f32,
+    pub pitch: f32,
+    /// The entity that made the sound, if the server told us about it.
+    pub source_entity: Option<Entity>,
+}
+
+/// Information about how and where our player died, sent in
+/// [`Event::DeathInfo`].
+#[derive(Debug, Clone)]
+pub struct DeathInfo {
+    /// The message shown on the death screen, like "Player was slain by
+    /// Zombie". This is `None` if the server didn't send a death screen.
+    pub message: Option<FormattedText>,
+    /// The entity that was responsible for our death, if any.
+    ///
+    /// This comes from the last damage event that the server sent us, so it
+    /// might not always be accurate (for example if the entity that killed us
+    /// was already despawned).
+    pub killer: Option<Entity>,
+    /// Our position when we died.
+    pub position: Vec3,
+    /// The packet that the server sent to show the death screen, if any.
+    pub packet: Option<Arc<ClientboundPlayerCombatKill>>,
+}
+impl DeathInfo {
+    pub fn new(
+        packet: Option<ClientboundPlayerCombatKill>,
+        position: Vec3,
+        last_damage_source: Option<&LastDamageSource>,
+    ) -> Self {
+        Self {
+            message: packet.as_ref().map(|p| p.message.clone()),
+            killer: last_damage_source.and_then(|s| s.cause.or(s.direct)),
+            position,
+            packet: packet.map(Arc::new),
+        }
+    }
 }
 
 /// A component that contains an event sender for events that are only
@@ -124,10 +247,22 @@ impl Plugin for EventPlugin {
                 update_player_listener,
                 remove_player_listener,
                 keepalive_listener,
+                unknown_packet_listener,
+                sound_listener,
+                particle_listener,
+                time_update_listener,
+                weather_change_listener,
+                game_state_change_listener,
                 death_listener,
-                disconnect_listener,
             ),
         )
+        .add_systems(
+            PostUpdate,
+            // this has to run before the LocalPlayerEvents component is removed
+            disconnect_listener
+                .after(disconnect_on_connection_dead)
+                .before(remove_components_from_disconnected_players),
+        )
         .add_systems(
             PreUpdate,
             init_listener.before(crate::packet_handling::game::process_packet_events),
@@ -152,10 +287,9 @@ pub fn login_listener(query: Query<&LocalPlayerEvents, Added<MinecraftEntityId>>
 
 pub fn chat_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<ChatReceivedEvent>) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive chat events");
-        let _ = local_player_events.send(Event::Chat(event.packet.clone()));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::Chat(event.packet.clone()));
+        }
     }
 }
 
@@ -168,10 +302,9 @@ pub fn tick_listener(query: Query<&LocalPlayerEvents, With<InstanceName>>) {
 
 pub fn packet_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<PacketEvent>) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive packet events");
-        let _ = local_player_events.send(Event::Packet(event.packet.clone()));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::Packet(event.packet.clone()));
+        }
     }
 }
 
@@ -180,10 +313,9 @@ pub fn add_player_listener(
     mut events: EventReader<AddPlayerEvent>,
 ) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive add player events");
-        let _ = local_player_events.send(Event::AddPlayer(event.info.clone()));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::AddPlayer(event.info.clone()));
+        }
     }
 }
 
@@ -192,10 +324,9 @@ pub fn update_player_listener(
     mut events: EventReader<UpdatePlayerEvent>,
 ) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive update player events");
-        let _ = local_player_events.send(Event::UpdatePlayer(event.info.clone()));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::UpdatePlayer(event.info.clone()));
+        }
     }
 }
 
@@ -204,17 +335,24 @@ pub fn remove_player_listener(
     mut events: EventReader<RemovePlayerEvent>,
 ) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive remove player events");
-        let _ = local_player_events.send(Event::RemovePlayer(event.info.clone()));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::RemovePlayer(event.info.clone()));
+        }
     }
 }
 
-pub fn death_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<DeathEvent>) {
+pub fn death_listener(
+    query: Query<(&LocalPlayerEvents, &Position, Option<&LastDamageSource>)>,
+    mut events: EventReader<DeathEvent>,
+) {
     for event in events.read() {
-        if let Ok(local_player_events) = query.get(event.entity) {
+        if let Ok((local_player_events, position, last_damage_source)) = query.get(event.entity) {
             let _ = local_player_events.send(Event::Death(event.packet.clone().map(|p| p.into())));
+            let _ = local_player_events.send(Event::DeathInfo(DeathInfo::new(
+                event.packet.clone(),
+                **position,
+                last_damage_source,
+            )));
         }
     }
 }
@@ -224,10 +362,73 @@ pub fn keepalive_listener(
     mut events: EventReader<KeepAliveEvent>,
 ) {
     for event in events.read() {
-        let local_player_events = query
-            .get(event.entity)
-            .expect("Non-local entities shouldn't be able to receive keepalive events");
-        let _ = local_player_events.send(Event::KeepAlive(event.id));
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::KeepAlive(event.id));
+        }
+    }
+}
+
+pub fn unknown_packet_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<UnknownPacketEvent>,
+) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::UnknownPacket {
+                state: event.state,
+                id: event.id,
+                bytes: event.bytes.clone(),
+            });
+        }
+    }
+}
+
+pub fn sound_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<PlaySoundEvent>) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::Sound(event.sound.clone()));
+        }
+    }
+}
+
+pub fn particle_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<ParticleEvent>) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::Particle(event.packet.clone()));
+        }
+    }
+}
+
+pub fn time_update_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<TimeUpdateEvent>,
+) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::TimeUpdate(event.time.clone()));
+        }
+    }
+}
+
+pub fn weather_change_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<WeatherChangeEvent>,
+) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::WeatherChange(event.new.clone()));
+        }
+    }
+}
+
+pub fn game_state_change_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<GameStateChangeEvent>,
+) {
+    for event in events.read() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            let _ = local_player_events.send(Event::GameStateChange(event.change));
+        }
     }
 }
 
@@ -241,3 +442,36 @@ pub fn disconnect_listener(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{event::Events, schedule::Schedule, world::World};
+
+    use super::*;
+
+    #[test]
+    fn test_unknown_packet_event() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<UnknownPacketEvent>>();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let entity = ecs.spawn(LocalPlayerEvents(sender)).id();
+
+        ecs.send_event(UnknownPacketEvent {
+            entity,
+            state: ConnectionProtocol::Game,
+            id: 0x80,
+            bytes: vec![1, 2, 3].into_boxed_slice(),
+        });
+        let mut schedule = Schedule::default();
+        schedule.add_systems(unknown_packet_listener);
+        schedule.run(&mut ecs);
+
+        let Ok(Event::UnknownPacket { state, id, bytes }) = receiver.try_recv() else {
+            panic!("expected an unknown packet event");
+        };
+        assert_eq!(state, ConnectionProtocol::Game);
+        assert_eq!(id, 0x80);
+        assert_eq!(&*bytes, &[1, 2, 3]);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -8,16 +8,18 @@ use azalea_core::{
     position::{BlockPos, Vec3},
 };
 use azalea_entity::{
-    clamp_look_direction, view_vector, Attributes, EyeHeight, LocalEntity, LookDirection, Position,
+    clamp_look_direction, metadata::ShiftKeyDown, view_vector, Attributes, EyeHeight, LocalEntity,
+    LookDirection, Physics, Position,
 };
 use azalea_inventory::{components, ItemStack, ItemStackData};
 use azalea_physics::clip::{BlockShapeType, ClipContext, FluidPickType};
 use azalea_protocol::packets::game::{
-    s_interact::InteractionHand,
+    s_interact::{self, InteractionHand, ServerboundInteract},
     s_swing::ServerboundSwing,
+    s_use_item::ServerboundUseItem,
     s_use_item_on::{BlockHit, ServerboundUseItemOn},
 };
-use azalea_world::{Instance, InstanceContainer, InstanceName};
+use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId};
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::{
     component::Component,
@@ -45,6 +47,8 @@ pub struct InteractPlugin;
 impl Plugin for InteractPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BlockInteractEvent>()
+            .add_event::<StartUseItemEvent>()
+            .add_event::<EntityInteractEvent>()
             .add_event::<SwingArmEvent>()
             .add_systems(
                 Update,
@@ -52,6 +56,8 @@ impl Plugin for InteractPlugin {
                     (
                         update_hit_result_component.after(clamp_look_direction),
                         handle_block_interact_event,
+                        handle_start_use_item_event,
+                        handle_entity_interact_event,
                         handle_swing_arm_event,
                     )
                         .before(handle_send_packet_event)
@@ -80,6 +86,149 @@ impl Client {
             position,
         });
     }
+
+    /// Right click an entity with the item in the given hand, like to trade
+    /// with a villager or to make a pet sit.
+    ///
+    /// What this does depends on the entity and the item that we're holding.
+    /// We'll be sneaking while interacting if [`ShiftKeyDown`] is set, which
+    /// changes what some interactions do.
+    pub fn interact_entity(&mut self, entity: Entity, hand: InteractionHand) {
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand,
+            location: None,
+        });
+    }
+
+    /// Right click a specific part of an entity with the item in the given
+    /// hand. The location is relative to the entity's position.
+    ///
+    /// This matters for a few entities like armor stands, where the location
+    /// decides which armor slot we take the item from.
+    pub fn interact_entity_at(&mut self, entity: Entity, location: Vec3, hand: InteractionHand) {
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand,
+            location: Some(location),
+        });
+    }
+
+    /// Right click with the item in our main hand, without targeting a block.
+    /// This is used for things like eating, drinking, and throwing items.
+    pub fn start_use_item(&mut self) {
+        self.ecs.lock().send_event(StartUseItemEvent {
+            entity: self.entity,
+            hand: InteractionHand::MainHand,
+        });
+    }
+}
+
+/// Right click an entity. See [`Client::interact_entity`].
+#[derive(Event)]
+pub struct EntityInteractEvent {
+    /// The local player entity that's doing the interaction.
+    pub entity: Entity,
+    /// The entity that we're right clicking.
+    pub target: Entity,
+    pub hand: InteractionHand,
+    /// Where on the entity we clicked, relative to its position. If this is
+    /// `None`, it's calculated from where we're looking.
+    pub location: Option<Vec3>,
+}
+pub fn handle_entity_interact_event(
+    mut events: EventReader<EntityInteractEvent>,
+    player_query: Query<(&Position, &EyeHeight, &LookDirection, Option<&ShiftKeyDown>)>,
+    target_query: Query<(&MinecraftEntityId, &Position, Option<&Physics>)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok((position, eye_height, look_direction, sneaking)) = player_query.get(event.entity)
+        else {
+            warn!("Sent EntityInteractEvent for entity that doesn't have the required components");
+            continue;
+        };
+        let Ok((target_id, target_position, target_physics)) = target_query.get(event.target)
+        else {
+            warn!(
+                "Tried to interact with {:?} but it doesn't have a MinecraftEntityId",
+                event.target
+            );
+            continue;
+        };
+        let sneaking = sneaking.is_some_and(|sneaking| **sneaking);
+
+        // like vanilla, we send an InteractAt with where on the entity we clicked and
+        // then a normal Interact. the location is relative to the entity's position.
+        let location = event.location.unwrap_or_else(|| {
+            let eye_position = **position + Vec3::new(0., **eye_height as f64, 0.);
+            let hit_location = target_physics
+                .map(|physics| {
+                    let end_position = eye_position + view_vector(look_direction) * 6.;
+                    physics
+                        .bounding_box
+                        .clip(&eye_position, &end_position)
+                        // if we're not looking at the entity then pretend we clicked the middle
+                        .unwrap_or_else(|| physics.bounding_box.get_center())
+                })
+                .unwrap_or(**target_position);
+            hit_location - **target_position
+        });
+
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundInteract {
+                entity_id: **target_id,
+                action: s_interact::ActionType::InteractAt {
+                    location,
+                    hand: event.hand,
+                },
+                using_secondary_action: sneaking,
+            },
+        ));
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundInteract {
+                entity_id: **target_id,
+                action: s_interact::ActionType::Interact { hand: event.hand },
+                using_secondary_action: sneaking,
+            },
+        ));
+    }
+}
+
+/// Right click with the item in our hand without targeting a block, like when
+/// eating food or throwing an ender pearl.
+#[derive(Event)]
+pub struct StartUseItemEvent {
+    pub entity: Entity,
+    pub hand: InteractionHand,
+}
+pub fn handle_start_use_item_event(
+    mut events: EventReader<StartUseItemEvent>,
+    mut query: Query<(&mut CurrentSequenceNumber, &LookDirection)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok((mut sequence_number, look_direction)) = query.get_mut(event.entity) else {
+            warn!("Sent StartUseItemEvent for entity that doesn't have the required components");
+            continue;
+        };
+
+        *sequence_number += 1;
+
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundUseItem {
+                hand: event.hand,
+                sequence: sequence_number.0,
+                yaw: look_direction.y_rot,
+                pitch: look_direction.x_rot,
+            },
+        ));
+    }
 }
 
 /// Right click a block. The behavior of this depends on the target block,
@@ -10,7 +10,8 @@ use azalea_inventory::{
     },
 };
 use azalea_protocol::packets::game::{
-    s_container_click::ServerboundContainerClick, s_container_close::ServerboundContainerClose,
+    c_merchant_offers::MerchantOffer, s_container_click::ServerboundContainerClick,
+    s_container_close::ServerboundContainerClose, s_select_trade::ServerboundSelectTrade,
     s_set_carried_item::ServerboundSetCarriedItem,
 };
 use azalea_registry::MenuKind;
@@ -21,7 +22,7 @@ use bevy_ecs::{
     event::EventReader,
     prelude::{Event, EventWriter},
     schedule::{IntoSystemConfigs, SystemSet},
-    system::Query,
+    system::{Commands, Query},
 };
 use tracing::warn;
 
@@ -41,12 +42,14 @@ impl Plugin for InventoryPlugin {
             .add_event::<ContainerClickEvent>()
             .add_event::<SetContainerContentEvent>()
             .add_event::<SetSelectedHotbarSlotEvent>()
+            .add_event::<SelectTradeEvent>()
             .add_systems(
                 Update,
                 (
                     handle_set_selected_hotbar_slot_event,
                     handle_menu_opened_event,
                     handle_set_container_content_event,
+                    handle_select_trade_event,
                     handle_container_click_event,
                     handle_container_close_event.before(handle_send_packet_event),
                     handle_client_side_close_container_event,
@@ -593,7 +596,9 @@ fn handle_menu_opened_event(
     mut query: Query<&mut Inventory>,
 ) {
     for event in events.read() {
-        let mut inventory = query.get_mut(event.entity).unwrap();
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
         inventory.id = event.window_id;
         inventory.container_menu = Some(Menu::from_kind(event.menu_type));
         inventory.container_menu_title = Some(event.title.clone());
@@ -618,7 +623,9 @@ fn handle_container_close_event(
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
     for event in events.read() {
-        let (entity, inventory) = query.get(event.entity).unwrap();
+        let Ok((entity, inventory)) = query.get(event.entity) else {
+            continue;
+        };
         if event.id != inventory.id {
             warn!(
                 "Tried to close container with ID {}, but the current container ID is {}",
@@ -649,12 +656,60 @@ pub struct ClientSideCloseContainerEvent {
 pub fn handle_client_side_close_container_event(
     mut events: EventReader<ClientSideCloseContainerEvent>,
     mut query: Query<&mut Inventory>,
+    mut commands: Commands,
 ) {
     for event in events.read() {
-        let mut inventory = query.get_mut(event.entity).unwrap();
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
         inventory.container_menu = None;
         inventory.id = 0;
         inventory.container_menu_title = None;
+        commands.entity(event.entity).remove::<MerchantOffers>();
+    }
+}
+
+/// A component present on local players that have a villager's (or wandering
+/// trader's) trading menu open. It contains the trades that are being offered.
+///
+/// This gets removed when the menu is closed.
+#[derive(Component, Debug, Clone)]
+pub struct MerchantOffers {
+    /// The ID of the merchant menu that these offers are for.
+    pub container_id: i32,
+    pub offers: Vec<MerchantOffer>,
+    /// The level of the villager, from 1 (novice) to 5 (master). This is 0 for
+    /// wandering traders.
+    pub villager_level: u32,
+    pub villager_xp: u32,
+}
+
+/// Pick one of the trades in the merchant menu that we have open. The server
+/// will move the items needed for the trade from our inventory into the
+/// payment slots, and the result will show up in the result slot (slot 2).
+#[derive(Event, Debug)]
+pub struct SelectTradeEvent {
+    pub entity: Entity,
+    /// The index of the trade in [`MerchantOffers::offers`].
+    pub index: u32,
+}
+fn handle_select_trade_event(
+    mut events: EventReader<SelectTradeEvent>,
+    query: Query<&Inventory>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok(inventory) = query.get(event.entity) else {
+            continue;
+        };
+        if !matches!(inventory.container_menu, Some(Menu::Merchant { .. })) {
+            warn!("Tried to select a trade without having a merchant menu open");
+            continue;
+        }
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundSelectTrade { item: event.index },
+        ));
     }
 }
 
@@ -670,7 +725,9 @@ pub fn handle_container_click_event(
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
     for event in events.read() {
-        let (entity, mut inventory) = query.get_mut(event.entity).unwrap();
+        let Ok((entity, mut inventory)) = query.get_mut(event.entity) else {
+            continue;
+        };
         if inventory.id != event.window_id {
             warn!(
                 "Tried to click container with ID {}, but the current container ID is {}",
@@ -722,7 +779,9 @@ fn handle_set_container_content_event(
     mut query: Query<&mut Inventory>,
 ) {
     for event in events.read() {
-        let mut inventory = query.get_mut(event.entity).unwrap();
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
 
         if event.container_id != inventory.id {
             warn!(
@@ -753,7 +812,9 @@ fn handle_set_selected_hotbar_slot_event(
     mut query: Query<&mut Inventory>,
 ) {
     for event in events.read() {
-        let mut inventory = query.get_mut(event.entity).unwrap();
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
 
         // if the slot is already selected, don't send a packet
         if inventory.selected_hotbar_slot == event.slot {
@@ -9,36 +9,66 @@
 #![feature(error_generic_member_access)]
 
 mod account;
+pub mod action_queue;
 pub mod attack;
+pub mod block_effects;
+pub mod bungeecord_forwarding;
 pub mod chat;
+pub mod chat_history;
+pub mod chat_signing;
 pub mod chunks;
 mod client;
+pub mod component_watch;
 pub mod configuration;
 pub mod disconnect;
+pub mod elytra;
+pub mod ender_chest;
 mod entity_query;
+pub mod error;
 pub mod events;
 pub mod interact;
 pub mod inventory;
 mod local_player;
+pub mod maps;
 pub mod mining;
 pub mod movement;
+pub mod movement_arbiter;
 pub mod packet_handling;
+pub mod packet_logger;
+pub mod packet_middleware;
+pub mod packet_replay;
 pub mod ping;
 mod player;
+pub mod plugin_channels;
+pub mod proxy_protocol;
 pub mod raw_connection;
+pub mod replay_mod;
 pub mod respawn;
 pub mod send_client_end;
+pub mod server_commands;
+pub mod session_stats;
+pub mod spectator;
 pub mod task_pool;
+pub mod tps;
+pub mod transfer;
+pub mod vehicle;
+pub mod velocity_forwarding;
+pub mod world_audit;
+pub mod world_download;
 
 pub use account::{Account, AccountOpts};
 pub use azalea_protocol::common::client_information::ClientInformation;
 pub use client::{
-    start_ecs_runner, Client, DefaultPlugins, InConfigState, JoinError, JoinedClientBundle,
-    LocalPlayerBundle, StartClientOpts, TickBroadcast,
+    start_ecs_runner, Client, ClientSocketOptions, DefaultPlugins, InConfigState, JoinError,
+    JoinedClientBundle, LocalPlayerBundle, StartClientOpts, TickBroadcast,
 };
+pub use error::ClientError;
 pub use events::Event;
-pub use local_player::{GameProfileComponent, Hunger, InstanceHolder, TabList};
+pub use local_player::{
+    Experience, GameProfileComponent, GameTime, Hunger, InstanceHolder, LastDamageSource,
+    MeasurePing, Ping, ShareInstances, TabList, TabListHeader, Weather, WorldBorder,
+};
 pub use movement::{
-    PhysicsState, SprintDirection, StartSprintEvent, StartWalkEvent, WalkDirection,
+    PhysicsState, SprintDirection, StartSprintEvent, StartWalkEvent, SwimDirection, WalkDirection,
 };
 pub use player::PlayerInfo;
@@ -1,8 +1,9 @@
-use std::{collections::HashMap, io, sync::Arc};
+use std::{collections::HashMap, io, sync::Arc, time::Duration};
 
 use azalea_auth::game_profile::GameProfile;
-use azalea_core::game_type::GameMode;
-use azalea_entity::Dead;
+use azalea_chat::FormattedText;
+use azalea_core::{game_type::GameMode, position::Vec3, resource_location::ResourceLocation};
+use azalea_entity::{Dead, Position};
 use azalea_protocol::packets::game::c_player_abilities::ClientboundPlayerAbilities;
 use azalea_world::{Instance, PartialInstance};
 use bevy_ecs::{component::Component, prelude::*};
@@ -14,7 +15,7 @@ use tracing::error;
 use uuid::Uuid;
 
 use crate::{
-    events::{Event as AzaleaEvent, LocalPlayerEvents},
+    events::{DeathInfo, Event as AzaleaEvent, LocalPlayerEvents},
     ClientInformation, PlayerInfo,
 };
 
@@ -40,6 +41,38 @@ pub struct InstanceHolder {
     pub instance: Arc<RwLock<Instance>>,
 }
 
+/// Whether local players that are in the same world share an [`Instance`],
+/// which is the default.
+///
+/// If this is false, every local player gets its own instance with only the
+/// chunks and entities that it was sent, and its [`InstanceName`] will have
+/// its username added to the end (like `minecraft:overworld/bot0`).
+///
+/// [`InstanceName`]: azalea_world::InstanceName
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct ShareInstances(pub bool);
+
+impl Default for ShareInstances {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+impl ShareInstances {
+    /// Get the name of the instance that a local player with the given
+    /// username should be in when it's in the given dimension.
+    pub fn instance_name(&self, dimension: &ResourceLocation, username: &str) -> ResourceLocation {
+        if self.0 {
+            dimension.clone()
+        } else {
+            ResourceLocation {
+                namespace: dimension.namespace.clone(),
+                path: format!("{}/{username}", dimension.path),
+            }
+        }
+    }
+}
+
 /// A component only present in players that contains the [`GameProfile`] (which
 /// you can use to get a player's name).
 ///
@@ -91,18 +124,36 @@ pub struct PermissionLevel(pub u8);
 /// A component that contains a map of player UUIDs to their information in the
 /// tab list.
 ///
+/// The header and footer of the tab list are stored separately in
+/// [`TabListHeader`].
+///
 /// ```
 /// # use azalea_client::TabList;
 /// # fn example(client: &azalea_client::Client) {
 /// let tab_list = client.component::<TabList>();
 /// println!("Online players:");
 /// for (uuid, player_info) in tab_list.iter() {
-///     println!("- {} ({}ms)", player_info.profile.name, player_info.latency);
+///     println!(
+///         "- {} ({:?}, {}ms)",
+///         player_info.profile.name, player_info.gamemode, player_info.latency
+///     );
 /// }
 /// # }
 #[derive(Component, Resource, Clone, Debug, Deref, DerefMut, Default)]
 pub struct TabList(HashMap<Uuid, PlayerInfo>);
 
+/// A component that contains the text that's shown above and below the list of
+/// players in the tab list.
+#[derive(Component, Clone, Debug, Default)]
+pub struct TabListHeader {
+    /// The text that's shown above the list of players. Servers with queues
+    /// (and proxies) often put information like your position in the queue
+    /// here.
+    pub header: FormattedText,
+    /// The text that's shown below the list of players.
+    pub footer: FormattedText,
+}
+
 #[derive(Component, Clone)]
 pub struct Hunger {
     /// The main hunger bar. Goes from 0 to 20.
@@ -122,6 +173,133 @@ impl Default for Hunger {
     }
 }
 
+/// The experience level and progress of a local player.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Experience {
+    /// The current level, as shown above the experience bar.
+    pub level: u32,
+    /// How far we are towards the next level, from 0 to 1.
+    pub progress: f32,
+    /// The total amount of experience points that we've collected since our
+    /// last death.
+    pub total: u32,
+}
+
+/// How long it takes for packets to get from a local player to the server and
+/// back.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Ping {
+    /// The round trip time that we measured ourselves. This is only set if
+    /// the local player has the [`MeasurePing`] component.
+    pub measured: Option<Duration>,
+    /// The latency that the server shows for us in the tab list. This is
+    /// measured by the server from its keep-alives, and some servers change
+    /// it or never send it.
+    pub tab_list: Option<Duration>,
+}
+
+/// A marker component that makes a local player measure its own ping by sending
+/// the server a ping request every time it gets a keep-alive (about every 15
+/// seconds), which updates [`Ping::measured`].
+///
+/// Vanilla clients only send ping requests while the ping graph in the debug
+/// screen is open, so servers could use this to tell that we're a bot. Without
+/// this, only the latency from the tab list is used.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MeasurePing;
+
+impl Ping {
+    /// The best estimate of our ping that we have, preferring the one that we
+    /// measured ourselves.
+    pub fn get(&self) -> Option<Duration> {
+        self.measured.or(self.tab_list)
+    }
+}
+
+/// The world border that the server sent to a local player.
+///
+/// This is only present after we receive an `InitializeBorder` packet. If the
+/// border is currently moving, the size here is the size that it's moving
+/// towards.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// The length of one side of the border, in blocks.
+    pub size: f64,
+}
+
+impl WorldBorder {
+    /// Whether the given position is inside of the world border.
+    pub fn contains(&self, position: Vec3) -> bool {
+        let half_size = self.size / 2.;
+        (position.x - self.center_x).abs() < half_size
+            && (position.z - self.center_z).abs() < half_size
+    }
+}
+
+/// The time in the world that a local player is in.
+///
+/// This is only present after we receive a `SetTime` packet, which the server
+/// sends about once a second.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct GameTime {
+    /// The number of ticks since the world was created.
+    pub world_age: u64,
+    /// The number of ticks since the first day started. This doesn't reset
+    /// every day, use [`Self::time_of_day`] if you want that.
+    pub day_time: u64,
+    /// Whether the time of day is moving forward. This is false if the
+    /// `doDaylightCycle` gamerule is disabled.
+    pub ticking: bool,
+}
+
+impl GameTime {
+    /// The number of ticks since the start of the current day, from 0 to
+    /// 23999. 0 is sunrise, 6000 is noon, 12000 is sunset, and 18000 is
+    /// midnight.
+    pub fn time_of_day(&self) -> u64 {
+        self.day_time % 24000
+    }
+
+    /// The number of days that have passed since the first day.
+    pub fn day(&self) -> u64 {
+        self.day_time / 24000
+    }
+
+    /// Whether players are allowed to sleep in beds right now (ignoring the
+    /// weather).
+    pub fn can_sleep(&self) -> bool {
+        (12542..23460).contains(&self.time_of_day())
+    }
+}
+
+/// The weather in the world that a local player is in.
+///
+/// The rain and thunder levels change gradually when it starts or stops
+/// raining, so you should usually use [`Self::is_raining`] and
+/// [`Self::is_thundering`] instead of checking the levels directly.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Weather {
+    /// How heavy the rain is, from 0 to 1.
+    pub rain_level: f32,
+    /// How heavy the thunderstorm is, from 0 to 1. Thunderstorms only happen
+    /// while it's also raining.
+    pub thunder_level: f32,
+}
+
+impl Weather {
+    /// Whether it's raining, using the same threshold as vanilla.
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > 0.2
+    }
+
+    /// Whether there's a thunderstorm, using the same threshold as vanilla.
+    pub fn is_thundering(&self) -> bool {
+        self.rain_level * self.thunder_level > 0.9
+    }
+}
+
 impl InstanceHolder {
     /// Create a new `InstanceHolder` for the given entity.
     ///
@@ -143,10 +321,32 @@ impl InstanceHolder {
     }
 }
 
+/// The last thing that damaged our local player, from the most recent
+/// `ClientboundDamageEvent` that was about us.
+///
+/// This is removed when we respawn.
+#[derive(Component, Clone, Debug, Default)]
+pub struct LastDamageSource {
+    /// The entity that was responsible for the damage, like the player that
+    /// shot an arrow.
+    pub cause: Option<Entity>,
+    /// The entity that directly did the damage, like the arrow.
+    pub direct: Option<Entity>,
+    /// The position that the damage came from, if it wasn't from an entity.
+    pub position: Option<Vec3>,
+}
+
 /// Send the "Death" event for [`LocalEntity`]s that died with no reason.
-pub fn death_event(query: Query<&LocalPlayerEvents, Added<Dead>>) {
-    for local_player_events in &query {
-        local_player_events.send(AzaleaEvent::Death(None)).unwrap();
+pub fn death_event(
+    query: Query<(&LocalPlayerEvents, &Position, Option<&LastDamageSource>), Added<Dead>>,
+) {
+    for (local_player_events, position, last_damage_source) in &query {
+        let _ = local_player_events.send(AzaleaEvent::Death(None));
+        let _ = local_player_events.send(AzaleaEvent::DeathInfo(DeathInfo::new(
+            None,
+            **position,
+            last_damage_source,
+        )));
     }
 }
 
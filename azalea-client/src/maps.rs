@@ -0,0 +1,211 @@
+//! Keep track of the contents of maps (the item), and convert them to images.
+
+use std::collections::HashMap;
+
+use azalea_protocol::packets::game::c_map_item_data::{
+    ClientboundMapItemData, MapDecoration, MapPatch,
+};
+pub use azalea_world::map_render::color_id_to_rgba;
+use bevy_ecs::component::Component;
+use derive_more::{Deref, DerefMut};
+
+use crate::Client;
+
+/// The width and height of every map, in pixels.
+pub const MAP_SIZE: usize = 128;
+
+/// A component present on local players that contains every map that the
+/// server has sent us, indexed by the map's ID.
+///
+/// The ID of a map item can be found in its `MapId` data component.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct Maps(pub HashMap<u32, MapData>);
+
+/// The contents of a map.
+#[derive(Clone, Debug)]
+pub struct MapData {
+    /// How zoomed out the map is, from 0 (1 block per pixel) to 4 (16 blocks
+    /// per pixel).
+    pub scale: u8,
+    /// Whether the map was locked in a cartography table.
+    pub locked: bool,
+    /// The color IDs of each pixel in the map, row by row. Use
+    /// [`Self::to_rgba`] to get the actual colors.
+    pub colors: Vec<u8>,
+    /// The icons on the map, like players and banners.
+    pub decorations: Vec<MapDecoration>,
+}
+impl Default for MapData {
+    fn default() -> Self {
+        Self {
+            scale: 0,
+            locked: false,
+            colors: vec![0; MAP_SIZE * MAP_SIZE],
+            decorations: Vec::new(),
+        }
+    }
+}
+
+impl MapData {
+    /// Update the map with the data from a `MapItemData` packet.
+    pub fn update(&mut self, packet: &ClientboundMapItemData) {
+        self.scale = packet.scale;
+        self.locked = packet.locked;
+        // if the decorations aren't sent then they didn't change
+        if let Some(decorations) = &packet.decorations {
+            self.decorations.clone_from(decorations);
+        }
+        if let Some(patch) = &packet.color_patch.0 {
+            self.apply_patch(patch);
+        }
+    }
+
+    /// Replace a rectangle of the map's colors.
+    pub fn apply_patch(&mut self, patch: &MapPatch) {
+        let width = patch.width as usize;
+        for y in 0..patch.height as usize {
+            for x in 0..width {
+                let map_x = patch.start_x as usize + x;
+                let map_y = patch.start_y as usize + y;
+                if map_x >= MAP_SIZE || map_y >= MAP_SIZE {
+                    continue;
+                }
+                if let Some(&color) = patch.map_colors.get(x + y * width) {
+                    self.colors[map_x + map_y * MAP_SIZE] = color;
+                }
+            }
+        }
+    }
+
+    /// Get the color of the pixel at the given coordinates, as `[r, g, b, a]`.
+    ///
+    /// Returns `None` if the coordinates are outside of the map.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[u8; 4]> {
+        if x >= MAP_SIZE || y >= MAP_SIZE {
+            return None;
+        }
+        self.colors
+            .get(x + y * MAP_SIZE)
+            .copied()
+            .map(color_id_to_rgba)
+    }
+
+    /// Convert the map to an RGBA image that's 128x128 pixels, with 4 bytes
+    /// per pixel. Transparent pixels (the parts of the map that haven't been
+    /// explored) have an alpha of 0.
+    ///
+    /// The result can be passed directly to most image libraries, like
+    /// `image::RgbaImage::from_raw(128, 128, map.to_rgba())`.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .flat_map(|&color| color_id_to_rgba(color))
+            .collect()
+    }
+}
+
+impl Client {
+    /// Get the contents of the map with the given ID, if the server has sent
+    /// it to us.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// if let Some(map) = bot.map(0) {
+    ///     let rgba = map.to_rgba();
+    ///     assert_eq!(rgba.len(), 128 * 128 * 4);
+    /// }
+    /// # }
+    /// ```
+    pub fn map(&self, map_id: u32) -> Option<MapData> {
+        self.get_component::<Maps>()
+            .and_then(|maps| maps.get(&map_id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::c_map_item_data::OptionalMapPatch;
+
+    use super::*;
+
+    fn packet(patch: Option<MapPatch>) -> ClientboundMapItemData {
+        ClientboundMapItemData {
+            map_id: 0,
+            scale: 2,
+            locked: true,
+            decorations: None,
+            color_patch: OptionalMapPatch(patch),
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let mut map = MapData::default();
+        assert_eq!(map.pixel(0, 0), Some([0, 0, 0, 0]));
+
+        map.update(&packet(Some(MapPatch {
+            width: 2,
+            height: 2,
+            start_x: 10,
+            start_y: 20,
+            map_colors: vec![4, 5, 6, 7],
+        })));
+        assert_eq!(map.scale, 2);
+        assert!(map.locked);
+        assert_eq!(map.colors[10 + 20 * MAP_SIZE], 4);
+        assert_eq!(map.colors[11 + 20 * MAP_SIZE], 5);
+        assert_eq!(map.colors[10 + 21 * MAP_SIZE], 6);
+        assert_eq!(map.colors[11 + 21 * MAP_SIZE], 7);
+        assert_eq!(map.pixel(11, 21), Some(color_id_to_rgba(7)));
+        assert_eq!(map.pixel(11, 21).unwrap()[3], 255);
+
+        // a packet without a patch doesn't change the colors
+        map.update(&packet(None));
+        assert_eq!(map.colors[10 + 20 * MAP_SIZE], 4);
+    }
+
+    #[test]
+    fn test_patch_past_edge() {
+        let mut map = MapData::default();
+        map.apply_patch(&MapPatch {
+            width: 4,
+            height: 1,
+            start_x: 126,
+            start_y: 127,
+            map_colors: vec![4, 5, 6, 7],
+        });
+        assert_eq!(map.colors[126 + 127 * MAP_SIZE], 4);
+        assert_eq!(map.colors[127 + 127 * MAP_SIZE], 5);
+        assert_eq!(map.colors.len(), MAP_SIZE * MAP_SIZE);
+
+        // patches with fewer colors than their size are ignored past the end
+        map.apply_patch(&MapPatch {
+            width: 2,
+            height: 2,
+            start_x: 0,
+            start_y: 0,
+            map_colors: vec![8],
+        });
+        assert_eq!(map.colors[0], 8);
+        assert_eq!(map.colors[1], 0);
+    }
+
+    #[test]
+    fn test_pixel_out_of_bounds() {
+        let map = MapData::default();
+        assert_eq!(map.pixel(127, 127), Some([0, 0, 0, 0]));
+        assert_eq!(map.pixel(128, 0), None);
+        assert_eq!(map.pixel(0, 128), None);
+    }
+
+    #[test]
+    fn test_to_rgba() {
+        let mut map = MapData::default();
+        map.colors[1] = 7;
+        let rgba = map.to_rgba();
+        assert_eq!(rgba.len(), MAP_SIZE * MAP_SIZE * 4);
+        assert_eq!(rgba[0..4], [0, 0, 0, 0]);
+        assert_eq!(rgba[4..8], color_id_to_rgba(7));
+    }
+}
@@ -1,6 +1,8 @@
 use azalea_block::{fluid_state::FluidState, Block, BlockState};
 use azalea_core::{direction::Direction, game_type::GameMode, position::BlockPos, tick::GameTick};
-use azalea_entity::{mining::get_mine_progress, FluidOnEyes, Physics};
+use azalea_entity::{
+    effects::ActiveEffects, mining::get_mine_progress, Attributes, FluidOnEyes, Physics,
+};
 use azalea_inventory::ItemStack;
 use azalea_physics::PhysicsSet;
 use azalea_protocol::packets::game::s_player_action::{self, ServerboundPlayerAction};
@@ -10,6 +12,7 @@ use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 
 use crate::{
+    block_effects::{BlockBreakEffectEvent, BlockEffectSource},
     interact::{
         can_use_game_master_blocks, check_is_interaction_restricted, CurrentSequenceNumber,
         HitResultComponent, SwingArmEvent,
@@ -17,7 +20,7 @@ use crate::{
     inventory::{Inventory, InventorySet},
     local_player::{LocalGameMode, PermissionLevel, PlayerAbilities},
     movement::MoveEventsSet,
-    packet_handling::game::SendPacketEvent,
+    packet_handling::game::{BlockUpdateEvent, SendPacketEvent},
     Client,
 };
 
@@ -159,7 +162,9 @@ fn handle_start_mining_block_event(
     mut query: Query<&HitResultComponent>,
 ) {
     for event in events.read() {
-        let hit_result = query.get_mut(event.entity).unwrap();
+        let Ok(hit_result) = query.get_mut(event.entity) else {
+            continue;
+        };
         let direction = if hit_result.block_pos == event.position {
             // we're looking at the block
             hit_result.direction
@@ -194,6 +199,8 @@ fn handle_start_mining_block_with_direction_event(
         &Inventory,
         &FluidOnEyes,
         &Physics,
+        &Attributes,
+        &ActiveEffects,
         Option<&Mining>,
         &mut CurrentSequenceNumber,
         &mut MineDelay,
@@ -206,12 +213,14 @@ fn handle_start_mining_block_with_direction_event(
     mut commands: Commands,
 ) {
     for event in events.read() {
-        let (
+        let Ok((
             instance_name,
             game_mode,
             inventory,
             fluid_on_eyes,
             physics,
+            attributes,
+            active_effects,
             mining,
             mut sequence_number,
             mut mine_delay,
@@ -219,9 +228,14 @@ fn handle_start_mining_block_with_direction_event(
             mut mine_ticks,
             mut current_mining_item,
             mut current_mining_pos,
-        ) = query.get_mut(event.entity).unwrap();
+        )) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
 
-        let instance_lock = instances.get(instance_name).unwrap();
+        let Some(instance_lock) = instances.get(instance_name) else {
+            continue;
+        };
         let instance = instance_lock.read();
         if check_is_interaction_restricted(
             &instance,
@@ -300,6 +314,8 @@ fn handle_start_mining_block_with_direction_event(
                     &inventory.inventory_menu,
                     fluid_on_eyes,
                     physics,
+                    attributes,
+                    active_effects,
                 ) >= 1.
             {
                 // block was broken instantly
@@ -425,11 +441,24 @@ pub fn handle_finish_mining_block_event(
         &mut CurrentSequenceNumber,
     )>,
     instances: Res<InstanceContainer>,
+    mut block_break_effect_events: EventWriter<BlockBreakEffectEvent>,
+    mut block_update_events: EventWriter<BlockUpdateEvent>,
 ) {
     for event in events.read() {
-        let (instance_name, game_mode, inventory, abilities, permission_level, _sequence_number) =
-            query.get_mut(event.entity).unwrap();
-        let instance_lock = instances.get(instance_name).unwrap();
+        let Ok((
+            instance_name,
+            game_mode,
+            inventory,
+            abilities,
+            permission_level,
+            _sequence_number,
+        )) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
+        let Some(instance_lock) = instances.get(instance_name) else {
+            continue;
+        };
         let instance = instance_lock.read();
         if check_is_interaction_restricted(
             &instance,
@@ -468,10 +497,28 @@ pub fn handle_finish_mining_block_event(
             continue;
         }
 
+        block_break_effect_events.send(BlockBreakEffectEvent {
+            entity: event.entity,
+            position: event.position,
+            block_state,
+            source: BlockEffectSource::Local,
+        });
+
         // when we break a waterlogged block we want to keep the water there
         let fluid_state = FluidState::from(block_state);
         let block_state_for_fluid = BlockState::from(fluid_state);
         instance.set_block_state(&event.position, block_state_for_fluid);
+        // the server's block update won't change anything since we already
+        // removed the block, so the event is sent now
+        if block_state_for_fluid != block_state {
+            block_update_events.send(BlockUpdateEvent {
+                entity: event.entity,
+                instance: instance_name.clone(),
+                pos: event.position,
+                old: block_state,
+                new: block_state_for_fluid,
+            });
+        }
     }
 }
 
@@ -488,7 +535,10 @@ pub fn handle_stop_mining_block_event(
     mut commands: Commands,
 ) {
     for event in events.read() {
-        let (mut _mining, mine_block_pos, mut mine_progress) = query.get_mut(event.entity).unwrap();
+        let Ok((mut _mining, mine_block_pos, mut mine_progress)) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
 
         let mine_block_pos =
             mine_block_pos.expect("IsMining is true so MineBlockPos must be present");
@@ -522,6 +572,8 @@ pub fn continue_mining_block(
         &MineItem,
         &FluidOnEyes,
         &Physics,
+        &Attributes,
+        &ActiveEffects,
         &Mining,
         &mut MineDelay,
         &mut MineProgress,
@@ -545,6 +597,8 @@ pub fn continue_mining_block(
         current_mining_item,
         fluid_on_eyes,
         physics,
+        attributes,
+        active_effects,
         mining,
         mut mine_delay,
         mut mine_progress,
@@ -581,7 +635,9 @@ pub fn continue_mining_block(
             current_mining_pos,
             current_mining_item,
         ) {
-            let instance_lock = instances.get(instance_name).unwrap();
+            let Some(instance_lock) = instances.get(instance_name) else {
+                continue;
+            };
             let instance = instance_lock.read();
             let target_block_state = instance.get_block_state(&mining.pos).unwrap_or_default();
 
@@ -596,6 +652,8 @@ pub fn continue_mining_block(
                 &inventory.inventory_menu,
                 fluid_on_eyes,
                 physics,
+                attributes,
+                active_effects,
             );
 
             if **mine_ticks % 4. == 0. {
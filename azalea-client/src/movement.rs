@@ -1,8 +1,9 @@
 use std::backtrace::Backtrace;
 
+use azalea_block::fluid_state::FluidKind;
 use azalea_core::position::Vec3;
 use azalea_core::tick::GameTick;
-use azalea_entity::{metadata::Sprinting, Attributes, Jumping};
+use azalea_entity::{metadata::Sprinting, Attributes, FluidOnEyes, Jumping};
 use azalea_entity::{InLoadedChunk, LastSentPosition, LookDirection, Physics, Position};
 use azalea_physics::{ai_step, PhysicsSet};
 use azalea_protocol::packets::game::{ServerboundPlayerCommand, ServerboundPlayerInput};
@@ -17,7 +18,7 @@ use azalea_protocol::packets::{
 };
 use azalea_world::{MinecraftEntityId, MoveEntityError};
 use bevy_app::{App, Plugin, Update};
-use bevy_ecs::prelude::{Event, EventWriter};
+use bevy_ecs::prelude::{Event, EventWriter, ResMut};
 use bevy_ecs::schedule::SystemSet;
 use bevy_ecs::system::Commands;
 use bevy_ecs::{
@@ -27,7 +28,12 @@ use bevy_ecs::{
 use thiserror::Error;
 
 use crate::client::Client;
+use crate::movement_arbiter::{
+    resolve_movement_requests, MovementArbiter, MovementPriority, MovementRequest,
+};
 use crate::packet_handling::game::SendPacketEvent;
+use crate::spectator::SpectatorCamera;
+use crate::vehicle::{Vehicle, WantsToDismount};
 
 #[derive(Error, Debug)]
 pub enum MovePlayerError {
@@ -54,6 +60,7 @@ impl Plugin for PlayerMovePlugin {
         app.add_event::<StartWalkEvent>()
             .add_event::<StartSprintEvent>()
             .add_event::<KnockbackEvent>()
+            .init_resource::<MovementArbiter>()
             .add_systems(
                 Update,
                 (handle_sprint, handle_walk, handle_knockback)
@@ -63,7 +70,11 @@ impl Plugin for PlayerMovePlugin {
             .add_systems(
                 GameTick,
                 (
-                    (tick_controls, local_player_ai_step)
+                    (
+                        resolve_movement_requests,
+                        tick_controls,
+                        local_player_ai_step,
+                    )
                         .chain()
                         .in_set(PhysicsSet)
                         .before(ai_step)
@@ -142,6 +153,12 @@ pub struct PhysicsState {
     pub move_direction: WalkDirection,
     pub forward_impulse: f32,
     pub left_impulse: f32,
+
+    /// The direction that we're trying to swim in while we're in water.
+    pub swim_direction: SwimDirection,
+    /// Whether we should stay at the surface when we're in water, like when
+    /// holding space in vanilla.
+    pub floating: bool,
 }
 
 #[allow(clippy::type_complexity)]
@@ -155,8 +172,9 @@ pub fn send_position(
             &mut LastSentPosition,
             &mut Physics,
             &mut LastSentLookDirection,
+            Option<&Vehicle>,
         ),
-        With<InLoadedChunk>,
+        (With<InLoadedChunk>, Without<SpectatorCamera>),
     >,
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
@@ -168,11 +186,12 @@ pub fn send_position(
         mut last_sent_position,
         mut physics,
         mut last_direction,
+        vehicle,
     ) in query.iter_mut()
     {
         let packet = {
-            // TODO: the camera being able to be controlled by other entities isn't
-            // implemented yet if !self.is_controlled_camera() { return };
+            // we don't send our position while we're spectating another entity, since the
+            // server moves us with it
 
             let x_delta = position.x - last_sent_position.x;
             let y_delta = position.y - last_sent_position.y;
@@ -189,10 +208,17 @@ pub fn send_position(
                 || physics_state.position_remainder >= 20;
             let sending_direction = y_rot_delta != 0.0 || x_rot_delta != 0.0;
 
-            // if self.is_passenger() {
-            //   TODO: posrot packet for being a passenger
-            // }
-            let packet = if sending_position && sending_direction {
+            // passengers only send their rotation, the position of the vehicle is sent
+            // separately by the vehicle plugin if we're controlling it
+            let packet = if vehicle.is_some() {
+                Some(
+                    ServerboundMovePlayerRot {
+                        look_direction: *direction,
+                        on_ground: physics.on_ground(),
+                    }
+                    .into_variant(),
+                )
+            } else if sending_position && sending_direction {
                 Some(
                     ServerboundMovePlayerPosRot {
                         pos: **position,
@@ -228,7 +254,7 @@ pub fn send_position(
                 None
             };
 
-            if sending_position {
+            if sending_position && vehicle.is_none() {
                 **last_sent_position = **position;
                 physics_state.position_remainder = 0;
             }
@@ -256,11 +282,17 @@ pub fn send_position(
 #[derive(Debug, Default, Component, Clone, PartialEq, Eq)]
 pub struct LastSentInput(pub ServerboundPlayerInput);
 pub fn send_player_input_packet(
-    mut query: Query<(Entity, &PhysicsState, &Jumping, Option<&LastSentInput>)>,
+    mut query: Query<(
+        Entity,
+        &PhysicsState,
+        &Jumping,
+        Option<&LastSentInput>,
+        Option<&WantsToDismount>,
+    )>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
     mut commands: Commands,
 ) {
-    for (entity, physics_state, jumping, last_sent_input) in query.iter_mut() {
+    for (entity, physics_state, jumping, last_sent_input, wants_to_dismount) in query.iter_mut() {
         let dir = physics_state.move_direction;
         type D = WalkDirection;
         let input = ServerboundPlayerInput {
@@ -269,8 +301,8 @@ pub fn send_player_input_packet(
             left: matches!(dir, D::Left | D::ForwardLeft | D::BackwardLeft),
             right: matches!(dir, D::Right | D::ForwardRight | D::BackwardRight),
             jump: **jumping,
-            // TODO: implement sneaking
-            shift: false,
+            // TODO: implement sneaking (other than for getting off of vehicles)
+            shift: wants_to_dismount.is_some(),
             sprint: physics_state.trying_to_sprint,
         };
 
@@ -315,7 +347,7 @@ fn send_sprinting_if_needed(
 
 /// Update the impulse from self.move_direction. The multiplier is used for
 /// sneaking.
-pub(crate) fn tick_controls(mut query: Query<&mut PhysicsState>) {
+pub fn tick_controls(mut query: Query<&mut PhysicsState>) {
     for mut physics_state in query.iter_mut() {
         let multiplier: Option<f32> = None;
 
@@ -356,11 +388,34 @@ pub(crate) fn tick_controls(mut query: Query<&mut PhysicsState>) {
 /// automatically by the client.
 pub fn local_player_ai_step(
     mut query: Query<
-        (&PhysicsState, &mut Physics, &mut Sprinting, &mut Attributes),
+        (
+            &PhysicsState,
+            &mut Physics,
+            &mut Sprinting,
+            &mut Attributes,
+            Option<&FluidOnEyes>,
+        ),
         With<InLoadedChunk>,
     >,
 ) {
-    for (physics_state, mut physics, mut sprinting, mut attributes) in query.iter_mut() {
+    for (physics_state, mut physics, mut sprinting, mut attributes, fluid_on_eyes) in
+        query.iter_mut()
+    {
+        if physics.is_in_water() {
+            let underwater = fluid_on_eyes.is_some_and(|f| **f == FluidKind::Water);
+            match physics_state.swim_direction {
+                // same as jumping in water
+                SwimDirection::Up => physics.velocity.y += 0.04,
+                // same as sneaking in water (goDownInWater)
+                SwimDirection::Down => physics.velocity.y -= 0.04,
+                SwimDirection::None => {
+                    if physics_state.floating && underwater {
+                        physics.velocity.y += 0.04;
+                    }
+                }
+            }
+        }
+
         // server ai step
         physics.x_acceleration = physics_state.left_impulse;
         physics.z_acceleration = physics_state.forward_impulse;
@@ -395,6 +450,11 @@ impl Client {
     /// [`Client::sprint`]. To stop walking, call walk with
     /// `WalkDirection::None`.
     ///
+    /// This takes priority over the pathfinder. See [`movement_arbiter`] if you
+    /// need more control.
+    ///
+    /// [`movement_arbiter`]: crate::movement_arbiter
+    ///
     /// # Examples
     ///
     /// Walk for 1 second
@@ -437,12 +497,38 @@ impl Client {
             direction,
         });
     }
+
+    /// Start swimming up or down while we're in water. This has no effect when
+    /// we're not in water. To stop, call swim with `SwimDirection::None`.
+    ///
+    /// To swim horizontally, use [`Client::walk`] or [`Client::sprint`] while
+    /// looking in the direction you want to go.
+    pub fn swim(&mut self, direction: SwimDirection) {
+        let mut ecs = self.ecs.lock();
+        let mut physics_state = self.query::<&mut PhysicsState>(&mut ecs);
+        physics_state.swim_direction = direction;
+    }
+
+    /// Set whether we should float at the surface of water instead of sinking.
+    /// This acts like holding space in vanilla, so the bot will bob up and down
+    /// at the surface.
+    ///
+    /// [`Client::swim`] takes priority over this while it's set to something
+    /// other than `SwimDirection::None`.
+    pub fn float(&mut self, floating: bool) {
+        let mut ecs = self.ecs.lock();
+        let mut physics_state = self.query::<&mut PhysicsState>(&mut ecs);
+        physics_state.floating = floating;
+    }
 }
 
 /// An event sent when the client starts walking. This does not get sent for
 /// non-local entities.
 ///
 /// To stop walking or sprinting, send this event with `WalkDirection::None`.
+///
+/// This is held in the [`MovementArbiter`] with [`MovementPriority::DEFAULT`],
+/// so anything with a higher priority can still move us differently.
 #[derive(Event, Debug)]
 pub struct StartWalkEvent {
     pub entity: Entity,
@@ -453,20 +539,26 @@ pub struct StartWalkEvent {
 /// [`StartWalkEvent`].
 pub fn handle_walk(
     mut events: EventReader<StartWalkEvent>,
-    mut query: Query<(&mut PhysicsState, &mut Sprinting, &mut Attributes)>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
 ) {
     for event in events.read() {
-        if let Ok((mut physics_state, mut sprinting, mut attributes)) = query.get_mut(event.entity)
-        {
-            physics_state.move_direction = event.direction;
-            physics_state.trying_to_sprint = false;
-            set_sprinting(false, &mut sprinting, &mut attributes);
+        if event.direction == WalkDirection::None {
+            movement_arbiter.release(event.entity, MovementPriority::DEFAULT);
+        } else {
+            movement_arbiter.hold(
+                event.entity,
+                MovementPriority::DEFAULT,
+                MovementRequest::walk(event.direction),
+            );
         }
     }
 }
 
 /// An event sent when the client starts sprinting. This does not get sent for
 /// non-local entities.
+///
+/// Like [`StartWalkEvent`], this is held in the [`MovementArbiter`] with
+/// [`MovementPriority::DEFAULT`].
 #[derive(Event)]
 pub struct StartSprintEvent {
     pub entity: Entity,
@@ -475,21 +567,22 @@ pub struct StartSprintEvent {
 /// The system that makes the player start sprinting when they receive a
 /// [`StartSprintEvent`].
 pub fn handle_sprint(
-    mut query: Query<&mut PhysicsState>,
     mut events: EventReader<StartSprintEvent>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
 ) {
     for event in events.read() {
-        if let Ok(mut physics_state) = query.get_mut(event.entity) {
-            physics_state.move_direction = WalkDirection::from(event.direction);
-            physics_state.trying_to_sprint = true;
-        }
+        movement_arbiter.hold(
+            event.entity,
+            MovementPriority::DEFAULT,
+            MovementRequest::sprint(event.direction),
+        );
     }
 }
 
 /// Change whether we're sprinting by adding an attribute modifier to the
 /// player. You should use the [`walk`] and [`sprint`] methods instead.
 /// Returns if the operation was successful.
-fn set_sprinting(
+pub(crate) fn set_sprinting(
     sprinting: bool,
     currently_sprinting: &mut Sprinting,
     attributes: &mut Attributes,
@@ -561,6 +654,15 @@ pub enum WalkDirection {
     BackwardLeft,
 }
 
+/// The vertical directions that we can swim in. See [`Client::swim`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwimDirection {
+    #[default]
+    None,
+    Up,
+    Down,
+}
+
 /// The directions that we can sprint in. It's a subset of [`WalkDirection`].
 #[derive(Clone, Copy, Debug)]
 pub enum SprintDirection {
@@ -578,3 +680,77 @@ impl From<SprintDirection> for WalkDirection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use azalea_entity::EntityDimensions;
+    use bevy_ecs::{schedule::Schedule, world::World};
+
+    use super::*;
+
+    /// Run [`local_player_ai_step`] once and return our vertical velocity.
+    fn y_velocity_after_ai_step(
+        physics_state: PhysicsState,
+        in_water: bool,
+        underwater: bool,
+    ) -> f64 {
+        let mut world = World::new();
+        let mut physics = Physics::new(
+            EntityDimensions::from(azalea_registry::EntityKind::Player),
+            Vec3::default(),
+        );
+        physics.was_touching_water = in_water;
+        let mut entity = world.spawn((
+            physics_state,
+            physics,
+            Sprinting(false),
+            Attributes::default(),
+            InLoadedChunk,
+        ));
+        if underwater {
+            entity.insert(FluidOnEyes::new(FluidKind::Water));
+        }
+        let entity = entity.id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(local_player_ai_step);
+        schedule.run(&mut world);
+        world.get::<Physics>(entity).unwrap().velocity.y
+    }
+
+    fn swimming(swim_direction: SwimDirection, floating: bool) -> PhysicsState {
+        PhysicsState {
+            swim_direction,
+            floating,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_swim_directions() {
+        assert_eq!(
+            y_velocity_after_ai_step(swimming(SwimDirection::None, false), true, true),
+            0.
+        );
+        assert!(y_velocity_after_ai_step(swimming(SwimDirection::Up, false), true, true) > 0.);
+        assert!(y_velocity_after_ai_step(swimming(SwimDirection::Down, false), true, true) < 0.);
+        // swimming takes priority over floating
+        assert!(y_velocity_after_ai_step(swimming(SwimDirection::Down, true), true, true) < 0.);
+        // and none of this does anything out of water
+        assert_eq!(
+            y_velocity_after_ai_step(swimming(SwimDirection::Up, false), false, false),
+            0.
+        );
+    }
+
+    #[test]
+    fn test_float() {
+        // we only push ourselves up while our head is underwater, which makes us bob
+        // at the surface
+        assert!(y_velocity_after_ai_step(swimming(SwimDirection::None, true), true, true) > 0.);
+        assert_eq!(
+            y_velocity_after_ai_step(swimming(SwimDirection::None, true), true, false),
+            0.
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! Decide how bots move when more than one thing wants to press their movement
+//! keys.
+//!
+//! Plugins like the pathfinder [submit](MovementArbiter::submit) a
+//! [`MovementRequest`] to the [`MovementArbiter`] for every tick that they want
+//! to be moving, and [`Client::walk`] and [`Client::sprint`]
+//! [hold](MovementArbiter::hold) one until they're told to stop. At the start
+//! of every tick, the requests for each bot are combined by their
+//! [`MovementPriority`].
+//!
+//! A request normally decides both whether we're going forwards or backwards
+//! and whether we're going left or right, but a
+//! [blended](MovementRequest::blend) request only takes the directions that
+//! it's actually moving in. For example, a blended request to strafe left while
+//! the pathfinder is walking forward makes the bot walk forward and to the
+//! left.
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use azalea_entity::{metadata::Sprinting, Attributes};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    movement::{set_sprinting, PhysicsState},
+    Client, SprintDirection, WalkDirection,
+};
+
+/// How important a movement request is. Requests with a higher priority
+/// decide how we move over ones with a lower priority.
+///
+/// You can make your own priorities in between the constants here if you need
+/// to, like `MovementPriority(MovementPriority::COMBAT.0 + 1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MovementPriority(pub u8);
+
+impl MovementPriority {
+    /// For wandering around when there's nothing else to do.
+    pub const IDLE: Self = Self(0);
+    /// Used by the pathfinder, and for walking and jumping towards positions.
+    pub const PATHFINDER: Self = Self(64);
+    /// Used by [`Client::walk`] and [`Client::sprint`]. The pathfinder releases
+    /// this when it's given a new goal, so a walk that was never stopped
+    /// doesn't keep it from moving.
+    pub const DEFAULT: Self = Self(128);
+    /// For things like strafing around a target while attacking it.
+    pub const COMBAT: Self = Self(192);
+    pub const HIGHEST: Self = Self(u8::MAX);
+}
+
+impl Default for MovementPriority {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The movement keys that something wants to press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MovementRequest {
+    pub direction: WalkDirection,
+    /// Whether we should try to sprint. This only works if the request that
+    /// decided that we're moving forward wants to sprint.
+    pub sprint: bool,
+    /// If this is true, the request only decides the directions that it's
+    /// moving in and leaves the rest to requests with a lower priority.
+    pub blend: bool,
+}
+
+impl MovementRequest {
+    pub fn walk(direction: WalkDirection) -> Self {
+        Self {
+            direction,
+            sprint: false,
+            blend: false,
+        }
+    }
+
+    pub fn sprint(direction: SprintDirection) -> Self {
+        Self {
+            direction: direction.into(),
+            sprint: true,
+            blend: false,
+        }
+    }
+
+    /// Let requests with a lower priority decide the directions that this one
+    /// isn't moving in.
+    ///
+    /// ```
+    /// # use azalea_client::{movement_arbiter::MovementRequest, WalkDirection};
+    /// let strafe = MovementRequest::walk(WalkDirection::Left).blend();
+    /// ```
+    #[must_use]
+    pub fn blend(mut self) -> Self {
+        self.blend = true;
+        self
+    }
+}
+
+/// A resource with the movement requests for every bot.
+///
+/// Requests that are [submitted](Self::submit) only last until the next tick,
+/// so systems that want to keep moving should submit a request every tick.
+/// Requests that are [held](Self::hold) last until they're released.
+#[derive(Resource, Debug, Default)]
+pub struct MovementArbiter {
+    requests: HashMap<Entity, Vec<(MovementPriority, MovementRequest)>>,
+    held: HashMap<Entity, HashMap<MovementPriority, MovementRequest>>,
+}
+
+impl MovementArbiter {
+    /// Move in the given way on the next tick.
+    pub fn submit(&mut self, entity: Entity, priority: MovementPriority, request: MovementRequest) {
+        self.requests
+            .entry(entity)
+            .or_default()
+            .push((priority, request));
+    }
+
+    /// Remove the requests with the given priority that were submitted since
+    /// the last tick, for when something stops moving before the next tick
+    /// starts.
+    pub fn withdraw(&mut self, entity: Entity, priority: MovementPriority) {
+        if let Some(requests) = self.requests.get_mut(&entity) {
+            requests.retain(|(p, _)| *p != priority);
+        }
+    }
+
+    /// Keep moving in the given way until [`Self::release`] is called with the
+    /// same priority. This replaces any other request that's being held with
+    /// that priority.
+    pub fn hold(&mut self, entity: Entity, priority: MovementPriority, request: MovementRequest) {
+        self.held
+            .entry(entity)
+            .or_default()
+            .insert(priority, request);
+    }
+
+    /// Stop holding the request that was given to [`Self::hold`].
+    pub fn release(&mut self, entity: Entity, priority: MovementPriority) {
+        if let Some(held) = self.held.get_mut(&entity) {
+            held.remove(&priority);
+        }
+    }
+
+    /// Stop holding every request for the entity.
+    pub fn release_all(&mut self, entity: Entity) {
+        self.held.remove(&entity);
+    }
+
+    /// The direction that we'll move in and whether we'll try to sprint if
+    /// nothing else is submitted before the next tick.
+    pub fn resolve(&self, entity: Entity) -> (WalkDirection, bool) {
+        // later requests win ties, and submitted requests win ties with held
+        // ones
+        let mut requests = self
+            .held
+            .get(&entity)
+            .into_iter()
+            .flat_map(|held| held.iter().map(|(p, r)| (*p, *r)))
+            .chain(self.requests.get(&entity).into_iter().flatten().copied())
+            .collect::<Vec<_>>();
+        requests.reverse();
+        requests.sort_by_key(|(priority, _)| Reverse(*priority));
+
+        let mut forward = None;
+        let mut right = None;
+        let mut sprint = false;
+        for (_, request) in requests {
+            let (request_forward, request_right) = axes(request.direction);
+            if forward.is_none() && (request_forward != 0 || !request.blend) {
+                forward = Some(request_forward);
+                sprint = request.sprint;
+            }
+            if right.is_none() && (request_right != 0 || !request.blend) {
+                right = Some(request_right);
+            }
+            if forward.is_some() && right.is_some() {
+                break;
+            }
+        }
+
+        let forward = forward.unwrap_or_default();
+        (
+            from_axes(forward, right.unwrap_or_default()),
+            sprint && forward > 0,
+        )
+    }
+}
+
+/// How much a direction moves us forward and to the right, from -1 to 1.
+fn axes(direction: WalkDirection) -> (i8, i8) {
+    use WalkDirection as D;
+    let forward = match direction {
+        D::Forward | D::ForwardLeft | D::ForwardRight => 1,
+        D::Backward | D::BackwardLeft | D::BackwardRight => -1,
+        _ => 0,
+    };
+    let right = match direction {
+        D::Right | D::ForwardRight | D::BackwardRight => 1,
+        D::Left | D::ForwardLeft | D::BackwardLeft => -1,
+        _ => 0,
+    };
+    (forward, right)
+}
+
+fn from_axes(forward: i8, right: i8) -> WalkDirection {
+    use WalkDirection as D;
+    match (forward.signum(), right.signum()) {
+        (1, 1) => D::ForwardRight,
+        (1, -1) => D::ForwardLeft,
+        (1, _) => D::Forward,
+        (-1, 1) => D::BackwardRight,
+        (-1, -1) => D::BackwardLeft,
+        (-1, _) => D::Backward,
+        (_, 1) => D::Right,
+        (_, -1) => D::Left,
+        _ => D::None,
+    }
+}
+
+/// Combine the movement requests for every local player and update their
+/// [`PhysicsState`] to match.
+pub fn resolve_movement_requests(
+    mut movement_arbiter: ResMut<MovementArbiter>,
+    mut query: Query<(Entity, &mut PhysicsState, &mut Sprinting, &mut Attributes)>,
+) {
+    for (entity, mut physics_state, mut sprinting, mut attributes) in &mut query {
+        let (direction, sprint) = movement_arbiter.resolve(entity);
+        physics_state.move_direction = direction;
+        physics_state.trying_to_sprint = sprint;
+        if !sprint && **sprinting {
+            set_sprinting(false, &mut sprinting, &mut attributes);
+        }
+    }
+
+    movement_arbiter.requests.clear();
+    // don't keep requests for players that are gone
+    movement_arbiter
+        .held
+        .retain(|entity, held| !held.is_empty() && query.contains(*entity));
+}
+
+impl Client {
+    /// Keep moving in the given way with a different priority than
+    /// [`MovementPriority::DEFAULT`], which is what [`Client::walk`] and
+    /// [`Client::sprint`] use.
+    ///
+    /// This lasts until [`Client::release_movement`] is called with the same
+    /// priority.
+    pub fn hold_movement(&mut self, priority: MovementPriority, request: MovementRequest) {
+        self.ecs
+            .lock()
+            .resource_mut::<MovementArbiter>()
+            .hold(self.entity, priority, request);
+    }
+
+    /// Stop moving in the way that was given to [`Client::hold_movement`].
+    pub fn release_movement(&mut self, priority: MovementPriority) {
+        self.ecs
+            .lock()
+            .resource_mut::<MovementArbiter>()
+            .release(self.entity, priority);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity() -> Entity {
+        Entity::from_raw(0)
+    }
+
+    #[test]
+    fn test_higher_priority_wins() {
+        let mut movement_arbiter = MovementArbiter::default();
+        movement_arbiter.hold(
+            entity(),
+            MovementPriority::DEFAULT,
+            MovementRequest::walk(WalkDirection::Backward),
+        );
+        movement_arbiter.submit(
+            entity(),
+            MovementPriority::PATHFINDER,
+            MovementRequest::sprint(SprintDirection::Forward),
+        );
+        assert_eq!(
+            movement_arbiter.resolve(entity()),
+            (WalkDirection::Backward, false)
+        );
+
+        movement_arbiter.release(entity(), MovementPriority::DEFAULT);
+        assert_eq!(
+            movement_arbiter.resolve(entity()),
+            (WalkDirection::Forward, true)
+        );
+    }
+
+    #[test]
+    fn test_blend() {
+        let mut movement_arbiter = MovementArbiter::default();
+        movement_arbiter.submit(
+            entity(),
+            MovementPriority::PATHFINDER,
+            MovementRequest::sprint(SprintDirection::Forward),
+        );
+        movement_arbiter.submit(
+            entity(),
+            MovementPriority::COMBAT,
+            MovementRequest::walk(WalkDirection::Left).blend(),
+        );
+        assert_eq!(
+            movement_arbiter.resolve(entity()),
+            (WalkDirection::ForwardLeft, true)
+        );
+
+        // standing still without blending blocks everything below it
+        movement_arbiter.submit(
+            entity(),
+            MovementPriority::DEFAULT,
+            MovementRequest::walk(WalkDirection::None),
+        );
+        assert_eq!(
+            movement_arbiter.resolve(entity()),
+            (WalkDirection::Left, false)
+        );
+    }
+}
@@ -1,24 +1,19 @@
-use std::io::Cursor;
-
-use azalea_entity::indexing::EntityIdIndex;
 use azalea_protocol::packets::config::s_finish_configuration::ServerboundFinishConfiguration;
 use azalea_protocol::packets::config::s_keep_alive::ServerboundKeepAlive;
 use azalea_protocol::packets::config::s_select_known_packs::ServerboundSelectKnownPacks;
 use azalea_protocol::packets::config::{
-    self, ClientboundConfigPacket, ServerboundConfigPacket, ServerboundCookieResponse,
-    ServerboundResourcePack,
+    self, ClientboundConfigPacket, ServerboundConfigPacket, ServerboundResourcePack,
 };
 use azalea_protocol::packets::{ConnectionProtocol, Packet};
-use azalea_protocol::read::deserialize_packet;
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::SystemState;
 use tracing::{debug, error, warn};
 
 use crate::client::InConfigState;
 use crate::disconnect::DisconnectEvent;
-use crate::local_player::Hunger;
 use crate::packet_handling::game::KeepAliveEvent;
-use crate::raw_connection::RawConnection;
+use crate::packet_handling::{IncomingPacketReader, LenientPacketParsing};
+use crate::raw_connection::{PacketFlow, RawConnection};
 use crate::InstanceHolder;
 
 #[derive(Event, Debug, Clone)]
@@ -30,27 +25,27 @@ pub struct ConfigurationEvent {
 }
 
 pub fn send_packet_events(
-    query: Query<(Entity, &RawConnection), With<InConfigState>>,
+    query: Query<(Entity, &RawConnection, Has<LenientPacketParsing>), With<InConfigState>>,
     mut packet_events: ResMut<Events<ConfigurationEvent>>,
+    mut packet_reader: IncomingPacketReader,
 ) {
     // we manually clear and send the events at the beginning of each update
     // since otherwise it'd cause issues with events in process_packet_events
     // running twice
     packet_events.clear();
-    for (player_entity, raw_conn) in &query {
+    for (player_entity, raw_conn, lenient) in &query {
         let packets_lock = raw_conn.incoming_packet_queue();
         let mut packets = packets_lock.lock();
         if !packets.is_empty() {
             for raw_packet in packets.iter() {
-                let packet = match deserialize_packet::<ClientboundConfigPacket>(&mut Cursor::new(
+                raw_conn.notify_packet_listeners(PacketFlow::Clientbound, raw_packet);
+                let Some(packet) = packet_reader.read::<ClientboundConfigPacket>(
+                    player_entity,
+                    ConnectionProtocol::Configuration,
                     raw_packet,
-                )) {
-                    Ok(packet) => packet,
-                    Err(err) => {
-                        error!("failed to read packet: {err:?}");
-                        debug!("packet bytes: {raw_packet:?}");
-                        continue;
-                    }
+                    lenient,
+                ) else {
+                    continue;
                 };
                 packet_events.send(ConfigurationEvent {
                     entity: player_entity,
@@ -81,7 +76,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut system_state: SystemState<Query<&mut InstanceHolder>> =
                     SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let instance_holder = query.get_mut(player_entity).unwrap();
+                let instance_holder = get_client_or_continue!(query, player_entity);
                 let mut instance = instance_holder.instance.write();
 
                 // add the new registry data
@@ -107,7 +102,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut system_state: SystemState<Query<&mut RawConnection>> =
                     SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let mut raw_conn = query.get_mut(player_entity).unwrap();
+                let mut raw_conn = get_client_or_continue!(query, player_entity);
 
                 raw_conn
                     .write_packet(ServerboundFinishConfiguration)
@@ -119,24 +114,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 // these components are added now that we're going to be in the Game state
                 ecs.entity_mut(player_entity)
                     .remove::<InConfigState>()
-                    .insert(crate::JoinedClientBundle {
-                        physics_state: crate::PhysicsState::default(),
-                        inventory: crate::inventory::Inventory::default(),
-                        tab_list: crate::local_player::TabList::default(),
-                        current_sequence_number: crate::interact::CurrentSequenceNumber::default(),
-                        last_sent_direction: crate::movement::LastSentLookDirection::default(),
-                        abilities: crate::local_player::PlayerAbilities::default(),
-                        permission_level: crate::local_player::PermissionLevel::default(),
-                        hunger: Hunger::default(),
-                        chunk_batch_info: crate::chunks::ChunkBatchInfo::default(),
-
-                        entity_id_index: EntityIdIndex::default(),
-
-                        mining: crate::mining::MineBundle::default(),
-                        attack: crate::attack::AttackBundle::default(),
-
-                        _local_entity: azalea_entity::LocalEntity,
-                    });
+                    .insert(crate::JoinedClientBundle::default());
             }
             ClientboundConfigPacket::KeepAlive(p) => {
                 debug!("Got keep alive packet (in configuration) {p:?} for {player_entity:?}");
@@ -145,8 +123,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<&RawConnection>,
                     EventWriter<KeepAliveEvent>,
                 )> = SystemState::new(ecs);
-                let (query, mut keepalive_events) = system_state.get_mut(ecs);
-                let raw_conn = query.get(player_entity).unwrap();
+                let (mut query, mut keepalive_events) = system_state.get_mut(ecs);
+                let raw_conn = get_client_or_continue!(query, player_entity);
 
                 keepalive_events.send(KeepAliveEvent {
                     entity: player_entity,
@@ -161,7 +139,7 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 let mut system_state: SystemState<Query<&RawConnection>> = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let raw_conn = query.get_mut(player_entity).unwrap();
+                let raw_conn = get_client_or_continue!(query, player_entity);
 
                 raw_conn
                     .write_packet(config::s_pong::ServerboundPong { id: p.id })
@@ -172,7 +150,7 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 let mut system_state: SystemState<Query<&RawConnection>> = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let raw_conn = query.get_mut(player_entity).unwrap();
+                let raw_conn = get_client_or_continue!(query, player_entity);
 
                 // always accept resource pack
                 raw_conn
@@ -193,34 +171,25 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundConfigPacket::CookieRequest(p) => {
                 debug!("Got cookie request packet {p:?}");
-
-                let mut system_state: SystemState<Query<&RawConnection>> = SystemState::new(ecs);
-                let mut query = system_state.get_mut(ecs);
-                let raw_conn = query.get_mut(player_entity).unwrap();
-
-                raw_conn
-                    .write_packet(ServerboundCookieResponse {
-                        key: p.key,
-                        // cookies aren't implemented
-                        payload: None,
-                    })
-                    .unwrap();
+                // this is handled in transfer::handle_config_transfer_packets
             }
             ClientboundConfigPacket::ResetChat(p) => {
                 debug!("Got reset chat packet {p:?}");
             }
             ClientboundConfigPacket::StoreCookie(p) => {
                 debug!("Got store cookie packet {p:?}");
+                // this is handled in transfer::handle_config_transfer_packets
             }
             ClientboundConfigPacket::Transfer(p) => {
                 debug!("Got transfer packet {p:?}");
+                // this is handled in transfer::handle_config_transfer_packets
             }
             ClientboundConfigPacket::SelectKnownPacks(p) => {
                 debug!("Got select known packs packet {p:?}");
 
                 let mut system_state: SystemState<Query<&RawConnection>> = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let raw_conn = query.get_mut(player_entity).unwrap();
+                let raw_conn = get_client_or_continue!(query, player_entity);
 
                 // resource pack management isn't implemented
                 raw_conn
@@ -1,35 +1,40 @@
 use std::{
     collections::HashSet,
-    io::Cursor,
     ops::Add,
     sync::{Arc, Weak},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use azalea_block::BlockState;
 use azalea_chat::FormattedText;
 use azalea_core::{
     game_type::GameMode,
     math,
-    position::{ChunkPos, Vec3},
+    position::{BlockPos, ChunkPos, ChunkSectionPos, Vec3},
     resource_location::ResourceLocation,
 };
 use azalea_entity::{
-    indexing::{EntityIdIndex, EntityUuidIndex},
-    metadata::{apply_metadata, Health},
-    Dead, EntityBundle, EntityKind, LastSentPosition, LoadedBy, LocalEntity, LookDirection,
-    Physics, Position, RelativeEntityUpdate,
+    effects::{ActiveEffects, MobEffectData},
+    indexing::EntityIdIndex,
+    metadata::{apply_default_metadata, apply_metadata, Health, ItemItem},
+    Attributes, Dead, EntityBundle, EntityKind, EntityUuid, ExperienceOrbValue, LastSentPosition,
+    LeashHolder, LoadedBy, LocalEntity, LookDirection, Physics, Position, RelativeEntityUpdate,
 };
-use azalea_protocol::{
-    packets::{
-        game::{
-            c_player_combat_kill::ClientboundPlayerCombatKill,
-            s_accept_teleportation::ServerboundAcceptTeleportation,
-            s_configuration_acknowledged::ServerboundConfigurationAcknowledged,
-            s_keep_alive::ServerboundKeepAlive, s_move_player_pos_rot::ServerboundMovePlayerPosRot,
-            s_pong::ServerboundPong, ClientboundGamePacket, ServerboundGamePacket,
-        },
-        Packet,
+use azalea_inventory::ItemStack;
+use azalea_protocol::packets::{
+    game::{
+        c_game_event::{ClientboundGameEvent, EventType},
+        c_level_particles::ClientboundLevelParticles,
+        c_player_combat_kill::ClientboundPlayerCombatKill,
+        s_accept_teleportation::ServerboundAcceptTeleportation,
+        s_configuration_acknowledged::ServerboundConfigurationAcknowledged,
+        s_keep_alive::ServerboundKeepAlive,
+        s_move_player_pos_rot::ServerboundMovePlayerPosRot,
+        s_ping_request::ServerboundPingRequest,
+        s_pong::ServerboundPong,
+        ClientboundGamePacket, ServerboundGamePacket,
     },
-    read::deserialize_packet,
+    ConnectionProtocol, Packet,
 };
 use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId, PartialInstance};
 use bevy_ecs::{prelude::*, system::SystemState};
@@ -41,14 +46,22 @@ use crate::{
     chat::{ChatPacket, ChatReceivedEvent},
     chunks,
     disconnect::DisconnectEvent,
+    events::SoundInfo,
     inventory::{
-        ClientSideCloseContainerEvent, Inventory, MenuOpenedEvent, SetContainerContentEvent,
+        ClientSideCloseContainerEvent, Inventory, MenuOpenedEvent, MerchantOffers,
+        SetContainerContentEvent,
     },
     local_player::{
-        GameProfileComponent, Hunger, InstanceHolder, LocalGameMode, PlayerAbilities, TabList,
+        Experience, GameProfileComponent, GameTime, Hunger, InstanceHolder, LastDamageSource,
+        LocalGameMode, MeasurePing, Ping, PlayerAbilities, ShareInstances, TabList, TabListHeader,
+        Weather, WorldBorder,
     },
+    maps::Maps,
     movement::{KnockbackEvent, KnockbackType},
-    raw_connection::RawConnection,
+    packet_handling::{IncomingPacketReader, LenientPacketParsing},
+    raw_connection::{PacketFlow, RawConnection},
+    spectator::SpectatorCamera,
+    vehicle::{Passengers, Vehicle, WantsToDismount},
     ClientInformation, PlayerInfo,
 };
 
@@ -104,6 +117,15 @@ pub struct UpdatePlayerEvent {
     pub entity: Entity,
     pub info: PlayerInfo,
 }
+/// The tab list of a local player changed. This is sent after the
+/// [`TabList`] or [`TabListHeader`] component is updated, whether that's
+/// because players were added, removed, or updated, or because the header or
+/// footer changed.
+#[derive(Event, Debug, Clone)]
+pub struct TabListUpdatedEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+}
 
 /// Event for when an entity dies. dies. If it's a local player and there's a
 /// reason in the death screen, the [`ClientboundPlayerCombatKill`] will
@@ -114,6 +136,169 @@ pub struct DeathEvent {
     pub packet: Option<ClientboundPlayerCombatKill>,
 }
 
+/// Sent when the experience level or progress of a local player changes.
+#[derive(Event, Debug, Clone)]
+pub struct XpChangeEvent {
+    pub entity: Entity,
+    pub old: Experience,
+    pub new: Experience,
+}
+
+/// Sent when the server tells a local player what time it is, which happens
+/// about once a second.
+#[derive(Event, Debug, Clone)]
+pub struct TimeUpdateEvent {
+    pub entity: Entity,
+    pub time: GameTime,
+}
+
+/// Sent when the rain or thunder level in the world of a local player changes.
+#[derive(Event, Debug, Clone)]
+pub struct WeatherChangeEvent {
+    pub entity: Entity,
+    pub old: Weather,
+    pub new: Weather,
+}
+
+/// Sent when the server sends a local player a game event, which is used for
+/// a bunch of unrelated things like the weather and showing the credits.
+#[derive(Event, Debug, Clone)]
+pub struct GameStateChangeEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub change: GameStateChange,
+}
+
+/// A decoded [`ClientboundGameEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameStateChange {
+    /// We tried to respawn at a bed or respawn anchor that's missing or
+    /// obstructed.
+    NoRespawnBlockAvailable,
+    /// It started raining, and the rain level will gradually go up with
+    /// [`Self::RainLevelChanged`].
+    RainStarted,
+    RainStopped,
+    GameModeChanged(GameMode),
+    /// We went through the exit portal in the End.
+    ///
+    /// If `show_credits` is false, the player already saw the credits and the
+    /// client is expected to respawn right away.
+    GameWon {
+        show_credits: bool,
+    },
+    Demo(DemoEvent),
+    /// An arrow that we shot hit a player.
+    ArrowHitPlayer,
+    RainLevelChanged(f32),
+    ThunderLevelChanged(f32),
+    PufferFishSting,
+    /// An elder guardian gave us mining fatigue, which shows its face on the
+    /// screen.
+    ElderGuardianEffect,
+    /// Whether we skip the death screen changed, from the
+    /// `doImmediateRespawn` gamerule.
+    ImmediateRespawnChanged(bool),
+    /// Whether we can only craft recipes that we've unlocked changed, from the
+    /// `doLimitedCrafting` gamerule.
+    LimitedCraftingChanged(bool),
+    /// The server is about to send us the chunks around where we spawned.
+    WaitForLevelChunks,
+}
+
+/// The screens and hints that are shown to players in the demo version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoEvent {
+    Intro,
+    MovementHint,
+    JumpHint,
+    InventoryHint,
+    ScreenshotHint,
+}
+
+impl GameStateChange {
+    /// Decode the parameter of a game event packet based on its type.
+    ///
+    /// Returns `None` if the parameter isn't valid for the type, like an
+    /// unknown game mode.
+    pub fn from_packet(packet: &ClientboundGameEvent) -> Option<Self> {
+        let param = packet.param;
+        Some(match packet.event {
+            EventType::NoRespawnBlockAvailable => Self::NoRespawnBlockAvailable,
+            EventType::StartRaining => Self::RainStarted,
+            EventType::StopRaining => Self::RainStopped,
+            EventType::ChangeGameMode => Self::GameModeChanged(GameMode::from_id(param as u8)?),
+            EventType::WinGame => Self::GameWon {
+                show_credits: param == 1.,
+            },
+            EventType::DemoEvent => Self::Demo(match param as u32 {
+                0 => DemoEvent::Intro,
+                101 => DemoEvent::MovementHint,
+                102 => DemoEvent::JumpHint,
+                103 => DemoEvent::InventoryHint,
+                104 => DemoEvent::ScreenshotHint,
+                _ => return None,
+            }),
+            EventType::ArrowHitPlayer => Self::ArrowHitPlayer,
+            EventType::RainLevelChange => Self::RainLevelChanged(param),
+            EventType::ThunderLevelChange => Self::ThunderLevelChanged(param),
+            EventType::PufferFishSting => Self::PufferFishSting,
+            EventType::GuardianElderEffect => Self::ElderGuardianEffect,
+            EventType::ImmediateRespawn => Self::ImmediateRespawnChanged(param == 1.),
+            EventType::LimitedCrafting => Self::LimitedCraftingChanged(param == 1.),
+            EventType::WaitForLevelChunks => Self::WaitForLevelChunks,
+        })
+    }
+}
+
+/// Sent when a local player or another entity picks up an item.
+///
+/// This is sent before the item entity is removed, so its components can
+/// still be accessed.
+#[derive(Event, Debug, Clone)]
+pub struct ItemPickupEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    /// The entity that picked up the item. This is the same as `entity` if
+    /// it was us.
+    pub collector: Entity,
+    /// The item entity that was picked up.
+    pub item_entity: Entity,
+    /// The item that was picked up. The count is how many were picked up,
+    /// which may be less than the size of the stack on the ground if the
+    /// collector's inventory was full.
+    pub item: ItemStack,
+}
+
+/// Sent when a local player or another entity picks up an experience orb.
+#[derive(Event, Debug, Clone)]
+pub struct ExperienceOrbPickupEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    /// The entity that picked up the experience orb.
+    pub collector: Entity,
+    /// The experience orb entity that was picked up.
+    pub orb_entity: Entity,
+    /// How much experience the orb was worth.
+    pub value: u16,
+}
+
+/// A sound was played near a local player.
+#[derive(Event, Debug, Clone)]
+pub struct PlaySoundEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub sound: SoundInfo,
+}
+
+/// Particles were spawned near a local player.
+#[derive(Event, Debug, Clone)]
+pub struct ParticleEvent {
+    /// The local player entity that received this event.
+    pub entity: Entity,
+    pub packet: ClientboundLevelParticles,
+}
+
 /// A KeepAlive packet is sent from the server to verify that the client is
 /// still connected.
 #[derive(Event, Debug, Clone)]
@@ -147,29 +332,69 @@ pub struct InstanceLoadedEvent {
     pub instance: Weak<RwLock<Instance>>,
 }
 
+/// A block in the world was changed by a block update packet, or by us
+/// breaking it.
+///
+/// This is only sent if the block actually changed, so clients in a swarm that
+/// share a world won't send it more than once for the same change. Updates for
+/// chunks that are still being decoded are sent after the
+/// [`ChunkLoadedEvent`] for the chunk.
+///
+/// Blocks that we break are removed from the world before the server tells us
+/// about it, so the event for them is sent right away and the block update
+/// from the server doesn't send another one (unless the server put the block
+/// back).
+///
+/// [`ChunkLoadedEvent`]: crate::chunks::ChunkLoadedEvent
+#[derive(Event, Debug, Clone)]
+pub struct BlockUpdateEvent {
+    /// The local player entity that received the block update.
+    pub entity: Entity,
+    pub instance: InstanceName,
+    pub pos: BlockPos,
+    pub old: BlockState,
+    pub new: BlockState,
+}
+
+/// All of the blocks that a section blocks update packet changed at once,
+/// which is useful if you'd rather not handle lots of [`BlockUpdateEvent`]s
+/// one at a time.
+///
+/// A [`BlockUpdateEvent`] is still sent for each block, and like them, this
+/// isn't sent if none of the blocks actually changed.
+#[derive(Event, Debug, Clone)]
+pub struct SectionBlocksUpdateEvent {
+    /// The local player entity that received the update.
+    pub entity: Entity,
+    pub instance: InstanceName,
+    pub section_pos: ChunkSectionPos,
+    /// The blocks that changed, as `(pos, old, new)`.
+    pub changes: Vec<(BlockPos, BlockState, BlockState)>,
+}
+
 pub fn send_packet_events(
-    query: Query<(Entity, &RawConnection), With<LocalEntity>>,
+    query: Query<(Entity, &RawConnection, Has<LenientPacketParsing>), With<LocalEntity>>,
     mut packet_events: ResMut<Events<PacketEvent>>,
+    mut packet_reader: IncomingPacketReader,
 ) {
     // we manually clear and send the events at the beginning of each update
     // since otherwise it'd cause issues with events in process_packet_events
     // running twice
     packet_events.clear();
-    for (player_entity, raw_connection) in &query {
+    for (player_entity, raw_connection, lenient) in &query {
         let packets_lock = raw_connection.incoming_packet_queue();
         let mut packets = packets_lock.lock();
         if !packets.is_empty() {
             for raw_packet in packets.iter() {
-                let packet =
-                    match deserialize_packet::<ClientboundGamePacket>(&mut Cursor::new(raw_packet))
-                    {
-                        Ok(packet) => packet,
-                        Err(err) => {
-                            error!("failed to read packet: {err:?}");
-                            debug!("packet bytes: {raw_packet:?}");
-                            continue;
-                        }
-                    };
+                raw_connection.notify_packet_listeners(PacketFlow::Clientbound, raw_packet);
+                let Some(packet) = packet_reader.read::<ClientboundGamePacket>(
+                    player_entity,
+                    ConnectionProtocol::Game,
+                    raw_packet,
+                    lenient,
+                ) else {
+                    continue;
+                };
                 packet_events.send(PacketEvent {
                     entity: player_entity,
                     packet: Arc::new(packet),
@@ -195,7 +420,27 @@ pub fn process_packet_events(ecs: &mut World) {
             events_owned.push((*player_entity, packet.clone()));
         }
     }
-    for (player_entity, packet) in events_owned {
+    process_packets(ecs, events_owned);
+
+    // the chunks were being decoded while the other packets were handled, so the
+    // ones that are already done are added now instead of a frame late
+    let deferred_packets = chunks::load_decoded_chunks(ecs);
+    process_packets(ecs, deferred_packets);
+}
+
+/// Handle game packets that clients received, which is done by
+/// [`process_packet_events`].
+///
+/// Packets that depend on a chunk that's still being decoded are deferred
+/// until it's added to the world, see [`chunks::PendingChunks::defer_packet`].
+pub(crate) fn process_packets(ecs: &mut World, packets: Vec<(Entity, Arc<ClientboundGamePacket>)>) {
+    for (player_entity, packet) in packets {
+        if let Some(mut pending_chunks) = ecs.get_mut::<chunks::PendingChunks>(player_entity) {
+            if pending_chunks.defer_packet(&packet) {
+                continue;
+            }
+        }
+
         let packet_clone = packet.clone();
         let packet_ref = packet_clone.as_ref();
         match packet_ref {
@@ -215,16 +460,16 @@ pub fn process_packet_events(ecs: &mut World) {
                     )>,
                     EventWriter<InstanceLoadedEvent>,
                     ResMut<InstanceContainer>,
-                    ResMut<EntityUuidIndex>,
                     EventWriter<SendPacketEvent>,
+                    Res<ShareInstances>,
                 )> = SystemState::new(ecs);
                 let (
                     mut commands,
                     mut query,
                     mut instance_loaded_events,
                     mut instance_container,
-                    mut entity_uuid_index,
                     mut send_packet_events,
+                    share_instances,
                 ) = system_state.get_mut(ecs);
                 let (
                     game_profile,
@@ -233,10 +478,11 @@ pub fn process_packet_events(ecs: &mut World) {
                     loaded_by,
                     mut entity_id_index,
                     mut instance_holder,
-                ) = query.get_mut(player_entity).unwrap();
+                ) = get_client_or_continue!(query, player_entity);
 
                 {
-                    let new_instance_name = p.common.dimension.clone();
+                    let new_instance_name =
+                        share_instances.instance_name(&p.common.dimension, &game_profile.name);
 
                     if let Some(mut instance_name) = instance_name {
                         *instance_name = instance_name.clone();
@@ -246,19 +492,22 @@ pub fn process_packet_events(ecs: &mut World) {
                             .insert(InstanceName(new_instance_name.clone()));
                     }
 
-                    let Some(dimension_type_element) =
-                        instance_holder.instance.read().registries.dimension_type()
+                    let Some((dimension_type_name, dimension)) = instance_holder
+                        .instance
+                        .read()
+                        .registries
+                        .dimension_type_by_id(p.common.dimension_type)
                     else {
-                        error!("Server didn't send dimension type registry, can't log in");
-                        continue;
-                    };
-
-                    let dimension_name = ResourceLocation::new(&p.common.dimension.to_string());
-
-                    let Some(dimension) = dimension_type_element.map.get(&dimension_name) else {
-                        error!("No dimension_type with name {dimension_name}");
+                        error!(
+                            "No dimension_type with ID {}, can't join {}",
+                            p.common.dimension_type, p.common.dimension
+                        );
                         continue;
                     };
+                    debug!(
+                        "{} uses the dimension type {dimension_type_name} (height {}, min y {})",
+                        p.common.dimension, dimension.height, dimension.min_y
+                    );
 
                     // add this world to the instance_container (or don't if it's already
                     // there)
@@ -306,21 +555,23 @@ pub fn process_packet_events(ecs: &mut World) {
                     );
                     let entity_id = MinecraftEntityId(p.player_id);
                     // insert our components into the ecs :)
-                    commands.entity(player_entity).insert((
-                        entity_id,
-                        LocalGameMode {
-                            current: p.common.game_type,
-                            previous: p.common.previous_game_type.into(),
-                        },
-                        entity_bundle,
-                    ));
+                    commands
+                        .entity(player_entity)
+                        .remove::<SpectatorCamera>()
+                        .insert((
+                            entity_id,
+                            LocalGameMode {
+                                current: p.common.game_type,
+                                previous: p.common.previous_game_type.into(),
+                            },
+                            entity_bundle,
+                        ));
 
                     azalea_entity::indexing::add_entity_to_indexes(
                         entity_id,
                         player_entity,
                         Some(game_profile.uuid),
                         &mut entity_id_index,
-                        &mut entity_uuid_index,
                         &mut instance_holder.instance.write(),
                     );
 
@@ -387,7 +638,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut system_state: SystemState<Query<&mut PlayerAbilities>> =
                     SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let mut player_abilities = query.get_mut(player_entity).unwrap();
+                let mut player_abilities = get_client_or_continue!(query, player_entity);
 
                 *player_abilities = PlayerAbilities::from(p);
             }
@@ -520,20 +771,32 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 #[allow(clippy::type_complexity)]
                 let mut system_state: SystemState<(
-                    Query<&mut TabList>,
+                    Query<(&mut TabList, &GameProfileComponent, Option<&mut Ping>)>,
                     EventWriter<AddPlayerEvent>,
                     EventWriter<UpdatePlayerEvent>,
+                    EventWriter<TabListUpdatedEvent>,
                     ResMut<TabList>,
                 )> = SystemState::new(ecs);
                 let (
                     mut query,
                     mut add_player_events,
                     mut update_player_events,
+                    mut tab_list_updated_events,
                     mut tab_list_resource,
                 ) = system_state.get_mut(ecs);
-                let mut tab_list = query.get_mut(player_entity).unwrap();
+                let (mut tab_list, game_profile, mut ping) =
+                    get_client_or_continue!(query, player_entity);
 
                 for updated_info in &p.entries {
+                    if (p.actions.add_player || p.actions.update_latency)
+                        && updated_info.profile.uuid == game_profile.uuid
+                    {
+                        if let Some(ping) = &mut ping {
+                            ping.tab_list =
+                                Some(Duration::from_millis(updated_info.latency.max(0) as u64));
+                        }
+                    }
+
                     // add the new player maybe
                     if p.actions.add_player {
                         let info = PlayerInfo {
@@ -574,16 +837,24 @@ pub fn process_packet_events(ecs: &mut World) {
                 }
 
                 *tab_list_resource = tab_list.clone();
+                tab_list_updated_events.send(TabListUpdatedEvent {
+                    entity: player_entity,
+                });
             }
             ClientboundGamePacket::PlayerInfoRemove(p) => {
                 let mut system_state: SystemState<(
                     Query<&mut TabList>,
                     EventWriter<RemovePlayerEvent>,
+                    EventWriter<TabListUpdatedEvent>,
                     ResMut<TabList>,
                 )> = SystemState::new(ecs);
-                let (mut query, mut remove_player_events, mut tab_list_resource) =
-                    system_state.get_mut(ecs);
-                let mut tab_list = query.get_mut(player_entity).unwrap();
+                let (
+                    mut query,
+                    mut remove_player_events,
+                    mut tab_list_updated_events,
+                    mut tab_list_resource,
+                ) = system_state.get_mut(ecs);
+                let mut tab_list = get_client_or_continue!(query, player_entity);
 
                 for uuid in &p.profile_ids {
                     if let Some(info) = tab_list.remove(uuid) {
@@ -594,6 +865,9 @@ pub fn process_packet_events(ecs: &mut World) {
                     }
                     tab_list_resource.remove(uuid);
                 }
+                tab_list_updated_events.send(TabListUpdatedEvent {
+                    entity: player_entity,
+                });
             }
             ClientboundGamePacket::SetChunkCacheCenter(p) => {
                 debug!("Got chunk cache center packet {p:?}");
@@ -601,7 +875,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut system_state: SystemState<Query<&mut InstanceHolder>> =
                     SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let instance_holder = query.get_mut(player_entity).unwrap();
+                let instance_holder = get_client_or_continue!(query, player_entity);
                 let mut partial_world = instance_holder.partial_instance.write();
 
                 partial_world
@@ -609,19 +883,32 @@ pub fn process_packet_events(ecs: &mut World) {
                     .update_view_center(ChunkPos::new(p.x, p.z));
             }
             ClientboundGamePacket::ChunksBiomes(_) => {}
-            ClientboundGamePacket::LightUpdate(_p) => {
-                // debug!("Got light update packet {p:?}");
+            ClientboundGamePacket::LightUpdate(p) => {
+                trace!("Got light update packet for {} {}", p.x, p.z);
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let local_player = get_client_or_continue!(query, player_entity);
+
+                let pos = ChunkPos::new(p.x, p.z);
+                let instance = local_player.instance.read();
+                if let Some(chunk) = instance.chunks.get(&pos) {
+                    chunks::update_light(&mut chunk.write().light, &p.light_data);
+                }
             }
             ClientboundGamePacket::LevelChunkWithLight(p) => {
                 debug!("Got chunk with light packet {} {}", p.x, p.z);
 
-                let mut system_state: SystemState<EventWriter<chunks::ReceiveChunkEvent>> =
-                    SystemState::new(ecs);
-                let mut receive_chunk_events = system_state.get_mut(ecs);
-                receive_chunk_events.send(chunks::ReceiveChunkEvent {
-                    entity: player_entity,
-                    packet: p.clone(),
-                });
+                let mut system_state: SystemState<
+                    Query<(&InstanceHolder, &mut chunks::PendingChunks)>,
+                > = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let (local_player, mut pending_chunks) =
+                    get_client_or_continue!(query, player_entity);
+
+                // this starts decoding the chunk right away, so packets after this one that
+                // depend on it know to wait for it
+                chunks::receive_chunk(local_player, &mut pending_chunks, packet.clone());
             }
             ClientboundGamePacket::AddEntity(p) => {
                 debug!("Got add entity packet {p:?}");
@@ -632,19 +919,19 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&mut EntityIdIndex, Option<&InstanceName>, Option<&TabList>)>,
                     Query<&mut LoadedBy>,
                     Query<Entity>,
+                    Query<&EntityUuid>,
                     Res<InstanceContainer>,
-                    ResMut<EntityUuidIndex>,
                 )> = SystemState::new(ecs);
                 let (
                     mut commands,
                     mut query,
                     mut loaded_by_query,
                     entity_query,
+                    entity_uuid_query,
                     instance_container,
-                    mut entity_uuid_index,
                 ) = system_state.get_mut(ecs);
                 let (mut entity_id_index, instance_name, tab_list) =
-                    query.get_mut(player_entity).unwrap();
+                    get_client_or_continue!(query, player_entity);
 
                 let entity_id = MinecraftEntityId(p.id);
 
@@ -654,8 +941,25 @@ pub fn process_packet_events(ecs: &mut World) {
                 };
 
                 // check if the entity already exists, and if it does then only add to LoadedBy
-                let instance = instance_container.get(instance_name).unwrap();
-                if let Some(&ecs_entity) = instance.read().entity_by_id.get(&entity_id) {
+                let Some(instance) = instance_container.get(instance_name) else {
+                    warn!("got add player packet for an instance that doesn't exist");
+                    continue;
+                };
+                let existing_entity = instance.read().entity_by_id.get(&entity_id).copied();
+                // servers reuse ids, so if another client in the swarm still has an old
+                // entity with this id loaded then it'll be in the index even though it's not
+                // the entity that we're adding
+                let existing_entity = existing_entity.filter(|&ecs_entity| {
+                    let Ok(uuid) = entity_uuid_query.get(ecs_entity) else {
+                        return true;
+                    };
+                    if **uuid != p.uuid {
+                        debug!("entity id {entity_id:?} was reused, spawning a new entity instead of using {ecs_entity:?}");
+                        return false;
+                    }
+                    true
+                });
+                if let Some(ecs_entity) = existing_entity {
                     // entity already exists
                     let Ok(mut loaded_by) = loaded_by_query.get_mut(ecs_entity) else {
                         // LoadedBy for this entity isn't in the ecs! figure out what went wrong
@@ -692,7 +996,6 @@ pub fn process_packet_events(ecs: &mut World) {
                     ecs_entity,
                     Some(p.uuid),
                     &mut entity_id_index,
-                    &mut entity_uuid_index,
                     &mut instance.write(),
                 );
 
@@ -722,7 +1025,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<&EntityKind>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query, entity_kind_query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 let entity = entity_id_index.get(MinecraftEntityId(p.id));
 
@@ -731,7 +1035,12 @@ pub fn process_packet_events(ecs: &mut World) {
                     debug!("Server sent an entity data packet for an entity id ({}) that we don't know about", p.id);
                     continue;
                 };
-                let entity_kind = *entity_kind_query.get(entity).unwrap();
+                let Ok(&entity_kind) = entity_kind_query.get(entity) else {
+                    warn!(
+                        "Got an entity data packet for {entity}, but it doesn't have an EntityKind"
+                    );
+                    continue;
+                };
 
                 let packed_items = p.packed_items.clone().to_vec();
 
@@ -757,8 +1066,39 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 system_state.apply(ecs);
             }
-            ClientboundGamePacket::UpdateAttributes(_p) => {
+            ClientboundGamePacket::UpdateAttributes(p) => {
                 // debug!("Got update attributes packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&EntityIdIndex>, Query<&mut Attributes>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut attributes_query) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(entity) = entity_id_index.get(MinecraftEntityId(p.entity_id)) else {
+                    debug!(
+                        "Got update attributes packet for unknown entity id {}",
+                        p.entity_id
+                    );
+                    continue;
+                };
+                let Ok(mut attributes) = attributes_query.get_mut(entity) else {
+                    continue;
+                };
+
+                // the rest of the attributes are calculated by us, since the server doesn't
+                // know about things like us starting to sprint until we tell it
+                for snapshot in &p.values {
+                    let attribute = match snapshot.attribute {
+                        azalea_registry::Attribute::BlockBreakSpeed => {
+                            &mut attributes.block_break_speed
+                        }
+                        azalea_registry::Attribute::SubmergedMiningSpeed => {
+                            &mut attributes.submerged_mining_speed
+                        }
+                        _ => continue,
+                    };
+                    attribute.set(snapshot.base, snapshot.modifiers.iter().cloned());
+                }
             }
             ClientboundGamePacket::SetEntityMotion(p) => {
                 // vanilla servers use this packet for knockback, but note that the Explode
@@ -769,7 +1109,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 let Some(entity) = entity_id_index.get(MinecraftEntityId(p.id)) else {
                     // note that this log (and some other ones like the one in RemoveEntities)
@@ -804,12 +1145,69 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::SetEntityLink(p) => {
                 debug!("Got set entity link packet {p:?}");
+
+                let mut system_state: SystemState<(Commands, Query<&EntityIdIndex>)> =
+                    SystemState::new(ecs);
+                let (mut commands, mut query) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(leashed) = entity_id_index.get(MinecraftEntityId(p.source_id)) else {
+                    debug!(
+                        "Got set entity link packet for unknown entity id {}",
+                        p.source_id
+                    );
+                    continue;
+                };
+                // the vanilla server uses 0 to mean that the entity isn't leashed anymore
+                let holder = if p.dest_id == 0 {
+                    None
+                } else {
+                    entity_id_index.get(MinecraftEntityId(p.dest_id))
+                };
+                match holder {
+                    Some(holder) => {
+                        commands.entity(leashed).insert(LeashHolder(holder));
+                    }
+                    None => {
+                        commands.entity(leashed).remove::<LeashHolder>();
+                    }
+                }
+
+                system_state.apply(ecs);
             }
             ClientboundGamePacket::InitializeBorder(p) => {
                 debug!("Got initialize border packet {p:?}");
+
+                let mut system_state: SystemState<Commands> = SystemState::new(ecs);
+                let mut commands = system_state.get_mut(ecs);
+
+                commands.entity(player_entity).insert(WorldBorder {
+                    center_x: p.new_center_x,
+                    center_z: p.new_center_z,
+                    size: p.new_size,
+                });
+
+                system_state.apply(ecs);
             }
-            ClientboundGamePacket::SetTime(_p) => {
+            ClientboundGamePacket::SetTime(p) => {
                 // debug!("Got set time packet {p:?}");
+
+                let mut system_state: SystemState<(Commands, EventWriter<TimeUpdateEvent>)> =
+                    SystemState::new(ecs);
+                let (mut commands, mut time_update_events) = system_state.get_mut(ecs);
+
+                let time = GameTime {
+                    world_age: p.game_time,
+                    day_time: p.day_time,
+                    ticking: p.tick_day_time,
+                };
+                commands.entity(player_entity).insert(time.clone());
+                time_update_events.send(TimeUpdateEvent {
+                    entity: player_entity,
+                    time,
+                });
+
+                system_state.apply(ecs);
             }
             ClientboundGamePacket::SetDefaultSpawnPosition(p) => {
                 debug!("Got set default spawn position packet {p:?}");
@@ -820,7 +1218,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut system_state: SystemState<Query<(&mut Health, &mut Hunger)>> =
                     SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let (mut health, mut hunger) = query.get_mut(player_entity).unwrap();
+                let (mut health, mut hunger) = get_client_or_continue!(query, player_entity);
 
                 **health = p.health;
                 (hunger.food, hunger.saturation) = (p.food, p.saturation);
@@ -831,6 +1229,27 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::SetExperience(p) => {
                 debug!("Got set experience packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&mut Experience>,
+                    EventWriter<XpChangeEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut xp_change_events) = system_state.get_mut(ecs);
+                let mut experience = get_client_or_continue!(query, player_entity);
+
+                let new_experience = Experience {
+                    level: p.experience_level,
+                    progress: p.experience_progress,
+                    total: p.total_experience,
+                };
+                if *experience != new_experience {
+                    xp_change_events.send(XpChangeEvent {
+                        entity: player_entity,
+                        old: experience.clone(),
+                        new: new_experience.clone(),
+                    });
+                    *experience = new_experience;
+                }
             }
             ClientboundGamePacket::TeleportEntity(p) => {
                 let mut system_state: SystemState<(
@@ -838,7 +1257,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 let Some(entity) = entity_id_index.get(MinecraftEntityId(p.id)) else {
                     warn!("Got teleport entity packet for unknown entity id {}", p.id);
@@ -853,17 +1273,23 @@ pub fn process_packet_events(ecs: &mut World) {
                 commands.entity(entity).queue(RelativeEntityUpdate {
                     partial_world: instance_holder.partial_instance.clone(),
                     update: Box::new(move |entity| {
-                        let mut position = entity.get_mut::<Position>().unwrap();
+                        let Some(mut position) = entity.get_mut::<Position>() else {
+                            return;
+                        };
                         if new_pos != **position {
                             **position = new_pos;
                         }
                         let position = *position;
-                        let mut look_direction = entity.get_mut::<LookDirection>().unwrap();
+                        let Some(mut look_direction) = entity.get_mut::<LookDirection>() else {
+                            return;
+                        };
                         if new_look_direction != *look_direction {
                             *look_direction = new_look_direction;
                         }
                         // old_pos is set to the current position when we're teleported
-                        let mut physics = entity.get_mut::<Physics>().unwrap();
+                        let Some(mut physics) = entity.get_mut::<Physics>() else {
+                            return;
+                        };
                         physics.set_old_pos(&position);
                     }),
                 });
@@ -882,7 +1308,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 debug!("Got move entity pos packet {p:?}");
 
@@ -899,7 +1326,9 @@ pub fn process_packet_events(ecs: &mut World) {
                 commands.entity(entity).queue(RelativeEntityUpdate {
                     partial_world: instance_holder.partial_instance.clone(),
                     update: Box::new(move |entity_mut| {
-                        let mut physics = entity_mut.get_mut::<Physics>().unwrap();
+                        let Some(mut physics) = entity_mut.get_mut::<Physics>() else {
+                            return;
+                        };
                         let new_pos = physics.vec_delta_codec.decode(
                             new_delta.xa as i64,
                             new_delta.ya as i64,
@@ -908,7 +1337,9 @@ pub fn process_packet_events(ecs: &mut World) {
                         physics.vec_delta_codec.set_base(new_pos);
                         physics.set_on_ground(new_on_ground);
 
-                        let mut position = entity_mut.get_mut::<Position>().unwrap();
+                        let Some(mut position) = entity_mut.get_mut::<Position>() else {
+                            return;
+                        };
                         if new_pos != **position {
                             **position = new_pos;
                         }
@@ -923,7 +1354,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 debug!("Got move entity pos rot packet {p:?}");
 
@@ -941,7 +1373,9 @@ pub fn process_packet_events(ecs: &mut World) {
                     commands.entity(entity).queue(RelativeEntityUpdate {
                         partial_world: instance_holder.partial_instance.clone(),
                         update: Box::new(move |entity_mut| {
-                            let mut physics = entity_mut.get_mut::<Physics>().unwrap();
+                            let Some(mut physics) = entity_mut.get_mut::<Physics>() else {
+                                return;
+                            };
                             let new_pos = physics.vec_delta_codec.decode(
                                 new_delta.xa as i64,
                                 new_delta.ya as i64,
@@ -950,12 +1384,17 @@ pub fn process_packet_events(ecs: &mut World) {
                             physics.vec_delta_codec.set_base(new_pos);
                             physics.set_on_ground(new_on_ground);
 
-                            let mut position = entity_mut.get_mut::<Position>().unwrap();
+                            let Some(mut position) = entity_mut.get_mut::<Position>() else {
+                                return;
+                            };
                             if new_pos != **position {
                                 **position = new_pos;
                             }
 
-                            let mut look_direction = entity_mut.get_mut::<LookDirection>().unwrap();
+                            let Some(mut look_direction) = entity_mut.get_mut::<LookDirection>()
+                            else {
+                                return;
+                            };
                             if new_look_direction != *look_direction {
                                 *look_direction = new_look_direction;
                             }
@@ -978,7 +1417,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 let entity = entity_id_index.get(MinecraftEntityId(p.entity_id));
 
@@ -992,10 +1432,15 @@ pub fn process_packet_events(ecs: &mut World) {
                     commands.entity(entity).queue(RelativeEntityUpdate {
                         partial_world: instance_holder.partial_instance.clone(),
                         update: Box::new(move |entity_mut| {
-                            let mut physics = entity_mut.get_mut::<Physics>().unwrap();
+                            let Some(mut physics) = entity_mut.get_mut::<Physics>() else {
+                                return;
+                            };
                             physics.set_on_ground(new_on_ground);
 
-                            let mut look_direction = entity_mut.get_mut::<LookDirection>().unwrap();
+                            let Some(mut look_direction) = entity_mut.get_mut::<LookDirection>()
+                            else {
+                                return;
+                            };
                             if new_look_direction != *look_direction {
                                 *look_direction = new_look_direction;
                             }
@@ -1014,10 +1459,12 @@ pub fn process_packet_events(ecs: &mut World) {
                 debug!("Got keep alive packet {p:?} for {player_entity:?}");
 
                 let mut system_state: SystemState<(
+                    Query<Has<MeasurePing>>,
                     EventWriter<KeepAliveEvent>,
                     EventWriter<SendPacketEvent>,
                 )> = SystemState::new(ecs);
-                let (mut keepalive_events, mut send_packet_events) = system_state.get_mut(ecs);
+                let (query, mut keepalive_events, mut send_packet_events) =
+                    system_state.get_mut(ecs);
 
                 keepalive_events.send(KeepAliveEvent {
                     entity: player_entity,
@@ -1027,6 +1474,16 @@ pub fn process_packet_events(ecs: &mut World) {
                     player_entity,
                     ServerboundKeepAlive { id: p.id },
                 ));
+                // vanilla clients only send ping requests while the ping graph is open, so
+                // measuring our ping this way is opt-in
+                if query.get(player_entity).unwrap_or_default() {
+                    send_packet_events.send(SendPacketEvent::new(
+                        player_entity,
+                        ServerboundPingRequest {
+                            time: unix_time_millis(),
+                        },
+                    ));
+                }
             }
             ClientboundGamePacket::RemoveEntities(p) => {
                 debug!("Got remove entities packet {p:?}");
@@ -1099,8 +1556,25 @@ pub fn process_packet_events(ecs: &mut World) {
                     packet: ChatPacket::Disguised(Arc::new(p.clone())),
                 });
             }
-            ClientboundGamePacket::Sound(_p) => {
-                // debug!("Got sound packet {p:?}");
+            ClientboundGamePacket::Sound(p) => {
+                trace!("Got sound packet {p:?}");
+
+                let mut system_state: SystemState<EventWriter<PlaySoundEvent>> =
+                    SystemState::new(ecs);
+                let mut play_sound_events = system_state.get_mut(ecs);
+
+                play_sound_events.send(PlaySoundEvent {
+                    entity: player_entity,
+                    sound: SoundInfo {
+                        sound: p.sound.clone(),
+                        source: p.source,
+                        // the position is sent as fixed-point numbers
+                        position: Vec3::new(p.x as f64 / 8., p.y as f64 / 8., p.z as f64 / 8.),
+                        volume: p.volume,
+                        pitch: p.pitch,
+                        source_entity: None,
+                    },
+                });
             }
             ClientboundGamePacket::LevelEvent(p) => {
                 debug!("Got level event packet {p:?}");
@@ -1108,54 +1582,151 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::BlockUpdate(p) => {
                 debug!("Got block update packet {p:?}");
 
-                let mut system_state: SystemState<Query<&mut InstanceHolder>> =
-                    SystemState::new(ecs);
-                let mut query = system_state.get_mut(ecs);
-                let local_player = query.get_mut(player_entity).unwrap();
+                let mut system_state: SystemState<(
+                    Query<(&mut InstanceHolder, Option<&InstanceName>)>,
+                    EventWriter<BlockUpdateEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut block_update_events) = system_state.get_mut(ecs);
+                let (local_player, instance_name) = get_client_or_continue!(query, player_entity);
 
                 let world = local_player.instance.write();
 
-                world.chunks.set_block_state(&p.pos, p.block_state);
+                let old_state = world.chunks.set_block_state(&p.pos, p.block_state);
+                if let (Some(old_state), Some(instance_name)) = (old_state, instance_name) {
+                    if old_state != p.block_state {
+                        block_update_events.send(BlockUpdateEvent {
+                            entity: player_entity,
+                            instance: instance_name.clone(),
+                            pos: p.pos,
+                            old: old_state,
+                            new: p.block_state,
+                        });
+                    }
+                }
             }
             ClientboundGamePacket::Animate(p) => {
                 debug!("Got animate packet {p:?}");
             }
             ClientboundGamePacket::SectionBlocksUpdate(p) => {
                 debug!("Got section blocks update packet {p:?}");
-                let mut system_state: SystemState<Query<&mut InstanceHolder>> =
-                    SystemState::new(ecs);
-                let mut query = system_state.get_mut(ecs);
-                let local_player = query.get_mut(player_entity).unwrap();
+                let mut system_state: SystemState<(
+                    Query<(&mut InstanceHolder, Option<&InstanceName>)>,
+                    EventWriter<BlockUpdateEvent>,
+                    EventWriter<SectionBlocksUpdateEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut block_update_events, mut section_blocks_update_events) =
+                    system_state.get_mut(ecs);
+                let (local_player, instance_name) = get_client_or_continue!(query, player_entity);
 
                 let world = local_player.instance.write();
 
+                let mut changes = Vec::new();
                 for state in &p.states {
-                    world
-                        .chunks
-                        .set_block_state(&(p.section_pos + state.pos), state.state);
+                    let pos = p.section_pos + state.pos;
+                    let old_state = world.chunks.set_block_state(&pos, state.state);
+                    if let Some(old_state) = old_state {
+                        if old_state != state.state {
+                            changes.push((pos, old_state, state.state));
+                        }
+                    }
+                }
+
+                if let Some(instance_name) = instance_name {
+                    if !changes.is_empty() {
+                        block_update_events.send_batch(changes.iter().map(|&(pos, old, new)| {
+                            BlockUpdateEvent {
+                                entity: player_entity,
+                                instance: instance_name.clone(),
+                                pos,
+                                old,
+                                new,
+                            }
+                        }));
+                        section_blocks_update_events.send(SectionBlocksUpdateEvent {
+                            entity: player_entity,
+                            instance: instance_name.clone(),
+                            section_pos: p.section_pos,
+                            changes,
+                        });
+                    }
                 }
             }
             ClientboundGamePacket::GameEvent(p) => {
-                use azalea_protocol::packets::game::c_game_event::EventType;
-
                 debug!("Got game event packet {p:?}");
 
-                #[allow(clippy::single_match)]
-                match p.event {
-                    EventType::ChangeGameMode => {
+                let Some(change) = GameStateChange::from_packet(p) else {
+                    warn!("Got game event packet with an invalid parameter: {p:?}");
+                    continue;
+                };
+
+                let mut system_state: SystemState<EventWriter<GameStateChangeEvent>> =
+                    SystemState::new(ecs);
+                let mut game_state_change_events = system_state.get_mut(ecs);
+                game_state_change_events.send(GameStateChangeEvent {
+                    entity: player_entity,
+                    change,
+                });
+
+                match change {
+                    GameStateChange::GameModeChanged(new_game_mode) => {
                         let mut system_state: SystemState<Query<&mut LocalGameMode>> =
                             SystemState::new(ecs);
                         let mut query = system_state.get_mut(ecs);
-                        let mut local_game_mode = query.get_mut(player_entity).unwrap();
-                        if let Some(new_game_mode) = GameMode::from_id(p.param as u8) {
-                            local_game_mode.current = new_game_mode;
+                        let mut local_game_mode = get_client_or_continue!(query, player_entity);
+                        local_game_mode.current = new_game_mode;
+                    }
+                    GameStateChange::RainStarted
+                    | GameStateChange::RainStopped
+                    | GameStateChange::RainLevelChanged(_)
+                    | GameStateChange::ThunderLevelChanged(_) => {
+                        let mut system_state: SystemState<(
+                            Commands,
+                            Query<Option<&Weather>>,
+                            EventWriter<WeatherChangeEvent>,
+                        )> = SystemState::new(ecs);
+                        let (mut commands, mut query, mut weather_change_events) =
+                            system_state.get_mut(ecs);
+                        let old = get_client_or_continue!(query, player_entity)
+                            .cloned()
+                            .unwrap_or_default();
+
+                        let mut new = old.clone();
+                        match change {
+                            // the level changes gradually after this, with RainLevelChanged
+                            GameStateChange::RainStarted => new.rain_level = 0.,
+                            GameStateChange::RainStopped => new.rain_level = 1.,
+                            GameStateChange::RainLevelChanged(level) => new.rain_level = level,
+                            GameStateChange::ThunderLevelChanged(level) => {
+                                new.thunder_level = level
+                            }
+                            _ => unreachable!(),
+                        }
+
+                        if new != old {
+                            commands.entity(player_entity).insert(new.clone());
+                            weather_change_events.send(WeatherChangeEvent {
+                                entity: player_entity,
+                                old,
+                                new,
+                            });
                         }
+
+                        system_state.apply(ecs);
                     }
                     _ => {}
                 }
             }
             ClientboundGamePacket::LevelParticles(p) => {
-                debug!("Got level particles packet {p:?}");
+                trace!("Got level particles packet {p:?}");
+
+                let mut system_state: SystemState<EventWriter<ParticleEvent>> =
+                    SystemState::new(ecs);
+                let mut particle_events = system_state.get_mut(ecs);
+
+                particle_events.send(ParticleEvent {
+                    entity: player_entity,
+                    packet: p.clone(),
+                });
             }
             ClientboundGamePacket::ServerData(p) => {
                 debug!("Got server data packet {p:?}");
@@ -1165,8 +1736,98 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::UpdateMobEffect(p) => {
                 debug!("Got update mob effect packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<&mut ActiveEffects>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut active_effects_query) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(entity) = entity_id_index.get(MinecraftEntityId(p.entity_id)) else {
+                    debug!(
+                        "Got update mob effect packet for unknown entity id {}",
+                        p.entity_id
+                    );
+                    continue;
+                };
+                let Ok(mut active_effects) = active_effects_query.get_mut(entity) else {
+                    continue;
+                };
+                active_effects.insert(
+                    p.mob_effect,
+                    MobEffectData {
+                        amplifier: p.effect_amplifier,
+                        // infinite effects are sent as -1
+                        duration_ticks: p.effect_duration_ticks as i32,
+                    },
+                );
+            }
+            ClientboundGamePacket::AddExperienceOrb(p) => {
+                debug!("Got add experience orb packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<(&mut EntityIdIndex, Option<&InstanceName>)>,
+                    Query<&mut LoadedBy>,
+                    Res<InstanceContainer>,
+                )> = SystemState::new(ecs);
+                let (mut commands, mut query, mut loaded_by_query, instance_container) =
+                    system_state.get_mut(ecs);
+                let (mut entity_id_index, instance_name) =
+                    get_client_or_continue!(query, player_entity);
+
+                let entity_id = MinecraftEntityId(p.id);
+
+                let Some(instance_name) = instance_name else {
+                    warn!("got add experience orb packet but we haven't gotten a login packet yet");
+                    continue;
+                };
+
+                let Some(instance) = instance_container.get(instance_name) else {
+                    warn!(
+                        "got add experience orb packet but the instance {} doesn't exist",
+                        **instance_name
+                    );
+                    continue;
+                };
+                if let Some(&ecs_entity) = instance.read().entity_by_id.get(&entity_id) {
+                    // another client already has this orb loaded
+                    if let Ok(mut loaded_by) = loaded_by_query.get_mut(ecs_entity) {
+                        loaded_by.insert(player_entity);
+                    }
+                    entity_id_index.insert(entity_id, ecs_entity);
+                    continue;
+                }
+
+                // experience orbs don't have a uuid in the packet, so they're only indexed by
+                // their id
+                let bundle = EntityBundle::new(
+                    Uuid::nil(),
+                    p.pos,
+                    azalea_registry::EntityKind::ExperienceOrb,
+                    (**instance_name).clone(),
+                );
+                let mut spawned = commands.spawn((
+                    entity_id,
+                    LoadedBy(HashSet::from([player_entity])),
+                    ExperienceOrbValue(p.value),
+                    bundle,
+                ));
+                let ecs_entity = spawned.id();
+                apply_default_metadata(&mut spawned, azalea_registry::EntityKind::ExperienceOrb);
+
+                azalea_entity::indexing::add_entity_to_indexes(
+                    entity_id,
+                    ecs_entity,
+                    None,
+                    &mut entity_id_index,
+                    &mut instance.write(),
+                );
+
+                system_state.apply(ecs);
             }
-            ClientboundGamePacket::AddExperienceOrb(_) => {}
             ClientboundGamePacket::AwardStats(_) => {}
             ClientboundGamePacket::BlockChangedAck(_) => {}
             ClientboundGamePacket::BlockDestruction(_) => {}
@@ -1184,7 +1845,7 @@ pub fn process_packet_events(ecs: &mut World) {
                     EventWriter<SetContainerContentEvent>,
                 )> = SystemState::new(ecs);
                 let (mut query, mut events) = system_state.get_mut(ecs);
-                let mut inventory = query.get_mut(player_entity).unwrap();
+                let mut inventory = get_client_or_continue!(query, player_entity);
 
                 // container id 0 is always the player's inventory
                 if p.container_id == 0 {
@@ -1221,7 +1882,7 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 let mut system_state: SystemState<Query<&mut Inventory>> = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let mut inventory = query.get_mut(player_entity).unwrap();
+                let mut inventory = get_client_or_continue!(query, player_entity);
 
                 if p.container_id == -1 {
                     // -1 means carried item
@@ -1283,19 +1944,75 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::ForgetLevelChunk(p) => {
                 debug!("Got forget level chunk packet {p:?}");
 
-                let mut system_state: SystemState<Query<&mut InstanceHolder>> =
-                    SystemState::new(ecs);
+                let mut system_state: SystemState<
+                    Query<(&mut InstanceHolder, Option<&mut chunks::PendingChunks>)>,
+                > = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
-                let local_player = query.get_mut(player_entity).unwrap();
+                let (local_player, pending_chunks) = get_client_or_continue!(query, player_entity);
 
-                let mut partial_instance = local_player.partial_instance.write();
+                if let Some(mut pending_chunks) = pending_chunks {
+                    pending_chunks.cancel(&p.pos);
+                }
 
-                partial_instance.chunks.limited_set(&p.pos, None);
+                let chunk = local_player
+                    .partial_instance
+                    .write()
+                    .chunks
+                    .limited_get_mut(&p.pos)
+                    .and_then(Option::take);
+                // forget the chunk (or move it to the cache) if we were the last client that
+                // had it
+                if let Some(chunk) = chunk {
+                    local_player.instance.write().chunks.unload(p.pos, chunk);
+                }
             }
             ClientboundGamePacket::HorseScreenOpen(_) => {}
-            ClientboundGamePacket::MapItemData(_) => {}
-            ClientboundGamePacket::MerchantOffers(_) => {}
-            ClientboundGamePacket::MoveVehicle(_) => {}
+            ClientboundGamePacket::MapItemData(p) => {
+                debug!("Got map item data packet for map {}", p.map_id);
+
+                let mut system_state: SystemState<Query<&mut Maps>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let Ok(mut maps) = query.get_mut(player_entity) else {
+                    warn!("got map item data packet but we don't have a Maps component");
+                    continue;
+                };
+
+                maps.entry(p.map_id).or_default().update(p);
+            }
+            ClientboundGamePacket::MerchantOffers(p) => {
+                debug!("Got merchant offers packet {p:?}");
+
+                let mut system_state: SystemState<Commands> = SystemState::new(ecs);
+                let mut commands = system_state.get_mut(ecs);
+                commands.entity(player_entity).insert(MerchantOffers {
+                    container_id: p.container_id,
+                    offers: p.offers.clone(),
+                    villager_level: p.villager_level,
+                    villager_xp: p.villager_xp,
+                });
+                system_state.apply(ecs);
+            }
+            ClientboundGamePacket::MoveVehicle(p) => {
+                debug!("Got move vehicle packet {p:?}");
+
+                // the server is correcting the position of the vehicle that we're controlling
+                let mut system_state: SystemState<(
+                    Query<&Vehicle>,
+                    Query<(&mut Position, &mut LookDirection), Without<LocalEntity>>,
+                )> = SystemState::new(ecs);
+                let (vehicle_query, mut vehicle_position_query) = system_state.get_mut(ecs);
+                let Ok(vehicle) = vehicle_query.get(player_entity) else {
+                    debug!("Got move vehicle packet but we're not riding anything");
+                    continue;
+                };
+                let Ok((mut position, mut look_direction)) =
+                    vehicle_position_query.get_mut(**vehicle)
+                else {
+                    continue;
+                };
+                **position = p.pos;
+                *look_direction = p.look_direction;
+            }
             ClientboundGamePacket::OpenBook(_) => {}
             ClientboundGamePacket::OpenScreen(p) => {
                 debug!("Got open screen packet {p:?}");
@@ -1335,7 +2052,7 @@ pub fn process_packet_events(ecs: &mut World) {
                     EventWriter<DeathEvent>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query, mut death_events) = system_state.get_mut(ecs);
-                let (entity_id, dead) = query.get_mut(player_entity).unwrap();
+                let (entity_id, dead) = get_client_or_continue!(query, player_entity);
 
                 if **entity_id == p.player_id && dead.is_none() {
                     commands.entity(player_entity).insert(Dead);
@@ -1348,7 +2065,27 @@ pub fn process_packet_events(ecs: &mut World) {
                 system_state.apply(ecs);
             }
             ClientboundGamePacket::PlayerLookAt(_) => {}
-            ClientboundGamePacket::RemoveMobEffect(_) => {}
+            ClientboundGamePacket::RemoveMobEffect(p) => {
+                debug!("Got remove mob effect packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<&mut ActiveEffects>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut active_effects_query) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(entity) = entity_id_index.get(MinecraftEntityId(p.entity_id)) else {
+                    debug!(
+                        "Got remove mob effect packet for unknown entity id {}",
+                        p.entity_id
+                    );
+                    continue;
+                };
+                if let Ok(mut active_effects) = active_effects_query.get_mut(entity) {
+                    active_effects.remove(&p.effect);
+                }
+            }
             ClientboundGamePacket::ResourcePackPush(p) => {
                 debug!("Got resource pack packet {p:?}");
 
@@ -1381,28 +2118,38 @@ pub fn process_packet_events(ecs: &mut World) {
                     )>,
                     EventWriter<InstanceLoadedEvent>,
                     ResMut<InstanceContainer>,
+                    Res<ShareInstances>,
                 )> = SystemState::new(ecs);
-                let (mut commands, mut query, mut instance_loaded_events, mut instance_container) =
-                    system_state.get_mut(ecs);
+                let (
+                    mut commands,
+                    mut query,
+                    mut instance_loaded_events,
+                    mut instance_container,
+                    share_instances,
+                ) = system_state.get_mut(ecs);
                 let (mut instance_holder, game_profile, client_information) =
-                    query.get_mut(player_entity).unwrap();
+                    get_client_or_continue!(query, player_entity);
 
                 {
-                    let new_instance_name = p.common.dimension.clone();
-
-                    let Some(dimension_type_element) =
-                        instance_holder.instance.read().registries.dimension_type()
+                    let new_instance_name =
+                        share_instances.instance_name(&p.common.dimension, &game_profile.name);
+
+                    let Some((dimension_type_name, dimension)) = instance_holder
+                        .instance
+                        .read()
+                        .registries
+                        .dimension_type_by_id(p.common.dimension_type)
                     else {
-                        error!("Server didn't send dimension type registry, can't log in.");
-                        continue;
-                    };
-
-                    let dimension_name = ResourceLocation::new(&p.common.dimension.to_string());
-
-                    let Some(dimension) = dimension_type_element.map.get(&dimension_name) else {
-                        error!("No dimension_type with name {dimension_name}");
+                        error!(
+                            "No dimension_type with ID {}, can't join {}",
+                            p.common.dimension_type, p.common.dimension
+                        );
                         continue;
                     };
+                    debug!(
+                        "{} uses the dimension type {dimension_type_name} (height {}, min y {})",
+                        p.common.dimension, dimension.height, dimension.min_y
+                    );
 
                     // add this world to the instance_container (or don't if it's already
                     // there)
@@ -1444,23 +2191,32 @@ pub fn process_packet_events(ecs: &mut World) {
                         },
                         entity_bundle,
                     ));
+                    // the server will tell us if it's raining in the new world
+                    commands
+                        .entity(player_entity)
+                        .remove::<(Weather, SpectatorCamera)>();
                 }
 
                 // Remove the Dead marker component from the player.
-                commands.entity(player_entity).remove::<Dead>();
+                commands
+                    .entity(player_entity)
+                    .remove::<(Dead, LastDamageSource)>();
 
                 system_state.apply(ecs);
             }
 
             ClientboundGamePacket::StartConfiguration(_p) => {
-                let mut system_state: SystemState<(Commands, EventWriter<SendPacketEvent>)> =
+                let mut system_state: SystemState<(Commands, Query<&mut RawConnection>)> =
                     SystemState::new(ecs);
-                let (mut commands, mut packet_events) = system_state.get_mut(ecs);
+                let (mut commands, mut query) = system_state.get_mut(ecs);
+                let mut raw_conn = get_client_or_continue!(query, player_entity);
 
-                packet_events.send(SendPacketEvent::new(
-                    player_entity,
-                    ServerboundConfigurationAcknowledged {},
-                ));
+                // this is written right away instead of with a SendPacketEvent
+                // so it's sent before we switch states
+                if let Err(e) = raw_conn.write_packet(ServerboundConfigurationAcknowledged {}) {
+                    error!("Failed to send packet: {e}");
+                }
+                raw_conn.set_state(ConnectionProtocol::Configuration);
 
                 commands
                     .entity(player_entity)
@@ -1476,7 +2232,8 @@ pub fn process_packet_events(ecs: &mut World) {
                     Query<(&EntityIdIndex, &InstanceHolder)>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query) = system_state.get_mut(ecs);
-                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+                let (entity_id_index, instance_holder) =
+                    get_client_or_continue!(query, player_entity);
 
                 let Some(entity) = entity_id_index.get(MinecraftEntityId(p.id)) else {
                     debug!("Got teleport entity packet for unknown entity id {}", p.id);
@@ -1491,7 +2248,9 @@ pub fn process_packet_events(ecs: &mut World) {
                     partial_world: instance_holder.partial_instance.clone(),
                     update: Box::new(move |entity_mut| {
                         let is_local_entity = entity_mut.get::<LocalEntity>().is_some();
-                        let mut physics = entity_mut.get_mut::<Physics>().unwrap();
+                        let Some(mut physics) = entity_mut.get_mut::<Physics>() else {
+                            return;
+                        };
 
                         physics.vec_delta_codec.set_base(new_position);
 
@@ -1502,13 +2261,19 @@ pub fn process_packet_events(ecs: &mut World) {
 
                         physics.set_on_ground(new_on_ground);
 
-                        let mut last_sent_position =
-                            entity_mut.get_mut::<LastSentPosition>().unwrap();
+                        let Some(mut last_sent_position) = entity_mut.get_mut::<LastSentPosition>()
+                        else {
+                            return;
+                        };
                         **last_sent_position = new_position;
-                        let mut position = entity_mut.get_mut::<Position>().unwrap();
+                        let Some(mut position) = entity_mut.get_mut::<Position>() else {
+                            return;
+                        };
                         **position = new_position;
 
-                        let mut look_direction = entity_mut.get_mut::<LookDirection>().unwrap();
+                        let Some(mut look_direction) = entity_mut.get_mut::<LookDirection>() else {
+                            return;
+                        };
                         *look_direction = new_look_direction;
                     }),
                 });
@@ -1518,15 +2283,109 @@ pub fn process_packet_events(ecs: &mut World) {
 
             ClientboundGamePacket::SelectAdvancementsTab(_) => {}
             ClientboundGamePacket::SetActionBarText(_) => {}
-            ClientboundGamePacket::SetBorderCenter(_) => {}
-            ClientboundGamePacket::SetBorderLerpSize(_) => {}
-            ClientboundGamePacket::SetBorderSize(_) => {}
+            ClientboundGamePacket::SetBorderCenter(p) => {
+                debug!("Got set border center packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.center_x = p.new_center_x;
+                    world_border.center_z = p.new_center_z;
+                }
+            }
+            ClientboundGamePacket::SetBorderLerpSize(p) => {
+                debug!("Got set border lerp size packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.size = p.new_size;
+                }
+            }
+            ClientboundGamePacket::SetBorderSize(p) => {
+                debug!("Got set border size packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.size = p.size;
+                }
+            }
             ClientboundGamePacket::SetBorderWarningDelay(_) => {}
             ClientboundGamePacket::SetBorderWarningDistance(_) => {}
-            ClientboundGamePacket::SetCamera(_) => {}
+            ClientboundGamePacket::SetCamera(p) => {
+                debug!("Got set camera packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<(&EntityIdIndex, &MinecraftEntityId)>,
+                )> = SystemState::new(ecs);
+                let (mut commands, query) = system_state.get_mut(ecs);
+                let Ok((entity_id_index, our_entity_id)) = query.get(player_entity) else {
+                    continue;
+                };
+
+                let camera_id = MinecraftEntityId(p.camera_id);
+                if camera_id == *our_entity_id {
+                    commands.entity(player_entity).remove::<SpectatorCamera>();
+                } else if let Some(camera_entity) = entity_id_index.get(camera_id) {
+                    commands
+                        .entity(player_entity)
+                        .insert(SpectatorCamera(camera_entity));
+                } else {
+                    warn!("Server set our camera to an unknown entity {camera_id:?}");
+                }
+
+                system_state.apply(ecs);
+            }
             ClientboundGamePacket::SetDisplayObjective(_) => {}
             ClientboundGamePacket::SetObjective(_) => {}
-            ClientboundGamePacket::SetPassengers(_) => {}
+            ClientboundGamePacket::SetPassengers(p) => {
+                debug!("Got set passengers packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<&EntityIdIndex>,
+                    Query<&Passengers>,
+                )> = SystemState::new(ecs);
+                let (mut commands, mut query, passengers_query) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(vehicle) = entity_id_index.get(MinecraftEntityId(p.vehicle)) else {
+                    debug!(
+                        "Got set passengers packet for unknown entity id {}",
+                        p.vehicle
+                    );
+                    continue;
+                };
+                let passengers = p
+                    .passengers
+                    .iter()
+                    .filter_map(|&id| entity_id_index.get(MinecraftEntityId(id)))
+                    .collect::<Vec<_>>();
+
+                // entities that got off of the vehicle
+                if let Ok(old_passengers) = passengers_query.get(vehicle) {
+                    for &old_passenger in old_passengers.iter() {
+                        if !passengers.contains(&old_passenger) {
+                            if let Some(mut entity_commands) = commands.get_entity(old_passenger) {
+                                entity_commands.remove::<(Vehicle, WantsToDismount)>();
+                            }
+                        }
+                    }
+                }
+
+                for &passenger in &passengers {
+                    commands.entity(passenger).insert(Vehicle(vehicle));
+                }
+                if passengers.is_empty() {
+                    commands.entity(vehicle).remove::<Passengers>();
+                } else {
+                    commands.entity(vehicle).insert(Passengers(passengers));
+                }
+
+                system_state.apply(ecs);
+            }
             ClientboundGamePacket::SetPlayerTeam(_) => {}
             ClientboundGamePacket::SetScore(_) => {}
             ClientboundGamePacket::SetSimulationDistance(_) => {}
@@ -1534,13 +2393,132 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::SetTitleText(_) => {}
             ClientboundGamePacket::SetTitlesAnimation(_) => {}
             ClientboundGamePacket::ClearTitles(_) => {}
-            ClientboundGamePacket::SoundEntity(_) => {}
+            ClientboundGamePacket::SoundEntity(p) => {
+                trace!("Got sound entity packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<&Position>,
+                    EventWriter<PlaySoundEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, position_query, mut play_sound_events) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let Some(source_entity) = entity_id_index.get(MinecraftEntityId(p.id)) else {
+                    debug!("Got sound entity packet for unknown entity id {}", p.id);
+                    continue;
+                };
+                let Ok(position) = position_query.get(source_entity) else {
+                    continue;
+                };
+
+                play_sound_events.send(PlaySoundEvent {
+                    entity: player_entity,
+                    sound: SoundInfo {
+                        sound: p.sound.clone(),
+                        source: p.source,
+                        position: **position,
+                        volume: p.volume,
+                        pitch: p.pitch,
+                        source_entity: Some(source_entity),
+                    },
+                });
+            }
             ClientboundGamePacket::StopSound(_) => {}
-            ClientboundGamePacket::TabList(_) => {}
+            ClientboundGamePacket::TabList(p) => {
+                debug!("Got tab list packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&mut TabListHeader>,
+                    EventWriter<TabListUpdatedEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut tab_list_updated_events) = system_state.get_mut(ecs);
+                let mut tab_list_header = get_client_or_continue!(query, player_entity);
+
+                tab_list_header.header = p.header.clone();
+                tab_list_header.footer = p.footer.clone();
+
+                tab_list_updated_events.send(TabListUpdatedEvent {
+                    entity: player_entity,
+                });
+            }
             ClientboundGamePacket::TagQuery(_) => {}
-            ClientboundGamePacket::TakeItemEntity(_) => {}
+            ClientboundGamePacket::TakeItemEntity(p) => {
+                debug!("Got take item entity packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<(Option<&ItemItem>, Option<&ExperienceOrbValue>)>,
+                    EventWriter<ItemPickupEvent>,
+                    EventWriter<ExperienceOrbPickupEvent>,
+                )> = SystemState::new(ecs);
+                let (
+                    mut query,
+                    picked_up_query,
+                    mut item_pickup_events,
+                    mut experience_orb_pickup_events,
+                ) = system_state.get_mut(ecs);
+                let entity_id_index = get_client_or_continue!(query, player_entity);
+
+                let (Some(picked_up), Some(collector)) = (
+                    entity_id_index.get(MinecraftEntityId(p.item_id)),
+                    entity_id_index.get(MinecraftEntityId(p.player_id)),
+                ) else {
+                    debug!("Got take item entity packet for unknown entities");
+                    continue;
+                };
+                let Ok((item, experience_orb_value)) = picked_up_query.get(picked_up) else {
+                    continue;
+                };
+
+                if let Some(item) = item {
+                    // the amount is how many were actually picked up
+                    let mut item = (**item).clone();
+                    if let ItemStack::Present(data) = &mut item {
+                        data.count = p.amount as i32;
+                    }
+                    item_pickup_events.send(ItemPickupEvent {
+                        entity: player_entity,
+                        collector,
+                        item_entity: picked_up,
+                        item,
+                    });
+                } else if let Some(value) = experience_orb_value {
+                    experience_orb_pickup_events.send(ExperienceOrbPickupEvent {
+                        entity: player_entity,
+                        collector,
+                        orb_entity: picked_up,
+                        value: **value,
+                    });
+                }
+            }
             ClientboundGamePacket::BundleDelimiter(_) => {}
-            ClientboundGamePacket::DamageEvent(_) => {}
+            ClientboundGamePacket::DamageEvent(p) => {
+                debug!("Got damage event packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<(&MinecraftEntityId, &EntityIdIndex)>,
+                )> = SystemState::new(ecs);
+                let (mut commands, mut query) = system_state.get_mut(ecs);
+                let (entity_id, entity_id_index) = get_client_or_continue!(query, player_entity);
+
+                // we only keep track of damage to ourselves
+                if **entity_id != p.entity_id {
+                    continue;
+                }
+
+                let get_entity =
+                    |id: Option<u32>| id.and_then(|id| entity_id_index.get(MinecraftEntityId(id)));
+                commands.entity(player_entity).insert(LastDamageSource {
+                    cause: get_entity(p.source_cause_id.0),
+                    direct: get_entity(p.source_direct_id.0),
+                    position: p.source_position,
+                });
+
+                system_state.apply(ecs);
+            }
             ClientboundGamePacket::HurtAnimation(_) => {}
 
             ClientboundGamePacket::TickingState(_) => {}
@@ -1549,7 +2527,18 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::ResetScore(_) => {}
             ClientboundGamePacket::CookieRequest(_) => {}
             ClientboundGamePacket::DebugSample(_) => {}
-            ClientboundGamePacket::PongResponse(_) => {}
+            ClientboundGamePacket::PongResponse(p) => {
+                debug!("Got pong response packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut Ping>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let Ok(mut ping) = query.get_mut(player_entity) else {
+                    continue;
+                };
+
+                let round_trip = unix_time_millis().saturating_sub(p.time);
+                ping.measured = Some(Duration::from_millis(round_trip));
+            }
             ClientboundGamePacket::StoreCookie(_) => {}
             ClientboundGamePacket::Transfer(_) => {}
             ClientboundGamePacket::MoveMinecartAlongTrack(_) => {}
@@ -1592,3 +2581,12 @@ pub fn handle_send_packet_event(
         }
     }
 }
+
+/// The number of milliseconds since the Unix epoch, used for measuring our
+/// ping.
+fn unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis() as u64)
+        .unwrap_or_default()
+}
@@ -1,22 +1,157 @@
+use std::{fmt::Debug, io::Cursor};
+
+use azalea_buf::AzaleaReadVar;
 use azalea_entity::{metadata::Health, EntityUpdateSet};
+use azalea_protocol::{
+    packets::{ConnectionProtocol, ProtocolPacket},
+    read::{deserialize_packet, deserialize_packet_lenient, PacketWarning, ReadPacketError},
+};
 use bevy_app::{App, First, Plugin, PreUpdate, Update};
-use bevy_ecs::prelude::*;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use tracing::{debug, error, warn};
 
 use self::{
     game::{
-        AddPlayerEvent, DeathEvent, InstanceLoadedEvent, KeepAliveEvent, RemovePlayerEvent,
-        ResourcePackEvent, UpdatePlayerEvent,
+        AddPlayerEvent, BlockUpdateEvent, DeathEvent, ExperienceOrbPickupEvent,
+        GameStateChangeEvent, InstanceLoadedEvent, ItemPickupEvent, KeepAliveEvent, ParticleEvent,
+        PlaySoundEvent, RemovePlayerEvent, ResourcePackEvent, SectionBlocksUpdateEvent,
+        TabListUpdatedEvent, TimeUpdateEvent, UpdatePlayerEvent, WeatherChangeEvent, XpChangeEvent,
     },
     login::{LoginPacketEvent, SendLoginPacketEvent},
 };
 use crate::{chat::ChatReceivedEvent, events::death_listener};
 
+/// Get our client's item from a query in a packet handler, or log why our
+/// client doesn't match the query and skip the packet.
+macro_rules! get_client_or_continue {
+    ($query:expr, $player_entity:expr) => {
+        match $crate::error::get_client(&mut $query, $player_entity) {
+            Ok(item) => item,
+            Err(err) => {
+                tracing::error!("Couldn't handle packet for {}: {err}", $player_entity);
+                continue;
+            }
+        }
+    };
+}
+
 pub mod configuration;
 pub mod game;
 pub mod login;
 
 pub struct PacketHandlerPlugin;
 
+/// An event sent when the server sends us a packet with an id that Azalea
+/// doesn't know about, which usually means that the server is modded.
+///
+/// Without this, the packet would only be logged as an error and ignored.
+#[derive(Event, Debug, Clone)]
+pub struct UnknownPacketEvent {
+    /// The client entity that received the packet.
+    pub entity: Entity,
+    /// The state that the connection was in when the packet was received.
+    pub state: ConnectionProtocol,
+    pub id: u32,
+    /// The contents of the packet, not including the id.
+    pub bytes: Box<[u8]>,
+}
+
+/// A component that makes a client read packets leniently, so packets with
+/// extra data at the end are still handled instead of being ignored.
+///
+/// A [`PacketWarningEvent`] is sent for every packet that had something wrong
+/// with it. See [`deserialize_packet_lenient`] for more details.
+///
+/// Packets that are received while logging in are always read strictly, so
+/// you'll usually want to insert this when you get [`Event::Init`].
+///
+/// ```
+/// # use azalea_client::{Client, packet_handling::LenientPacketParsing};
+/// # fn example(bot: &Client) {
+/// bot.ecs.lock().entity_mut(bot.entity).insert(LenientPacketParsing);
+/// # }
+/// ```
+///
+/// [`Event::Init`]: crate::Event::Init
+#[derive(Component, Clone, Debug)]
+pub struct LenientPacketParsing;
+
+/// An event sent when a client with [`LenientPacketParsing`] receives a packet
+/// that we could only partly understand.
+#[derive(Event, Debug, Clone)]
+pub struct PacketWarningEvent {
+    /// The client entity that received the packet.
+    pub entity: Entity,
+    pub warning: PacketWarning,
+}
+
+/// The events that can be sent when reading packets from the server.
+#[derive(SystemParam)]
+pub struct IncomingPacketReader<'w> {
+    unknown_packet_events: EventWriter<'w, UnknownPacketEvent>,
+    packet_warning_events: EventWriter<'w, PacketWarningEvent>,
+}
+
+impl IncomingPacketReader<'_> {
+    /// Deserialize a packet that a client received, or return `None` and log
+    /// or send an event if it couldn't be read.
+    pub fn read<P: ProtocolPacket + Debug>(
+        &mut self,
+        entity: Entity,
+        state: ConnectionProtocol,
+        raw_packet: &[u8],
+        lenient: bool,
+    ) -> Option<P> {
+        let result = if lenient {
+            deserialize_packet_lenient::<P>(&mut Cursor::new(raw_packet)).map(|lenient_packet| {
+                if let Some(warning) = lenient_packet.warning {
+                    warn!("{warning}");
+                    self.packet_warning_events
+                        .send(PacketWarningEvent { entity, warning });
+                }
+                lenient_packet.packet
+            })
+        } else {
+            deserialize_packet::<P>(&mut Cursor::new(raw_packet)).map(Some)
+        };
+
+        match result {
+            Ok(Some(packet)) => Some(packet),
+            Ok(None) => {
+                self.send_unknown_packet_event(entity, state, raw_packet);
+                None
+            }
+            Err(err) => {
+                if let ReadPacketError::UnknownPacketId { .. } = *err {
+                    self.send_unknown_packet_event(entity, state, raw_packet);
+                    return None;
+                }
+                error!("failed to read packet: {err:?}");
+                debug!("packet bytes: {raw_packet:?}");
+                None
+            }
+        }
+    }
+
+    fn send_unknown_packet_event(
+        &mut self,
+        entity: Entity,
+        state: ConnectionProtocol,
+        raw_packet: &[u8],
+    ) {
+        let mut cursor = Cursor::new(raw_packet);
+        let Ok(id) = u32::azalea_read_var(&mut cursor) else {
+            return;
+        };
+        self.unknown_packet_events.send(UnknownPacketEvent {
+            entity,
+            state,
+            id,
+            bytes: raw_packet[cursor.position() as usize..].into(),
+        });
+    }
+}
+
 pub fn death_event_on_0_health(
     query: Query<(Entity, &Health), Changed<Health>>,
     mut death_events: EventWriter<DeathEvent>,
@@ -35,7 +170,12 @@ impl Plugin for PacketHandlerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             First,
-            (game::send_packet_events, configuration::send_packet_events),
+            (
+                // the state has to be right before we read any packets
+                crate::raw_connection::apply_taken_over_state,
+                (game::send_packet_events, configuration::send_packet_events),
+            )
+                .chain(),
         )
         .add_systems(
             PreUpdate,
@@ -65,14 +205,55 @@ impl Plugin for PacketHandlerPlugin {
         .add_event::<game::SendPacketEvent>()
         .add_event::<configuration::SendConfigurationEvent>()
         .add_event::<AddPlayerEvent>()
+        .add_event::<BlockUpdateEvent>()
+        .add_event::<SectionBlocksUpdateEvent>()
         .add_event::<RemovePlayerEvent>()
         .add_event::<UpdatePlayerEvent>()
+        .add_event::<TabListUpdatedEvent>()
         .add_event::<ChatReceivedEvent>()
         .add_event::<DeathEvent>()
+        .add_event::<XpChangeEvent>()
+        .add_event::<TimeUpdateEvent>()
+        .add_event::<WeatherChangeEvent>()
+        .add_event::<GameStateChangeEvent>()
+        .add_event::<ItemPickupEvent>()
+        .add_event::<ExperienceOrbPickupEvent>()
+        .add_event::<PlaySoundEvent>()
+        .add_event::<ParticleEvent>()
         .add_event::<KeepAliveEvent>()
         .add_event::<ResourcePackEvent>()
         .add_event::<InstanceLoadedEvent>()
         .add_event::<LoginPacketEvent>()
-        .add_event::<SendLoginPacketEvent>();
+        .add_event::<SendLoginPacketEvent>()
+        .add_event::<UnknownPacketEvent>()
+        .add_event::<PacketWarningEvent>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_unknown_packet() {
+        let error = ReadPacketError::UnknownPacketId {
+            state_name: "game".to_owned(),
+            id: 0x80,
+        };
+        assert_eq!(
+            split_unknown_packet(&error, &[0x80, 0x01, 1, 2, 3]),
+            Some((0x80, vec![1, 2, 3].into_boxed_slice()))
+        );
+        assert_eq!(
+            split_unknown_packet(&error, &[0x05]),
+            Some((5, Box::default()))
+        );
+
+        // other errors aren't for unknown packets
+        let error = ReadPacketError::LeftoverData {
+            data: vec![1],
+            packet_name: "keep_alive".to_owned(),
+        };
+        assert_eq!(split_unknown_packet(&error, &[0x80, 0x01]), None);
     }
 }
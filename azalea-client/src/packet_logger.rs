@@ -0,0 +1,610 @@
+//! Record the packets that bots send and receive to a file so they can be
+//! looked at later.
+//!
+//! ```no_run
+//! # use azalea_client::packet_logger::{PacketFilter, PacketLoggerPlugin};
+//! # fn example(app: &mut bevy_app::App) {
+//! app.add_plugins(
+//!     PacketLoggerPlugin::new("packets.azpl")
+//!         .filter(PacketFilter::except(["level_chunk_with_light", "move_entity_pos"])),
+//! );
+//! # }
+//! ```
+//!
+//! The file starts with [`MAGIC`], the [`FORMAT_VERSION`], and the protocol
+//! version as a varint, and then has a record for every packet. Every record
+//! is:
+//!
+//! - The number of milliseconds since the log was started, as a varint.
+//! - A byte with the [`PacketFlow`] in the highest bit (1 for serverbound) and
+//!   the connection state in the other bits, which is the
+//!   [`ConnectionProtocol`] number plus one.
+//! - The id of the client that the packet belongs to, as a varint. This is only
+//!   unique within the log.
+//! - The length of the packet as a varint, followed by the packet id and data.
+//!
+//! Use [`PacketLogReader`] to read the records back.
+//!
+//! Everything that goes through a client's [`RawConnection`] is recorded, as
+//! well as the handshake and login packets that are sent and received while
+//! [`Client::join`] is connecting.
+//!
+//! [`Client::join`]: crate::Client::join
+
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    fs::File,
+    io::{self, BufWriter, Cursor, Read, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use azalea_buf::{AzaleaReadVar, AzaleaWrite, AzaleaWriteVar};
+use azalea_protocol::{
+    packets::{
+        config::{ClientboundConfigPacket, ServerboundConfigPacket},
+        game::{ClientboundGamePacket, ServerboundGamePacket},
+        handshake::ServerboundHandshakePacket,
+        login::{ClientboundLoginPacket, ServerboundLoginPacket},
+        status::{ClientboundStatusPacket, ServerboundStatusPacket},
+        ConnectionProtocol, ProtocolPacket, PROTOCOL_VERSION,
+    },
+    write::serialize_packet,
+};
+use bevy_app::{App, First, Last, Plugin};
+use bevy_ecs::prelude::*;
+use parking_lot::Mutex;
+use tracing::error;
+
+use crate::{
+    packet_handling::{configuration, game},
+    raw_connection::{PacketFlow, RawConnection},
+};
+
+/// The bytes that every packet log starts with.
+pub const MAGIC: [u8; 4] = *b"AZPL";
+/// The version of the packet log format. This is changed whenever the format
+/// changes in a way that makes old logs unreadable.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// A plugin that records every packet that's sent and received by every
+/// client into a file.
+///
+/// This isn't part of the default plugins, so you have to add it yourself.
+#[derive(Clone)]
+pub struct PacketLoggerPlugin {
+    pub path: PathBuf,
+    pub filter: PacketFilter,
+}
+
+impl PacketLoggerPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            filter: PacketFilter::default(),
+        }
+    }
+
+    /// Only record the packets that match the filter.
+    #[must_use]
+    pub fn filter(mut self, filter: PacketFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+impl Plugin for PacketLoggerPlugin {
+    fn build(&self, app: &mut App) {
+        let writer = match File::create(&self.path)
+            .and_then(|file| PacketLogWriter::new(BufWriter::new(file)))
+        {
+            Ok(writer) => writer,
+            Err(e) => {
+                error!("Couldn't create packet log at {}: {e}", self.path.display());
+                return;
+            }
+        };
+
+        app.insert_resource(PacketLogger {
+            writer: Arc::new(Mutex::new(writer)),
+            filter: Arc::new(self.filter.clone()),
+            next_client_id: Arc::new(AtomicU32::new(0)),
+        })
+        .add_systems(
+            First,
+            add_packet_listener
+                .before(game::send_packet_events)
+                .before(configuration::send_packet_events),
+        )
+        .add_systems(Last, flush_packet_log);
+    }
+}
+
+/// Which packets a [`PacketLoggerPlugin`] should record, by their names (like
+/// `keep_alive` or `level_chunk_with_light`).
+///
+/// Packets with an id that we don't know about are always recorded, since
+/// they're probably why you're looking at the log.
+#[derive(Clone, Debug, Default)]
+pub enum PacketFilter {
+    #[default]
+    All,
+    Only(HashSet<String>),
+    Except(HashSet<String>),
+}
+
+impl PacketFilter {
+    pub fn only(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::Only(names.into_iter().map(Into::into).collect())
+    }
+    pub fn except(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::Except(names.into_iter().map(Into::into).collect())
+    }
+
+    pub fn matches(&self, flow: PacketFlow, state: ConnectionProtocol, raw_packet: &[u8]) -> bool {
+        let names = match self {
+            Self::All => return true,
+            Self::Only(names) | Self::Except(names) => names,
+        };
+        let Some(name) = packet_name(flow, state, raw_packet) else {
+            return true;
+        };
+        names.contains(name) == matches!(self, Self::Only(_))
+    }
+}
+
+/// A packet that was recorded by a [`PacketLoggerPlugin`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketRecord {
+    /// How long after the log was started that the packet was sent or
+    /// received.
+    pub time: Duration,
+    /// The client that the packet belongs to. This is only unique within the
+    /// log.
+    pub client_id: u32,
+    pub flow: PacketFlow,
+    pub state: ConnectionProtocol,
+    /// The packet id followed by the packet's data.
+    pub data: Box<[u8]>,
+}
+
+impl PacketRecord {
+    pub fn packet_id(&self) -> Option<u32> {
+        u32::azalea_read_var(&mut Cursor::new(&self.data[..])).ok()
+    }
+
+    /// The name of the packet, like `keep_alive`, or `None` if it couldn't be
+    /// read.
+    pub fn packet_name(&self) -> Option<&'static str> {
+        packet_name(self.flow, self.state, &self.data)
+    }
+
+    pub fn write(&self, buf: &mut impl Write) -> io::Result<()> {
+        (self.time.as_millis() as u64).azalea_write_var(buf)?;
+        let flow_bit = match self.flow {
+            PacketFlow::Clientbound => 0,
+            PacketFlow::Serverbound => 0x80,
+        };
+        (flow_bit | (self.state as i32 + 1) as u8).azalea_write(buf)?;
+        self.client_id.azalea_write_var(buf)?;
+        (self.data.len() as u32).azalea_write_var(buf)?;
+        buf.write_all(&self.data)
+    }
+
+    /// Read a record, or return `None` if we're at the end of the log.
+    pub fn read(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let Some(time) = read_varint(reader, true)? else {
+            return Ok(None);
+        };
+        let mut flags = [0];
+        reader.read_exact(&mut flags)?;
+        let flow = if flags[0] & 0x80 == 0 {
+            PacketFlow::Clientbound
+        } else {
+            PacketFlow::Serverbound
+        };
+        let state = ConnectionProtocol::from_i32((flags[0] & 0x7f) as i32 - 1)
+            .ok_or_else(|| invalid_data("invalid connection state"))?;
+        let client_id = read_varint(reader, false)?.unwrap_or_default() as u32;
+        let length = read_varint(reader, false)?.unwrap_or_default() as usize;
+        let mut data = vec![0; length];
+        reader.read_exact(&mut data)?;
+
+        Ok(Some(Self {
+            time: Duration::from_millis(time),
+            client_id,
+            flow,
+            state,
+            data: data.into_boxed_slice(),
+        }))
+    }
+}
+
+/// Writes a packet log.
+pub struct PacketLogWriter<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> PacketLogWriter<W> {
+    /// Write the header, and start counting time from now.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&MAGIC)?;
+        FORMAT_VERSION.azalea_write(&mut writer)?;
+        PROTOCOL_VERSION.azalea_write_var(&mut writer)?;
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Record a packet that was sent or received just now.
+    pub fn write_packet(
+        &mut self,
+        client_id: u32,
+        flow: PacketFlow,
+        state: ConnectionProtocol,
+        data: &[u8],
+    ) -> io::Result<()> {
+        PacketRecord {
+            time: self.start.elapsed(),
+            client_id,
+            flow,
+            state,
+            data: data.into(),
+        }
+        .write(&mut self.writer)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads the records from a packet log.
+///
+/// ```no_run
+/// # use azalea_client::packet_logger::PacketLogReader;
+/// # fn example() -> std::io::Result<()> {
+/// let file = std::io::BufReader::new(std::fs::File::open("packets.azpl")?);
+/// for record in PacketLogReader::new(file)? {
+///     let record = record?;
+///     println!("{:?} {:?}", record.time, record.packet_name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PacketLogReader<R: Read> {
+    reader: R,
+    /// The protocol version of the client that recorded the log.
+    pub protocol_version: i32,
+}
+
+impl<R: Read> PacketLogReader<R> {
+    /// Read the header of the log.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(invalid_data("not a packet log"));
+        }
+        if header[4] != FORMAT_VERSION {
+            return Err(invalid_data("unsupported packet log version"));
+        }
+        let protocol_version = read_varint(&mut reader, false)?.unwrap_or_default() as i32;
+        Ok(Self {
+            reader,
+            protocol_version,
+        })
+    }
+}
+
+impl<R: Read> Iterator for PacketLogReader<R> {
+    type Item = io::Result<PacketRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        PacketRecord::read(&mut self.reader).transpose()
+    }
+}
+
+/// Read a varint from a stream, or return `None` if `allow_eof` is true and the
+/// stream ended before the first byte.
+fn read_varint(reader: &mut impl Read, allow_eof: bool) -> io::Result<Option<u64>> {
+    let mut value = 0;
+    for i in 0..10 {
+        let mut byte = [0];
+        if let Err(e) = reader.read_exact(&mut byte) {
+            if allow_eof && i == 0 && e.kind() == io::ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(e);
+        }
+        value |= ((byte[0] & 0x7f) as u64) << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(invalid_data("varint is too long"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Get the name of a raw packet from its id. Only the id at the start of the
+/// packet is read.
+pub fn packet_name(
+    flow: PacketFlow,
+    state: ConnectionProtocol,
+    raw_packet: &[u8],
+) -> Option<&'static str> {
+    let id = u32::azalea_read_var(&mut Cursor::new(raw_packet)).ok()?;
+
+    use ConnectionProtocol as S;
+    use PacketFlow as F;
+    match (flow, state) {
+        (F::Clientbound, S::Game) => ClientboundGamePacket::name_from_id(id),
+        (F::Serverbound, S::Game) => ServerboundGamePacket::name_from_id(id),
+        (F::Clientbound, S::Configuration) => ClientboundConfigPacket::name_from_id(id),
+        (F::Serverbound, S::Configuration) => ServerboundConfigPacket::name_from_id(id),
+        (F::Clientbound, S::Login) => ClientboundLoginPacket::name_from_id(id),
+        (F::Serverbound, S::Login) => ServerboundLoginPacket::name_from_id(id),
+        (F::Clientbound, S::Status) => ClientboundStatusPacket::name_from_id(id),
+        (F::Serverbound, S::Status) => ServerboundStatusPacket::name_from_id(id),
+        (F::Serverbound, S::Handshake) => ServerboundHandshakePacket::name_from_id(id),
+        (F::Clientbound, S::Handshake) => None,
+    }
+}
+
+/// The resource that's inserted by [`PacketLoggerPlugin`].
+#[derive(Resource, Clone)]
+pub struct PacketLogger {
+    writer: Arc<Mutex<PacketLogWriter<BufWriter<File>>>>,
+    filter: Arc<PacketFilter>,
+    next_client_id: Arc<AtomicU32>,
+}
+
+/// The id that a client's packets are recorded with in the packet log.
+///
+/// Entity ids can be reused after a client is removed, so these are given out
+/// by the [`PacketLogger`] instead.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacketLogClientId(pub u32);
+
+impl PacketLogger {
+    fn new_client_id(&self) -> PacketLogClientId {
+        PacketLogClientId(self.next_client_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn record(
+        &self,
+        client_id: u32,
+        flow: PacketFlow,
+        state: ConnectionProtocol,
+        raw_packet: &[u8],
+    ) {
+        if !self.filter.matches(flow, state, raw_packet) {
+            return;
+        }
+        if let Err(e) = self
+            .writer
+            .lock()
+            .write_packet(client_id, flow, state, raw_packet)
+        {
+            error!("Couldn't write to packet log: {e}");
+        }
+    }
+}
+
+fn add_packet_listener(
+    mut query: Query<
+        (Entity, &mut RawConnection, Option<&PacketLogClientId>),
+        Added<RawConnection>,
+    >,
+    packet_logger: Res<PacketLogger>,
+    mut commands: Commands,
+) {
+    for (entity, mut raw_connection, client_id) in &mut query {
+        let client_id = client_id.copied().unwrap_or_else(|| {
+            let client_id = packet_logger.new_client_id();
+            commands.entity(entity).insert(client_id);
+            client_id
+        });
+        let packet_logger = packet_logger.clone();
+        raw_connection
+            .packet_listeners
+            .push(Arc::new(move |flow, state, raw_packet| {
+                packet_logger.record(client_id.0, flow, state, raw_packet);
+            }));
+    }
+}
+
+/// Records the packets that are sent and received while a client is joining,
+/// before it has a [`RawConnection`]. This does nothing if there's no
+/// [`PacketLogger`].
+#[derive(Clone, Default)]
+pub(crate) struct JoinPacketLog(Option<(PacketLogger, PacketLogClientId)>);
+
+impl JoinPacketLog {
+    pub fn new(ecs: &mut World, entity: Entity) -> Self {
+        let Some(packet_logger) = ecs.get_resource::<PacketLogger>().cloned() else {
+            return Self(None);
+        };
+        let client_id = match ecs.get::<PacketLogClientId>(entity) {
+            Some(client_id) => *client_id,
+            None => {
+                let client_id = packet_logger.new_client_id();
+                ecs.entity_mut(entity).insert(client_id);
+                client_id
+            }
+        };
+        Self(Some((packet_logger, client_id)))
+    }
+
+    pub fn record<P: ProtocolPacket + Debug>(
+        &self,
+        flow: PacketFlow,
+        state: ConnectionProtocol,
+        packet: &P,
+    ) {
+        let Some((packet_logger, client_id)) = &self.0 else {
+            return;
+        };
+        match serialize_packet(packet) {
+            Ok(raw_packet) => packet_logger.record(client_id.0, flow, state, &raw_packet),
+            Err(e) => error!(
+                "Couldn't serialize {} for the packet log: {e}",
+                packet.name()
+            ),
+        }
+    }
+}
+
+fn flush_packet_log(packet_logger: Res<PacketLogger>) {
+    if let Err(e) = packet_logger.writer.lock().flush() {
+        error!("Couldn't flush packet log: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::{
+        game::ClientboundKeepAlive,
+        handshake::{s_intention::ServerboundIntention, ServerboundHandshakePacket},
+        ClientIntention, Packet,
+    };
+
+    use super::*;
+
+    fn keep_alive() -> Box<[u8]> {
+        serialize_packet(&ClientboundKeepAlive { id: 5 }.into_variant()).unwrap()
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let intention: ServerboundHandshakePacket = ServerboundIntention {
+            protocol_version: PROTOCOL_VERSION,
+            hostname: "localhost".to_owned(),
+            port: 25565,
+            intention: ClientIntention::Login,
+        }
+        .into_variant();
+        let intention = serialize_packet(&intention).unwrap();
+        // long enough that the length takes more than one byte
+        let mut unknown = vec![0x7f];
+        unknown.extend([1; 300]);
+
+        let mut log = Vec::new();
+        let mut writer = PacketLogWriter::new(&mut log).unwrap();
+        writer
+            .write_packet(
+                0,
+                PacketFlow::Serverbound,
+                ConnectionProtocol::Handshake,
+                &intention,
+            )
+            .unwrap();
+        writer
+            .write_packet(
+                300,
+                PacketFlow::Clientbound,
+                ConnectionProtocol::Game,
+                &keep_alive(),
+            )
+            .unwrap();
+        writer
+            .write_packet(
+                1,
+                PacketFlow::Clientbound,
+                ConnectionProtocol::Game,
+                &unknown,
+            )
+            .unwrap();
+
+        let reader = PacketLogReader::new(Cursor::new(log)).unwrap();
+        assert_eq!(reader.protocol_version, PROTOCOL_VERSION);
+        let records = reader.collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].client_id, 0);
+        assert_eq!(records[0].flow, PacketFlow::Serverbound);
+        assert_eq!(records[0].state, ConnectionProtocol::Handshake);
+        assert_eq!(records[0].data, intention);
+        assert_eq!(records[0].packet_name(), Some("intention"));
+
+        assert_eq!(records[1].client_id, 300);
+        assert_eq!(records[1].flow, PacketFlow::Clientbound);
+        assert_eq!(records[1].state, ConnectionProtocol::Game);
+        assert_eq!(records[1].packet_name(), Some("keep_alive"));
+
+        assert_eq!(&records[2].data[..], &unknown[..]);
+        assert_eq!(records[2].packet_id(), Some(0x7f));
+        assert_eq!(records[2].packet_name(), None);
+        assert!(records.windows(2).all(|w| w[0].time <= w[1].time));
+    }
+
+    #[test]
+    fn test_reader_rejects_other_files() {
+        assert!(PacketLogReader::new(Cursor::new(b"PK\x03\x04\x01\x00".to_vec())).is_err());
+        let mut log = MAGIC.to_vec();
+        log.extend([FORMAT_VERSION + 1, 0]);
+        assert!(PacketLogReader::new(Cursor::new(log)).is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let flow = PacketFlow::Clientbound;
+        let state = ConnectionProtocol::Game;
+        assert!(PacketFilter::All.matches(flow, state, &keep_alive()));
+        assert!(PacketFilter::only(["keep_alive"]).matches(flow, state, &keep_alive()));
+        assert!(!PacketFilter::except(["keep_alive"]).matches(flow, state, &keep_alive()));
+        assert!(!PacketFilter::only(["ping"]).matches(flow, state, &keep_alive()));
+        // packets that we don't know about are always kept
+        assert!(PacketFilter::only(["ping"]).matches(flow, state, &[0x7f]));
+    }
+
+    #[test]
+    fn test_client_ids_are_not_reused() {
+        let path = std::env::temp_dir().join(format!(
+            "azalea-packet-log-test-{}.azpl",
+            std::process::id()
+        ));
+        let writer = PacketLogWriter::new(BufWriter::new(File::create(&path).unwrap())).unwrap();
+        let mut ecs = World::new();
+        ecs.insert_resource(PacketLogger {
+            writer: Arc::new(Mutex::new(writer)),
+            filter: Arc::new(PacketFilter::All),
+            next_client_id: Arc::new(AtomicU32::new(0)),
+        });
+
+        let first = ecs.spawn_empty().id();
+        JoinPacketLog::new(&mut ecs, first);
+        assert_eq!(
+            ecs.get::<PacketLogClientId>(first),
+            Some(&PacketLogClientId(0))
+        );
+        // joining again with the same entity keeps the id
+        JoinPacketLog::new(&mut ecs, first);
+        assert_eq!(
+            ecs.get::<PacketLogClientId>(first),
+            Some(&PacketLogClientId(0))
+        );
+
+        // the entity index is probably reused here, but the id shouldn't be
+        ecs.despawn(first);
+        let second = ecs.spawn_empty().id();
+        JoinPacketLog::new(&mut ecs, second);
+        assert_eq!(
+            ecs.get::<PacketLogClientId>(second),
+            Some(&PacketLogClientId(1))
+        );
+
+        drop(ecs);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,304 @@
+//! Change, replace, or drop the game packets that clients send before they're
+//! written, without having to change every place that sends them.
+//!
+//! ```no_run
+//! # use azalea_client::packet_middleware::{PacketAction, PacketMiddlewarePlugin};
+//! # use azalea_protocol::packets::game::ServerboundGamePacket;
+//! # fn example(app: &mut bevy_app::App) {
+//! // hide our real coordinates by moving everything 1000 blocks
+//! app.add_plugins(PacketMiddlewarePlugin::new(|packet| {
+//!     if let ServerboundGamePacket::MovePlayerPos(p) = packet {
+//!         p.pos.x += 1000.;
+//!     }
+//!     PacketAction::Keep
+//! }));
+//! # }
+//! ```
+//!
+//! Middleware is run in the order that it was added, and it runs for every
+//! game packet that goes through [`RawConnection::write_packet`], which
+//! includes packets sent with [`SendPacketEvent`] and [`Client::write_packet`].
+//! Raw packets and packets in other states aren't passed to it.
+//!
+//! To only change the packets for one client, add to its
+//! [`RawConnection::packet_middleware`] instead. That only lasts until the
+//! client disconnects.
+//!
+//! Middleware can also delay packets with [`PacketAction::Delay`], which is
+//! useful for testing how bots behave on a laggy connection:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use azalea_client::packet_middleware::PacketMiddlewarePlugin;
+//! # fn example(app: &mut bevy_app::App) {
+//! app.add_plugins(PacketMiddlewarePlugin::latency(Duration::from_millis(200)));
+//! # }
+//! ```
+//!
+//! [`SendPacketEvent`]: crate::packet_handling::game::SendPacketEvent
+//! [`Client::write_packet`]: crate::Client::write_packet
+
+use std::{sync::Arc, time::Duration};
+
+use azalea_protocol::packets::game::ServerboundGamePacket;
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::raw_connection::RawConnection;
+
+/// What should happen to a packet after it went through a [`PacketMiddleware`].
+#[derive(Debug, Clone)]
+pub enum PacketAction {
+    /// Send the packet, including any changes that the middleware made to it.
+    Keep,
+    /// Don't send the packet, and don't run any of the middleware after this
+    /// one.
+    Drop,
+    /// Send this packet instead. The middleware after this one gets the new
+    /// packet.
+    Replace(ServerboundGamePacket),
+    /// Send the packet like [`Self::Keep`], but wait this long first. If more
+    /// than one middleware delays a packet, the delays are added together.
+    ///
+    /// The game packets that are sent after a delayed packet have to wait for
+    /// it, so the server still gets them in the right order. Delayed packets
+    /// are written when the client's schedule runs, so the delay might be up
+    /// to a tick longer than asked for.
+    Delay(Duration),
+}
+
+/// A function that's called with every game packet that a client is about to
+/// send to the server.
+pub type PacketMiddleware = Arc<dyn Fn(&mut ServerboundGamePacket) -> PacketAction + Send + Sync>;
+
+/// A plugin that adds a [`PacketMiddleware`] to every client.
+///
+/// This can be added more than once to add more middleware.
+#[derive(Clone)]
+pub struct PacketMiddlewarePlugin {
+    pub middleware: PacketMiddleware,
+}
+
+impl PacketMiddlewarePlugin {
+    pub fn new(
+        middleware: impl Fn(&mut ServerboundGamePacket) -> PacketAction + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            middleware: Arc::new(middleware),
+        }
+    }
+
+    /// Middleware that delays every game packet by the given amount of time,
+    /// like if the connection to the server was slower.
+    pub fn latency(latency: Duration) -> Self {
+        Self::new(move |_| PacketAction::Delay(latency))
+    }
+}
+
+impl Plugin for PacketMiddlewarePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<GlobalPacketMiddleware>() {
+            app.init_resource::<GlobalPacketMiddleware>()
+                .add_systems(Update, add_middleware_to_new_connections);
+        }
+        app.world_mut()
+            .resource_mut::<GlobalPacketMiddleware>()
+            .0
+            .push(self.middleware.clone());
+    }
+
+    fn is_unique(&self) -> bool {
+        false
+    }
+}
+
+/// The middleware from every [`PacketMiddlewarePlugin`], which is added to
+/// every client when it connects.
+#[derive(Resource, Default, Clone)]
+pub struct GlobalPacketMiddleware(pub Vec<PacketMiddleware>);
+
+/// Run the packet through every middleware in order. Returns how long the
+/// packet should be delayed for, or `None` if it should be dropped.
+pub fn run_packet_middleware(
+    middleware: &[PacketMiddleware],
+    packet: &mut ServerboundGamePacket,
+) -> Option<Duration> {
+    let mut delay = Duration::ZERO;
+    for middleware in middleware {
+        match middleware(packet) {
+            PacketAction::Keep => {}
+            PacketAction::Drop => return None,
+            PacketAction::Replace(new_packet) => *packet = new_packet,
+            PacketAction::Delay(middleware_delay) => delay += middleware_delay,
+        }
+    }
+    Some(delay)
+}
+
+/// Writes the packets that were delayed by [`PacketAction::Delay`]. This is
+/// in the default plugins.
+pub struct DelayedPacketsPlugin;
+impl Plugin for DelayedPacketsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, write_delayed_packets);
+    }
+}
+
+pub fn write_delayed_packets(query: Query<(Entity, &RawConnection)>) {
+    for (entity, raw_connection) in &query {
+        if let Err(err) = raw_connection.write_delayed_packets() {
+            // this only happens if we're disconnected, so the packets don't matter
+            debug!("Couldn't write delayed packets for {entity:?}: {err}");
+        }
+    }
+}
+
+fn add_middleware_to_new_connections(
+    mut query: Query<&mut RawConnection, Added<RawConnection>>,
+    global_middleware: Res<GlobalPacketMiddleware>,
+) {
+    for mut raw_connection in &mut query {
+        // the global middleware runs first, so middleware for a single client
+        // can see what it did
+        let client_middleware = std::mem::take(&mut raw_connection.packet_middleware);
+        raw_connection.packet_middleware = global_middleware
+            .0
+            .iter()
+            .cloned()
+            .chain(client_middleware)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use azalea_protocol::{
+        packets::{
+            game::{ServerboundClientTickEnd, ServerboundKeepAlive},
+            ConnectionProtocol, Packet,
+        },
+        read::deserialize_packet,
+    };
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn keep_alive_id(packet: &ServerboundGamePacket) -> Option<u64> {
+        match packet {
+            ServerboundGamePacket::KeepAlive(p) => Some(p.id),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_run_packet_middleware() {
+        let middleware: Vec<PacketMiddleware> = vec![
+            Arc::new(|packet| {
+                if let ServerboundGamePacket::KeepAlive(p) = packet {
+                    p.id += 1;
+                }
+                PacketAction::Keep
+            }),
+            Arc::new(|packet| match packet {
+                ServerboundGamePacket::ClientTickEnd(_) => {
+                    PacketAction::Replace(ServerboundKeepAlive { id: 10 }.into_variant())
+                }
+                _ => PacketAction::Keep,
+            }),
+            // the middleware after a replacement sees the new packet
+            Arc::new(|packet| {
+                if let ServerboundGamePacket::KeepAlive(p) = packet {
+                    p.id *= 2;
+                }
+                PacketAction::Keep
+            }),
+        ];
+
+        let mut packet = ServerboundKeepAlive { id: 1 }.into_variant();
+        assert_eq!(
+            run_packet_middleware(&middleware, &mut packet),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(keep_alive_id(&packet), Some(4));
+
+        let mut packet = ServerboundClientTickEnd.into_variant();
+        assert_eq!(
+            run_packet_middleware(&middleware, &mut packet),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(keep_alive_id(&packet), Some(20));
+    }
+
+    #[test]
+    fn test_drop_and_delay() {
+        static CALLS_AFTER_DROP: AtomicUsize = AtomicUsize::new(0);
+        let middleware: Vec<PacketMiddleware> = vec![
+            Arc::new(|_| PacketAction::Delay(Duration::from_millis(10))),
+            Arc::new(|packet| match keep_alive_id(packet) {
+                Some(0) => PacketAction::Drop,
+                _ => PacketAction::Delay(Duration::from_millis(5)),
+            }),
+            Arc::new(|_| {
+                CALLS_AFTER_DROP.fetch_add(1, Ordering::SeqCst);
+                PacketAction::Keep
+            }),
+        ];
+
+        let mut packet = ServerboundKeepAlive { id: 1 }.into_variant();
+        assert_eq!(
+            run_packet_middleware(&middleware, &mut packet),
+            Some(Duration::from_millis(15))
+        );
+        assert_eq!(CALLS_AFTER_DROP.load(Ordering::SeqCst), 1);
+
+        let mut packet = ServerboundKeepAlive { id: 0 }.into_variant();
+        assert_eq!(run_packet_middleware(&middleware, &mut packet), None);
+        // the middleware after the one that dropped it doesn't run
+        assert_eq!(CALLS_AFTER_DROP.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_latency() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+
+        let (mut raw_connection, mut outgoing) =
+            RawConnection::new_headless(mpsc::unbounded_channel().0, ConnectionProtocol::Game);
+        raw_connection.packet_middleware = vec![Arc::new(|packet| match keep_alive_id(packet) {
+            Some(1) => PacketAction::Delay(Duration::from_millis(50)),
+            _ => PacketAction::Keep,
+        })];
+
+        raw_connection
+            .write_packet(ServerboundKeepAlive { id: 1 })
+            .unwrap();
+        // this one isn't delayed, but it has to wait for the one before it
+        raw_connection
+            .write_packet(ServerboundKeepAlive { id: 2 })
+            .unwrap();
+        raw_connection.write_delayed_packets().unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        raw_connection.write_delayed_packets().unwrap();
+        let mut ids = Vec::new();
+        while let Ok(raw_packet) = outgoing.try_recv() {
+            let packet =
+                deserialize_packet::<ServerboundGamePacket>(&mut Cursor::new(&raw_packet)).unwrap();
+            ids.push(keep_alive_id(&packet).unwrap());
+        }
+        assert_eq!(ids, [1, 2]);
+
+        // once the queue is empty, packets are written right away again
+        raw_connection
+            .write_packet(ServerboundKeepAlive { id: 3 })
+            .unwrap();
+        assert!(outgoing.try_recv().is_ok());
+    }
+}
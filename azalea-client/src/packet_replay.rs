@@ -0,0 +1,456 @@
+//! Play back a packet log from [`PacketLoggerPlugin`] into a client that isn't
+//! connected to a server.
+//!
+//! The clientbound packets in the log are handled like they were sent by a
+//! real server, so this is useful for reproducing bugs and for running tests
+//! and experiments against real data.
+//!
+//! ```no_run
+//! # use azalea_client::{packet_replay::PacketReplay, Client};
+//! # async fn example() -> std::io::Result<()> {
+//! let replay = PacketReplay::open("packets.azpl")?.speed(4.);
+//! let (client, mut rx) = Client::replay(replay);
+//! while let Some(event) = rx.recv().await {
+//!     // ...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`PacketLoggerPlugin`]: crate::packet_logger::PacketLoggerPlugin
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufReader, Cursor, Read},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use azalea_auth::game_profile::GameProfile;
+use azalea_entity::indexing::LocalPlayerUuidIndex;
+use azalea_protocol::{
+    packets::{login::ClientboundLoginPacket, ConnectionProtocol, PROTOCOL_VERSION},
+    read::deserialize_packet,
+};
+use azalea_world::Instance;
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::prelude::*;
+use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::{
+    events::LocalPlayerEvents,
+    local_player::{GameProfileComponent, InstanceHolder},
+    packet_handling::{configuration, game},
+    packet_logger::{PacketLogReader, PacketRecord},
+    raw_connection::{PacketFlow, RawConnection},
+    start_ecs_runner, Account, Client, DefaultPlugins, InConfigState, LocalPlayerBundle,
+};
+
+/// A plugin that plays back [`PacketReplayPlayback`]s.
+///
+/// This isn't part of the default plugins, since it's only needed for
+/// replays. [`Client::replay`] adds it by itself.
+pub struct PacketReplayPlugin;
+impl Plugin for PacketReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReplayFinishedEvent>().add_systems(
+            First,
+            play_packet_replays
+                .before(game::send_packet_events)
+                .before(configuration::send_packet_events),
+        );
+    }
+}
+
+/// The packets that a server sent to one client, which can be played back with
+/// [`Client::replay`] or by adding a [`PacketReplayPlayback`] to a client.
+#[derive(Clone, Debug)]
+pub struct PacketReplay {
+    /// The clientbound packets that will be replayed, starting from the
+    /// configuration state.
+    pub records: VecDeque<PacketRecord>,
+    /// The profile that the server sent us when we logged in, if it's in the
+    /// log.
+    pub game_profile: Option<GameProfile>,
+    /// How many times faster than it was recorded that the log is played back.
+    /// Set this to [`f64::INFINITY`] to handle the packets as fast as possible.
+    pub speed: f64,
+}
+
+impl PacketReplay {
+    /// Make a replay from the records for one client. Serverbound packets are
+    /// ignored.
+    pub fn new(records: impl IntoIterator<Item = PacketRecord>) -> Self {
+        let mut game_profile = None;
+        let mut records = records
+            .into_iter()
+            .filter(|record| record.flow == PacketFlow::Clientbound)
+            .skip_while(|record| {
+                if record.state == ConnectionProtocol::Login {
+                    if let Ok(ClientboundLoginPacket::LoginFinished(p)) =
+                        deserialize_packet(&mut Cursor::new(&record.data[..]))
+                    {
+                        game_profile = Some(p.game_profile);
+                    }
+                }
+                // the client can't be put in any state before configuration
+                record.state != ConnectionProtocol::Configuration
+            })
+            .collect::<VecDeque<_>>();
+
+        // make the first packet arrive immediately
+        if let Some(start) = records.front().map(|record| record.time) {
+            for record in &mut records {
+                record.time -= start;
+            }
+        }
+
+        Self {
+            records,
+            game_profile,
+            speed: 1.,
+        }
+    }
+
+    /// Read the records for a client from a packet log. If `client_id` is
+    /// `None`, the first client in the log is used.
+    ///
+    /// This returns an error if the log was recorded with a different protocol
+    /// version.
+    pub fn from_log<R: Read>(log: PacketLogReader<R>, client_id: Option<u32>) -> io::Result<Self> {
+        if log.protocol_version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "packet log is for protocol version {}, but we're on {PROTOCOL_VERSION}",
+                    log.protocol_version
+                ),
+            ));
+        }
+
+        let mut client_id = client_id;
+        let mut records = Vec::new();
+        for record in log {
+            let record = record?;
+            if *client_id.get_or_insert(record.client_id) == record.client_id {
+                records.push(record);
+            }
+        }
+        Ok(Self::new(records))
+    }
+
+    /// Read the records for the first client in the packet log at the path.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let log = PacketLogReader::new(BufReader::new(File::open(path)?))?;
+        Self::from_log(log, None)
+    }
+
+    #[must_use]
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+/// A component for clients that are having a [`PacketReplay`] played back into
+/// them. This only works if the [`PacketReplayPlugin`] was added.
+///
+/// Packets are only handed to the client once it's in the state that they
+/// were recorded in, so a client that's waiting to switch states pauses the
+/// replay.
+#[derive(Component)]
+pub struct PacketReplayPlayback {
+    pub replay: PacketReplay,
+    /// When the replay started, which is set the first time it's played.
+    pub start: Option<Instant>,
+    /// Whether every packet was handed to the client and the
+    /// [`ReplayFinishedEvent`] was sent.
+    pub finished: bool,
+    /// The packets that the client tried to send, which are thrown away.
+    ///
+    /// This is kept after the replay is finished, since the client can't
+    /// write packets anymore once it's dropped.
+    pub outgoing_packets_receiver: Option<mpsc::UnboundedReceiver<Box<[u8]>>>,
+}
+
+impl PacketReplayPlayback {
+    pub fn new(replay: PacketReplay) -> Self {
+        Self {
+            replay,
+            start: None,
+            finished: false,
+            outgoing_packets_receiver: None,
+        }
+    }
+}
+
+/// An event that's sent when every packet in a client's [`PacketReplay`] was
+/// handed to it.
+#[derive(Event, Debug, Clone)]
+pub struct ReplayFinishedEvent {
+    pub entity: Entity,
+}
+
+pub fn play_packet_replays(
+    mut query: Query<(Entity, &mut PacketReplayPlayback, &RawConnection)>,
+    mut replay_finished_events: EventWriter<ReplayFinishedEvent>,
+) {
+    for (entity, mut playback, raw_conn) in &mut query {
+        let playback = &mut *playback;
+        if let Some(outgoing_packets_receiver) = &mut playback.outgoing_packets_receiver {
+            while let Ok(raw_packet) = outgoing_packets_receiver.try_recv() {
+                trace!("Ignoring packet sent during replay: {raw_packet:?}");
+            }
+        }
+
+        let elapsed = playback.start.get_or_insert_with(Instant::now).elapsed();
+        let replay = &mut playback.replay;
+
+        let queue = raw_conn.incoming_packet_queue();
+        let mut queue = queue.lock();
+        while let Some(record) = replay.records.front() {
+            let due = Duration::try_from_secs_f64(record.time.as_secs_f64() / replay.speed)
+                .unwrap_or(Duration::MAX);
+            // the client reads every packet in the queue with the state that
+            // it's in, so we have to wait until it switches
+            if due > elapsed || record.state != raw_conn.connection_protocol {
+                break;
+            }
+            let record = replay.records.pop_front().expect("we just checked front()");
+            queue.push(record.data);
+        }
+
+        if replay.records.is_empty() && !playback.finished {
+            playback.finished = true;
+            replay_finished_events.send(ReplayFinishedEvent { entity });
+        }
+    }
+}
+
+impl Client {
+    /// Make a client that isn't connected to a server and play a
+    /// [`PacketReplay`] into it.
+    ///
+    /// This must be called from inside a Tokio runtime. The client stays in
+    /// the ECS after the replay is finished, so you can keep looking at the
+    /// world that it ended up with.
+    pub fn replay(replay: PacketReplay) -> (Self, mpsc::UnboundedReceiver<crate::Event>) {
+        let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
+
+        let mut app = App::new();
+        app.add_plugins((DefaultPlugins, PacketReplayPlugin));
+        let ecs_lock = start_ecs_runner(app, run_schedule_receiver, run_schedule_sender.clone());
+
+        let game_profile = replay
+            .game_profile
+            .clone()
+            .unwrap_or_else(|| GameProfile::new(Uuid::nil(), "azalea".to_owned()));
+        let (raw_connection, outgoing_packets_receiver) = RawConnection::new_headless(
+            run_schedule_sender.clone(),
+            ConnectionProtocol::Configuration,
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let mut ecs = ecs_lock.lock();
+        let entity = ecs.spawn_empty().id();
+        ecs.resource_mut::<LocalPlayerUuidIndex>()
+            .insert(game_profile.uuid, entity);
+
+        let instance_holder =
+            InstanceHolder::new(entity, Arc::new(RwLock::new(Instance::default())));
+        ecs.entity_mut(entity).insert((
+            Account::offline(&game_profile.name),
+            LocalPlayerBundle {
+                raw_connection,
+                local_player_events: LocalPlayerEvents(tx),
+                game_profile: GameProfileComponent(game_profile.clone()),
+                client_information: crate::ClientInformation::default(),
+                instance_holder,
+                metadata: azalea_entity::metadata::PlayerMetadataBundle::default(),
+            },
+            InConfigState,
+            PacketReplayPlayback {
+                outgoing_packets_receiver: Some(outgoing_packets_receiver),
+                ..PacketReplayPlayback::new(replay)
+            },
+        ));
+        drop(ecs);
+
+        let client = Client::new(game_profile, entity, ecs_lock, run_schedule_sender);
+        (client, rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::{
+        packets::{
+            config::ClientboundFinishConfiguration, game::ClientboundKeepAlive,
+            login::ClientboundLoginFinished, Packet, ProtocolPacket,
+        },
+        write::serialize_packet,
+    };
+    use bevy_ecs::{event::Events, schedule::Schedule};
+
+    use super::*;
+
+    fn record(
+        millis: u64,
+        flow: PacketFlow,
+        state: ConnectionProtocol,
+        packet: &(impl ProtocolPacket + std::fmt::Debug),
+    ) -> PacketRecord {
+        PacketRecord {
+            time: Duration::from_millis(millis),
+            client_id: 0,
+            flow,
+            state,
+            data: serialize_packet(packet).unwrap(),
+        }
+    }
+
+    fn finish_configuration(millis: u64) -> PacketRecord {
+        record(
+            millis,
+            PacketFlow::Clientbound,
+            ConnectionProtocol::Configuration,
+            &ClientboundFinishConfiguration.into_variant(),
+        )
+    }
+
+    fn keep_alive(millis: u64) -> PacketRecord {
+        record(
+            millis,
+            PacketFlow::Clientbound,
+            ConnectionProtocol::Game,
+            &ClientboundKeepAlive { id: 1 }.into_variant(),
+        )
+    }
+
+    #[test]
+    fn test_new_starts_at_configuration() {
+        let profile = GameProfile::new(Uuid::from_u128(1), "bot".to_owned());
+        let login_finished = record(
+            1000,
+            PacketFlow::Clientbound,
+            ConnectionProtocol::Login,
+            &ClientboundLoginFinished {
+                game_profile: profile.clone(),
+            }
+            .into_variant(),
+        );
+        let mut serverbound = keep_alive(2500);
+        serverbound.flow = PacketFlow::Serverbound;
+
+        let replay = PacketReplay::new([
+            login_finished,
+            finish_configuration(2000),
+            serverbound,
+            keep_alive(3000),
+        ]);
+        assert_eq!(replay.game_profile.unwrap().uuid, profile.uuid);
+        let records = replay.records.iter().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].time, Duration::ZERO);
+        assert_eq!(records[0].state, ConnectionProtocol::Configuration);
+        assert_eq!(records[1].time, Duration::from_secs(1));
+        assert_eq!(records[1].state, ConnectionProtocol::Game);
+    }
+
+    #[test]
+    fn test_playback_waits_for_the_state() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ReplayFinishedEvent>>();
+        let (raw_connection, _outgoing) = RawConnection::new_headless(
+            mpsc::unbounded_channel().0,
+            ConnectionProtocol::Configuration,
+        );
+        let queue = raw_connection.incoming_packet_queue();
+        let replay =
+            PacketReplay::new([finish_configuration(0), keep_alive(10)]).speed(f64::INFINITY);
+        let entity = ecs
+            .spawn((raw_connection, PacketReplayPlayback::new(replay)))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(play_packet_replays);
+
+        schedule.run(&mut ecs);
+        // the keep alive is for the game state, so it has to wait
+        assert_eq!(queue.lock().len(), 1);
+        assert!(ecs.resource::<Events<ReplayFinishedEvent>>().is_empty());
+
+        ecs.get_mut::<RawConnection>(entity)
+            .unwrap()
+            .set_state(ConnectionProtocol::Game);
+        schedule.run(&mut ecs);
+        assert_eq!(queue.lock().len(), 2);
+        assert!(ecs.get::<PacketReplayPlayback>(entity).unwrap().finished);
+        assert_eq!(ecs.resource::<Events<ReplayFinishedEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn test_playback_keeps_timing() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ReplayFinishedEvent>>();
+        let (raw_connection, _outgoing) = RawConnection::new_headless(
+            mpsc::unbounded_channel().0,
+            ConnectionProtocol::Configuration,
+        );
+        let queue = raw_connection.incoming_packet_queue();
+        // a minute later even at double speed
+        let replay =
+            PacketReplay::new([finish_configuration(0), finish_configuration(120_000)]).speed(2.);
+        ecs.spawn((raw_connection, PacketReplayPlayback::new(replay)));
+        let mut schedule = Schedule::default();
+        schedule.add_systems(play_packet_replays);
+
+        schedule.run(&mut ecs);
+        assert_eq!(queue.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_client_replay() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let replay =
+                PacketReplay::new([finish_configuration(0), keep_alive(0)]).speed(f64::INFINITY);
+            let (client, _rx) = Client::replay(replay);
+
+            let start = Instant::now();
+            loop {
+                let finished = {
+                    let ecs = client.ecs.lock();
+                    ecs.get::<PacketReplayPlayback>(client.entity)
+                        .unwrap()
+                        .finished
+                        && !ecs.entity(client.entity).contains::<InConfigState>()
+                };
+                if finished {
+                    break;
+                }
+                assert!(
+                    start.elapsed() < Duration::from_secs(5),
+                    "replay didn't finish"
+                );
+                tokio::task::yield_now().await;
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            // the replay plugin was added by Client::replay
+            assert!(client
+                .ecs
+                .lock()
+                .contains_resource::<Events<ReplayFinishedEvent>>());
+        });
+    }
+}
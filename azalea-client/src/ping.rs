@@ -1,7 +1,12 @@
 //! Ping Minecraft servers.
 
-use std::io;
+use std::{
+    io::{self, Read},
+    net::SocketAddr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
+use azalea_chat::{text_component::TextComponent, FormattedText};
 use azalea_protocol::{
     connect::{Connection, ConnectionError, Proxy},
     packets::{
@@ -10,14 +15,22 @@ use azalea_protocol::{
             ServerboundHandshakePacket,
         },
         status::{
-            c_status_response::ClientboundStatusResponse,
-            s_status_request::ServerboundStatusRequest, ClientboundStatusPacket,
+            c_status_response::{ClientboundStatusResponse, ForgeData, ModInfo, Players, Version},
+            s_ping_request::ServerboundPingRequest,
+            s_status_request::ServerboundStatusRequest,
+            ClientboundStatusPacket, ServerboundStatusPacket,
         },
         ClientIntention, PROTOCOL_VERSION,
     },
     resolver, ServerAddress,
 };
+use base64::Engine;
+use flate2::read::ZlibDecoder;
 use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
 
 #[derive(Error, Debug)]
 pub enum PingError {
@@ -31,6 +44,8 @@ pub enum PingError {
     WritePacket(#[from] io::Error),
     #[error("The given address could not be parsed into a ServerAddress")]
     InvalidAddress,
+    #[error("The server sent an invalid response to a legacy ping")]
+    InvalidLegacyResponse,
 }
 
 /// Ping a Minecraft server.
@@ -55,7 +70,7 @@ pub async fn ping_server(
     ping_server_with_connection(address, conn).await
 }
 
-/// Ping a Minecraft server through a Socks5 proxy.
+/// Ping a Minecraft server through a Socks5 or HTTP proxy.
 pub async fn ping_server_with_proxy(
     address: impl TryInto<ServerAddress>,
     proxy: Proxy,
@@ -97,3 +112,695 @@ pub async fn ping_server_with_connection(
         }
     }
 }
+
+/// Everything that a server told us when we pinged it with [`ping`].
+#[derive(Clone, Debug)]
+pub struct PingResult {
+    /// The message of the day, which is shown under the server's name in the
+    /// server list.
+    pub motd: FormattedText,
+    pub version: Version,
+    /// The number of players online, and some of their names if the server
+    /// wants to show them.
+    pub players: Players,
+    pub favicon: Option<Favicon>,
+    pub forge_data: Option<ForgeData>,
+    pub mod_info: Option<ModInfo>,
+    pub enforces_secure_chat: Option<bool>,
+    /// How long it took for the server to respond to a ping.
+    pub latency: Duration,
+    /// Whether the server only responded to the legacy ping that's used by
+    /// versions of Minecraft before 1.7. Legacy servers don't send a
+    /// favicon, mods, or a player sample.
+    pub legacy: bool,
+}
+
+impl PingResult {
+    fn from_status(status: ClientboundStatusResponse, latency: Duration) -> Self {
+        Self {
+            motd: status.description,
+            version: status.version,
+            players: status.players,
+            favicon: status.favicon.as_deref().and_then(Favicon::from_data_url),
+            forge_data: status.forge_data,
+            mod_info: status.mod_info,
+            enforces_secure_chat: status.enforces_secure_chat,
+            latency,
+            legacy: false,
+        }
+    }
+}
+
+/// A server's icon, which is a 64x64 PNG image.
+#[derive(Clone, Debug)]
+pub struct Favicon {
+    /// The PNG file.
+    pub png: Box<[u8]>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Favicon {
+    /// Parse a favicon from the `data:image/png;base64,...` URL that servers
+    /// send. Returns `None` if it's not a valid PNG.
+    pub fn from_data_url(url: &str) -> Option<Self> {
+        let encoded = url.strip_prefix("data:image/png;base64,")?;
+        // some servers put line breaks in the base64
+        let encoded = encoded.replace(['\n', '\r'], "");
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok()?;
+        let header = PngHeader::read(&png)?;
+        Some(Self {
+            width: header.width,
+            height: header.height,
+            png: png.into(),
+        })
+    }
+
+    /// Decode the image into 8-bit RGBA pixels, one row after another.
+    ///
+    /// Only non-interlaced images with 8 bits per channel are supported, which
+    /// is what servers almost always send. `None` is returned for anything
+    /// else.
+    pub fn to_rgba(&self) -> Option<Vec<u8>> {
+        decode_png(&self.png)
+    }
+}
+
+/// Ping a server and get everything it says about itself, including how long
+/// it takes to respond.
+///
+/// If the server doesn't understand the ping (because it's older than 1.7),
+/// the legacy ping is tried instead.
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), azalea_client::ping::PingError> {
+/// let result = azalea_client::ping::ping("localhost").await?;
+/// println!(
+///     "{} ({}/{} players, {}ms)",
+///     result.motd.to_ansi(),
+///     result.players.online,
+///     result.players.max,
+///     result.latency.as_millis()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub async fn ping(address: impl TryInto<ServerAddress>) -> Result<PingResult, PingError> {
+    let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
+    let resolved_address = resolver::resolve_address(&address).await?;
+
+    match ping_modern(&address, &resolved_address).await {
+        Ok(result) => Ok(result),
+        // old servers just close the connection, or send a kick packet that
+        // we can't read
+        Err(PingError::ReadPacket(_) | PingError::WritePacket(_)) => {
+            ping_legacy_with_address(&address, &resolved_address).await
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn ping_modern(
+    address: &ServerAddress,
+    resolved_address: &SocketAddr,
+) -> Result<PingResult, PingError> {
+    let start = Instant::now();
+    let mut conn = Connection::new(resolved_address).await?;
+    conn.write(ServerboundIntention {
+        protocol_version: PROTOCOL_VERSION,
+        hostname: address.host.clone(),
+        port: address.port,
+        intention: ClientIntention::Status,
+    })
+    .await?;
+    let mut conn = conn.status();
+
+    conn.write(ServerboundStatusRequest {}).await?;
+    let status = loop {
+        if let ClientboundStatusPacket::StatusResponse(status) = conn.read().await? {
+            break status;
+        }
+    };
+    let status_latency = start.elapsed();
+
+    // like the vanilla client, we send the ping on the same connection after
+    // the status. some servers close the connection instead of responding, so
+    // the time that the status took is used then.
+    let latency = measure_latency(&mut conn).await.unwrap_or(status_latency);
+    Ok(PingResult::from_status(status, latency))
+}
+
+/// Measure how long it takes for the server to respond to a ping packet.
+async fn measure_latency(
+    conn: &mut Connection<ClientboundStatusPacket, ServerboundStatusPacket>,
+) -> Result<Duration, PingError> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let start = Instant::now();
+    conn.write(ServerboundPingRequest { time }).await?;
+    loop {
+        if let ClientboundStatusPacket::PongResponse(_) = conn.read().await? {
+            return Ok(start.elapsed());
+        }
+    }
+}
+
+/// Ping a server with the legacy ping from Minecraft 1.6, which is also
+/// understood by newer servers.
+///
+/// Unlike [`ping`], this doesn't return a favicon, mods, or a player sample,
+/// even if the server is new enough to have them.
+pub async fn ping_legacy(address: impl TryInto<ServerAddress>) -> Result<PingResult, PingError> {
+    let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
+    let resolved_address = resolver::resolve_address(&address).await?;
+    ping_legacy_with_address(&address, &resolved_address).await
+}
+
+/// The protocol version that we say we are in legacy pings, which is 1.6.4.
+const LEGACY_PROTOCOL_VERSION: u8 = 78;
+
+async fn ping_legacy_with_address(
+    address: &ServerAddress,
+    resolved_address: &SocketAddr,
+) -> Result<PingResult, PingError> {
+    let mut stream = TcpStream::connect(resolved_address).await?;
+
+    let start = Instant::now();
+    stream
+        .write_all(&legacy_ping_request(&address.host, address.port))
+        .await?;
+
+    if stream.read_u8().await? != 0xff {
+        return Err(PingError::InvalidLegacyResponse);
+    }
+    let length = stream.read_u16().await? as usize;
+    let mut response = vec![0; length * 2];
+    stream.read_exact(&mut response).await?;
+    let latency = start.elapsed();
+
+    let response = String::from_utf16(
+        &response
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|_| PingError::InvalidLegacyResponse)?;
+    parse_legacy_ping_response(&response, latency).ok_or(PingError::InvalidLegacyResponse)
+}
+
+fn legacy_ping_request(host: &str, port: u16) -> Vec<u8> {
+    fn write_string(buf: &mut Vec<u8>, s: &str) {
+        let chars = s.encode_utf16().collect::<Vec<_>>();
+        buf.extend((chars.len() as u16).to_be_bytes());
+        for c in chars {
+            buf.extend(c.to_be_bytes());
+        }
+    }
+
+    let mut buf = vec![0xfe, 0x01, 0xfa];
+    write_string(&mut buf, "MC|PingHost");
+    let host_length = host.encode_utf16().count() as u16;
+    buf.extend((7 + host_length * 2).to_be_bytes());
+    buf.push(LEGACY_PROTOCOL_VERSION);
+    write_string(&mut buf, host);
+    buf.extend((port as i32).to_be_bytes());
+    buf
+}
+
+/// Parse the string that the server sends back in a legacy ping.
+///
+/// ```
+/// # use azalea_client::ping::parse_legacy_ping_response;
+/// # use std::time::Duration;
+/// let result =
+///     parse_legacy_ping_response("§1\x0078\x001.6.4\x00A server\x005\x0020", Duration::ZERO)
+///         .unwrap();
+/// assert_eq!(result.version.name, "1.6.4");
+/// assert_eq!(result.players.online, 5);
+/// assert_eq!(result.motd.to_string(), "A server");
+///
+/// // servers before 1.4 send a simpler format
+/// let result = parse_legacy_ping_response("A server§5§20", Duration::ZERO).unwrap();
+/// assert_eq!(result.players.max, 20);
+/// ```
+pub fn parse_legacy_ping_response(response: &str, latency: Duration) -> Option<PingResult> {
+    let (motd, version, online, max) = if let Some(response) = response.strip_prefix("§1\0") {
+        let mut parts = response.split('\0');
+        let protocol = parts.next()?.parse().ok()?;
+        let name = parts.next()?.to_string();
+        let motd = parts.next()?;
+        let online = parts.next()?;
+        let max = parts.next()?;
+        (motd, Version { name, protocol }, online, max)
+    } else {
+        let mut parts = response.rsplitn(3, '§');
+        let max = parts.next()?;
+        let online = parts.next()?;
+        let motd = parts.next()?;
+        let version = Version {
+            name: String::new(),
+            protocol: 0,
+        };
+        (motd, version, online, max)
+    };
+
+    Some(PingResult {
+        motd: FormattedText::Text(TextComponent::new(motd.to_string())),
+        version,
+        players: Players {
+            max: max.parse().ok()?,
+            online: online.parse().ok()?,
+            sample: Vec::new(),
+        },
+        favicon: None,
+        forge_data: None,
+        mod_info: None,
+        enforces_secure_chat: None,
+        latency,
+        legacy: true,
+    })
+}
+
+struct PngHeader {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlace: u8,
+}
+
+impl PngHeader {
+    fn read(png: &[u8]) -> Option<Self> {
+        let (chunk_type, data) = png_chunks(png)?.next()?;
+        if &chunk_type != b"IHDR" || data.len() < 13 {
+            return None;
+        }
+        Some(Self {
+            width: u32::from_be_bytes(data[0..4].try_into().ok()?),
+            height: u32::from_be_bytes(data[4..8].try_into().ok()?),
+            bit_depth: data[8],
+            color_type: data[9],
+            interlace: data[12],
+        })
+    }
+}
+
+/// Iterate over the type and data of every chunk in a PNG file, or return
+/// `None` if it doesn't start with the PNG signature.
+fn png_chunks(png: &[u8]) -> Option<impl Iterator<Item = ([u8; 4], &[u8])>> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    let mut rest = png.strip_prefix(&SIGNATURE)?;
+    Some(std::iter::from_fn(move || {
+        let length = u32::from_be_bytes(rest.get(0..4)?.try_into().ok()?) as usize;
+        let chunk_type = rest.get(4..8)?.try_into().ok()?;
+        let data = rest.get(8..8 + length)?;
+        // skip the crc
+        rest = rest.get(8 + length + 4..)?;
+        Some((chunk_type, data))
+    }))
+}
+
+/// The largest width or height of a PNG that we'll decode. Favicons are
+/// always 64x64, so anything much bigger is probably trying to make us run
+/// out of memory.
+const MAX_PNG_SIZE: u32 = 1024;
+
+fn decode_png(png: &[u8]) -> Option<Vec<u8>> {
+    let header = PngHeader::read(png)?;
+    if header.bit_depth != 8 || header.interlace != 0 {
+        return None;
+    }
+    if header.width > MAX_PNG_SIZE || header.height > MAX_PNG_SIZE {
+        return None;
+    }
+    let channels = match header.color_type {
+        // grayscale
+        0 => 1,
+        // rgb
+        2 => 3,
+        // palette
+        3 => 1,
+        // grayscale and alpha
+        4 => 2,
+        // rgba
+        6 => 4,
+        _ => return None,
+    };
+
+    let mut compressed = Vec::new();
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    for (chunk_type, data) in png_chunks(png)? {
+        match &chunk_type {
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"PLTE" => palette = data,
+            b"tRNS" => transparency = data,
+            _ => {}
+        }
+    }
+
+    let width = header.width as usize;
+    let height = header.height as usize;
+    let stride = width.checked_mul(channels)?;
+    // every row starts with a byte for its filter type
+    let expected_size = stride.checked_add(1)?.checked_mul(height)?;
+
+    // the data is only read up to the size we expect, so a small image can't
+    // decompress into something huge
+    let mut filtered = Vec::with_capacity(expected_size);
+    ZlibDecoder::new(&compressed[..])
+        .take(expected_size as u64)
+        .read_to_end(&mut filtered)
+        .ok()?;
+    if filtered.len() != expected_size {
+        return None;
+    }
+
+    let mut pixels = vec![0; stride * height];
+    for y in 0..height {
+        let filter = filtered[y * (stride + 1)];
+        let row = &filtered[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (previous_rows, current_rows) = pixels.split_at_mut(y * stride);
+        let previous = previous_rows.get(previous_rows.len().saturating_sub(stride)..);
+        let current = &mut current_rows[..stride];
+        for x in 0..stride {
+            let a = if x >= channels {
+                current[x - channels]
+            } else {
+                0
+            };
+            let b = if y > 0 {
+                previous.map_or(0, |p| p[x])
+            } else {
+                0
+            };
+            let c = if y > 0 && x >= channels {
+                previous.map_or(0, |p| p[x - channels])
+            } else {
+                0
+            };
+            current[x] = match filter {
+                0 => row[x],
+                1 => row[x].wrapping_add(a),
+                2 => row[x].wrapping_add(b),
+                3 => row[x].wrapping_add(((a as u16 + b as u16) / 2) as u8),
+                4 => row[x].wrapping_add(paeth(a, b, c)),
+                _ => return None,
+            };
+        }
+    }
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for pixel in pixels.chunks_exact(channels) {
+        match header.color_type {
+            0 => rgba.extend([pixel[0], pixel[0], pixel[0], 255]),
+            2 => rgba.extend([pixel[0], pixel[1], pixel[2], 255]),
+            3 => {
+                let index = pixel[0] as usize;
+                let color = palette.get(index * 3..index * 3 + 3)?;
+                let alpha = transparency.get(index).copied().unwrap_or(255);
+                rgba.extend([color[0], color[1], color[2], alpha]);
+            }
+            4 => rgba.extend([pixel[0], pixel[0], pixel[0], pixel[1]]),
+            _ => rgba.extend_from_slice(pixel),
+        }
+    }
+    Some(rgba)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use azalea_protocol::packets::status::c_pong_response::ClientboundPongResponse;
+    use flate2::{write::ZlibEncoder, Compression};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Make a PNG file from the filter type and bytes of each row. The CRCs
+    /// are left as zeros since we don't check them.
+    fn encode_png(
+        width: u32,
+        height: u32,
+        color_type: u8,
+        rows: &[(u8, &[u8])],
+        extra_chunks: &[(&[u8; 4], &[u8])],
+    ) -> Vec<u8> {
+        fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+            png.extend((data.len() as u32).to_be_bytes());
+            png.extend(chunk_type);
+            png.extend(data);
+            png.extend([0; 4]);
+        }
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend([8, color_type, 0, 0, 0]);
+        write_chunk(&mut png, b"IHDR", &header);
+        for (chunk_type, data) in extra_chunks {
+            write_chunk(&mut png, chunk_type, data);
+        }
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for (filter, row) in rows {
+            encoder.write_all(&[*filter]).unwrap();
+            encoder.write_all(row).unwrap();
+        }
+        write_chunk(&mut png, b"IDAT", &encoder.finish().unwrap());
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_decode_png_filters() {
+        let png = encode_png(
+            2,
+            3,
+            6,
+            &[
+                (0, &[10, 20, 30, 255, 40, 50, 60, 255]),
+                // sub
+                (1, &[1, 2, 3, 4, 4, 5, 6, 7]),
+                // up
+                (2, &[1, 1, 1, 0, 1, 1, 1, 0]),
+            ],
+            &[],
+        );
+        assert_eq!(
+            decode_png(&png).unwrap(),
+            [
+                10, 20, 30, 255, 40, 50, 60, 255, //
+                1, 2, 3, 4, 5, 7, 9, 11, //
+                2, 3, 4, 4, 6, 8, 10, 11,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_png_palette() {
+        let png = encode_png(
+            2,
+            1,
+            3,
+            &[(0, &[1, 0])],
+            &[(b"PLTE", &[255, 0, 0, 0, 255, 0]), (b"tRNS", &[128])],
+        );
+        assert_eq!(decode_png(&png).unwrap(), [0, 255, 0, 255, 255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_decode_png_wrong_size() {
+        // a row is missing
+        let png = encode_png(1, 2, 0, &[(0, &[5])], &[]);
+        assert_eq!(decode_png(&png), None);
+
+        // the header says it's too big to be a favicon
+        let png = encode_png(MAX_PNG_SIZE + 1, 1, 0, &[], &[]);
+        assert_eq!(decode_png(&png), None);
+
+        // a 1x1 image whose data decompresses to way more than that only has
+        // the part that we need read
+        let png = encode_png(1, 1, 0, &[(0, &[7]), (0, &vec![0; 1024 * 1024])], &[]);
+        assert_eq!(decode_png(&png).unwrap(), [7, 7, 7, 255]);
+    }
+
+    #[test]
+    fn test_paeth() {
+        assert_eq!(paeth(10, 20, 5), 20);
+        assert_eq!(paeth(20, 10, 5), 20);
+        assert_eq!(paeth(10, 10, 10), 10);
+        assert_eq!(paeth(0, 0, 255), 0);
+    }
+
+    #[test]
+    fn test_favicon_from_data_url() {
+        let png = encode_png(1, 1, 2, &[(0, &[1, 2, 3])], &[]);
+        let mut encoded = base64::engine::general_purpose::STANDARD.encode(&png);
+        encoded.insert(10, '\n');
+        let favicon = Favicon::from_data_url(&format!("data:image/png;base64,{encoded}")).unwrap();
+        assert_eq!((favicon.width, favicon.height), (1, 1));
+        assert_eq!(&*favicon.png, &png);
+        assert_eq!(favicon.to_rgba().unwrap(), [1, 2, 3, 255]);
+
+        assert!(Favicon::from_data_url("data:image/jpeg;base64,AAAA").is_none());
+        assert!(Favicon::from_data_url("data:image/png;base64,AAAA").is_none());
+    }
+
+    #[test]
+    fn test_parse_legacy_ping_response() {
+        let result = parse_legacy_ping_response(
+            "§1\x00127\x001.6.4\x00A §aserver\x000\x00100",
+            Duration::from_millis(5),
+        )
+        .unwrap();
+        assert_eq!(result.version.protocol, 127);
+        assert_eq!(result.players.online, 0);
+        assert_eq!(result.players.max, 100);
+        assert_eq!(result.latency, Duration::from_millis(5));
+        assert!(result.legacy);
+
+        // the motd can have formatting codes in the old format too
+        let result = parse_legacy_ping_response("A §aserver§1§2", Duration::ZERO).unwrap();
+        assert_eq!(result.motd.to_string(), "A server");
+        assert_eq!(result.players.online, 1);
+
+        assert!(
+            parse_legacy_ping_response("§1\x0078\x001.6.4\x00motd\x005", Duration::ZERO).is_none()
+        );
+        assert!(parse_legacy_ping_response("motd§five§20", Duration::ZERO).is_none());
+        assert!(parse_legacy_ping_response("", Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_legacy_ping_request() {
+        let request = legacy_ping_request("ab", 25565);
+        let mut expected = vec![0xfe, 0x01, 0xfa, 0, 11];
+        for c in "MC|PingHost".encode_utf16() {
+            expected.extend(c.to_be_bytes());
+        }
+        expected.extend([0, 11, LEGACY_PROTOCOL_VERSION, 0, 2, 0, b'a', 0, b'b']);
+        expected.extend(25565i32.to_be_bytes());
+        assert_eq!(request, expected);
+    }
+
+    #[test]
+    fn test_ping_legacy() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let server = tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 3];
+                stream.read_exact(&mut request).await.unwrap();
+                assert_eq!(request, [0xfe, 0x01, 0xfa]);
+
+                let response = "§1\x0078\x001.6.4\x00hi\x001\x002"
+                    .encode_utf16()
+                    .collect::<Vec<_>>();
+                let mut buf = vec![0xff];
+                buf.extend((response.len() as u16).to_be_bytes());
+                for c in response {
+                    buf.extend(c.to_be_bytes());
+                }
+                stream.write_all(&buf).await.unwrap();
+            });
+
+            let result = ping_legacy(address.as_str()).await.unwrap();
+            assert_eq!(result.motd.to_string(), "hi");
+            assert_eq!(result.version.name, "1.6.4");
+            assert_eq!((result.players.online, result.players.max), (1, 2));
+            server.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_ping_uses_one_connection() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let connections = Arc::new(AtomicUsize::new(0));
+            tokio::spawn({
+                let connections = connections.clone();
+                async move {
+                    loop {
+                        let (stream, _) = listener.accept().await.unwrap();
+                        if connections.fetch_add(1, Ordering::SeqCst) > 0 {
+                            // only the first connection gets a response
+                            continue;
+                        }
+                        let mut conn = Connection::<
+                            ServerboundHandshakePacket,
+                            ClientboundHandshakePacket,
+                        >::wrap(stream);
+                        conn.read().await.unwrap();
+                        let mut conn = conn.status();
+                        let ServerboundStatusPacket::StatusRequest(_) = conn.read().await.unwrap()
+                        else {
+                            panic!("expected a status request");
+                        };
+                        conn.write(ClientboundStatusResponse {
+                            description: FormattedText::from("hello"),
+                            favicon: None,
+                            players: Players {
+                                max: 20,
+                                online: 3,
+                                sample: Vec::new(),
+                            },
+                            version: Version {
+                                name: "azalea".to_owned(),
+                                protocol: PROTOCOL_VERSION,
+                            },
+                            enforces_secure_chat: None,
+                            forge_data: None,
+                            mod_info: None,
+                        })
+                        .await
+                        .unwrap();
+                        let ServerboundStatusPacket::PingRequest(ping) = conn.read().await.unwrap()
+                        else {
+                            panic!("expected a ping request");
+                        };
+                        conn.write(ClientboundPongResponse { time: ping.time })
+                            .await
+                            .unwrap();
+                    }
+                }
+            });
+
+            let result = ping(address.as_str()).await.unwrap();
+            assert_eq!(result.motd.to_string(), "hello");
+            assert_eq!(result.players.online, 3);
+            assert!(!result.legacy);
+            assert_eq!(connections.load(Ordering::SeqCst), 1);
+        });
+    }
+}
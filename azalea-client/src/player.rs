@@ -1,14 +1,13 @@
 use azalea_auth::game_profile::GameProfile;
 use azalea_chat::FormattedText;
 use azalea_core::game_type::GameMode;
-use azalea_entity::indexing::EntityUuidIndex;
 use bevy_ecs::{
     event::EventReader,
-    system::{Commands, Res},
+    system::{Commands, Query},
 };
 use uuid::Uuid;
 
-use crate::{packet_handling::game::AddPlayerEvent, GameProfileComponent};
+use crate::{packet_handling::game::AddPlayerEvent, GameProfileComponent, InstanceHolder};
 
 /// A player in the tab list.
 #[derive(Debug, Clone)]
@@ -35,10 +34,19 @@ pub struct PlayerInfo {
 pub fn retroactively_add_game_profile_component(
     mut commands: Commands,
     mut events: EventReader<AddPlayerEvent>,
-    entity_uuid_index: Res<EntityUuidIndex>,
+    query: Query<&InstanceHolder>,
 ) {
     for event in events.read() {
-        if let Some(entity) = entity_uuid_index.get(&event.info.uuid) {
+        let Ok(instance_holder) = query.get(event.entity) else {
+            continue;
+        };
+        let entity = instance_holder
+            .instance
+            .read()
+            .entity_by_uuid
+            .get(&event.info.uuid)
+            .copied();
+        if let Some(entity) = entity {
             commands
                 .entity(entity)
                 .insert(GameProfileComponent(event.info.profile.clone()));
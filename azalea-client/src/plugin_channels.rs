@@ -0,0 +1,424 @@
+//! Send and receive plugin messages, which servers use for talking to mods
+//! and client-side companions of their plugins.
+//!
+//! ```no_run
+//! # use azalea_client::Client;
+//! # fn example(bot: &Client) -> Result<(), Box<dyn std::error::Error>> {
+//! let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+//! bot.register_plugin_channel("myplugin:main", move |data| {
+//!     // the ecs is locked while handlers run, so do the real work somewhere
+//!     // else
+//!     let _ = sender.send(data.to_vec());
+//! })?;
+//! bot.send_plugin_message("myplugin:main", b"hello")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The channels that we register are sent to the server in a
+//! `minecraft:register` message, which is how plugins on servers like Paper
+//! know that they're allowed to send us messages on them. They're sent again
+//! every time the server puts us in the configuration state.
+
+use std::collections::{HashMap, HashSet};
+
+use azalea_core::resource_location::ResourceLocation;
+use azalea_protocol::packets::{
+    config::{self, ClientboundConfigPacket},
+    game::{self, ClientboundGamePacket},
+    ConnectionProtocol,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::{
+    client::InConfigState,
+    packet_handling::{
+        configuration::{handle_send_packet_event, ConfigurationEvent, SendConfigurationEvent},
+        game::PacketEvent,
+    },
+    raw_connection::{RawConnection, WritePacketError},
+    Client,
+};
+
+/// The channel that's used for telling the other side which channels we can
+/// receive messages on.
+pub const REGISTER_CHANNEL: &str = "minecraft:register";
+/// The channel that's used for telling the other side that we can't receive
+/// messages on some channels anymore.
+pub const UNREGISTER_CHANNEL: &str = "minecraft:unregister";
+
+pub struct PluginChannelsPlugin;
+impl Plugin for PluginChannelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PluginMessageEvent>().add_systems(
+            Update,
+            (
+                handle_plugin_messages,
+                register_channels_in_configuration.before(handle_send_packet_event),
+            ),
+        );
+    }
+}
+
+/// A function that's called with the data of every plugin message that the
+/// server sends us on a channel. See [`Client::register_plugin_channel`].
+pub type PluginChannelHandler = Box<dyn FnMut(&[u8]) + Send + Sync>;
+
+/// The plugin channels that a client registered and the ones that the server
+/// told us about.
+#[derive(Component, Default)]
+pub struct PluginChannels {
+    handlers: HashMap<ResourceLocation, PluginChannelHandler>,
+    /// The channels that the server said it can receive messages on.
+    pub server_channels: HashSet<ResourceLocation>,
+}
+
+impl PluginChannels {
+    /// Whether we registered a handler for the channel.
+    pub fn is_registered(&self, channel: &ResourceLocation) -> bool {
+        self.handlers.contains_key(channel)
+    }
+
+    /// The channels that we registered handlers for.
+    pub fn registered(&self) -> impl Iterator<Item = &ResourceLocation> {
+        self.handlers.keys()
+    }
+}
+
+/// An event sent when the server sends us a plugin message, in either the
+/// configuration or game state.
+///
+/// This is sent for every channel, including ones that we didn't register.
+#[derive(Event, Debug, Clone)]
+pub struct PluginMessageEvent {
+    /// The client entity that received the message.
+    pub entity: Entity,
+    pub channel: ResourceLocation,
+    pub data: Box<[u8]>,
+}
+
+impl Client {
+    /// Call the handler whenever the server sends us a message on the given
+    /// channel, and tell the server that we can receive messages on it.
+    ///
+    /// The handler is called by [`handle_plugin_messages`] while the ECS is
+    /// locked, so it must not use the client or it'll deadlock. If you need to
+    /// do more than that, send the data somewhere else from it or read
+    /// [`PluginMessageEvent`]s instead. Registering a channel again replaces
+    /// its handler.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if we couldn't tell the server about the channel. The
+    /// handler is still registered in that case.
+    pub fn register_plugin_channel(
+        &self,
+        channel: &str,
+        handler: impl FnMut(&[u8]) + Send + Sync + 'static,
+    ) -> Result<(), WritePacketError> {
+        let channel = ResourceLocation::new(channel);
+
+        let mut ecs = self.ecs.lock();
+        let mut entity = ecs.entity_mut(self.entity);
+        let mut plugin_channels = entity.take::<PluginChannels>().unwrap_or_default();
+        plugin_channels
+            .handlers
+            .insert(channel.clone(), Box::new(handler));
+        entity.insert(plugin_channels);
+
+        // if we haven't gotten to the configuration state yet, the channel
+        // will be registered when we do
+        let raw_connection = self.raw_connection(&mut ecs);
+        if can_send_plugin_messages(raw_connection) {
+            write_plugin_message(
+                raw_connection,
+                ResourceLocation::new(REGISTER_CHANNEL),
+                channel.to_string().into_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Stop handling messages on the channel, and tell the server that we
+    /// can't receive them anymore.
+    pub fn unregister_plugin_channel(&self, channel: &str) -> Result<(), WritePacketError> {
+        let channel = ResourceLocation::new(channel);
+
+        let mut ecs = self.ecs.lock();
+        let Some(mut plugin_channels) = ecs.get_mut::<PluginChannels>(self.entity) else {
+            return Ok(());
+        };
+        if plugin_channels.handlers.remove(&channel).is_none() {
+            return Ok(());
+        }
+
+        let raw_connection = self.raw_connection(&mut ecs);
+        if can_send_plugin_messages(raw_connection) {
+            write_plugin_message(
+                raw_connection,
+                ResourceLocation::new(UNREGISTER_CHANNEL),
+                channel.to_string().into_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Send a message to the server on a plugin channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if we're not in the configuration or game state, or if
+    /// the packet couldn't be sent.
+    pub fn send_plugin_message(&self, channel: &str, data: &[u8]) -> Result<(), WritePacketError> {
+        let mut ecs = self.ecs.lock();
+        write_plugin_message(
+            self.raw_connection(&mut ecs),
+            ResourceLocation::new(channel),
+            data.to_vec(),
+        )
+    }
+
+    /// The channels that the server said it can receive plugin messages on.
+    pub fn server_plugin_channels(&self) -> HashSet<ResourceLocation> {
+        self.map_get_component::<PluginChannels, _>(|plugin_channels| {
+            plugin_channels
+                .map(|plugin_channels| plugin_channels.server_channels.clone())
+                .unwrap_or_default()
+        })
+    }
+}
+
+fn can_send_plugin_messages(raw_connection: &RawConnection) -> bool {
+    matches!(
+        raw_connection.connection_protocol,
+        ConnectionProtocol::Configuration | ConnectionProtocol::Game
+    )
+}
+
+/// Send a custom payload packet for whichever state we're in.
+fn write_plugin_message(
+    raw_connection: &RawConnection,
+    channel: ResourceLocation,
+    data: Vec<u8>,
+) -> Result<(), WritePacketError> {
+    match raw_connection.connection_protocol {
+        ConnectionProtocol::Configuration => {
+            raw_connection.write_packet(config::s_custom_payload::ServerboundCustomPayload {
+                identifier: channel,
+                data: data.into(),
+            })
+        }
+        ConnectionProtocol::Game => {
+            raw_connection.write_packet(game::s_custom_payload::ServerboundCustomPayload {
+                identifier: channel,
+                data: data.into(),
+            })
+        }
+        got => Err(WritePacketError::WrongState {
+            expected: ConnectionProtocol::Game,
+            got,
+        }),
+    }
+}
+
+/// Split the data from a `minecraft:register` or `minecraft:unregister`
+/// message into channel names.
+fn parse_channel_list(data: &[u8]) -> impl Iterator<Item = ResourceLocation> + '_ {
+    data.split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| std::str::from_utf8(name).ok())
+        .map(ResourceLocation::new)
+}
+
+pub fn handle_plugin_messages(
+    mut config_events: EventReader<ConfigurationEvent>,
+    mut game_events: EventReader<PacketEvent>,
+    mut query: Query<&mut PluginChannels>,
+    mut plugin_message_events: EventWriter<PluginMessageEvent>,
+) {
+    let config_messages = config_events
+        .read()
+        .filter_map(|event| match &event.packet {
+            ClientboundConfigPacket::CustomPayload(p) => {
+                Some((event.entity, &p.identifier, &p.data))
+            }
+            _ => None,
+        });
+    let game_messages = game_events
+        .read()
+        .filter_map(|event| match event.packet.as_ref() {
+            ClientboundGamePacket::CustomPayload(p) => Some((event.entity, &p.identifier, &p.data)),
+            _ => None,
+        });
+
+    for (entity, channel, data) in config_messages.chain(game_messages) {
+        if let Ok(mut plugin_channels) = query.get_mut(entity) {
+            match channel.to_string().as_str() {
+                REGISTER_CHANNEL => plugin_channels
+                    .server_channels
+                    .extend(parse_channel_list(data)),
+                UNREGISTER_CHANNEL => {
+                    for channel in parse_channel_list(data) {
+                        plugin_channels.server_channels.remove(&channel);
+                    }
+                }
+                _ => {}
+            }
+
+            if let Some(handler) = plugin_channels.handlers.get_mut(channel) {
+                debug!("Calling the handler for plugin channel {channel}");
+                handler(data);
+            }
+        }
+
+        plugin_message_events.send(PluginMessageEvent {
+            entity,
+            channel: channel.clone(),
+            data: data.to_vec().into(),
+        });
+    }
+}
+
+/// Tell the server about our channels when we start configuring, since it
+/// forgets about them when we join or switch servers.
+pub fn register_channels_in_configuration(
+    mut commands: Commands,
+    query: Query<(Entity, Option<&PluginChannels>), Added<InConfigState>>,
+    mut send_packet_events: EventWriter<SendConfigurationEvent>,
+) {
+    for (entity, plugin_channels) in &query {
+        let Some(plugin_channels) = plugin_channels else {
+            commands.entity(entity).insert(PluginChannels::default());
+            continue;
+        };
+        let channels = plugin_channels
+            .registered()
+            .map(|channel| channel.to_string())
+            .collect::<Vec<_>>();
+        if channels.is_empty() {
+            continue;
+        }
+        send_packet_events.send(SendConfigurationEvent::new(
+            entity,
+            config::s_custom_payload::ServerboundCustomPayload {
+                identifier: ResourceLocation::new(REGISTER_CHANNEL),
+                data: channels.join("\0").into_bytes().into(),
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_auth::game_profile::GameProfile;
+    use azalea_protocol::read::deserialize_packet;
+    use bevy_ecs::{event::Events, schedule::Schedule};
+    use parking_lot::Mutex;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_list() {
+        let channels = parse_channel_list(b"a:b\0minecraft:brand\0\0c:d\0").collect::<Vec<_>>();
+        assert_eq!(
+            channels,
+            [
+                ResourceLocation::new("a:b"),
+                ResourceLocation::new("minecraft:brand"),
+                ResourceLocation::new("c:d"),
+            ]
+        );
+        // names that aren't valid utf-8 are skipped
+        assert_eq!(parse_channel_list(b"\xff\0a:b").count(), 1);
+        assert_eq!(parse_channel_list(b"").count(), 0);
+    }
+
+    #[test]
+    fn test_register_without_runtime() {
+        // the connection's tasks need a runtime, but registering a channel
+        // shouldn't
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (raw_connection, mut outgoing) = {
+            let _guard = rt.enter();
+            RawConnection::new_headless(mpsc::unbounded_channel().0, ConnectionProtocol::Game)
+        };
+        let mut ecs = World::new();
+        let entity = ecs.spawn(raw_connection).id();
+        let client = Client::new(
+            GameProfile::new(Uuid::nil(), "bot".to_owned()),
+            entity,
+            Arc::new(Mutex::new(ecs)),
+            mpsc::unbounded_channel().0,
+        );
+
+        client.register_plugin_channel("a:b", |_| {}).unwrap();
+        client.register_plugin_channel("c:d", |_| {}).unwrap();
+
+        let ecs = client.ecs.lock();
+        let plugin_channels = ecs.get::<PluginChannels>(entity).unwrap();
+        assert!(plugin_channels.is_registered(&ResourceLocation::new("a:b")));
+        assert!(plugin_channels.is_registered(&ResourceLocation::new("c:d")));
+        drop(ecs);
+
+        let raw_packet = outgoing.try_recv().unwrap();
+        let packet = deserialize_packet::<game::ServerboundGamePacket>(&mut std::io::Cursor::new(
+            &raw_packet,
+        ))
+        .unwrap();
+        let game::ServerboundGamePacket::CustomPayload(packet) = packet else {
+            panic!("expected a custom payload packet, got {packet:?}");
+        };
+        assert_eq!(packet.identifier, ResourceLocation::new(REGISTER_CHANNEL));
+        assert_eq!(&*packet.data, b"a:b");
+    }
+
+    #[test]
+    fn test_handle_plugin_messages() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ConfigurationEvent>>();
+        ecs.init_resource::<Events<PacketEvent>>();
+        ecs.init_resource::<Events<PluginMessageEvent>>();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut plugin_channels = PluginChannels::default();
+        plugin_channels
+            .handlers
+            .insert(ResourceLocation::new("a:b"), {
+                let received = received.clone();
+                Box::new(move |data: &[u8]| received.lock().push(data.to_vec()))
+            });
+        let entity = ecs.spawn(plugin_channels).id();
+
+        let payload = |channel: &str, data: &[u8]| ConfigurationEvent {
+            entity,
+            packet: ClientboundConfigPacket::CustomPayload(
+                config::c_custom_payload::ClientboundCustomPayload {
+                    identifier: ResourceLocation::new(channel),
+                    data: data.to_vec().into(),
+                },
+            ),
+        };
+        ecs.send_event(payload(REGISTER_CHANNEL, b"x:y\0z:w"));
+        ecs.send_event(payload("a:b", b"hello"));
+        ecs.send_event(payload("other:channel", b"ignored"));
+        ecs.send_event(payload(UNREGISTER_CHANNEL, b"z:w"));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(handle_plugin_messages);
+        schedule.run(&mut ecs);
+
+        assert_eq!(*received.lock(), [b"hello".to_vec()]);
+        assert_eq!(
+            ecs.get::<PluginChannels>(entity).unwrap().server_channels,
+            HashSet::from([ResourceLocation::new("x:y")])
+        );
+        // every message is sent as an event, even ones we don't handle
+        assert_eq!(ecs.resource::<Events<PluginMessageEvent>>().len(), 4);
+    }
+}
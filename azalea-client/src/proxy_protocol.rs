@@ -0,0 +1,144 @@
+//! Join servers that are behind HAProxy (or anything else that expects the
+//! PROXY protocol) by sending the header that the proxy would have sent.
+//!
+//! Servers like Paper can be set up to read a PROXY protocol header at the
+//! start of every connection, which is how they know the real address of
+//! players that connected through HAProxy. Like with
+//! [`bungeecord_forwarding`](crate::bungeecord_forwarding), the server can't
+//! check where the header came from, so only use this on servers where that's
+//! fine.
+//!
+//! Only version 2 of the protocol (the binary one) is supported.
+
+use std::net::{IpAddr, SocketAddr};
+
+/// The bytes that every version 2 header starts with.
+pub const SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// A PROXY protocol version 2 header that's sent as soon as we connect.
+#[derive(Clone, Debug)]
+pub struct ProxyProtocolHeader {
+    /// The address that the server will think we're connecting from.
+    pub source_address: SocketAddr,
+    /// The address that the server will think we connected to. If this is
+    /// `None`, the address of the server is used.
+    pub destination_address: Option<SocketAddr>,
+}
+
+impl ProxyProtocolHeader {
+    pub fn new(source_address: SocketAddr) -> Self {
+        Self {
+            source_address,
+            destination_address: None,
+        }
+    }
+
+    /// Encode the header for a connection to the given server.
+    ///
+    /// If only one of the addresses is IPv6, the other one is sent as an
+    /// IPv4-mapped IPv6 address.
+    ///
+    /// ```
+    /// # use azalea_client::proxy_protocol::{ProxyProtocolHeader, SIGNATURE};
+    /// let header = ProxyProtocolHeader::new("10.0.0.2:51000".parse().unwrap());
+    /// let bytes = header.encode("10.0.0.1:25565".parse().unwrap());
+    /// assert_eq!(bytes[..12], SIGNATURE);
+    /// assert_eq!(
+    ///     bytes[12..],
+    ///     [
+    ///         0x21, 0x11, 0, 12, // version 2 PROXY command, TCP over IPv4
+    ///         10, 0, 0, 2, 10, 0, 0, 1, // source and destination addresses
+    ///         0xc7, 0x38, 0x63, 0xdd, // source and destination ports
+    ///     ]
+    /// );
+    /// ```
+    pub fn encode(&self, server_address: SocketAddr) -> Vec<u8> {
+        let source = self.source_address;
+        let destination = self.destination_address.unwrap_or(server_address);
+
+        let mut bytes = SIGNATURE.to_vec();
+        // version 2, and the PROXY command (as opposed to LOCAL)
+        bytes.push(0x21);
+        match (source.ip(), destination.ip()) {
+            (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => {
+                // TCP over IPv4
+                bytes.push(0x11);
+                bytes.extend(12u16.to_be_bytes());
+                bytes.extend(source_ip.octets());
+                bytes.extend(destination_ip.octets());
+            }
+            (source_ip, destination_ip) => {
+                // TCP over IPv6
+                bytes.push(0x21);
+                bytes.extend(36u16.to_be_bytes());
+                bytes.extend(to_ipv6_octets(source_ip));
+                bytes.extend(to_ipv6_octets(destination_ip));
+            }
+        }
+        bytes.extend(source.port().to_be_bytes());
+        bytes.extend(destination.port().to_be_bytes());
+        bytes
+    }
+}
+
+fn to_ipv6_octets(ip: IpAddr) -> [u8; 16] {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped().octets(),
+        IpAddr::V6(ip) => ip.octets(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+
+    #[test]
+    fn test_encode_ipv6() {
+        let header = ProxyProtocolHeader::new("[2001:db8::2]:51000".parse().unwrap());
+        let bytes = header.encode("[2001:db8::1]:25565".parse().unwrap());
+        assert_eq!(bytes[..12], SIGNATURE);
+        // version 2 PROXY command, TCP over IPv6
+        assert_eq!(bytes[12..14], [0x21, 0x21]);
+        // the addresses and ports are 36 bytes
+        assert_eq!(bytes[14..16], [0, 36]);
+        assert_eq!(bytes.len(), 16 + 36);
+        assert_eq!(
+            bytes[16..32],
+            "2001:db8::2".parse::<Ipv6Addr>().unwrap().octets()
+        );
+        assert_eq!(
+            bytes[32..48],
+            "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets()
+        );
+        assert_eq!(bytes[48..], [0xc7, 0x38, 0x63, 0xdd]);
+    }
+
+    #[test]
+    fn test_encode_mixed_addresses() {
+        let ipv4 = Ipv4Addr::new(10, 0, 0, 2);
+        let ipv6 = "2001:db8::1".parse::<Ipv6Addr>().unwrap();
+
+        // an IPv4 source connecting to an IPv6 server
+        let header = ProxyProtocolHeader::new(SocketAddr::new(ipv4.into(), 51000));
+        let bytes = header.encode(SocketAddr::new(ipv6.into(), 25565));
+        assert_eq!(bytes[13], 0x21);
+        assert_eq!(bytes.len(), 16 + 36);
+        assert_eq!(bytes[16..32], ipv4.to_ipv6_mapped().octets());
+        assert_eq!(bytes[32..48], ipv6.octets());
+        assert_eq!(bytes[48..], [0xc7, 0x38, 0x63, 0xdd]);
+
+        // and an IPv6 source with an IPv4 destination
+        let header = ProxyProtocolHeader {
+            source_address: SocketAddr::new(ipv6.into(), 51000),
+            destination_address: Some(SocketAddr::new(ipv4.into(), 25565)),
+        };
+        let bytes = header.encode("127.0.0.1:25565".parse().unwrap());
+        assert_eq!(bytes[13], 0x21);
+        assert_eq!(bytes.len(), 16 + 36);
+        assert_eq!(bytes[16..32], ipv6.octets());
+        assert_eq!(bytes[32..48], ipv4.to_ipv6_mapped().octets());
+        assert_eq!(bytes[48..], [0xc7, 0x38, 0x63, 0xdd]);
+    }
+}
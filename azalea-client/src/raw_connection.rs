@@ -1,18 +1,39 @@
+use std::any::Any;
+use std::collections::VecDeque;
 use std::fmt::Debug;
+use std::future::Future;
+use std::io::Cursor;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use azalea_buf::AzaleaReadVar;
 use azalea_protocol::{
     connect::{RawReadConnection, RawWriteConnection},
-    packets::{ConnectionProtocol, Packet, ProtocolPacket},
+    packets::{
+        config::s_finish_configuration::ServerboundFinishConfiguration,
+        game::{
+            s_configuration_acknowledged::ServerboundConfigurationAcknowledged,
+            ServerboundGamePacket,
+        },
+        ConnectionProtocol, Packet, ProtocolPacket,
+    },
     read::ReadPacketError,
     write::serialize_packet,
 };
 use bevy_ecs::prelude::*;
 use parking_lot::Mutex;
 use thiserror::Error;
-use tokio::sync::mpsc::{self, error::SendError};
+use tokio::sync::{
+    mpsc::{self, error::SendError},
+    oneshot,
+};
 use tracing::error;
 
+use crate::{
+    packet_middleware::{run_packet_middleware, PacketMiddleware},
+    InConfigState, JoinedClientBundle,
+};
+
 /// A component for clients that can read and write packets to the server. This
 /// works with raw bytes, so you'll have to serialize/deserialize packets
 /// yourself. It will do the compression and encryption for you though.
@@ -29,8 +50,56 @@ pub struct RawConnection {
     pub write_packets_task: tokio::task::JoinHandle<()>,
 
     pub connection_protocol: ConnectionProtocol,
+
+    /// Used for asking the read and write tasks to hand over their halves of
+    /// the connection. See [`RawConnection::take_over`].
+    pub take_over_senders: TakeOverSenders,
+    /// The state that the connection was switched to while it was taken over,
+    /// which is applied to the client by [`apply_taken_over_state`].
+    pub taken_over_state: Arc<Mutex<Option<ConnectionProtocol>>>,
+
+    /// Functions that are called with every raw packet that the client handles
+    /// or writes. See [`PacketListener`].
+    pub packet_listeners: Vec<PacketListener>,
+
+    /// Functions that can change or drop the game packets that we write. See
+    /// [`crate::packet_middleware`].
+    pub packet_middleware: Vec<PacketMiddleware>,
+    /// Packets that were delayed by a [`PacketAction::Delay`] and the time
+    /// that they should be written at, in order.
+    ///
+    /// [`PacketAction::Delay`]: crate::packet_middleware::PacketAction::Delay
+    pub delayed_packets: Mutex<VecDeque<(Instant, Box<[u8]>)>>,
+}
+
+/// Whether a packet was sent by the server or by us.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PacketFlow {
+    Clientbound,
+    Serverbound,
 }
 
+/// A function that's called with the raw bytes of a packet (the id followed by
+/// the data), and the state that the connection was in.
+///
+/// Clientbound packets are passed to the listeners right before the client
+/// tries to read them, so packets that can't be read are included too.
+/// Serverbound packets are passed when they're added to the queue of packets
+/// to write.
+pub type PacketListener = Arc<dyn Fn(PacketFlow, ConnectionProtocol, &[u8]) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct TakeOverSenders {
+    pub read: mpsc::UnboundedSender<TakeOverRequest<RawReadConnection>>,
+    pub write: mpsc::UnboundedSender<TakeOverRequest<RawWriteConnection>>,
+}
+
+/// A request for a task to give up its half of the connection.
+///
+/// The task sends its half and a sender for returning it, and then waits until
+/// it gets it back.
+pub type TakeOverRequest<T> = oneshot::Sender<(T, oneshot::Sender<T>)>;
+
 #[derive(Clone)]
 pub struct RawConnectionReader {
     pub incoming_packet_queue: Arc<Mutex<Vec<Box<[u8]>>>>,
@@ -58,6 +127,12 @@ pub enum WritePacketError {
     },
 }
 
+#[derive(Error, Debug)]
+pub enum TakeOverError {
+    #[error("The connection was closed before it could be taken over")]
+    ConnectionClosed,
+}
+
 impl RawConnection {
     pub fn new(
         run_schedule_sender: mpsc::UnboundedSender<()>,
@@ -66,6 +141,8 @@ impl RawConnection {
         raw_write_connection: RawWriteConnection,
     ) -> Self {
         let (outgoing_packets_sender, outgoing_packets_receiver) = mpsc::unbounded_channel();
+        let (read_take_over_sender, read_take_over_receiver) = mpsc::unbounded_channel();
+        let (write_take_over_sender, write_take_over_receiver) = mpsc::unbounded_channel();
 
         let incoming_packet_queue = Arc::new(Mutex::new(Vec::new()));
 
@@ -77,12 +154,16 @@ impl RawConnection {
             outgoing_packets_sender,
         };
 
-        let read_packets_task = tokio::spawn(reader.clone().read_task(raw_read_connection));
-        let write_packets_task = tokio::spawn(
-            writer
+        let read_packets_task = tokio::spawn(
+            reader
                 .clone()
-                .write_task(raw_write_connection, outgoing_packets_receiver),
+                .read_task(raw_read_connection, read_take_over_receiver),
         );
+        let write_packets_task = tokio::spawn(writer.clone().write_task(
+            raw_write_connection,
+            outgoing_packets_receiver,
+            write_take_over_receiver,
+        ));
 
         Self {
             reader,
@@ -90,31 +171,132 @@ impl RawConnection {
             read_packets_task,
             write_packets_task,
             connection_protocol,
+            take_over_senders: TakeOverSenders {
+                read: read_take_over_sender,
+                write: write_take_over_sender,
+            },
+            taken_over_state: Arc::new(Mutex::new(None)),
+            packet_listeners: Vec::new(),
+            packet_middleware: Vec::new(),
+            delayed_packets: Default::default(),
         }
     }
 
+    /// Make a connection that isn't connected to a server, for clients that
+    /// only handle packets that are injected into them, like when replaying a
+    /// packet log.
+    ///
+    /// Packets that the client writes are sent to the returned receiver. This
+    /// must be called from inside a Tokio runtime.
+    pub fn new_headless(
+        run_schedule_sender: mpsc::UnboundedSender<()>,
+        connection_protocol: ConnectionProtocol,
+    ) -> (Self, mpsc::UnboundedReceiver<Box<[u8]>>) {
+        let (outgoing_packets_sender, outgoing_packets_receiver) = mpsc::unbounded_channel();
+
+        let reader = RawConnectionReader {
+            incoming_packet_queue: Arc::new(Mutex::new(Vec::new())),
+            run_schedule_sender,
+        };
+        let writer = RawConnectionWriter {
+            outgoing_packets_sender,
+        };
+
+        // the client is disconnected when the read task ends, so these have to
+        // run forever
+        let read_packets_task = tokio::spawn(std::future::pending());
+        let write_packets_task = tokio::spawn(std::future::pending());
+
+        let raw_connection = Self {
+            reader,
+            writer,
+            read_packets_task,
+            write_packets_task,
+            connection_protocol,
+            // there's no connection to take over, so the receivers are dropped
+            take_over_senders: TakeOverSenders {
+                read: mpsc::unbounded_channel().0,
+                write: mpsc::unbounded_channel().0,
+            },
+            taken_over_state: Arc::new(Mutex::new(None)),
+            packet_listeners: Vec::new(),
+            packet_middleware: Vec::new(),
+            delayed_packets: Default::default(),
+        };
+        (raw_connection, outgoing_packets_receiver)
+    }
+
     pub fn write_raw_packet(&self, raw_packet: Box<[u8]>) -> Result<(), WritePacketError> {
+        self.notify_packet_listeners(PacketFlow::Serverbound, &raw_packet);
         self.writer.outgoing_packets_sender.send(raw_packet)?;
         Ok(())
     }
 
+    /// Call every [`PacketListener`] with the packet.
+    pub fn notify_packet_listeners(&self, flow: PacketFlow, raw_packet: &[u8]) {
+        for listener in &self.packet_listeners {
+            listener(flow, self.connection_protocol, raw_packet);
+        }
+    }
+
     /// Write the packet with the given state to the server.
     ///
+    /// Game packets go through the [`Self::packet_middleware`] first, and
+    /// they're not written if it drops them. If it delays them, they're
+    /// written by [`Self::write_delayed_packets`] later, and so is every game
+    /// packet after them so they stay in order.
+    ///
     /// # Errors
     ///
     /// Returns an error if the packet is not valid for the current state, or if
     /// encoding it failed somehow (like it's too big or something).
-    pub fn write_packet<P: ProtocolPacket + Debug>(
+    pub fn write_packet<P: ProtocolPacket + Debug + 'static>(
         &self,
         packet: impl Packet<P>,
     ) -> Result<(), WritePacketError> {
-        let packet = packet.into_variant();
+        let mut packet = packet.into_variant();
+        let mut delay = Duration::ZERO;
+        if let Some(game_packet) =
+            (&mut packet as &mut dyn Any).downcast_mut::<ServerboundGamePacket>()
+        {
+            match run_packet_middleware(&self.packet_middleware, game_packet) {
+                Some(middleware_delay) => delay = middleware_delay,
+                None => return Ok(()),
+            }
+        }
         let raw_packet = serialize_packet(&packet)?;
-        self.write_raw_packet(raw_packet)?;
+
+        let mut delayed_packets = self.delayed_packets.lock();
+        if delay.is_zero() && delayed_packets.is_empty() {
+            drop(delayed_packets);
+            self.write_raw_packet(raw_packet)?;
+        } else {
+            let mut write_at = Instant::now() + delay;
+            if let Some((last_write_at, _)) = delayed_packets.back() {
+                write_at = write_at.max(*last_write_at);
+            }
+            delayed_packets.push_back((write_at, raw_packet));
+        }
 
         Ok(())
     }
 
+    /// Write the packets that were delayed by the
+    /// [`Self::packet_middleware`] and are ready to be sent.
+    pub fn write_delayed_packets(&self) -> Result<(), WritePacketError> {
+        let now = Instant::now();
+        let mut delayed_packets = self.delayed_packets.lock();
+        while delayed_packets
+            .front()
+            .is_some_and(|(write_at, _)| *write_at <= now)
+        {
+            if let Some((_, raw_packet)) = delayed_packets.pop_front() {
+                self.write_raw_packet(raw_packet)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns whether the connection is still alive.
     pub fn is_alive(&self) -> bool {
         !self.read_packets_task.is_finished()
@@ -127,18 +309,227 @@ impl RawConnection {
     pub fn set_state(&mut self, connection_protocol: ConnectionProtocol) {
         self.connection_protocol = connection_protocol;
     }
+
+    /// Add a packet to the incoming packet queue as if it was sent by the
+    /// server, and run the schedule so it gets handled.
+    pub fn inject_raw_packet(&self, raw_packet: Box<[u8]>) {
+        let _ = self.reader.inject_raw_packet(raw_packet);
+    }
+
+    /// Temporarily take the connection away from the read and write tasks, so
+    /// you can read and write packets yourself. This is meant for implementing
+    /// packets or protocol features that Azalea doesn't support.
+    ///
+    /// The tasks hand over their halves of the connection in between packets,
+    /// and they get them back when the returned [`TakenOverConnection`] is
+    /// dropped. Packets that the client writes in the meantime are queued and
+    /// sent afterwards, and packets that you read won't be handled by the
+    /// client unless you pass them to
+    /// [`TakenOverConnection::inject_raw_packet`].
+    ///
+    /// If you switch between the configuration and game states by writing
+    /// the packet that acknowledges the switch with
+    /// [`TakenOverConnection::write`], the client is switched to the new state
+    /// when the connection is given back. If you switch states some other
+    /// way, call [`TakenOverConnection::set_state`].
+    ///
+    /// The returned future doesn't borrow the `RawConnection`, so the ECS
+    /// doesn't have to be locked while you wait for it. You probably want to
+    /// use [`Client::take_over_connection`](crate::Client::take_over_connection)
+    /// instead though.
+    pub fn take_over(
+        &self,
+    ) -> impl Future<Output = Result<TakenOverConnection, TakeOverError>> + Send + 'static {
+        let (read_sender, read_receiver) = oneshot::channel();
+        let (write_sender, write_receiver) = oneshot::channel();
+        let _ = self.take_over_senders.read.send(read_sender);
+        let _ = self.take_over_senders.write.send(write_sender);
+        let reader = self.reader.clone();
+        let state = self.connection_protocol;
+        let taken_over_state = self.taken_over_state.clone();
+
+        async move {
+            let (read_conn, read_return) = read_receiver
+                .await
+                .map_err(|_| TakeOverError::ConnectionClosed)?;
+            let Ok((write_conn, write_return)) = write_receiver.await else {
+                // give the read half back, although the client is probably
+                // about to disconnect anyways
+                let _ = read_return.send(read_conn);
+                return Err(TakeOverError::ConnectionClosed);
+            };
+
+            Ok(TakenOverConnection {
+                read: Some((read_conn, read_return)),
+                write: Some((write_conn, write_return)),
+                reader,
+                initial_state: state,
+                state,
+                taken_over_state,
+            })
+        }
+    }
+}
+
+/// A connection that was taken away from the read and write tasks with
+/// [`RawConnection::take_over`].
+///
+/// The connection is given back to the tasks when this is dropped.
+pub struct TakenOverConnection {
+    read: Option<(RawReadConnection, oneshot::Sender<RawReadConnection>)>,
+    write: Option<(RawWriteConnection, oneshot::Sender<RawWriteConnection>)>,
+    reader: RawConnectionReader,
+    initial_state: ConnectionProtocol,
+    state: ConnectionProtocol,
+    taken_over_state: Arc<Mutex<Option<ConnectionProtocol>>>,
+}
+
+impl TakenOverConnection {
+    /// The read half of the connection, which you can use to read raw packets
+    /// or change the compression and encryption.
+    pub fn read_conn(&mut self) -> &mut RawReadConnection {
+        &mut self.read.as_mut().expect("only taken when dropped").0
+    }
+    /// The write half of the connection, which you can use to write raw packets
+    /// or change the compression and encryption.
+    pub fn write_conn(&mut self) -> &mut RawWriteConnection {
+        &mut self.write.as_mut().expect("only taken when dropped").0
+    }
+
+    /// Read the next raw packet from the server. It won't be handled by the
+    /// client unless you pass it to [`Self::inject_raw_packet`].
+    pub async fn read(&mut self) -> Result<Box<[u8]>, Box<ReadPacketError>> {
+        self.read_conn().read().await
+    }
+
+    /// Write a raw packet to the server, bypassing the client's queue of
+    /// outgoing packets.
+    ///
+    /// If the packet makes the connection switch states, like
+    /// [`ServerboundConfigurationAcknowledged`] does, the client is switched to
+    /// the new state once the connection is given back.
+    pub async fn write(&mut self, raw_packet: &[u8]) -> std::io::Result<()> {
+        self.write_conn().write(raw_packet).await?;
+        self.state = state_after_writing(self.state, raw_packet);
+        Ok(())
+    }
+
+    /// The state that the connection is in, including any changes from
+    /// packets that were written with [`Self::write`].
+    pub fn state(&self) -> ConnectionProtocol {
+        self.state
+    }
+
+    /// Tell the client that the connection is now in a different state. This
+    /// is only necessary if the state was changed without using
+    /// [`Self::write`].
+    pub fn set_state(&mut self, state: ConnectionProtocol) {
+        self.state = state;
+    }
+
+    /// Let the client handle a packet as if it was received normally. This is
+    /// useful if you read a packet that you didn't mean to handle yourself.
+    pub fn inject_raw_packet(&self, raw_packet: Box<[u8]>) {
+        let _ = self.reader.inject_raw_packet(raw_packet);
+    }
+}
+
+impl Drop for TakenOverConnection {
+    /// Give the connection back to the read and write tasks.
+    fn drop(&mut self) {
+        if let Some((read_conn, read_return)) = self.read.take() {
+            let _ = read_return.send(read_conn);
+        }
+        if let Some((write_conn, write_return)) = self.write.take() {
+            let _ = write_return.send(write_conn);
+        }
+        if self.state != self.initial_state {
+            *self.taken_over_state.lock() = Some(self.state);
+            // run the schedule so it's applied before any more packets are
+            // handled
+            let _ = self.reader.run_schedule_sender.send(());
+        }
+    }
+}
+
+/// Get the state that the connection is in after we write the packet.
+fn state_after_writing(state: ConnectionProtocol, raw_packet: &[u8]) -> ConnectionProtocol {
+    let Ok(id) = u32::azalea_read_var(&mut Cursor::new(raw_packet)) else {
+        return state;
+    };
+    match state {
+        ConnectionProtocol::Game
+            if id == ServerboundConfigurationAcknowledged {}.into_variant().id() =>
+        {
+            ConnectionProtocol::Configuration
+        }
+        ConnectionProtocol::Configuration
+            if id == ServerboundFinishConfiguration.into_variant().id() =>
+        {
+            ConnectionProtocol::Game
+        }
+        _ => state,
+    }
+}
+
+/// Switch clients to the state that their connection was left in after it was
+/// taken over with [`RawConnection::take_over`].
+pub fn apply_taken_over_state(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RawConnection)>,
+) {
+    for (entity, mut raw_conn) in &mut query {
+        let Some(state) = raw_conn.taken_over_state.lock().take() else {
+            continue;
+        };
+        raw_conn.set_state(state);
+        match state {
+            ConnectionProtocol::Configuration => {
+                commands
+                    .entity(entity)
+                    .insert(InConfigState)
+                    .remove::<JoinedClientBundle>();
+            }
+            ConnectionProtocol::Game => {
+                commands
+                    .entity(entity)
+                    .remove::<InConfigState>()
+                    .insert(JoinedClientBundle::default());
+            }
+            _ => {}
+        }
+    }
 }
 
 impl RawConnectionReader {
     /// Loop that reads from the connection and adds the packets to the queue +
     /// runs the schedule.
-    pub async fn read_task(self, mut read_conn: RawReadConnection) {
+    pub async fn read_task(
+        self,
+        mut read_conn: RawReadConnection,
+        mut take_over_receiver: mpsc::UnboundedReceiver<TakeOverRequest<RawReadConnection>>,
+    ) {
         loop {
-            match read_conn.read().await {
+            // reading is cancel-safe since any partial packets are kept in the
+            // connection's buffer, so it's fine to stop in the middle of it
+            let result = tokio::select! {
+                biased;
+                Some(request) = take_over_receiver.recv() => {
+                    match hand_over(read_conn, request).await {
+                        Some(conn) => {
+                            read_conn = conn;
+                            continue;
+                        }
+                        // the connection was dropped while it was taken over
+                        None => break,
+                    }
+                }
+                result = read_conn.read() => result,
+            };
+
+            match result {
                 Ok(raw_packet) => {
-                    self.incoming_packet_queue.lock().push(raw_packet);
-                    // tell the client to run all the systems
-                    if self.run_schedule_sender.send(()).is_err() {
+                    if self.inject_raw_packet(raw_packet).is_err() {
                         // the client was dropped
                         break;
                     }
@@ -152,6 +543,12 @@ impl RawConnectionReader {
             }
         }
     }
+
+    /// Add a packet to the queue and tell the client to run all the systems.
+    pub fn inject_raw_packet(&self, raw_packet: Box<[u8]>) -> Result<(), SendError<()>> {
+        self.incoming_packet_queue.lock().push(raw_packet);
+        self.run_schedule_sender.send(())
+    }
 }
 
 impl RawConnectionWriter {
@@ -164,8 +561,28 @@ impl RawConnectionWriter {
         self,
         mut write_conn: RawWriteConnection,
         mut outgoing_packets_receiver: mpsc::UnboundedReceiver<Box<[u8]>>,
+        mut take_over_receiver: mpsc::UnboundedReceiver<TakeOverRequest<RawWriteConnection>>,
     ) {
-        while let Some(raw_packet) = outgoing_packets_receiver.recv().await {
+        loop {
+            let raw_packet = tokio::select! {
+                biased;
+                Some(request) = take_over_receiver.recv() => {
+                    // packets that are sent while the connection is taken over
+                    // stay in the queue until we get it back
+                    match hand_over(write_conn, request).await {
+                        Some(conn) => {
+                            write_conn = conn;
+                            continue;
+                        }
+                        None => break,
+                    }
+                }
+                raw_packet = outgoing_packets_receiver.recv() => raw_packet,
+            };
+            let Some(raw_packet) = raw_packet else {
+                break;
+            };
+
             if let Err(err) = write_conn.write(&raw_packet).await {
                 error!("Disconnecting because we couldn't write a packet: {err}.");
                 break;
@@ -175,6 +592,19 @@ impl RawConnectionWriter {
     }
 }
 
+/// Give a half of the connection to whoever sent the [`TakeOverRequest`], and
+/// wait for it to be returned.
+///
+/// Returns `None` if the connection was never returned.
+async fn hand_over<T>(conn: T, request: TakeOverRequest<T>) -> Option<T> {
+    let (return_sender, return_receiver) = oneshot::channel();
+    match request.send((conn, return_sender)) {
+        Ok(()) => return_receiver.await.ok(),
+        // nobody is waiting for it anymore
+        Err((conn, _)) => Some(conn),
+    }
+}
+
 impl Drop for RawConnection {
     /// Stop every active task when this `RawConnection` is dropped.
     fn drop(&mut self) {
@@ -182,3 +612,154 @@ impl Drop for RawConnection {
         self.write_packets_task.abort();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{
+        net::{TcpListener, TcpStream},
+        time::timeout,
+    };
+
+    use super::*;
+
+    fn raw_halves(stream: TcpStream) -> (RawReadConnection, RawWriteConnection) {
+        let (read_stream, write_stream) = stream.into_split();
+        (
+            RawReadConnection {
+                read_stream,
+                buffer: Cursor::new(Vec::new()),
+                compression_threshold: None,
+                dec_cipher: None,
+                read_timeout: None,
+            },
+            RawWriteConnection {
+                write_stream,
+                compression_threshold: None,
+                enc_cipher: None,
+                write_timeout: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_state_after_writing() {
+        let ack =
+            serialize_packet(&ServerboundConfigurationAcknowledged {}.into_variant()).unwrap();
+        let finish = serialize_packet(&ServerboundFinishConfiguration.into_variant()).unwrap();
+
+        assert_eq!(
+            state_after_writing(ConnectionProtocol::Game, &ack),
+            ConnectionProtocol::Configuration
+        );
+        assert_eq!(
+            state_after_writing(ConnectionProtocol::Configuration, &finish),
+            ConnectionProtocol::Game
+        );
+        // the ids only mean something in the right state
+        assert_eq!(
+            state_after_writing(ConnectionProtocol::Configuration, &ack),
+            ConnectionProtocol::Configuration
+        );
+        assert_eq!(
+            state_after_writing(ConnectionProtocol::Game, &[]),
+            ConnectionProtocol::Game
+        );
+    }
+
+    #[test]
+    fn test_take_over_connection() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let client_stream = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (server_stream, _) = listener.accept().await.unwrap();
+            let (mut server_read, mut server_write) = raw_halves(server_stream);
+
+            let (client_read, client_write) = raw_halves(client_stream);
+            let (run_schedule_sender, _run_schedule_receiver) = mpsc::unbounded_channel();
+            let raw_conn = RawConnection::new(
+                run_schedule_sender,
+                ConnectionProtocol::Game,
+                client_read,
+                client_write,
+            );
+
+            let mut taken_over = raw_conn.take_over().await.unwrap();
+
+            // packets that the client writes now have to wait
+            raw_conn
+                .write_raw_packet(vec![0x42, 1, 2].into_boxed_slice())
+                .unwrap();
+
+            let ack =
+                serialize_packet(&ServerboundConfigurationAcknowledged {}.into_variant()).unwrap();
+            taken_over.write(&ack).await.unwrap();
+            assert_eq!(server_read.read().await.unwrap(), ack);
+            assert_eq!(taken_over.state(), ConnectionProtocol::Configuration);
+
+            // packets that we read aren't handled by the client
+            server_write.write(&[0x10, 3]).await.unwrap();
+            assert_eq!(&*taken_over.read().await.unwrap(), &[0x10, 3]);
+            assert!(raw_conn.incoming_packet_queue().lock().is_empty());
+
+            drop(taken_over);
+
+            // the client got its connection back
+            let queued = timeout(Duration::from_secs(5), server_read.read())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(&*queued, &[0x42, 1, 2]);
+            server_write.write(&[0x11, 4]).await.unwrap();
+            timeout(Duration::from_secs(5), async {
+                while raw_conn.incoming_packet_queue().lock().is_empty() {
+                    tokio::task::yield_now().await;
+                }
+            })
+            .await
+            .unwrap();
+
+            assert_eq!(
+                *raw_conn.taken_over_state.lock(),
+                Some(ConnectionProtocol::Configuration)
+            );
+        });
+    }
+
+    #[test]
+    fn test_apply_taken_over_state() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+
+        let mut ecs = World::new();
+        let (run_schedule_sender, _run_schedule_receiver) = mpsc::unbounded_channel();
+        let (raw_conn, _outgoing) =
+            RawConnection::new_headless(run_schedule_sender, ConnectionProtocol::Game);
+        *raw_conn.taken_over_state.lock() = Some(ConnectionProtocol::Configuration);
+        let entity = ecs.spawn((raw_conn, JoinedClientBundle::default())).id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_taken_over_state);
+        schedule.run(&mut ecs);
+
+        assert!(ecs.get::<InConfigState>(entity).is_some());
+        assert!(ecs.get::<azalea_entity::LocalEntity>(entity).is_none());
+        let raw_conn = ecs.get::<RawConnection>(entity).unwrap();
+        assert_eq!(
+            raw_conn.connection_protocol,
+            ConnectionProtocol::Configuration
+        );
+        assert_eq!(*raw_conn.taken_over_state.lock(), None);
+
+        // and back again
+        let taken_over_state = raw_conn.taken_over_state.clone();
+        *taken_over_state.lock() = Some(ConnectionProtocol::Game);
+        schedule.run(&mut ecs);
+        assert!(ecs.get::<InConfigState>(entity).is_none());
+        assert!(ecs.get::<azalea_entity::LocalEntity>(entity).is_some());
+    }
+}
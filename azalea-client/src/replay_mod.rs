@@ -0,0 +1,705 @@
+//! Record what bots see into `.mcpr` files that can be watched with the
+//! [Replay Mod](https://www.replaymod.com/).
+//!
+//! ```no_run
+//! # use azalea_client::replay_mod::ReplayModRecorderPlugin;
+//! # fn example(app: &mut bevy_app::App) {
+//! app.add_plugins(ReplayModRecorderPlugin::new("replays"));
+//! # }
+//! ```
+//!
+//! Every client gets its own recording, which is named after its username and
+//! the time that it joined. The packets are written to a `.tmcpr` file next to
+//! it while the client is connected, and they're put into the `.mcpr` on a
+//! separate thread when it disconnects. If the program exits without
+//! disconnecting the client, the `.tmcpr` file is left behind.
+//!
+//! The Replay Mod normally adds the player that recorded the replay to it
+//! itself, so our own player won't be visible in the replays. Everything else
+//! that the server sent us will be.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use azalea_protocol::{
+    packets::{
+        login::{c_login_finished::ClientboundLoginFinished, ClientboundLoginPacket},
+        ConnectionProtocol, Packet, PROTOCOL_VERSION, VERSION_NAME,
+    },
+    write::serialize_packet,
+};
+use azalea_world::MinecraftEntityId;
+use bevy_app::{App, AppExit, First, Last, Plugin, Update};
+use bevy_ecs::prelude::*;
+use flate2::{write::DeflateEncoder, Compression, Crc};
+use parking_lot::Mutex;
+use tracing::error;
+
+use crate::{
+    disconnect::DisconnectEvent,
+    packet_handling::{configuration, game},
+    raw_connection::{PacketFlow, RawConnection},
+    GameProfileComponent,
+};
+
+/// A plugin that records a Replay Mod replay for every client.
+///
+/// This isn't part of the default plugins, so you have to add it yourself.
+#[derive(Clone)]
+pub struct ReplayModRecorderPlugin {
+    /// The directory that the replays are saved in. It's created if it doesn't
+    /// exist.
+    pub directory: PathBuf,
+}
+
+impl ReplayModRecorderPlugin {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl Plugin for ReplayModRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReplayDirectory(self.directory.clone()))
+            .init_resource::<ReplaySaveThreads>()
+            .add_systems(
+                First,
+                start_replay_recordings
+                    .before(game::send_packet_events)
+                    .before(configuration::send_packet_events),
+            )
+            .add_systems(
+                Update,
+                (update_replay_self_id, finish_replay_recordings).chain(),
+            )
+            .add_systems(Last, wait_for_replays_on_exit);
+    }
+}
+
+#[derive(Resource)]
+struct ReplayDirectory(PathBuf);
+
+/// The threads that are compressing replays into `.mcpr` files.
+///
+/// Compressing a long replay can take a while, so it's done on another thread
+/// instead of holding up the ECS. They're waited for when the app exits.
+#[derive(Resource, Clone, Default)]
+pub struct ReplaySaveThreads(Arc<Mutex<Vec<JoinHandle<()>>>>);
+
+impl ReplaySaveThreads {
+    /// Block until every replay that's being saved is done.
+    pub fn wait(&self) {
+        let threads = std::mem::take(&mut *self.0.lock());
+        for thread in threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A component for clients that are being recorded by the
+/// [`ReplayModRecorderPlugin`]. The replay is saved when this is removed.
+#[derive(Component)]
+pub struct ReplayModRecording {
+    writer: Arc<Mutex<Option<ReplayModWriter>>>,
+    save_threads: ReplaySaveThreads,
+}
+
+impl Drop for ReplayModRecording {
+    fn drop(&mut self) {
+        let Some(writer) = self.writer.lock().take() else {
+            return;
+        };
+        let thread = thread::Builder::new()
+            .name("replay-mod-save".to_owned())
+            .spawn(move || {
+                if let Err(e) = writer.finish() {
+                    error!("Couldn't save replay: {e}");
+                }
+            });
+        match thread {
+            Ok(thread) => {
+                let mut threads = self.save_threads.0.lock();
+                threads.retain(|thread| !thread.is_finished());
+                threads.push(thread);
+            }
+            Err(e) => error!("Couldn't start a thread for saving the replay: {e}"),
+        }
+    }
+}
+
+/// Writes a replay in the Replay Mod's format.
+///
+/// The packets are written to a temporary `.tmcpr` file as they come in, and
+/// [`Self::finish`] puts them into the `.mcpr` file with the metadata.
+pub struct ReplayModWriter {
+    path: PathBuf,
+    packets_path: PathBuf,
+    packets: BufWriter<File>,
+    start: Instant,
+    date: u128,
+    duration: u32,
+    /// Our entity id, which the Replay Mod uses to know who recorded the
+    /// replay.
+    pub self_id: i32,
+}
+
+impl ReplayModWriter {
+    /// Start a replay that will be saved at the given path, which should end
+    /// with `.mcpr`.
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let packets_path = path.with_extension("tmcpr");
+        let packets = BufWriter::new(File::create(&packets_path)?);
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        Ok(Self {
+            path,
+            packets_path,
+            packets,
+            start: Instant::now(),
+            date,
+            duration: 0,
+            self_id: -1,
+        })
+    }
+
+    /// Record a clientbound packet that was received just now. The packet
+    /// should start with its id.
+    pub fn write_packet(&mut self, raw_packet: &[u8]) -> io::Result<()> {
+        self.duration = self.start.elapsed().as_millis() as u32;
+        self.packets.write_all(&self.duration.to_be_bytes())?;
+        self.packets
+            .write_all(&(raw_packet.len() as u32).to_be_bytes())?;
+        self.packets.write_all(raw_packet)
+    }
+
+    /// Write the `.mcpr` file and delete the `.tmcpr` file.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.packets.flush()?;
+
+        let metadata = serde_json::json!({
+            "singleplayer": false,
+            "serverName": "",
+            "duration": self.duration,
+            "date": self.date as u64,
+            "mcversion": VERSION_NAME,
+            "fileFormat": "MCPR",
+            "fileFormatVersion": 14,
+            "protocol": PROTOCOL_VERSION,
+            "generator": "azalea",
+            "selfId": self.self_id,
+            "players": [],
+        })
+        .to_string();
+
+        let mut zip = ZipWriter::new(BufWriter::new(File::create(&self.path)?));
+        zip.add_file(
+            "recording.tmcpr",
+            &mut BufReader::new(File::open(&self.packets_path)?),
+        )?;
+        zip.add_file("metaData.json", &mut metadata.as_bytes())?;
+        zip.finish()?;
+
+        fs::remove_file(&self.packets_path)
+    }
+}
+
+fn start_replay_recordings(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RawConnection, &GameProfileComponent), Added<RawConnection>>,
+    directory: Res<ReplayDirectory>,
+    save_threads: Res<ReplaySaveThreads>,
+) {
+    for (entity, mut raw_connection, game_profile) in &mut query {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = directory
+            .0
+            .join(format!("{}_{time}.mcpr", game_profile.name));
+        let writer = match start_replay(&path, game_profile) {
+            Ok(writer) => writer,
+            Err(e) => {
+                error!("Couldn't start replay at {}: {e}", path.display());
+                continue;
+            }
+        };
+
+        let writer = Arc::new(Mutex::new(Some(writer)));
+        let listener_writer = writer.clone();
+        raw_connection
+            .packet_listeners
+            .push(Arc::new(move |flow, state, raw_packet| {
+                // the replay mod only needs what the server sent us
+                if flow == PacketFlow::Serverbound || state == ConnectionProtocol::Login {
+                    return;
+                }
+                if let Some(writer) = &mut *listener_writer.lock() {
+                    if let Err(e) = writer.write_packet(raw_packet) {
+                        error!("Couldn't write to replay: {e}");
+                    }
+                }
+            }));
+        commands.entity(entity).insert(ReplayModRecording {
+            writer,
+            save_threads: save_threads.clone(),
+        });
+    }
+}
+
+fn start_replay(path: &Path, game_profile: &GameProfileComponent) -> io::Result<ReplayModWriter> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let mut writer = ReplayModWriter::new(path)?;
+
+    // we're already past the login state by the time we can listen to
+    // packets, but replays have to start with this
+    let login_finished: ClientboundLoginPacket = ClientboundLoginFinished {
+        game_profile: game_profile.0.clone(),
+    }
+    .into_variant();
+    let raw_packet =
+        serialize_packet(&login_finished).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    writer.write_packet(&raw_packet)?;
+    Ok(writer)
+}
+
+fn update_replay_self_id(
+    query: Query<(&ReplayModRecording, &MinecraftEntityId), Changed<MinecraftEntityId>>,
+) {
+    for (recording, entity_id) in &query {
+        if let Some(writer) = &mut *recording.writer.lock() {
+            writer.self_id = entity_id.0 as i32;
+        }
+    }
+}
+
+fn finish_replay_recordings(
+    mut commands: Commands,
+    mut disconnect_events: EventReader<DisconnectEvent>,
+) {
+    for event in disconnect_events.read() {
+        if let Some(mut entity) = commands.get_entity(event.entity) {
+            entity.remove::<ReplayModRecording>();
+        }
+    }
+}
+
+fn wait_for_replays_on_exit(
+    mut commands: Commands,
+    mut exit_events: EventReader<AppExit>,
+    query: Query<Entity, With<ReplayModRecording>>,
+    save_threads: Res<ReplaySaveThreads>,
+) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    // the recordings are saved when they're dropped, so they have to be
+    // removed before we wait
+    for entity in &query {
+        commands.entity(entity).remove::<ReplayModRecording>();
+    }
+    let save_threads = save_threads.clone();
+    commands.queue(move |_: &mut World| save_threads.wait());
+}
+
+/// Just enough of a zip file writer for making `.mcpr` files.
+///
+/// Long recordings can be bigger than 4 GiB, so files always have a ZIP64
+/// extra field in their local header, and the central directory only uses
+/// ZIP64 when it has to.
+struct ZipWriter<W: Write + Seek> {
+    writer: W,
+    central_directory: Vec<u8>,
+    entries: u64,
+}
+
+/// The value that's put in a zip field when the real value is in the ZIP64
+/// extra field.
+const ZIP64_MARKER: u32 = u32::MAX;
+/// The version needed to extract files that use ZIP64.
+const ZIP64_VERSION: u16 = 45;
+
+impl<W: Write + Seek> ZipWriter<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            central_directory: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    fn add_file(&mut self, name: &str, contents: &mut impl Read) -> io::Result<()> {
+        let header_offset = self.writer.stream_position()?;
+
+        // the crc and sizes are filled in after the file is compressed
+        let mut header = Vec::new();
+        header.extend(0x04034b50u32.to_le_bytes());
+        header.extend(file_header_fields(0, ZIP64_MARKER, ZIP64_MARKER));
+        header.extend((name.len() as u16).to_le_bytes());
+        // extra field length
+        header.extend(20u16.to_le_bytes());
+        header.extend(name.as_bytes());
+        let zip64_sizes_offset = header_offset + header.len() as u64 + 4;
+        header.extend(zip64_extra_field(&[0, 0]));
+        self.writer.write_all(&header)?;
+
+        let data_offset = self.writer.stream_position()?;
+        let mut crc = Crc::new();
+        let mut uncompressed_size = 0u64;
+        let mut encoder = DeflateEncoder::new(&mut self.writer, Compression::default());
+        let mut buf = [0; 8192];
+        loop {
+            let n = contents.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            crc.update(&buf[..n]);
+            uncompressed_size += n as u64;
+            encoder.write_all(&buf[..n])?;
+        }
+        encoder.finish()?;
+        let end_offset = self.writer.stream_position()?;
+        let compressed_size = end_offset - data_offset;
+
+        // the crc is right after the version, flags, method, time, and date
+        self.writer.seek(SeekFrom::Start(header_offset + 14))?;
+        self.writer.write_all(&crc.sum().to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(zip64_sizes_offset))?;
+        self.writer.write_all(&uncompressed_size.to_le_bytes())?;
+        self.writer.write_all(&compressed_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end_offset))?;
+
+        // the central directory only has the ZIP64 fields that don't fit
+        let mut zip64_fields = Vec::new();
+        let mut fit = |value: u64| match u32::try_from(value) {
+            Ok(value) if value != ZIP64_MARKER => value,
+            _ => {
+                zip64_fields.push(value);
+                ZIP64_MARKER
+            }
+        };
+        let uncompressed_size = fit(uncompressed_size);
+        let compressed_size = fit(compressed_size);
+        let header_offset = fit(header_offset);
+        let extra_field = if zip64_fields.is_empty() {
+            Vec::new()
+        } else {
+            zip64_extra_field(&zip64_fields)
+        };
+
+        let directory = &mut self.central_directory;
+        directory.extend(0x02014b50u32.to_le_bytes());
+        // version made by
+        directory.extend(ZIP64_VERSION.to_le_bytes());
+        directory.extend(file_header_fields(
+            crc.sum(),
+            compressed_size,
+            uncompressed_size,
+        ));
+        directory.extend((name.len() as u16).to_le_bytes());
+        directory.extend((extra_field.len() as u16).to_le_bytes());
+        // comment, disk number, and internal and external attributes
+        directory.extend([0; 10]);
+        directory.extend(header_offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+        directory.extend(extra_field);
+        self.entries += 1;
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        let directory_offset = self.writer.stream_position()?;
+        let directory_size = self.central_directory.len() as u64;
+        self.writer.write_all(&self.central_directory)?;
+
+        let entries = u16::try_from(self.entries).ok().filter(|&n| n != u16::MAX);
+        let directory_size_32 = u32::try_from(directory_size)
+            .ok()
+            .filter(|&n| n != ZIP64_MARKER);
+        let directory_offset_32 = u32::try_from(directory_offset)
+            .ok()
+            .filter(|&n| n != ZIP64_MARKER);
+
+        let mut end = Vec::new();
+        if entries.is_none() || directory_size_32.is_none() || directory_offset_32.is_none() {
+            let zip64_end_offset = self.writer.stream_position()?;
+            // zip64 end of central directory record
+            end.extend(0x06064b50u32.to_le_bytes());
+            // size of the rest of the record
+            end.extend(44u64.to_le_bytes());
+            // version made by and version needed
+            end.extend(ZIP64_VERSION.to_le_bytes());
+            end.extend(ZIP64_VERSION.to_le_bytes());
+            // disk numbers
+            end.extend([0; 8]);
+            end.extend(self.entries.to_le_bytes());
+            end.extend(self.entries.to_le_bytes());
+            end.extend(directory_size.to_le_bytes());
+            end.extend(directory_offset.to_le_bytes());
+
+            // zip64 end of central directory locator
+            end.extend(0x07064b50u32.to_le_bytes());
+            end.extend(0u32.to_le_bytes());
+            end.extend(zip64_end_offset.to_le_bytes());
+            // total number of disks
+            end.extend(1u32.to_le_bytes());
+        }
+
+        let entries = entries.unwrap_or(u16::MAX);
+        end.extend(0x06054b50u32.to_le_bytes());
+        // disk numbers
+        end.extend([0; 4]);
+        end.extend(entries.to_le_bytes());
+        end.extend(entries.to_le_bytes());
+        end.extend(directory_size_32.unwrap_or(ZIP64_MARKER).to_le_bytes());
+        end.extend(directory_offset_32.unwrap_or(ZIP64_MARKER).to_le_bytes());
+        // comment length
+        end.extend(0u16.to_le_bytes());
+        self.writer.write_all(&end)?;
+        self.writer.flush()
+    }
+}
+
+/// The fields that are the same in the local file header and the central
+/// directory, from the version needed to extract up to the uncompressed size.
+fn file_header_fields(crc: u32, compressed_size: u32, uncompressed_size: u32) -> Vec<u8> {
+    let mut fields = Vec::new();
+    fields.extend(ZIP64_VERSION.to_le_bytes());
+    // flags
+    fields.extend(0u16.to_le_bytes());
+    // deflate
+    fields.extend(8u16.to_le_bytes());
+    // modification time and date, which is 1980-01-01 00:00
+    fields.extend(0u16.to_le_bytes());
+    fields.extend(0x21u16.to_le_bytes());
+    fields.extend(crc.to_le_bytes());
+    fields.extend(compressed_size.to_le_bytes());
+    fields.extend(uncompressed_size.to_le_bytes());
+    fields
+}
+
+/// A ZIP64 extended information extra field with the given values, which have
+/// to be in the order that the spec says.
+fn zip64_extra_field(values: &[u64]) -> Vec<u8> {
+    let mut field = Vec::new();
+    field.extend(0x0001u16.to_le_bytes());
+    field.extend(((values.len() * 8) as u16).to_le_bytes());
+    for value in values {
+        field.extend(value.to_le_bytes());
+    }
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Cursor};
+
+    use flate2::read::DeflateDecoder;
+
+    use super::*;
+
+    /// A writer that acts like it starts at `offset`, so we can test what
+    /// happens past 4 GiB without writing that much.
+    struct OffsetCursor {
+        offset: u64,
+        inner: Cursor<Vec<u8>>,
+    }
+    impl Write for OffsetCursor {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.inner.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    impl Seek for OffsetCursor {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            let pos = match pos {
+                SeekFrom::Start(pos) => SeekFrom::Start(pos - self.offset),
+                pos => pos,
+            };
+            Ok(self.inner.seek(pos)? + self.offset)
+        }
+    }
+
+    fn u16_at(data: &[u8], i: usize) -> u16 {
+        u16::from_le_bytes(data[i..i + 2].try_into().unwrap())
+    }
+    fn u32_at(data: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes(data[i..i + 4].try_into().unwrap())
+    }
+    fn u64_at(data: &[u8], i: usize) -> u64 {
+        u64::from_le_bytes(data[i..i + 8].try_into().unwrap())
+    }
+
+    /// Read every file in a zip that was written starting at `offset`,
+    /// following the ZIP64 fields like other zip readers do.
+    fn read_zip(data: &[u8], offset: u64) -> HashMap<String, Vec<u8>> {
+        let at = |position: u64| (position - offset) as usize;
+
+        let end = data.len() - 22;
+        assert_eq!(u32_at(data, end), 0x06054b50);
+        let mut entries = u16_at(data, end + 10) as u64;
+        let mut directory_offset = u32_at(data, end + 16) as u64;
+        if entries == u16::MAX as u64 || directory_offset == ZIP64_MARKER as u64 {
+            let locator = end - 20;
+            assert_eq!(u32_at(data, locator), 0x07064b50);
+            let zip64_end = at(u64_at(data, locator + 8));
+            assert_eq!(u32_at(data, zip64_end), 0x06064b50);
+            entries = u64_at(data, zip64_end + 32);
+            directory_offset = u64_at(data, zip64_end + 48);
+        }
+
+        let mut files = HashMap::new();
+        let mut i = at(directory_offset);
+        for _ in 0..entries {
+            assert_eq!(u32_at(data, i), 0x02014b50);
+            let crc = u32_at(data, i + 16);
+            let mut compressed_size = u32_at(data, i + 20) as u64;
+            let mut uncompressed_size = u32_at(data, i + 24) as u64;
+            let name_length = u16_at(data, i + 28) as usize;
+            let extra_length = u16_at(data, i + 30) as usize;
+            let mut header_offset = u32_at(data, i + 42) as u64;
+            let name = String::from_utf8(data[i + 46..i + 46 + name_length].to_vec()).unwrap();
+
+            let extra = &data[i + 46 + name_length..i + 46 + name_length + extra_length];
+            let mut zip64_values =
+                (0..extra.len().saturating_sub(4) / 8).map(|j| u64_at(extra, 4 + j * 8));
+            if uncompressed_size == ZIP64_MARKER as u64 {
+                uncompressed_size = zip64_values.next().unwrap();
+            }
+            if compressed_size == ZIP64_MARKER as u64 {
+                compressed_size = zip64_values.next().unwrap();
+            }
+            if header_offset == ZIP64_MARKER as u64 {
+                header_offset = zip64_values.next().unwrap();
+            }
+
+            let header = at(header_offset);
+            assert_eq!(u32_at(data, header), 0x04034b50);
+            assert_eq!(u32_at(data, header + 14), crc);
+            // the local header always has the sizes in its ZIP64 field
+            let local_extra = header + 30 + u16_at(data, header + 26) as usize;
+            assert_eq!(u16_at(data, local_extra), 0x0001);
+            assert_eq!(u64_at(data, local_extra + 4), uncompressed_size);
+            assert_eq!(u64_at(data, local_extra + 12), compressed_size);
+
+            let start = local_extra + u16_at(data, header + 28) as usize;
+            let compressed = &data[start..start + compressed_size as usize];
+            let mut contents = Vec::new();
+            DeflateDecoder::new(compressed)
+                .read_to_end(&mut contents)
+                .unwrap();
+            assert_eq!(contents.len() as u64, uncompressed_size);
+            let mut actual_crc = Crc::new();
+            actual_crc.update(&contents);
+            assert_eq!(actual_crc.sum(), crc);
+
+            files.insert(name, contents);
+            i += 46 + name_length + extra_length;
+        }
+        files
+    }
+
+    fn write_zip(offset: u64, files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut cursor = OffsetCursor {
+            offset,
+            inner: Cursor::new(Vec::new()),
+        };
+        let mut zip = ZipWriter::new(&mut cursor);
+        for &(name, contents) in files {
+            let mut contents = contents;
+            zip.add_file(name, &mut contents).unwrap();
+        }
+        zip.finish().unwrap();
+        cursor.inner.into_inner()
+    }
+
+    #[test]
+    fn test_zip_round_trip() {
+        let big = (0..100_000u32)
+            .flat_map(u32::to_le_bytes)
+            .collect::<Vec<_>>();
+        let data = write_zip(0, &[("a.txt", b"hello"), ("big.bin", &big), ("empty", b"")]);
+        let files = read_zip(&data, 0);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files["a.txt"], b"hello");
+        assert_eq!(files["big.bin"], big);
+        assert_eq!(files["empty"], b"");
+        // small zips don't need the ZIP64 end of central directory
+        assert_ne!(u32_at(&data, data.len() - 42), 0x07064b50);
+    }
+
+    #[test]
+    fn test_zip64_offsets() {
+        let offset = 5 << 30;
+        let data = write_zip(offset, &[("a.txt", b"hello"), ("b.txt", b"world")]);
+        assert_eq!(u32_at(&data, data.len() - 42), 0x07064b50);
+        let files = read_zip(&data, offset);
+        assert_eq!(files["a.txt"], b"hello");
+        assert_eq!(files["b.txt"], b"world");
+    }
+
+    #[test]
+    fn test_replay_file() {
+        let directory =
+            std::env::temp_dir().join(format!("azalea-replay-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("bot.mcpr");
+
+        let mut writer = ReplayModWriter::new(&path).unwrap();
+        writer.self_id = 7;
+        writer.write_packet(&[1, 2, 3]).unwrap();
+        writer.write_packet(&[4]).unwrap();
+        writer.finish().unwrap();
+        assert!(!path.with_extension("tmcpr").exists());
+
+        let files = read_zip(&fs::read(&path).unwrap(), 0);
+        let recording = &files["recording.tmcpr"];
+        // each packet is the time and length as big endian u32s, and then the
+        // packet
+        assert_eq!(recording.len(), (8 + 3) + (8 + 1));
+        assert_eq!(&recording[4..8], &3u32.to_be_bytes());
+        assert_eq!(&recording[8..11], &[1, 2, 3]);
+        assert_eq!(&recording[15..20], &[0, 0, 0, 1, 4]);
+
+        let metadata: serde_json::Value = serde_json::from_slice(&files["metaData.json"]).unwrap();
+        assert_eq!(metadata["selfId"], 7);
+        assert_eq!(metadata["protocol"], PROTOCOL_VERSION);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_dropping_a_recording_saves_it_in_the_background() {
+        let directory =
+            std::env::temp_dir().join(format!("azalea-replay-drop-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("bot.mcpr");
+
+        let save_threads = ReplaySaveThreads::default();
+        drop(ReplayModRecording {
+            writer: Arc::new(Mutex::new(Some(ReplayModWriter::new(&path).unwrap()))),
+            save_threads: save_threads.clone(),
+        });
+        save_threads.wait();
+        assert!(path.exists());
+        assert!(!path.with_extension("tmcpr").exists());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
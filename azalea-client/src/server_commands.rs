@@ -0,0 +1,523 @@
+//! The commands that the server told us we can run.
+//!
+//! Servers send the tree of commands that the player has permission to use in
+//! the [`ClientboundCommands`] packet. Vanilla uses it for highlighting and
+//! auto-completing commands, and bots can use it to check that a command exists
+//! before sending it.
+
+use azalea_protocol::packets::game::{
+    c_commands::{
+        BrigadierNodeStub, BrigadierParser, BrigadierString, ClientboundCommands, NodeType,
+    },
+    ClientboundGamePacket,
+};
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    packet_handling::game::{process_packet_events, PacketEvent},
+    Client,
+};
+
+pub struct ServerCommandsPlugin;
+impl Plugin for ServerCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            update_server_commands.before(process_packet_events),
+        );
+    }
+}
+
+/// The tree of commands that the server says we can run.
+///
+/// This is updated every time we get a [`ClientboundCommands`] packet, which
+/// usually happens when we join and when our permission level changes.
+#[derive(Component, Clone, Debug)]
+pub struct ServerCommands {
+    pub nodes: Vec<BrigadierNodeStub>,
+    pub root_index: u32,
+}
+
+/// A reference to a node in [`ServerCommands`].
+///
+/// The indexes in the tree come from the server, so they're checked when the
+/// node is created and nodes that would be out of bounds are skipped.
+#[derive(Clone, Copy, Debug)]
+pub struct ServerCommandNode<'a> {
+    commands: &'a ServerCommands,
+    stub: &'a BrigadierNodeStub,
+}
+
+impl ServerCommands {
+    pub fn new(packet: &ClientboundCommands) -> Self {
+        Self {
+            nodes: packet.entries.clone(),
+            root_index: packet.root_index,
+        }
+    }
+
+    /// The root of the command tree. Its children are the names of every
+    /// command.
+    ///
+    /// This is only `None` if the server sent an invalid root index.
+    pub fn root(&self) -> Option<ServerCommandNode<'_>> {
+        self.node(self.root_index)
+    }
+
+    /// Get the node at the given index, or `None` if it's out of bounds.
+    pub fn node(&self, index: u32) -> Option<ServerCommandNode<'_>> {
+        Some(ServerCommandNode {
+            commands: self,
+            stub: self.nodes.get(index as usize)?,
+        })
+    }
+
+    /// Whether the command exists and has all of the arguments that it needs.
+    /// The leading slash is optional.
+    ///
+    /// Arguments are checked as well as we can without knowing about the
+    /// world, so numbers have to be in range but things like entity selectors
+    /// and block states are accepted as long as they're there.
+    ///
+    /// ```
+    /// # use azalea_client::server_commands::ServerCommands;
+    /// # fn example(commands: &ServerCommands) {
+    /// if commands.is_valid("/home set") {
+    ///     // ...
+    /// }
+    /// # }
+    /// ```
+    pub fn is_valid(&self, command: &str) -> bool {
+        let Some(root) = self.root() else {
+            return false;
+        };
+        let mut nodes = Vec::new();
+        reachable_nodes(root, &split_command(command), &mut nodes);
+        nodes.iter().any(|node| node.is_executable())
+    }
+
+    /// Get the node that the given command ends at, which is useful for
+    /// checking what arguments it takes. Returns `None` if the command doesn't
+    /// exist.
+    pub fn find(&self, command: &str) -> Option<ServerCommandNode<'_>> {
+        let mut nodes = Vec::new();
+        reachable_nodes(self.root()?, &split_command(command), &mut nodes);
+        nodes.first().copied()
+    }
+
+    /// Get the possible literal words that could come next in the command.
+    ///
+    /// If the command ends in the middle of a word, only the words that start
+    /// with what was typed are returned. Suggestions for arguments come from
+    /// the server, so they aren't included.
+    pub fn completions(&self, command: &str) -> Vec<String> {
+        let command = command.trim_start();
+        let command = command.strip_prefix('/').unwrap_or(command);
+        let (complete, partial) = if command.is_empty() || command.ends_with(char::is_whitespace) {
+            (command, "")
+        } else {
+            match command.rsplit_once(char::is_whitespace) {
+                Some((complete, partial)) => (complete, partial),
+                None => ("", command),
+            }
+        };
+
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+        let mut nodes = Vec::new();
+        reachable_nodes(root, &split_command(complete), &mut nodes);
+
+        let mut completions = Vec::new();
+        for node in nodes {
+            for child in node.children() {
+                if let NodeType::Literal { name } = &child.stub().node_type {
+                    if name.starts_with(partial) {
+                        completions.push(name.clone());
+                    }
+                }
+            }
+        }
+        completions.sort();
+        completions.dedup();
+        completions
+    }
+
+    /// Get the `message` arguments in the command, which have to be signed
+    /// when the command is sent. The name of each argument is returned along
+    /// with its value.
+    pub fn signed_arguments<'a>(&self, command: &'a str) -> Vec<(String, &'a str)> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+        let words = split_command(command);
+        let mut path = Vec::new();
+        if !find_path(root, &words, 0, &mut path) {
+            return Vec::new();
+        }
+
+        let mut arguments = Vec::new();
+        for (node, first_word) in path {
+            if let (Some(name), Some(BrigadierParser::Message)) = (node.name(), node.parser()) {
+                // the words are slices of the command, so this is where the argument starts
+                let start = words[first_word].as_ptr() as usize - command.as_ptr() as usize;
+                arguments.push((name.to_owned(), command[start..].trim_end()));
+            }
+        }
+        arguments
+    }
+}
+
+impl<'a> ServerCommandNode<'a> {
+    /// The data that the server sent for this node.
+    pub fn stub(&self) -> &'a BrigadierNodeStub {
+        self.stub
+    }
+
+    /// The name of the literal or argument, or `None` if this is the root.
+    pub fn name(&self) -> Option<&'a str> {
+        self.stub().name()
+    }
+
+    /// The parser for this node if it's an argument.
+    pub fn parser(&self) -> Option<&'a BrigadierParser> {
+        match &self.stub().node_type {
+            NodeType::Argument { parser, .. } => Some(parser),
+            _ => None,
+        }
+    }
+
+    /// Whether the command can be run if it ends at this node.
+    pub fn is_executable(&self) -> bool {
+        self.stub().is_executable
+    }
+
+    /// The nodes that can come after this one. If this node redirects to
+    /// another one (like `/execute as <targets>` going back to `/execute`),
+    /// these are the children of that node instead.
+    pub fn children(&self) -> impl Iterator<Item = ServerCommandNode<'a>> + 'a {
+        let commands = self.commands;
+        let stub = match self.stub.redirect_node {
+            Some(redirect) => commands.nodes.get(redirect as usize).unwrap_or(self.stub),
+            None => self.stub,
+        };
+        stub.children
+            .iter()
+            .filter_map(move |&index| commands.node(index))
+    }
+
+    /// Get the literal or argument child with the given name.
+    pub fn child(&self, name: &str) -> Option<ServerCommandNode<'a>> {
+        self.children().find(|child| child.name() == Some(name))
+    }
+}
+
+/// Find a way through the tree that reads all the words and ends at a node
+/// that can be executed. The nodes are added to `path` along with the index of
+/// the first word that they read.
+fn find_path<'a>(
+    node: ServerCommandNode<'a>,
+    words: &[&str],
+    first_word: usize,
+    path: &mut Vec<(ServerCommandNode<'a>, usize)>,
+) -> bool {
+    if first_word == words.len() {
+        return node.is_executable();
+    }
+    let remaining = &words[first_word..];
+    for child in node.children() {
+        let Some(word_count) = words_read_by(child, remaining) else {
+            continue;
+        };
+        path.push((child, first_word));
+        if find_path(child, words, first_word + word_count, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Add every node that we could end up at after reading all the words,
+/// starting from the given node.
+fn reachable_nodes<'a>(
+    node: ServerCommandNode<'a>,
+    words: &[&str],
+    out: &mut Vec<ServerCommandNode<'a>>,
+) {
+    if words.is_empty() {
+        out.push(node);
+        return;
+    }
+    for child in node.children() {
+        if let Some(word_count) = words_read_by(child, words) {
+            reachable_nodes(child, &words[word_count..], out);
+        }
+    }
+}
+
+/// The number of words that the node would read from the start of `words`, or
+/// `None` if it doesn't accept them. `words` can't be empty.
+fn words_read_by(node: ServerCommandNode<'_>, words: &[&str]) -> Option<usize> {
+    match &node.stub.node_type {
+        NodeType::Root => None,
+        NodeType::Literal { name } => (name == words[0]).then_some(1),
+        NodeType::Argument { parser, .. } => {
+            let word_count = match argument_width(parser) {
+                ArgumentWidth::Words(n) => n,
+                ArgumentWidth::Greedy => words.len(),
+            };
+            (words.len() >= word_count && parser_accepts(parser, &words[..word_count]))
+                .then_some(word_count)
+        }
+    }
+}
+
+enum ArgumentWidth {
+    Words(usize),
+    /// The argument takes the rest of the command.
+    Greedy,
+}
+
+fn argument_width(parser: &BrigadierParser) -> ArgumentWidth {
+    match parser {
+        BrigadierParser::String(BrigadierString::GreedyPhrase) | BrigadierParser::Message => {
+            ArgumentWidth::Greedy
+        }
+        BrigadierParser::BlockPos | BrigadierParser::Vec3 => ArgumentWidth::Words(3),
+        BrigadierParser::ColumnPos | BrigadierParser::Vec2 | BrigadierParser::Rotation => {
+            ArgumentWidth::Words(2)
+        }
+        _ => ArgumentWidth::Words(1),
+    }
+}
+
+fn parser_accepts(parser: &BrigadierParser, words: &[&str]) -> bool {
+    fn in_range<T: PartialOrd>(value: T, min: Option<T>, max: Option<T>) -> bool {
+        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+    }
+
+    match parser {
+        BrigadierParser::Bool => matches!(words[0], "true" | "false"),
+        BrigadierParser::Integer(range) => words[0]
+            .parse::<i32>()
+            .is_ok_and(|value| in_range(value, range.min, range.max)),
+        BrigadierParser::Long(range) => words[0]
+            .parse::<i64>()
+            .is_ok_and(|value| in_range(value, range.min, range.max)),
+        BrigadierParser::Float(range) => words[0]
+            .parse::<f32>()
+            .is_ok_and(|value| in_range(value, range.min, range.max)),
+        BrigadierParser::Double(range) => words[0]
+            .parse::<f64>()
+            .is_ok_and(|value| in_range(value, range.min, range.max)),
+        BrigadierParser::BlockPos
+        | BrigadierParser::Vec3
+        | BrigadierParser::ColumnPos
+        | BrigadierParser::Vec2
+        | BrigadierParser::Rotation => words.iter().all(|word| is_coordinate(word)),
+        _ => true,
+    }
+}
+
+/// Whether the word is a number or a relative (`~`) or local (`^`) coordinate.
+fn is_coordinate(word: &str) -> bool {
+    let number = word
+        .strip_prefix('~')
+        .or_else(|| word.strip_prefix('^'))
+        .unwrap_or(word);
+    (number.is_empty() && word.len() == 1) || number.parse::<f64>().is_ok()
+}
+
+/// Split a command into words, without splitting inside of quotes or brackets
+/// (so entity selectors like `@e[type=cow, limit=1]` and NBT are one word).
+fn split_command(command: &str) -> Vec<&str> {
+    let command = command.trim();
+    let command = command.strip_prefix('/').unwrap_or(command);
+
+    let mut words = Vec::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = None;
+    for (i, c) in command.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' | '{' if !in_quotes => depth += 1,
+            ']' | '}' if !in_quotes && depth > 0 => depth -= 1,
+            c if c.is_whitespace() && !in_quotes && depth == 0 => {
+                if let Some(start) = start.take() {
+                    words.push(&command[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(start) = start {
+        words.push(&command[start..]);
+    }
+    words
+}
+
+pub fn update_server_commands(mut commands: Commands, mut events: EventReader<PacketEvent>) {
+    for event in events.read() {
+        if let ClientboundGamePacket::Commands(p) = event.packet.as_ref() {
+            commands.entity(event.entity).insert(ServerCommands::new(p));
+        }
+    }
+}
+
+impl Client {
+    /// Get the commands that the server says we can run, or `None` if the
+    /// server hasn't sent them yet.
+    pub fn server_commands(&self) -> Option<ServerCommands> {
+        self.get_component::<ServerCommands>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::c_commands::BrigadierNumber;
+
+    use super::*;
+
+    fn literal(name: &str, children: Vec<u32>, is_executable: bool) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            is_executable,
+            children,
+            redirect_node: None,
+            node_type: NodeType::Literal {
+                name: name.to_owned(),
+            },
+        }
+    }
+    fn argument(
+        name: &str,
+        parser: BrigadierParser,
+        children: Vec<u32>,
+        is_executable: bool,
+    ) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            is_executable,
+            children,
+            redirect_node: None,
+            node_type: NodeType::Argument {
+                name: name.to_owned(),
+                parser,
+                suggestions_type: None,
+            },
+        }
+    }
+
+    fn server_commands() -> ServerCommands {
+        ServerCommands::new(&ClientboundCommands {
+            entries: vec![
+                // 0
+                BrigadierNodeStub {
+                    is_executable: false,
+                    children: vec![1, 3, 5, 8, 9],
+                    redirect_node: None,
+                    node_type: NodeType::Root,
+                },
+                // 1
+                literal("home", vec![2], true),
+                // 2
+                literal("set", vec![], true),
+                // 3
+                literal("tp", vec![4], false),
+                // 4
+                argument("destination", BrigadierParser::BlockPos, vec![], true),
+                // 5
+                literal("msg", vec![6], false),
+                // 6
+                argument("targets", BrigadierParser::GameProfile, vec![7], false),
+                // 7
+                argument("message", BrigadierParser::Message, vec![], true),
+                // 8
+                literal("give", vec![10], false),
+                // 9: the server sent indexes that don't exist
+                BrigadierNodeStub {
+                    redirect_node: Some(1000),
+                    ..literal("broken", vec![100], true)
+                },
+                // 10
+                argument(
+                    "count",
+                    BrigadierParser::Integer(BrigadierNumber::new(Some(1), Some(64))),
+                    vec![],
+                    true,
+                ),
+            ],
+            root_index: 0,
+        })
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let commands = server_commands();
+        assert!(commands.is_valid("/home"));
+        assert!(commands.is_valid("home set"));
+        assert!(!commands.is_valid("/home sett"));
+        assert!(!commands.is_valid("/tp"));
+        assert!(commands.is_valid("/tp 1 ~2 ^"));
+        assert!(!commands.is_valid("/tp 1 2 x"));
+        assert!(commands.is_valid("/msg @a[name=\"a b\"] hello there"));
+        assert!(!commands.is_valid("/msg bot"));
+        assert!(commands.is_valid("/give 64"));
+        assert!(!commands.is_valid("/give 65"));
+        assert!(!commands.is_valid("/nonexistent"));
+    }
+
+    #[test]
+    fn test_completions() {
+        let commands = server_commands();
+        assert_eq!(
+            commands.completions("/"),
+            ["broken", "give", "home", "msg", "tp"]
+        );
+        assert_eq!(commands.completions("/h"), ["home"]);
+        assert_eq!(commands.completions("/home "), ["set"]);
+        assert!(commands.completions("/tp ").is_empty());
+    }
+
+    #[test]
+    fn test_signed_arguments() {
+        let commands = server_commands();
+        assert_eq!(
+            commands.signed_arguments("/msg bot hello  there "),
+            [("message".to_owned(), "hello  there")]
+        );
+        assert!(commands.signed_arguments("/home").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_indexes() {
+        let commands = server_commands();
+        // the invalid child and redirect are ignored instead of panicking
+        assert!(commands.is_valid("/broken"));
+        assert!(!commands.is_valid("/broken anything"));
+        assert_eq!(commands.find("/broken").unwrap().children().count(), 0);
+        assert!(commands.node(100).is_none());
+
+        let commands = ServerCommands {
+            root_index: 5,
+            ..ServerCommands::new(&ClientboundCommands {
+                entries: vec![],
+                root_index: 0,
+            })
+        };
+        assert!(commands.root().is_none());
+        assert!(!commands.is_valid("/home"));
+        assert!(commands.completions("/").is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+//! Counting what a client did while it was connected, like how far it walked
+//! and how many blocks it broke.
+//!
+//! A summary is sent as a [`SessionSummaryEvent`] when the client disconnects,
+//! which is useful for keeping track of what a swarm of bots is doing.
+
+use std::time::{Duration, Instant};
+
+use azalea_block::BlockState;
+use azalea_core::{
+    position::{BlockPos, Vec3},
+    tick::GameTick,
+};
+use azalea_entity::{Dead, Physics, Position};
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::ClientboundGamePacket;
+use bevy_app::{App, Plugin, PostUpdate, Update};
+use bevy_ecs::prelude::*;
+use tracing::info;
+
+use crate::{
+    block_effects::{BlockBreakEffectEvent, BlockEffectSource, BlockPlaceEffectEvent},
+    chat::{ChatKind, ChatReceivedEvent, SendChatKindEvent},
+    disconnect::{remove_components_from_disconnected_players, DisconnectEvent},
+    packet_handling::game::{ItemPickupEvent, PacketEvent},
+    Client,
+};
+
+/// Movement that's farther than this in one tick is assumed to be a teleport
+/// and isn't counted as walking.
+const MAX_WALK_DISTANCE_PER_TICK: f64 = 10.;
+/// How many ticks we wait for the server to confirm that we broke a block
+/// before forgetting about it.
+const BLOCK_BREAK_CONFIRMATION_TICKS: u32 = 20 * 5;
+
+pub struct SessionStatsPlugin;
+impl Plugin for SessionStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SessionSummaryEvent>()
+            .add_systems(
+                GameTick,
+                (
+                    update_distance_walked.after(PhysicsSet),
+                    forget_unconfirmed_block_breaks,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (track_block_breaks, count_blocks_broken).chain(),
+                    count_blocks_placed,
+                    count_deaths,
+                    count_items_collected,
+                    count_chat_messages,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                send_session_summary.before(remove_components_from_disconnected_players),
+            );
+    }
+}
+
+/// Statistics about what a local player did since it joined the server.
+///
+/// This is reset every time the client joins.
+#[derive(Component, Clone, Debug)]
+pub struct SessionStats {
+    /// When we joined the server.
+    pub started_at: Instant,
+    /// The horizontal distance that we moved while on the ground, in blocks.
+    pub distance_walked: f64,
+    /// The number of blocks that the server confirmed that we broke.
+    pub blocks_broken: u32,
+    /// The number of blocks that the server confirmed that we placed.
+    pub blocks_placed: u32,
+    pub deaths: u32,
+    /// The total number of items that we picked up, counting every item in a
+    /// stack.
+    pub items_collected: u32,
+    /// The number of chat messages that we received, including system
+    /// messages.
+    pub chat_messages_received: u32,
+    /// The number of chat messages that we sent, not including commands.
+    pub chat_messages_sent: u32,
+
+    last_position: Option<Vec3>,
+    /// Blocks that we broke and are waiting for the server to confirm.
+    pending_breaks: Vec<PendingBlockBreak>,
+}
+
+#[derive(Clone, Debug)]
+struct PendingBlockBreak {
+    position: BlockPos,
+    /// The block that was there before we broke it.
+    block_state: BlockState,
+    ticks: u32,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            distance_walked: 0.,
+            blocks_broken: 0,
+            blocks_placed: 0,
+            deaths: 0,
+            items_collected: 0,
+            chat_messages_received: 0,
+            chat_messages_sent: 0,
+            last_position: None,
+            pending_breaks: Vec::new(),
+        }
+    }
+}
+
+impl SessionStats {
+    /// How long we've been connected for.
+    pub fn duration(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Sent right before a local player's components are removed because it
+/// disconnected, with the stats for the session that just ended.
+#[derive(Event, Debug, Clone)]
+pub struct SessionSummaryEvent {
+    pub entity: Entity,
+    pub stats: SessionStats,
+    /// How long we were connected for.
+    pub duration: Duration,
+}
+
+pub fn update_distance_walked(mut query: Query<(&Position, &Physics, &mut SessionStats)>) {
+    for (position, physics, mut stats) in &mut query {
+        let position = **position;
+        if let Some(last_position) = stats.last_position {
+            let distance = position
+                .horizontal_distance_squared_to(&last_position)
+                .sqrt();
+            if physics.on_ground() && distance < MAX_WALK_DISTANCE_PER_TICK {
+                stats.distance_walked += distance;
+            }
+        }
+        stats.last_position = Some(position);
+    }
+}
+
+/// Remember the blocks that we broke, so they can be counted once the server
+/// confirms it.
+pub fn track_block_breaks(
+    mut events: EventReader<BlockBreakEffectEvent>,
+    mut query: Query<&mut SessionStats>,
+) {
+    for event in events.read() {
+        if event.source != BlockEffectSource::Local {
+            continue;
+        }
+        if let Ok(mut stats) = query.get_mut(event.entity) {
+            stats.pending_breaks.push(PendingBlockBreak {
+                position: event.position,
+                block_state: event.block_state,
+                ticks: 0,
+            });
+        }
+    }
+}
+
+/// Count the blocks that we broke when the server sends us their new state.
+///
+/// The server tells us about the block that's there after it handled our break,
+/// so if it rejected it then we'll get the old block back and it isn't counted.
+pub fn count_blocks_broken(
+    mut events: EventReader<PacketEvent>,
+    mut query: Query<&mut SessionStats>,
+) {
+    for event in events.read() {
+        let Ok(mut stats) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if stats.pending_breaks.is_empty() {
+            continue;
+        }
+        match event.packet.as_ref() {
+            ClientboundGamePacket::BlockUpdate(p) => {
+                stats.confirm_block_break(p.pos, p.block_state);
+            }
+            ClientboundGamePacket::SectionBlocksUpdate(p) => {
+                for state in &p.states {
+                    stats.confirm_block_break(p.section_pos + state.pos, state.state);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl SessionStats {
+    fn confirm_block_break(&mut self, position: BlockPos, new_state: BlockState) {
+        let Some(index) = self
+            .pending_breaks
+            .iter()
+            .position(|pending| pending.position == position)
+        else {
+            return;
+        };
+        let pending = self.pending_breaks.remove(index);
+        if pending.block_state != new_state {
+            self.blocks_broken += 1;
+        }
+    }
+}
+
+pub fn forget_unconfirmed_block_breaks(mut query: Query<&mut SessionStats>) {
+    for mut stats in &mut query {
+        if stats.pending_breaks.is_empty() {
+            continue;
+        }
+        for pending in &mut stats.pending_breaks {
+            pending.ticks += 1;
+        }
+        stats
+            .pending_breaks
+            .retain(|pending| pending.ticks < BLOCK_BREAK_CONFIRMATION_TICKS);
+    }
+}
+
+pub fn count_blocks_placed(
+    mut events: EventReader<BlockPlaceEffectEvent>,
+    mut query: Query<&mut SessionStats>,
+) {
+    for event in events.read() {
+        if let Ok(mut stats) = query.get_mut(event.entity) {
+            stats.blocks_placed += 1;
+        }
+    }
+}
+
+pub fn count_deaths(mut query: Query<&mut SessionStats, Added<Dead>>) {
+    for mut stats in &mut query {
+        stats.deaths += 1;
+    }
+}
+
+pub fn count_items_collected(
+    mut events: EventReader<ItemPickupEvent>,
+    mut query: Query<&mut SessionStats>,
+) {
+    for event in events.read() {
+        // we also get events for other entities picking up items
+        if event.collector != event.entity {
+            continue;
+        }
+        if let Ok(mut stats) = query.get_mut(event.entity) {
+            stats.items_collected += event.item.count() as u32;
+        }
+    }
+}
+
+pub fn count_chat_messages(
+    mut received_events: EventReader<ChatReceivedEvent>,
+    mut sent_events: EventReader<SendChatKindEvent>,
+    mut query: Query<&mut SessionStats>,
+) {
+    for event in received_events.read() {
+        if let Ok(mut stats) = query.get_mut(event.entity) {
+            stats.chat_messages_received += 1;
+        }
+    }
+    for event in sent_events.read() {
+        if event.kind != ChatKind::Message {
+            continue;
+        }
+        if let Ok(mut stats) = query.get_mut(event.entity) {
+            stats.chat_messages_sent += 1;
+        }
+    }
+}
+
+pub fn send_session_summary(
+    mut events: EventReader<DisconnectEvent>,
+    query: Query<&SessionStats>,
+    mut session_summary_events: EventWriter<SessionSummaryEvent>,
+) {
+    for event in events.read() {
+        let Ok(stats) = query.get(event.entity) else {
+            continue;
+        };
+        let duration = stats.duration();
+        info!(
+            "Session for {:?} lasted {duration:?}: walked {:.1} blocks, broke {} blocks, placed {} blocks, died {} times, collected {} items, received {} and sent {} chat messages",
+            event.entity,
+            stats.distance_walked,
+            stats.blocks_broken,
+            stats.blocks_placed,
+            stats.deaths,
+            stats.items_collected,
+            stats.chat_messages_received,
+            stats.chat_messages_sent,
+        );
+        session_summary_events.send(SessionSummaryEvent {
+            entity: event.entity,
+            stats: stats.clone(),
+            duration,
+        });
+    }
+}
+
+impl Client {
+    /// Get the statistics for what this client did since it joined. See
+    /// [`SessionStats`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the client isn't in the game yet.
+    pub fn session_stats(&self) -> SessionStats {
+        self.component::<SessionStats>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_block::blocks;
+    use azalea_protocol::packets::{game::c_block_update::ClientboundBlockUpdate, Packet};
+    use bevy_ecs::event::Events;
+
+    use super::*;
+
+    #[test]
+    fn test_count_confirmed_block_breaks() {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<BlockBreakEffectEvent>>();
+        ecs.init_resource::<Events<PacketEvent>>();
+        let entity = ecs.spawn(SessionStats::default()).id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((track_block_breaks, count_blocks_broken).chain());
+
+        let stone = BlockState::from(blocks::Stone {});
+        for (x, source) in [
+            (0, BlockEffectSource::Local),
+            (1, BlockEffectSource::Local),
+            (2, BlockEffectSource::Server),
+        ] {
+            ecs.send_event(BlockBreakEffectEvent {
+                entity,
+                position: BlockPos::new(x, 0, 0),
+                block_state: stone,
+                source,
+            });
+        }
+        schedule.run(&mut ecs);
+        // nothing is counted until the server tells us what happened
+        assert_eq!(ecs.get::<SessionStats>(entity).unwrap().blocks_broken, 0);
+
+        for (x, block_state) in [(0, BlockState::AIR), (1, stone), (2, BlockState::AIR)] {
+            ecs.send_event(PacketEvent {
+                entity,
+                packet: Arc::new(
+                    ClientboundBlockUpdate {
+                        pos: BlockPos::new(x, 0, 0),
+                        block_state,
+                    }
+                    .into_variant(),
+                ),
+            });
+        }
+        schedule.run(&mut ecs);
+
+        // the second break was rejected and the third one was someone else
+        let stats = ecs.get::<SessionStats>(entity).unwrap();
+        assert_eq!(stats.blocks_broken, 1);
+        assert!(stats.pending_breaks.is_empty());
+    }
+
+    #[test]
+    fn test_forget_unconfirmed_block_breaks() {
+        let mut ecs = World::new();
+        let mut stats = SessionStats::default();
+        stats.pending_breaks.push(PendingBlockBreak {
+            position: BlockPos::new(0, 0, 0),
+            block_state: blocks::Stone {}.into(),
+            ticks: 0,
+        });
+        let entity = ecs.spawn(stats).id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(forget_unconfirmed_block_breaks);
+        for _ in 0..BLOCK_BREAK_CONFIRMATION_TICKS {
+            schedule.run(&mut ecs);
+        }
+        let stats = ecs.get::<SessionStats>(entity).unwrap();
+        assert!(stats.pending_breaks.is_empty());
+        assert_eq!(stats.blocks_broken, 0);
+    }
+}
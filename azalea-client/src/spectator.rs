@@ -0,0 +1,79 @@
+//! Helpers for bots that are in spectator mode.
+
+use azalea_core::{game_type::GameMode, tick::GameTick};
+use azalea_entity::Position;
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::ServerboundTeleportToEntity;
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    local_player::LocalGameMode, movement::send_position, packet_handling::game::SendPacketEvent,
+    Client,
+};
+
+pub struct SpectatorPlugin;
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            GameTick,
+            follow_spectator_camera
+                .after(PhysicsSet)
+                .before(send_position),
+        );
+    }
+}
+
+/// The entity that we're looking through, which is set by the server when
+/// we're in spectator mode and click on an entity.
+///
+/// This is only present while the camera is on an entity other than ourselves.
+/// We don't send our position to the server while this is present, since the
+/// server moves us along with the camera entity.
+#[derive(Component, Clone, Copy, Debug, Deref, DerefMut, PartialEq, Eq)]
+pub struct SpectatorCamera(pub Entity);
+
+impl Client {
+    /// Teleport to the player with the given UUID. This only works while we're
+    /// in spectator mode, and the player can be in any dimension.
+    ///
+    /// This is what happens when you pick a player from the spectator menu in
+    /// vanilla.
+    pub fn spectate(&self, player_uuid: Uuid) {
+        if let Some(game_mode) = self.get_component::<LocalGameMode>() {
+            if game_mode.current != GameMode::Spectator {
+                warn!("Tried to spectate a player while we're not in spectator mode");
+            }
+        }
+        self.ecs.lock().send_event(SendPacketEvent::new(
+            self.entity,
+            ServerboundTeleportToEntity { uuid: player_uuid },
+        ));
+    }
+
+    /// Get the entity that our camera is attached to, or `None` if we're
+    /// looking through our own eyes.
+    pub fn spectator_camera(&self) -> Option<Entity> {
+        self.get_component::<SpectatorCamera>()
+            .map(|camera| *camera)
+    }
+}
+
+/// Keep our position in sync with the entity that we're spectating, like the
+/// server does.
+pub fn follow_spectator_camera(
+    mut query: Query<(&SpectatorCamera, &mut Position)>,
+    camera_query: Query<&Position, Without<SpectatorCamera>>,
+) {
+    for (camera, mut position) in &mut query {
+        let Ok(camera_position) = camera_query.get(**camera) else {
+            continue;
+        };
+        if **position != **camera_position {
+            **position = **camera_position;
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Estimate how fast the server is ticking.
+//!
+//! Vanilla servers send us the time every 20 ticks, so we can compare how many
+//! ticks passed on the server to how much time passed for us.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    packet_handling::game::{KeepAliveEvent, TimeUpdateEvent},
+    Client,
+};
+
+/// The number of samples that are used for calculating the TPS. Since the
+/// server sends the time about once a second, this is roughly the number of
+/// seconds that are averaged.
+pub const TPS_WINDOW_SIZE: usize = 20;
+
+/// If a time update has a world age that's this many ticks behind the last one,
+/// we assume that it's from a different server (or that the server was
+/// restarted). Smaller differences happen when the clients in a swarm receive
+/// the same time updates at slightly different times.
+const MAX_OUT_OF_ORDER_TICKS: u64 = 200;
+
+/// How often vanilla servers send keep-alive packets.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+pub struct TpsPlugin;
+impl Plugin for TpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerTps>()
+            .add_systems(Update, (update_server_tps, update_keep_alive_jitter));
+    }
+}
+
+/// An estimate of how fast the server is running, based on the last
+/// [`TPS_WINDOW_SIZE`] time updates that it sent us.
+///
+/// This is a resource since every client in a swarm is usually connected to
+/// the same server. If you have clients on different servers then the
+/// estimate will be for whichever one sent us the most recent time.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ServerTps {
+    /// The world age and the time that we received it for the most recent
+    /// time update.
+    last_update: Option<(u64, Instant)>,
+    /// The number of ticks that passed and how long they took, for every time
+    /// update in the window.
+    samples: VecDeque<(u64, Duration)>,
+    /// How much later or earlier than expected the recent keep-alives were.
+    keep_alive_jitter: VecDeque<Duration>,
+    last_keep_alive: HashMap<Entity, Instant>,
+}
+
+impl ServerTps {
+    /// The estimated number of ticks per second that the server is running at,
+    /// or `None` if we haven't received enough time updates yet.
+    ///
+    /// A server that's keeping up will be at 20. This is capped at 20 since
+    /// the server never runs faster than that, and network delays can
+    /// otherwise make it look like it did.
+    pub fn tps(&self) -> Option<f64> {
+        let (ticks, elapsed) = self.totals()?;
+        Some((ticks as f64 / elapsed.as_secs_f64()).min(20.))
+    }
+
+    /// The average amount of real time that each server tick took, or `None`
+    /// if we haven't received enough time updates yet.
+    ///
+    /// This is 50ms when the server is keeping up. Note that this isn't the
+    /// same as the MSPT that the server shows, since a server that finishes
+    /// its ticks early will still wait before starting the next one.
+    pub fn mean_tick_time(&self) -> Option<Duration> {
+        let (ticks, elapsed) = self.totals()?;
+        Some(elapsed / ticks as u32)
+    }
+
+    /// How far off from the expected interval the recent keep-alive packets
+    /// were, on average. Large values usually mean the server (or the
+    /// connection) is lagging.
+    pub fn keep_alive_jitter(&self) -> Option<Duration> {
+        if self.keep_alive_jitter.is_empty() {
+            return None;
+        }
+        let total: Duration = self.keep_alive_jitter.iter().sum();
+        Some(total / self.keep_alive_jitter.len() as u32)
+    }
+
+    /// Whether the server seems to be running slower than it should.
+    pub fn is_lagging(&self) -> bool {
+        self.tps().is_some_and(|tps| tps < 18.)
+    }
+
+    fn totals(&self) -> Option<(u64, Duration)> {
+        let ticks: u64 = self.samples.iter().map(|(ticks, _)| ticks).sum();
+        let elapsed: Duration = self.samples.iter().map(|(_, elapsed)| *elapsed).sum();
+        if ticks == 0 || elapsed.is_zero() {
+            return None;
+        }
+        Some((ticks, elapsed))
+    }
+
+    /// Add a time update from the server to the estimate.
+    pub fn add_time_update(&mut self, world_age: u64, received_at: Instant) {
+        match self.last_update {
+            // other clients on the same server will send us the same world age
+            Some((last_world_age, _)) if world_age == last_world_age => return,
+            // we probably joined a different server
+            Some((last_world_age, _)) if world_age + MAX_OUT_OF_ORDER_TICKS < last_world_age => {
+                self.samples.clear()
+            }
+            // another client got an older time update after we already got a newer one
+            Some((last_world_age, _)) if world_age < last_world_age => return,
+            Some((last_world_age, last_received_at)) => {
+                let elapsed = received_at.saturating_duration_since(last_received_at);
+                self.samples
+                    .push_back((world_age - last_world_age, elapsed));
+                if self.samples.len() > TPS_WINDOW_SIZE {
+                    self.samples.pop_front();
+                }
+            }
+            None => {}
+        }
+        self.last_update = Some((world_age, received_at));
+    }
+}
+
+impl Client {
+    /// Get the current estimate of how fast the server is running.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// if bot.server_tps().is_lagging() {
+    ///     println!("The server is lagging, slowing down");
+    /// }
+    /// # }
+    /// ```
+    pub fn server_tps(&self) -> ServerTps {
+        self.ecs.lock().resource::<ServerTps>().clone()
+    }
+}
+
+pub fn update_server_tps(mut events: EventReader<TimeUpdateEvent>, mut tps: ResMut<ServerTps>) {
+    for event in events.read() {
+        tps.add_time_update(event.time.world_age, Instant::now());
+    }
+}
+
+pub fn update_keep_alive_jitter(
+    mut events: EventReader<KeepAliveEvent>,
+    mut tps: ResMut<ServerTps>,
+) {
+    for event in events.read() {
+        let now = Instant::now();
+        if let Some(last) = tps.last_keep_alive.insert(event.entity, now) {
+            let interval = now.saturating_duration_since(last);
+            let jitter = interval.abs_diff(KEEP_ALIVE_INTERVAL);
+            tps.keep_alive_jitter.push_back(jitter);
+            if tps.keep_alive_jitter.len() > TPS_WINDOW_SIZE {
+                tps.keep_alive_jitter.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Add time updates that are 20 ticks apart and take the given amount of
+    /// time each.
+    fn add_updates(tps: &mut ServerTps, start: Instant, count: u64, interval: Duration) {
+        for i in 0..count {
+            tps.add_time_update(i * 20, start + interval * i as u32);
+        }
+    }
+
+    #[test]
+    fn test_no_estimate_without_updates() {
+        let mut tps = ServerTps::default();
+        assert_eq!(tps.tps(), None);
+        tps.add_time_update(0, Instant::now());
+        assert_eq!(tps.tps(), None);
+        assert_eq!(tps.mean_tick_time(), None);
+        assert!(!tps.is_lagging());
+    }
+
+    #[test]
+    fn test_full_speed() {
+        let mut tps = ServerTps::default();
+        add_updates(&mut tps, Instant::now(), 10, Duration::from_secs(1));
+        assert_eq!(tps.tps(), Some(20.));
+        assert_eq!(tps.mean_tick_time(), Some(Duration::from_millis(50)));
+        assert!(!tps.is_lagging());
+    }
+
+    #[test]
+    fn test_half_speed() {
+        let mut tps = ServerTps::default();
+        add_updates(&mut tps, Instant::now(), 10, Duration::from_secs(2));
+        assert_eq!(tps.tps(), Some(10.));
+        assert_eq!(tps.mean_tick_time(), Some(Duration::from_millis(100)));
+        assert!(tps.is_lagging());
+    }
+
+    #[test]
+    fn test_window() {
+        let mut tps = ServerTps::default();
+        let start = Instant::now();
+        // the server was lagging a while ago, but it's fine now
+        add_updates(&mut tps, start, 10, Duration::from_secs(2));
+        for i in 0..TPS_WINDOW_SIZE as u64 {
+            tps.add_time_update(
+                (10 + i) * 20,
+                start + Duration::from_secs(18) + Duration::from_secs(i + 1),
+            );
+        }
+        assert_eq!(tps.samples.len(), TPS_WINDOW_SIZE);
+        assert_eq!(tps.tps(), Some(20.));
+    }
+
+    #[test]
+    fn test_swarm_out_of_order_updates() {
+        let mut tps = ServerTps::default();
+        let start = Instant::now();
+        add_updates(&mut tps, start, 10, Duration::from_secs(1));
+
+        // another bot in the swarm got the same update
+        tps.add_time_update(180, start + Duration::from_millis(9010));
+        // and one bot is a bit behind and just got the previous one
+        tps.add_time_update(160, start + Duration::from_millis(9020));
+
+        assert_eq!(tps.samples.len(), 9);
+        assert_eq!(tps.tps(), Some(20.));
+
+        tps.add_time_update(200, start + Duration::from_secs(10));
+        assert_eq!(tps.samples.len(), 10);
+        assert_eq!(tps.tps(), Some(20.));
+    }
+
+    #[test]
+    fn test_reset_on_new_server() {
+        let mut tps = ServerTps::default();
+        let start = Instant::now();
+        add_updates(
+            &mut tps,
+            start + Duration::from_secs(1000),
+            100,
+            Duration::from_secs(2),
+        );
+        assert!(tps.is_lagging());
+
+        // the world age went way back, so this is probably a different server
+        add_updates(
+            &mut tps,
+            start + Duration::from_secs(2000),
+            3,
+            Duration::from_secs(1),
+        );
+        assert_eq!(tps.samples.len(), 2);
+        assert_eq!(tps.tps(), Some(20.));
+    }
+}
@@ -0,0 +1,445 @@
+//! Handle the transfer packets that servers use to send us to other servers,
+//! and the cookies that they can store on us before doing that.
+//!
+//! When a server transfers us, we add a [`PendingTransfer`] to the client and
+//! disconnect. Then the new server is joined with the same entity and state,
+//! and an [`Event::Transferred`] is sent when it worked. Clients that were
+//! made with [`Client::join`] or [`Client::join_with_proxy`] do this by
+//! themselves (see [`FollowTransfers`]), and `azalea`'s swarms do it for their
+//! bots.
+//!
+//! Like in vanilla, cookies are only kept when we're transferred. They're
+//! cleared when we join a server any other way.
+
+use std::collections::HashMap;
+
+use azalea_core::resource_location::ResourceLocation;
+use azalea_protocol::{
+    connect::Proxy,
+    packets::{
+        config::{self, ClientboundConfigPacket},
+        game::{self, ClientboundGamePacket},
+    },
+    resolver, ServerAddress,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::{
+    client::{ClientSocketOptions, JoinError, StartClientOpts},
+    disconnect::DisconnectEvent,
+    events::LocalPlayerEvents,
+    packet_handling::{
+        configuration::{
+            self as configuration_handling, ConfigurationEvent, SendConfigurationEvent,
+        },
+        game::{self as game_handling, PacketEvent, SendPacketEvent},
+    },
+    Account, Client, Event,
+};
+
+/// The most data that a server can store in one cookie, which is the same
+/// limit that vanilla has.
+pub const MAX_COOKIE_SIZE: usize = 5 * 1024;
+
+pub struct TransferPlugin;
+impl Plugin for TransferPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                handle_config_transfer_packets
+                    .before(configuration_handling::handle_send_packet_event),
+                handle_game_transfer_packets.before(game_handling::handle_send_packet_event),
+            ),
+        );
+    }
+}
+
+/// The cookies that servers stored on the client, which they can read after
+/// transferring us to another server.
+///
+/// This is added to the client when it starts logging in.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Cookies(pub HashMap<ResourceLocation, Vec<u8>>);
+
+impl Cookies {
+    pub fn get(&self, key: &ResourceLocation) -> Option<&Vec<u8>> {
+        self.0.get(key)
+    }
+
+    /// Store a cookie, or ignore it if it's bigger than [`MAX_COOKIE_SIZE`].
+    pub fn insert(&mut self, key: ResourceLocation, payload: Vec<u8>) {
+        if payload.len() > MAX_COOKIE_SIZE {
+            warn!(
+                "Ignoring cookie {key} since it's {} bytes, which is more than the limit of {MAX_COOKIE_SIZE}",
+                payload.len()
+            );
+            return;
+        }
+        self.0.insert(key, payload);
+    }
+}
+
+/// A component for clients that a server told to go to another server. The
+/// client is disconnected when this is added, and it's removed when we start
+/// joining the new server.
+#[derive(Component, Clone, Debug)]
+pub struct PendingTransfer {
+    pub address: ServerAddress,
+}
+
+/// A component for clients that join the server they're transferred to by
+/// themselves, instead of having a swarm do it for them.
+///
+/// The address is sent to a task along with the sender for the client's
+/// events, since that's removed from the client when it disconnects.
+#[derive(Component, Clone)]
+pub struct FollowTransfers(mpsc::UnboundedSender<(ServerAddress, mpsc::UnboundedSender<Event>)>);
+
+/// Make a client that isn't in a swarm follow the transfers that servers send
+/// it, keeping its state and sending its events to the same receiver.
+///
+/// This spawns a tokio task, so it has to be called from inside a runtime.
+pub(crate) fn follow_transfers(client: &Client, proxy: Option<Proxy>) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    client
+        .ecs
+        .lock()
+        .entity_mut(client.entity)
+        .insert(FollowTransfers(sender));
+
+    let client = client.clone();
+    tokio::spawn(async move {
+        while let Some((address, events)) = receiver.recv().await {
+            // if this fails, the events sender is dropped and the client stays
+            // disconnected like it would've been if it was kicked
+            if let Err(e) = join_transferred(&client, proxy.clone(), &address, events).await {
+                error!(
+                    "Error following transfer to {}:{}: {e}",
+                    address.host, address.port
+                );
+            }
+        }
+    });
+}
+
+async fn join_transferred(
+    client: &Client,
+    proxy: Option<Proxy>,
+    address: &ServerAddress,
+    events: mpsc::UnboundedSender<Event>,
+) -> Result<(), JoinError> {
+    let account = client
+        .get_component::<Account>()
+        .ok_or(JoinError::MissingAccount)?;
+    // the proxy is passed in since it's only known when the client is made, but
+    // the socket options are stored on the client
+    let socket_options = client
+        .get_component::<ClientSocketOptions>()
+        .map(|options| options.0)
+        .unwrap_or_default();
+    let resolved_address = resolver::resolve_address(address).await?;
+    let (_, mut new_events) = Client::start_client(StartClientOpts {
+        ecs_lock: client.ecs.clone(),
+        account: &account,
+        address,
+        resolved_address: &resolved_address,
+        proxy,
+        socket_options,
+        bungeecord_forwarding: None,
+        proxy_protocol: None,
+        run_schedule_sender: client.run_schedule_sender.clone(),
+    })
+    .await?;
+
+    // keep sending events to the receiver from when the client first joined.
+    // the ecs is locked while we switch, so no events can get lost.
+    let mut ecs = client.ecs.lock();
+    ecs.entity_mut(client.entity)
+        .insert(LocalPlayerEvents(events.clone()));
+    let _ = events.send(Event::Transferred {
+        address: address.clone(),
+    });
+    while let Ok(event) = new_events.try_recv() {
+        let _ = events.send(event);
+    }
+    Ok(())
+}
+
+fn start_transfer(
+    commands: &mut Commands,
+    disconnect_events: &mut EventWriter<DisconnectEvent>,
+    followers: &Query<(&FollowTransfers, &LocalPlayerEvents)>,
+    entity: Entity,
+    host: &str,
+    port: u32,
+) {
+    let Ok(port) = u16::try_from(port) else {
+        warn!("Ignoring transfer to {host}:{port} since the port is invalid");
+        return;
+    };
+    info!("Got transferred to {host}:{port}");
+    let address = ServerAddress {
+        host: host.to_owned(),
+        port,
+    };
+    if let Ok((follow_transfers, local_player_events)) = followers.get(entity) {
+        let _ = follow_transfers
+            .0
+            .send((address.clone(), local_player_events.0.clone()));
+    }
+    commands.entity(entity).insert(PendingTransfer { address });
+    disconnect_events.send(DisconnectEvent {
+        entity,
+        reason: None,
+    });
+}
+
+pub fn handle_config_transfer_packets(
+    mut commands: Commands,
+    mut events: EventReader<ConfigurationEvent>,
+    mut query: Query<&mut Cookies>,
+    mut send_packet_events: EventWriter<SendConfigurationEvent>,
+    mut disconnect_events: EventWriter<DisconnectEvent>,
+    followers: Query<(&FollowTransfers, &LocalPlayerEvents)>,
+) {
+    for event in events.read() {
+        match &event.packet {
+            ClientboundConfigPacket::CookieRequest(p) => {
+                let payload = query
+                    .get(event.entity)
+                    .ok()
+                    .and_then(|cookies| cookies.get(&p.key).cloned());
+                send_packet_events.send(SendConfigurationEvent::new(
+                    event.entity,
+                    config::ServerboundCookieResponse {
+                        key: p.key.clone(),
+                        payload,
+                    },
+                ));
+            }
+            ClientboundConfigPacket::StoreCookie(p) => {
+                if let Ok(mut cookies) = query.get_mut(event.entity) {
+                    cookies.insert(p.key.clone(), p.payload.clone());
+                }
+            }
+            ClientboundConfigPacket::Transfer(p) => {
+                start_transfer(
+                    &mut commands,
+                    &mut disconnect_events,
+                    &followers,
+                    event.entity,
+                    &p.host,
+                    p.port,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn handle_game_transfer_packets(
+    mut commands: Commands,
+    mut events: EventReader<PacketEvent>,
+    mut query: Query<&mut Cookies>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut disconnect_events: EventWriter<DisconnectEvent>,
+    followers: Query<(&FollowTransfers, &LocalPlayerEvents)>,
+) {
+    for event in events.read() {
+        match event.packet.as_ref() {
+            ClientboundGamePacket::CookieRequest(p) => {
+                let payload = query
+                    .get(event.entity)
+                    .ok()
+                    .and_then(|cookies| cookies.get(&p.key).cloned());
+                send_packet_events.send(SendPacketEvent::new(
+                    event.entity,
+                    game::ServerboundCookieResponse {
+                        key: p.key.clone(),
+                        payload,
+                    },
+                ));
+            }
+            ClientboundGamePacket::StoreCookie(p) => {
+                if let Ok(mut cookies) = query.get_mut(event.entity) {
+                    cookies.insert(p.key.clone(), p.payload.clone());
+                }
+            }
+            ClientboundGamePacket::Transfer(p) => {
+                start_transfer(
+                    &mut commands,
+                    &mut disconnect_events,
+                    &followers,
+                    event.entity,
+                    &p.host,
+                    p.port,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_protocol::packets::{
+        config::ServerboundConfigPacket, game::ServerboundGamePacket, Packet,
+    };
+    use bevy_ecs::{event::Events, schedule::Schedule};
+
+    use super::*;
+
+    fn world_with_events() -> World {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<ConfigurationEvent>>();
+        ecs.init_resource::<Events<SendConfigurationEvent>>();
+        ecs.init_resource::<Events<PacketEvent>>();
+        ecs.init_resource::<Events<SendPacketEvent>>();
+        ecs.init_resource::<Events<DisconnectEvent>>();
+        ecs
+    }
+
+    fn run(ecs: &mut World) {
+        let mut schedule = Schedule::default();
+        schedule.add_systems((handle_config_transfer_packets, handle_game_transfer_packets));
+        schedule.run(ecs);
+    }
+
+    #[test]
+    fn test_cookie_size_limit() {
+        let mut cookies = Cookies::default();
+        let key = ResourceLocation::new("azalea:cookie");
+        cookies.insert(key.clone(), vec![0; MAX_COOKIE_SIZE + 1]);
+        assert_eq!(cookies.get(&key), None);
+        cookies.insert(key.clone(), vec![1; MAX_COOKIE_SIZE]);
+        assert_eq!(cookies.get(&key).map(Vec::len), Some(MAX_COOKIE_SIZE));
+    }
+
+    #[test]
+    fn test_config_cookies() {
+        let mut ecs = world_with_events();
+        let entity = ecs.spawn(Cookies::default()).id();
+        let key = ResourceLocation::new("azalea:cookie");
+
+        ecs.send_event(ConfigurationEvent {
+            entity,
+            packet: config::ClientboundStoreCookie {
+                key: key.clone(),
+                payload: vec![1, 2, 3],
+            }
+            .into_variant(),
+        });
+        run(&mut ecs);
+        assert_eq!(
+            ecs.get::<Cookies>(entity).unwrap().get(&key),
+            Some(&vec![1, 2, 3])
+        );
+
+        for key in [key, ResourceLocation::new("azalea:missing")] {
+            ecs.send_event(ConfigurationEvent {
+                entity,
+                packet: config::ClientboundCookieRequest { key }.into_variant(),
+            });
+        }
+        run(&mut ecs);
+        let responses = ecs
+            .resource::<Events<SendConfigurationEvent>>()
+            .iter_current_update_events()
+            .map(|event| match &event.packet {
+                ServerboundConfigPacket::CookieResponse(p) => p.payload.clone(),
+                packet => panic!("expected a cookie response, got {packet:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(responses, [Some(vec![1, 2, 3]), None]);
+    }
+
+    #[test]
+    fn test_game_cookie_request() {
+        let mut ecs = world_with_events();
+        let key = ResourceLocation::new("azalea:cookie");
+        let mut cookies = Cookies::default();
+        cookies.insert(key.clone(), vec![4]);
+        let entity = ecs.spawn(cookies).id();
+
+        ecs.send_event(PacketEvent {
+            entity,
+            packet: Arc::new(game::ClientboundCookieRequest { key: key.clone() }.into_variant()),
+        });
+        run(&mut ecs);
+        let events = ecs.resource::<Events<SendPacketEvent>>();
+        let event = events.iter_current_update_events().next().unwrap();
+        let ServerboundGamePacket::CookieResponse(p) = &event.packet else {
+            panic!("expected a cookie response, got {:?}", event.packet);
+        };
+        assert_eq!(p.key, key);
+        assert_eq!(p.payload, Some(vec![4]));
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut ecs = world_with_events();
+        let (follow_sender, mut follow_receiver) = mpsc::unbounded_channel();
+        let (events_sender, _events_receiver) = mpsc::unbounded_channel();
+        let follower = ecs
+            .spawn((
+                Cookies::default(),
+                FollowTransfers(follow_sender),
+                LocalPlayerEvents(events_sender),
+            ))
+            .id();
+        // bots in swarms don't follow transfers by themselves
+        let swarm_bot = ecs.spawn(Cookies::default()).id();
+
+        for entity in [follower, swarm_bot] {
+            ecs.send_event(PacketEvent {
+                entity,
+                packet: Arc::new(
+                    game::ClientboundTransfer {
+                        host: "example.com".to_owned(),
+                        port: 25566,
+                    }
+                    .into_variant(),
+                ),
+            });
+        }
+        run(&mut ecs);
+
+        for entity in [follower, swarm_bot] {
+            let address = &ecs.get::<PendingTransfer>(entity).unwrap().address;
+            assert_eq!(
+                (address.host.as_str(), address.port),
+                ("example.com", 25566)
+            );
+        }
+        assert_eq!(ecs.resource::<Events<DisconnectEvent>>().len(), 2);
+        let (address, _events) = follow_receiver.try_recv().unwrap();
+        assert_eq!(
+            (address.host.as_str(), address.port),
+            ("example.com", 25566)
+        );
+        assert!(follow_receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_transfer_invalid_port() {
+        let mut ecs = world_with_events();
+        let entity = ecs.spawn(Cookies::default()).id();
+        ecs.send_event(ConfigurationEvent {
+            entity,
+            packet: config::ClientboundTransfer {
+                host: "example.com".to_owned(),
+                port: 70000,
+            }
+            .into_variant(),
+        });
+        run(&mut ecs);
+        assert!(ecs.get::<PendingTransfer>(entity).is_none());
+        assert!(ecs.resource::<Events<DisconnectEvent>>().is_empty());
+    }
+}
@@ -0,0 +1,452 @@
+//! Riding and steering entities like boats and horses.
+
+use azalea_core::{position::Vec3, tick::GameTick};
+use azalea_entity::{input_vector, EntityKind, LocalEntity, LookDirection, Physics, Position};
+use azalea_physics::{
+    collision::{move_colliding, MoverType},
+    PhysicsSet,
+};
+use azalea_protocol::packets::game::{
+    s_interact::{self, InteractionHand, ServerboundInteract},
+    ServerboundMoveVehicle, ServerboundPaddleBoat,
+};
+use azalea_world::{InstanceContainer, InstanceName, MinecraftEntityId};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use tracing::warn;
+
+use crate::{
+    movement::{send_position, MoveEventsSet, PhysicsState},
+    packet_handling::game::SendPacketEvent,
+    Client,
+};
+
+pub struct VehiclePlugin;
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MountEvent>()
+            .add_event::<DismountEvent>()
+            .add_systems(
+                Update,
+                (handle_mount_event, handle_dismount_event).before(MoveEventsSet),
+            )
+            .add_systems(
+                GameTick,
+                (
+                    steer_vehicle.after(PhysicsSet).before(send_position),
+                    move_passengers_with_vehicle
+                        .after(PhysicsSet)
+                        .before(send_position),
+                    send_vehicle_position.after(send_position),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The entities that are riding this entity, in the order that they were sent
+/// by the server.
+///
+/// The first passenger is the one that controls the vehicle.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut, PartialEq, Eq)]
+pub struct Passengers(pub Vec<Entity>);
+
+/// The entity that this entity is riding.
+#[derive(Component, Clone, Copy, Debug, Deref, DerefMut, PartialEq, Eq)]
+pub struct Vehicle(pub Entity);
+
+/// A marker component for local players that are trying to get off of their
+/// vehicle. This makes us send the sneak key in our input packets until the
+/// server tells us that we're not riding anything anymore.
+#[derive(Component, Clone, Debug, Default)]
+pub struct WantsToDismount;
+
+/// Which of our boat's paddles we last told the server that we're moving.
+///
+/// We only send [`ServerboundPaddleBoat`] when this changes, so a boat that
+/// we're sitting still in doesn't send a packet every tick.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BoatPaddles {
+    pub left: bool,
+    pub right: bool,
+}
+
+impl Client {
+    /// Start riding the given entity, like a boat, minecart, or horse.
+    ///
+    /// This works by right clicking the entity, so the server has to let us
+    /// ride it. You can check if it worked by checking [`Client::vehicle`].
+    pub fn mount(&mut self, entity: Entity) {
+        self.ecs.lock().send_event(MountEvent {
+            entity: self.entity,
+            vehicle: entity,
+        });
+    }
+
+    /// Stop riding our current vehicle. This is the same as pressing shift in
+    /// vanilla.
+    pub fn dismount(&mut self) {
+        self.ecs.lock().send_event(DismountEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Get the entity that we're currently riding, if any.
+    pub fn vehicle(&self) -> Option<Entity> {
+        self.get_component::<Vehicle>().map(|vehicle| *vehicle)
+    }
+}
+
+/// Right click an entity to ride it.
+#[derive(Event)]
+pub struct MountEvent {
+    pub entity: Entity,
+    pub vehicle: Entity,
+}
+pub fn handle_mount_event(
+    mut events: EventReader<MountEvent>,
+    query: Query<&MinecraftEntityId>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.read() {
+        let Ok(vehicle_id) = query.get(event.vehicle) else {
+            warn!(
+                "Tried to mount {:?} but it doesn't have a MinecraftEntityId",
+                event.vehicle
+            );
+            continue;
+        };
+        send_packet_events.send(SendPacketEvent::new(
+            event.entity,
+            ServerboundInteract {
+                entity_id: **vehicle_id,
+                action: s_interact::ActionType::Interact {
+                    hand: InteractionHand::MainHand,
+                },
+                using_secondary_action: false,
+            },
+        ));
+    }
+}
+
+/// Get off of the vehicle that we're riding.
+#[derive(Event)]
+pub struct DismountEvent {
+    pub entity: Entity,
+}
+pub fn handle_dismount_event(
+    mut events: EventReader<DismountEvent>,
+    query: Query<(), With<Vehicle>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if query.get(event.entity).is_err() {
+            continue;
+        }
+        commands.entity(event.entity).insert(WantsToDismount);
+    }
+}
+
+/// Returns whether a passenger is able to steer the given kind of entity.
+///
+/// Pigs and striders are excluded since they need to be steered with an item.
+pub fn is_steerable(kind: azalea_registry::EntityKind) -> bool {
+    use azalea_registry::EntityKind as K;
+    is_boat(kind)
+        || matches!(
+            kind,
+            K::Horse | K::Donkey | K::Mule | K::SkeletonHorse | K::ZombieHorse | K::Camel
+        )
+}
+
+/// Returns whether the given kind of entity is a boat or raft (including ones
+/// with chests).
+pub fn is_boat(kind: azalea_registry::EntityKind) -> bool {
+    use azalea_registry::EntityKind as K;
+    matches!(
+        kind,
+        K::AcaciaBoat
+            | K::AcaciaChestBoat
+            | K::BambooRaft
+            | K::BambooChestRaft
+            | K::BirchBoat
+            | K::BirchChestBoat
+            | K::CherryBoat
+            | K::CherryChestBoat
+            | K::DarkOakBoat
+            | K::DarkOakChestBoat
+            | K::JungleBoat
+            | K::JungleChestBoat
+            | K::MangroveBoat
+            | K::MangroveChestBoat
+            | K::OakBoat
+            | K::OakChestBoat
+            | K::PaleOakBoat
+            | K::PaleOakChestBoat
+            | K::SpruceBoat
+            | K::SpruceChestBoat
+    )
+}
+
+/// Returns the vehicle that the given local player is steering, if any.
+fn controlled_vehicle(
+    entity: Entity,
+    vehicle: &Vehicle,
+    vehicles: &Query<(&Passengers, &EntityKind)>,
+) -> Option<Entity> {
+    let (passengers, kind) = vehicles.get(**vehicle).ok()?;
+    if passengers.first() != Some(&entity) || !is_steerable(**kind) {
+        return None;
+    }
+    Some(**vehicle)
+}
+
+/// Move the vehicles that our local players are controlling based on their
+/// inputs.
+///
+/// This isn't a full reimplementation of vanilla's boat and horse physics, it
+/// just moves the vehicle at roughly the right speed in the direction that
+/// we're looking.
+#[allow(clippy::type_complexity)]
+pub fn steer_vehicle(
+    players: Query<
+        (
+            Entity,
+            &Vehicle,
+            &PhysicsState,
+            &LookDirection,
+            &InstanceName,
+            Option<&BoatPaddles>,
+        ),
+        With<LocalEntity>,
+    >,
+    vehicles: Query<(&Passengers, &EntityKind)>,
+    mut vehicle_query: Query<
+        (&mut Position, &mut Physics, &mut LookDirection, &EntityKind),
+        Without<LocalEntity>,
+    >,
+    instance_container: Res<InstanceContainer>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut commands: Commands,
+) {
+    for (entity, vehicle, physics_state, look_direction, instance_name, last_paddles) in &players {
+        let Some(vehicle) = controlled_vehicle(entity, vehicle, &vehicles) else {
+            continue;
+        };
+        let Ok((mut vehicle_position, mut vehicle_physics, mut vehicle_look_direction, kind)) =
+            vehicle_query.get_mut(vehicle)
+        else {
+            continue;
+        };
+        let Some(world_lock) = instance_container.get(instance_name) else {
+            continue;
+        };
+        let world = world_lock.read();
+
+        let boat = is_boat(**kind);
+        if boat {
+            let left = physics_state.left_impulse > 0.;
+            let right = physics_state.left_impulse < 0.;
+            let forward = physics_state.forward_impulse > 0.;
+            let paddles = BoatPaddles {
+                left: right && !left || forward,
+                right: left && !right || forward,
+            };
+            if last_paddles.copied().unwrap_or_default() != paddles {
+                send_packet_events.send(SendPacketEvent::new(
+                    entity,
+                    ServerboundPaddleBoat {
+                        left: paddles.left,
+                        right: paddles.right,
+                    },
+                ));
+                commands.entity(entity).insert(paddles);
+            }
+        }
+
+        // boats are a bit faster than horses when they're on water
+        let speed = if boat { 0.35 } else { 0.225 };
+        let mut movement = input_vector(
+            look_direction,
+            speed,
+            &Vec3::new(
+                physics_state.left_impulse as f64,
+                0.,
+                physics_state.forward_impulse as f64,
+            ),
+        );
+        vehicle_look_direction.y_rot = look_direction.y_rot;
+
+        if !boat {
+            // land vehicles fall
+            vehicle_physics.velocity.y = (vehicle_physics.velocity.y - 0.08) * 0.98;
+            movement.y = vehicle_physics.velocity.y;
+        }
+
+        if let Err(e) = move_colliding(
+            MoverType::Own,
+            &movement,
+            &world,
+            &mut vehicle_position,
+            &mut vehicle_physics,
+        ) {
+            warn!("Failed to move vehicle: {e}");
+        }
+        if vehicle_physics.vertical_collision {
+            vehicle_physics.velocity.y = 0.;
+        }
+    }
+}
+
+/// Keep our local players on top of the vehicle that they're riding.
+#[allow(clippy::type_complexity)]
+pub fn move_passengers_with_vehicle(
+    mut players: Query<(Entity, &Vehicle, &mut Position, &mut Physics), With<LocalEntity>>,
+    vehicles: Query<(&Position, &Physics), Without<LocalEntity>>,
+    mut commands: Commands,
+) {
+    for (entity, vehicle, mut position, mut physics) in &mut players {
+        let Ok((vehicle_position, vehicle_physics)) = vehicles.get(**vehicle) else {
+            // the vehicle isn't loaded anymore
+            commands.entity(entity).remove::<Vehicle>();
+            continue;
+        };
+        let new_position =
+            **vehicle_position + Vec3::new(0., vehicle_physics.dimensions.height as f64 * 0.75, 0.);
+        if **position != new_position {
+            **position = new_position;
+        }
+        physics.velocity = Vec3::default();
+        physics.set_on_ground(false);
+    }
+}
+
+/// Send a [`ServerboundMoveVehicle`] for vehicles that we're controlling.
+pub fn send_vehicle_position(
+    players: Query<(Entity, &Vehicle), With<LocalEntity>>,
+    vehicles: Query<(&Passengers, &EntityKind)>,
+    vehicle_query: Query<(&Position, &LookDirection)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, vehicle) in &players {
+        let Some(vehicle) = controlled_vehicle(entity, vehicle, &vehicles) else {
+            continue;
+        };
+        let Ok((vehicle_position, vehicle_look_direction)) = vehicle_query.get(vehicle) else {
+            continue;
+        };
+        send_packet_events.send(SendPacketEvent::new(
+            entity,
+            ServerboundMoveVehicle {
+                pos: **vehicle_position,
+                look_direction: *vehicle_look_direction,
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_core::resource_location::ResourceLocation;
+    use azalea_entity::EntityDimensions;
+    use azalea_protocol::packets::game::ServerboundGamePacket;
+    use azalea_world::{ChunkStorage, Instance};
+    use parking_lot::RwLock;
+
+    use super::*;
+    use crate::WalkDirection;
+
+    #[test]
+    fn test_steerable() {
+        assert!(is_boat(azalea_registry::EntityKind::BambooRaft));
+        assert!(is_steerable(azalea_registry::EntityKind::OakChestBoat));
+        assert!(is_steerable(azalea_registry::EntityKind::Horse));
+        assert!(!is_steerable(azalea_registry::EntityKind::Pig));
+        assert!(!is_boat(azalea_registry::EntityKind::Minecart));
+    }
+
+    /// Make an app with a local player in a boat. The instance has to be kept
+    /// alive since the app only has a weak reference to it.
+    fn app_with_boat() -> (App, Entity, Arc<RwLock<Instance>>) {
+        let instance_name = ResourceLocation::new("azalea:test");
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+
+        let mut app = App::new();
+        let mut instance_container = InstanceContainer::new();
+        instance_container
+            .instances
+            .insert(instance_name.clone(), Arc::downgrade(&instance));
+        app.insert_resource(instance_container)
+            .add_event::<SendPacketEvent>()
+            .add_systems(Update, steer_vehicle);
+
+        let position = Vec3::new(0.5, 64., 0.5);
+        let boat = app
+            .world_mut()
+            .spawn((
+                EntityKind(azalea_registry::EntityKind::OakBoat),
+                Position::new(position),
+                Physics::new(
+                    EntityDimensions::from(azalea_registry::EntityKind::OakBoat),
+                    position,
+                ),
+                LookDirection::default(),
+            ))
+            .id();
+        let player = app
+            .world_mut()
+            .spawn((
+                LocalEntity,
+                Vehicle(boat),
+                PhysicsState::default(),
+                LookDirection::default(),
+                InstanceName(instance_name),
+            ))
+            .id();
+        app.world_mut()
+            .entity_mut(boat)
+            .insert(Passengers(vec![player]));
+        (app, player, instance)
+    }
+
+    fn sent_paddles(app: &App) -> Vec<(bool, bool)> {
+        app.world()
+            .resource::<Events<SendPacketEvent>>()
+            .iter_current_update_events()
+            .filter_map(|event| match &event.packet {
+                ServerboundGamePacket::PaddleBoat(p) => Some((p.left, p.right)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paddle_only_sent_when_changed() {
+        let (mut app, player, _instance) = app_with_boat();
+
+        // sitting still in a boat doesn't send anything
+        app.update();
+        assert_eq!(sent_paddles(&app), vec![]);
+        app.update();
+        assert_eq!(sent_paddles(&app), vec![]);
+
+        app.world_mut()
+            .get_mut::<PhysicsState>(player)
+            .unwrap()
+            .forward_impulse = 1.;
+        app.update();
+        assert_eq!(sent_paddles(&app), vec![(true, true)]);
+        // still going forward, so there's nothing new to tell the server
+        app.update();
+        assert_eq!(sent_paddles(&app), vec![]);
+
+        let mut physics_state = app.world_mut().get_mut::<PhysicsState>(player).unwrap();
+        physics_state.forward_impulse = 0.;
+        physics_state.move_direction = WalkDirection::None;
+        app.update();
+        assert_eq!(sent_paddles(&app), vec![(false, false)]);
+    }
+}
@@ -0,0 +1,205 @@
+//! Connect directly to backend servers that are behind a Velocity proxy with
+//! modern forwarding enabled.
+//!
+//! Velocity normally tells the backend server who's joining by answering a
+//! login plugin request on the `velocity:player_info` channel, signed with a
+//! secret that's shared between the proxy and the backend. If you know the
+//! secret, this plugin lets bots answer it themselves so they don't have to go
+//! through the proxy.
+//!
+//! ```no_run
+//! # use azalea_client::velocity_forwarding::VelocityForwardingPlugin;
+//! # fn example(app: &mut bevy_app::App) {
+//! app.add_plugins(VelocityForwardingPlugin::new("the forwarding secret"));
+//! # }
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use azalea_auth::game_profile::GameProfile;
+use azalea_buf::{AzaleaWrite, AzaleaWriteVar, UnsizedByteArray};
+use azalea_protocol::packets::login::{
+    s_custom_query_answer::ServerboundCustomQueryAnswer, ClientboundLoginPacket,
+};
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::{
+    packet_handling::login::{self, IgnoreQueryIds, LoginPacketEvent, SendLoginPacketEvent},
+    Account,
+};
+
+/// The channel that Velocity uses to send player info to backend servers.
+pub const PLAYER_INFO_CHANNEL: &str = "velocity:player_info";
+/// The version of the forwarding protocol that we use. This is
+/// `MODERN_DEFAULT` in Velocity, which is the only one that doesn't need the
+/// player's chat signing key.
+const FORWARDING_VERSION: i32 = 1;
+
+/// A plugin that answers Velocity's player info requests with the given
+/// forwarding secret.
+///
+/// This isn't part of the default plugins, so you have to add it yourself.
+#[derive(Clone)]
+pub struct VelocityForwardingPlugin {
+    /// The secret from Velocity's `forwarding.secret` file.
+    pub secret: String,
+    /// The IP address that we tell the backend server we're connecting from.
+    ///
+    /// This defaults to `127.0.0.1`.
+    pub client_address: IpAddr,
+}
+impl VelocityForwardingPlugin {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            client_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+}
+impl Plugin for VelocityForwardingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(VelocityForwarding {
+            secret: self.secret.clone(),
+            client_address: self.client_address,
+        })
+        .add_systems(
+            PreUpdate,
+            answer_player_info_queries
+                .before(login::process_packet_events)
+                .before(login::handle_send_packet_event),
+        );
+    }
+}
+
+/// The resource with the settings from [`VelocityForwardingPlugin`]. You can
+/// change this at runtime.
+#[derive(Resource, Clone, Debug)]
+pub struct VelocityForwarding {
+    pub secret: String,
+    pub client_address: IpAddr,
+}
+
+pub fn answer_player_info_queries(
+    mut events: EventReader<LoginPacketEvent>,
+    mut query: Query<(&Account, &mut IgnoreQueryIds)>,
+    forwarding: Res<VelocityForwarding>,
+    mut send_packet_events: EventWriter<SendLoginPacketEvent>,
+) {
+    for event in events.read() {
+        let ClientboundLoginPacket::CustomQuery(p) = event.packet.as_ref() else {
+            continue;
+        };
+        if p.identifier.to_string() != PLAYER_INFO_CHANNEL {
+            continue;
+        }
+        let Ok((account, mut ignore_query_ids)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        debug!(
+            "Answering Velocity player info request for {}",
+            account.username
+        );
+
+        let profile = GameProfile::new(account.uuid_or_offline(), account.username.clone());
+        let data = signed_player_info(&forwarding, &profile);
+
+        // so the query isn't answered with nothing
+        ignore_query_ids.insert(p.transaction_id);
+        send_packet_events.send(SendLoginPacketEvent::new(
+            event.entity,
+            ServerboundCustomQueryAnswer {
+                transaction_id: p.transaction_id,
+                data: Some(UnsizedByteArray::from(data)),
+            },
+        ));
+    }
+}
+
+/// Encode the player info the same way Velocity does, with the signature at
+/// the start.
+fn signed_player_info(forwarding: &VelocityForwarding, profile: &GameProfile) -> Vec<u8> {
+    let mut payload = Vec::new();
+    FORWARDING_VERSION
+        .azalea_write_var(&mut payload)
+        .expect("writing to a vec can't fail");
+    forwarding
+        .client_address
+        .to_string()
+        .azalea_write(&mut payload)
+        .expect("writing to a vec can't fail");
+    // this is encoded the same way as in the protocol
+    profile
+        .azalea_write(&mut payload)
+        .expect("writing to a vec can't fail");
+
+    let signature = azalea_crypto::hmac_sha256(forwarding.secret.as_bytes(), &payload);
+    let mut data = signature.to_vec();
+    data.extend(payload);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use azalea_auth::game_profile::ProfilePropertyValue;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_signed_player_info() {
+        let forwarding = VelocityForwarding {
+            secret: "secret".to_owned(),
+            client_address: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        };
+        let profile = GameProfile {
+            uuid: Uuid::from_u128(0x0123456789abcdef0123456789abcdef),
+            name: "bot".to_owned(),
+            properties: HashMap::from([(
+                "textures".to_owned(),
+                ProfilePropertyValue {
+                    value: "abc".to_owned(),
+                    signature: Some("sig".to_owned()),
+                },
+            )]),
+        };
+        let data = signed_player_info(&forwarding, &profile);
+
+        // HMAC-SHA256 of the rest of the data with the key "secret"
+        let signature = [
+            0xc0, 0xbf, 0x99, 0x1f, 0xc8, 0x34, 0x29, 0xe1, 0x37, 0xbf, 0x36, 0x73, 0xd8, 0xf6,
+            0xe3, 0x51, 0x2e, 0x17, 0xfc, 0xd8, 0x22, 0x01, 0x9c, 0xae, 0x37, 0x97, 0x12, 0xc4,
+            0xea, 0xeb, 0x77, 0x64,
+        ];
+        let payload: &[&[u8]] = &[
+            // forwarding version
+            &[1],
+            // client address
+            &[8],
+            b"10.0.0.1",
+            // uuid
+            &[
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+                0xcd, 0xef,
+            ],
+            // username
+            &[3],
+            b"bot",
+            // properties
+            &[1],
+            &[8],
+            b"textures",
+            &[3],
+            b"abc",
+            // has signature
+            &[1],
+            &[3],
+            b"sig",
+        ];
+        assert_eq!(data[..32], signature);
+        assert_eq!(data[32..], payload.concat());
+    }
+}
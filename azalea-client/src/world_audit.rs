@@ -0,0 +1,352 @@
+//! A debugging tool for checking that the world we have stored matches what
+//! the server sent us.
+//!
+//! When this is enabled with [`Client::enable_world_audit`], the raw data of
+//! every chunk packet and every block update that we receive is kept around.
+//! Calling [`Client::audit_world`] parses the chunks again from scratch,
+//! replays the block updates on top, and compares the result to what's in the
+//! [`ChunkStorage`]. This is useful for tracking down bugs in chunk parsing or
+//! in the code that modifies the world.
+//!
+//! Keeping the raw packets around uses a lot of memory, so you shouldn't
+//! leave this enabled unless you're debugging something.
+//!
+//! [`ChunkStorage`]: azalea_world::ChunkStorage
+
+use std::{collections::HashMap, io::Cursor, ops::Deref};
+
+use azalea_block::BlockState;
+use azalea_core::position::{BlockPos, ChunkBlockPos, ChunkPos};
+use azalea_protocol::packets::game::ClientboundGamePacket;
+use azalea_world::{Chunk, Instance};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use simdnbt::owned::BaseNbt;
+use thiserror::Error;
+
+use crate::{error::ClientError, packet_handling::game::PacketEvent, Client};
+
+pub struct WorldAuditPlugin;
+impl Plugin for WorldAuditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, record_world_packets);
+    }
+}
+
+/// The packets that we need to rebuild the world from scratch. This is only
+/// present on clients that have had [`Client::enable_world_audit`] called.
+#[derive(Component, Clone, Debug, Default)]
+pub struct WorldAuditLog {
+    /// The raw data for the most recent chunk packet that we got at each
+    /// position.
+    pub chunks: HashMap<ChunkPos, Vec<u8>>,
+    /// The block updates that happened in each chunk after we received it.
+    pub block_updates: HashMap<ChunkPos, HashMap<BlockPos, BlockState>>,
+}
+
+/// The result of [`Client::audit_world`].
+#[derive(Clone, Debug, Default)]
+pub struct WorldAuditReport {
+    /// The number of chunks that were compared.
+    pub chunks_checked: usize,
+    pub divergences: Vec<ChunkDivergence>,
+}
+impl WorldAuditReport {
+    /// Whether the stored world matched what the server sent for every chunk.
+    pub fn is_ok(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// A chunk where the stored world doesn't match what the server sent.
+#[derive(Clone, Debug)]
+pub struct ChunkDivergence {
+    pub pos: ChunkPos,
+    pub kind: DivergenceKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum DivergenceKind {
+    /// The server sent us this chunk but it's not in our world.
+    Missing,
+    /// The chunk packet couldn't be parsed again.
+    ParseError(String),
+    /// Some blocks in the chunk are different from what the server sent.
+    Blocks(Vec<BlockDivergence>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockDivergence {
+    pub pos: BlockPos,
+    /// The block that the server told us is here.
+    pub expected: BlockState,
+    /// The block that's actually in our world.
+    pub actual: BlockState,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WorldAuditError {
+    #[error("The world audit isn't enabled, call Client::enable_world_audit first")]
+    NotEnabled,
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+impl Client {
+    /// Start keeping the packets that are needed for [`Self::audit_world`].
+    ///
+    /// Only chunks that are received after this is called can be checked.
+    pub fn enable_world_audit(&self) {
+        let mut ecs = self.ecs.lock();
+        let mut entity = ecs.entity_mut(self.entity);
+        if !entity.contains::<WorldAuditLog>() {
+            entity.insert(WorldAuditLog::default());
+        }
+    }
+
+    /// Stop keeping packets for auditing the world, and free the memory that
+    /// was used for them.
+    pub fn disable_world_audit(&self) {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .remove::<WorldAuditLog>();
+    }
+
+    /// Compare the blocks in our world to what the server sent us, and return
+    /// every chunk that's different.
+    ///
+    /// This is slow since it has to parse every chunk again, so it should only
+    /// be used for debugging. Note that blocks that we changed ourselves (like
+    /// by mining) without the server confirming it yet will also show up as
+    /// divergences.
+    ///
+    /// Returns [`WorldAuditError::NotEnabled`] if
+    /// [`Self::enable_world_audit`] wasn't called.
+    pub fn audit_world(&self) -> Result<WorldAuditReport, WorldAuditError> {
+        let instance = self.try_world()?;
+        match self.try_map_component::<WorldAuditLog, _>(|log| audit_chunks(log, &instance.read()))
+        {
+            Ok(report) => Ok(report),
+            // we already know that we're in the world, so the only component that can be
+            // missing is the log
+            Err(ClientError::MissingComponent { .. }) => Err(WorldAuditError::NotEnabled),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Parse every chunk in the log again, replay the block updates on top, and
+/// compare them to the chunks in the instance.
+pub fn audit_chunks(log: &WorldAuditLog, instance: &Instance) -> WorldAuditReport {
+    let height = instance.chunks.height;
+    let min_y = instance.chunks.min_y;
+
+    let empty_nbt = BaseNbt::default();
+    let mut report = WorldAuditReport::default();
+    for (&pos, data) in &log.chunks {
+        report.chunks_checked += 1;
+
+        let Some(stored_chunk) = instance.chunks.get(&pos) else {
+            report.divergences.push(ChunkDivergence {
+                pos,
+                kind: DivergenceKind::Missing,
+            });
+            continue;
+        };
+        let mut expected_chunk = match Chunk::read_with_dimension_height(
+            &mut Cursor::new(data.as_slice()),
+            height,
+            min_y,
+            empty_nbt.deref(),
+        ) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                report.divergences.push(ChunkDivergence {
+                    pos,
+                    kind: DivergenceKind::ParseError(e.to_string()),
+                });
+                continue;
+            }
+        };
+        for (block_pos, &state) in log.block_updates.get(&pos).into_iter().flatten() {
+            expected_chunk.set(&ChunkBlockPos::from(block_pos), state, min_y);
+        }
+
+        let blocks = compare_chunks(pos, &expected_chunk, &stored_chunk.read(), height, min_y);
+        if !blocks.is_empty() {
+            report.divergences.push(ChunkDivergence {
+                pos,
+                kind: DivergenceKind::Blocks(blocks),
+            });
+        }
+    }
+    report
+}
+
+fn compare_chunks(
+    chunk_pos: ChunkPos,
+    expected: &Chunk,
+    actual: &Chunk,
+    height: u32,
+    min_y: i32,
+) -> Vec<BlockDivergence> {
+    let mut divergences = Vec::new();
+    for y in min_y..min_y + height as i32 {
+        for z in 0..16 {
+            for x in 0..16 {
+                let chunk_block_pos = ChunkBlockPos::new(x, y, z);
+                let expected_state = expected.get(&chunk_block_pos, min_y).unwrap_or_default();
+                let actual_state = actual.get(&chunk_block_pos, min_y).unwrap_or_default();
+                if expected_state != actual_state {
+                    divergences.push(BlockDivergence {
+                        pos: BlockPos::new(
+                            chunk_pos.x * 16 + x as i32,
+                            y,
+                            chunk_pos.z * 16 + z as i32,
+                        ),
+                        expected: expected_state,
+                        actual: actual_state,
+                    });
+                }
+            }
+        }
+    }
+    divergences
+}
+
+pub fn record_world_packets(
+    mut events: EventReader<PacketEvent>,
+    mut query: Query<&mut WorldAuditLog>,
+) {
+    for event in events.read() {
+        let Ok(mut log) = query.get_mut(event.entity) else {
+            continue;
+        };
+        match event.packet.as_ref() {
+            ClientboundGamePacket::LevelChunkWithLight(p) => {
+                let pos = ChunkPos::new(p.x, p.z);
+                log.chunks.insert(pos, p.chunk_data.data.clone());
+                log.block_updates.remove(&pos);
+            }
+            ClientboundGamePacket::ForgetLevelChunk(p) => {
+                log.chunks.remove(&p.pos);
+                log.block_updates.remove(&p.pos);
+            }
+            ClientboundGamePacket::BlockUpdate(p) => {
+                log.block_updates
+                    .entry(ChunkPos::from(&p.pos))
+                    .or_default()
+                    .insert(p.pos, p.block_state);
+            }
+            ClientboundGamePacket::SectionBlocksUpdate(p) => {
+                for state in &p.states {
+                    let pos = p.section_pos + state.pos;
+                    log.block_updates
+                        .entry(ChunkPos::from(&pos))
+                        .or_default()
+                        .insert(pos, state.state);
+                }
+            }
+            ClientboundGamePacket::Login(_) | ClientboundGamePacket::Respawn(_) => {
+                // we might be in a different dimension now
+                log.chunks.clear();
+                log.block_updates.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_block::blocks;
+    use azalea_buf::AzaleaWrite;
+    use azalea_world::{ChunkStorage, PartialChunkStorage};
+
+    use super::*;
+
+    fn chunk_data(chunk: &Chunk) -> Vec<u8> {
+        let mut data = Vec::new();
+        chunk.azalea_write(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_audit_chunks() {
+        let mut instance = Instance::from(ChunkStorage::default());
+        let min_y = instance.chunks.min_y;
+        let pos = ChunkPos::new(0, 0);
+        let stone_pos = BlockPos::new(1, 64, 1);
+
+        let mut chunk = Chunk::default();
+        chunk.set(
+            &ChunkBlockPos::from(&stone_pos),
+            blocks::Stone {}.into(),
+            min_y,
+        );
+        let mut log = WorldAuditLog::default();
+        log.chunks.insert(pos, chunk_data(&chunk));
+
+        let report = audit_chunks(&log, &instance);
+        assert_eq!(report.chunks_checked, 1);
+        assert!(matches!(
+            report.divergences[..],
+            [ChunkDivergence {
+                kind: DivergenceKind::Missing,
+                ..
+            }]
+        ));
+
+        let mut partial_chunks = PartialChunkStorage::new(8);
+        partial_chunks.set(&pos, Some(chunk), &mut instance.chunks);
+        assert!(audit_chunks(&log, &instance).is_ok());
+
+        // the server told us that the stone was mined, but our world still has it
+        log.block_updates
+            .entry(pos)
+            .or_default()
+            .insert(stone_pos, BlockState::AIR);
+        let report = audit_chunks(&log, &instance);
+        let [ChunkDivergence {
+            kind: DivergenceKind::Blocks(blocks),
+            ..
+        }] = &report.divergences[..]
+        else {
+            panic!("expected one chunk with divergent blocks: {report:?}");
+        };
+        assert_eq!(
+            blocks,
+            &[BlockDivergence {
+                pos: stone_pos,
+                expected: BlockState::AIR,
+                actual: blocks::Stone {}.into(),
+            }]
+        );
+
+        instance.chunks.set_block_state(&stone_pos, BlockState::AIR);
+        assert!(audit_chunks(&log, &instance).is_ok());
+    }
+
+    #[test]
+    fn test_audit_unparseable_chunk() {
+        let mut instance = Instance::from(ChunkStorage::default());
+        let mut log = WorldAuditLog::default();
+        log.chunks.insert(ChunkPos::new(0, 0), vec![0xff; 4]);
+
+        let mut partial_chunks = PartialChunkStorage::new(8);
+        partial_chunks.set(
+            &ChunkPos::new(0, 0),
+            Some(Chunk::default()),
+            &mut instance.chunks,
+        );
+        let report = audit_chunks(&log, &instance);
+        assert!(matches!(
+            report.divergences[..],
+            [ChunkDivergence {
+                kind: DivergenceKind::ParseError(_),
+                ..
+            }]
+        ));
+    }
+}
@@ -0,0 +1,441 @@
+//! Save the chunks that bots receive into region files, which turns any bot
+//! into a world downloader.
+//!
+//! ```no_run
+//! # use azalea_client::world_download::WorldDownloadPlugin;
+//! # fn example(app: &mut bevy_app::App) {
+//! app.add_plugins(WorldDownloadPlugin::new("downloaded_world"));
+//! # }
+//! ```
+//!
+//! Chunks are marked as changed when we receive them and when blocks in them
+//! are updated, and the changed chunks are written by a background thread every
+//! [`WorldDownloadPlugin::save_interval`]. Chunks that are about to be unloaded
+//! are written right away, and everything that's left is written when the app
+//! exits (or the [`WorldDownloader`] is dropped). Each dimension is put where
+//! vanilla would put it (see [`anvil::region_dir`]), and chunks that are shared
+//! by clients in a swarm are only written once.
+//!
+//! Only blocks, biomes, and heightmaps are saved, and vanilla calculates the
+//! light when it loads the chunks. There's no `level.dat`, so to open the
+//! world in vanilla, copy the dimension directories into an existing world.
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Weak},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use azalea_core::{
+    position::ChunkPos, registry_holder::RegistryHolder, resource_location::ResourceLocation,
+};
+use azalea_protocol::packets::game::ClientboundGamePacket;
+use azalea_world::{anvil, Chunk, Instance, InstanceName};
+use bevy_app::{App, AppExit, Last, Plugin, PreUpdate, Update};
+use bevy_ecs::prelude::*;
+use parking_lot::RwLock;
+use tracing::error;
+
+use crate::{
+    chunks::{self, ChunkLoadedEvent},
+    packet_handling::game::{self, PacketEvent},
+    InstanceHolder,
+};
+
+/// A plugin that saves the chunks that every client receives into a world
+/// directory.
+///
+/// This isn't part of the default plugins, so you have to add it yourself.
+#[derive(Clone)]
+pub struct WorldDownloadPlugin {
+    /// The directory of the world that the chunks are saved in. It's created
+    /// if it doesn't exist.
+    pub world_dir: PathBuf,
+    /// How often the chunks that changed are saved.
+    pub save_interval: Duration,
+}
+
+impl WorldDownloadPlugin {
+    pub fn new(world_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            world_dir: world_dir.into(),
+            save_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// Change how often the chunks that changed are saved. The default is 10
+    /// seconds.
+    #[must_use]
+    pub fn save_interval(mut self, save_interval: Duration) -> Self {
+        self.save_interval = save_interval;
+        self
+    }
+}
+
+impl Plugin for WorldDownloadPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        let writer_thread = match thread::Builder::new()
+            .name("world download".to_owned())
+            .spawn(move || write_saved_chunks(receiver))
+        {
+            Ok(writer_thread) => writer_thread,
+            Err(e) => {
+                error!("Couldn't start the world download thread: {e}");
+                return;
+            }
+        };
+
+        app.insert_resource(WorldDownloader {
+            world_dir: self.world_dir.clone(),
+            save_interval: self.save_interval,
+            last_save: Instant::now(),
+            instances: HashMap::new(),
+            sender,
+            writer_thread: Some(writer_thread),
+        })
+        .add_systems(
+            PreUpdate,
+            save_chunks_before_unloading.before(game::process_packet_events),
+        )
+        .add_systems(
+            Update,
+            (mark_changed_chunks, save_changed_chunks)
+                .chain()
+                .after(chunks::handle_decoded_chunks),
+        )
+        .add_systems(Last, save_chunks_on_exit);
+    }
+}
+
+/// The state of the [`WorldDownloadPlugin`].
+#[derive(Resource)]
+pub struct WorldDownloader {
+    world_dir: PathBuf,
+    save_interval: Duration,
+    last_save: Instant,
+    instances: HashMap<ResourceLocation, DownloadedInstance>,
+    sender: mpsc::Sender<SavedChunks>,
+    /// The thread that writes the chunks, which is `None` after
+    /// [`Self::shutdown`].
+    writer_thread: Option<JoinHandle<()>>,
+}
+
+impl WorldDownloader {
+    /// The number of chunks that changed since they were last saved.
+    pub fn unsaved_chunk_count(&self) -> usize {
+        self.instances
+            .values()
+            .map(|instance| instance.changed_chunks.len())
+            .sum()
+    }
+
+    /// Save every chunk that changed now instead of waiting for the save
+    /// interval. The chunks are written in the background.
+    pub fn save_all(&mut self) {
+        for downloaded_instance in self.instances.values_mut() {
+            downloaded_instance.save(&self.sender);
+        }
+    }
+
+    /// Save every chunk that changed and block until they're all written.
+    ///
+    /// Nothing is saved after this, which is why it's only done when the app
+    /// exits or the downloader is dropped.
+    pub fn shutdown(&mut self) {
+        self.save_all();
+        // the thread stops once it wrote everything that was sent and there's no
+        // sender left
+        drop(mem::replace(&mut self.sender, mpsc::channel().0));
+        if let Some(writer_thread) = self.writer_thread.take() {
+            if writer_thread.join().is_err() {
+                error!("The world download thread panicked");
+            }
+        }
+    }
+
+    /// Mark the chunk as changed so it's saved next time.
+    fn mark_changed(
+        &mut self,
+        name: &InstanceName,
+        instance_holder: &InstanceHolder,
+        pos: ChunkPos,
+    ) {
+        let world_dir = &self.world_dir;
+        let downloaded_instance = self
+            .instances
+            .entry(name.0.clone())
+            .or_insert_with(|| DownloadedInstance::new(world_dir, name, &instance_holder.instance));
+        if downloaded_instance.instance.as_ptr() != Arc::as_ptr(&instance_holder.instance) {
+            // we're in a different instance that has the same name, probably
+            // because we joined another server
+            downloaded_instance.save(&self.sender);
+            *downloaded_instance =
+                DownloadedInstance::new(world_dir, name, &instance_holder.instance);
+        }
+        downloaded_instance.changed_chunks.insert(pos);
+    }
+}
+
+impl Drop for WorldDownloader {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// An instance that we're saving chunks from.
+struct DownloadedInstance {
+    instance: Weak<RwLock<Instance>>,
+    region_dir: PathBuf,
+    min_y: i32,
+    /// These are used for getting the names of biomes.
+    registries: Arc<RegistryHolder>,
+    changed_chunks: HashSet<ChunkPos>,
+}
+
+impl DownloadedInstance {
+    fn new(world_dir: &Path, name: &InstanceName, instance: &Arc<RwLock<Instance>>) -> Self {
+        let instance_lock = instance.read();
+        Self {
+            instance: Arc::downgrade(instance),
+            region_dir: anvil::region_dir(world_dir, name),
+            min_y: instance_lock.chunks.min_y,
+            registries: Arc::new(instance_lock.registries.clone()),
+            changed_chunks: HashSet::new(),
+        }
+    }
+
+    /// Send the chunks that changed to the background thread to be saved.
+    fn save(&mut self, sender: &mpsc::Sender<SavedChunks>) {
+        self.save_only(sender, |_| true);
+    }
+
+    /// Like [`Self::save`], but only for the changed chunks that match the
+    /// filter.
+    fn save_only(
+        &mut self,
+        sender: &mpsc::Sender<SavedChunks>,
+        mut filter: impl FnMut(&ChunkPos) -> bool,
+    ) {
+        let mut positions = Vec::new();
+        self.changed_chunks.retain(|pos| {
+            if filter(pos) {
+                positions.push(*pos);
+                false
+            } else {
+                true
+            }
+        });
+        if positions.is_empty() {
+            return;
+        }
+        // if the instance is gone then so are its chunks
+        let Some(instance) = self.instance.upgrade() else {
+            return;
+        };
+
+        let instance = instance.read();
+        let chunks = positions
+            .into_iter()
+            .filter_map(|pos| Some((pos, instance.chunks.get(&pos)?.read().clone())))
+            .collect::<Vec<_>>();
+        if chunks.is_empty() {
+            return;
+        }
+        let _ = sender.send(SavedChunks {
+            region_dir: self.region_dir.clone(),
+            min_y: self.min_y,
+            registries: self.registries.clone(),
+            chunks,
+        });
+    }
+}
+
+/// Copies of chunks that are sent to the background thread to be saved.
+struct SavedChunks {
+    region_dir: PathBuf,
+    min_y: i32,
+    registries: Arc<RegistryHolder>,
+    chunks: Vec<(ChunkPos, Chunk)>,
+}
+
+fn write_saved_chunks(receiver: mpsc::Receiver<SavedChunks>) {
+    for saved_chunks in receiver {
+        let chunks = saved_chunks.chunks.iter().map(|(pos, chunk)| {
+            (
+                *pos,
+                anvil::write_chunk(chunk, *pos, saved_chunks.min_y, &saved_chunks.registries),
+            )
+        });
+        if let Err(e) = anvil::write_region_dir(&saved_chunks.region_dir, chunks) {
+            error!(
+                "Couldn't save chunks to {}: {e}",
+                saved_chunks.region_dir.display()
+            );
+        }
+    }
+}
+
+pub fn mark_changed_chunks(
+    mut chunk_loaded_events: EventReader<ChunkLoadedEvent>,
+    mut packet_events: EventReader<PacketEvent>,
+    query: Query<(&InstanceName, &InstanceHolder)>,
+    mut world_downloader: ResMut<WorldDownloader>,
+) {
+    let loaded_chunks = chunk_loaded_events
+        .read()
+        .map(|event| (event.entity, event.pos));
+    let updated_chunks = packet_events
+        .read()
+        .flat_map(|event| match event.packet.as_ref() {
+            ClientboundGamePacket::BlockUpdate(p) => vec![(event.entity, ChunkPos::from(&p.pos))],
+            ClientboundGamePacket::SectionBlocksUpdate(p) => {
+                vec![(event.entity, ChunkPos::from(p.section_pos))]
+            }
+            _ => vec![],
+        });
+
+    for (entity, pos) in loaded_chunks.chain(updated_chunks) {
+        let Ok((instance_name, instance_holder)) = query.get(entity) else {
+            continue;
+        };
+        world_downloader.mark_changed(instance_name, instance_holder, pos);
+    }
+}
+
+pub fn save_changed_chunks(mut world_downloader: ResMut<WorldDownloader>) {
+    if world_downloader.last_save.elapsed() < world_downloader.save_interval {
+        return;
+    }
+    world_downloader.last_save = Instant::now();
+
+    world_downloader.save_all();
+    // forget about instances that don't exist anymore
+    world_downloader
+        .instances
+        .retain(|_, downloaded_instance| downloaded_instance.instance.strong_count() > 0);
+}
+
+/// Save the chunks that changed right before the server makes us unload them,
+/// since they might be gone by the time we'd normally save them.
+pub fn save_chunks_before_unloading(
+    mut packet_events: EventReader<PacketEvent>,
+    query: Query<&InstanceName>,
+    mut world_downloader: ResMut<WorldDownloader>,
+) {
+    let WorldDownloader {
+        instances, sender, ..
+    } = &mut *world_downloader;
+    for event in packet_events.read() {
+        let Ok(instance_name) = query.get(event.entity) else {
+            continue;
+        };
+        let Some(downloaded_instance) = instances.get_mut(&instance_name.0) else {
+            continue;
+        };
+        match event.packet.as_ref() {
+            ClientboundGamePacket::ForgetLevelChunk(p) => {
+                downloaded_instance.save_only(sender, |pos| *pos == p.pos);
+            }
+            ClientboundGamePacket::Login(_) | ClientboundGamePacket::Respawn(_) => {
+                // we might be going to a different dimension
+                downloaded_instance.save(sender);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write everything that's left before the app exits, since the background
+/// thread would be killed with the process otherwise.
+pub fn save_chunks_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    mut world_downloader: ResMut<WorldDownloader>,
+) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    world_downloader.shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use azalea_block::blocks;
+    use azalea_core::position::ChunkBlockPos;
+    use azalea_world::ChunkStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_save_chunks_on_exit() {
+        let world_dir =
+            std::env::temp_dir().join(format!("azalea-world-download-test-{}", std::process::id()));
+
+        let mut app = App::new();
+        app.add_plugins(WorldDownloadPlugin::new(&world_dir))
+            .add_event::<PacketEvent>()
+            .add_event::<ChunkLoadedEvent>();
+
+        let instance = Arc::new(RwLock::new(Instance::from(ChunkStorage::default())));
+        let entity = app
+            .world_mut()
+            .spawn(InstanceName(ResourceLocation::new("minecraft:overworld")))
+            .id();
+        let instance_holder = InstanceHolder::new(entity, instance.clone());
+        let mut chunk = Chunk::default();
+        chunk.set(
+            &ChunkBlockPos::new(1, 2, 3),
+            blocks::Stone {}.into(),
+            instance.read().chunks.min_y,
+        );
+        instance_holder.partial_instance.write().chunks.set(
+            &ChunkPos::new(0, 0),
+            Some(chunk),
+            &mut instance.write().chunks,
+        );
+        app.world_mut().entity_mut(entity).insert(instance_holder);
+
+        app.world_mut().send_event(ChunkLoadedEvent {
+            entity,
+            pos: ChunkPos::new(0, 0),
+        });
+        app.update();
+        // it's not time to save yet
+        let world_downloader = app.world().resource::<WorldDownloader>();
+        assert_eq!(world_downloader.unsaved_chunk_count(), 1);
+        assert!(world_downloader.writer_thread.is_some());
+
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        let world_downloader = app.world().resource::<WorldDownloader>();
+        assert_eq!(world_downloader.unsaved_chunk_count(), 0);
+        // the thread was joined, so the chunk is already written
+        assert!(world_downloader.writer_thread.is_none());
+        let region = anvil::RegionFile::open(world_dir.join("region").join("r.0.0.mca")).unwrap();
+        assert!(region.contains(&ChunkPos::new(0, 0)));
+        let (height, min_y) = {
+            let instance = instance.read();
+            (instance.chunks.height, instance.chunks.min_y)
+        };
+        let saved_chunk = region
+            .read_chunk(
+                &ChunkPos::new(0, 0),
+                height,
+                min_y,
+                &RegistryHolder::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            saved_chunk.get(&ChunkBlockPos::new(1, 2, 3), min_y),
+            Some(blocks::Stone {}.into())
+        );
+
+        fs::remove_dir_all(world_dir).unwrap();
+    }
+}
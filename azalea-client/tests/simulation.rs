@@ -1,34 +1,58 @@
-use std::{fmt::Debug, sync::Arc, time::Duration};
+use std::{fmt::Debug, io::Cursor, sync::Arc, time::Duration};
 
 use azalea_auth::game_profile::GameProfile;
+use azalea_block::{blocks, BlockState};
+use azalea_buf::AzaleaWrite;
 use azalea_client::{
+    chunks::{ChunkLoadedEvent, PendingChunks},
     events::LocalPlayerEvents,
-    raw_connection::{RawConnection, RawConnectionReader, RawConnectionWriter},
-    ClientInformation, GameProfileComponent, InConfigState, InstanceHolder, LocalPlayerBundle,
+    mining::FinishMiningBlockEvent,
+    packet_handling::{
+        game::{
+            BlockUpdateEvent, ExperienceOrbPickupEvent, ItemPickupEvent, SectionBlocksUpdateEvent,
+        },
+        UnknownPacketEvent,
+    },
+    raw_connection::{RawConnection, RawConnectionReader, RawConnectionWriter, TakeOverSenders},
+    ClientInformation, GameProfileComponent, Hunger, InConfigState, InstanceHolder,
+    LocalPlayerBundle, MeasurePing, Ping, ShareInstances,
 };
 use azalea_core::{
+    bitset::BitSet,
     game_type::{GameMode, OptionalGameType},
+    position::{BlockPos, ChunkPos, ChunkSectionBlockPos, ChunkSectionPos, Vec3},
     resource_location::ResourceLocation,
     tick::GameTick,
 };
 use azalea_entity::{
+    indexing::{EntityIdIndex, LocalPlayerUuidIndex},
     metadata::{Health, PlayerMetadataBundle},
-    LocalEntity,
+    EntityDataItem, EntityDataValue, EntityMetadataItems, LocalEntity,
 };
+use azalea_inventory::{ItemStack, ItemStackData};
 use azalea_protocol::packets::{
     common::CommonPlayerSpawnInfo,
     config::{ClientboundFinishConfiguration, ClientboundRegistryData},
-    game::{ClientboundLogin, ClientboundSetHealth},
+    game::{
+        c_level_chunk_with_light::ClientboundLevelChunkPacketData,
+        c_light_update::ClientboundLightUpdatePacketData,
+        c_player_info_update::{ActionEnumSet, PlayerInfoEntry},
+        c_section_blocks_update::BlockStateWithPosition,
+        ClientboundAddEntity, ClientboundAddExperienceOrb, ClientboundBlockUpdate,
+        ClientboundKeepAlive, ClientboundLevelChunkWithLight, ClientboundLogin,
+        ClientboundPlayerInfoUpdate, ClientboundPongResponse, ClientboundSectionBlocksUpdate,
+        ClientboundSetEntityData, ClientboundSetHealth, ClientboundTakeItemEntity,
+        ServerboundGamePacket,
+    },
     ConnectionProtocol, Packet, ProtocolPacket,
 };
-use azalea_registry::DimensionType;
-use azalea_world::Instance;
+use azalea_world::{Chunk, Instance, MinecraftEntityId};
 use bevy_app::App;
 use bevy_app::PluginGroup;
 use bevy_ecs::{prelude::*, schedule::ExecutorKind};
 use bevy_log::{tracing_subscriber, LogPlugin};
 use parking_lot::{Mutex, RwLock};
-use simdnbt::owned::{NbtCompound, NbtTag};
+use simdnbt::owned::{Nbt, NbtCompound, NbtTag};
 use tokio::{sync::mpsc, time::sleep};
 use uuid::Uuid;
 
@@ -77,7 +101,7 @@ fn test_set_health_before_login() {
         show_death_screen: true,
         do_limited_crafting: false,
         common: CommonPlayerSpawnInfo {
-            dimension_type: DimensionType::Overworld,
+            dimension_type: 0,
             dimension: ResourceLocation::new("minecraft:overworld"),
             seed: 0,
             game_type: GameMode::Survival,
@@ -96,6 +120,593 @@ fn test_set_health_before_login() {
     assert_eq!(*simulation.component::<Health>(), 15.);
 }
 
+#[test]
+fn test_custom_dimension_height() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    let dimension_type = |height, min_y| {
+        Some(NbtCompound::from_values(vec![
+            ("height".into(), NbtTag::Int(height)),
+            ("min_y".into(), NbtTag::Int(min_y)),
+        ]))
+    };
+    simulation.receive_packet(ClientboundRegistryData {
+        registry_id: ResourceLocation::new("minecraft:dimension_type"),
+        entries: vec![
+            (
+                ResourceLocation::new("minecraft:overworld"),
+                dimension_type(384, -64),
+            ),
+            (
+                ResourceLocation::new("azalea:tall"),
+                dimension_type(1024, -512),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    });
+    simulation.tick();
+    simulation.receive_packet(ClientboundFinishConfiguration);
+    simulation.tick();
+
+    // the name of the world doesn't match the name of its dimension type, so the
+    // dimension type has to be looked up by its ID
+    simulation.receive_packet(ClientboundLogin {
+        player_id: 0,
+        hardcore: false,
+        levels: vec![ResourceLocation::new("azalea:arena")],
+        max_players: 20,
+        chunk_radius: 8,
+        simulation_distance: 8,
+        reduced_debug_info: false,
+        show_death_screen: true,
+        do_limited_crafting: false,
+        common: CommonPlayerSpawnInfo {
+            dimension_type: 1,
+            dimension: ResourceLocation::new("azalea:arena"),
+            seed: 0,
+            game_type: GameMode::Survival,
+            previous_game_type: OptionalGameType(None),
+            is_debug: false,
+            is_flat: false,
+            last_death_location: None,
+            portal_cooldown: 0,
+            sea_level: 63,
+        },
+        enforces_secure_chat: false,
+    });
+    simulation.tick();
+
+    let instance = simulation.component::<InstanceHolder>().instance;
+    let instance = instance.read();
+    assert_eq!(instance.chunks.height, 1024);
+    assert_eq!(instance.chunks.min_y, -512);
+}
+
+#[test]
+fn test_experience_orb_pickup() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+
+    simulation.receive_packet(ClientboundAddExperienceOrb {
+        id: 5,
+        pos: Vec3::new(1., 64., 1.),
+        value: 7,
+    });
+    simulation.tick();
+    simulation.receive_packet(ClientboundTakeItemEntity {
+        item_id: 5,
+        player_id: 0,
+        amount: 1,
+    });
+    simulation.tick();
+
+    let events = simulation.events::<ExperienceOrbPickupEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].collector, simulation.entity);
+    assert_eq!(events[0].value, 7);
+    assert!(simulation.events::<ItemPickupEvent>().is_empty());
+}
+
+#[test]
+fn test_item_pickup() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+
+    simulation.receive_packet(ClientboundAddEntity {
+        id: 6,
+        uuid: Uuid::from_u128(6),
+        entity_type: azalea_registry::EntityKind::Item,
+        position: Vec3::new(1., 64., 1.),
+        x_rot: 0,
+        y_rot: 0,
+        y_head_rot: 0,
+        data: 0,
+        x_vel: 0,
+        y_vel: 0,
+        z_vel: 0,
+    });
+    simulation.receive_packet(ClientboundSetEntityData {
+        id: 6,
+        packed_items: EntityMetadataItems(vec![EntityDataItem {
+            index: 8,
+            value: EntityDataValue::ItemStack(ItemStack::Present(ItemStackData {
+                kind: azalea_registry::Item::Cobblestone,
+                count: 16,
+                components: Default::default(),
+            })),
+        }]),
+    });
+    simulation.tick();
+    // our inventory was almost full so we only picked up some of them
+    simulation.receive_packet(ClientboundTakeItemEntity {
+        item_id: 6,
+        player_id: 0,
+        amount: 3,
+    });
+    simulation.tick();
+
+    let events = simulation.events::<ItemPickupEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].collector, simulation.entity);
+    assert_eq!(events[0].item.kind(), azalea_registry::Item::Cobblestone);
+    assert_eq!(events[0].item.count(), 3);
+
+    // picking up an entity that we don't know about is ignored
+    simulation.receive_packet(ClientboundTakeItemEntity {
+        item_id: 100,
+        player_id: 0,
+        amount: 1,
+    });
+    simulation.tick();
+    assert!(simulation.events::<ItemPickupEvent>().is_empty());
+}
+
+#[test]
+fn test_ping_from_tab_list() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+    simulation.sent_packets();
+
+    simulation.receive_packet(ClientboundPlayerInfoUpdate {
+        actions: ActionEnumSet {
+            add_player: false,
+            initialize_chat: false,
+            update_game_mode: false,
+            update_listed: false,
+            update_latency: true,
+            update_display_name: false,
+            update_hat: false,
+            update_list_order: false,
+        },
+        entries: vec![PlayerInfoEntry {
+            profile: GameProfile::new(Uuid::nil(), "azalea".to_owned()),
+            latency: 42,
+            ..Default::default()
+        }],
+    });
+    simulation.tick();
+    assert_eq!(
+        simulation.component::<Ping>().tab_list,
+        Some(Duration::from_millis(42))
+    );
+
+    // we don't send ping requests unless we opted into it
+    simulation.receive_packet(ClientboundKeepAlive { id: 1 });
+    simulation.tick();
+    let sent = simulation.sent_packets();
+    assert!(sent
+        .iter()
+        .any(|packet| matches!(packet, ServerboundGamePacket::KeepAlive(p) if p.id == 1)));
+    assert!(!sent
+        .iter()
+        .any(|packet| matches!(packet, ServerboundGamePacket::PingRequest(_))));
+    assert_eq!(simulation.component::<Ping>().measured, None);
+}
+
+#[test]
+fn test_measure_ping() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+    simulation
+        .app
+        .world_mut()
+        .entity_mut(simulation.entity)
+        .insert(MeasurePing);
+    simulation.sent_packets();
+
+    simulation.receive_packet(ClientboundKeepAlive { id: 1 });
+    simulation.tick();
+    let sent = simulation.sent_packets();
+    let ping_request_time = sent
+        .iter()
+        .find_map(|packet| match packet {
+            ServerboundGamePacket::PingRequest(p) => Some(p.time),
+            _ => None,
+        })
+        .expect("we should've sent a ping request");
+
+    simulation.receive_packet(ClientboundPongResponse {
+        time: ping_request_time,
+    });
+    simulation.tick();
+    let measured = simulation.component::<Ping>().measured.unwrap();
+    assert!(measured < Duration::from_secs(5), "{measured:?}");
+}
+
+#[test]
+fn test_packet_for_client_missing_component() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+
+    // this used to panic in the packet handler, which would take down every other
+    // client in the swarm too
+    let hunger = simulation.component::<Hunger>();
+    let health = *simulation.component::<Health>();
+    simulation
+        .app
+        .world_mut()
+        .entity_mut(simulation.entity)
+        .remove::<Hunger>();
+    simulation.receive_packet(ClientboundSetHealth {
+        health: 15.,
+        food: 10,
+        saturation: 5.,
+    });
+    simulation.tick();
+    assert_eq!(*simulation.component::<Health>(), health);
+
+    // and the packets after it are still handled normally
+    simulation
+        .app
+        .world_mut()
+        .entity_mut(simulation.entity)
+        .insert(hunger);
+    simulation.receive_packet(ClientboundSetHealth {
+        health: 15.,
+        food: 10,
+        saturation: 5.,
+    });
+    simulation.tick();
+    assert_eq!(*simulation.component::<Health>(), 15.);
+    assert_eq!(simulation.component::<Hunger>().food, 10);
+}
+
+#[test]
+fn test_isolated_instances_have_separate_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    // what `SwarmBuilder::share_world(false)` does
+    simulation.app.insert_resource(ShareInstances(false));
+    let first = simulation.entity;
+
+    let second_uuid = Uuid::from_u128(2);
+    let second = simulation.app.world_mut().spawn_empty().id();
+    let (player, _second_outgoing_packets, mut second_incoming_packets, _second_rt) =
+        create_local_player_bundle(second, ConnectionProtocol::Configuration);
+    simulation.app.world_mut().entity_mut(second).insert((
+        player,
+        GameProfileComponent(GameProfile::new(second_uuid, "bot2".to_owned())),
+        InConfigState,
+    ));
+    // this is done when the client connects
+    simulation
+        .app
+        .world_mut()
+        .resource_mut::<LocalPlayerUuidIndex>()
+        .insert(second_uuid, second);
+
+    join_overworld(&mut simulation);
+    std::mem::swap(
+        &mut simulation.incoming_packet_queue,
+        &mut second_incoming_packets,
+    );
+    join_overworld(&mut simulation);
+
+    // both bots see another player, and the first bot also sees the second one
+    let add_player = |id: i32, uuid: Uuid| ClientboundAddEntity {
+        id,
+        uuid,
+        entity_type: azalea_registry::EntityKind::Player,
+        position: Vec3::new(1., 64., 1.),
+        x_rot: 0,
+        y_rot: 0,
+        y_head_rot: 0,
+        data: 0,
+        x_vel: 0,
+        y_vel: 0,
+        z_vel: 0,
+    };
+    let other_uuid = Uuid::from_u128(3);
+    simulation.receive_packet(add_player(5, other_uuid));
+    std::mem::swap(
+        &mut simulation.incoming_packet_queue,
+        &mut second_incoming_packets,
+    );
+    simulation.receive_packet(add_player(5, other_uuid));
+    simulation.receive_packet(add_player(6, second_uuid));
+    simulation.tick();
+
+    let world = simulation.app.world();
+    let instance_of = |entity: Entity| {
+        world
+            .get::<InstanceHolder>(entity)
+            .unwrap()
+            .instance
+            .clone()
+    };
+    let entity_of = |entity: Entity, id: i32| {
+        world
+            .get::<EntityIdIndex>(entity)
+            .unwrap()
+            .get(MinecraftEntityId(id))
+    };
+    let (first_instance, second_instance) = (instance_of(first), instance_of(second));
+    assert!(!Arc::ptr_eq(&first_instance, &second_instance));
+
+    let (first_view, second_view) = (entity_of(first, 5), entity_of(second, 5));
+    assert!(first_view.is_some());
+    assert!(second_view.is_some());
+    assert_ne!(first_view, second_view);
+    assert_eq!(
+        first_instance
+            .read()
+            .entity_by_uuid
+            .get(&other_uuid)
+            .copied(),
+        first_view
+    );
+    assert_eq!(
+        second_instance
+            .read()
+            .entity_by_uuid
+            .get(&other_uuid)
+            .copied(),
+        second_view
+    );
+
+    // the first bot's copy of the second bot didn't replace the real one
+    let second_bot_view = entity_of(first, 6);
+    assert!(second_bot_view.is_some());
+    assert_ne!(second_bot_view, Some(second));
+    assert_eq!(
+        first_instance
+            .read()
+            .entity_by_uuid
+            .get(&second_uuid)
+            .copied(),
+        second_bot_view
+    );
+    assert_eq!(
+        second_instance
+            .read()
+            .entity_by_uuid
+            .get(&second_uuid)
+            .copied(),
+        Some(second)
+    );
+    assert_eq!(
+        world.resource::<LocalPlayerUuidIndex>().get(&second_uuid),
+        Some(second)
+    );
+}
+
+#[test]
+fn test_unknown_packet() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+
+    // a packet id that no vanilla packet uses, followed by its contents
+    simulation
+        .incoming_packet_queue
+        .lock()
+        .push(vec![0xff, 0x01, 1, 2, 3].into_boxed_slice());
+    // and a known packet after it, which should still be handled
+    simulation.receive_packet(ClientboundSetHealth {
+        health: 15.,
+        food: 20,
+        saturation: 20.,
+    });
+    simulation.tick();
+
+    let events = simulation.events::<UnknownPacketEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].entity, simulation.entity);
+    assert_eq!(events[0].state, ConnectionProtocol::Game);
+    assert_eq!(events[0].id, 0xff);
+    assert_eq!(&*events[0].bytes, &[1, 2, 3]);
+    assert_eq!(*simulation.component::<Health>(), 15.);
+}
+
+#[test]
+fn test_block_update_while_chunk_is_decoding() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+
+    // the block update comes right after the chunk, so it has to wait for the chunk
+    // to be decoded
+    let pos = BlockPos::new(1, 2, 3);
+    let stone = BlockState::from(blocks::Stone {});
+    simulation.receive_packet(empty_chunk_packet(0, 0));
+    simulation.receive_packet(ClientboundBlockUpdate {
+        pos,
+        block_state: stone,
+    });
+    simulation.tick();
+
+    // the block update isn't applied to a chunk that isn't in the world yet
+    let instance = simulation.component::<InstanceHolder>().instance;
+    if simulation.pending_chunks().is_pending(&ChunkPos::new(0, 0)) {
+        assert_eq!(instance.read().get_block_state(&pos), None);
+    }
+
+    simulation.wait_for_chunks();
+    assert!(instance.read().chunks.get(&ChunkPos::new(0, 0)).is_some());
+    assert_eq!(instance.read().get_block_state(&pos), Some(stone));
+
+    let chunk_loaded_events = simulation.events::<ChunkLoadedEvent>();
+    assert_eq!(chunk_loaded_events.len(), 1);
+    assert_eq!(chunk_loaded_events[0].pos, ChunkPos::new(0, 0));
+    let block_update_events = simulation.events::<BlockUpdateEvent>();
+    assert_eq!(block_update_events.len(), 1);
+    assert_eq!(block_update_events[0].pos, pos);
+    assert!(block_update_events[0].old.is_air());
+    assert_eq!(block_update_events[0].new, stone);
+}
+
+#[test]
+fn test_block_update_event() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+    simulation.receive_packet(empty_chunk_packet(0, 0));
+    simulation.tick();
+    simulation.wait_for_chunks();
+
+    let pos = BlockPos::new(1, 2, 3);
+    let stone = BlockState::from(blocks::Stone {});
+    simulation.receive_packet(ClientboundBlockUpdate {
+        pos,
+        block_state: stone,
+    });
+    simulation.tick();
+    let events = simulation.events::<BlockUpdateEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].entity, simulation.entity);
+    assert_eq!(events[0].instance.to_string(), "minecraft:overworld");
+    assert_eq!(events[0].pos, pos);
+    assert!(events[0].old.is_air());
+    assert_eq!(events[0].new, stone);
+
+    // nothing changed, so there's no event
+    simulation.receive_packet(ClientboundBlockUpdate {
+        pos,
+        block_state: stone,
+    });
+    simulation.tick();
+    assert!(simulation.events::<BlockUpdateEvent>().is_empty());
+
+    // breaking the block removes it before the server says so, so the event is sent
+    // right away
+    simulation
+        .app
+        .world_mut()
+        .send_event(FinishMiningBlockEvent {
+            entity: simulation.entity,
+            position: pos,
+        });
+    simulation.tick();
+    let events = simulation.events::<BlockUpdateEvent>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].pos, pos);
+    assert_eq!(events[0].old, stone);
+    assert!(events[0].new.is_air());
+
+    // and the server agreeing with us doesn't send another one
+    simulation.receive_packet(ClientboundBlockUpdate {
+        pos,
+        block_state: BlockState::AIR,
+    });
+    simulation.tick();
+    assert!(simulation.events::<BlockUpdateEvent>().is_empty());
+}
+
+#[test]
+fn test_section_blocks_update_event() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut simulation = Simulation::new(ConnectionProtocol::Configuration);
+    join_overworld(&mut simulation);
+    simulation.receive_packet(empty_chunk_packet(0, 0));
+    simulation.tick();
+    simulation.wait_for_chunks();
+
+    let stone = BlockState::from(blocks::Stone {});
+    let section_pos = ChunkSectionPos { x: 0, y: 0, z: 0 };
+    simulation.receive_packet(ClientboundSectionBlocksUpdate {
+        section_pos,
+        states: vec![
+            BlockStateWithPosition {
+                pos: ChunkSectionBlockPos::new(1, 2, 3),
+                state: stone,
+            },
+            // this one is already air, so it doesn't count as a change
+            BlockStateWithPosition {
+                pos: ChunkSectionBlockPos::new(4, 5, 6),
+                state: BlockState::AIR,
+            },
+        ],
+    });
+    simulation.tick();
+
+    let block_update_events = simulation.events::<BlockUpdateEvent>();
+    assert_eq!(block_update_events.len(), 1);
+    assert_eq!(block_update_events[0].pos, BlockPos::new(1, 2, 3));
+
+    let section_events = simulation.events::<SectionBlocksUpdateEvent>();
+    assert_eq!(section_events.len(), 1);
+    assert_eq!(section_events[0].entity, simulation.entity);
+    assert_eq!(section_events[0].section_pos, section_pos);
+    assert_eq!(
+        section_events[0].changes,
+        [(BlockPos::new(1, 2, 3), BlockState::AIR, stone)]
+    );
+
+    // nothing changed this time
+    simulation.receive_packet(ClientboundSectionBlocksUpdate {
+        section_pos,
+        states: vec![BlockStateWithPosition {
+            pos: ChunkSectionBlockPos::new(1, 2, 3),
+            state: stone,
+        }],
+    });
+    simulation.tick();
+    assert!(simulation.events::<BlockUpdateEvent>().is_empty());
+    assert!(simulation.events::<SectionBlocksUpdateEvent>().is_empty());
+}
+
+/// A chunk packet for a chunk that's only air.
+fn empty_chunk_packet(x: i32, z: i32) -> ClientboundLevelChunkWithLight {
+    let mut data = Vec::new();
+    Chunk::default()
+        .azalea_write(&mut data)
+        .expect("writing to a Vec can't fail");
+    ClientboundLevelChunkWithLight {
+        x,
+        z,
+        chunk_data: ClientboundLevelChunkPacketData {
+            heightmaps: Nbt::None,
+            data,
+            block_entities: Vec::new(),
+        },
+        light_data: ClientboundLightUpdatePacketData {
+            sky_y_mask: BitSet::default(),
+            block_y_mask: BitSet::default(),
+            empty_sky_y_mask: BitSet::default(),
+            empty_block_y_mask: BitSet::default(),
+            sky_updates: Vec::new(),
+            block_updates: Vec::new(),
+        },
+    }
+}
+
 pub fn create_local_player_bundle(
     entity: Entity,
     connection_protocol: ConnectionProtocol,
@@ -138,6 +749,14 @@ pub fn create_local_player_bundle(
         read_packets_task,
         write_packets_task,
         connection_protocol,
+        packet_listeners: Vec::new(),
+        packet_middleware: Vec::new(),
+        delayed_packets: Default::default(),
+        take_over_senders: TakeOverSenders {
+            read: mpsc::unbounded_channel().0,
+            write: mpsc::unbounded_channel().0,
+        },
+        taken_over_state: Default::default(),
     };
 
     let (local_player_events_sender, _local_player_events_receiver) = mpsc::unbounded_channel();
@@ -229,6 +848,85 @@ impl Simulation {
     pub fn has_component<T: Component>(&self) -> bool {
         self.app.world().get::<T>(self.entity).is_some()
     }
+    /// Get the game packets that we sent since the last time this was called.
+    pub fn sent_packets(&mut self) -> Vec<ServerboundGamePacket> {
+        let mut packets = Vec::new();
+        while let Ok(raw_packet) = self.outgoing_packets_receiver.try_recv() {
+            packets.push(
+                azalea_protocol::read::deserialize_packet(&mut Cursor::new(&raw_packet)).unwrap(),
+            );
+        }
+        packets
+    }
+    pub fn pending_chunks(&self) -> &PendingChunks {
+        self.app.world().get::<PendingChunks>(self.entity).unwrap()
+    }
+    /// Update the app until every chunk that's being decoded was added to the
+    /// world, since the decoding happens on another thread and can take more
+    /// than one frame.
+    pub fn wait_for_chunks(&mut self) {
+        for _ in 0..1000 {
+            if self.pending_chunks().is_empty() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            self.app.update();
+        }
+        panic!("The chunks took too long to decode");
+    }
+    /// Get the events of the given type that were sent during the last tick.
+    pub fn events<T: Event + Clone>(&self) -> Vec<T> {
+        self.app
+            .world()
+            .resource::<Events<T>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Finish configuration and join an overworld where our entity id is 0.
+fn join_overworld(simulation: &mut Simulation) {
+    simulation.receive_packet(ClientboundRegistryData {
+        registry_id: ResourceLocation::new("minecraft:dimension_type"),
+        entries: vec![(
+            ResourceLocation::new("minecraft:overworld"),
+            Some(NbtCompound::from_values(vec![
+                ("height".into(), NbtTag::Int(384)),
+                ("min_y".into(), NbtTag::Int(-64)),
+            ])),
+        )]
+        .into_iter()
+        .collect(),
+    });
+    simulation.tick();
+    simulation.receive_packet(ClientboundFinishConfiguration);
+    simulation.tick();
+    simulation.receive_packet(ClientboundLogin {
+        player_id: 0,
+        hardcore: false,
+        levels: vec![],
+        max_players: 20,
+        chunk_radius: 8,
+        simulation_distance: 8,
+        reduced_debug_info: false,
+        show_death_screen: true,
+        do_limited_crafting: false,
+        common: CommonPlayerSpawnInfo {
+            dimension_type: 0,
+            dimension: ResourceLocation::new("minecraft:overworld"),
+            seed: 0,
+            game_type: GameMode::Survival,
+            previous_game_type: OptionalGameType(None),
+            is_debug: false,
+            is_flat: false,
+            last_death_location: None,
+            portal_cooldown: 0,
+            sea_level: 63,
+        },
+        enforces_secure_chat: false,
+    });
+    simulation.tick();
 }
 
 fn tick_app(app: &mut App) {
@@ -159,6 +159,35 @@ impl AABB {
         })
     }
 
+    /// The box where this box and the other one overlap, or `None` if they
+    /// don't overlap.
+    pub fn intersection(&self, other: &AABB) -> Option<AABB> {
+        if self.intersects_aabb(other) {
+            Some(self.intersect(other))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the other box is completely inside of this one.
+    pub fn contains_aabb(&self, other: &AABB) -> bool {
+        other.min.x >= self.min.x
+            && other.max.x <= self.max.x
+            && other.min.y >= self.min.y
+            && other.max.y <= self.max.y
+            && other.min.z >= self.min.z
+            && other.max.z <= self.max.z
+    }
+
+    /// The squared distance from the point to the closest point in this box,
+    /// or 0 if the point is inside of it.
+    pub fn distance_to_sqr(&self, point: &Vec3) -> f64 {
+        let x = f64::max(0., f64::max(self.min.x - point.x, point.x - self.max.x));
+        let y = f64::max(0., f64::max(self.min.y - point.y, point.y - self.max.y));
+        let z = f64::max(0., f64::max(self.min.z - point.z, point.z - self.max.z));
+        x * x + y * y + z * z
+    }
+
     pub fn contains(&self, point: &Vec3) -> bool {
         point.x >= self.min.x
             && point.x < self.max.x
@@ -503,4 +532,50 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn test_aabb_intersection() {
+        let a = AABB {
+            min: Vec3::new(0., 0., 0.),
+            max: Vec3::new(2., 2., 2.),
+        };
+        let b = AABB {
+            min: Vec3::new(1., 1., 1.),
+            max: Vec3::new(3., 3., 3.),
+        };
+        assert_eq!(
+            a.intersection(&b),
+            Some(AABB {
+                min: Vec3::new(1., 1., 1.),
+                max: Vec3::new(2., 2., 2.),
+            })
+        );
+        // touching faces don't count as overlapping
+        let c = a.move_relative(Vec3::new(2., 0., 0.));
+        assert_eq!(a.intersection(&c), None);
+    }
+
+    #[test]
+    fn test_aabb_contains_aabb() {
+        let a = AABB {
+            min: Vec3::new(0., 0., 0.),
+            max: Vec3::new(4., 4., 4.),
+        };
+        assert!(a.contains_aabb(&AABB {
+            min: Vec3::new(1., 0., 1.),
+            max: Vec3::new(2., 4., 2.),
+        }));
+        assert!(!a.contains_aabb(&a.move_relative(Vec3::new(0., 1., 0.))));
+    }
+
+    #[test]
+    fn test_aabb_distance_to_sqr() {
+        let a = AABB {
+            min: Vec3::new(0., 0., 0.),
+            max: Vec3::new(1., 1., 1.),
+        };
+        assert_eq!(a.distance_to_sqr(&Vec3::new(0.5, 0.5, 0.5)), 0.);
+        assert_eq!(a.distance_to_sqr(&Vec3::new(3., 0.5, 0.5)), 4.);
+        assert_eq!(a.distance_to_sqr(&Vec3::new(-1., 2., 0.5)), 2.);
+    }
 }
@@ -18,8 +18,12 @@ impl BitSet {
         }
     }
 
+    /// Whether the bit at the index is set. Like in Java, bits past the end of
+    /// the set are `false`.
     pub fn index(&self, index: usize) -> bool {
-        (self.data[index / 64] & (1u64 << (index % 64))) != 0
+        self.data
+            .get(index / 64)
+            .is_some_and(|word| (word & (1u64 << (index % 64))) != 0)
     }
 
     fn check_range(&self, from_index: usize, to_index: usize) {
@@ -194,6 +198,7 @@ mod tests {
         assert!(!bitset.index(0));
         assert!(bitset.index(1));
         assert!(!bitset.index(2));
+        assert!(!bitset.index(1000));
     }
 
     #[test]
@@ -0,0 +1,188 @@
+//! Converting coordinates between dimensions.
+//!
+//! Every dimension type has a coordinate scale, which is how many blocks in
+//! the overworld one block in that dimension is worth. In vanilla, this is 8
+//! for the nether and 1 for everything else.
+
+use crate::{
+    position::{BlockPos, GlobalPos, Vec3},
+    resource_location::ResourceLocation,
+};
+
+/// How far from 0 the x and z coordinates of a position can be after it's
+/// moved to a different dimension. This is the edge of the default world
+/// border.
+pub const MAX_HORIZONTAL_COORDINATE: f64 = 29_999_984.;
+
+/// The dimensions that exist in vanilla.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VanillaDimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl VanillaDimension {
+    /// Get the dimension from its name, like `minecraft:the_nether`. Returns
+    /// `None` if it's not a vanilla dimension.
+    pub fn from_resource_location(name: &ResourceLocation) -> Option<Self> {
+        if name.namespace != "minecraft" {
+            return None;
+        }
+        match name.path.as_str() {
+            "overworld" => Some(Self::Overworld),
+            "the_nether" => Some(Self::Nether),
+            "the_end" => Some(Self::End),
+            _ => None,
+        }
+    }
+
+    pub fn resource_location(self) -> ResourceLocation {
+        ResourceLocation::new(match self {
+            Self::Overworld => "minecraft:overworld",
+            Self::Nether => "minecraft:the_nether",
+            Self::End => "minecraft:the_end",
+        })
+    }
+
+    /// The coordinate scale of the dimension type that this dimension uses by
+    /// default.
+    pub fn coordinate_scale(self) -> f64 {
+        match self {
+            Self::Overworld | Self::End => 1.,
+            Self::Nether => 8.,
+        }
+    }
+}
+
+/// The number that coordinates are multiplied by when going from a dimension
+/// with the coordinate scale `from` to one with the coordinate scale `to`.
+pub fn teleportation_scale(from: f64, to: f64) -> f64 {
+    from / to
+}
+
+/// Get the position in another dimension that's at the same location as the
+/// given one, like vanilla does when an entity goes through a nether portal.
+///
+/// Only the x and z coordinates are scaled, and they're clamped to the world
+/// border.
+pub fn convert_position(pos: Vec3, from_scale: f64, to_scale: f64) -> Vec3 {
+    let scale = teleportation_scale(from_scale, to_scale);
+    Vec3::new(
+        (pos.x * scale).clamp(-MAX_HORIZONTAL_COORDINATE, MAX_HORIZONTAL_COORDINATE),
+        pos.y,
+        (pos.z * scale).clamp(-MAX_HORIZONTAL_COORDINATE, MAX_HORIZONTAL_COORDINATE),
+    )
+}
+
+/// [`convert_position`] but for block positions. The block that contains the
+/// scaled position is returned.
+pub fn convert_block_pos(pos: BlockPos, from_scale: f64, to_scale: f64) -> BlockPos {
+    // scale from the corner of the block so going back and forth doesn't drift
+    let scaled = convert_position(pos.to_vec3_floored(), from_scale, to_scale);
+    BlockPos::from(scaled)
+}
+
+/// The nether position that's at the same location as the given overworld
+/// position.
+pub fn overworld_to_nether(pos: BlockPos) -> BlockPos {
+    convert_block_pos(
+        pos,
+        VanillaDimension::Overworld.coordinate_scale(),
+        VanillaDimension::Nether.coordinate_scale(),
+    )
+}
+
+/// The overworld position that's at the same location as the given nether
+/// position.
+pub fn nether_to_overworld(pos: BlockPos) -> BlockPos {
+    convert_block_pos(
+        pos,
+        VanillaDimension::Nether.coordinate_scale(),
+        VanillaDimension::Overworld.coordinate_scale(),
+    )
+}
+
+/// Get the position in the dimension `to` that's at the same location as a
+/// position in another vanilla dimension.
+///
+/// Returns `None` if either of the dimensions aren't from vanilla, since we
+/// don't know their coordinate scales. The end isn't really at the same
+/// location as the other dimensions, but it has a scale of 1 so the
+/// coordinates are kept the same.
+pub fn same_location_in(pos: &GlobalPos, to: &ResourceLocation) -> Option<GlobalPos> {
+    let from_dimension = VanillaDimension::from_resource_location(&pos.world)?;
+    let to_dimension = VanillaDimension::from_resource_location(to)?;
+    Some(GlobalPos {
+        world: to.clone(),
+        pos: convert_block_pos(
+            pos.pos,
+            from_dimension.coordinate_scale(),
+            to_dimension.coordinate_scale(),
+        ),
+    })
+}
+
+/// Figure out where a player will respawn.
+///
+/// `respawn_point` is the bed or respawn anchor that the player last used, if
+/// it's still valid. Otherwise, players respawn at the world spawn, which is
+/// always in the overworld.
+pub fn resolve_respawn_point(
+    respawn_point: Option<&GlobalPos>,
+    world_spawn: BlockPos,
+) -> GlobalPos {
+    match respawn_point {
+        Some(respawn_point) => respawn_point.clone(),
+        None => GlobalPos {
+            world: VanillaDimension::Overworld.resource_location(),
+            pos: world_spawn,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overworld_to_nether() {
+        assert_eq!(
+            overworld_to_nether(BlockPos::new(800, 64, -800)),
+            BlockPos::new(100, 64, -100)
+        );
+        // rounds down like vanilla
+        assert_eq!(
+            overworld_to_nether(BlockPos::new(-1, 64, 15)),
+            BlockPos::new(-1, 64, 1)
+        );
+    }
+
+    #[test]
+    fn test_nether_to_overworld_round_trip() {
+        let nether_pos = BlockPos::new(-37, 100, 12);
+        let overworld_pos = nether_to_overworld(nether_pos);
+        assert_eq!(overworld_pos, BlockPos::new(-296, 100, 96));
+        assert_eq!(overworld_to_nether(overworld_pos), nether_pos);
+    }
+
+    #[test]
+    fn test_convert_position_clamps_to_world_border() {
+        let pos = convert_position(Vec3::new(29_000_000., 0., 0.), 8., 1.);
+        assert_eq!(pos.x, MAX_HORIZONTAL_COORDINATE);
+    }
+
+    #[test]
+    fn test_same_location_in() {
+        let pos = GlobalPos {
+            world: ResourceLocation::new("minecraft:the_nether"),
+            pos: BlockPos::new(10, 70, 10),
+        };
+        let overworld = ResourceLocation::new("minecraft:overworld");
+        let converted = same_location_in(&pos, &overworld).unwrap();
+        assert_eq!(converted.world, overworld);
+        assert_eq!(converted.pos, BlockPos::new(80, 70, 80));
+
+        assert!(same_location_in(&pos, &ResourceLocation::new("custom:dimension")).is_none());
+    }
+}
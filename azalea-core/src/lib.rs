@@ -10,6 +10,7 @@ pub mod cursor3d;
 pub mod data_registry;
 pub mod delta;
 pub mod difficulty;
+pub mod dimension;
 pub mod direction;
 pub mod game_type;
 pub mod math;
@@ -17,6 +18,7 @@ pub mod objectives;
 pub mod position;
 pub mod registry_holder;
 pub mod resource_location;
+pub mod rotation;
 #[cfg(feature = "bevy_ecs")]
 pub mod tick;
 pub mod tier;
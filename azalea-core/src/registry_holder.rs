@@ -57,22 +57,52 @@ impl RegistryHolder {
         }
     }
 
+    /// Get a dimension type from its ID, which is its index in the dimension
+    /// type registry that the server sent. This is how dimension types are
+    /// referred to in packets like `ClientboundLogin` and `ClientboundRespawn`.
+    ///
+    /// Returns the name of the dimension type along with its data. This works
+    /// for dimension types that were added by data packs, unlike
+    /// `azalea_registry::DimensionType`.
+    pub fn dimension_type_by_id(
+        &self,
+        id: u32,
+    ) -> Option<(ResourceLocation, DimensionTypeElement)> {
+        let name = ResourceLocation::new("minecraft:dimension_type");
+        let (key, value) = self.map.get(&name)?.get_index(id as usize)?;
+        match deserialize_entry(value)? {
+            Ok(dimension_type) => Some((key.clone(), dimension_type)),
+            Err(err) => {
+                error!("Error deserializing dimension type {key}: {err:?}\n{value:?}");
+                None
+            }
+        }
+    }
+
+    /// Get a dimension type from its name, like `minecraft:overworld`.
+    ///
+    /// Note that the names of dimension types aren't always the same as the
+    /// names of the worlds that use them, especially when data packs are used.
+    pub fn dimension_type_by_name(&self, name: &ResourceLocation) -> Option<DimensionTypeElement> {
+        let registry_name = ResourceLocation::new("minecraft:dimension_type");
+        let value = self.map.get(&registry_name)?.get(name)?;
+        match deserialize_entry(value)? {
+            Ok(dimension_type) => Some(dimension_type),
+            Err(err) => {
+                error!("Error deserializing dimension type {name}: {err:?}\n{value:?}");
+                None
+            }
+        }
+    }
+
     fn get<T: Deserialize>(
         &self,
         name: &ResourceLocation,
     ) -> Option<Result<RegistryType<T>, simdnbt::DeserializeError>> {
-        // this is suboptimal, ideally simdnbt should just have a way to get the
-        // owned::NbtCompound as a borrow::NbtCompound
-
         let mut map = HashMap::new();
 
         for (key, value) in self.map.get(name)? {
-            // convert the value to T
-            let mut nbt_bytes = Vec::new();
-            value.write(&mut nbt_bytes);
-            let nbt_borrow_compound =
-                simdnbt::borrow::read_compound(&mut Cursor::new(&nbt_bytes)).ok()?;
-            let value = match T::from_compound((&nbt_borrow_compound).into()) {
+            let value = match deserialize_entry(value)? {
                 Ok(value) => value,
                 Err(err) => {
                     return Some(Err(err));
@@ -86,6 +116,19 @@ impl RegistryHolder {
     }
 }
 
+/// Convert an entry in a registry to `T`. Returns `None` if the NBT couldn't be
+/// read again after writing it.
+fn deserialize_entry<T: Deserialize>(
+    value: &NbtCompound,
+) -> Option<Result<T, simdnbt::DeserializeError>> {
+    // this is suboptimal, ideally simdnbt should just have a way to get the
+    // owned::NbtCompound as a borrow::NbtCompound
+    let mut nbt_bytes = Vec::new();
+    value.write(&mut nbt_bytes);
+    let nbt_borrow_compound = simdnbt::borrow::read_compound(&mut Cursor::new(&nbt_bytes)).ok()?;
+    Some(T::from_compound((&nbt_borrow_compound).into()))
+}
+
 /// A collection of values for a certain type of registry data.
 #[derive(Debug, Clone)]
 pub struct RegistryType<T> {
@@ -0,0 +1,173 @@
+//! Math for rotations, which are stored as a `y_rot` (yaw) and `x_rot` (pitch)
+//! in degrees like vanilla does.
+//!
+//! Rotations are passed around as `(y_rot, x_rot)` tuples here, which is the
+//! same order that `LookDirection` in `azalea-entity` can be converted from.
+//!
+//! A `y_rot` of 0 means facing south (+z), and it goes clockwise when looking
+//! from above, so 90 is west (-x). An `x_rot` of -90 means looking straight
+//! up, and 90 is straight down.
+
+use std::f64::consts::PI;
+
+use crate::{math, position::Vec3};
+
+/// The number that degrees are multiplied by to get radians, with the same
+/// precision that vanilla uses.
+pub const DEGREES_TO_RADIANS: f32 = 0.017453292;
+
+/// Get the rotation that an entity at `from` would need to have to be looking
+/// directly at `to`.
+///
+/// ```
+/// # use azalea_core::{position::Vec3, rotation::look_at};
+/// // looking towards -x is a y_rot of 90
+/// let (y_rot, x_rot) = look_at(&Vec3::new(0., 0., 0.), &Vec3::new(-5., 0., 0.));
+/// assert_eq!((y_rot, x_rot), (90., 0.));
+/// ```
+pub fn look_at(from: &Vec3, to: &Vec3) -> (f32, f32) {
+    rotation_of(&(to - from))
+}
+
+/// Get the rotation that points in the same direction as the given vector.
+/// The vector doesn't have to be normalized.
+///
+/// This is the inverse of [`view_vector`], except that the returned `y_rot` is
+/// always between 0 and 360.
+pub fn rotation_of(direction: &Vec3) -> (f32, f32) {
+    // borrowed from mineflayer's Bot.lookAt because i didn't want to do math
+    let y_rot = (PI - f64::atan2(-direction.x, -direction.z)) * (180.0 / PI);
+    let ground_distance = f64::sqrt(direction.x * direction.x + direction.z * direction.z);
+    let x_rot = f64::atan2(direction.y, ground_distance) * -(180.0 / PI);
+
+    (normalize_y_rot(y_rot as f32), clamp_x_rot(x_rot as f32))
+}
+
+/// Get the unit vector for the direction that an entity with the given
+/// rotation is looking in.
+///
+/// This uses vanilla's lookup table for sine and cosine, so the result is
+/// exactly what the server will calculate.
+pub fn view_vector(y_rot: f32, x_rot: f32) -> Vec3 {
+    let x_rot = x_rot * DEGREES_TO_RADIANS;
+    let y_rot = -y_rot * DEGREES_TO_RADIANS;
+    let y_rot_cos = math::cos(y_rot);
+    let y_rot_sin = math::sin(y_rot);
+    let x_rot_cos = math::cos(x_rot);
+    let x_rot_sin = math::sin(x_rot);
+    Vec3 {
+        x: (y_rot_sin * x_rot_cos) as f64,
+        y: (-x_rot_sin) as f64,
+        z: (y_rot_cos * x_rot_cos) as f64,
+    }
+}
+
+/// Wrap the angle so it's between -180 (inclusive) and 180 (exclusive).
+///
+/// This is equivalent to vanilla's `Mth.wrapDegrees`.
+pub fn wrap_degrees(degrees: f32) -> f32 {
+    let mut degrees = degrees % 360.;
+    if degrees >= 180. {
+        degrees -= 360.;
+    }
+    if degrees < -180. {
+        degrees += 360.;
+    }
+    degrees
+}
+
+/// The shortest signed angle to turn by to get from `from` to `to`, between
+/// -180 and 180.
+pub fn degrees_difference(from: f32, to: f32) -> f32 {
+    wrap_degrees(to - from)
+}
+
+/// Turn from `current` towards `target` by at most `max_step` degrees, taking
+/// the shortest way around.
+///
+/// This is useful for rotating smoothly over several ticks instead of snapping
+/// to the target rotation.
+pub fn approach_degrees(current: f32, target: f32, max_step: f32) -> f32 {
+    let difference = degrees_difference(current, target);
+    current + difference.clamp(-max_step, max_step)
+}
+
+/// Wrap the `y_rot` so it's between 0 (inclusive) and 360 (exclusive).
+pub fn normalize_y_rot(y_rot: f32) -> f32 {
+    let y_rot = y_rot.rem_euclid(360.);
+    // rem_euclid can return 360 for tiny negative numbers because of rounding
+    if y_rot >= 360. {
+        0.
+    } else {
+        y_rot
+    }
+}
+
+/// Clamp the `x_rot` so it's not looking further up or down than vanilla
+/// allows.
+pub fn clamp_x_rot(x_rot: f32) -> f32 {
+    x_rot.clamp(-90., 90.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rotation_eq(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_look_at_cardinal_directions() {
+        let origin = Vec3::new(0., 64., 0.);
+        assert_rotation_eq(look_at(&origin, &Vec3::new(0., 64., 1.)), (0., 0.));
+        assert_rotation_eq(look_at(&origin, &Vec3::new(-1., 64., 0.)), (90., 0.));
+        assert_rotation_eq(look_at(&origin, &Vec3::new(0., 64., -1.)), (180., 0.));
+        assert_rotation_eq(look_at(&origin, &Vec3::new(1., 64., 0.)), (270., 0.));
+    }
+
+    #[test]
+    fn test_look_at_up_and_down() {
+        let origin = Vec3::new(0., 64., 0.);
+        assert_eq!(look_at(&origin, &Vec3::new(0., 70., 0.)).1, -90.);
+        assert_eq!(look_at(&origin, &Vec3::new(0., 60., 0.)).1, 90.);
+        assert_rotation_eq(look_at(&origin, &Vec3::new(0., 65., 1.)), (0., -45.));
+    }
+
+    #[test]
+    fn test_view_vector_round_trip() {
+        for (y_rot, x_rot) in [(0., 0.), (45., 30.), (135., -60.), (300., 10.)] {
+            let vector = view_vector(y_rot, x_rot);
+            assert!((vector.length() - 1.).abs() < 0.001);
+            assert_rotation_eq(rotation_of(&vector), (y_rot, x_rot));
+        }
+    }
+
+    #[test]
+    fn test_wrap_degrees() {
+        assert_eq!(wrap_degrees(0.), 0.);
+        assert_eq!(wrap_degrees(180.), -180.);
+        assert_eq!(wrap_degrees(-180.), -180.);
+        assert_eq!(wrap_degrees(270.), -90.);
+        assert_eq!(wrap_degrees(-270.), 90.);
+        assert_eq!(wrap_degrees(720. + 45.), 45.);
+    }
+
+    #[test]
+    fn test_approach_degrees_takes_shortest_way() {
+        assert_eq!(degrees_difference(350., 10.), 20.);
+        assert_eq!(degrees_difference(10., 350.), -20.);
+        assert_eq!(approach_degrees(350., 10., 5.), 355.);
+        assert_eq!(approach_degrees(350., 10., 90.), 370.);
+    }
+
+    #[test]
+    fn test_normalize_y_rot() {
+        assert_eq!(normalize_y_rot(-90.), 270.);
+        assert_eq!(normalize_y_rot(360.), 0.);
+        assert!(normalize_y_rot(-0.000_001) < 360.);
+    }
+}
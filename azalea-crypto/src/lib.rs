@@ -8,7 +8,9 @@ use aes::{
     Aes128,
 };
 use rand::{rngs::OsRng, RngCore};
+use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 pub use signing::*;
 
 fn generate_secret_key() -> [u8; 16] {
@@ -17,6 +19,14 @@ fn generate_secret_key() -> [u8; 16] {
     key
 }
 
+/// Generate the random challenge that servers send to clients to make sure
+/// they encrypted it with the right key.
+pub fn generate_challenge() -> [u8; 4] {
+    let mut challenge = [0u8; 4];
+    OsRng.fill_bytes(&mut challenge);
+    challenge
+}
+
 pub fn digest_data(server_id: &[u8], public_key: &[u8], private_key: &[u8]) -> Vec<u8> {
     let mut digest = Sha1::new();
     digest.update(server_id);
@@ -35,6 +45,27 @@ pub fn hex_digest(digest: &[u8]) -> String {
     num_bigint::BigInt::from_signed_bytes_be(digest).to_str_radix(16)
 }
 
+/// Sign the data with HMAC-SHA256, like Velocity does for the player info it
+/// forwards to backend servers.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
 #[derive(Debug)]
 pub struct EncryptResult {
     pub secret_key: [u8; 16],
@@ -62,6 +93,44 @@ pub fn encrypt(public_key: &[u8], challenge: &[u8]) -> Result<EncryptResult, Str
     })
 }
 
+/// The RSA key pair that a server uses to agree on a shared secret with the
+/// client, which is then used for encrypting the connection.
+pub struct ServerKeyPair {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKeyPair {
+    /// Generate a new 1024-bit key pair, which is the same size that vanilla
+    /// servers use.
+    pub fn generate() -> Self {
+        let private_key =
+            RsaPrivateKey::new(&mut OsRng, 1024).expect("generating an RSA key shouldn't fail");
+        let public_key_der = RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .expect("encoding an RSA public key shouldn't fail")
+            .into_vec();
+        Self {
+            private_key,
+            public_key_der,
+        }
+    }
+
+    /// The public key in the DER format, which is what's sent to clients in
+    /// the hello packet.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    /// Decrypt something that the client encrypted with our public key, like
+    /// the shared secret or the challenge.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|e| e.to_string())
+    }
+}
+
 pub type Aes128CfbEnc = cfb8::Encryptor<Aes128>;
 pub type Aes128CfbDec = cfb8::Decryptor<Aes128>;
 
@@ -93,6 +162,31 @@ mod tests {
         assert_eq!(key.len(), 16);
     }
 
+    #[test]
+    fn server_key_pair_decrypt() {
+        let key_pair = ServerKeyPair::generate();
+        let challenge = generate_challenge();
+        let encrypted = encrypt(key_pair.public_key(), &challenge).unwrap();
+        assert_eq!(
+            key_pair.decrypt(&encrypted.encrypted_challenge).unwrap(),
+            challenge
+        );
+        assert_eq!(
+            key_pair.decrypt(&encrypted.encrypted_public_key).unwrap(),
+            encrypted.secret_key
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // test case 2 from rfc 4231
+        let hmac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hmac.map(|b| format!("{b:02x}")).concat(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
     #[test]
     fn test_hex_digest() {
         let digest = hex_digest(&digest_data(b"Notch", &[], &[]));
@@ -12,6 +12,20 @@ pub struct Attributes {
     pub speed: AttributeInstance,
     pub attack_speed: AttributeInstance,
     pub water_movement_efficiency: AttributeInstance,
+    pub block_break_speed: AttributeInstance,
+    pub submerged_mining_speed: AttributeInstance,
+}
+impl Default for Attributes {
+    /// The default attributes for a player.
+    fn default() -> Self {
+        Self {
+            speed: AttributeInstance::new(0.1),
+            attack_speed: AttributeInstance::new(4.0),
+            water_movement_efficiency: AttributeInstance::new(0.0),
+            block_break_speed: AttributeInstance::new(1.0),
+            submerged_mining_speed: AttributeInstance::new(0.2),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -70,6 +84,16 @@ impl AttributeInstance {
     pub fn remove(&mut self, id: &ResourceLocation) -> Option<AttributeModifier> {
         self.modifiers_by_id.remove(id)
     }
+
+    /// Replace the base value and all of the modifiers of this attribute, like
+    /// when the server sends us the attribute's value.
+    pub fn set(&mut self, base: f64, modifiers: impl IntoIterator<Item = AttributeModifier>) {
+        self.base = base;
+        self.modifiers_by_id = modifiers
+            .into_iter()
+            .map(|modifier| (modifier.id.clone(), modifier))
+            .collect();
+    }
 }
 
 #[derive(Clone, Debug, AzBuf)]
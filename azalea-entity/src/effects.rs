@@ -1,26 +1,49 @@
-// TODO
+use std::collections::HashMap;
 
-// pub struct ActiveEffects(HashMap<azalea_registry::MobEffect, MobEffectData>);
+use azalea_registry::MobEffect;
+use bevy_ecs::component::Component;
+use derive_more::{Deref, DerefMut};
 
-/// Returns the level of the given effect, or `None` if the effect is not
-/// active. The lowest level is 0.
-pub fn get_effect(_effect: azalea_registry::MobEffect) -> Option<u32> {
-    // TODO
-    None
+/// The potion effects that an entity currently has.
+///
+/// This is updated by the server, so for entities other than local players it
+/// only contains the effects that the server decided to tell us about.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct ActiveEffects(pub HashMap<MobEffect, MobEffectData>);
+
+/// The data for a single effect that an entity has.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MobEffectData {
+    /// The level of the effect, starting at 0. For example, Haste II has an
+    /// amplifier of 1.
+    pub amplifier: u32,
+    /// The number of ticks left before the effect runs out, or -1 if the effect
+    /// lasts forever.
+    pub duration_ticks: i32,
 }
 
-pub fn get_dig_speed_amplifier() -> Option<u32> {
-    let effect_plus_one = u32::max(
-        get_effect(azalea_registry::MobEffect::Haste)
-            .map(|x| x + 1)
-            .unwrap_or_default(),
-        get_effect(azalea_registry::MobEffect::ConduitPower)
-            .map(|x| x + 1)
-            .unwrap_or_default(),
-    );
-    if effect_plus_one > 0 {
-        Some(effect_plus_one - 1)
-    } else {
-        None
+impl ActiveEffects {
+    /// Returns the level of the given effect, or `None` if the effect is not
+    /// active. The lowest level is 0.
+    pub fn get_level(&self, effect: MobEffect) -> Option<u32> {
+        self.0.get(&effect).map(|data| data.amplifier)
+    }
+
+    /// The highest level of haste or conduit power that the entity has, since
+    /// they both make you mine faster in the same way.
+    pub fn get_dig_speed_amplifier(&self) -> Option<u32> {
+        let effect_plus_one = u32::max(
+            self.get_level(MobEffect::Haste)
+                .map(|x| x + 1)
+                .unwrap_or_default(),
+            self.get_level(MobEffect::ConduitPower)
+                .map(|x| x + 1)
+                .unwrap_or_default(),
+        );
+        if effect_plus_one > 0 {
+            Some(effect_plus_one - 1)
+        } else {
+            None
+        }
     }
 }
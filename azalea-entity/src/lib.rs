@@ -3,7 +3,7 @@
 pub mod attributes;
 mod data;
 mod dimensions;
-mod effects;
+pub mod effects;
 mod enchantments;
 pub mod metadata;
 pub mod mining;
@@ -24,12 +24,14 @@ use azalea_core::{
     math,
     position::{BlockPos, ChunkPos, Vec3},
     resource_location::ResourceLocation,
+    rotation,
 };
 use azalea_world::{ChunkStorage, InstanceName};
 use bevy_ecs::{bundle::Bundle, component::Component};
 pub use data::*;
 use derive_more::{Deref, DerefMut};
 pub use dimensions::EntityDimensions;
+use effects::ActiveEffects;
 use plugin::indexing::EntityChunkPos;
 use uuid::Uuid;
 use vec_delta_codec::VecDeltaCodec;
@@ -68,17 +70,7 @@ pub fn input_vector(direction: &LookDirection, speed: f32, acceleration: &Vec3)
 }
 
 pub fn view_vector(look_direction: &LookDirection) -> Vec3 {
-    let x_rot = look_direction.x_rot * 0.017453292;
-    let y_rot = -look_direction.y_rot * 0.017453292;
-    let y_rot_cos = math::cos(y_rot);
-    let y_rot_sin = math::sin(y_rot);
-    let x_rot_cos = math::cos(x_rot);
-    let x_rot_sin = math::sin(x_rot);
-    Vec3 {
-        x: (y_rot_sin * x_rot_cos) as f64,
-        y: (-x_rot_sin) as f64,
-        z: (y_rot_cos * x_rot_cos) as f64,
-    }
+    rotation::view_vector(look_direction.y_rot, look_direction.x_rot)
 }
 
 /// Get the position of the block below the entity, but a little lower.
@@ -381,6 +373,20 @@ impl Physics {
 #[derive(Component, Copy, Clone, Default)]
 pub struct Dead;
 
+/// The entity that's holding the lead that this entity is attached to. This
+/// can be a player or a leash knot on a fence.
+///
+/// This is only present while the entity is leashed.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct LeashHolder(pub bevy_ecs::entity::Entity);
+
+/// The amount of experience that an experience orb is worth.
+///
+/// Experience orbs don't have any metadata, so this is sent when they're
+/// spawned instead.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct ExperienceOrbValue(pub u16);
+
 /// A component that contains the offset of the entity's eyes from the entity
 /// coordinates.
 ///
@@ -440,6 +446,7 @@ pub struct EntityBundle {
     pub jumping: Jumping,
     pub fluid_on_eyes: FluidOnEyes,
     pub on_climbable: OnClimbable,
+    pub active_effects: ActiveEffects,
 }
 
 impl EntityBundle {
@@ -469,11 +476,14 @@ impl EntityBundle {
                 speed: AttributeInstance::new(0.1),
                 attack_speed: AttributeInstance::new(4.0),
                 water_movement_efficiency: AttributeInstance::new(0.0),
+                block_break_speed: AttributeInstance::new(1.0),
+                submerged_mining_speed: AttributeInstance::new(0.2),
             },
 
             jumping: Jumping(false),
             fluid_on_eyes: FluidOnEyes(FluidKind::Empty),
             on_climbable: OnClimbable(false),
+            active_effects: ActiveEffects::default(),
         }
     }
 }
@@ -1,8 +1,8 @@
-use azalea_block::{Block, BlockBehavior};
+use azalea_block::{fluid_state::FluidKind, Block, BlockBehavior};
 use azalea_core::tier::get_item_tier;
 use azalea_registry as registry;
 
-use crate::{effects, FluidOnEyes, Physics};
+use crate::{effects::ActiveEffects, Attributes, FluidOnEyes, Physics};
 
 /// How much progress is made towards mining the block per tick, as a
 /// percentage. If this is 1 then the block gets broken instantly.
@@ -11,13 +11,17 @@ use crate::{effects, FluidOnEyes, Physics};
 /// takes to mine the block.
 ///
 /// The player inventory is needed to check your armor and offhand for modifiers
-/// to your mining speed.
+/// to your mining speed. Effects like haste and mining fatigue are taken from
+/// the [`ActiveEffects`], and the penalty for mining underwater comes from the
+/// `submerged_mining_speed` attribute (which aqua affinity increases).
 pub fn get_mine_progress(
     block: &dyn Block,
     held_item: registry::Item,
     player_inventory: &azalea_inventory::Menu,
     fluid_on_eyes: &FluidOnEyes,
     physics: &Physics,
+    attributes: &Attributes,
+    active_effects: &ActiveEffects,
 ) -> f32 {
     let block_behavior: BlockBehavior = block.behavior();
 
@@ -37,6 +41,8 @@ pub fn get_mine_progress(
         player_inventory,
         fluid_on_eyes,
         physics,
+        attributes,
+        active_effects,
     ) / destroy_time)
         / divider as f32
 }
@@ -77,8 +83,10 @@ fn destroy_speed(
     block: registry::Block,
     tool: registry::Item,
     _player_inventory: &azalea_inventory::Menu,
-    _fluid_on_eyes: &FluidOnEyes,
+    fluid_on_eyes: &FluidOnEyes,
     physics: &Physics,
+    attributes: &Attributes,
+    active_effects: &ActiveEffects,
 ) -> f32 {
     let mut base_destroy_speed = base_destroy_speed(block, tool);
 
@@ -92,11 +100,11 @@ fn destroy_speed(
     // efficiency_level + 1) as f32;     }
     // }
 
-    if let Some(dig_speed_amplifier) = effects::get_dig_speed_amplifier() {
+    if let Some(dig_speed_amplifier) = active_effects.get_dig_speed_amplifier() {
         base_destroy_speed *= 1. + (dig_speed_amplifier + 1) as f32 * 0.2;
     }
 
-    if let Some(dig_slowdown) = effects::get_effect(registry::MobEffect::MiningFatigue) {
+    if let Some(dig_slowdown) = active_effects.get_level(registry::MobEffect::MiningFatigue) {
         let multiplier = match dig_slowdown {
             0 => 0.3,
             1 => 0.09,
@@ -106,13 +114,12 @@ fn destroy_speed(
         base_destroy_speed *= multiplier;
     }
 
-    // TODO
-    // if **fluid_on_eyes == FluidKind::Water
-    //     && enchantments::get_enchant_level(registry::Enchantment::AquaAffinity,
-    // player_inventory)         == 0
-    // {
-    //     base_destroy_speed /= 5.;
-    // }
+    base_destroy_speed *= attributes.block_break_speed.calculate() as f32;
+
+    // aqua affinity is applied by the server as a modifier to this attribute
+    if **fluid_on_eyes == FluidKind::Water {
+        base_destroy_speed *= attributes.submerged_mining_speed.calculate() as f32;
+    }
 
     if !physics.on_ground {
         base_destroy_speed /= 5.;
@@ -168,3 +175,124 @@ fn base_destroy_speed(block: registry::Block, tool: registry::Item) -> f32 {
         1.
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use azalea_block::BlockState;
+    use azalea_inventory::{Menu, Player};
+    use azalea_registry::MobEffect;
+
+    use super::*;
+    use crate::{effects::MobEffectData, EntityDimensions};
+
+    /// How many ticks it takes to mine stone with a wooden pickaxe.
+    fn ticks_to_mine_stone(
+        fluid: FluidKind,
+        on_ground: bool,
+        attributes: &Attributes,
+        active_effects: &ActiveEffects,
+    ) -> f32 {
+        let stone = Box::<dyn Block>::from(BlockState::from(registry::Block::Stone));
+        let mut physics = Physics::new(
+            EntityDimensions::from(registry::EntityKind::Player),
+            Default::default(),
+        );
+        physics.set_on_ground(on_ground);
+        let progress = get_mine_progress(
+            stone.as_ref(),
+            registry::Item::WoodenPickaxe,
+            &Menu::Player(Player::default()),
+            &FluidOnEyes::new(fluid),
+            &physics,
+            attributes,
+            active_effects,
+        );
+        (1. / progress).ceil()
+    }
+
+    fn effect(effect: MobEffect, amplifier: u32) -> ActiveEffects {
+        let mut active_effects = ActiveEffects::default();
+        active_effects.insert(
+            effect,
+            MobEffectData {
+                amplifier,
+                duration_ticks: 100,
+            },
+        );
+        active_effects
+    }
+
+    #[test]
+    fn test_mine_stone() {
+        let attributes = Attributes::default();
+        let no_effects = ActiveEffects::default();
+
+        // 1.5 hardness / 2 speed * 30 = 22.5 ticks
+        assert_eq!(
+            ticks_to_mine_stone(FluidKind::Empty, true, &attributes, &no_effects),
+            23.
+        );
+        // 5x slower in the air
+        assert_eq!(
+            ticks_to_mine_stone(FluidKind::Empty, false, &attributes, &no_effects),
+            113.
+        );
+        // and 5x slower underwater without aqua affinity
+        assert_eq!(
+            ticks_to_mine_stone(FluidKind::Water, true, &attributes, &no_effects),
+            113.
+        );
+    }
+
+    #[test]
+    fn test_mine_with_effects() {
+        let attributes = Attributes::default();
+
+        // haste II is 40% faster
+        assert_eq!(
+            ticks_to_mine_stone(
+                FluidKind::Empty,
+                true,
+                &attributes,
+                &effect(MobEffect::Haste, 1)
+            ),
+            17.
+        );
+        // conduit power works like haste
+        assert_eq!(
+            ticks_to_mine_stone(
+                FluidKind::Empty,
+                true,
+                &attributes,
+                &effect(MobEffect::ConduitPower, 1)
+            ),
+            17.
+        );
+        // mining fatigue I makes it 0.3x as fast (22.5 / 0.3 = 75, but it might be
+        // rounded up because of floating point errors)
+        let ticks = ticks_to_mine_stone(
+            FluidKind::Empty,
+            true,
+            &attributes,
+            &effect(MobEffect::MiningFatigue, 0),
+        );
+        assert!((75.0..=76.0).contains(&ticks), "{ticks}");
+    }
+
+    #[test]
+    fn test_mine_with_aqua_affinity() {
+        let mut attributes = Attributes::default();
+        // aqua affinity sets the submerged mining speed to 1
+        attributes.submerged_mining_speed.base = 1.;
+
+        assert_eq!(
+            ticks_to_mine_stone(
+                FluidKind::Water,
+                true,
+                &attributes,
+                &ActiveEffects::default()
+            ),
+            23.
+        );
+    }
+}
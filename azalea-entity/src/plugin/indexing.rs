@@ -12,15 +12,24 @@ use bevy_ecs::{
 };
 use derive_more::{Deref, DerefMut};
 use nohash_hasher::IntMap;
+use thiserror::Error;
 use tracing::{debug, warn};
 use uuid::Uuid;
 
 use super::LoadedBy;
 use crate::{EntityUuid, Position};
 
+/// An index of local player entities by the UUID of their account, so a
+/// client that reconnects can reuse its entity.
+///
+/// This used to be called `EntityUuidIndex` and had every entity in it. Other
+/// entities are now indexed by their UUID in [`Instance::entity_by_uuid`]
+/// instead, since local players that don't share an instance each have their
+/// own entity for the same player. To find an entity by its UUID, use the
+/// index of the instance that it's in.
 #[derive(Resource, Default)]
-pub struct EntityUuidIndex {
-    /// An index of entities by their UUIDs
+pub struct LocalPlayerUuidIndex {
+    /// An index of local players by their UUIDs
     entity_by_uuid: HashMap<Uuid, Entity>,
 }
 
@@ -30,13 +39,38 @@ pub struct EntityUuidIndex {
 ///
 /// If you need a per-instance instead of per-client version of this, you can
 /// use [`Instance::entity_by_id`].
+///
+/// Servers reuse entity IDs, so if you're keeping an ID around for a while
+/// you should use an [`EntityIdRef`] from [`Self::get_ref`] instead. It'll
+/// fail to resolve if the ID was given to a different entity in the meantime.
 #[derive(Component, Default)]
 pub struct EntityIdIndex {
     /// An index of entities by their MinecraftEntityId
     entity_by_id: IntMap<MinecraftEntityId, Entity>,
+    /// The generation of each ID that's currently in the index. Entries are
+    /// removed along with their entity, and reused IDs get a new generation
+    /// from `next_generation`.
+    generations: IntMap<MinecraftEntityId, u32>,
+    next_generation: u32,
+}
+
+/// A Minecraft entity ID along with the generation that it had in an
+/// [`EntityIdIndex`] when this was created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntityIdRef {
+    pub id: MinecraftEntityId,
+    pub generation: u32,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityIdError {
+    #[error("There's no entity with the ID {0:?}")]
+    NotFound(MinecraftEntityId),
+    #[error("The entity ID {0:?} was reused by a different entity")]
+    Stale(MinecraftEntityId),
 }
 
-impl EntityUuidIndex {
+impl LocalPlayerUuidIndex {
     pub fn new() -> Self {
         Self {
             entity_by_uuid: HashMap::default(),
@@ -58,6 +92,13 @@ impl EntityUuidIndex {
     pub fn remove(&mut self, uuid: &Uuid) -> Option<Entity> {
         self.entity_by_uuid.remove(uuid)
     }
+
+    /// Remove the UUID from the index if it belongs to the given entity.
+    fn remove_if_entity(&mut self, uuid: &Uuid, entity: Entity) {
+        if self.get(uuid) == Some(entity) {
+            self.entity_by_uuid.remove(uuid);
+        }
+    }
 }
 
 impl EntityIdIndex {
@@ -71,16 +112,43 @@ impl EntityIdIndex {
 
     pub fn insert(&mut self, id: MinecraftEntityId, entity: Entity) {
         self.entity_by_id.insert(id, entity);
+        self.generations.insert(id, self.next_generation);
+        self.next_generation = self.next_generation.wrapping_add(1);
     }
 
     pub fn remove(&mut self, id: MinecraftEntityId) -> Option<Entity> {
+        self.generations.remove(&id);
         self.entity_by_id.remove(&id)
     }
+
+    /// Get a reference to the entity that currently has the given ID, which
+    /// can be resolved later with [`Self::resolve`].
+    pub fn get_ref(&self, id: MinecraftEntityId) -> Option<EntityIdRef> {
+        Some(EntityIdRef {
+            id,
+            generation: *self.generations.get(&id)?,
+        })
+    }
+
+    /// Get the ECS entity for an [`EntityIdRef`], or an error if the entity
+    /// was removed or its ID now belongs to a different entity.
+    ///
+    /// Unlike [`Self::get`], this will never return a different entity than
+    /// the one that the reference was created for.
+    pub fn resolve(&self, entity_ref: EntityIdRef) -> Result<Entity, EntityIdError> {
+        let entity = self
+            .get(entity_ref.id)
+            .ok_or(EntityIdError::NotFound(entity_ref.id))?;
+        if self.generations.get(&entity_ref.id).copied() != Some(entity_ref.generation) {
+            return Err(EntityIdError::Stale(entity_ref.id));
+        }
+        Ok(entity)
+    }
 }
 
-impl Debug for EntityUuidIndex {
+impl Debug for LocalPlayerUuidIndex {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("EntityUuidIndex").finish()
+        f.debug_struct("LocalPlayerUuidIndex").finish()
     }
 }
 
@@ -141,7 +209,7 @@ pub fn insert_entity_chunk_position(
 #[allow(clippy::type_complexity)]
 pub fn remove_despawned_entities_from_indexes(
     mut commands: Commands,
-    mut entity_uuid_index: ResMut<EntityUuidIndex>,
+    mut entity_uuid_index: ResMut<LocalPlayerUuidIndex>,
     instance_container: Res<InstanceContainer>,
     query: Query<
         (
@@ -161,11 +229,7 @@ pub fn remove_despawned_entities_from_indexes(
             debug!(
                 "Despawned entity {entity:?} because it's in an instance that isn't loaded anymore"
             );
-            if entity_uuid_index.entity_by_uuid.remove(uuid).is_none() {
-                warn!(
-                    "Tried to remove entity {entity:?} from the uuid index but it was not there."
-                );
-            }
+            entity_uuid_index.remove_if_entity(uuid, entity);
             // and now remove the entity from the ecs
             commands.entity(entity).despawn();
 
@@ -195,12 +259,32 @@ pub fn remove_despawned_entities_from_indexes(
         } else {
             debug!("Tried to remove entity {entity:?} from chunk {chunk:?} but the chunk was not found.");
         }
-        // remove it from the uuid index
-        if entity_uuid_index.entity_by_uuid.remove(uuid).is_none() {
-            warn!("Tried to remove entity {entity:?} from the uuid index but it was not there.");
+        // remove it from the uuid indexes, unless a different entity with the same uuid
+        // was added since then
+        match instance.entity_by_uuid.get(uuid) {
+            Some(&indexed_entity) if indexed_entity == entity => {
+                instance.entity_by_uuid.remove(uuid);
+            }
+            Some(_) => {}
+            None => {
+                warn!(
+                    "Tried to remove entity {entity:?} from the uuid index but it was not there."
+                );
+            }
         }
-        if instance.entity_by_id.remove(minecraft_id).is_none() {
-            warn!("Tried to remove entity {entity:?} from the id index but it was not there.");
+        entity_uuid_index.remove_if_entity(uuid, entity);
+        // the server might've already given this id to a different entity, in which
+        // case we shouldn't remove that one from the index
+        match instance.entity_by_id.get(minecraft_id) {
+            Some(&indexed_entity) if indexed_entity == entity => {
+                instance.entity_by_id.remove(minecraft_id);
+            }
+            Some(_) => {
+                debug!("Not removing {minecraft_id:?} from the id index since it was reused by a different entity");
+            }
+            None => {
+                warn!("Tried to remove entity {entity:?} from the id index but it was not there.");
+            }
         }
         // and now remove the entity from the ecs
         commands.entity(entity).despawn();
@@ -213,7 +297,6 @@ pub fn add_entity_to_indexes(
     ecs_entity: Entity,
     entity_uuid: Option<Uuid>,
     entity_id_index: &mut EntityIdIndex,
-    entity_uuid_index: &mut EntityUuidIndex,
     instance: &mut Instance,
 ) {
     // per-client id index
@@ -224,6 +307,54 @@ pub fn add_entity_to_indexes(
 
     if let Some(uuid) = entity_uuid {
         // per-instance uuid index
-        entity_uuid_index.insert(uuid, ecs_entity);
+        instance.entity_by_uuid.insert(uuid, ecs_entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::World;
+
+    use super::*;
+
+    #[test]
+    fn test_entity_id_ref() {
+        let mut ecs = World::new();
+        let (first, second) = (ecs.spawn_empty().id(), ecs.spawn_empty().id());
+        let id = MinecraftEntityId(5);
+
+        let mut index = EntityIdIndex::default();
+        assert_eq!(index.get_ref(id), None);
+        index.insert(id, first);
+        let first_ref = index.get_ref(id).unwrap();
+        assert_eq!(index.resolve(first_ref), Ok(first));
+
+        // the server reused the id without removing the old entity first
+        index.insert(id, second);
+        assert_eq!(index.resolve(first_ref), Err(EntityIdError::Stale(id)));
+        let second_ref = index.get_ref(id).unwrap();
+        assert_eq!(index.resolve(second_ref), Ok(second));
+
+        index.remove(id);
+        assert_eq!(index.resolve(second_ref), Err(EntityIdError::NotFound(id)));
+
+        // and reused after being removed
+        index.insert(id, first);
+        assert_eq!(index.resolve(first_ref), Err(EntityIdError::Stale(id)));
+        assert_eq!(index.resolve(second_ref), Err(EntityIdError::Stale(id)));
+    }
+
+    #[test]
+    fn test_generations_are_pruned() {
+        let mut ecs = World::new();
+        let entity = ecs.spawn_empty().id();
+
+        let mut index = EntityIdIndex::default();
+        for i in 0..1000 {
+            index.insert(MinecraftEntityId(i), entity);
+            index.remove(MinecraftEntityId(i));
+        }
+        assert!(index.entity_by_id.is_empty());
+        assert!(index.generations.is_empty());
     }
 }
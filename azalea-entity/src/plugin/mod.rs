@@ -6,13 +6,14 @@ use std::collections::HashSet;
 use azalea_block::{fluid_state::FluidKind, BlockState};
 use azalea_core::{
     position::{BlockPos, ChunkPos, Vec3},
+    rotation,
     tick::GameTick,
 };
 use azalea_world::{InstanceContainer, InstanceName, MinecraftEntityId};
 use bevy_app::{App, Plugin, PreUpdate, Update};
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
-use indexing::EntityUuidIndex;
+use indexing::LocalPlayerUuidIndex;
 pub use relative_updates::RelativeEntityUpdate;
 use tracing::debug;
 
@@ -63,7 +64,7 @@ impl Plugin for EntityPlugin {
         )
         .add_systems(Update, update_bounding_box)
         .add_systems(GameTick, update_in_loaded_chunk)
-        .init_resource::<EntityUuidIndex>();
+        .init_resource::<LocalPlayerUuidIndex>();
     }
 }
 
@@ -187,8 +188,8 @@ pub struct LoadedBy(pub HashSet<Entity>);
 
 pub fn clamp_look_direction(mut query: Query<&mut LookDirection>) {
     for mut look_direction in &mut query {
-        look_direction.y_rot = look_direction.y_rot.rem_euclid(360.0);
-        look_direction.x_rot = look_direction.x_rot.clamp(-90.0, 90.0) % 360.0;
+        look_direction.y_rot = rotation::normalize_y_rot(look_direction.y_rot);
+        look_direction.x_rot = rotation::clamp_x_rot(look_direction.x_rot);
     }
 }
 
@@ -0,0 +1,80 @@
+// This file was generated by codegen/lib/code/items.py, don't edit it manually!
+
+use azalea_registry::Item;
+
+/// The durability of the item when it's new, or `None` if it can't be damaged.
+///
+/// This is the default value of the `max_damage` component for the item.
+pub fn default_max_damage(item: Item) -> Option<i32> {
+    Some(match item {
+        Item::CarrotOnAStick => 25,
+        Item::WarpedFungusOnAStick => 100,
+        Item::Elytra => 432,
+        Item::TurtleHelmet => 275,
+        Item::WolfArmor => 64,
+        Item::FlintAndSteel => 64,
+        Item::Bow => 384,
+        Item::WoodenSword => 59,
+        Item::WoodenShovel => 59,
+        Item::WoodenPickaxe => 59,
+        Item::WoodenAxe => 59,
+        Item::WoodenHoe => 59,
+        Item::StoneSword => 131,
+        Item::StoneShovel => 131,
+        Item::StonePickaxe => 131,
+        Item::StoneAxe => 131,
+        Item::StoneHoe => 131,
+        Item::GoldenSword => 32,
+        Item::GoldenShovel => 32,
+        Item::GoldenPickaxe => 32,
+        Item::GoldenAxe => 32,
+        Item::GoldenHoe => 32,
+        Item::IronSword => 250,
+        Item::IronShovel => 250,
+        Item::IronPickaxe => 250,
+        Item::IronAxe => 250,
+        Item::IronHoe => 250,
+        Item::DiamondSword => 1561,
+        Item::DiamondShovel => 1561,
+        Item::DiamondPickaxe => 1561,
+        Item::DiamondAxe => 1561,
+        Item::DiamondHoe => 1561,
+        Item::NetheriteSword => 2031,
+        Item::NetheriteShovel => 2031,
+        Item::NetheritePickaxe => 2031,
+        Item::NetheriteAxe => 2031,
+        Item::NetheriteHoe => 2031,
+        Item::LeatherHelmet => 55,
+        Item::LeatherChestplate => 80,
+        Item::LeatherLeggings => 75,
+        Item::LeatherBoots => 65,
+        Item::ChainmailHelmet => 165,
+        Item::ChainmailChestplate => 240,
+        Item::ChainmailLeggings => 225,
+        Item::ChainmailBoots => 195,
+        Item::IronHelmet => 165,
+        Item::IronChestplate => 240,
+        Item::IronLeggings => 225,
+        Item::IronBoots => 195,
+        Item::DiamondHelmet => 363,
+        Item::DiamondChestplate => 528,
+        Item::DiamondLeggings => 495,
+        Item::DiamondBoots => 429,
+        Item::GoldenHelmet => 77,
+        Item::GoldenChestplate => 112,
+        Item::GoldenLeggings => 105,
+        Item::GoldenBoots => 91,
+        Item::NetheriteHelmet => 407,
+        Item::NetheriteChestplate => 592,
+        Item::NetheriteLeggings => 555,
+        Item::NetheriteBoots => 481,
+        Item::FishingRod => 64,
+        Item::Shears => 238,
+        Item::Mace => 500,
+        Item::Shield => 336,
+        Item::Trident => 250,
+        Item::Crossbow => 465,
+        Item::Brush => 64,
+        _ => return None,
+    })
+}
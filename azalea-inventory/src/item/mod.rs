@@ -1,3 +1,5 @@
+mod max_damage;
+
 pub trait MaxStackSizeExt {
     /// Get the maximum stack size for this item.
     ///
@@ -21,3 +23,21 @@ impl MaxStackSizeExt for azalea_registry::Item {
         64
     }
 }
+
+pub trait MaxDamageExt {
+    /// Get the durability of this item when it's new, or `None` if it can't be
+    /// damaged.
+    ///
+    /// Items can override this with the [`MaxDamage`] component, so prefer
+    /// [`ItemStackData::max_damage`] if you have one.
+    ///
+    /// [`MaxDamage`]: crate::components::MaxDamage
+    /// [`ItemStackData::max_damage`]: crate::ItemStackData::max_damage
+    fn max_damage(&self) -> Option<i32>;
+}
+
+impl MaxDamageExt for azalea_registry::Item {
+    fn max_damage(&self) -> Option<i32> {
+        max_damage::default_max_damage(*self)
+    }
+}
@@ -3,12 +3,14 @@
 /// Representations of various inventory data structures in Minecraft.
 pub mod components;
 pub mod item;
+mod matcher;
 pub mod operations;
 mod slot;
 
 use std::ops::{Deref, DerefMut, RangeInclusive};
 
 use azalea_inventory_macros::declare_menus;
+pub use matcher::ItemMatcher;
 pub use slot::{DataComponentPatch, ItemStack, ItemStackData};
 
 // TODO: remove this here and in azalea-inventory-macros when rust makes
@@ -48,6 +50,38 @@ impl Menu {
             unreachable!("Called `Menu::as_player` on a menu that wasn't `Player`.")
         }
     }
+
+    /// Get the index of the first slot with an item that matches.
+    ///
+    /// Use [`Self::find_in`] if you only want to search some of the slots, like
+    /// [`Self::player_slots_range`].
+    pub fn find(&self, matcher: &ItemMatcher) -> Option<usize> {
+        self.find_in(0..=self.len() - 1, matcher)
+    }
+
+    /// Get the index of the first slot in the range with an item that matches.
+    pub fn find_in(&self, range: RangeInclusive<usize>, matcher: &ItemMatcher) -> Option<usize> {
+        range
+            .into_iter()
+            .find(|&i| self.slot(i).is_some_and(|item| matcher.matches(item)))
+    }
+
+    /// Get the indexes of every slot in the range with an item that matches.
+    pub fn find_all_in(&self, range: RangeInclusive<usize>, matcher: &ItemMatcher) -> Vec<usize> {
+        range
+            .into_iter()
+            .filter(|&i| self.slot(i).is_some_and(|item| matcher.matches(item)))
+            .collect()
+    }
+
+    /// The total number of matching items in the range.
+    pub fn count_in(&self, range: RangeInclusive<usize>, matcher: &ItemMatcher) -> i32 {
+        self.find_all_in(range, matcher)
+            .into_iter()
+            .filter_map(|i| self.slot(i))
+            .map(ItemStack::count)
+            .sum()
+    }
 }
 
 // the player inventory part is always the last 36 slots (except in the Player
@@ -0,0 +1,343 @@
+use std::{
+    collections::HashSet,
+    ops::{Bound, RangeBounds},
+};
+
+use azalea_registry::{Enchantment, Item};
+
+use crate::{components, ItemStack, ItemStackData};
+
+/// A filter for items, so searching inventories and containers works the same
+/// everywhere.
+///
+/// Every condition that's set has to match, and a matcher with no conditions
+/// matches every item. Empty slots never match.
+///
+/// ```
+/// # use azalea_inventory::ItemMatcher;
+/// # use azalea_registry::{Enchantment, Item};
+/// # fn example(efficiency: Enchantment) {
+/// // a pickaxe with at least efficiency 3 that isn't about to break. the ids
+/// // of enchantments are decided by the server, so `efficiency` has to be
+/// // looked up in the registries that it sent.
+/// let matcher = ItemMatcher::new()
+///     .kinds([Item::DiamondPickaxe, Item::NetheritePickaxe])
+///     .enchantment(efficiency, 3)
+///     .min_durability(100);
+/// # }
+///
+/// // any kind of log, as long as there's a full stack of it
+/// let matcher = ItemMatcher::new()
+///     .tag(&azalea_registry::tags::items::LOGS)
+///     .count(64..);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ItemMatcher {
+    kinds: Option<HashSet<Item>>,
+    tags: Vec<&'static HashSet<Item>>,
+    enchantments: Vec<(Enchantment, u32)>,
+    name_pattern: Option<String>,
+    min_durability: Option<i32>,
+    count: Option<(Bound<i32>, Bound<i32>)>,
+}
+
+impl ItemMatcher {
+    /// Create a matcher that matches every item.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match this kind of item.
+    ///
+    /// This can be called multiple times to match any of the kinds.
+    #[must_use]
+    pub fn kind(self, kind: Item) -> Self {
+        self.kinds([kind])
+    }
+    /// Only match these kinds of items.
+    #[must_use]
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = Item>) -> Self {
+        self.kinds.get_or_insert_with(HashSet::new).extend(kinds);
+        self
+    }
+
+    /// Only match items in the given tag, like
+    /// [`azalea_registry::tags::items::LOGS`].
+    ///
+    /// If this is called multiple times, the item has to be in every tag.
+    #[must_use]
+    pub fn tag(mut self, tag: &'static HashSet<Item>) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Only match items that have the given enchantment at the given level or
+    /// higher. Enchanted books count too.
+    #[must_use]
+    pub fn enchantment(mut self, enchantment: Enchantment, min_level: u32) -> Self {
+        self.enchantments.push((enchantment, min_level));
+        self
+    }
+
+    /// Only match items whose name matches the pattern, ignoring case. `*`
+    /// matches any number of characters.
+    ///
+    /// The name is the custom name if the item was renamed, or the item's id
+    /// without the `minecraft:` otherwise, like `diamond_pickaxe`.
+    ///
+    /// ```
+    /// # use azalea_inventory::{ItemMatcher, ItemStack, ItemStackData};
+    /// # use azalea_registry::Item;
+    /// let item = ItemStack::Present(ItemStackData {
+    ///     count: 1,
+    ///     kind: Item::DiamondPickaxe,
+    ///     components: Default::default(),
+    /// });
+    /// assert!(ItemMatcher::new().name("*pickaxe").matches(&item));
+    /// assert!(ItemMatcher::new().name("Diamond_*").matches(&item));
+    /// assert!(!ItemMatcher::new().name("diamond").matches(&item));
+    /// ```
+    #[must_use]
+    pub fn name(mut self, pattern: impl Into<String>) -> Self {
+        self.name_pattern = Some(pattern.into().to_lowercase());
+        self
+    }
+
+    /// Only match items that have at least this much durability left. Items
+    /// that can't be damaged always match.
+    #[must_use]
+    pub fn min_durability(mut self, durability: i32) -> Self {
+        self.min_durability = Some(durability);
+        self
+    }
+
+    /// Only match stacks with a count in the given range, like `16..` or
+    /// `1..=8`.
+    #[must_use]
+    pub fn count(mut self, range: impl RangeBounds<i32>) -> Self {
+        self.count = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    /// Whether the item in the slot matches every condition.
+    pub fn matches(&self, item: &ItemStack) -> bool {
+        match item {
+            ItemStack::Empty => false,
+            ItemStack::Present(item) => self.matches_data(item),
+        }
+    }
+
+    /// Like [`Self::matches`], but for an item that's known to be present.
+    pub fn matches_data(&self, item: &ItemStackData) -> bool {
+        if item.is_empty() {
+            return false;
+        }
+
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&item.kind) {
+                return false;
+            }
+        }
+        if !self.tags.iter().all(|tag| tag.contains(&item.kind)) {
+            return false;
+        }
+        if !self
+            .enchantments
+            .iter()
+            .all(|(enchantment, min_level)| enchantment_level(item, *enchantment) >= *min_level)
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.name_pattern {
+            if !glob_matches(pattern, &item_name(item).to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(min_durability) = self.min_durability {
+            if item
+                .durability()
+                .is_some_and(|durability| durability < min_durability)
+            {
+                return false;
+            }
+        }
+        if let Some(count) = &self.count {
+            if !count.contains(&item.count) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl From<Item> for ItemMatcher {
+    fn from(kind: Item) -> Self {
+        Self::new().kind(kind)
+    }
+}
+
+fn enchantment_level(item: &ItemStackData, enchantment: Enchantment) -> u32 {
+    let level = item
+        .components
+        .get::<components::Enchantments>()
+        .and_then(|e| e.levels.get(&enchantment).copied())
+        .unwrap_or_default();
+    let stored_level = item
+        .components
+        .get::<components::StoredEnchantments>()
+        .and_then(|e| e.enchantments.get(&enchantment).copied())
+        .unwrap_or_default()
+        .max(0) as u32;
+    level.max(stored_level)
+}
+
+fn item_name(item: &ItemStackData) -> String {
+    if let Some(custom_name) = item.components.get::<components::CustomName>() {
+        return custom_name.name.to_string();
+    }
+    let id = item.kind.to_string();
+    id.strip_prefix("minecraft:").unwrap_or(&id).to_owned()
+}
+
+/// Check whether the text matches the pattern, where `*` matches any number of
+/// characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // there's always at least one part, even if the pattern is empty
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // no wildcards, so the whole text has to match
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Cursor};
+
+    use azalea_buf::AzaleaRead;
+
+    use super::*;
+    use crate::DataComponentPatch;
+
+    /// Enchantments are in a registry that's sent by the server, so they can
+    /// only be made by reading their id.
+    fn enchantment_with_id(id: u8) -> Enchantment {
+        Enchantment::azalea_read(&mut Cursor::new(&[id])).unwrap()
+    }
+
+    fn item(kind: Item, count: i32, components: DataComponentPatch) -> ItemStack {
+        ItemStack::Present(ItemStackData {
+            kind,
+            count,
+            components,
+        })
+    }
+
+    fn enchanted(enchantment: Enchantment, level: u32) -> DataComponentPatch {
+        let mut components = DataComponentPatch::default();
+        components.insert(components::Enchantments {
+            levels: HashMap::from([(enchantment, level)]),
+            show_in_tooltip: true,
+        });
+        components
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("stone", "stone"));
+        assert!(!glob_matches("stone", "stone_bricks"));
+        assert!(!glob_matches("stone", "cobblestone"));
+
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("*_log", "oak_log"));
+        assert!(!glob_matches("*_log", "oak_logs"));
+        assert!(glob_matches("oak_*", "oak_planks"));
+        assert!(glob_matches("*oak*", "dark_oak_log"));
+        assert!(glob_matches("a*b*c", "abc"));
+        assert!(glob_matches("a*b*c", "a_b_b_c"));
+        assert!(!glob_matches("a*b*c", "a_c"));
+        // the parts can't overlap
+        assert!(!glob_matches("ab*ba", "aba"));
+        assert!(glob_matches("**", "x"));
+        assert!(glob_matches("", ""));
+        assert!(!glob_matches("", "x"));
+    }
+
+    #[test]
+    fn test_enchantment_level() {
+        let sharpness = enchantment_with_id(0);
+        let knockback = enchantment_with_id(1);
+        let mending = enchantment_with_id(2);
+
+        let ItemStack::Present(sword) = item(Item::DiamondSword, 1, enchanted(sharpness, 3)) else {
+            unreachable!()
+        };
+        assert_eq!(enchantment_level(&sword, sharpness), 3);
+        assert_eq!(enchantment_level(&sword, knockback), 0);
+
+        let mut components = DataComponentPatch::default();
+        components.insert(components::StoredEnchantments {
+            enchantments: HashMap::from([(mending, 1)]),
+            show_in_tooltip: true,
+        });
+        let ItemStack::Present(book) = item(Item::EnchantedBook, 1, components) else {
+            unreachable!()
+        };
+        assert_eq!(enchantment_level(&book, mending), 1);
+
+        let matcher = ItemMatcher::new().enchantment(sharpness, 3);
+        assert!(matcher.matches(&item(Item::DiamondSword, 1, enchanted(sharpness, 5))));
+        assert!(!matcher.matches(&item(Item::DiamondSword, 1, enchanted(sharpness, 2))));
+        assert!(!matcher.matches(&item(Item::DiamondSword, 1, Default::default())));
+    }
+
+    #[test]
+    fn test_count() {
+        let stack = |count| item(Item::Stone, count, Default::default());
+
+        let matcher = ItemMatcher::new().count(16..);
+        assert!(!matcher.matches(&stack(15)));
+        assert!(matcher.matches(&stack(16)));
+        assert!(matcher.matches(&stack(64)));
+
+        let matcher = ItemMatcher::new().count(1..=8);
+        assert!(matcher.matches(&stack(1)));
+        assert!(matcher.matches(&stack(8)));
+        assert!(!matcher.matches(&stack(9)));
+
+        let matcher = ItemMatcher::new().count(..4);
+        assert!(matcher.matches(&stack(3)));
+        assert!(!matcher.matches(&stack(4)));
+
+        // empty slots never match, even if the range includes 0
+        assert!(!ItemMatcher::new().count(..).matches(&ItemStack::Empty));
+    }
+
+    #[test]
+    fn test_min_durability() {
+        let mut damaged = DataComponentPatch::default();
+        damaged.insert(components::Damage { amount: 1500 });
+        let matcher = ItemMatcher::new().min_durability(100);
+
+        assert!(matcher.matches(&item(Item::DiamondPickaxe, 1, Default::default())));
+        // 1561 - 1500 = 61
+        assert!(!matcher.matches(&item(Item::DiamondPickaxe, 1, damaged)));
+        // items that can't be damaged always match
+        assert!(matcher.matches(&item(Item::Stone, 1, Default::default())));
+    }
+}
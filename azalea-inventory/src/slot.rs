@@ -8,7 +8,10 @@ use azalea_buf::{AzaleaRead, AzaleaReadVar, AzaleaWrite, AzaleaWriteVar, BufRead
 use azalea_registry::DataComponentKind;
 use indexmap::IndexMap;
 
-use crate::components::{self};
+use crate::{
+    components::{self},
+    item::MaxDamageExt,
+};
 
 /// Either an item in an inventory or nothing.
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -139,6 +142,26 @@ impl ItemStackData {
     pub fn is_same_item_and_components(&self, other: &ItemStackData) -> bool {
         self.kind == other.kind && self.components == other.components
     }
+
+    /// The durability of this item when it's new, or `None` if it can't be
+    /// damaged.
+    pub fn max_damage(&self) -> Option<i32> {
+        if let Some(max_damage) = self.components.get::<components::MaxDamage>() {
+            return Some(max_damage.amount);
+        }
+        self.kind.max_damage()
+    }
+
+    /// How much durability this item has left, or `None` if it can't be
+    /// damaged.
+    pub fn durability(&self) -> Option<i32> {
+        let damage = self
+            .components
+            .get::<components::Damage>()
+            .map(|d| d.amount)
+            .unwrap_or_default();
+        self.max_damage().map(|max_damage| max_damage - damage)
+    }
 }
 
 impl AzaleaRead for ItemStack {
@@ -227,6 +250,15 @@ impl DataComponentPatch {
     pub fn has_kind(&self, kind: DataComponentKind) -> bool {
         self.get_kind(kind).is_some()
     }
+
+    /// Set the component in the patch, replacing the old value if there was
+    /// one.
+    pub fn insert<T>(&mut self, component: T)
+    where
+        T: components::DataComponent + components::EncodableDataComponent,
+    {
+        self.components.insert(T::KIND, Some(Box::new(component)));
+    }
 }
 
 impl AzaleaRead for DataComponentPatch {
@@ -1,10 +1,70 @@
 #![doc = include_str!("../README.md")]
 
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::Path,
+    sync::{Arc, LazyLock, RwLock},
+};
 
+/// The embedded `en_us` translations, which are used when a key is missing
+/// from the current [`Language`].
 pub static STORAGE: LazyLock<HashMap<String, String>> =
     LazyLock::new(|| serde_json::from_str(include_str!("en_us.json")).unwrap());
 
-pub fn get(key: &str) -> Option<&str> {
+static CURRENT_LANGUAGE: LazyLock<RwLock<Arc<Language>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(Language::default())));
+
+/// Get the embedded `en_us` translation for a key.
+///
+/// This ignores the language that was set with [`set_language`], use
+/// [`current_language`] if you want that.
+pub fn get(key: &str) -> Option<&'static str> {
     STORAGE.get(key).map(|s| s.as_str())
 }
+
+/// A set of translations, like the ones in a Minecraft language file
+/// (`assets/minecraft/lang/de_de.json`).
+///
+/// Keys that are missing fall back to the embedded `en_us` translations.
+#[derive(Clone, Debug, Default)]
+pub struct Language {
+    translations: HashMap<String, String>,
+}
+
+impl Language {
+    pub fn new(translations: HashMap<String, String>) -> Self {
+        Self { translations }
+    }
+
+    /// Parse the contents of a language file.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    /// Read and parse a language file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Get the translation for a key, falling back to `en_us` if this language
+    /// doesn't have it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.translations
+            .get(key)
+            .map(|s| s.as_str())
+            .or_else(|| get(key))
+    }
+}
+
+/// Change the language that translatable text is shown in.
+pub fn set_language(language: Language) {
+    *CURRENT_LANGUAGE.write().unwrap() = Arc::new(language);
+}
+
+/// The language that was set with [`set_language`], or a language that only
+/// has the embedded `en_us` translations if it was never set.
+pub fn current_language() -> Arc<Language> {
+    CURRENT_LANGUAGE.read().unwrap().clone()
+}
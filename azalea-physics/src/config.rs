@@ -0,0 +1,67 @@
+use bevy_ecs::system::Resource;
+
+/// The constants that are used for simulating the movement of local entities.
+///
+/// These default to vanilla's values, but some servers have plugins that
+/// change things like gravity or movement speed. If our physics don't match
+/// the server's then we'll constantly get teleported back, so you can change
+/// these to keep the client's prediction in sync.
+///
+/// ```
+/// # use azalea_physics::PhysicsConfig;
+/// # fn example(mut commands: bevy_ecs::system::Commands) {
+/// // the server has slightly lower gravity than normal
+/// commands.insert_resource(PhysicsConfig {
+///     gravity: 0.06,
+///     ..Default::default()
+/// });
+/// # }
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct PhysicsConfig {
+    /// How much is subtracted from the entity's vertical velocity every tick
+    /// while it's in the air.
+    pub gravity: f64,
+    /// What the vertical velocity is multiplied by every tick while the entity
+    /// is in the air.
+    pub vertical_drag: f64,
+    /// What the horizontal velocity is multiplied by every tick while the
+    /// entity is in the air. On the ground, this is also multiplied by the
+    /// friction of the block below.
+    pub horizontal_drag: f32,
+    /// What the entity's movement input is multiplied by every tick.
+    pub input_drag: f32,
+
+    /// The vertical velocity that's set when the entity jumps, before the
+    /// jump factor of the block it's on is applied.
+    pub jump_power: f32,
+    /// The extra horizontal velocity that's added when the entity jumps while
+    /// sprinting.
+    pub sprint_jump_boost: f32,
+
+    /// Used to calculate how fast the entity accelerates on the ground from
+    /// its speed attribute and the friction of the block below.
+    pub ground_acceleration: f32,
+    /// How fast the entity accelerates while it's in the air.
+    pub air_acceleration: f32,
+    /// How fast the entity accelerates while it's in the air and sprinting.
+    pub sprinting_air_acceleration: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 0.08,
+            vertical_drag: 0.9800000190734863,
+            horizontal_drag: 0.91,
+            input_drag: 0.98,
+
+            jump_power: 0.42,
+            sprint_jump_boost: 0.2,
+
+            ground_acceleration: 0.216,
+            air_acceleration: 0.02,
+            sprinting_air_acceleration: 0.025999999,
+        }
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod clip;
 pub mod collision;
+pub mod config;
 pub mod fluids;
 pub mod travel;
 
@@ -28,6 +29,7 @@ use bevy_ecs::{
 };
 use clip::box_traverse_blocks;
 use collision::{move_colliding, BlockWithShape, MoverType, VoxelShape, BLOCK_SHAPE};
+pub use config::PhysicsConfig;
 
 /// A Bevy [`SystemSet`] for running physics that makes entities do things.
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
@@ -36,7 +38,7 @@ pub struct PhysicsSet;
 pub struct PhysicsPlugin;
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<PhysicsConfig>().add_systems(
             GameTick,
             (
                 fluids::update_in_water_state_and_do_fluid_pushing
@@ -44,6 +46,7 @@ impl Plugin for PhysicsPlugin {
                 update_old_position,
                 fluids::update_swimming.after(azalea_entity::update_fluid_on_eyes),
                 ai_step,
+                travel::boost_fall_flying_with_fireworks,
                 travel::travel,
                 apply_effects_from_blocks,
             )
@@ -71,6 +74,7 @@ pub fn ai_step(
         (With<LocalEntity>, With<InLoadedChunk>),
     >,
     instance_container: Res<InstanceContainer>,
+    config: Res<PhysicsConfig>,
 ) {
     for (mut physics, jumping, position, look_direction, sprinting, instance_name) in &mut query {
         // vanilla does movement interpolation here, doesn't really matter much for a
@@ -119,6 +123,7 @@ pub fn ai_step(
                                 sprinting,
                                 instance_name,
                                 &instance_container,
+                                &config,
                             );
                             physics.no_jump_delay = 10;
                         }
@@ -133,8 +138,8 @@ pub fn ai_step(
             physics.no_jump_delay = 0;
         }
 
-        physics.x_acceleration *= 0.98;
-        physics.z_acceleration *= 0.98;
+        physics.x_acceleration *= config.input_drag;
+        physics.z_acceleration *= config.input_drag;
 
         // TODO: freezing, pushEntities, drowning damage (in their own systems,
         // after `travel`)
@@ -314,13 +319,14 @@ pub fn jump_from_ground(
     sprinting: &Sprinting,
     instance_name: &InstanceName,
     instance_container: &InstanceContainer,
+    config: &PhysicsConfig,
 ) {
     let world_lock = instance_container
         .get(instance_name)
         .expect("All entities should be in a valid world");
     let world = world_lock.read();
 
-    let jump_power: f64 = jump_power(&world, position) as f64 + jump_boost_power();
+    let jump_power: f64 = jump_power(&world, position, config) as f64 + jump_boost_power();
     let old_delta_movement = physics.velocity;
     physics.velocity = Vec3 {
         x: old_delta_movement.x,
@@ -331,9 +337,9 @@ pub fn jump_from_ground(
         // sprint jumping gives some extra velocity
         let y_rot = look_direction.y_rot * 0.017453292;
         physics.velocity += Vec3 {
-            x: (-math::sin(y_rot) * 0.2) as f64,
+            x: (-math::sin(y_rot) * config.sprint_jump_boost) as f64,
             y: 0.,
-            z: (math::cos(y_rot) * 0.2) as f64,
+            z: (math::cos(y_rot) * config.sprint_jump_boost) as f64,
         };
     }
 
@@ -367,6 +373,7 @@ struct HandleRelativeFrictionAndCalculateMovementOpts<'a> {
     on_climbable: &'a OnClimbable,
     pose: Option<&'a Pose>,
     jumping: &'a Jumping,
+    config: &'a PhysicsConfig,
 }
 fn handle_relative_friction_and_calculate_movement(
     HandleRelativeFrictionAndCalculateMovementOpts {
@@ -380,12 +387,13 @@ fn handle_relative_friction_and_calculate_movement(
         on_climbable,
         pose,
         jumping,
+        config,
     }: HandleRelativeFrictionAndCalculateMovementOpts<'_>,
 ) -> Vec3 {
     move_relative(
         physics,
         direction,
-        get_friction_influenced_speed(physics, attributes, block_friction, is_sprinting),
+        get_friction_influenced_speed(physics, attributes, block_friction, is_sprinting, config),
         &Vec3 {
             x: physics.x_acceleration as f64,
             y: physics.y_acceleration as f64,
@@ -469,17 +477,18 @@ fn get_friction_influenced_speed(
     attributes: &Attributes,
     friction: f32,
     is_sprinting: bool,
+    config: &PhysicsConfig,
 ) -> f32 {
     // TODO: have speed & flying_speed fields in entity
     if physics.on_ground() {
         let speed: f32 = attributes.speed.calculate() as f32;
-        speed * (0.216f32 / (friction * friction * friction))
+        speed * (config.ground_acceleration / (friction * friction * friction))
     } else {
         // entity.flying_speed
         if is_sprinting {
-            0.025999999f32
+            config.sprinting_air_acceleration
         } else {
-            0.02
+            config.air_acceleration
         }
     }
 }
@@ -514,8 +523,8 @@ fn block_jump_factor(world: &Instance, position: &Position) -> f32 {
 // public double getJumpBoostPower() {
 //     return this.hasEffect(MobEffects.JUMP) ? (double)(0.1F *
 // (float)(this.getEffect(MobEffects.JUMP).getAmplifier() + 1)) : 0.0D; }
-fn jump_power(world: &Instance, position: &Position) -> f32 {
-    0.42 * block_jump_factor(world, position)
+fn jump_power(world: &Instance, position: &Position, config: &PhysicsConfig) -> f32 {
+    config.jump_power * block_jump_factor(world, position)
 }
 
 fn jump_boost_power() -> f64 {
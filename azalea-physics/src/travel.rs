@@ -1,16 +1,18 @@
 use azalea_block::{Block, BlockState};
 use azalea_core::{aabb::AABB, position::Vec3};
 use azalea_entity::{
-    metadata::Sprinting, move_relative, Attributes, InLoadedChunk, Jumping, LocalEntity,
-    LookDirection, OnClimbable, Physics, Pose, Position,
+    metadata::{AttachedToTarget, FallFlying, FireworkRocket, Sprinting},
+    move_relative, view_vector, Attributes, InLoadedChunk, Jumping, LocalEntity, LookDirection,
+    OnClimbable, Physics, Pose, Position,
 };
-use azalea_world::{Instance, InstanceContainer, InstanceName};
+use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId};
 use bevy_ecs::prelude::*;
+use tracing::warn;
 
 use crate::{
     collision::{move_colliding, MoverType},
     get_block_pos_below_that_affects_movement, handle_relative_friction_and_calculate_movement,
-    HandleRelativeFrictionAndCalculateMovementOpts,
+    HandleRelativeFrictionAndCalculateMovementOpts, PhysicsConfig,
 };
 
 /// Move the entity with the given acceleration while handling friction,
@@ -28,10 +30,12 @@ pub fn travel(
             &InstanceName,
             &OnClimbable,
             &Jumping,
+            Option<&FallFlying>,
         ),
         (With<LocalEntity>, With<InLoadedChunk>),
     >,
     instance_container: Res<InstanceContainer>,
+    config: Res<PhysicsConfig>,
 ) {
     for (
         mut physics,
@@ -43,6 +47,7 @@ pub fn travel(
         world_name,
         on_climbable,
         jumping,
+        fall_flying,
     ) in &mut query
     {
         let Some(world_lock) = instance_container.get(world_name) else {
@@ -52,8 +57,6 @@ pub fn travel(
 
         let sprinting = *sprinting.unwrap_or(&Sprinting(false));
 
-        // TODO: elytras
-
         if physics.is_in_water() || physics.is_in_lava() {
             // minecraft also checks for `this.isAffectedByFluids() &&
             // !this.canStandOnFluid(fluidAtBlock)` here but it doesn't matter
@@ -66,7 +69,10 @@ pub fn travel(
                 sprinting,
                 on_climbable,
                 &world,
+                &config,
             );
+        } else if fall_flying.is_some_and(|f| **f) {
+            travel_fall_flying(&mut physics, &direction, position, &world, &config);
         } else {
             travel_in_air(
                 &mut physics,
@@ -78,6 +84,44 @@ pub fn travel(
                 pose,
                 jumping,
                 &world,
+                &config,
+            );
+        }
+    }
+}
+
+/// Apply the boost from firework rockets that are attached to local players
+/// that are gliding with an elytra.
+///
+/// The server is the one that spawns the rocket, so this doesn't start
+/// happening until we receive the rocket entity.
+#[allow(clippy::type_complexity)]
+pub fn boost_fall_flying_with_fireworks(
+    rockets: Query<&AttachedToTarget, With<FireworkRocket>>,
+    mut query: Query<
+        (
+            &MinecraftEntityId,
+            &mut Physics,
+            &LookDirection,
+            &FallFlying,
+        ),
+        (With<LocalEntity>, With<InLoadedChunk>),
+    >,
+) {
+    for attached_to_target in &rockets {
+        let Some(target_id) = attached_to_target.0 .0 else {
+            continue;
+        };
+        for (entity_id, mut physics, direction, fall_flying) in &mut query {
+            if **entity_id != target_id || !**fall_flying {
+                continue;
+            }
+            let look_angle = view_vector(direction);
+            let velocity = physics.velocity;
+            physics.velocity += Vec3::new(
+                look_angle.x * 0.1 + (look_angle.x * 1.5 - velocity.x) * 0.5,
+                look_angle.y * 0.1 + (look_angle.y * 1.5 - velocity.y) * 0.5,
+                look_angle.z * 0.1 + (look_angle.z * 1.5 - velocity.z) * 0.5,
             );
         }
     }
@@ -95,8 +139,9 @@ fn travel_in_air(
     pose: Option<&Pose>,
     jumping: &Jumping,
     world: &Instance,
+    config: &PhysicsConfig,
 ) {
-    let gravity = get_effective_gravity();
+    let gravity = get_effective_gravity(config);
 
     let block_pos_below = get_block_pos_below_that_affects_movement(&position);
 
@@ -108,9 +153,9 @@ fn travel_in_air(
     let block_friction = block_below.behavior().friction;
 
     let inertia = if physics.on_ground() {
-        block_friction * 0.91
+        block_friction * config.horizontal_drag
     } else {
-        0.91
+        config.horizontal_drag
     };
 
     // this applies the current delta
@@ -126,6 +171,7 @@ fn travel_in_air(
             on_climbable,
             pose,
             jumping,
+            config,
         },
     );
 
@@ -143,12 +189,76 @@ fn travel_in_air(
     } else {
         physics.velocity = Vec3 {
             x: movement.x * inertia as f64,
-            y: movement.y * 0.9800000190734863f64,
+            y: movement.y * config.vertical_drag,
             z: movement.z * inertia as f64,
         };
     }
 }
 
+/// The movement when we're gliding with an elytra.
+fn travel_fall_flying(
+    physics: &mut Physics,
+    direction: &LookDirection,
+    mut position: Mut<Position>,
+    world: &Instance,
+    config: &PhysicsConfig,
+) {
+    physics.velocity = update_fall_flying_movement(physics.velocity, direction, config);
+    if let Err(e) = move_colliding(
+        MoverType::Own,
+        &physics.velocity.clone(),
+        world,
+        &mut position,
+        physics,
+    ) {
+        warn!("Failed to move while gliding: {e}");
+    }
+}
+
+fn update_fall_flying_movement(
+    mut velocity: Vec3,
+    direction: &LookDirection,
+    config: &PhysicsConfig,
+) -> Vec3 {
+    let look_angle = view_vector(direction);
+    let x_rot = direction.x_rot.to_radians();
+    let look_horizontal_distance = look_angle.horizontal_distance_squared().sqrt();
+    let horizontal_speed = velocity.horizontal_distance_squared().sqrt();
+    let gravity = get_effective_gravity(config);
+    let lift = (x_rot as f64).cos().powi(2);
+
+    velocity.y += gravity * (-1.0 + lift * 0.75);
+    if velocity.y < 0.0 && look_horizontal_distance > 0.0 {
+        let i = velocity.y * -0.1 * lift;
+        velocity += Vec3::new(
+            look_angle.x * i / look_horizontal_distance,
+            i,
+            look_angle.z * i / look_horizontal_distance,
+        );
+    }
+    if x_rot < 0.0 && look_horizontal_distance > 0.0 {
+        let i = horizontal_speed * (-x_rot.sin() as f64) * 0.04;
+        velocity += Vec3::new(
+            -look_angle.x * i / look_horizontal_distance,
+            i * 3.2,
+            -look_angle.z * i / look_horizontal_distance,
+        );
+    }
+    if look_horizontal_distance > 0.0 {
+        velocity.x +=
+            (look_angle.x / look_horizontal_distance * horizontal_speed - velocity.x) * 0.1;
+        velocity.z +=
+            (look_angle.z / look_horizontal_distance * horizontal_speed - velocity.z) * 0.1;
+    }
+
+    Vec3 {
+        x: velocity.x * 0.99f32 as f64,
+        y: velocity.y * 0.98f32 as f64,
+        z: velocity.z * 0.99f32 as f64,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn travel_in_fluid(
     physics: &mut Physics,
     direction: &LookDirection,
@@ -157,10 +267,11 @@ fn travel_in_fluid(
     sprinting: Sprinting,
     on_climbable: &OnClimbable,
     world: &Instance,
+    config: &PhysicsConfig,
 ) {
     let moving_down = physics.velocity.y <= 0.;
     let y = position.y;
-    let gravity = get_effective_gravity();
+    let gravity = get_effective_gravity(config);
 
     let acceleration = Vec3::new(
         physics.x_acceleration as f64,
@@ -286,9 +397,9 @@ fn is_free(bounding_box: AABB, world: &Instance, x: f64, y: f64, z: f64) -> bool
     true
 }
 
-fn get_effective_gravity() -> f64 {
+fn get_effective_gravity(config: &PhysicsConfig) -> f64 {
     // TODO: slow falling effect
-    0.08
+    config.gravity
 }
 
 pub fn fluid_jump_threshold() -> f64 {
@@ -297,3 +408,94 @@ pub fn fluid_jump_threshold() -> f64 {
     // was shrunk)
     0.4
 }
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::{position::ChunkPos, resource_location::ResourceLocation, tick::GameTick};
+    use azalea_entity::EntityBundle;
+    use azalea_world::{Chunk, PartialInstance};
+    use bevy_app::App;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn glide(velocity: Vec3, x_rot: f32, ticks: usize) -> Vec3 {
+        let config = PhysicsConfig::default();
+        let direction = LookDirection::new(0., x_rot);
+        (0..ticks).fold(velocity, |velocity, _| {
+            update_fall_flying_movement(velocity, &direction, &config)
+        })
+    }
+
+    #[test]
+    fn test_gliding_falls_slowly() {
+        let velocity = glide(Vec3::new(0., 0., 1.), 0., 20);
+        // without an elytra we'd be falling at more than a block per tick by now
+        assert!(velocity.y < 0. && velocity.y > -0.5, "{velocity:?}");
+        assert!(velocity.z > 0.5, "{velocity:?}");
+        // we keep going in the direction we're looking
+        assert!(velocity.x.abs() < 1e-6, "{velocity:?}");
+    }
+
+    #[test]
+    fn test_gliding_pitch() {
+        let level = glide(Vec3::new(0., 0., 1.), 0., 20);
+        let diving = glide(Vec3::new(0., 0., 1.), 45., 20);
+        assert!(diving.y < level.y);
+        assert!(diving.z > level.z);
+
+        // looking up turns our speed into height
+        let climbing = glide(Vec3::new(0., 0., 1.5), -30., 1);
+        assert!(climbing.y > 0., "{climbing:?}");
+        assert!(climbing.z < 1.5, "{climbing:?}");
+    }
+
+    #[test]
+    fn test_travel_fall_flying() {
+        let mut app = App::new();
+        app.add_plugins((crate::PhysicsPlugin, azalea_entity::EntityPlugin))
+            .init_resource::<InstanceContainer>();
+        let world_lock = app.world_mut().resource_mut::<InstanceContainer>().insert(
+            ResourceLocation::new("minecraft:overworld"),
+            384,
+            -64,
+        );
+        let mut partial_world = PartialInstance::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut world_lock.write().chunks,
+        );
+
+        let spawn_entity = |app: &mut App, x: f64, fall_flying: bool| {
+            let entity = app
+                .world_mut()
+                .spawn((
+                    EntityBundle::new(
+                        Uuid::from_u128(x as u128),
+                        Vec3::new(x, 100., 0.5),
+                        azalea_registry::EntityKind::Player,
+                        ResourceLocation::new("minecraft:overworld"),
+                    ),
+                    MinecraftEntityId(x as i32),
+                    LocalEntity,
+                    FallFlying(fall_flying),
+                ))
+                .id();
+            app.world_mut().get_mut::<Physics>(entity).unwrap().velocity = Vec3::new(0., 0., 0.5);
+            entity
+        };
+        let gliding = spawn_entity(&mut app, 2.5, true);
+        let falling = spawn_entity(&mut app, 5.5, false);
+
+        for _ in 0..20 {
+            app.update();
+            app.world_mut().run_schedule(GameTick);
+        }
+        let gliding_pos = **app.world().get::<Position>(gliding).unwrap();
+        let falling_pos = **app.world().get::<Position>(falling).unwrap();
+        assert!(gliding_pos.y > falling_pos.y, "{gliding_pos} {falling_pos}");
+        assert!(gliding_pos.z > falling_pos.z, "{gliding_pos} {falling_pos}");
+        assert!((gliding_pos.x - 2.5).abs() < 1e-6, "{gliding_pos}");
+    }
+}
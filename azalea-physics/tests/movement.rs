@@ -0,0 +1,75 @@
+mod simulation;
+
+use azalea_block::{blocks::StoneSlab, properties::Type};
+use simulation::{Input, TestWorld};
+
+/// The `y_rot` for facing towards +x.
+const EAST: f32 = 270.;
+
+#[test]
+fn test_fall_onto_slab() {
+    let mut sim = TestWorld::new(&[&["_"], &["."], &["P"]])
+        .block(
+            '_',
+            StoneSlab {
+                kind: Type::Bottom,
+                waterlogged: false,
+            },
+        )
+        .spawn();
+    sim.wait(20);
+    assert_eq!(sim.position().y, 0.5);
+    assert!(sim.physics().on_ground());
+}
+
+#[test]
+fn test_walk_into_wall() {
+    #[rustfmt::skip]
+    let mut sim = TestWorld::new(&[
+        &["#####"],
+        &["P..#."],
+        &["...#."],
+    ])
+    .spawn();
+    sim.run(&[(40, Input::forward().facing(EAST))]);
+
+    let position = sim.position();
+    // the player is 0.6 blocks wide, so they stop 0.3 blocks before the wall
+    assert!((position.x - 2.7).abs() < 0.001, "{position:?}");
+    assert_eq!(position.y, 1.);
+    assert!(sim.physics().horizontal_collision);
+}
+
+#[test]
+fn test_jump_height() {
+    let mut sim = TestWorld::new(&[&["#"], &["P"]]).spawn();
+    sim.wait(5);
+    assert_eq!(sim.position().y, 1.);
+
+    sim.tick(Input::jump());
+    let mut max_y = sim.position().y;
+    for _ in 0..20 {
+        sim.wait(1);
+        max_y = max_y.max(sim.position().y);
+    }
+    // vanilla jumps are about 1.25 blocks high
+    assert!(max_y > 2.2 && max_y < 2.3, "{max_y}");
+    assert_eq!(sim.position().y, 1.);
+}
+
+#[test]
+fn test_sprinting_is_faster_than_walking() {
+    let layers: &[&[&str]] = &[&["####################"], &["P..................."]];
+
+    let mut walking = TestWorld::new(layers).spawn();
+    walking.wait(5);
+    walking.run(&[(20, Input::forward().facing(EAST))]);
+
+    let mut sprinting = TestWorld::new(layers).spawn();
+    sprinting.wait(5);
+    sprinting.run(&[(20, Input::forward().and_sprint().facing(EAST))]);
+
+    assert!(walking.position().x > 2.);
+    assert!(sprinting.position().x > walking.position().x);
+    assert!(sprinting.position().x < 20.);
+}
@@ -4,7 +4,7 @@ use azalea_core::{
     tick::GameTick,
 };
 use azalea_entity::{EntityBundle, EntityPlugin, LocalEntity, Physics, Position};
-use azalea_physics::PhysicsPlugin;
+use azalea_physics::{PhysicsConfig, PhysicsPlugin};
 use azalea_world::{Chunk, InstanceContainer, MinecraftEntityId, PartialInstance};
 use bevy_app::App;
 use uuid::Uuid;
@@ -78,6 +78,50 @@ fn test_gravity() {
     }
 }
 #[test]
+fn test_no_gravity_config() {
+    let mut app = make_test_app();
+    app.insert_resource(PhysicsConfig {
+        gravity: 0.,
+        ..Default::default()
+    });
+    let world_lock = app.world_mut().resource_mut::<InstanceContainer>().insert(
+        ResourceLocation::new("minecraft:overworld"),
+        384,
+        -64,
+    );
+    let mut partial_world = PartialInstance::default();
+    partial_world.chunks.set(
+        &ChunkPos { x: 0, z: 0 },
+        Some(Chunk::default()),
+        &mut world_lock.write().chunks,
+    );
+
+    let entity = app
+        .world_mut()
+        .spawn((
+            EntityBundle::new(
+                Uuid::nil(),
+                Vec3 {
+                    x: 0.,
+                    y: 70.,
+                    z: 0.,
+                },
+                azalea_registry::EntityKind::Zombie,
+                ResourceLocation::new("minecraft:overworld"),
+            ),
+            MinecraftEntityId(0),
+            LocalEntity,
+        ))
+        .id();
+    app.update();
+    for _ in 0..5 {
+        app.world_mut().run_schedule(GameTick);
+        app.update();
+    }
+    let entity_pos = *app.world_mut().get::<Position>(entity).unwrap();
+    assert_eq!(entity_pos.y, 70.);
+}
+#[test]
 fn test_collision() {
     let mut app = make_test_app();
     let world_lock = app.world_mut().resource_mut::<InstanceContainer>().insert(
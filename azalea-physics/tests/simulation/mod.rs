@@ -0,0 +1,248 @@
+//! A small DSL for writing movement tests.
+//!
+//! The world is written as a list of layers from the bottom up, where each
+//! layer is a list of rows going towards +z and each character in a row is a
+//! block going towards +x. A `P` marks where the player starts, with their feet
+//! at the bottom of that block.
+//!
+//! ```ignore
+//! let mut sim = TestWorld::new(&[
+//!     &[
+//!         "###",
+//!         "#_#",
+//!     ],
+//!     &[
+//!         "P..",
+//!         "...",
+//!     ],
+//! ])
+//! .block('_', StoneSlab { kind: Type::Bottom, waterlogged: false })
+//! .spawn();
+//! sim.run(&[(10, Input::forward().facing(270.)), (5, Input::default())]);
+//! assert_eq!(sim.position().y, 1.);
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use azalea_block::BlockState;
+use azalea_core::{
+    position::{BlockPos, ChunkPos, Vec3},
+    resource_location::ResourceLocation,
+    tick::GameTick,
+};
+use azalea_entity::{
+    attributes,
+    metadata::{PlayerMetadataBundle, Sprinting},
+    Attributes, EntityBundle, EntityPlugin, Jumping, LocalEntity, LookDirection, Physics, Position,
+};
+use azalea_physics::PhysicsPlugin;
+use azalea_world::{Chunk, Instance, InstanceContainer, MinecraftEntityId, PartialInstance};
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+/// Where the bottom corner of the first row of the first layer is placed.
+pub const ORIGIN: BlockPos = BlockPos::new(0, 64, 0);
+
+/// A world described as layers of characters. See the module docs for the
+/// format.
+pub struct TestWorld<'a> {
+    layers: &'a [&'a [&'a str]],
+    legend: HashMap<char, BlockState>,
+}
+
+impl<'a> TestWorld<'a> {
+    /// Create a world from the given layers. `#` is stone and `.` or a space
+    /// is air, other blocks can be added with [`Self::block`].
+    pub fn new(layers: &'a [&'a [&'a str]]) -> Self {
+        let mut legend = HashMap::new();
+        legend.insert('#', azalea_registry::Block::Stone.into());
+        Self { layers, legend }
+    }
+
+    /// Use the given block wherever the character appears in the layers.
+    pub fn block(mut self, c: char, block: impl Into<BlockState>) -> Self {
+        self.legend.insert(c, block.into());
+        self
+    }
+
+    /// Build the world and put a player at the `P`.
+    pub fn spawn(self) -> Simulation {
+        let mut app = App::new();
+        app.add_plugins((PhysicsPlugin, EntityPlugin))
+            .init_resource::<InstanceContainer>();
+        let instance = app.world_mut().resource_mut::<InstanceContainer>().insert(
+            ResourceLocation::new("minecraft:overworld"),
+            384,
+            -64,
+        );
+        let mut partial_instance = PartialInstance::default();
+
+        let mut start = None;
+        for (y, layer) in self.layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    let pos = BlockPos::new(
+                        ORIGIN.x + x as i32,
+                        ORIGIN.y + y as i32,
+                        ORIGIN.z + z as i32,
+                    );
+                    let chunk_pos = ChunkPos::from(&pos);
+                    if instance.read().chunks.get(&chunk_pos).is_none() {
+                        partial_instance.chunks.set(
+                            &chunk_pos,
+                            Some(Chunk::default()),
+                            &mut instance.write().chunks,
+                        );
+                    }
+
+                    let state = match c {
+                        '.' | ' ' => continue,
+                        'P' => {
+                            assert!(start.is_none(), "The world can only have one P");
+                            start = Some(pos);
+                            continue;
+                        }
+                        c => *self
+                            .legend
+                            .get(&c)
+                            .unwrap_or_else(|| panic!("{c:?} isn't in the legend")),
+                    };
+                    partial_instance
+                        .chunks
+                        .set_block_state(&pos, state, &instance.write().chunks)
+                        .expect("The chunk should be loaded");
+                }
+            }
+        }
+        let start = start.expect("The world must have a P where the player starts");
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                EntityBundle::new(
+                    Uuid::nil(),
+                    Vec3::new(start.x as f64 + 0.5, start.y as f64, start.z as f64 + 0.5),
+                    azalea_registry::EntityKind::Player,
+                    ResourceLocation::new("minecraft:overworld"),
+                ),
+                PlayerMetadataBundle::default(),
+                MinecraftEntityId(0),
+                LocalEntity,
+            ))
+            .id();
+        app.update();
+
+        Simulation {
+            app,
+            entity,
+            _instance: instance,
+            _partial_instance: partial_instance,
+        }
+    }
+}
+
+/// The controls that the player is using for a tick.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Input {
+    /// 1 for walking forward and -1 for walking backward.
+    pub forward: f32,
+    /// 1 for walking left and -1 for walking right.
+    pub left: f32,
+    pub jump: bool,
+    pub sprint: bool,
+    /// The direction that the player is facing. See
+    /// [`azalea_core::rotation`].
+    pub y_rot: f32,
+}
+
+impl Input {
+    pub fn forward() -> Self {
+        Self {
+            forward: 1.,
+            ..Default::default()
+        }
+    }
+    pub fn jump() -> Self {
+        Self {
+            jump: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn and_sprint(self) -> Self {
+        Self {
+            sprint: true,
+            ..self
+        }
+    }
+    pub fn facing(self, y_rot: f32) -> Self {
+        Self { y_rot, ..self }
+    }
+}
+
+/// A player in a [`TestWorld`].
+pub struct Simulation {
+    pub app: App,
+    pub entity: Entity,
+    // the world is only kept alive as long as something has a strong reference to it
+    _instance: Arc<RwLock<Instance>>,
+    _partial_instance: PartialInstance,
+}
+
+impl Simulation {
+    /// Run one tick with the given controls.
+    pub fn tick(&mut self, input: Input) {
+        let mut entity = self.app.world_mut().entity_mut(self.entity);
+
+        let mut physics = entity.get_mut::<Physics>().unwrap();
+        physics.x_acceleration = input.left;
+        physics.z_acceleration = input.forward;
+        **entity.get_mut::<Jumping>().unwrap() = input.jump;
+        entity.get_mut::<LookDirection>().unwrap().y_rot = input.y_rot;
+
+        let was_sprinting = **entity.get::<Sprinting>().unwrap();
+        if input.sprint != was_sprinting {
+            **entity.get_mut::<Sprinting>().unwrap() = input.sprint;
+            let mut entity_attributes = entity.get_mut::<Attributes>().unwrap();
+            if input.sprint {
+                let _ = entity_attributes
+                    .speed
+                    .try_insert(attributes::sprinting_modifier());
+            } else {
+                entity_attributes
+                    .speed
+                    .remove(&attributes::sprinting_modifier().id);
+            }
+        }
+
+        self.app.world_mut().run_schedule(GameTick);
+        self.app.update();
+    }
+
+    /// Run each input for the given number of ticks, in order.
+    pub fn run(&mut self, script: &[(u32, Input)]) {
+        for &(ticks, input) in script {
+            for _ in 0..ticks {
+                self.tick(input);
+            }
+        }
+    }
+
+    /// Run ticks without pressing anything.
+    pub fn wait(&mut self, ticks: u32) {
+        self.run(&[(ticks, Input::default())]);
+    }
+
+    /// The position of the player's feet, relative to the bottom corner of the
+    /// first row of the first layer.
+    pub fn position(&self) -> Vec3 {
+        let position = **self.app.world().get::<Position>(self.entity).unwrap();
+        position - ORIGIN.to_vec3_floored()
+    }
+
+    pub fn physics(&self) -> &Physics {
+        self.app.world().get::<Physics>(self.entity).unwrap()
+    }
+}
@@ -198,6 +198,8 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
     let mut serverbound_id_match_contents = quote!();
     let mut clientbound_name_match_contents = quote!();
     let mut serverbound_name_match_contents = quote!();
+    let mut clientbound_name_from_id_match_contents = quote!();
+    let mut serverbound_name_from_id_match_contents = quote!();
     let mut clientbound_write_match_contents = quote!();
     let mut serverbound_write_match_contents = quote!();
     let mut clientbound_read_match_contents = quote!();
@@ -225,34 +227,19 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         clientbound_name_match_contents.extend(quote! {
             #clientbound_state_name::#variant_name(..) => #packet_name_litstr,
         });
+        clientbound_name_from_id_match_contents.extend(quote! {
+            #id => #packet_name_litstr,
+        });
         clientbound_write_match_contents.extend(quote! {
             #clientbound_state_name::#variant_name(packet) => packet.write(buf),
         });
         clientbound_read_match_contents.extend(quote! {
-            #id => {
-                let data = #module_name::#struct_name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
-                    source: e,
-                    packet_id: #id,
-                    backtrace: Box::new(std::backtrace::Backtrace::capture()),
-                    packet_name: #packet_name_litstr.to_string(),
-                })?;
-                #[cfg(debug_assertions)]
-                {
-                    let mut leftover = Vec::new();
-                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
-                    if !leftover.is_empty() {
-                        return Err(
-                            Box::new(
-                                crate::read::ReadPacketError::LeftoverData {
-                                    packet_name: #packet_name_litstr.to_string(),
-                                    data: leftover
-                                }
-                            )
-                        );
-                    }
-                }
-                data
-            },
+            #id => #module_name::#struct_name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
+                source: e,
+                packet_id: #id,
+                backtrace: Box::new(std::backtrace::Backtrace::capture()),
+                packet_name: #packet_name_litstr.to_string(),
+            })?,
         });
     }
     for (id, packet_name) in input.serverbound.packets.iter().enumerate() {
@@ -277,27 +264,19 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         serverbound_name_match_contents.extend(quote! {
             #serverbound_state_name::#variant_name(..) => #packet_name_litstr,
         });
+        serverbound_name_from_id_match_contents.extend(quote! {
+            #id => #packet_name_litstr,
+        });
         serverbound_write_match_contents.extend(quote! {
             #serverbound_state_name::#variant_name(packet) => packet.write(buf),
         });
         serverbound_read_match_contents.extend(quote! {
-            #id => {
-                let data = #module_name::#struct_name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
-                    source: e,
-                    packet_id: #id,
-                    backtrace: Box::new(std::backtrace::Backtrace::capture()),
-                    packet_name: #packet_name_litstr.to_string(),
-                })?;
-                #[cfg(debug_assertions)]
-                {
-                    let mut leftover = Vec::new();
-                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
-                    if !leftover.is_empty() {
-                        return Err(Box::new(crate::read::ReadPacketError::LeftoverData { packet_name: #packet_name_litstr.to_string(), data: leftover }));
-                    }
-                }
-                data
-            },
+            #id => #module_name::#struct_name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
+                source: e,
+                packet_id: #id,
+                backtrace: Box::new(std::backtrace::Backtrace::capture()),
+                packet_name: #packet_name_litstr.to_string(),
+            })?,
         });
     }
 
@@ -358,6 +337,13 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 }
             }
 
+            fn name_from_id(id: u32) -> Option<&'static str> {
+                Some(match id {
+                    #serverbound_name_from_id_match_contents
+                    _ => return None,
+                })
+            }
+
             fn write(&self, buf: &mut impl std::io::Write) -> Result<(), std::io::Error> {
                 match self {
                     #serverbound_write_match_contents
@@ -369,6 +355,25 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 id: u32,
                 buf: &mut std::io::Cursor<&[u8]>,
             ) -> Result<#serverbound_state_name, Box<crate::read::ReadPacketError>>
+            where
+                Self: Sized,
+            {
+                let data = Self::read_allowing_leftover(id, buf)?;
+                #[cfg(debug_assertions)]
+                {
+                    let mut leftover = Vec::new();
+                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
+                    if !leftover.is_empty() {
+                        return Err(Box::new(crate::read::ReadPacketError::LeftoverData { packet_name: crate::packets::ProtocolPacket::name(&data).to_string(), data: leftover }));
+                    }
+                }
+                Ok(data)
+            }
+
+            fn read_allowing_leftover(
+                id: u32,
+                buf: &mut std::io::Cursor<&[u8]>,
+            ) -> Result<#serverbound_state_name, Box<crate::read::ReadPacketError>>
             where
                 Self: Sized,
             {
@@ -402,6 +407,13 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 }
             }
 
+            fn name_from_id(id: u32) -> Option<&'static str> {
+                Some(match id {
+                    #clientbound_name_from_id_match_contents
+                    _ => return None,
+                })
+            }
+
             fn write(&self, buf: &mut impl std::io::Write) -> Result<(), std::io::Error> {
                 match self {
                     #clientbound_write_match_contents
@@ -413,6 +425,25 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 id: u32,
                 buf: &mut std::io::Cursor<&[u8]>,
             ) -> Result<#clientbound_state_name, Box<crate::read::ReadPacketError>>
+            where
+                Self: Sized,
+            {
+                let data = Self::read_allowing_leftover(id, buf)?;
+                #[cfg(debug_assertions)]
+                {
+                    let mut leftover = Vec::new();
+                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
+                    if !leftover.is_empty() {
+                        return Err(Box::new(crate::read::ReadPacketError::LeftoverData { packet_name: crate::packets::ProtocolPacket::name(&data).to_string(), data: leftover }));
+                    }
+                }
+                Ok(data)
+            }
+
+            fn read_allowing_leftover(
+                id: u32,
+                buf: &mut std::io::Cursor<&[u8]>,
+            ) -> Result<#clientbound_state_name, Box<crate::read::ReadPacketError>>
             where
                 Self: Sized,
             {
@@ -0,0 +1,150 @@
+use std::{hint::black_box, io::Cursor};
+
+use azalea_buf::{AzaleaRead, AzaleaWrite};
+use azalea_core::position::Vec3;
+use azalea_entity::LookDirection;
+use azalea_protocol::{
+    packets::{
+        game::{
+            ClientboundGamePacket, ClientboundLevelChunkWithLight, ServerboundGamePacket,
+            ServerboundMovePlayerPosRot,
+        },
+        Packet,
+    },
+    read::deserialize_packet,
+    write::serialize_packet,
+};
+use azalea_world::Chunk;
+use criterion::{criterion_group, criterion_main, Criterion};
+use simdnbt::owned::{BaseNbt, Nbt, NbtCompound, NbtList, NbtTag};
+
+/// A chunk packet from a vanilla server, without the packet id.
+const LEVEL_CHUNK_WITH_LIGHT: &[u8] = include_bytes!("fixtures/level_chunk_with_light.bin");
+
+fn level_chunk_with_light() -> ClientboundLevelChunkWithLight {
+    ClientboundLevelChunkWithLight::azalea_read(&mut Cursor::new(LEVEL_CHUNK_WITH_LIGHT)).unwrap()
+}
+
+fn bench_chunk_decode(c: &mut Criterion) {
+    let empty_heightmaps = BaseNbt::default();
+    c.bench_function("decode chunk packet", |b| {
+        b.iter(|| {
+            let packet = ClientboundLevelChunkWithLight::azalea_read(&mut Cursor::new(black_box(
+                LEVEL_CHUNK_WITH_LIGHT,
+            )))
+            .unwrap();
+            let chunk = Chunk::read_with_dimension_height(
+                &mut Cursor::new(&packet.chunk_data.data),
+                256,
+                0,
+                packet.chunk_data.heightmaps.unwrap_or(&empty_heightmaps),
+            )
+            .unwrap();
+            black_box(chunk);
+        });
+    });
+}
+
+fn bench_packet_serialization(c: &mut Criterion) {
+    let chunk_packet = level_chunk_with_light().into_variant();
+    c.bench_function("serialize chunk packet", |b| {
+        b.iter(|| black_box(serialize_packet(black_box(&chunk_packet)).unwrap()));
+    });
+    let chunk_bytes = serialize_packet(&chunk_packet).unwrap();
+    c.bench_function("deserialize chunk packet", |b| {
+        b.iter(|| {
+            black_box(
+                deserialize_packet::<ClientboundGamePacket>(&mut Cursor::new(black_box(
+                    &chunk_bytes,
+                )))
+                .unwrap(),
+            )
+        });
+    });
+
+    let move_packet = ServerboundMovePlayerPosRot {
+        pos: Vec3::new(123.5, 64., -456.25),
+        look_direction: LookDirection::new(90., 12.5),
+        on_ground: true,
+    }
+    .into_variant();
+    c.bench_function("serialize movement packet", |b| {
+        b.iter(|| black_box(serialize_packet(black_box(&move_packet)).unwrap()));
+    });
+    let move_bytes = serialize_packet(&move_packet).unwrap();
+    c.bench_function("deserialize movement packet", |b| {
+        b.iter(|| {
+            black_box(
+                deserialize_packet::<ServerboundGamePacket>(&mut Cursor::new(black_box(
+                    &move_bytes,
+                )))
+                .unwrap(),
+            )
+        });
+    });
+}
+
+/// NBT that looks like a chest full of items, which has more nesting and
+/// strings than the heightmaps in chunk packets.
+fn generate_chest_nbt() -> Nbt {
+    let items = (0..27)
+        .map(|slot| {
+            NbtCompound::from_values(vec![
+                ("Slot".into(), NbtTag::Byte(slot)),
+                (
+                    "id".into(),
+                    NbtTag::String("minecraft:diamond_sword".into()),
+                ),
+                ("count".into(), NbtTag::Int(1)),
+                (
+                    "components".into(),
+                    NbtTag::Compound(NbtCompound::from_values(vec![
+                        ("minecraft:damage".into(), NbtTag::Int(slot as i32 * 10)),
+                        (
+                            "minecraft:custom_name".into(),
+                            NbtTag::String(format!("{{\"text\":\"Sword {slot}\"}}").into()),
+                        ),
+                    ])),
+                ),
+            ])
+        })
+        .collect();
+    Nbt::Some(BaseNbt::new(
+        "",
+        NbtCompound::from_values(vec![
+            ("id".into(), NbtTag::String("minecraft:chest".into())),
+            ("Items".into(), NbtTag::List(NbtList::Compound(items))),
+        ]),
+    ))
+}
+
+fn bench_nbt(c: &mut Criterion) {
+    let heightmaps = level_chunk_with_light().chunk_data.heightmaps;
+    let chest = generate_chest_nbt();
+
+    for (name, nbt) in [("heightmaps", heightmaps), ("chest", chest)] {
+        let mut group = c.benchmark_group(format!("NBT round-trip ({name})"));
+        let mut bytes = Vec::new();
+        nbt.azalea_write(&mut bytes).unwrap();
+
+        group.bench_function("write", |b| {
+            b.iter(|| {
+                let mut bytes = Vec::new();
+                black_box(&nbt).azalea_write(&mut bytes).unwrap();
+                black_box(bytes);
+            });
+        });
+        group.bench_function("read", |b| {
+            b.iter(|| black_box(Nbt::azalea_read(&mut Cursor::new(black_box(&bytes))).unwrap()));
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_chunk_decode,
+    bench_packet_serialization,
+    bench_nbt
+);
+criterion_main!(benches);
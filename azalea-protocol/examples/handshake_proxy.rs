@@ -110,6 +110,8 @@ async fn handle_connection(stream: TcpStream) -> anyhow::Result<()> {
                                 players: PROXY_PLAYERS.clone(),
                                 version: PROXY_VERSION.clone(),
                                 enforces_secure_chat: PROXY_SECURE_CHAT,
+                                forge_data: None,
+                                mod_info: None,
                             })
                             .await?;
                         }
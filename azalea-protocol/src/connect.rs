@@ -1,17 +1,19 @@
 //! Connect to remote servers/clients.
 
 use std::fmt::Debug;
-use std::io::Cursor;
+use std::io::{self, Cursor};
 use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use azalea_auth::game_profile::GameProfile;
 use azalea_auth::sessionserver::{ClientSessionServerError, ServerSessionServerError};
 use azalea_crypto::{Aes128CfbDec, Aes128CfbEnc};
+use base64::{prelude::BASE64_STANDARD, Engine};
 use thiserror::Error;
-use tokio::io::{AsyncWriteExt, BufStream};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufStream};
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
-use tokio::net::TcpStream;
+use tokio::net::{TcpSocket, TcpStream};
 use tracing::{error, info};
 use uuid::Uuid;
 
@@ -22,7 +24,10 @@ use crate::packets::login::c_hello::ClientboundHello;
 use crate::packets::login::{ClientboundLoginPacket, ServerboundLoginPacket};
 use crate::packets::status::{ClientboundStatusPacket, ServerboundStatusPacket};
 use crate::packets::ProtocolPacket;
-use crate::read::{deserialize_packet, read_raw_packet, try_read_raw_packet, ReadPacketError};
+use crate::read::{
+    deserialize_packet, deserialize_packet_lenient, read_raw_packet, try_read_raw_packet,
+    LenientPacket, ReadPacketError,
+};
 use crate::write::{serialize_packet, write_raw_packet};
 
 pub struct RawReadConnection {
@@ -30,12 +35,18 @@ pub struct RawReadConnection {
     pub buffer: Cursor<Vec<u8>>,
     pub compression_threshold: Option<u32>,
     pub dec_cipher: Option<Aes128CfbDec>,
+    /// How long to wait for a packet before giving up on the connection. See
+    /// [`SocketOptions::read_timeout`].
+    pub read_timeout: Option<Duration>,
 }
 
 pub struct RawWriteConnection {
     pub write_stream: OwnedWriteHalf,
     pub compression_threshold: Option<u32>,
     pub enc_cipher: Option<Aes128CfbEnc>,
+    /// How long writing a packet can take before giving up on the
+    /// connection. See [`SocketOptions::write_timeout`].
+    pub write_timeout: Option<Duration>,
 }
 
 /// The read half of a connection.
@@ -135,13 +146,24 @@ pub struct Connection<R: ProtocolPacket, W: ProtocolPacket> {
 
 impl RawReadConnection {
     pub async fn read(&mut self) -> Result<Box<[u8]>, Box<ReadPacketError>> {
-        read_raw_packet::<_>(
+        let read_timeout = self.read_timeout;
+        let read = read_raw_packet::<_>(
             &mut self.read_stream,
             &mut self.buffer,
             self.compression_threshold,
             &mut self.dec_cipher,
-        )
-        .await
+        );
+        match read_timeout {
+            Some(read_timeout) => tokio::time::timeout(read_timeout, read)
+                .await
+                .map_err(|_| {
+                    Box::new(ReadPacketError::from(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("Didn't get a packet for {read_timeout:?}"),
+                    )))
+                })?,
+            None => read.await,
+        }
     }
 
     pub fn try_read(&mut self) -> Result<Option<Box<[u8]>>, Box<ReadPacketError>> {
@@ -156,14 +178,25 @@ impl RawReadConnection {
 
 impl RawWriteConnection {
     pub async fn write(&mut self, packet: &[u8]) -> std::io::Result<()> {
-        if let Err(e) = write_raw_packet(
+        let write_timeout = self.write_timeout;
+        let write = write_raw_packet(
             packet,
             &mut self.write_stream,
             self.compression_threshold,
             &mut self.enc_cipher,
-        )
-        .await
-        {
+        );
+        let result = match write_timeout {
+            Some(write_timeout) => tokio::time::timeout(write_timeout, write)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("Couldn't write a packet in {write_timeout:?}"),
+                    ))
+                }),
+            None => write.await,
+        };
+        if let Err(e) = result {
             // detect broken pipe
             if e.kind() == std::io::ErrorKind::BrokenPipe {
                 info!("Broken pipe, shutting down connection.");
@@ -200,6 +233,15 @@ where
         };
         Ok(Some(deserialize_packet(&mut Cursor::new(&raw_packet))?))
     }
+
+    /// Read a packet from the stream, but return a warning instead of an error
+    /// if it has an unknown id or extra data at the end.
+    ///
+    /// See [`deserialize_packet_lenient`] for more details.
+    pub async fn read_lenient(&mut self) -> Result<LenientPacket<R>, Box<ReadPacketError>> {
+        let raw_packet = self.raw.read().await?;
+        deserialize_packet_lenient(&mut Cursor::new(&raw_packet))
+    }
 }
 impl<W> WriteConnection<W>
 where
@@ -232,6 +274,13 @@ where
         self.reader.try_read()
     }
 
+    /// Read a packet from the other side of the connection, but return a
+    /// warning instead of an error if it has an unknown id or extra data at
+    /// the end.
+    pub async fn read_lenient(&mut self) -> Result<LenientPacket<R>, Box<ReadPacketError>> {
+        self.reader.read_lenient().await
+    }
+
     /// Write a packet to the other side of the connection.
     pub async fn write(&mut self, packet: impl crate::packets::Packet<W>) -> std::io::Result<()> {
         let packet = packet.into_variant();
@@ -249,47 +298,256 @@ where
 pub enum ConnectionError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("The HTTP proxy refused to connect: {0}")]
+    HttpProxy(String),
 }
 
 use socks5_impl::protocol::UserKey;
 
+/// A proxy that connections to servers can be routed through, so bots can
+/// connect from different IP addresses.
 #[derive(Debug, Clone)]
 pub struct Proxy {
     pub addr: SocketAddr,
     pub auth: Option<UserKey>,
+    /// The protocol that's used to talk to the proxy. This is
+    /// [`ProxyKind::Socks5`] by default.
+    pub kind: ProxyKind,
+}
+
+/// The protocols that a [`Proxy`] can use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProxyKind {
+    #[default]
+    Socks5,
+    /// An HTTP proxy that supports the `CONNECT` method. The auth is sent with
+    /// basic authentication.
+    Http,
 }
 
 impl Proxy {
+    /// Create a new Socks5 proxy. This is the same as [`Self::socks5`].
     pub fn new(addr: SocketAddr, auth: Option<UserKey>) -> Self {
-        Self { addr, auth }
+        Self::socks5(addr, auth)
+    }
+    pub fn socks5(addr: SocketAddr, auth: Option<UserKey>) -> Self {
+        Self {
+            addr,
+            auth,
+            kind: ProxyKind::Socks5,
+        }
+    }
+    pub fn http(addr: SocketAddr, auth: Option<UserKey>) -> Self {
+        Self {
+            addr,
+            auth,
+            kind: ProxyKind::Http,
+        }
+    }
+
+    /// Connect to the proxy and ask it to open a tunnel to the given address.
+    pub async fn connect(&self, address: &SocketAddr) -> Result<TcpStream, ConnectionError> {
+        self.connect_with_options(address, &SocketOptions::default())
+            .await
+    }
+
+    /// Same as [`Self::connect`], but the connection to the proxy is made
+    /// with the given socket options.
+    pub async fn connect_with_options(
+        &self,
+        address: &SocketAddr,
+        options: &SocketOptions,
+    ) -> Result<TcpStream, ConnectionError> {
+        let mut stream = options.connect(&self.addr).await?;
+
+        match self.kind {
+            ProxyKind::Socks5 => {
+                let mut stream = BufStream::new(stream);
+                socks5_impl::client::connect(&mut stream, address, self.auth.clone())
+                    .await
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+                Ok(stream.into_inner())
+            }
+            ProxyKind::Http => {
+                http_connect(&mut stream, address, self.auth.as_ref()).await?;
+                Ok(stream)
+            }
+        }
+    }
+}
+
+/// Options for the TCP socket that's used for connecting to a server.
+///
+/// ```
+/// # use azalea_protocol::connect::SocketOptions;
+/// # use std::time::Duration;
+/// let options = SocketOptions {
+///     // connect from a specific IP on a host that has more than one
+///     bind_address: Some("192.0.2.10:0".parse().unwrap()),
+///     // the server sends a keepalive every 15 seconds, so if we don't get
+///     // anything for 30 seconds then the connection is probably dead
+///     read_timeout: Some(Duration::from_secs(30)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct SocketOptions {
+    /// The local address to connect from. The port should usually be 0, so
+    /// the OS picks one for us.
+    pub bind_address: Option<SocketAddr>,
+    /// The name of the network interface to connect through, like `eth1`.
+    ///
+    /// This only works on Linux, and it usually needs the `CAP_NET_RAW`
+    /// capability. Connecting fails on other platforms if this is set.
+    pub bind_interface: Option<String>,
+    /// Whether to send packets as soon as they're written instead of waiting
+    /// to combine small packets (`TCP_NODELAY`). This is `true` by default.
+    pub nodelay: bool,
+    /// How long to wait for a packet from the server before the connection is
+    /// closed.
+    pub read_timeout: Option<Duration>,
+    /// How long writing a packet can take before the connection is closed.
+    pub write_timeout: Option<Duration>,
+    /// How long the connection has to be idle before the OS starts sending
+    /// TCP keepalive probes, or `None` to not send them.
+    pub keepalive: Option<Duration>,
+    /// The size of the OS's send buffer for the socket (`SO_SNDBUF`), or
+    /// `None` to use the default. Linux doubles whatever is set here.
+    pub send_buffer_size: Option<u32>,
+    /// The size of the OS's receive buffer for the socket (`SO_RCVBUF`), or
+    /// `None` to use the default. Linux doubles whatever is set here.
+    pub recv_buffer_size: Option<u32>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            bind_address: None,
+            bind_interface: None,
+            nodelay: true,
+            read_timeout: None,
+            write_timeout: None,
+            keepalive: None,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+        }
     }
 }
 
+impl SocketOptions {
+    /// Open a TCP connection to the address with these options.
+    ///
+    /// The read and write timeouts aren't used here, since they're applied
+    /// by [`RawReadConnection`] and [`RawWriteConnection`].
+    pub async fn connect(&self, address: &SocketAddr) -> io::Result<TcpStream> {
+        let socket = if address.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+
+        if let Some(interface) = &self.bind_interface {
+            #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+            socket.bind_device(Some(interface.as_bytes()))?;
+            #[cfg(not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")))]
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Can't bind to the interface {interface} on this platform"),
+            ));
+        }
+        if let Some(bind_address) = self.bind_address {
+            socket.bind(bind_address)?;
+        }
+        // these have to be set before connecting so the TCP window is sized
+        // correctly
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+
+        let stream = socket.connect(*address).await?;
+        stream.set_nodelay(self.nodelay)?;
+        if let Some(keepalive) = self.keepalive {
+            socket2::SockRef::from(&stream)
+                .set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(keepalive))?;
+        }
+        Ok(stream)
+    }
+}
+
+/// The most bytes that we'll read from an HTTP proxy's response headers
+/// before giving up on it.
+const MAX_HTTP_PROXY_RESPONSE_SIZE: usize = 8192;
+
+/// Send an HTTP `CONNECT` request and wait for the proxy to accept it.
+async fn http_connect(
+    stream: &mut TcpStream,
+    address: &SocketAddr,
+    auth: Option<&UserKey>,
+) -> Result<(), ConnectionError> {
+    let mut request = format!("CONNECT {address} HTTP/1.1\r\nHost: {address}\r\n");
+    if let Some(auth) = auth {
+        let credentials = BASE64_STANDARD.encode(format!("{}:{}", auth.username, auth.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // read one byte at a time so we don't consume anything that the server
+    // sends after the headers
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_PROXY_RESPONSE_SIZE {
+            return Err(ConnectionError::HttpProxy(
+                "Response headers are too long".to_string(),
+            ));
+        }
+        response.push(stream.read_u8().await?);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(ConnectionError::HttpProxy(status_line.to_string()));
+    }
+    Ok(())
+}
+
 impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
     /// Create a new connection to the given address.
     pub async fn new(address: &SocketAddr) -> Result<Self, ConnectionError> {
-        let stream = TcpStream::connect(address).await?;
-
-        // enable tcp_nodelay
-        stream.set_nodelay(true)?;
-
-        Self::new_from_stream(stream).await
+        Self::new_with_options(address, None, &SocketOptions::default()).await
     }
 
-    /// Create a new connection to the given address and Socks5 proxy. If you're
+    /// Create a new connection to the given address through a proxy. If you're
     /// not using a proxy, use [`Self::new`] instead.
     pub async fn new_with_proxy(
         address: &SocketAddr,
         proxy: Proxy,
     ) -> Result<Self, ConnectionError> {
-        let proxy_stream = TcpStream::connect(proxy.addr).await?;
-        let mut stream = BufStream::new(proxy_stream);
-
-        let _ = socks5_impl::client::connect(&mut stream, address, proxy.auth)
-            .await
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        Self::new_with_options(address, Some(proxy), &SocketOptions::default()).await
+    }
 
-        Self::new_from_stream(stream.into_inner()).await
+    /// Create a new connection to the given address with custom socket
+    /// options, optionally through a proxy.
+    ///
+    /// If there's a proxy, the options are used for the connection to it.
+    pub async fn new_with_options(
+        address: &SocketAddr,
+        proxy: Option<Proxy>,
+        options: &SocketOptions,
+    ) -> Result<Self, ConnectionError> {
+        let stream = match proxy {
+            Some(proxy) => proxy.connect_with_options(address, options).await?,
+            None => options.connect(address).await?,
+        };
+        let mut conn = Self::new_from_stream(stream).await?;
+        conn.reader.raw.read_timeout = options.read_timeout;
+        conn.writer.raw.write_timeout = options.write_timeout;
+        Ok(conn)
     }
 
     /// Create a new connection from an existing stream. Useful if you want to
@@ -304,6 +562,7 @@ impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
                     buffer: Cursor::new(Vec::new()),
                     compression_threshold: None,
                     dec_cipher: None,
+                    read_timeout: None,
                 },
                 _reading: PhantomData,
             },
@@ -312,6 +571,7 @@ impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
                     write_stream,
                     compression_threshold: None,
                     enc_cipher: None,
+                    write_timeout: None,
                 },
                 _writing: PhantomData,
             },
@@ -569,6 +829,7 @@ where
                     buffer: Cursor::new(Vec::new()),
                     compression_threshold: None,
                     dec_cipher: None,
+                    read_timeout: None,
                 },
                 _reading: PhantomData,
             },
@@ -577,6 +838,7 @@ where
                     write_stream,
                     compression_threshold: None,
                     enc_cipher: None,
+                    write_timeout: None,
                 },
                 _writing: PhantomData,
             },
@@ -591,3 +853,121 @@ where
             .reunite(self.writer.raw.write_stream)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Accept one connection on the listener, read the request headers, and
+    /// reply with the response. The request is returned.
+    async fn respond_to_connect(listener: TcpListener, response: &'static str) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            request.push(stream.read_u8().await.unwrap());
+        }
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8(request).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Proxy::http(
+            listener.local_addr().unwrap(),
+            Some(UserKey::new("user", "pass")),
+        );
+        let server = tokio::spawn(respond_to_connect(
+            listener,
+            "HTTP/1.1 200 Connection established\r\n\r\nhello",
+        ));
+
+        let target = "192.0.2.1:25565".parse().unwrap();
+        let mut stream = proxy.connect(&target).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert_eq!(
+            request,
+            "CONNECT 192.0.2.1:25565 HTTP/1.1\r\n\
+             Host: 192.0.2.1:25565\r\n\
+             Proxy-Authorization: Basic dXNlcjpwYXNz\r\n\
+             \r\n"
+        );
+
+        // anything after the headers is from the server, so it has to be left
+        // in the stream
+        let mut data = [0; 5];
+        stream.read_exact(&mut data).await.unwrap();
+        assert_eq!(&data, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Proxy::http(listener.local_addr().unwrap(), None);
+        let server = tokio::spawn(respond_to_connect(
+            listener,
+            "HTTP/1.1 407 Proxy Authentication Required\r\n\r\n",
+        ));
+
+        let target = "192.0.2.1:25565".parse().unwrap();
+        let err = proxy.connect(&target).await.unwrap_err();
+        let request = server.await.unwrap();
+        assert!(!request.contains("Proxy-Authorization"));
+        assert!(matches!(
+            err,
+            ConnectionError::HttpProxy(status) if status == "HTTP/1.1 407 Proxy Authentication Required"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_socket_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let options = SocketOptions {
+            nodelay: true,
+            keepalive: Some(Duration::from_secs(30)),
+            send_buffer_size: Some(64 * 1024),
+            recv_buffer_size: Some(64 * 1024),
+            ..Default::default()
+        };
+        let stream = options.connect(&address).await.unwrap();
+        let (_server_stream, client_address) = listener.accept().await.unwrap();
+        assert_eq!(stream.local_addr().unwrap(), client_address);
+
+        let socket = socket2::SockRef::from(&stream);
+        assert!(stream.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        // the OS is allowed to round these up (and Linux doubles them)
+        assert!(socket.send_buffer_size().unwrap() >= 64 * 1024);
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_socket_options_disabled() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let options = SocketOptions {
+            nodelay: false,
+            keepalive: None,
+            ..Default::default()
+        };
+        let stream = options.connect(&address).await.unwrap();
+        listener.accept().await.unwrap();
+
+        let socket = socket2::SockRef::from(&stream);
+        assert!(!stream.nodelay().unwrap());
+        assert!(!socket.keepalive().unwrap());
+    }
+
+    #[test]
+    fn test_proxy_defaults_to_socks5() {
+        let addr = "127.0.0.1:1080".parse().unwrap();
+        assert_eq!(Proxy::new(addr, None).kind, ProxyKind::Socks5);
+        assert_eq!(Proxy::http(addr, None).kind, ProxyKind::Http);
+    }
+}
@@ -19,8 +19,12 @@ pub mod common;
 pub mod connect;
 #[cfg(feature = "packets")]
 pub mod packets;
+#[cfg(feature = "connecting")]
+pub mod proxy;
 pub mod read;
 pub mod resolver;
+#[cfg(feature = "connecting")]
+pub mod server;
 pub mod write;
 
 /// A host and port. It's possible that the port doesn't resolve to anything.
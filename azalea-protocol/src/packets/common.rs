@@ -7,7 +7,12 @@ use azalea_core::{
 
 #[derive(Clone, Debug, AzBuf)]
 pub struct CommonPlayerSpawnInfo {
-    pub dimension_type: azalea_registry::DimensionType,
+    /// The ID of the dimension type in the `minecraft:dimension_type` registry
+    /// that the server sent, which can include dimension types from data packs.
+    ///
+    /// Use `RegistryHolder::dimension_type_by_id` to get the dimension type.
+    #[var]
+    pub dimension_type: u32,
     pub dimension: ResourceLocation,
     pub seed: i64,
     pub game_type: GameMode,
@@ -50,9 +50,23 @@ where
     /// like `pong`.
     fn name(&self) -> &'static str;
 
+    /// Returns the name of the packet with the given id, without having to
+    /// read the packet. This is `None` if there's no packet with that id.
+    fn name_from_id(id: u32) -> Option<&'static str>;
+
     /// Read a packet by its id, `ConnectionProtocol`, and flow
+    ///
+    /// In debug builds, this returns [`ReadPacketError::LeftoverData`] if
+    /// there's data left in the buffer after the packet.
     fn read(id: u32, buf: &mut Cursor<&[u8]>) -> Result<Self, Box<ReadPacketError>>;
 
+    /// Like [`Self::read`], but data after the packet is left in the buffer
+    /// instead of being an error in debug builds.
+    fn read_allowing_leftover(
+        id: u32,
+        buf: &mut Cursor<&[u8]>,
+    ) -> Result<Self, Box<ReadPacketError>>;
+
     fn write(&self, buf: &mut impl Write) -> Result<(), std::io::Error>;
 }
 
@@ -39,6 +39,68 @@ pub struct ClientboundStatusResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "enforcesSecureChat")]
     pub enforces_secure_chat: Option<bool>,
+    /// Sent by servers running Forge for Minecraft 1.13 and above.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "forgeData")]
+    pub forge_data: Option<ForgeData>,
+    /// Sent by servers running Forge for versions of Minecraft before 1.13.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "modinfo")]
+    pub mod_info: Option<ModInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForgeData {
+    #[serde(default)]
+    pub channels: Vec<ForgeChannel>,
+    #[serde(default)]
+    pub mods: Vec<ForgeMod>,
+    #[serde(rename = "fmlNetworkVersion")]
+    pub fml_network_version: i32,
+    /// Whether some mods and channels were left out because there were too
+    /// many.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Newer versions of Forge put the mods and channels in here in a
+    /// compressed format instead of in [`Self::mods`] and [`Self::channels`].
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForgeChannel {
+    pub res: String,
+    pub version: String,
+    pub required: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForgeMod {
+    #[serde(rename = "modId")]
+    pub mod_id: String,
+    /// The version of the mod, or a marker like `ANY` or `OHNOES` if the mod
+    /// doesn't care about the version or isn't on the server.
+    #[serde(rename = "modmarker")]
+    pub mod_marker: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModInfo {
+    /// This is usually `FML`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "modList")]
+    pub mod_list: Vec<ModInfoEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModInfoEntry {
+    #[serde(rename = "modid")]
+    pub mod_id: String,
+    pub version: String,
 }
 
 impl AzaleaRead for ClientboundStatusResponse {
@@ -0,0 +1,870 @@
+//! A man-in-the-middle proxy that sits between a vanilla client and a real
+//! server, and lets you see and change the packets that go through it.
+//!
+//! The proxy logs in to the server by itself, so each leg of the connection
+//! has its own encryption and compression. This means the packets can be read
+//! even if the server is in online mode, as long as you give the proxy an
+//! account to join with.
+//!
+//! ```no_run
+//! use azalea_protocol::{
+//!     packets::game::ServerboundGamePacket,
+//!     proxy::{MitmProxy, PacketAction, PacketHandler, ProxySession},
+//! };
+//!
+//! struct LogChat;
+//! impl PacketHandler for LogChat {
+//!     fn serverbound_game(
+//!         &self,
+//!         session: &ProxySession,
+//!         packet: &ServerboundGamePacket,
+//!     ) -> PacketAction<ServerboundGamePacket> {
+//!         if let ServerboundGamePacket::Chat(p) = packet {
+//!             println!("{}: {}", session.username, p.message);
+//!         }
+//!         PacketAction::Forward
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! MitmProxy::bind("127.0.0.1:25566", "localhost".try_into()?)
+//!     .await?
+//!     .handler(LogChat)
+//!     .run()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt::Debug,
+    io::{self, Cursor},
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use azalea_auth::{
+    game_profile::GameProfile,
+    sessionserver::{ClientSessionServerError, ServerSessionServerError},
+};
+use azalea_chat::FormattedText;
+use azalea_crypto::ServerKeyPair;
+use thiserror::Error;
+use tokio::{net::ToSocketAddrs, sync::mpsc};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    connect::{Connection, ConnectionError, RawReadConnection, RawWriteConnection},
+    packets::{
+        config::{ClientboundConfigPacket, ServerboundConfigPacket},
+        game::{ClientboundGamePacket, ServerboundGamePacket},
+        handshake::{
+            s_intention::ServerboundIntention, ClientboundHandshakePacket,
+            ServerboundHandshakePacket,
+        },
+        login::{
+            ClientboundHello, ClientboundLoginCompression, ClientboundLoginDisconnect,
+            ClientboundLoginPacket, ServerboundCookieResponse, ServerboundCustomQueryAnswer,
+            ServerboundHello, ServerboundKey, ServerboundLoginAcknowledged, ServerboundLoginPacket,
+        },
+        status::{ClientboundStatusPacket, ServerboundStatusPacket},
+        ClientIntention, Packet, ProtocolPacket, PROTOCOL_VERSION,
+    },
+    read::{deserialize_packet, ReadPacketError},
+    resolver::{self, ResolverError},
+    server::{Listener, ServerConnection, ServerConnectionError},
+    write::serialize_packet,
+    ServerAddress,
+};
+
+/// A proxy that accepts connections from clients and forwards them to a
+/// server. See the [module-level documentation](self) for more.
+pub struct MitmProxy {
+    listener: Listener,
+    target: ServerAddress,
+    account: Option<ProxyAccount>,
+    online_mode: bool,
+    handler: Arc<dyn PacketHandler>,
+}
+
+/// The account that the proxy uses to join the server.
+#[derive(Debug, Clone)]
+pub struct ProxyAccount {
+    pub username: String,
+    pub uuid: Uuid,
+    /// The Minecraft access token, which is required for joining online-mode
+    /// servers.
+    pub access_token: Option<String>,
+}
+
+/// What should be done with a packet that went through the proxy.
+pub enum PacketAction<P> {
+    /// Send the packet on unchanged.
+    Forward,
+    /// Send this packet instead.
+    Replace(P),
+    /// Don't send anything.
+    Drop,
+}
+
+/// Lets you see and change the packets that go through a [`MitmProxy`].
+///
+/// Only packets in the configuration and game states go through here, since
+/// the proxy handles logging in by itself. Packets that Azalea couldn't decode
+/// are always forwarded unchanged.
+pub trait PacketHandler: Send + Sync + 'static {
+    fn serverbound_config(
+        &self,
+        _session: &ProxySession,
+        _packet: &ServerboundConfigPacket,
+    ) -> PacketAction<ServerboundConfigPacket> {
+        PacketAction::Forward
+    }
+    fn clientbound_config(
+        &self,
+        _session: &ProxySession,
+        _packet: &ClientboundConfigPacket,
+    ) -> PacketAction<ClientboundConfigPacket> {
+        PacketAction::Forward
+    }
+    fn serverbound_game(
+        &self,
+        _session: &ProxySession,
+        _packet: &ServerboundGamePacket,
+    ) -> PacketAction<ServerboundGamePacket> {
+        PacketAction::Forward
+    }
+    fn clientbound_game(
+        &self,
+        _session: &ProxySession,
+        _packet: &ClientboundGamePacket,
+    ) -> PacketAction<ClientboundGamePacket> {
+        PacketAction::Forward
+    }
+}
+
+/// Forward every packet unchanged.
+impl PacketHandler for () {}
+
+/// A client that's connected through the proxy.
+pub struct ProxySession {
+    /// The username that the client logged in to the proxy with.
+    pub username: String,
+    /// The profile that the client logged in to the proxy with. If the proxy
+    /// is in online mode, this is the profile that Mojang authenticated,
+    /// including its skin.
+    pub game_profile: GameProfile,
+    pub client_address: SocketAddr,
+    to_client: mpsc::UnboundedSender<Box<[u8]>>,
+    to_server: mpsc::UnboundedSender<Box<[u8]>>,
+}
+
+impl ProxySession {
+    /// Send a game packet to the client, as if it came from the server.
+    ///
+    /// Only do this while the client is in the game state.
+    pub fn send_to_client(&self, packet: impl Packet<ClientboundGamePacket>) {
+        send_packet(&self.to_client, &packet.into_variant());
+    }
+    /// Send a game packet to the server, as if it came from the client.
+    ///
+    /// Only do this while the client is in the game state.
+    pub fn send_to_server(&self, packet: impl Packet<ServerboundGamePacket>) {
+        send_packet(&self.to_server, &packet.into_variant());
+    }
+
+    /// Send an already serialized packet to the client.
+    pub fn send_raw_to_client(&self, raw_packet: Box<[u8]>) {
+        let _ = self.to_client.send(raw_packet);
+    }
+    /// Send an already serialized packet to the server.
+    pub fn send_raw_to_server(&self, raw_packet: Box<[u8]>) {
+        let _ = self.to_server.send(raw_packet);
+    }
+}
+
+fn send_packet<P: ProtocolPacket + Debug>(sender: &mpsc::UnboundedSender<Box<[u8]>>, packet: &P) {
+    match serialize_packet(packet) {
+        Ok(raw_packet) => {
+            let _ = sender.send(raw_packet);
+        }
+        Err(e) => warn!("Proxy couldn't serialize packet {packet:?}: {e}"),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ProxyError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Read(#[from] Box<ReadPacketError>),
+    #[error(transparent)]
+    Resolver(#[from] ResolverError),
+    #[error(transparent)]
+    Connection(#[from] ConnectionError),
+    #[error(transparent)]
+    ServerConnection(#[from] ServerConnectionError),
+    #[error(transparent)]
+    ClientSessionServer(#[from] ClientSessionServerError),
+    #[error(transparent)]
+    ServerSessionServer(#[from] ServerSessionServerError),
+    #[error("Encryption failed: {0}")]
+    Encryption(String),
+    #[error("The server is in online mode, but the proxy doesn't have an access token")]
+    MissingAccessToken,
+    #[error("The server disconnected the proxy while logging in: {0}")]
+    Disconnected(FormattedText),
+    #[error("The client's protocol version ({0}) isn't supported by the proxy")]
+    UnsupportedVersion(i32),
+}
+
+impl MitmProxy {
+    /// Start listening for clients on the given address. They'll be forwarded
+    /// to `target` once you call [`Self::run`].
+    pub async fn bind(address: impl ToSocketAddrs, target: ServerAddress) -> io::Result<Self> {
+        Ok(Self {
+            listener: Listener::bind(address).await?,
+            target,
+            account: None,
+            online_mode: false,
+            handler: Arc::new(()),
+        })
+    }
+
+    /// The address that clients should connect to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Set the handler that gets to see the packets going through the proxy.
+    #[must_use]
+    pub fn handler(mut self, handler: impl PacketHandler) -> Self {
+        self.handler = Arc::new(handler);
+        self
+    }
+
+    /// Set the account that the proxy joins the server with. By default, it
+    /// uses the client's username and UUID without authenticating, which only
+    /// works for offline-mode servers.
+    #[must_use]
+    pub fn account(mut self, account: ProxyAccount) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Whether clients have to authenticate with Mojang to join the proxy.
+    /// This also makes the connection between the client and the proxy
+    /// encrypted. Defaults to false.
+    #[must_use]
+    pub fn online_mode(mut self, online_mode: bool) -> Self {
+        self.online_mode = online_mode;
+        self
+    }
+
+    /// Accept clients forever, handling each of them in a new task.
+    pub async fn run(self) -> io::Result<()> {
+        let shared = Arc::new(SharedProxyState {
+            target: self.target,
+            account: self.account,
+            key_pair: self.online_mode.then(ServerKeyPair::generate),
+            handler: self.handler,
+        });
+
+        loop {
+            let (conn, client_address) = self.listener.accept().await?;
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_client(conn, client_address, &shared).await {
+                    warn!("Proxied connection from {client_address} ended with an error: {e}");
+                }
+            });
+        }
+    }
+}
+
+struct SharedProxyState {
+    target: ServerAddress,
+    account: Option<ProxyAccount>,
+    /// Only present if the proxy is in online mode.
+    key_pair: Option<ServerKeyPair>,
+    handler: Arc<dyn PacketHandler>,
+}
+
+async fn handle_client(
+    conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket>,
+    client_address: SocketAddr,
+    shared: &SharedProxyState,
+) -> Result<(), ProxyError> {
+    let (intention, conn) = conn.handshake().await?;
+
+    let conn = match conn {
+        // the proxy has to be able to decode the packets to know which state
+        // the connection is in, so it only works with its own version
+        ServerConnection::Login(mut client_conn)
+            if intention.protocol_version != PROTOCOL_VERSION =>
+        {
+            client_conn
+                .write(ClientboundLoginDisconnect {
+                    reason: format!("This proxy only supports protocol version {PROTOCOL_VERSION}")
+                        .into(),
+                })
+                .await?;
+            return Err(ProxyError::UnsupportedVersion(intention.protocol_version));
+        }
+        conn => conn,
+    };
+
+    let target_address = resolver::resolve_address(&shared.target).await?;
+    let mut server_conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> =
+        Connection::new(&target_address).await?;
+
+    match conn {
+        ServerConnection::Status(client_conn) => {
+            server_conn
+                .write(ServerboundIntention {
+                    // so the server replies with whether the client's version
+                    // is supported
+                    protocol_version: intention.protocol_version,
+                    hostname: shared.target.host.clone(),
+                    port: shared.target.port,
+                    intention: ClientIntention::Status,
+                })
+                .await?;
+            forward_status(client_conn, server_conn.status()).await
+        }
+        ServerConnection::Login(client_conn) => {
+            server_conn
+                .write(ServerboundIntention {
+                    protocol_version: intention.protocol_version,
+                    hostname: shared.target.host.clone(),
+                    port: shared.target.port,
+                    intention: ClientIntention::Login,
+                })
+                .await?;
+            login(client_conn, server_conn.login(), client_address, shared).await
+        }
+    }
+}
+
+async fn forward_status(
+    mut client_conn: Connection<ServerboundStatusPacket, ClientboundStatusPacket>,
+    mut server_conn: Connection<ClientboundStatusPacket, ServerboundStatusPacket>,
+) -> Result<(), ProxyError> {
+    loop {
+        let packet = match client_conn.read().await {
+            Ok(packet) => packet,
+            Err(e) if matches!(*e, ReadPacketError::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let is_ping = matches!(packet, ServerboundStatusPacket::PingRequest(_));
+        server_conn.write(packet).await?;
+        client_conn.write(server_conn.read().await?).await?;
+        if is_ping {
+            return Ok(());
+        }
+    }
+}
+
+async fn login(
+    mut client_conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    mut server_conn: Connection<ClientboundLoginPacket, ServerboundLoginPacket>,
+    client_address: SocketAddr,
+    shared: &SharedProxyState,
+) -> Result<(), ProxyError> {
+    let hello = client_conn.read_hello().await?;
+    let mut client_profile = GameProfile::new(hello.profile_id, hello.name.clone());
+
+    if let Some(key_pair) = &shared.key_pair {
+        let challenge = azalea_crypto::generate_challenge();
+        client_conn
+            .write(ClientboundHello {
+                server_id: String::new(),
+                public_key: key_pair.public_key().to_vec(),
+                challenge: challenge.to_vec(),
+                should_authenticate: true,
+            })
+            .await?;
+        let key = loop {
+            if let ServerboundLoginPacket::Key(key) = client_conn.read().await? {
+                break key;
+            }
+        };
+        if key_pair
+            .decrypt(&key.encrypted_challenge)
+            .map_err(ProxyError::Encryption)?
+            != challenge
+        {
+            return Err(ProxyError::Encryption(
+                "The client sent the wrong challenge".to_string(),
+            ));
+        }
+        let secret_key: [u8; 16] = key_pair
+            .decrypt(&key.key_bytes)
+            .map_err(ProxyError::Encryption)?
+            .try_into()
+            .map_err(|_| {
+                ProxyError::Encryption("The shared secret is the wrong size".to_string())
+            })?;
+        client_conn.set_encryption_key(secret_key);
+        // the uuid in the hello packet is only what the client claims, so
+        // use the one that Mojang gave us instead
+        client_profile = client_conn
+            .authenticate(&hello.name, key_pair.public_key(), &secret_key, None)
+            .await?;
+    }
+
+    let account = shared.account.clone().unwrap_or_else(|| ProxyAccount {
+        username: client_profile.name.clone(),
+        uuid: client_profile.uuid,
+        access_token: None,
+    });
+    server_conn
+        .write(ServerboundHello {
+            name: account.username.clone(),
+            profile_id: account.uuid,
+        })
+        .await?;
+
+    let game_profile = loop {
+        match server_conn.read().await? {
+            ClientboundLoginPacket::Hello(p) => {
+                let e = azalea_crypto::encrypt(&p.public_key, &p.challenge)
+                    .map_err(ProxyError::Encryption)?;
+                if p.should_authenticate {
+                    let access_token = account
+                        .access_token
+                        .as_ref()
+                        .ok_or(ProxyError::MissingAccessToken)?;
+                    server_conn
+                        .authenticate(access_token, &account.uuid, e.secret_key, &p)
+                        .await?;
+                }
+                server_conn
+                    .write(ServerboundKey {
+                        key_bytes: e.encrypted_public_key,
+                        encrypted_challenge: e.encrypted_challenge,
+                    })
+                    .await?;
+                server_conn.set_encryption_key(e.secret_key);
+            }
+            ClientboundLoginPacket::LoginCompression(p) => {
+                server_conn.set_compression_threshold(p.compression_threshold);
+                // use the same threshold with the client, since it's probably
+                // what the server thinks is best
+                client_conn
+                    .write(ClientboundLoginCompression {
+                        compression_threshold: p.compression_threshold,
+                    })
+                    .await?;
+                client_conn.set_compression_threshold(p.compression_threshold);
+            }
+            ClientboundLoginPacket::CustomQuery(p) => {
+                // this is what vanilla clients reply with
+                server_conn
+                    .write(ServerboundCustomQueryAnswer {
+                        transaction_id: p.transaction_id,
+                        data: None,
+                    })
+                    .await?;
+            }
+            ClientboundLoginPacket::CookieRequest(p) => {
+                server_conn
+                    .write(ServerboundCookieResponse {
+                        key: p.key,
+                        payload: None,
+                    })
+                    .await?;
+            }
+            ClientboundLoginPacket::LoginDisconnect(p) => {
+                client_conn
+                    .write(ClientboundLoginDisconnect {
+                        reason: p.reason.clone(),
+                    })
+                    .await?;
+                return Err(ProxyError::Disconnected(p.reason));
+            }
+            ClientboundLoginPacket::LoginFinished(p) => break p.game_profile,
+        }
+    };
+
+    let client_conn = client_conn.finish_login(game_profile).await?;
+    server_conn.write(ServerboundLoginAcknowledged).await?;
+    let server_conn = server_conn.config();
+    info!("{} joined through the proxy", client_profile.name);
+
+    let (to_client, to_client_receiver) = mpsc::unbounded_channel();
+    let (to_server, to_server_receiver) = mpsc::unbounded_channel();
+    let session = ProxySession {
+        username: client_profile.name.clone(),
+        game_profile: client_profile,
+        client_address,
+        to_client,
+        to_server,
+    };
+
+    let (client_read, client_write) = client_conn.into_split();
+    let (server_read, server_write) = server_conn.into_split();
+    let result = tokio::select! {
+        r = forward_serverbound(client_read.raw, &session, &*shared.handler) => r,
+        r = forward_clientbound(server_read.raw, &session, &*shared.handler) => r,
+        _ = write_packets(client_write.raw, to_client_receiver) => Ok(()),
+        _ = write_packets(server_write.raw, to_server_receiver) => Ok(()),
+    };
+    info!("{} left the proxy", session.username);
+    result
+}
+
+async fn forward_serverbound(
+    mut read_conn: RawReadConnection,
+    session: &ProxySession,
+    handler: &dyn PacketHandler,
+) -> Result<(), ProxyError> {
+    // each direction switches states at a different time, so they're tracked
+    // separately. both start in the configuration state.
+    let mut in_game = false;
+    loop {
+        let raw_packet = read_or_closed(&mut read_conn).await?;
+        let Some(raw_packet) = raw_packet else {
+            return Ok(());
+        };
+
+        let raw_packet = if in_game {
+            let (raw_packet, packet) = intercept(raw_packet, |p: &ServerboundGamePacket| {
+                handler.serverbound_game(session, p)
+            });
+            if let Some(ServerboundGamePacket::ConfigurationAcknowledged(_)) = packet {
+                in_game = false;
+            }
+            raw_packet
+        } else {
+            let (raw_packet, packet) = intercept(raw_packet, |p: &ServerboundConfigPacket| {
+                handler.serverbound_config(session, p)
+            });
+            if let Some(ServerboundConfigPacket::FinishConfiguration(_)) = packet {
+                in_game = true;
+            }
+            raw_packet
+        };
+
+        if let Some(raw_packet) = raw_packet {
+            let _ = session.to_server.send(raw_packet);
+        }
+    }
+}
+
+async fn forward_clientbound(
+    mut read_conn: RawReadConnection,
+    session: &ProxySession,
+    handler: &dyn PacketHandler,
+) -> Result<(), ProxyError> {
+    // clients start reading packets in the new state as soon as they get
+    // these packets, before the server knows that they've acknowledged them
+    let mut in_game = false;
+    loop {
+        let raw_packet = read_or_closed(&mut read_conn).await?;
+        let Some(raw_packet) = raw_packet else {
+            return Ok(());
+        };
+
+        let raw_packet = if in_game {
+            let (raw_packet, packet) = intercept(raw_packet, |p: &ClientboundGamePacket| {
+                handler.clientbound_game(session, p)
+            });
+            if let Some(ClientboundGamePacket::StartConfiguration(_)) = packet {
+                in_game = false;
+            }
+            raw_packet
+        } else {
+            let (raw_packet, packet) = intercept(raw_packet, |p: &ClientboundConfigPacket| {
+                handler.clientbound_config(session, p)
+            });
+            if let Some(ClientboundConfigPacket::FinishConfiguration(_)) = packet {
+                in_game = true;
+            }
+            raw_packet
+        };
+
+        if let Some(raw_packet) = raw_packet {
+            let _ = session.to_client.send(raw_packet);
+        }
+    }
+}
+
+/// Read a raw packet, or return `None` if the connection was closed normally.
+async fn read_or_closed(
+    read_conn: &mut RawReadConnection,
+) -> Result<Option<Box<[u8]>>, ProxyError> {
+    match read_conn.read().await {
+        Ok(raw_packet) => Ok(Some(raw_packet)),
+        Err(e) if matches!(*e, ReadPacketError::ConnectionClosed) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Decode a packet and let the handler decide what to do with it.
+///
+/// Returns the bytes that should be forwarded, and the original packet if it
+/// could be decoded.
+fn intercept<P: ProtocolPacket + Debug>(
+    raw_packet: Box<[u8]>,
+    handle: impl FnOnce(&P) -> PacketAction<P>,
+) -> (Option<Box<[u8]>>, Option<P>) {
+    let packet = match deserialize_packet::<P>(&mut Cursor::new(&raw_packet)) {
+        Ok(packet) => packet,
+        Err(e) => {
+            debug!("Proxy is forwarding a packet that it couldn't decode: {e}");
+            return (Some(raw_packet), None);
+        }
+    };
+
+    let raw_packet = match handle(&packet) {
+        PacketAction::Forward => Some(raw_packet),
+        PacketAction::Replace(new_packet) => match serialize_packet(&new_packet) {
+            Ok(new_raw_packet) => Some(new_raw_packet),
+            Err(e) => {
+                warn!("Proxy couldn't serialize replacement packet {new_packet:?}, forwarding the original instead: {e}");
+                Some(raw_packet)
+            }
+        },
+        PacketAction::Drop => None,
+    };
+    (raw_packet, Some(packet))
+}
+
+async fn write_packets(
+    mut write_conn: RawWriteConnection,
+    mut receiver: mpsc::UnboundedReceiver<Box<[u8]>>,
+) {
+    while let Some(raw_packet) = receiver.recv().await {
+        if let Err(e) = write_conn.write(&raw_packet).await {
+            debug!("Proxy couldn't write a packet: {e}");
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::packets::{
+        config::{
+            c_finish_configuration::ClientboundFinishConfiguration,
+            s_finish_configuration::ServerboundFinishConfiguration,
+        },
+        game::{
+            c_start_configuration::ClientboundStartConfiguration,
+            s_configuration_acknowledged::ServerboundConfigurationAcknowledged,
+        },
+        login::ServerboundLoginAcknowledged,
+    };
+
+    /// Records the packets that go through the proxy, and changes the id of
+    /// game keep alive packets.
+    struct RecordPackets(Arc<Mutex<Vec<String>>>);
+    impl PacketHandler for RecordPackets {
+        fn serverbound_config(
+            &self,
+            _session: &ProxySession,
+            packet: &ServerboundConfigPacket,
+        ) -> PacketAction<ServerboundConfigPacket> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("serverbound config {}", packet.name()));
+            PacketAction::Forward
+        }
+        fn clientbound_config(
+            &self,
+            _session: &ProxySession,
+            packet: &ClientboundConfigPacket,
+        ) -> PacketAction<ClientboundConfigPacket> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("clientbound config {}", packet.name()));
+            PacketAction::Forward
+        }
+        fn serverbound_game(
+            &self,
+            session: &ProxySession,
+            packet: &ServerboundGamePacket,
+        ) -> PacketAction<ServerboundGamePacket> {
+            assert_eq!(session.game_profile.name, "bot");
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("serverbound game {}", packet.name()));
+            PacketAction::Forward
+        }
+        fn clientbound_game(
+            &self,
+            _session: &ProxySession,
+            packet: &ClientboundGamePacket,
+        ) -> PacketAction<ClientboundGamePacket> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("clientbound game {}", packet.name()));
+            match packet {
+                ClientboundGamePacket::KeepAlive(p) => PacketAction::Replace(
+                    crate::packets::game::c_keep_alive::ClientboundKeepAlive { id: p.id + 1 }
+                        .into_variant(),
+                ),
+                _ => PacketAction::Forward,
+            }
+        }
+    }
+
+    async fn start_proxy(
+        handler: impl PacketHandler,
+    ) -> (
+        Listener,
+        SocketAddr,
+        tokio::task::JoinHandle<io::Result<()>>,
+    ) {
+        let server = Listener::bind("127.0.0.1:0").await.unwrap();
+        let target = ServerAddress {
+            host: "127.0.0.1".to_string(),
+            port: server.local_addr().unwrap().port(),
+        };
+        let proxy = MitmProxy::bind("127.0.0.1:0", target)
+            .await
+            .unwrap()
+            .handler(handler);
+        let proxy_address = proxy.local_addr().unwrap();
+        (server, proxy_address, tokio::spawn(proxy.run()))
+    }
+
+    async fn connect_client(
+        proxy_address: SocketAddr,
+        protocol_version: i32,
+    ) -> Connection<ClientboundLoginPacket, ServerboundLoginPacket> {
+        let mut conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> =
+            Connection::new(&proxy_address).await.unwrap();
+        conn.write(ServerboundIntention {
+            protocol_version,
+            hostname: "127.0.0.1".to_string(),
+            port: proxy_address.port(),
+            intention: ClientIntention::Login,
+        })
+        .await
+        .unwrap();
+        let mut conn = conn.login();
+        conn.write(ServerboundHello {
+            name: "bot".to_string(),
+            profile_id: Uuid::nil(),
+        })
+        .await
+        .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_proxy_follows_states_in_each_direction() {
+        let packets = Arc::new(Mutex::new(Vec::new()));
+        let (server, proxy_address, proxy_task) = start_proxy(RecordPackets(packets.clone())).await;
+
+        let server_task = tokio::spawn(async move {
+            let (conn, _) = server.accept().await.unwrap();
+            let (intention, ServerConnection::Login(mut conn)) = conn.handshake().await.unwrap()
+            else {
+                panic!("the proxy should be logging in");
+            };
+            assert_eq!(intention.protocol_version, PROTOCOL_VERSION);
+            let hello = conn.read_hello().await.unwrap();
+            assert_eq!(hello.name, "bot");
+            let mut conn = conn
+                .finish_login(GameProfile::new(hello.profile_id, hello.name))
+                .await
+                .unwrap();
+
+            conn.write(ClientboundFinishConfiguration).await.unwrap();
+            assert!(matches!(
+                conn.read().await.unwrap(),
+                ServerboundConfigPacket::FinishConfiguration(_)
+            ));
+
+            let mut conn = conn.game();
+            conn.write(crate::packets::game::c_keep_alive::ClientboundKeepAlive { id: 1 })
+                .await
+                .unwrap();
+            conn.write(ClientboundStartConfiguration).await.unwrap();
+            // the client hasn't acknowledged the switch yet, but it's already
+            // reading configuration packets
+            let mut conn: Connection<ServerboundGamePacket, ClientboundConfigPacket> =
+                Connection::from(conn);
+            conn.write(crate::packets::config::c_keep_alive::ClientboundKeepAlive { id: 3 })
+                .await
+                .unwrap();
+            assert!(matches!(
+                conn.read().await.unwrap(),
+                ServerboundGamePacket::ConfigurationAcknowledged(_)
+            ));
+        });
+
+        let mut conn = connect_client(proxy_address, PROTOCOL_VERSION).await;
+        let ClientboundLoginPacket::LoginFinished(p) = conn.read().await.unwrap() else {
+            panic!("expected LoginFinished");
+        };
+        assert_eq!(p.game_profile.name, "bot");
+        conn.write(ServerboundLoginAcknowledged).await.unwrap();
+
+        let mut conn = conn.config();
+        assert!(matches!(
+            conn.read().await.unwrap(),
+            ClientboundConfigPacket::FinishConfiguration(_)
+        ));
+        conn.write(ServerboundFinishConfiguration).await.unwrap();
+
+        let mut conn = conn.game();
+        let ClientboundGamePacket::KeepAlive(p) = conn.read().await.unwrap() else {
+            panic!("expected KeepAlive");
+        };
+        // the handler changed it
+        assert_eq!(p.id, 2);
+        assert!(matches!(
+            conn.read().await.unwrap(),
+            ClientboundGamePacket::StartConfiguration(_)
+        ));
+
+        let mut conn: Connection<ClientboundConfigPacket, ServerboundGamePacket> =
+            Connection::from(conn);
+        let ClientboundConfigPacket::KeepAlive(p) = conn.read().await.unwrap() else {
+            panic!("expected KeepAlive");
+        };
+        assert_eq!(p.id, 3);
+        conn.write(ServerboundConfigurationAcknowledged)
+            .await
+            .unwrap();
+
+        server_task.await.unwrap();
+        proxy_task.abort();
+
+        assert_eq!(
+            *packets.lock().unwrap(),
+            [
+                "clientbound config finish_configuration",
+                "serverbound config finish_configuration",
+                "clientbound game keep_alive",
+                "clientbound game start_configuration",
+                "clientbound config keep_alive",
+                "serverbound game configuration_acknowledged",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proxy_rejects_other_versions() {
+        let (_server, proxy_address, proxy_task) = start_proxy(()).await;
+
+        let mut conn = connect_client(proxy_address, PROTOCOL_VERSION - 1).await;
+        assert!(matches!(
+            conn.read().await.unwrap(),
+            ClientboundLoginPacket::LoginDisconnect(_)
+        ));
+        proxy_task.abort();
+    }
+}
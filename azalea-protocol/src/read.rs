@@ -148,6 +148,79 @@ pub fn deserialize_packet<P: ProtocolPacket + Debug>(
     P::read(packet_id, stream)
 }
 
+/// Something that was wrong with a packet that was read by
+/// [`deserialize_packet_lenient`].
+#[derive(Error, Debug, Clone)]
+pub enum PacketWarning {
+    #[error("Unknown packet id {id} in state {state_name}")]
+    UnknownPacketId {
+        state_name: String,
+        id: u32,
+        /// The whole packet, including the id.
+        raw_packet: Box<[u8]>,
+    },
+    #[error("Leftover data after reading packet {packet_name}: {data:?}")]
+    LeftoverData {
+        packet_name: String,
+        data: Vec<u8>,
+        /// The whole packet, including the id.
+        raw_packet: Box<[u8]>,
+    },
+}
+
+/// A packet that was read by [`deserialize_packet_lenient`].
+#[derive(Debug)]
+pub struct LenientPacket<P> {
+    /// The packet, or `None` if it had an unknown id.
+    pub packet: Option<P>,
+    pub warning: Option<PacketWarning>,
+}
+
+/// Like [`deserialize_packet`], but packets with unknown ids or with extra
+/// bytes at the end result in a [`PacketWarning`] instead of an error.
+///
+/// This is meant for connecting to modded servers or servers that are on a
+/// slightly different version, where these would usually be harmless. Packets
+/// that we know about but couldn't parse are still errors.
+pub fn deserialize_packet_lenient<P: ProtocolPacket + Debug>(
+    stream: &mut Cursor<&[u8]>,
+) -> Result<LenientPacket<P>, Box<ReadPacketError>> {
+    let data: &[u8] = *stream.get_ref();
+    let raw_packet = || Box::<[u8]>::from(data);
+
+    let packet_id =
+        u32::azalea_read_var(stream).map_err(|e| ReadPacketError::ReadPacketId { source: e })?;
+    let packet = match P::read_allowing_leftover(packet_id, stream) {
+        Ok(packet) => packet,
+        Err(err) => {
+            let ReadPacketError::UnknownPacketId { state_name, id } = *err else {
+                return Err(err);
+            };
+            return Ok(LenientPacket {
+                packet: None,
+                warning: Some(PacketWarning::UnknownPacketId {
+                    state_name,
+                    id,
+                    raw_packet: raw_packet(),
+                }),
+            });
+        }
+    };
+
+    let mut leftover = Vec::new();
+    let _ = stream.read_to_end(&mut leftover);
+    let warning = (!leftover.is_empty()).then(|| PacketWarning::LeftoverData {
+        packet_name: packet.name().to_string(),
+        data: leftover,
+        raw_packet: raw_packet(),
+    });
+
+    Ok(LenientPacket {
+        packet: Some(packet),
+        warning,
+    })
+}
+
 // this is always true in multiplayer, false in singleplayer
 static VALIDATE_DECOMPRESSED: bool = true;
 
@@ -388,3 +461,99 @@ where
 
     Ok(Some(buf))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packets::{
+            game::{ClientboundGamePacket, ClientboundKeepAlive},
+            Packet,
+        },
+        write::serialize_packet,
+    };
+
+    fn keep_alive() -> Vec<u8> {
+        serialize_packet(&ClientboundKeepAlive { id: 1 }.into_variant())
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_lenient_known_packet() {
+        let raw_packet = keep_alive();
+        let packet =
+            deserialize_packet_lenient::<ClientboundGamePacket>(&mut Cursor::new(&raw_packet))
+                .unwrap();
+        assert!(matches!(
+            packet.packet,
+            Some(ClientboundGamePacket::KeepAlive(ClientboundKeepAlive {
+                id: 1
+            }))
+        ));
+        assert!(packet.warning.is_none());
+    }
+
+    #[test]
+    fn test_lenient_unknown_packet_id() {
+        let raw_packet = [0xff, 0x01, 1, 2, 3];
+        let packet =
+            deserialize_packet_lenient::<ClientboundGamePacket>(&mut Cursor::new(&raw_packet))
+                .unwrap();
+        assert!(packet.packet.is_none());
+        let Some(PacketWarning::UnknownPacketId {
+            state_name,
+            id,
+            raw_packet: warning_raw_packet,
+        }) = packet.warning
+        else {
+            panic!("expected an unknown packet id warning");
+        };
+        assert_eq!(state_name, "GamePacket");
+        assert_eq!(id, 0xff);
+        assert_eq!(&*warning_raw_packet, &raw_packet);
+    }
+
+    #[test]
+    fn test_lenient_leftover_data() {
+        let mut raw_packet = keep_alive();
+        raw_packet.extend([4, 5]);
+        let packet =
+            deserialize_packet_lenient::<ClientboundGamePacket>(&mut Cursor::new(&raw_packet))
+                .unwrap();
+        assert!(matches!(
+            packet.packet,
+            Some(ClientboundGamePacket::KeepAlive(ClientboundKeepAlive {
+                id: 1
+            }))
+        ));
+        let Some(PacketWarning::LeftoverData {
+            packet_name,
+            data,
+            raw_packet: warning_raw_packet,
+        }) = packet.warning
+        else {
+            panic!("expected a leftover data warning");
+        };
+        assert_eq!(packet_name, "keep_alive");
+        assert_eq!(data, [4, 5]);
+        assert_eq!(&*warning_raw_packet, &*raw_packet);
+
+        // the strict reader only checks this in debug builds
+        if cfg!(debug_assertions) {
+            let err = deserialize_packet::<ClientboundGamePacket>(&mut Cursor::new(&raw_packet))
+                .unwrap_err();
+            assert!(matches!(*err, ReadPacketError::LeftoverData { .. }));
+        }
+    }
+
+    #[test]
+    fn test_lenient_broken_packet() {
+        // a keep alive that's too short is still an error
+        let raw_packet = keep_alive();
+        let raw_packet = &raw_packet[..raw_packet.len() - 2];
+        let err = deserialize_packet_lenient::<ClientboundGamePacket>(&mut Cursor::new(raw_packet))
+            .unwrap_err();
+        assert!(matches!(*err, ReadPacketError::Parse { .. }));
+    }
+}
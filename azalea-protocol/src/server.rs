@@ -0,0 +1,349 @@
+//! Accept connections from Minecraft clients, for making servers and proxies.
+//!
+//! This only handles the protocol, so things like authentication and actually
+//! simulating a world are up to you.
+//!
+//! ```no_run
+//! use azalea_protocol::{
+//!     packets::status::{
+//!         c_status_response::{Players, Version},
+//!         ClientboundStatusResponse,
+//!     },
+//!     server::{Listener, ServerConnection},
+//! };
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let listener = Listener::bind("127.0.0.1:25565").await?;
+//! loop {
+//!     let (conn, _address) = listener.accept().await?;
+//!     tokio::spawn(async move {
+//!         let (_intention, conn) = conn.handshake().await?;
+//!         match conn {
+//!             ServerConnection::Status(conn) => {
+//!                 conn.respond_to_status(ClientboundStatusResponse {
+//!                     description: "An Azalea server".into(),
+//!                     favicon: None,
+//!                     players: Players {
+//!                         max: 20,
+//!                         online: 0,
+//!                         sample: Vec::new(),
+//!                     },
+//!                     version: Version {
+//!                         name: azalea_protocol::packets::VERSION_NAME.to_string(),
+//!                         protocol: azalea_protocol::packets::PROTOCOL_VERSION,
+//!                     },
+//!                     enforces_secure_chat: Some(false),
+//!                     forge_data: None,
+//!                     mod_info: None,
+//!                 })
+//!                 .await?;
+//!             }
+//!             ServerConnection::Login(conn) => {
+//!                 // ...
+//!             }
+//!         }
+//!         Ok::<_, azalea_protocol::server::ServerConnectionError>(())
+//!     });
+//! }
+//! # }
+//! ```
+
+use std::{io, net::SocketAddr};
+
+use azalea_auth::game_profile::GameProfile;
+use thiserror::Error;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+use crate::{
+    connect::Connection,
+    packets::{
+        config::{ClientboundConfigPacket, ServerboundConfigPacket},
+        handshake::{
+            s_intention::ServerboundIntention, ClientboundHandshakePacket,
+            ServerboundHandshakePacket,
+        },
+        login::{
+            c_login_finished::ClientboundLoginFinished, s_hello::ServerboundHello,
+            ClientboundLoginPacket, ServerboundLoginPacket,
+        },
+        status::{
+            c_pong_response::ClientboundPongResponse, c_status_response::ClientboundStatusResponse,
+            ClientboundStatusPacket, ServerboundStatusPacket,
+        },
+        ClientIntention,
+    },
+    read::ReadPacketError,
+};
+
+/// A TCP listener that accepts connections from Minecraft clients.
+pub struct Listener {
+    listener: TcpListener,
+}
+
+impl Listener {
+    /// Start listening on the given address.
+    pub async fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+        })
+    }
+
+    /// The address that we're listening on. This is useful if you bound to
+    /// port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for a client to connect.
+    ///
+    /// The returned connection is in the handshake state, so you'll probably
+    /// want to spawn a task that calls [`Connection::handshake`] on it. That
+    /// isn't done here so a slow client can't stop other clients from
+    /// connecting.
+    pub async fn accept(
+        &self,
+    ) -> io::Result<(
+        Connection<ServerboundHandshakePacket, ClientboundHandshakePacket>,
+        SocketAddr,
+    )> {
+        let (stream, address) = self.listener.accept().await?;
+        stream.set_nodelay(true)?;
+        Ok((Connection::wrap(stream), address))
+    }
+}
+
+/// A connection from a client after the handshake, in the state that the
+/// client asked for.
+pub enum ServerConnection {
+    Status(Connection<ServerboundStatusPacket, ClientboundStatusPacket>),
+    /// The client wants to join. This is also used for clients that were
+    /// transferred from another server.
+    Login(Connection<ServerboundLoginPacket, ClientboundLoginPacket>),
+}
+
+#[derive(Error, Debug)]
+pub enum ServerConnectionError {
+    #[error(transparent)]
+    Read(#[from] Box<ReadPacketError>),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("The client disconnected before finishing logging in")]
+    Disconnected,
+}
+
+impl Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> {
+    /// Read the intention packet that the client sends first, and switch to
+    /// the state that it asked for.
+    pub async fn handshake(
+        mut self,
+    ) -> Result<(ServerboundIntention, ServerConnection), ServerConnectionError> {
+        let ServerboundHandshakePacket::Intention(intention) = self.read().await?;
+        let conn = match intention.intention {
+            ClientIntention::Status => ServerConnection::Status(self.status()),
+            ClientIntention::Login | ClientIntention::Transfer => {
+                ServerConnection::Login(self.login())
+            }
+        };
+        Ok((intention, conn))
+    }
+}
+
+impl Connection<ServerboundStatusPacket, ClientboundStatusPacket> {
+    /// Reply to the client's status request with the given response, and to
+    /// its ping with a pong. The connection is closed afterwards.
+    pub async fn respond_to_status(
+        mut self,
+        response: ClientboundStatusResponse,
+    ) -> Result<(), ServerConnectionError> {
+        loop {
+            match self.read().await {
+                Ok(ServerboundStatusPacket::StatusRequest(_)) => {
+                    self.write(response.clone()).await?;
+                }
+                Ok(ServerboundStatusPacket::PingRequest(p)) => {
+                    self.write(ClientboundPongResponse { time: p.time }).await?;
+                    return Ok(());
+                }
+                // some clients close the connection without pinging
+                Err(e) if matches!(*e, ReadPacketError::ConnectionClosed) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Connection<ServerboundLoginPacket, ClientboundLoginPacket> {
+    /// Wait for the client to tell us its name and UUID, which is the first
+    /// thing it sends when logging in.
+    pub async fn read_hello(&mut self) -> Result<ServerboundHello, ServerConnectionError> {
+        loop {
+            if let ServerboundLoginPacket::Hello(hello) = self.read().await? {
+                return Ok(hello);
+            }
+        }
+    }
+
+    /// Tell the client that it's done logging in with the given profile, and
+    /// switch to the configuration state once it acknowledges it.
+    ///
+    /// Encryption and compression should be set up before this, if you want
+    /// them.
+    pub async fn finish_login(
+        mut self,
+        game_profile: GameProfile,
+    ) -> Result<Connection<ServerboundConfigPacket, ClientboundConfigPacket>, ServerConnectionError>
+    {
+        self.write(ClientboundLoginFinished { game_profile })
+            .await?;
+        loop {
+            match self.read().await {
+                Ok(ServerboundLoginPacket::LoginAcknowledged(_)) => return Ok(self.config()),
+                Ok(_) => {}
+                Err(e) if matches!(*e, ReadPacketError::ConnectionClosed) => {
+                    return Err(ServerConnectionError::Disconnected)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::packets::{
+        login::s_login_acknowledged::ServerboundLoginAcknowledged,
+        status::{
+            c_status_response::{Players, Version},
+            s_ping_request::ServerboundPingRequest,
+            s_status_request::ServerboundStatusRequest,
+        },
+        PROTOCOL_VERSION,
+    };
+
+    /// Connect to the listener and send an intention packet, returning the
+    /// client's side of the connection.
+    async fn connect(
+        listener: &Listener,
+        intention: ClientIntention,
+    ) -> Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
+        let address = listener.local_addr().unwrap();
+        let mut conn = Connection::new(&address).await.unwrap();
+        conn.write(ServerboundIntention {
+            protocol_version: PROTOCOL_VERSION,
+            hostname: "localhost".to_string(),
+            port: address.port(),
+            intention,
+        })
+        .await
+        .unwrap();
+        conn
+    }
+
+    fn status_response() -> ClientboundStatusResponse {
+        ClientboundStatusResponse {
+            description: "An Azalea server".into(),
+            favicon: None,
+            players: Players {
+                max: 20,
+                online: 0,
+                sample: Vec::new(),
+            },
+            version: Version {
+                name: "test".to_string(),
+                protocol: PROTOCOL_VERSION,
+            },
+            enforces_secure_chat: Some(false),
+            forge_data: None,
+            mod_info: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let listener = Listener::bind("127.0.0.1:0").await.unwrap();
+        let client = tokio::spawn({
+            let mut conn = connect(&listener, ClientIntention::Status).await.status();
+            async move {
+                conn.write(ServerboundStatusRequest).await.unwrap();
+                let ClientboundStatusPacket::StatusResponse(response) = conn.read().await.unwrap()
+                else {
+                    panic!("expected a status response");
+                };
+                conn.write(ServerboundPingRequest { time: 1234 })
+                    .await
+                    .unwrap();
+                let ClientboundStatusPacket::PongResponse(pong) = conn.read().await.unwrap() else {
+                    panic!("expected a pong");
+                };
+                (response, pong.time)
+            }
+        });
+
+        let (conn, _) = listener.accept().await.unwrap();
+        let (intention, conn) = conn.handshake().await.unwrap();
+        assert_eq!(intention.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(intention.hostname, "localhost");
+        assert_eq!(intention.intention, ClientIntention::Status);
+        let ServerConnection::Status(conn) = conn else {
+            panic!("expected the status state");
+        };
+        conn.respond_to_status(status_response()).await.unwrap();
+
+        let (response, time) = client.await.unwrap();
+        assert_eq!(response.version.name, "test");
+        assert_eq!(response.players.max, 20);
+        assert_eq!(time, 1234);
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        let listener = Listener::bind("127.0.0.1:0").await.unwrap();
+        let uuid = Uuid::from_u128(0x0123456789abcdef0123456789abcdef);
+        let client = tokio::spawn({
+            let mut conn = connect(&listener, ClientIntention::Login).await.login();
+            async move {
+                conn.write(ServerboundHello {
+                    name: "bot".to_string(),
+                    profile_id: uuid,
+                })
+                .await
+                .unwrap();
+                let ClientboundLoginPacket::LoginFinished(p) = conn.read().await.unwrap() else {
+                    panic!("expected login finished");
+                };
+                conn.write(ServerboundLoginAcknowledged).await.unwrap();
+                p.game_profile
+            }
+        });
+
+        let (conn, _) = listener.accept().await.unwrap();
+        let (intention, conn) = conn.handshake().await.unwrap();
+        assert_eq!(intention.intention, ClientIntention::Login);
+        let ServerConnection::Login(mut conn) = conn else {
+            panic!("expected the login state");
+        };
+        let hello = conn.read_hello().await.unwrap();
+        assert_eq!(hello.name, "bot");
+        assert_eq!(hello.profile_id, uuid);
+        conn.finish_login(GameProfile::new(hello.profile_id, hello.name))
+            .await
+            .unwrap();
+
+        let game_profile = client.await.unwrap();
+        assert_eq!(game_profile, GameProfile::new(uuid, "bot".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_is_login() {
+        let listener = Listener::bind("127.0.0.1:0").await.unwrap();
+        let _client = connect(&listener, ClientIntention::Transfer).await;
+
+        let (conn, _) = listener.accept().await.unwrap();
+        let (intention, conn) = conn.handshake().await.unwrap();
+        assert_eq!(intention.intention, ClientIntention::Transfer);
+        assert!(matches!(conn, ServerConnection::Login(_)));
+    }
+}
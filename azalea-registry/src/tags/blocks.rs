@@ -3992,3 +3992,973 @@ pub static WOOL_CARPETS: LazyLock<HashSet<Block>> = LazyLock::new(|| {
         Block::BlackCarpet,
     ])
 });
+
+/// A vanilla block tag, which can be checked with [`Block::is_in`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockTag {
+    AcaciaLogs,
+    Air,
+    AllHangingSigns,
+    AllSigns,
+    AncientCityReplaceable,
+    AnimalsSpawnableOn,
+    Anvil,
+    ArmadilloSpawnableOn,
+    AxolotlsSpawnableOn,
+    AzaleaGrowsOn,
+    AzaleaRootReplaceable,
+    BadlandsTerracotta,
+    BambooBlocks,
+    BambooPlantableOn,
+    Banners,
+    BaseStoneNether,
+    BaseStoneOverworld,
+    BatsSpawnableOn,
+    BeaconBaseBlocks,
+    Beds,
+    BeeAttractive,
+    BeeGrowables,
+    Beehives,
+    BigDripleafPlaceable,
+    BirchLogs,
+    BlocksWindChargeExplosions,
+    Buttons,
+    CamelSandStepSoundBlocks,
+    Campfires,
+    CandleCakes,
+    Candles,
+    Cauldrons,
+    CaveVines,
+    CeilingHangingSigns,
+    CherryLogs,
+    Climbable,
+    CoalOres,
+    CombinationStepSoundBlocks,
+    CompletesFindTreeTutorial,
+    ConcretePowder,
+    ConvertableToMud,
+    CopperOres,
+    CoralBlocks,
+    CoralPlants,
+    Corals,
+    CrimsonStems,
+    Crops,
+    CrystalSoundBlocks,
+    DampensVibrations,
+    DarkOakLogs,
+    DeadBushMayPlaceOn,
+    DeepslateOreReplaceables,
+    DiamondOres,
+    Dirt,
+    DoesNotBlockHoppers,
+    Doors,
+    DragonImmune,
+    DragonTransparent,
+    DripstoneReplaceableBlocks,
+    EmeraldOres,
+    EnchantmentPowerProvider,
+    EnchantmentPowerTransmitter,
+    EndermanHoldable,
+    FallDamageResetting,
+    FeaturesCannotReplace,
+    FenceGates,
+    Fences,
+    Fire,
+    FlowerPots,
+    Flowers,
+    FoxesSpawnableOn,
+    FrogPreferJumpTo,
+    FrogsSpawnableOn,
+    GeodeInvalidBlocks,
+    GoatsSpawnableOn,
+    GoldOres,
+    GuardedByPiglins,
+    HoglinRepellents,
+    Ice,
+    Impermeable,
+    IncorrectForDiamondTool,
+    IncorrectForGoldTool,
+    IncorrectForIronTool,
+    IncorrectForNetheriteTool,
+    IncorrectForStoneTool,
+    IncorrectForWoodenTool,
+    InfiniburnEnd,
+    InfiniburnNether,
+    InfiniburnOverworld,
+    InsideStepSoundBlocks,
+    InvalidSpawnInside,
+    IronOres,
+    JungleLogs,
+    LapisOres,
+    LavaPoolStoneCannotReplace,
+    Leaves,
+    Logs,
+    LogsThatBurn,
+    LushGroundReplaceable,
+    MaintainsFarmland,
+    MangroveLogs,
+    MangroveLogsCanGrowThrough,
+    MangroveRootsCanGrowThrough,
+    MineableAxe,
+    MineableHoe,
+    MineablePickaxe,
+    MineableShovel,
+    MobInteractableDoors,
+    MooshroomsSpawnableOn,
+    MossReplaceable,
+    MushroomGrowBlock,
+    NeedsDiamondTool,
+    NeedsIronTool,
+    NeedsStoneTool,
+    NetherCarverReplaceables,
+    Nylium,
+    OakLogs,
+    OccludesVibrationSignals,
+    OverworldCarverReplaceables,
+    OverworldNaturalLogs,
+    PaleOakLogs,
+    ParrotsSpawnableOn,
+    PiglinRepellents,
+    Planks,
+    PolarBearsSpawnableOnAlternate,
+    Portals,
+    PressurePlates,
+    PreventMobSpawningInside,
+    RabbitsSpawnableOn,
+    Rails,
+    RedstoneOres,
+    Replaceable,
+    ReplaceableByTrees,
+    Sand,
+    Saplings,
+    SculkReplaceable,
+    SculkReplaceableWorldGen,
+    ShulkerBoxes,
+    Signs,
+    Slabs,
+    SmallDripleafPlaceable,
+    SmallFlowers,
+    SmeltsToGlass,
+    SnapsGoatHorn,
+    SnifferDiggableBlock,
+    SnifferEggHatchBoost,
+    Snow,
+    SnowLayerCanSurviveOn,
+    SnowLayerCannotSurviveOn,
+    SoulFireBaseBlocks,
+    SoulSpeedBlocks,
+    SpruceLogs,
+    Stairs,
+    StandingSigns,
+    StoneBricks,
+    StoneButtons,
+    StoneOreReplaceables,
+    StonePressurePlates,
+    StriderWarmBlocks,
+    SwordEfficient,
+    Terracotta,
+    TrailRuinsReplaceable,
+    Trapdoors,
+    UnderwaterBonemeals,
+    UnstableBottomCenter,
+    ValidSpawn,
+    VibrationResonators,
+    WallCorals,
+    WallHangingSigns,
+    WallPostOverride,
+    WallSigns,
+    Walls,
+    WarpedStems,
+    WartBlocks,
+    WitherImmune,
+    WitherSummonBaseBlocks,
+    WolvesSpawnableOn,
+    WoodenButtons,
+    WoodenDoors,
+    WoodenFences,
+    WoodenPressurePlates,
+    WoodenSlabs,
+    WoodenStairs,
+    WoodenTrapdoors,
+    Wool,
+    WoolCarpets,
+}
+
+impl BlockTag {
+    /// Every vanilla block tag.
+    pub const ALL: &'static [BlockTag] = &[
+        BlockTag::AcaciaLogs,
+        BlockTag::Air,
+        BlockTag::AllHangingSigns,
+        BlockTag::AllSigns,
+        BlockTag::AncientCityReplaceable,
+        BlockTag::AnimalsSpawnableOn,
+        BlockTag::Anvil,
+        BlockTag::ArmadilloSpawnableOn,
+        BlockTag::AxolotlsSpawnableOn,
+        BlockTag::AzaleaGrowsOn,
+        BlockTag::AzaleaRootReplaceable,
+        BlockTag::BadlandsTerracotta,
+        BlockTag::BambooBlocks,
+        BlockTag::BambooPlantableOn,
+        BlockTag::Banners,
+        BlockTag::BaseStoneNether,
+        BlockTag::BaseStoneOverworld,
+        BlockTag::BatsSpawnableOn,
+        BlockTag::BeaconBaseBlocks,
+        BlockTag::Beds,
+        BlockTag::BeeAttractive,
+        BlockTag::BeeGrowables,
+        BlockTag::Beehives,
+        BlockTag::BigDripleafPlaceable,
+        BlockTag::BirchLogs,
+        BlockTag::BlocksWindChargeExplosions,
+        BlockTag::Buttons,
+        BlockTag::CamelSandStepSoundBlocks,
+        BlockTag::Campfires,
+        BlockTag::CandleCakes,
+        BlockTag::Candles,
+        BlockTag::Cauldrons,
+        BlockTag::CaveVines,
+        BlockTag::CeilingHangingSigns,
+        BlockTag::CherryLogs,
+        BlockTag::Climbable,
+        BlockTag::CoalOres,
+        BlockTag::CombinationStepSoundBlocks,
+        BlockTag::CompletesFindTreeTutorial,
+        BlockTag::ConcretePowder,
+        BlockTag::ConvertableToMud,
+        BlockTag::CopperOres,
+        BlockTag::CoralBlocks,
+        BlockTag::CoralPlants,
+        BlockTag::Corals,
+        BlockTag::CrimsonStems,
+        BlockTag::Crops,
+        BlockTag::CrystalSoundBlocks,
+        BlockTag::DampensVibrations,
+        BlockTag::DarkOakLogs,
+        BlockTag::DeadBushMayPlaceOn,
+        BlockTag::DeepslateOreReplaceables,
+        BlockTag::DiamondOres,
+        BlockTag::Dirt,
+        BlockTag::DoesNotBlockHoppers,
+        BlockTag::Doors,
+        BlockTag::DragonImmune,
+        BlockTag::DragonTransparent,
+        BlockTag::DripstoneReplaceableBlocks,
+        BlockTag::EmeraldOres,
+        BlockTag::EnchantmentPowerProvider,
+        BlockTag::EnchantmentPowerTransmitter,
+        BlockTag::EndermanHoldable,
+        BlockTag::FallDamageResetting,
+        BlockTag::FeaturesCannotReplace,
+        BlockTag::FenceGates,
+        BlockTag::Fences,
+        BlockTag::Fire,
+        BlockTag::FlowerPots,
+        BlockTag::Flowers,
+        BlockTag::FoxesSpawnableOn,
+        BlockTag::FrogPreferJumpTo,
+        BlockTag::FrogsSpawnableOn,
+        BlockTag::GeodeInvalidBlocks,
+        BlockTag::GoatsSpawnableOn,
+        BlockTag::GoldOres,
+        BlockTag::GuardedByPiglins,
+        BlockTag::HoglinRepellents,
+        BlockTag::Ice,
+        BlockTag::Impermeable,
+        BlockTag::IncorrectForDiamondTool,
+        BlockTag::IncorrectForGoldTool,
+        BlockTag::IncorrectForIronTool,
+        BlockTag::IncorrectForNetheriteTool,
+        BlockTag::IncorrectForStoneTool,
+        BlockTag::IncorrectForWoodenTool,
+        BlockTag::InfiniburnEnd,
+        BlockTag::InfiniburnNether,
+        BlockTag::InfiniburnOverworld,
+        BlockTag::InsideStepSoundBlocks,
+        BlockTag::InvalidSpawnInside,
+        BlockTag::IronOres,
+        BlockTag::JungleLogs,
+        BlockTag::LapisOres,
+        BlockTag::LavaPoolStoneCannotReplace,
+        BlockTag::Leaves,
+        BlockTag::Logs,
+        BlockTag::LogsThatBurn,
+        BlockTag::LushGroundReplaceable,
+        BlockTag::MaintainsFarmland,
+        BlockTag::MangroveLogs,
+        BlockTag::MangroveLogsCanGrowThrough,
+        BlockTag::MangroveRootsCanGrowThrough,
+        BlockTag::MineableAxe,
+        BlockTag::MineableHoe,
+        BlockTag::MineablePickaxe,
+        BlockTag::MineableShovel,
+        BlockTag::MobInteractableDoors,
+        BlockTag::MooshroomsSpawnableOn,
+        BlockTag::MossReplaceable,
+        BlockTag::MushroomGrowBlock,
+        BlockTag::NeedsDiamondTool,
+        BlockTag::NeedsIronTool,
+        BlockTag::NeedsStoneTool,
+        BlockTag::NetherCarverReplaceables,
+        BlockTag::Nylium,
+        BlockTag::OakLogs,
+        BlockTag::OccludesVibrationSignals,
+        BlockTag::OverworldCarverReplaceables,
+        BlockTag::OverworldNaturalLogs,
+        BlockTag::PaleOakLogs,
+        BlockTag::ParrotsSpawnableOn,
+        BlockTag::PiglinRepellents,
+        BlockTag::Planks,
+        BlockTag::PolarBearsSpawnableOnAlternate,
+        BlockTag::Portals,
+        BlockTag::PressurePlates,
+        BlockTag::PreventMobSpawningInside,
+        BlockTag::RabbitsSpawnableOn,
+        BlockTag::Rails,
+        BlockTag::RedstoneOres,
+        BlockTag::Replaceable,
+        BlockTag::ReplaceableByTrees,
+        BlockTag::Sand,
+        BlockTag::Saplings,
+        BlockTag::SculkReplaceable,
+        BlockTag::SculkReplaceableWorldGen,
+        BlockTag::ShulkerBoxes,
+        BlockTag::Signs,
+        BlockTag::Slabs,
+        BlockTag::SmallDripleafPlaceable,
+        BlockTag::SmallFlowers,
+        BlockTag::SmeltsToGlass,
+        BlockTag::SnapsGoatHorn,
+        BlockTag::SnifferDiggableBlock,
+        BlockTag::SnifferEggHatchBoost,
+        BlockTag::Snow,
+        BlockTag::SnowLayerCanSurviveOn,
+        BlockTag::SnowLayerCannotSurviveOn,
+        BlockTag::SoulFireBaseBlocks,
+        BlockTag::SoulSpeedBlocks,
+        BlockTag::SpruceLogs,
+        BlockTag::Stairs,
+        BlockTag::StandingSigns,
+        BlockTag::StoneBricks,
+        BlockTag::StoneButtons,
+        BlockTag::StoneOreReplaceables,
+        BlockTag::StonePressurePlates,
+        BlockTag::StriderWarmBlocks,
+        BlockTag::SwordEfficient,
+        BlockTag::Terracotta,
+        BlockTag::TrailRuinsReplaceable,
+        BlockTag::Trapdoors,
+        BlockTag::UnderwaterBonemeals,
+        BlockTag::UnstableBottomCenter,
+        BlockTag::ValidSpawn,
+        BlockTag::VibrationResonators,
+        BlockTag::WallCorals,
+        BlockTag::WallHangingSigns,
+        BlockTag::WallPostOverride,
+        BlockTag::WallSigns,
+        BlockTag::Walls,
+        BlockTag::WarpedStems,
+        BlockTag::WartBlocks,
+        BlockTag::WitherImmune,
+        BlockTag::WitherSummonBaseBlocks,
+        BlockTag::WolvesSpawnableOn,
+        BlockTag::WoodenButtons,
+        BlockTag::WoodenDoors,
+        BlockTag::WoodenFences,
+        BlockTag::WoodenPressurePlates,
+        BlockTag::WoodenSlabs,
+        BlockTag::WoodenStairs,
+        BlockTag::WoodenTrapdoors,
+        BlockTag::Wool,
+        BlockTag::WoolCarpets,
+    ];
+
+    /// The name of the tag without the `minecraft:` namespace, like
+    /// `mineable/pickaxe`.
+    pub fn name(self) -> &'static str {
+        match self {
+            BlockTag::AcaciaLogs => "acacia_logs",
+            BlockTag::Air => "air",
+            BlockTag::AllHangingSigns => "all_hanging_signs",
+            BlockTag::AllSigns => "all_signs",
+            BlockTag::AncientCityReplaceable => "ancient_city_replaceable",
+            BlockTag::AnimalsSpawnableOn => "animals_spawnable_on",
+            BlockTag::Anvil => "anvil",
+            BlockTag::ArmadilloSpawnableOn => "armadillo_spawnable_on",
+            BlockTag::AxolotlsSpawnableOn => "axolotls_spawnable_on",
+            BlockTag::AzaleaGrowsOn => "azalea_grows_on",
+            BlockTag::AzaleaRootReplaceable => "azalea_root_replaceable",
+            BlockTag::BadlandsTerracotta => "badlands_terracotta",
+            BlockTag::BambooBlocks => "bamboo_blocks",
+            BlockTag::BambooPlantableOn => "bamboo_plantable_on",
+            BlockTag::Banners => "banners",
+            BlockTag::BaseStoneNether => "base_stone_nether",
+            BlockTag::BaseStoneOverworld => "base_stone_overworld",
+            BlockTag::BatsSpawnableOn => "bats_spawnable_on",
+            BlockTag::BeaconBaseBlocks => "beacon_base_blocks",
+            BlockTag::Beds => "beds",
+            BlockTag::BeeAttractive => "bee_attractive",
+            BlockTag::BeeGrowables => "bee_growables",
+            BlockTag::Beehives => "beehives",
+            BlockTag::BigDripleafPlaceable => "big_dripleaf_placeable",
+            BlockTag::BirchLogs => "birch_logs",
+            BlockTag::BlocksWindChargeExplosions => "blocks_wind_charge_explosions",
+            BlockTag::Buttons => "buttons",
+            BlockTag::CamelSandStepSoundBlocks => "camel_sand_step_sound_blocks",
+            BlockTag::Campfires => "campfires",
+            BlockTag::CandleCakes => "candle_cakes",
+            BlockTag::Candles => "candles",
+            BlockTag::Cauldrons => "cauldrons",
+            BlockTag::CaveVines => "cave_vines",
+            BlockTag::CeilingHangingSigns => "ceiling_hanging_signs",
+            BlockTag::CherryLogs => "cherry_logs",
+            BlockTag::Climbable => "climbable",
+            BlockTag::CoalOres => "coal_ores",
+            BlockTag::CombinationStepSoundBlocks => "combination_step_sound_blocks",
+            BlockTag::CompletesFindTreeTutorial => "completes_find_tree_tutorial",
+            BlockTag::ConcretePowder => "concrete_powder",
+            BlockTag::ConvertableToMud => "convertable_to_mud",
+            BlockTag::CopperOres => "copper_ores",
+            BlockTag::CoralBlocks => "coral_blocks",
+            BlockTag::CoralPlants => "coral_plants",
+            BlockTag::Corals => "corals",
+            BlockTag::CrimsonStems => "crimson_stems",
+            BlockTag::Crops => "crops",
+            BlockTag::CrystalSoundBlocks => "crystal_sound_blocks",
+            BlockTag::DampensVibrations => "dampens_vibrations",
+            BlockTag::DarkOakLogs => "dark_oak_logs",
+            BlockTag::DeadBushMayPlaceOn => "dead_bush_may_place_on",
+            BlockTag::DeepslateOreReplaceables => "deepslate_ore_replaceables",
+            BlockTag::DiamondOres => "diamond_ores",
+            BlockTag::Dirt => "dirt",
+            BlockTag::DoesNotBlockHoppers => "does_not_block_hoppers",
+            BlockTag::Doors => "doors",
+            BlockTag::DragonImmune => "dragon_immune",
+            BlockTag::DragonTransparent => "dragon_transparent",
+            BlockTag::DripstoneReplaceableBlocks => "dripstone_replaceable_blocks",
+            BlockTag::EmeraldOres => "emerald_ores",
+            BlockTag::EnchantmentPowerProvider => "enchantment_power_provider",
+            BlockTag::EnchantmentPowerTransmitter => "enchantment_power_transmitter",
+            BlockTag::EndermanHoldable => "enderman_holdable",
+            BlockTag::FallDamageResetting => "fall_damage_resetting",
+            BlockTag::FeaturesCannotReplace => "features_cannot_replace",
+            BlockTag::FenceGates => "fence_gates",
+            BlockTag::Fences => "fences",
+            BlockTag::Fire => "fire",
+            BlockTag::FlowerPots => "flower_pots",
+            BlockTag::Flowers => "flowers",
+            BlockTag::FoxesSpawnableOn => "foxes_spawnable_on",
+            BlockTag::FrogPreferJumpTo => "frog_prefer_jump_to",
+            BlockTag::FrogsSpawnableOn => "frogs_spawnable_on",
+            BlockTag::GeodeInvalidBlocks => "geode_invalid_blocks",
+            BlockTag::GoatsSpawnableOn => "goats_spawnable_on",
+            BlockTag::GoldOres => "gold_ores",
+            BlockTag::GuardedByPiglins => "guarded_by_piglins",
+            BlockTag::HoglinRepellents => "hoglin_repellents",
+            BlockTag::Ice => "ice",
+            BlockTag::Impermeable => "impermeable",
+            BlockTag::IncorrectForDiamondTool => "incorrect_for_diamond_tool",
+            BlockTag::IncorrectForGoldTool => "incorrect_for_gold_tool",
+            BlockTag::IncorrectForIronTool => "incorrect_for_iron_tool",
+            BlockTag::IncorrectForNetheriteTool => "incorrect_for_netherite_tool",
+            BlockTag::IncorrectForStoneTool => "incorrect_for_stone_tool",
+            BlockTag::IncorrectForWoodenTool => "incorrect_for_wooden_tool",
+            BlockTag::InfiniburnEnd => "infiniburn_end",
+            BlockTag::InfiniburnNether => "infiniburn_nether",
+            BlockTag::InfiniburnOverworld => "infiniburn_overworld",
+            BlockTag::InsideStepSoundBlocks => "inside_step_sound_blocks",
+            BlockTag::InvalidSpawnInside => "invalid_spawn_inside",
+            BlockTag::IronOres => "iron_ores",
+            BlockTag::JungleLogs => "jungle_logs",
+            BlockTag::LapisOres => "lapis_ores",
+            BlockTag::LavaPoolStoneCannotReplace => "lava_pool_stone_cannot_replace",
+            BlockTag::Leaves => "leaves",
+            BlockTag::Logs => "logs",
+            BlockTag::LogsThatBurn => "logs_that_burn",
+            BlockTag::LushGroundReplaceable => "lush_ground_replaceable",
+            BlockTag::MaintainsFarmland => "maintains_farmland",
+            BlockTag::MangroveLogs => "mangrove_logs",
+            BlockTag::MangroveLogsCanGrowThrough => "mangrove_logs_can_grow_through",
+            BlockTag::MangroveRootsCanGrowThrough => "mangrove_roots_can_grow_through",
+            BlockTag::MineableAxe => "mineable/axe",
+            BlockTag::MineableHoe => "mineable/hoe",
+            BlockTag::MineablePickaxe => "mineable/pickaxe",
+            BlockTag::MineableShovel => "mineable/shovel",
+            BlockTag::MobInteractableDoors => "mob_interactable_doors",
+            BlockTag::MooshroomsSpawnableOn => "mooshrooms_spawnable_on",
+            BlockTag::MossReplaceable => "moss_replaceable",
+            BlockTag::MushroomGrowBlock => "mushroom_grow_block",
+            BlockTag::NeedsDiamondTool => "needs_diamond_tool",
+            BlockTag::NeedsIronTool => "needs_iron_tool",
+            BlockTag::NeedsStoneTool => "needs_stone_tool",
+            BlockTag::NetherCarverReplaceables => "nether_carver_replaceables",
+            BlockTag::Nylium => "nylium",
+            BlockTag::OakLogs => "oak_logs",
+            BlockTag::OccludesVibrationSignals => "occludes_vibration_signals",
+            BlockTag::OverworldCarverReplaceables => "overworld_carver_replaceables",
+            BlockTag::OverworldNaturalLogs => "overworld_natural_logs",
+            BlockTag::PaleOakLogs => "pale_oak_logs",
+            BlockTag::ParrotsSpawnableOn => "parrots_spawnable_on",
+            BlockTag::PiglinRepellents => "piglin_repellents",
+            BlockTag::Planks => "planks",
+            BlockTag::PolarBearsSpawnableOnAlternate => "polar_bears_spawnable_on_alternate",
+            BlockTag::Portals => "portals",
+            BlockTag::PressurePlates => "pressure_plates",
+            BlockTag::PreventMobSpawningInside => "prevent_mob_spawning_inside",
+            BlockTag::RabbitsSpawnableOn => "rabbits_spawnable_on",
+            BlockTag::Rails => "rails",
+            BlockTag::RedstoneOres => "redstone_ores",
+            BlockTag::Replaceable => "replaceable",
+            BlockTag::ReplaceableByTrees => "replaceable_by_trees",
+            BlockTag::Sand => "sand",
+            BlockTag::Saplings => "saplings",
+            BlockTag::SculkReplaceable => "sculk_replaceable",
+            BlockTag::SculkReplaceableWorldGen => "sculk_replaceable_world_gen",
+            BlockTag::ShulkerBoxes => "shulker_boxes",
+            BlockTag::Signs => "signs",
+            BlockTag::Slabs => "slabs",
+            BlockTag::SmallDripleafPlaceable => "small_dripleaf_placeable",
+            BlockTag::SmallFlowers => "small_flowers",
+            BlockTag::SmeltsToGlass => "smelts_to_glass",
+            BlockTag::SnapsGoatHorn => "snaps_goat_horn",
+            BlockTag::SnifferDiggableBlock => "sniffer_diggable_block",
+            BlockTag::SnifferEggHatchBoost => "sniffer_egg_hatch_boost",
+            BlockTag::Snow => "snow",
+            BlockTag::SnowLayerCanSurviveOn => "snow_layer_can_survive_on",
+            BlockTag::SnowLayerCannotSurviveOn => "snow_layer_cannot_survive_on",
+            BlockTag::SoulFireBaseBlocks => "soul_fire_base_blocks",
+            BlockTag::SoulSpeedBlocks => "soul_speed_blocks",
+            BlockTag::SpruceLogs => "spruce_logs",
+            BlockTag::Stairs => "stairs",
+            BlockTag::StandingSigns => "standing_signs",
+            BlockTag::StoneBricks => "stone_bricks",
+            BlockTag::StoneButtons => "stone_buttons",
+            BlockTag::StoneOreReplaceables => "stone_ore_replaceables",
+            BlockTag::StonePressurePlates => "stone_pressure_plates",
+            BlockTag::StriderWarmBlocks => "strider_warm_blocks",
+            BlockTag::SwordEfficient => "sword_efficient",
+            BlockTag::Terracotta => "terracotta",
+            BlockTag::TrailRuinsReplaceable => "trail_ruins_replaceable",
+            BlockTag::Trapdoors => "trapdoors",
+            BlockTag::UnderwaterBonemeals => "underwater_bonemeals",
+            BlockTag::UnstableBottomCenter => "unstable_bottom_center",
+            BlockTag::ValidSpawn => "valid_spawn",
+            BlockTag::VibrationResonators => "vibration_resonators",
+            BlockTag::WallCorals => "wall_corals",
+            BlockTag::WallHangingSigns => "wall_hanging_signs",
+            BlockTag::WallPostOverride => "wall_post_override",
+            BlockTag::WallSigns => "wall_signs",
+            BlockTag::Walls => "walls",
+            BlockTag::WarpedStems => "warped_stems",
+            BlockTag::WartBlocks => "wart_blocks",
+            BlockTag::WitherImmune => "wither_immune",
+            BlockTag::WitherSummonBaseBlocks => "wither_summon_base_blocks",
+            BlockTag::WolvesSpawnableOn => "wolves_spawnable_on",
+            BlockTag::WoodenButtons => "wooden_buttons",
+            BlockTag::WoodenDoors => "wooden_doors",
+            BlockTag::WoodenFences => "wooden_fences",
+            BlockTag::WoodenPressurePlates => "wooden_pressure_plates",
+            BlockTag::WoodenSlabs => "wooden_slabs",
+            BlockTag::WoodenStairs => "wooden_stairs",
+            BlockTag::WoodenTrapdoors => "wooden_trapdoors",
+            BlockTag::Wool => "wool",
+            BlockTag::WoolCarpets => "wool_carpets",
+        }
+    }
+
+    /// Get the tag from its name, which can start with `minecraft:`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {
+            "acacia_logs" => BlockTag::AcaciaLogs,
+            "air" => BlockTag::Air,
+            "all_hanging_signs" => BlockTag::AllHangingSigns,
+            "all_signs" => BlockTag::AllSigns,
+            "ancient_city_replaceable" => BlockTag::AncientCityReplaceable,
+            "animals_spawnable_on" => BlockTag::AnimalsSpawnableOn,
+            "anvil" => BlockTag::Anvil,
+            "armadillo_spawnable_on" => BlockTag::ArmadilloSpawnableOn,
+            "axolotls_spawnable_on" => BlockTag::AxolotlsSpawnableOn,
+            "azalea_grows_on" => BlockTag::AzaleaGrowsOn,
+            "azalea_root_replaceable" => BlockTag::AzaleaRootReplaceable,
+            "badlands_terracotta" => BlockTag::BadlandsTerracotta,
+            "bamboo_blocks" => BlockTag::BambooBlocks,
+            "bamboo_plantable_on" => BlockTag::BambooPlantableOn,
+            "banners" => BlockTag::Banners,
+            "base_stone_nether" => BlockTag::BaseStoneNether,
+            "base_stone_overworld" => BlockTag::BaseStoneOverworld,
+            "bats_spawnable_on" => BlockTag::BatsSpawnableOn,
+            "beacon_base_blocks" => BlockTag::BeaconBaseBlocks,
+            "beds" => BlockTag::Beds,
+            "bee_attractive" => BlockTag::BeeAttractive,
+            "bee_growables" => BlockTag::BeeGrowables,
+            "beehives" => BlockTag::Beehives,
+            "big_dripleaf_placeable" => BlockTag::BigDripleafPlaceable,
+            "birch_logs" => BlockTag::BirchLogs,
+            "blocks_wind_charge_explosions" => BlockTag::BlocksWindChargeExplosions,
+            "buttons" => BlockTag::Buttons,
+            "camel_sand_step_sound_blocks" => BlockTag::CamelSandStepSoundBlocks,
+            "campfires" => BlockTag::Campfires,
+            "candle_cakes" => BlockTag::CandleCakes,
+            "candles" => BlockTag::Candles,
+            "cauldrons" => BlockTag::Cauldrons,
+            "cave_vines" => BlockTag::CaveVines,
+            "ceiling_hanging_signs" => BlockTag::CeilingHangingSigns,
+            "cherry_logs" => BlockTag::CherryLogs,
+            "climbable" => BlockTag::Climbable,
+            "coal_ores" => BlockTag::CoalOres,
+            "combination_step_sound_blocks" => BlockTag::CombinationStepSoundBlocks,
+            "completes_find_tree_tutorial" => BlockTag::CompletesFindTreeTutorial,
+            "concrete_powder" => BlockTag::ConcretePowder,
+            "convertable_to_mud" => BlockTag::ConvertableToMud,
+            "copper_ores" => BlockTag::CopperOres,
+            "coral_blocks" => BlockTag::CoralBlocks,
+            "coral_plants" => BlockTag::CoralPlants,
+            "corals" => BlockTag::Corals,
+            "crimson_stems" => BlockTag::CrimsonStems,
+            "crops" => BlockTag::Crops,
+            "crystal_sound_blocks" => BlockTag::CrystalSoundBlocks,
+            "dampens_vibrations" => BlockTag::DampensVibrations,
+            "dark_oak_logs" => BlockTag::DarkOakLogs,
+            "dead_bush_may_place_on" => BlockTag::DeadBushMayPlaceOn,
+            "deepslate_ore_replaceables" => BlockTag::DeepslateOreReplaceables,
+            "diamond_ores" => BlockTag::DiamondOres,
+            "dirt" => BlockTag::Dirt,
+            "does_not_block_hoppers" => BlockTag::DoesNotBlockHoppers,
+            "doors" => BlockTag::Doors,
+            "dragon_immune" => BlockTag::DragonImmune,
+            "dragon_transparent" => BlockTag::DragonTransparent,
+            "dripstone_replaceable_blocks" => BlockTag::DripstoneReplaceableBlocks,
+            "emerald_ores" => BlockTag::EmeraldOres,
+            "enchantment_power_provider" => BlockTag::EnchantmentPowerProvider,
+            "enchantment_power_transmitter" => BlockTag::EnchantmentPowerTransmitter,
+            "enderman_holdable" => BlockTag::EndermanHoldable,
+            "fall_damage_resetting" => BlockTag::FallDamageResetting,
+            "features_cannot_replace" => BlockTag::FeaturesCannotReplace,
+            "fence_gates" => BlockTag::FenceGates,
+            "fences" => BlockTag::Fences,
+            "fire" => BlockTag::Fire,
+            "flower_pots" => BlockTag::FlowerPots,
+            "flowers" => BlockTag::Flowers,
+            "foxes_spawnable_on" => BlockTag::FoxesSpawnableOn,
+            "frog_prefer_jump_to" => BlockTag::FrogPreferJumpTo,
+            "frogs_spawnable_on" => BlockTag::FrogsSpawnableOn,
+            "geode_invalid_blocks" => BlockTag::GeodeInvalidBlocks,
+            "goats_spawnable_on" => BlockTag::GoatsSpawnableOn,
+            "gold_ores" => BlockTag::GoldOres,
+            "guarded_by_piglins" => BlockTag::GuardedByPiglins,
+            "hoglin_repellents" => BlockTag::HoglinRepellents,
+            "ice" => BlockTag::Ice,
+            "impermeable" => BlockTag::Impermeable,
+            "incorrect_for_diamond_tool" => BlockTag::IncorrectForDiamondTool,
+            "incorrect_for_gold_tool" => BlockTag::IncorrectForGoldTool,
+            "incorrect_for_iron_tool" => BlockTag::IncorrectForIronTool,
+            "incorrect_for_netherite_tool" => BlockTag::IncorrectForNetheriteTool,
+            "incorrect_for_stone_tool" => BlockTag::IncorrectForStoneTool,
+            "incorrect_for_wooden_tool" => BlockTag::IncorrectForWoodenTool,
+            "infiniburn_end" => BlockTag::InfiniburnEnd,
+            "infiniburn_nether" => BlockTag::InfiniburnNether,
+            "infiniburn_overworld" => BlockTag::InfiniburnOverworld,
+            "inside_step_sound_blocks" => BlockTag::InsideStepSoundBlocks,
+            "invalid_spawn_inside" => BlockTag::InvalidSpawnInside,
+            "iron_ores" => BlockTag::IronOres,
+            "jungle_logs" => BlockTag::JungleLogs,
+            "lapis_ores" => BlockTag::LapisOres,
+            "lava_pool_stone_cannot_replace" => BlockTag::LavaPoolStoneCannotReplace,
+            "leaves" => BlockTag::Leaves,
+            "logs" => BlockTag::Logs,
+            "logs_that_burn" => BlockTag::LogsThatBurn,
+            "lush_ground_replaceable" => BlockTag::LushGroundReplaceable,
+            "maintains_farmland" => BlockTag::MaintainsFarmland,
+            "mangrove_logs" => BlockTag::MangroveLogs,
+            "mangrove_logs_can_grow_through" => BlockTag::MangroveLogsCanGrowThrough,
+            "mangrove_roots_can_grow_through" => BlockTag::MangroveRootsCanGrowThrough,
+            "mineable/axe" => BlockTag::MineableAxe,
+            "mineable/hoe" => BlockTag::MineableHoe,
+            "mineable/pickaxe" => BlockTag::MineablePickaxe,
+            "mineable/shovel" => BlockTag::MineableShovel,
+            "mob_interactable_doors" => BlockTag::MobInteractableDoors,
+            "mooshrooms_spawnable_on" => BlockTag::MooshroomsSpawnableOn,
+            "moss_replaceable" => BlockTag::MossReplaceable,
+            "mushroom_grow_block" => BlockTag::MushroomGrowBlock,
+            "needs_diamond_tool" => BlockTag::NeedsDiamondTool,
+            "needs_iron_tool" => BlockTag::NeedsIronTool,
+            "needs_stone_tool" => BlockTag::NeedsStoneTool,
+            "nether_carver_replaceables" => BlockTag::NetherCarverReplaceables,
+            "nylium" => BlockTag::Nylium,
+            "oak_logs" => BlockTag::OakLogs,
+            "occludes_vibration_signals" => BlockTag::OccludesVibrationSignals,
+            "overworld_carver_replaceables" => BlockTag::OverworldCarverReplaceables,
+            "overworld_natural_logs" => BlockTag::OverworldNaturalLogs,
+            "pale_oak_logs" => BlockTag::PaleOakLogs,
+            "parrots_spawnable_on" => BlockTag::ParrotsSpawnableOn,
+            "piglin_repellents" => BlockTag::PiglinRepellents,
+            "planks" => BlockTag::Planks,
+            "polar_bears_spawnable_on_alternate" => BlockTag::PolarBearsSpawnableOnAlternate,
+            "portals" => BlockTag::Portals,
+            "pressure_plates" => BlockTag::PressurePlates,
+            "prevent_mob_spawning_inside" => BlockTag::PreventMobSpawningInside,
+            "rabbits_spawnable_on" => BlockTag::RabbitsSpawnableOn,
+            "rails" => BlockTag::Rails,
+            "redstone_ores" => BlockTag::RedstoneOres,
+            "replaceable" => BlockTag::Replaceable,
+            "replaceable_by_trees" => BlockTag::ReplaceableByTrees,
+            "sand" => BlockTag::Sand,
+            "saplings" => BlockTag::Saplings,
+            "sculk_replaceable" => BlockTag::SculkReplaceable,
+            "sculk_replaceable_world_gen" => BlockTag::SculkReplaceableWorldGen,
+            "shulker_boxes" => BlockTag::ShulkerBoxes,
+            "signs" => BlockTag::Signs,
+            "slabs" => BlockTag::Slabs,
+            "small_dripleaf_placeable" => BlockTag::SmallDripleafPlaceable,
+            "small_flowers" => BlockTag::SmallFlowers,
+            "smelts_to_glass" => BlockTag::SmeltsToGlass,
+            "snaps_goat_horn" => BlockTag::SnapsGoatHorn,
+            "sniffer_diggable_block" => BlockTag::SnifferDiggableBlock,
+            "sniffer_egg_hatch_boost" => BlockTag::SnifferEggHatchBoost,
+            "snow" => BlockTag::Snow,
+            "snow_layer_can_survive_on" => BlockTag::SnowLayerCanSurviveOn,
+            "snow_layer_cannot_survive_on" => BlockTag::SnowLayerCannotSurviveOn,
+            "soul_fire_base_blocks" => BlockTag::SoulFireBaseBlocks,
+            "soul_speed_blocks" => BlockTag::SoulSpeedBlocks,
+            "spruce_logs" => BlockTag::SpruceLogs,
+            "stairs" => BlockTag::Stairs,
+            "standing_signs" => BlockTag::StandingSigns,
+            "stone_bricks" => BlockTag::StoneBricks,
+            "stone_buttons" => BlockTag::StoneButtons,
+            "stone_ore_replaceables" => BlockTag::StoneOreReplaceables,
+            "stone_pressure_plates" => BlockTag::StonePressurePlates,
+            "strider_warm_blocks" => BlockTag::StriderWarmBlocks,
+            "sword_efficient" => BlockTag::SwordEfficient,
+            "terracotta" => BlockTag::Terracotta,
+            "trail_ruins_replaceable" => BlockTag::TrailRuinsReplaceable,
+            "trapdoors" => BlockTag::Trapdoors,
+            "underwater_bonemeals" => BlockTag::UnderwaterBonemeals,
+            "unstable_bottom_center" => BlockTag::UnstableBottomCenter,
+            "valid_spawn" => BlockTag::ValidSpawn,
+            "vibration_resonators" => BlockTag::VibrationResonators,
+            "wall_corals" => BlockTag::WallCorals,
+            "wall_hanging_signs" => BlockTag::WallHangingSigns,
+            "wall_post_override" => BlockTag::WallPostOverride,
+            "wall_signs" => BlockTag::WallSigns,
+            "walls" => BlockTag::Walls,
+            "warped_stems" => BlockTag::WarpedStems,
+            "wart_blocks" => BlockTag::WartBlocks,
+            "wither_immune" => BlockTag::WitherImmune,
+            "wither_summon_base_blocks" => BlockTag::WitherSummonBaseBlocks,
+            "wolves_spawnable_on" => BlockTag::WolvesSpawnableOn,
+            "wooden_buttons" => BlockTag::WoodenButtons,
+            "wooden_doors" => BlockTag::WoodenDoors,
+            "wooden_fences" => BlockTag::WoodenFences,
+            "wooden_pressure_plates" => BlockTag::WoodenPressurePlates,
+            "wooden_slabs" => BlockTag::WoodenSlabs,
+            "wooden_stairs" => BlockTag::WoodenStairs,
+            "wooden_trapdoors" => BlockTag::WoodenTrapdoors,
+            "wool" => BlockTag::Wool,
+            "wool_carpets" => BlockTag::WoolCarpets,
+            _ => return None,
+        })
+    }
+
+    /// Everything that's in the tag.
+    pub fn values(self) -> &'static HashSet<Block> {
+        match self {
+            BlockTag::AcaciaLogs => &ACACIA_LOGS,
+            BlockTag::Air => &AIR,
+            BlockTag::AllHangingSigns => &ALL_HANGING_SIGNS,
+            BlockTag::AllSigns => &ALL_SIGNS,
+            BlockTag::AncientCityReplaceable => &ANCIENT_CITY_REPLACEABLE,
+            BlockTag::AnimalsSpawnableOn => &ANIMALS_SPAWNABLE_ON,
+            BlockTag::Anvil => &ANVIL,
+            BlockTag::ArmadilloSpawnableOn => &ARMADILLO_SPAWNABLE_ON,
+            BlockTag::AxolotlsSpawnableOn => &AXOLOTLS_SPAWNABLE_ON,
+            BlockTag::AzaleaGrowsOn => &AZALEA_GROWS_ON,
+            BlockTag::AzaleaRootReplaceable => &AZALEA_ROOT_REPLACEABLE,
+            BlockTag::BadlandsTerracotta => &BADLANDS_TERRACOTTA,
+            BlockTag::BambooBlocks => &BAMBOO_BLOCKS,
+            BlockTag::BambooPlantableOn => &BAMBOO_PLANTABLE_ON,
+            BlockTag::Banners => &BANNERS,
+            BlockTag::BaseStoneNether => &BASE_STONE_NETHER,
+            BlockTag::BaseStoneOverworld => &BASE_STONE_OVERWORLD,
+            BlockTag::BatsSpawnableOn => &BATS_SPAWNABLE_ON,
+            BlockTag::BeaconBaseBlocks => &BEACON_BASE_BLOCKS,
+            BlockTag::Beds => &BEDS,
+            BlockTag::BeeAttractive => &BEE_ATTRACTIVE,
+            BlockTag::BeeGrowables => &BEE_GROWABLES,
+            BlockTag::Beehives => &BEEHIVES,
+            BlockTag::BigDripleafPlaceable => &BIG_DRIPLEAF_PLACEABLE,
+            BlockTag::BirchLogs => &BIRCH_LOGS,
+            BlockTag::BlocksWindChargeExplosions => &BLOCKS_WIND_CHARGE_EXPLOSIONS,
+            BlockTag::Buttons => &BUTTONS,
+            BlockTag::CamelSandStepSoundBlocks => &CAMEL_SAND_STEP_SOUND_BLOCKS,
+            BlockTag::Campfires => &CAMPFIRES,
+            BlockTag::CandleCakes => &CANDLE_CAKES,
+            BlockTag::Candles => &CANDLES,
+            BlockTag::Cauldrons => &CAULDRONS,
+            BlockTag::CaveVines => &CAVE_VINES,
+            BlockTag::CeilingHangingSigns => &CEILING_HANGING_SIGNS,
+            BlockTag::CherryLogs => &CHERRY_LOGS,
+            BlockTag::Climbable => &CLIMBABLE,
+            BlockTag::CoalOres => &COAL_ORES,
+            BlockTag::CombinationStepSoundBlocks => &COMBINATION_STEP_SOUND_BLOCKS,
+            BlockTag::CompletesFindTreeTutorial => &COMPLETES_FIND_TREE_TUTORIAL,
+            BlockTag::ConcretePowder => &CONCRETE_POWDER,
+            BlockTag::ConvertableToMud => &CONVERTABLE_TO_MUD,
+            BlockTag::CopperOres => &COPPER_ORES,
+            BlockTag::CoralBlocks => &CORAL_BLOCKS,
+            BlockTag::CoralPlants => &CORAL_PLANTS,
+            BlockTag::Corals => &CORALS,
+            BlockTag::CrimsonStems => &CRIMSON_STEMS,
+            BlockTag::Crops => &CROPS,
+            BlockTag::CrystalSoundBlocks => &CRYSTAL_SOUND_BLOCKS,
+            BlockTag::DampensVibrations => &DAMPENS_VIBRATIONS,
+            BlockTag::DarkOakLogs => &DARK_OAK_LOGS,
+            BlockTag::DeadBushMayPlaceOn => &DEAD_BUSH_MAY_PLACE_ON,
+            BlockTag::DeepslateOreReplaceables => &DEEPSLATE_ORE_REPLACEABLES,
+            BlockTag::DiamondOres => &DIAMOND_ORES,
+            BlockTag::Dirt => &DIRT,
+            BlockTag::DoesNotBlockHoppers => &DOES_NOT_BLOCK_HOPPERS,
+            BlockTag::Doors => &DOORS,
+            BlockTag::DragonImmune => &DRAGON_IMMUNE,
+            BlockTag::DragonTransparent => &DRAGON_TRANSPARENT,
+            BlockTag::DripstoneReplaceableBlocks => &DRIPSTONE_REPLACEABLE_BLOCKS,
+            BlockTag::EmeraldOres => &EMERALD_ORES,
+            BlockTag::EnchantmentPowerProvider => &ENCHANTMENT_POWER_PROVIDER,
+            BlockTag::EnchantmentPowerTransmitter => &ENCHANTMENT_POWER_TRANSMITTER,
+            BlockTag::EndermanHoldable => &ENDERMAN_HOLDABLE,
+            BlockTag::FallDamageResetting => &FALL_DAMAGE_RESETTING,
+            BlockTag::FeaturesCannotReplace => &FEATURES_CANNOT_REPLACE,
+            BlockTag::FenceGates => &FENCE_GATES,
+            BlockTag::Fences => &FENCES,
+            BlockTag::Fire => &FIRE,
+            BlockTag::FlowerPots => &FLOWER_POTS,
+            BlockTag::Flowers => &FLOWERS,
+            BlockTag::FoxesSpawnableOn => &FOXES_SPAWNABLE_ON,
+            BlockTag::FrogPreferJumpTo => &FROG_PREFER_JUMP_TO,
+            BlockTag::FrogsSpawnableOn => &FROGS_SPAWNABLE_ON,
+            BlockTag::GeodeInvalidBlocks => &GEODE_INVALID_BLOCKS,
+            BlockTag::GoatsSpawnableOn => &GOATS_SPAWNABLE_ON,
+            BlockTag::GoldOres => &GOLD_ORES,
+            BlockTag::GuardedByPiglins => &GUARDED_BY_PIGLINS,
+            BlockTag::HoglinRepellents => &HOGLIN_REPELLENTS,
+            BlockTag::Ice => &ICE,
+            BlockTag::Impermeable => &IMPERMEABLE,
+            BlockTag::IncorrectForDiamondTool => &INCORRECT_FOR_DIAMOND_TOOL,
+            BlockTag::IncorrectForGoldTool => &INCORRECT_FOR_GOLD_TOOL,
+            BlockTag::IncorrectForIronTool => &INCORRECT_FOR_IRON_TOOL,
+            BlockTag::IncorrectForNetheriteTool => &INCORRECT_FOR_NETHERITE_TOOL,
+            BlockTag::IncorrectForStoneTool => &INCORRECT_FOR_STONE_TOOL,
+            BlockTag::IncorrectForWoodenTool => &INCORRECT_FOR_WOODEN_TOOL,
+            BlockTag::InfiniburnEnd => &INFINIBURN_END,
+            BlockTag::InfiniburnNether => &INFINIBURN_NETHER,
+            BlockTag::InfiniburnOverworld => &INFINIBURN_OVERWORLD,
+            BlockTag::InsideStepSoundBlocks => &INSIDE_STEP_SOUND_BLOCKS,
+            BlockTag::InvalidSpawnInside => &INVALID_SPAWN_INSIDE,
+            BlockTag::IronOres => &IRON_ORES,
+            BlockTag::JungleLogs => &JUNGLE_LOGS,
+            BlockTag::LapisOres => &LAPIS_ORES,
+            BlockTag::LavaPoolStoneCannotReplace => &LAVA_POOL_STONE_CANNOT_REPLACE,
+            BlockTag::Leaves => &LEAVES,
+            BlockTag::Logs => &LOGS,
+            BlockTag::LogsThatBurn => &LOGS_THAT_BURN,
+            BlockTag::LushGroundReplaceable => &LUSH_GROUND_REPLACEABLE,
+            BlockTag::MaintainsFarmland => &MAINTAINS_FARMLAND,
+            BlockTag::MangroveLogs => &MANGROVE_LOGS,
+            BlockTag::MangroveLogsCanGrowThrough => &MANGROVE_LOGS_CAN_GROW_THROUGH,
+            BlockTag::MangroveRootsCanGrowThrough => &MANGROVE_ROOTS_CAN_GROW_THROUGH,
+            BlockTag::MineableAxe => &MINEABLE_AXE,
+            BlockTag::MineableHoe => &MINEABLE_HOE,
+            BlockTag::MineablePickaxe => &MINEABLE_PICKAXE,
+            BlockTag::MineableShovel => &MINEABLE_SHOVEL,
+            BlockTag::MobInteractableDoors => &MOB_INTERACTABLE_DOORS,
+            BlockTag::MooshroomsSpawnableOn => &MOOSHROOMS_SPAWNABLE_ON,
+            BlockTag::MossReplaceable => &MOSS_REPLACEABLE,
+            BlockTag::MushroomGrowBlock => &MUSHROOM_GROW_BLOCK,
+            BlockTag::NeedsDiamondTool => &NEEDS_DIAMOND_TOOL,
+            BlockTag::NeedsIronTool => &NEEDS_IRON_TOOL,
+            BlockTag::NeedsStoneTool => &NEEDS_STONE_TOOL,
+            BlockTag::NetherCarverReplaceables => &NETHER_CARVER_REPLACEABLES,
+            BlockTag::Nylium => &NYLIUM,
+            BlockTag::OakLogs => &OAK_LOGS,
+            BlockTag::OccludesVibrationSignals => &OCCLUDES_VIBRATION_SIGNALS,
+            BlockTag::OverworldCarverReplaceables => &OVERWORLD_CARVER_REPLACEABLES,
+            BlockTag::OverworldNaturalLogs => &OVERWORLD_NATURAL_LOGS,
+            BlockTag::PaleOakLogs => &PALE_OAK_LOGS,
+            BlockTag::ParrotsSpawnableOn => &PARROTS_SPAWNABLE_ON,
+            BlockTag::PiglinRepellents => &PIGLIN_REPELLENTS,
+            BlockTag::Planks => &PLANKS,
+            BlockTag::PolarBearsSpawnableOnAlternate => &POLAR_BEARS_SPAWNABLE_ON_ALTERNATE,
+            BlockTag::Portals => &PORTALS,
+            BlockTag::PressurePlates => &PRESSURE_PLATES,
+            BlockTag::PreventMobSpawningInside => &PREVENT_MOB_SPAWNING_INSIDE,
+            BlockTag::RabbitsSpawnableOn => &RABBITS_SPAWNABLE_ON,
+            BlockTag::Rails => &RAILS,
+            BlockTag::RedstoneOres => &REDSTONE_ORES,
+            BlockTag::Replaceable => &REPLACEABLE,
+            BlockTag::ReplaceableByTrees => &REPLACEABLE_BY_TREES,
+            BlockTag::Sand => &SAND,
+            BlockTag::Saplings => &SAPLINGS,
+            BlockTag::SculkReplaceable => &SCULK_REPLACEABLE,
+            BlockTag::SculkReplaceableWorldGen => &SCULK_REPLACEABLE_WORLD_GEN,
+            BlockTag::ShulkerBoxes => &SHULKER_BOXES,
+            BlockTag::Signs => &SIGNS,
+            BlockTag::Slabs => &SLABS,
+            BlockTag::SmallDripleafPlaceable => &SMALL_DRIPLEAF_PLACEABLE,
+            BlockTag::SmallFlowers => &SMALL_FLOWERS,
+            BlockTag::SmeltsToGlass => &SMELTS_TO_GLASS,
+            BlockTag::SnapsGoatHorn => &SNAPS_GOAT_HORN,
+            BlockTag::SnifferDiggableBlock => &SNIFFER_DIGGABLE_BLOCK,
+            BlockTag::SnifferEggHatchBoost => &SNIFFER_EGG_HATCH_BOOST,
+            BlockTag::Snow => &SNOW,
+            BlockTag::SnowLayerCanSurviveOn => &SNOW_LAYER_CAN_SURVIVE_ON,
+            BlockTag::SnowLayerCannotSurviveOn => &SNOW_LAYER_CANNOT_SURVIVE_ON,
+            BlockTag::SoulFireBaseBlocks => &SOUL_FIRE_BASE_BLOCKS,
+            BlockTag::SoulSpeedBlocks => &SOUL_SPEED_BLOCKS,
+            BlockTag::SpruceLogs => &SPRUCE_LOGS,
+            BlockTag::Stairs => &STAIRS,
+            BlockTag::StandingSigns => &STANDING_SIGNS,
+            BlockTag::StoneBricks => &STONE_BRICKS,
+            BlockTag::StoneButtons => &STONE_BUTTONS,
+            BlockTag::StoneOreReplaceables => &STONE_ORE_REPLACEABLES,
+            BlockTag::StonePressurePlates => &STONE_PRESSURE_PLATES,
+            BlockTag::StriderWarmBlocks => &STRIDER_WARM_BLOCKS,
+            BlockTag::SwordEfficient => &SWORD_EFFICIENT,
+            BlockTag::Terracotta => &TERRACOTTA,
+            BlockTag::TrailRuinsReplaceable => &TRAIL_RUINS_REPLACEABLE,
+            BlockTag::Trapdoors => &TRAPDOORS,
+            BlockTag::UnderwaterBonemeals => &UNDERWATER_BONEMEALS,
+            BlockTag::UnstableBottomCenter => &UNSTABLE_BOTTOM_CENTER,
+            BlockTag::ValidSpawn => &VALID_SPAWN,
+            BlockTag::VibrationResonators => &VIBRATION_RESONATORS,
+            BlockTag::WallCorals => &WALL_CORALS,
+            BlockTag::WallHangingSigns => &WALL_HANGING_SIGNS,
+            BlockTag::WallPostOverride => &WALL_POST_OVERRIDE,
+            BlockTag::WallSigns => &WALL_SIGNS,
+            BlockTag::Walls => &WALLS,
+            BlockTag::WarpedStems => &WARPED_STEMS,
+            BlockTag::WartBlocks => &WART_BLOCKS,
+            BlockTag::WitherImmune => &WITHER_IMMUNE,
+            BlockTag::WitherSummonBaseBlocks => &WITHER_SUMMON_BASE_BLOCKS,
+            BlockTag::WolvesSpawnableOn => &WOLVES_SPAWNABLE_ON,
+            BlockTag::WoodenButtons => &WOODEN_BUTTONS,
+            BlockTag::WoodenDoors => &WOODEN_DOORS,
+            BlockTag::WoodenFences => &WOODEN_FENCES,
+            BlockTag::WoodenPressurePlates => &WOODEN_PRESSURE_PLATES,
+            BlockTag::WoodenSlabs => &WOODEN_SLABS,
+            BlockTag::WoodenStairs => &WOODEN_STAIRS,
+            BlockTag::WoodenTrapdoors => &WOODEN_TRAPDOORS,
+            BlockTag::Wool => &WOOL,
+            BlockTag::WoolCarpets => &WOOL_CARPETS,
+        }
+    }
+}
+
+impl Block {
+    /// Whether this is in the given vanilla tag. Servers can change tags with
+    /// data packs, but this only knows about the vanilla ones.
+    pub fn is_in(self, tag: BlockTag) -> bool {
+        tag.values().contains(&self)
+    }
+}
@@ -8,3 +8,50 @@ pub static LAVA: LazyLock<HashSet<Fluid>> =
     LazyLock::new(|| HashSet::from_iter(vec![Fluid::Lava, Fluid::FlowingLava]));
 pub static WATER: LazyLock<HashSet<Fluid>> =
     LazyLock::new(|| HashSet::from_iter(vec![Fluid::Water, Fluid::FlowingWater]));
+
+/// A vanilla fluid tag, which can be checked with [`Fluid::is_in`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FluidTag {
+    Lava,
+    Water,
+}
+
+impl FluidTag {
+    /// Every vanilla fluid tag.
+    pub const ALL: &'static [FluidTag] = &[FluidTag::Lava, FluidTag::Water];
+
+    /// The name of the tag without the `minecraft:` namespace, like
+    /// `mineable/pickaxe`.
+    pub fn name(self) -> &'static str {
+        match self {
+            FluidTag::Lava => "lava",
+            FluidTag::Water => "water",
+        }
+    }
+
+    /// Get the tag from its name, which can start with `minecraft:`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {
+            "lava" => FluidTag::Lava,
+            "water" => FluidTag::Water,
+            _ => return None,
+        })
+    }
+
+    /// Everything that's in the tag.
+    pub fn values(self) -> &'static HashSet<Fluid> {
+        match self {
+            FluidTag::Lava => &LAVA,
+            FluidTag::Water => &WATER,
+        }
+    }
+}
+
+impl Fluid {
+    /// Whether this is in the given vanilla tag. Servers can change tags with
+    /// data packs, but this only knows about the vanilla ones.
+    pub fn is_in(self, tag: FluidTag) -> bool {
+        tag.values().contains(&self)
+    }
+}
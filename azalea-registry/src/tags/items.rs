@@ -2055,3 +2055,908 @@ pub static WOOL_CARPETS: LazyLock<HashSet<Item>> = LazyLock::new(|| {
         Item::BlackCarpet,
     ])
 });
+
+/// A vanilla item tag, which can be checked with [`Item::is_in`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemTag {
+    AcaciaLogs,
+    Anvil,
+    ArmadilloFood,
+    Arrows,
+    Axes,
+    AxolotlFood,
+    BambooBlocks,
+    Banners,
+    BeaconPaymentItems,
+    Beds,
+    BeeFood,
+    BirchLogs,
+    Boats,
+    BookshelfBooks,
+    BreaksDecoratedPots,
+    BrewingFuel,
+    Bundles,
+    Buttons,
+    CamelFood,
+    Candles,
+    CatFood,
+    CherryLogs,
+    ChestArmor,
+    ChestBoats,
+    ChickenFood,
+    ClusterMaxHarvestables,
+    CoalOres,
+    Coals,
+    Compasses,
+    CompletesFindTreeTutorial,
+    CopperOres,
+    CowFood,
+    CreeperDropMusicDiscs,
+    CreeperIgniters,
+    CrimsonStems,
+    DampensVibrations,
+    DarkOakLogs,
+    DecoratedPotIngredients,
+    DecoratedPotSherds,
+    DiamondOres,
+    DiamondToolMaterials,
+    Dirt,
+    Doors,
+    DrownedPreferredWeapons,
+    DuplicatesAllays,
+    Dyeable,
+    EmeraldOres,
+    EnchantableArmor,
+    EnchantableBow,
+    EnchantableChestArmor,
+    EnchantableCrossbow,
+    EnchantableDurability,
+    EnchantableEquippable,
+    EnchantableFireAspect,
+    EnchantableFishing,
+    EnchantableFootArmor,
+    EnchantableHeadArmor,
+    EnchantableLegArmor,
+    EnchantableMace,
+    EnchantableMining,
+    EnchantableMiningLoot,
+    EnchantableSharpWeapon,
+    EnchantableSword,
+    EnchantableTrident,
+    EnchantableVanishing,
+    EnchantableWeapon,
+    FenceGates,
+    Fences,
+    Fishes,
+    FootArmor,
+    FoxFood,
+    FreezeImmuneWearables,
+    FrogFood,
+    FurnaceMinecartFuel,
+    GazeDisguiseEquipment,
+    GoatFood,
+    GoldOres,
+    GoldToolMaterials,
+    HangingSigns,
+    HeadArmor,
+    Hoes,
+    HoglinFood,
+    HorseFood,
+    HorseTemptItems,
+    IgnoredByPiglinBabies,
+    IronOres,
+    IronToolMaterials,
+    JungleLogs,
+    LapisOres,
+    Leaves,
+    LecternBooks,
+    LegArmor,
+    LlamaFood,
+    LlamaTemptItems,
+    Logs,
+    LogsThatBurn,
+    MangroveLogs,
+    MapInvisibilityEquipment,
+    Meat,
+    NetheriteToolMaterials,
+    NonFlammableWood,
+    NoteblockTopInstruments,
+    OakLogs,
+    OcelotFood,
+    PaleOakLogs,
+    PandaEatsFromGround,
+    PandaFood,
+    ParrotFood,
+    ParrotPoisonousFood,
+    Pickaxes,
+    PigFood,
+    PiglinFood,
+    PiglinLoved,
+    PiglinPreferredWeapons,
+    PiglinRepellents,
+    PiglinSafeArmor,
+    PillagerPreferredWeapons,
+    Planks,
+    RabbitFood,
+    Rails,
+    RedstoneOres,
+    RepairsChainArmor,
+    RepairsDiamondArmor,
+    RepairsGoldArmor,
+    RepairsIronArmor,
+    RepairsLeatherArmor,
+    RepairsNetheriteArmor,
+    RepairsTurtleHelmet,
+    RepairsWolfArmor,
+    Sand,
+    Saplings,
+    SheepFood,
+    Shovels,
+    ShulkerBoxes,
+    Signs,
+    SkeletonPreferredWeapons,
+    Skulls,
+    Slabs,
+    SmallFlowers,
+    SmeltsToGlass,
+    SnifferFood,
+    SoulFireBaseBlocks,
+    SpruceLogs,
+    Stairs,
+    StoneBricks,
+    StoneButtons,
+    StoneCraftingMaterials,
+    StoneToolMaterials,
+    StriderFood,
+    StriderTemptItems,
+    Swords,
+    Terracotta,
+    Trapdoors,
+    TrimMaterials,
+    TrimmableArmor,
+    TurtleFood,
+    VillagerPicksUp,
+    VillagerPlantableSeeds,
+    Walls,
+    WarpedStems,
+    WartBlocks,
+    WitherSkeletonDislikedWeapons,
+    WolfFood,
+    WoodenButtons,
+    WoodenDoors,
+    WoodenFences,
+    WoodenPressurePlates,
+    WoodenSlabs,
+    WoodenStairs,
+    WoodenToolMaterials,
+    WoodenTrapdoors,
+    Wool,
+    WoolCarpets,
+}
+
+impl ItemTag {
+    /// Every vanilla item tag.
+    pub const ALL: &'static [ItemTag] = &[
+        ItemTag::AcaciaLogs,
+        ItemTag::Anvil,
+        ItemTag::ArmadilloFood,
+        ItemTag::Arrows,
+        ItemTag::Axes,
+        ItemTag::AxolotlFood,
+        ItemTag::BambooBlocks,
+        ItemTag::Banners,
+        ItemTag::BeaconPaymentItems,
+        ItemTag::Beds,
+        ItemTag::BeeFood,
+        ItemTag::BirchLogs,
+        ItemTag::Boats,
+        ItemTag::BookshelfBooks,
+        ItemTag::BreaksDecoratedPots,
+        ItemTag::BrewingFuel,
+        ItemTag::Bundles,
+        ItemTag::Buttons,
+        ItemTag::CamelFood,
+        ItemTag::Candles,
+        ItemTag::CatFood,
+        ItemTag::CherryLogs,
+        ItemTag::ChestArmor,
+        ItemTag::ChestBoats,
+        ItemTag::ChickenFood,
+        ItemTag::ClusterMaxHarvestables,
+        ItemTag::CoalOres,
+        ItemTag::Coals,
+        ItemTag::Compasses,
+        ItemTag::CompletesFindTreeTutorial,
+        ItemTag::CopperOres,
+        ItemTag::CowFood,
+        ItemTag::CreeperDropMusicDiscs,
+        ItemTag::CreeperIgniters,
+        ItemTag::CrimsonStems,
+        ItemTag::DampensVibrations,
+        ItemTag::DarkOakLogs,
+        ItemTag::DecoratedPotIngredients,
+        ItemTag::DecoratedPotSherds,
+        ItemTag::DiamondOres,
+        ItemTag::DiamondToolMaterials,
+        ItemTag::Dirt,
+        ItemTag::Doors,
+        ItemTag::DrownedPreferredWeapons,
+        ItemTag::DuplicatesAllays,
+        ItemTag::Dyeable,
+        ItemTag::EmeraldOres,
+        ItemTag::EnchantableArmor,
+        ItemTag::EnchantableBow,
+        ItemTag::EnchantableChestArmor,
+        ItemTag::EnchantableCrossbow,
+        ItemTag::EnchantableDurability,
+        ItemTag::EnchantableEquippable,
+        ItemTag::EnchantableFireAspect,
+        ItemTag::EnchantableFishing,
+        ItemTag::EnchantableFootArmor,
+        ItemTag::EnchantableHeadArmor,
+        ItemTag::EnchantableLegArmor,
+        ItemTag::EnchantableMace,
+        ItemTag::EnchantableMining,
+        ItemTag::EnchantableMiningLoot,
+        ItemTag::EnchantableSharpWeapon,
+        ItemTag::EnchantableSword,
+        ItemTag::EnchantableTrident,
+        ItemTag::EnchantableVanishing,
+        ItemTag::EnchantableWeapon,
+        ItemTag::FenceGates,
+        ItemTag::Fences,
+        ItemTag::Fishes,
+        ItemTag::FootArmor,
+        ItemTag::FoxFood,
+        ItemTag::FreezeImmuneWearables,
+        ItemTag::FrogFood,
+        ItemTag::FurnaceMinecartFuel,
+        ItemTag::GazeDisguiseEquipment,
+        ItemTag::GoatFood,
+        ItemTag::GoldOres,
+        ItemTag::GoldToolMaterials,
+        ItemTag::HangingSigns,
+        ItemTag::HeadArmor,
+        ItemTag::Hoes,
+        ItemTag::HoglinFood,
+        ItemTag::HorseFood,
+        ItemTag::HorseTemptItems,
+        ItemTag::IgnoredByPiglinBabies,
+        ItemTag::IronOres,
+        ItemTag::IronToolMaterials,
+        ItemTag::JungleLogs,
+        ItemTag::LapisOres,
+        ItemTag::Leaves,
+        ItemTag::LecternBooks,
+        ItemTag::LegArmor,
+        ItemTag::LlamaFood,
+        ItemTag::LlamaTemptItems,
+        ItemTag::Logs,
+        ItemTag::LogsThatBurn,
+        ItemTag::MangroveLogs,
+        ItemTag::MapInvisibilityEquipment,
+        ItemTag::Meat,
+        ItemTag::NetheriteToolMaterials,
+        ItemTag::NonFlammableWood,
+        ItemTag::NoteblockTopInstruments,
+        ItemTag::OakLogs,
+        ItemTag::OcelotFood,
+        ItemTag::PaleOakLogs,
+        ItemTag::PandaEatsFromGround,
+        ItemTag::PandaFood,
+        ItemTag::ParrotFood,
+        ItemTag::ParrotPoisonousFood,
+        ItemTag::Pickaxes,
+        ItemTag::PigFood,
+        ItemTag::PiglinFood,
+        ItemTag::PiglinLoved,
+        ItemTag::PiglinPreferredWeapons,
+        ItemTag::PiglinRepellents,
+        ItemTag::PiglinSafeArmor,
+        ItemTag::PillagerPreferredWeapons,
+        ItemTag::Planks,
+        ItemTag::RabbitFood,
+        ItemTag::Rails,
+        ItemTag::RedstoneOres,
+        ItemTag::RepairsChainArmor,
+        ItemTag::RepairsDiamondArmor,
+        ItemTag::RepairsGoldArmor,
+        ItemTag::RepairsIronArmor,
+        ItemTag::RepairsLeatherArmor,
+        ItemTag::RepairsNetheriteArmor,
+        ItemTag::RepairsTurtleHelmet,
+        ItemTag::RepairsWolfArmor,
+        ItemTag::Sand,
+        ItemTag::Saplings,
+        ItemTag::SheepFood,
+        ItemTag::Shovels,
+        ItemTag::ShulkerBoxes,
+        ItemTag::Signs,
+        ItemTag::SkeletonPreferredWeapons,
+        ItemTag::Skulls,
+        ItemTag::Slabs,
+        ItemTag::SmallFlowers,
+        ItemTag::SmeltsToGlass,
+        ItemTag::SnifferFood,
+        ItemTag::SoulFireBaseBlocks,
+        ItemTag::SpruceLogs,
+        ItemTag::Stairs,
+        ItemTag::StoneBricks,
+        ItemTag::StoneButtons,
+        ItemTag::StoneCraftingMaterials,
+        ItemTag::StoneToolMaterials,
+        ItemTag::StriderFood,
+        ItemTag::StriderTemptItems,
+        ItemTag::Swords,
+        ItemTag::Terracotta,
+        ItemTag::Trapdoors,
+        ItemTag::TrimMaterials,
+        ItemTag::TrimmableArmor,
+        ItemTag::TurtleFood,
+        ItemTag::VillagerPicksUp,
+        ItemTag::VillagerPlantableSeeds,
+        ItemTag::Walls,
+        ItemTag::WarpedStems,
+        ItemTag::WartBlocks,
+        ItemTag::WitherSkeletonDislikedWeapons,
+        ItemTag::WolfFood,
+        ItemTag::WoodenButtons,
+        ItemTag::WoodenDoors,
+        ItemTag::WoodenFences,
+        ItemTag::WoodenPressurePlates,
+        ItemTag::WoodenSlabs,
+        ItemTag::WoodenStairs,
+        ItemTag::WoodenToolMaterials,
+        ItemTag::WoodenTrapdoors,
+        ItemTag::Wool,
+        ItemTag::WoolCarpets,
+    ];
+
+    /// The name of the tag without the `minecraft:` namespace, like
+    /// `mineable/pickaxe`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemTag::AcaciaLogs => "acacia_logs",
+            ItemTag::Anvil => "anvil",
+            ItemTag::ArmadilloFood => "armadillo_food",
+            ItemTag::Arrows => "arrows",
+            ItemTag::Axes => "axes",
+            ItemTag::AxolotlFood => "axolotl_food",
+            ItemTag::BambooBlocks => "bamboo_blocks",
+            ItemTag::Banners => "banners",
+            ItemTag::BeaconPaymentItems => "beacon_payment_items",
+            ItemTag::Beds => "beds",
+            ItemTag::BeeFood => "bee_food",
+            ItemTag::BirchLogs => "birch_logs",
+            ItemTag::Boats => "boats",
+            ItemTag::BookshelfBooks => "bookshelf_books",
+            ItemTag::BreaksDecoratedPots => "breaks_decorated_pots",
+            ItemTag::BrewingFuel => "brewing_fuel",
+            ItemTag::Bundles => "bundles",
+            ItemTag::Buttons => "buttons",
+            ItemTag::CamelFood => "camel_food",
+            ItemTag::Candles => "candles",
+            ItemTag::CatFood => "cat_food",
+            ItemTag::CherryLogs => "cherry_logs",
+            ItemTag::ChestArmor => "chest_armor",
+            ItemTag::ChestBoats => "chest_boats",
+            ItemTag::ChickenFood => "chicken_food",
+            ItemTag::ClusterMaxHarvestables => "cluster_max_harvestables",
+            ItemTag::CoalOres => "coal_ores",
+            ItemTag::Coals => "coals",
+            ItemTag::Compasses => "compasses",
+            ItemTag::CompletesFindTreeTutorial => "completes_find_tree_tutorial",
+            ItemTag::CopperOres => "copper_ores",
+            ItemTag::CowFood => "cow_food",
+            ItemTag::CreeperDropMusicDiscs => "creeper_drop_music_discs",
+            ItemTag::CreeperIgniters => "creeper_igniters",
+            ItemTag::CrimsonStems => "crimson_stems",
+            ItemTag::DampensVibrations => "dampens_vibrations",
+            ItemTag::DarkOakLogs => "dark_oak_logs",
+            ItemTag::DecoratedPotIngredients => "decorated_pot_ingredients",
+            ItemTag::DecoratedPotSherds => "decorated_pot_sherds",
+            ItemTag::DiamondOres => "diamond_ores",
+            ItemTag::DiamondToolMaterials => "diamond_tool_materials",
+            ItemTag::Dirt => "dirt",
+            ItemTag::Doors => "doors",
+            ItemTag::DrownedPreferredWeapons => "drowned_preferred_weapons",
+            ItemTag::DuplicatesAllays => "duplicates_allays",
+            ItemTag::Dyeable => "dyeable",
+            ItemTag::EmeraldOres => "emerald_ores",
+            ItemTag::EnchantableArmor => "enchantable/armor",
+            ItemTag::EnchantableBow => "enchantable/bow",
+            ItemTag::EnchantableChestArmor => "enchantable/chest_armor",
+            ItemTag::EnchantableCrossbow => "enchantable/crossbow",
+            ItemTag::EnchantableDurability => "enchantable/durability",
+            ItemTag::EnchantableEquippable => "enchantable/equippable",
+            ItemTag::EnchantableFireAspect => "enchantable/fire_aspect",
+            ItemTag::EnchantableFishing => "enchantable/fishing",
+            ItemTag::EnchantableFootArmor => "enchantable/foot_armor",
+            ItemTag::EnchantableHeadArmor => "enchantable/head_armor",
+            ItemTag::EnchantableLegArmor => "enchantable/leg_armor",
+            ItemTag::EnchantableMace => "enchantable/mace",
+            ItemTag::EnchantableMining => "enchantable/mining",
+            ItemTag::EnchantableMiningLoot => "enchantable/mining_loot",
+            ItemTag::EnchantableSharpWeapon => "enchantable/sharp_weapon",
+            ItemTag::EnchantableSword => "enchantable/sword",
+            ItemTag::EnchantableTrident => "enchantable/trident",
+            ItemTag::EnchantableVanishing => "enchantable/vanishing",
+            ItemTag::EnchantableWeapon => "enchantable/weapon",
+            ItemTag::FenceGates => "fence_gates",
+            ItemTag::Fences => "fences",
+            ItemTag::Fishes => "fishes",
+            ItemTag::FootArmor => "foot_armor",
+            ItemTag::FoxFood => "fox_food",
+            ItemTag::FreezeImmuneWearables => "freeze_immune_wearables",
+            ItemTag::FrogFood => "frog_food",
+            ItemTag::FurnaceMinecartFuel => "furnace_minecart_fuel",
+            ItemTag::GazeDisguiseEquipment => "gaze_disguise_equipment",
+            ItemTag::GoatFood => "goat_food",
+            ItemTag::GoldOres => "gold_ores",
+            ItemTag::GoldToolMaterials => "gold_tool_materials",
+            ItemTag::HangingSigns => "hanging_signs",
+            ItemTag::HeadArmor => "head_armor",
+            ItemTag::Hoes => "hoes",
+            ItemTag::HoglinFood => "hoglin_food",
+            ItemTag::HorseFood => "horse_food",
+            ItemTag::HorseTemptItems => "horse_tempt_items",
+            ItemTag::IgnoredByPiglinBabies => "ignored_by_piglin_babies",
+            ItemTag::IronOres => "iron_ores",
+            ItemTag::IronToolMaterials => "iron_tool_materials",
+            ItemTag::JungleLogs => "jungle_logs",
+            ItemTag::LapisOres => "lapis_ores",
+            ItemTag::Leaves => "leaves",
+            ItemTag::LecternBooks => "lectern_books",
+            ItemTag::LegArmor => "leg_armor",
+            ItemTag::LlamaFood => "llama_food",
+            ItemTag::LlamaTemptItems => "llama_tempt_items",
+            ItemTag::Logs => "logs",
+            ItemTag::LogsThatBurn => "logs_that_burn",
+            ItemTag::MangroveLogs => "mangrove_logs",
+            ItemTag::MapInvisibilityEquipment => "map_invisibility_equipment",
+            ItemTag::Meat => "meat",
+            ItemTag::NetheriteToolMaterials => "netherite_tool_materials",
+            ItemTag::NonFlammableWood => "non_flammable_wood",
+            ItemTag::NoteblockTopInstruments => "noteblock_top_instruments",
+            ItemTag::OakLogs => "oak_logs",
+            ItemTag::OcelotFood => "ocelot_food",
+            ItemTag::PaleOakLogs => "pale_oak_logs",
+            ItemTag::PandaEatsFromGround => "panda_eats_from_ground",
+            ItemTag::PandaFood => "panda_food",
+            ItemTag::ParrotFood => "parrot_food",
+            ItemTag::ParrotPoisonousFood => "parrot_poisonous_food",
+            ItemTag::Pickaxes => "pickaxes",
+            ItemTag::PigFood => "pig_food",
+            ItemTag::PiglinFood => "piglin_food",
+            ItemTag::PiglinLoved => "piglin_loved",
+            ItemTag::PiglinPreferredWeapons => "piglin_preferred_weapons",
+            ItemTag::PiglinRepellents => "piglin_repellents",
+            ItemTag::PiglinSafeArmor => "piglin_safe_armor",
+            ItemTag::PillagerPreferredWeapons => "pillager_preferred_weapons",
+            ItemTag::Planks => "planks",
+            ItemTag::RabbitFood => "rabbit_food",
+            ItemTag::Rails => "rails",
+            ItemTag::RedstoneOres => "redstone_ores",
+            ItemTag::RepairsChainArmor => "repairs_chain_armor",
+            ItemTag::RepairsDiamondArmor => "repairs_diamond_armor",
+            ItemTag::RepairsGoldArmor => "repairs_gold_armor",
+            ItemTag::RepairsIronArmor => "repairs_iron_armor",
+            ItemTag::RepairsLeatherArmor => "repairs_leather_armor",
+            ItemTag::RepairsNetheriteArmor => "repairs_netherite_armor",
+            ItemTag::RepairsTurtleHelmet => "repairs_turtle_helmet",
+            ItemTag::RepairsWolfArmor => "repairs_wolf_armor",
+            ItemTag::Sand => "sand",
+            ItemTag::Saplings => "saplings",
+            ItemTag::SheepFood => "sheep_food",
+            ItemTag::Shovels => "shovels",
+            ItemTag::ShulkerBoxes => "shulker_boxes",
+            ItemTag::Signs => "signs",
+            ItemTag::SkeletonPreferredWeapons => "skeleton_preferred_weapons",
+            ItemTag::Skulls => "skulls",
+            ItemTag::Slabs => "slabs",
+            ItemTag::SmallFlowers => "small_flowers",
+            ItemTag::SmeltsToGlass => "smelts_to_glass",
+            ItemTag::SnifferFood => "sniffer_food",
+            ItemTag::SoulFireBaseBlocks => "soul_fire_base_blocks",
+            ItemTag::SpruceLogs => "spruce_logs",
+            ItemTag::Stairs => "stairs",
+            ItemTag::StoneBricks => "stone_bricks",
+            ItemTag::StoneButtons => "stone_buttons",
+            ItemTag::StoneCraftingMaterials => "stone_crafting_materials",
+            ItemTag::StoneToolMaterials => "stone_tool_materials",
+            ItemTag::StriderFood => "strider_food",
+            ItemTag::StriderTemptItems => "strider_tempt_items",
+            ItemTag::Swords => "swords",
+            ItemTag::Terracotta => "terracotta",
+            ItemTag::Trapdoors => "trapdoors",
+            ItemTag::TrimMaterials => "trim_materials",
+            ItemTag::TrimmableArmor => "trimmable_armor",
+            ItemTag::TurtleFood => "turtle_food",
+            ItemTag::VillagerPicksUp => "villager_picks_up",
+            ItemTag::VillagerPlantableSeeds => "villager_plantable_seeds",
+            ItemTag::Walls => "walls",
+            ItemTag::WarpedStems => "warped_stems",
+            ItemTag::WartBlocks => "wart_blocks",
+            ItemTag::WitherSkeletonDislikedWeapons => "wither_skeleton_disliked_weapons",
+            ItemTag::WolfFood => "wolf_food",
+            ItemTag::WoodenButtons => "wooden_buttons",
+            ItemTag::WoodenDoors => "wooden_doors",
+            ItemTag::WoodenFences => "wooden_fences",
+            ItemTag::WoodenPressurePlates => "wooden_pressure_plates",
+            ItemTag::WoodenSlabs => "wooden_slabs",
+            ItemTag::WoodenStairs => "wooden_stairs",
+            ItemTag::WoodenToolMaterials => "wooden_tool_materials",
+            ItemTag::WoodenTrapdoors => "wooden_trapdoors",
+            ItemTag::Wool => "wool",
+            ItemTag::WoolCarpets => "wool_carpets",
+        }
+    }
+
+    /// Get the tag from its name, which can start with `minecraft:`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {
+            "acacia_logs" => ItemTag::AcaciaLogs,
+            "anvil" => ItemTag::Anvil,
+            "armadillo_food" => ItemTag::ArmadilloFood,
+            "arrows" => ItemTag::Arrows,
+            "axes" => ItemTag::Axes,
+            "axolotl_food" => ItemTag::AxolotlFood,
+            "bamboo_blocks" => ItemTag::BambooBlocks,
+            "banners" => ItemTag::Banners,
+            "beacon_payment_items" => ItemTag::BeaconPaymentItems,
+            "beds" => ItemTag::Beds,
+            "bee_food" => ItemTag::BeeFood,
+            "birch_logs" => ItemTag::BirchLogs,
+            "boats" => ItemTag::Boats,
+            "bookshelf_books" => ItemTag::BookshelfBooks,
+            "breaks_decorated_pots" => ItemTag::BreaksDecoratedPots,
+            "brewing_fuel" => ItemTag::BrewingFuel,
+            "bundles" => ItemTag::Bundles,
+            "buttons" => ItemTag::Buttons,
+            "camel_food" => ItemTag::CamelFood,
+            "candles" => ItemTag::Candles,
+            "cat_food" => ItemTag::CatFood,
+            "cherry_logs" => ItemTag::CherryLogs,
+            "chest_armor" => ItemTag::ChestArmor,
+            "chest_boats" => ItemTag::ChestBoats,
+            "chicken_food" => ItemTag::ChickenFood,
+            "cluster_max_harvestables" => ItemTag::ClusterMaxHarvestables,
+            "coal_ores" => ItemTag::CoalOres,
+            "coals" => ItemTag::Coals,
+            "compasses" => ItemTag::Compasses,
+            "completes_find_tree_tutorial" => ItemTag::CompletesFindTreeTutorial,
+            "copper_ores" => ItemTag::CopperOres,
+            "cow_food" => ItemTag::CowFood,
+            "creeper_drop_music_discs" => ItemTag::CreeperDropMusicDiscs,
+            "creeper_igniters" => ItemTag::CreeperIgniters,
+            "crimson_stems" => ItemTag::CrimsonStems,
+            "dampens_vibrations" => ItemTag::DampensVibrations,
+            "dark_oak_logs" => ItemTag::DarkOakLogs,
+            "decorated_pot_ingredients" => ItemTag::DecoratedPotIngredients,
+            "decorated_pot_sherds" => ItemTag::DecoratedPotSherds,
+            "diamond_ores" => ItemTag::DiamondOres,
+            "diamond_tool_materials" => ItemTag::DiamondToolMaterials,
+            "dirt" => ItemTag::Dirt,
+            "doors" => ItemTag::Doors,
+            "drowned_preferred_weapons" => ItemTag::DrownedPreferredWeapons,
+            "duplicates_allays" => ItemTag::DuplicatesAllays,
+            "dyeable" => ItemTag::Dyeable,
+            "emerald_ores" => ItemTag::EmeraldOres,
+            "enchantable/armor" => ItemTag::EnchantableArmor,
+            "enchantable/bow" => ItemTag::EnchantableBow,
+            "enchantable/chest_armor" => ItemTag::EnchantableChestArmor,
+            "enchantable/crossbow" => ItemTag::EnchantableCrossbow,
+            "enchantable/durability" => ItemTag::EnchantableDurability,
+            "enchantable/equippable" => ItemTag::EnchantableEquippable,
+            "enchantable/fire_aspect" => ItemTag::EnchantableFireAspect,
+            "enchantable/fishing" => ItemTag::EnchantableFishing,
+            "enchantable/foot_armor" => ItemTag::EnchantableFootArmor,
+            "enchantable/head_armor" => ItemTag::EnchantableHeadArmor,
+            "enchantable/leg_armor" => ItemTag::EnchantableLegArmor,
+            "enchantable/mace" => ItemTag::EnchantableMace,
+            "enchantable/mining" => ItemTag::EnchantableMining,
+            "enchantable/mining_loot" => ItemTag::EnchantableMiningLoot,
+            "enchantable/sharp_weapon" => ItemTag::EnchantableSharpWeapon,
+            "enchantable/sword" => ItemTag::EnchantableSword,
+            "enchantable/trident" => ItemTag::EnchantableTrident,
+            "enchantable/vanishing" => ItemTag::EnchantableVanishing,
+            "enchantable/weapon" => ItemTag::EnchantableWeapon,
+            "fence_gates" => ItemTag::FenceGates,
+            "fences" => ItemTag::Fences,
+            "fishes" => ItemTag::Fishes,
+            "foot_armor" => ItemTag::FootArmor,
+            "fox_food" => ItemTag::FoxFood,
+            "freeze_immune_wearables" => ItemTag::FreezeImmuneWearables,
+            "frog_food" => ItemTag::FrogFood,
+            "furnace_minecart_fuel" => ItemTag::FurnaceMinecartFuel,
+            "gaze_disguise_equipment" => ItemTag::GazeDisguiseEquipment,
+            "goat_food" => ItemTag::GoatFood,
+            "gold_ores" => ItemTag::GoldOres,
+            "gold_tool_materials" => ItemTag::GoldToolMaterials,
+            "hanging_signs" => ItemTag::HangingSigns,
+            "head_armor" => ItemTag::HeadArmor,
+            "hoes" => ItemTag::Hoes,
+            "hoglin_food" => ItemTag::HoglinFood,
+            "horse_food" => ItemTag::HorseFood,
+            "horse_tempt_items" => ItemTag::HorseTemptItems,
+            "ignored_by_piglin_babies" => ItemTag::IgnoredByPiglinBabies,
+            "iron_ores" => ItemTag::IronOres,
+            "iron_tool_materials" => ItemTag::IronToolMaterials,
+            "jungle_logs" => ItemTag::JungleLogs,
+            "lapis_ores" => ItemTag::LapisOres,
+            "leaves" => ItemTag::Leaves,
+            "lectern_books" => ItemTag::LecternBooks,
+            "leg_armor" => ItemTag::LegArmor,
+            "llama_food" => ItemTag::LlamaFood,
+            "llama_tempt_items" => ItemTag::LlamaTemptItems,
+            "logs" => ItemTag::Logs,
+            "logs_that_burn" => ItemTag::LogsThatBurn,
+            "mangrove_logs" => ItemTag::MangroveLogs,
+            "map_invisibility_equipment" => ItemTag::MapInvisibilityEquipment,
+            "meat" => ItemTag::Meat,
+            "netherite_tool_materials" => ItemTag::NetheriteToolMaterials,
+            "non_flammable_wood" => ItemTag::NonFlammableWood,
+            "noteblock_top_instruments" => ItemTag::NoteblockTopInstruments,
+            "oak_logs" => ItemTag::OakLogs,
+            "ocelot_food" => ItemTag::OcelotFood,
+            "pale_oak_logs" => ItemTag::PaleOakLogs,
+            "panda_eats_from_ground" => ItemTag::PandaEatsFromGround,
+            "panda_food" => ItemTag::PandaFood,
+            "parrot_food" => ItemTag::ParrotFood,
+            "parrot_poisonous_food" => ItemTag::ParrotPoisonousFood,
+            "pickaxes" => ItemTag::Pickaxes,
+            "pig_food" => ItemTag::PigFood,
+            "piglin_food" => ItemTag::PiglinFood,
+            "piglin_loved" => ItemTag::PiglinLoved,
+            "piglin_preferred_weapons" => ItemTag::PiglinPreferredWeapons,
+            "piglin_repellents" => ItemTag::PiglinRepellents,
+            "piglin_safe_armor" => ItemTag::PiglinSafeArmor,
+            "pillager_preferred_weapons" => ItemTag::PillagerPreferredWeapons,
+            "planks" => ItemTag::Planks,
+            "rabbit_food" => ItemTag::RabbitFood,
+            "rails" => ItemTag::Rails,
+            "redstone_ores" => ItemTag::RedstoneOres,
+            "repairs_chain_armor" => ItemTag::RepairsChainArmor,
+            "repairs_diamond_armor" => ItemTag::RepairsDiamondArmor,
+            "repairs_gold_armor" => ItemTag::RepairsGoldArmor,
+            "repairs_iron_armor" => ItemTag::RepairsIronArmor,
+            "repairs_leather_armor" => ItemTag::RepairsLeatherArmor,
+            "repairs_netherite_armor" => ItemTag::RepairsNetheriteArmor,
+            "repairs_turtle_helmet" => ItemTag::RepairsTurtleHelmet,
+            "repairs_wolf_armor" => ItemTag::RepairsWolfArmor,
+            "sand" => ItemTag::Sand,
+            "saplings" => ItemTag::Saplings,
+            "sheep_food" => ItemTag::SheepFood,
+            "shovels" => ItemTag::Shovels,
+            "shulker_boxes" => ItemTag::ShulkerBoxes,
+            "signs" => ItemTag::Signs,
+            "skeleton_preferred_weapons" => ItemTag::SkeletonPreferredWeapons,
+            "skulls" => ItemTag::Skulls,
+            "slabs" => ItemTag::Slabs,
+            "small_flowers" => ItemTag::SmallFlowers,
+            "smelts_to_glass" => ItemTag::SmeltsToGlass,
+            "sniffer_food" => ItemTag::SnifferFood,
+            "soul_fire_base_blocks" => ItemTag::SoulFireBaseBlocks,
+            "spruce_logs" => ItemTag::SpruceLogs,
+            "stairs" => ItemTag::Stairs,
+            "stone_bricks" => ItemTag::StoneBricks,
+            "stone_buttons" => ItemTag::StoneButtons,
+            "stone_crafting_materials" => ItemTag::StoneCraftingMaterials,
+            "stone_tool_materials" => ItemTag::StoneToolMaterials,
+            "strider_food" => ItemTag::StriderFood,
+            "strider_tempt_items" => ItemTag::StriderTemptItems,
+            "swords" => ItemTag::Swords,
+            "terracotta" => ItemTag::Terracotta,
+            "trapdoors" => ItemTag::Trapdoors,
+            "trim_materials" => ItemTag::TrimMaterials,
+            "trimmable_armor" => ItemTag::TrimmableArmor,
+            "turtle_food" => ItemTag::TurtleFood,
+            "villager_picks_up" => ItemTag::VillagerPicksUp,
+            "villager_plantable_seeds" => ItemTag::VillagerPlantableSeeds,
+            "walls" => ItemTag::Walls,
+            "warped_stems" => ItemTag::WarpedStems,
+            "wart_blocks" => ItemTag::WartBlocks,
+            "wither_skeleton_disliked_weapons" => ItemTag::WitherSkeletonDislikedWeapons,
+            "wolf_food" => ItemTag::WolfFood,
+            "wooden_buttons" => ItemTag::WoodenButtons,
+            "wooden_doors" => ItemTag::WoodenDoors,
+            "wooden_fences" => ItemTag::WoodenFences,
+            "wooden_pressure_plates" => ItemTag::WoodenPressurePlates,
+            "wooden_slabs" => ItemTag::WoodenSlabs,
+            "wooden_stairs" => ItemTag::WoodenStairs,
+            "wooden_tool_materials" => ItemTag::WoodenToolMaterials,
+            "wooden_trapdoors" => ItemTag::WoodenTrapdoors,
+            "wool" => ItemTag::Wool,
+            "wool_carpets" => ItemTag::WoolCarpets,
+            _ => return None,
+        })
+    }
+
+    /// Everything that's in the tag.
+    pub fn values(self) -> &'static HashSet<Item> {
+        match self {
+            ItemTag::AcaciaLogs => &ACACIA_LOGS,
+            ItemTag::Anvil => &ANVIL,
+            ItemTag::ArmadilloFood => &ARMADILLO_FOOD,
+            ItemTag::Arrows => &ARROWS,
+            ItemTag::Axes => &AXES,
+            ItemTag::AxolotlFood => &AXOLOTL_FOOD,
+            ItemTag::BambooBlocks => &BAMBOO_BLOCKS,
+            ItemTag::Banners => &BANNERS,
+            ItemTag::BeaconPaymentItems => &BEACON_PAYMENT_ITEMS,
+            ItemTag::Beds => &BEDS,
+            ItemTag::BeeFood => &BEE_FOOD,
+            ItemTag::BirchLogs => &BIRCH_LOGS,
+            ItemTag::Boats => &BOATS,
+            ItemTag::BookshelfBooks => &BOOKSHELF_BOOKS,
+            ItemTag::BreaksDecoratedPots => &BREAKS_DECORATED_POTS,
+            ItemTag::BrewingFuel => &BREWING_FUEL,
+            ItemTag::Bundles => &BUNDLES,
+            ItemTag::Buttons => &BUTTONS,
+            ItemTag::CamelFood => &CAMEL_FOOD,
+            ItemTag::Candles => &CANDLES,
+            ItemTag::CatFood => &CAT_FOOD,
+            ItemTag::CherryLogs => &CHERRY_LOGS,
+            ItemTag::ChestArmor => &CHEST_ARMOR,
+            ItemTag::ChestBoats => &CHEST_BOATS,
+            ItemTag::ChickenFood => &CHICKEN_FOOD,
+            ItemTag::ClusterMaxHarvestables => &CLUSTER_MAX_HARVESTABLES,
+            ItemTag::CoalOres => &COAL_ORES,
+            ItemTag::Coals => &COALS,
+            ItemTag::Compasses => &COMPASSES,
+            ItemTag::CompletesFindTreeTutorial => &COMPLETES_FIND_TREE_TUTORIAL,
+            ItemTag::CopperOres => &COPPER_ORES,
+            ItemTag::CowFood => &COW_FOOD,
+            ItemTag::CreeperDropMusicDiscs => &CREEPER_DROP_MUSIC_DISCS,
+            ItemTag::CreeperIgniters => &CREEPER_IGNITERS,
+            ItemTag::CrimsonStems => &CRIMSON_STEMS,
+            ItemTag::DampensVibrations => &DAMPENS_VIBRATIONS,
+            ItemTag::DarkOakLogs => &DARK_OAK_LOGS,
+            ItemTag::DecoratedPotIngredients => &DECORATED_POT_INGREDIENTS,
+            ItemTag::DecoratedPotSherds => &DECORATED_POT_SHERDS,
+            ItemTag::DiamondOres => &DIAMOND_ORES,
+            ItemTag::DiamondToolMaterials => &DIAMOND_TOOL_MATERIALS,
+            ItemTag::Dirt => &DIRT,
+            ItemTag::Doors => &DOORS,
+            ItemTag::DrownedPreferredWeapons => &DROWNED_PREFERRED_WEAPONS,
+            ItemTag::DuplicatesAllays => &DUPLICATES_ALLAYS,
+            ItemTag::Dyeable => &DYEABLE,
+            ItemTag::EmeraldOres => &EMERALD_ORES,
+            ItemTag::EnchantableArmor => &ENCHANTABLE_ARMOR,
+            ItemTag::EnchantableBow => &ENCHANTABLE_BOW,
+            ItemTag::EnchantableChestArmor => &ENCHANTABLE_CHEST_ARMOR,
+            ItemTag::EnchantableCrossbow => &ENCHANTABLE_CROSSBOW,
+            ItemTag::EnchantableDurability => &ENCHANTABLE_DURABILITY,
+            ItemTag::EnchantableEquippable => &ENCHANTABLE_EQUIPPABLE,
+            ItemTag::EnchantableFireAspect => &ENCHANTABLE_FIRE_ASPECT,
+            ItemTag::EnchantableFishing => &ENCHANTABLE_FISHING,
+            ItemTag::EnchantableFootArmor => &ENCHANTABLE_FOOT_ARMOR,
+            ItemTag::EnchantableHeadArmor => &ENCHANTABLE_HEAD_ARMOR,
+            ItemTag::EnchantableLegArmor => &ENCHANTABLE_LEG_ARMOR,
+            ItemTag::EnchantableMace => &ENCHANTABLE_MACE,
+            ItemTag::EnchantableMining => &ENCHANTABLE_MINING,
+            ItemTag::EnchantableMiningLoot => &ENCHANTABLE_MINING_LOOT,
+            ItemTag::EnchantableSharpWeapon => &ENCHANTABLE_SHARP_WEAPON,
+            ItemTag::EnchantableSword => &ENCHANTABLE_SWORD,
+            ItemTag::EnchantableTrident => &ENCHANTABLE_TRIDENT,
+            ItemTag::EnchantableVanishing => &ENCHANTABLE_VANISHING,
+            ItemTag::EnchantableWeapon => &ENCHANTABLE_WEAPON,
+            ItemTag::FenceGates => &FENCE_GATES,
+            ItemTag::Fences => &FENCES,
+            ItemTag::Fishes => &FISHES,
+            ItemTag::FootArmor => &FOOT_ARMOR,
+            ItemTag::FoxFood => &FOX_FOOD,
+            ItemTag::FreezeImmuneWearables => &FREEZE_IMMUNE_WEARABLES,
+            ItemTag::FrogFood => &FROG_FOOD,
+            ItemTag::FurnaceMinecartFuel => &FURNACE_MINECART_FUEL,
+            ItemTag::GazeDisguiseEquipment => &GAZE_DISGUISE_EQUIPMENT,
+            ItemTag::GoatFood => &GOAT_FOOD,
+            ItemTag::GoldOres => &GOLD_ORES,
+            ItemTag::GoldToolMaterials => &GOLD_TOOL_MATERIALS,
+            ItemTag::HangingSigns => &HANGING_SIGNS,
+            ItemTag::HeadArmor => &HEAD_ARMOR,
+            ItemTag::Hoes => &HOES,
+            ItemTag::HoglinFood => &HOGLIN_FOOD,
+            ItemTag::HorseFood => &HORSE_FOOD,
+            ItemTag::HorseTemptItems => &HORSE_TEMPT_ITEMS,
+            ItemTag::IgnoredByPiglinBabies => &IGNORED_BY_PIGLIN_BABIES,
+            ItemTag::IronOres => &IRON_ORES,
+            ItemTag::IronToolMaterials => &IRON_TOOL_MATERIALS,
+            ItemTag::JungleLogs => &JUNGLE_LOGS,
+            ItemTag::LapisOres => &LAPIS_ORES,
+            ItemTag::Leaves => &LEAVES,
+            ItemTag::LecternBooks => &LECTERN_BOOKS,
+            ItemTag::LegArmor => &LEG_ARMOR,
+            ItemTag::LlamaFood => &LLAMA_FOOD,
+            ItemTag::LlamaTemptItems => &LLAMA_TEMPT_ITEMS,
+            ItemTag::Logs => &LOGS,
+            ItemTag::LogsThatBurn => &LOGS_THAT_BURN,
+            ItemTag::MangroveLogs => &MANGROVE_LOGS,
+            ItemTag::MapInvisibilityEquipment => &MAP_INVISIBILITY_EQUIPMENT,
+            ItemTag::Meat => &MEAT,
+            ItemTag::NetheriteToolMaterials => &NETHERITE_TOOL_MATERIALS,
+            ItemTag::NonFlammableWood => &NON_FLAMMABLE_WOOD,
+            ItemTag::NoteblockTopInstruments => &NOTEBLOCK_TOP_INSTRUMENTS,
+            ItemTag::OakLogs => &OAK_LOGS,
+            ItemTag::OcelotFood => &OCELOT_FOOD,
+            ItemTag::PaleOakLogs => &PALE_OAK_LOGS,
+            ItemTag::PandaEatsFromGround => &PANDA_EATS_FROM_GROUND,
+            ItemTag::PandaFood => &PANDA_FOOD,
+            ItemTag::ParrotFood => &PARROT_FOOD,
+            ItemTag::ParrotPoisonousFood => &PARROT_POISONOUS_FOOD,
+            ItemTag::Pickaxes => &PICKAXES,
+            ItemTag::PigFood => &PIG_FOOD,
+            ItemTag::PiglinFood => &PIGLIN_FOOD,
+            ItemTag::PiglinLoved => &PIGLIN_LOVED,
+            ItemTag::PiglinPreferredWeapons => &PIGLIN_PREFERRED_WEAPONS,
+            ItemTag::PiglinRepellents => &PIGLIN_REPELLENTS,
+            ItemTag::PiglinSafeArmor => &PIGLIN_SAFE_ARMOR,
+            ItemTag::PillagerPreferredWeapons => &PILLAGER_PREFERRED_WEAPONS,
+            ItemTag::Planks => &PLANKS,
+            ItemTag::RabbitFood => &RABBIT_FOOD,
+            ItemTag::Rails => &RAILS,
+            ItemTag::RedstoneOres => &REDSTONE_ORES,
+            ItemTag::RepairsChainArmor => &REPAIRS_CHAIN_ARMOR,
+            ItemTag::RepairsDiamondArmor => &REPAIRS_DIAMOND_ARMOR,
+            ItemTag::RepairsGoldArmor => &REPAIRS_GOLD_ARMOR,
+            ItemTag::RepairsIronArmor => &REPAIRS_IRON_ARMOR,
+            ItemTag::RepairsLeatherArmor => &REPAIRS_LEATHER_ARMOR,
+            ItemTag::RepairsNetheriteArmor => &REPAIRS_NETHERITE_ARMOR,
+            ItemTag::RepairsTurtleHelmet => &REPAIRS_TURTLE_HELMET,
+            ItemTag::RepairsWolfArmor => &REPAIRS_WOLF_ARMOR,
+            ItemTag::Sand => &SAND,
+            ItemTag::Saplings => &SAPLINGS,
+            ItemTag::SheepFood => &SHEEP_FOOD,
+            ItemTag::Shovels => &SHOVELS,
+            ItemTag::ShulkerBoxes => &SHULKER_BOXES,
+            ItemTag::Signs => &SIGNS,
+            ItemTag::SkeletonPreferredWeapons => &SKELETON_PREFERRED_WEAPONS,
+            ItemTag::Skulls => &SKULLS,
+            ItemTag::Slabs => &SLABS,
+            ItemTag::SmallFlowers => &SMALL_FLOWERS,
+            ItemTag::SmeltsToGlass => &SMELTS_TO_GLASS,
+            ItemTag::SnifferFood => &SNIFFER_FOOD,
+            ItemTag::SoulFireBaseBlocks => &SOUL_FIRE_BASE_BLOCKS,
+            ItemTag::SpruceLogs => &SPRUCE_LOGS,
+            ItemTag::Stairs => &STAIRS,
+            ItemTag::StoneBricks => &STONE_BRICKS,
+            ItemTag::StoneButtons => &STONE_BUTTONS,
+            ItemTag::StoneCraftingMaterials => &STONE_CRAFTING_MATERIALS,
+            ItemTag::StoneToolMaterials => &STONE_TOOL_MATERIALS,
+            ItemTag::StriderFood => &STRIDER_FOOD,
+            ItemTag::StriderTemptItems => &STRIDER_TEMPT_ITEMS,
+            ItemTag::Swords => &SWORDS,
+            ItemTag::Terracotta => &TERRACOTTA,
+            ItemTag::Trapdoors => &TRAPDOORS,
+            ItemTag::TrimMaterials => &TRIM_MATERIALS,
+            ItemTag::TrimmableArmor => &TRIMMABLE_ARMOR,
+            ItemTag::TurtleFood => &TURTLE_FOOD,
+            ItemTag::VillagerPicksUp => &VILLAGER_PICKS_UP,
+            ItemTag::VillagerPlantableSeeds => &VILLAGER_PLANTABLE_SEEDS,
+            ItemTag::Walls => &WALLS,
+            ItemTag::WarpedStems => &WARPED_STEMS,
+            ItemTag::WartBlocks => &WART_BLOCKS,
+            ItemTag::WitherSkeletonDislikedWeapons => &WITHER_SKELETON_DISLIKED_WEAPONS,
+            ItemTag::WolfFood => &WOLF_FOOD,
+            ItemTag::WoodenButtons => &WOODEN_BUTTONS,
+            ItemTag::WoodenDoors => &WOODEN_DOORS,
+            ItemTag::WoodenFences => &WOODEN_FENCES,
+            ItemTag::WoodenPressurePlates => &WOODEN_PRESSURE_PLATES,
+            ItemTag::WoodenSlabs => &WOODEN_SLABS,
+            ItemTag::WoodenStairs => &WOODEN_STAIRS,
+            ItemTag::WoodenToolMaterials => &WOODEN_TOOL_MATERIALS,
+            ItemTag::WoodenTrapdoors => &WOODEN_TRAPDOORS,
+            ItemTag::Wool => &WOOL,
+            ItemTag::WoolCarpets => &WOOL_CARPETS,
+        }
+    }
+}
+
+impl Item {
+    /// Whether this is in the given vanilla tag. Servers can change tags with
+    /// data packs, but this only knows about the vanilla ones.
+    pub fn is_in(self, tag: ItemTag) -> bool {
+        tag.values().contains(&self)
+    }
+}
@@ -1,3 +1,25 @@
+//! The vanilla block, item, and fluid tags.
+//!
+//! Every tag is a `static` set, and there's also an enum for each kind of tag
+//! so they can be checked with methods like [`Block::is_in`].
+//!
+//! ```
+//! use azalea_registry::{tags::BlockTag, Block};
+//!
+//! assert!(Block::Stone.is_in(BlockTag::MineablePickaxe));
+//! assert!(!Block::Dirt.is_in(BlockTag::MineablePickaxe));
+//! assert_eq!(
+//!     BlockTag::from_name("minecraft:mineable/pickaxe"),
+//!     Some(BlockTag::MineablePickaxe)
+//! );
+//! ```
+//!
+//! [`Block::is_in`]: crate::Block::is_in
+
 pub mod blocks;
 pub mod fluids;
 pub mod items;
+
+pub use blocks::BlockTag;
+pub use fluids::FluidTag;
+pub use items::ItemTag;
@@ -1,8 +1,46 @@
-use std::hint::black_box;
+use std::{hint::black_box, io::Cursor};
 
+use azalea_block::block_state::BlockStateIntegerRepr;
+use azalea_buf::AzaleaWrite;
 use azalea_core::position::ChunkBlockPos;
-use azalea_world::{BitStorage, Chunk};
-use criterion::{criterion_group, criterion_main, Criterion};
+use azalea_world::{
+    palette::{PalettedContainer, PalettedContainerKind},
+    BitStorage, Chunk,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use simdnbt::owned::NbtCompound;
+
+/// A chunk with stone at the bottom, a layer of dirt and grass, and some ores
+/// mixed in, so its sections use a few different kinds of palettes.
+fn generate_chunk() -> Chunk {
+    let mut chunk = Chunk::default();
+    let ores = [
+        azalea_registry::Block::CoalOre,
+        azalea_registry::Block::IronOre,
+        azalea_registry::Block::GoldOre,
+        azalea_registry::Block::DiamondOre,
+    ];
+    for x in 0..16_u8 {
+        for z in 0..16_u8 {
+            let height = 60 + ((x as i32 * 7 + z as i32 * 3) % 8);
+            for y in -64..height {
+                let block = if y == -64 {
+                    azalea_registry::Block::Bedrock
+                } else if y == height - 1 {
+                    azalea_registry::Block::GrassBlock
+                } else if y > height - 4 {
+                    azalea_registry::Block::Dirt
+                } else if (x as i32 * 31 + y * 17 + z as i32 * 13) % 23 == 0 {
+                    ores[(y as usize + x as usize) % ores.len()]
+                } else {
+                    azalea_registry::Block::Stone
+                };
+                chunk.set(&ChunkBlockPos::new(x, y, z), block.into(), -64);
+            }
+        }
+    }
+    chunk
+}
 
 fn bench_chunks(c: &mut Criterion) {
     c.bench_function("Chunk::set", |b| {
@@ -34,5 +72,118 @@ fn bench_bitstorage(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_chunks, bench_bitstorage);
+fn bench_chunk_decode(c: &mut Criterion) {
+    let mut data = Vec::new();
+    generate_chunk().azalea_write(&mut data).unwrap();
+    let heightmaps = NbtCompound::new();
+
+    c.bench_function("Chunk::read_with_dimension_height", |b| {
+        b.iter(|| {
+            let chunk = Chunk::read_with_dimension_height(
+                &mut Cursor::new(black_box(&data)),
+                384,
+                -64,
+                &heightmaps,
+            )
+            .unwrap();
+            black_box(chunk);
+        });
+    });
+    c.bench_function("Chunk::azalea_write", |b| {
+        let chunk = generate_chunk();
+        b.iter(|| {
+            let mut data = Vec::new();
+            chunk.azalea_write(&mut data).unwrap();
+            black_box(data);
+        });
+    });
+}
+
+fn bench_palette_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PalettedContainer::set");
+    // these are picked so the palette ends up as each of the palette kinds
+    for unique_states in [1, 16, 200, 4096] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(unique_states),
+            &unique_states,
+            |b, &unique_states| {
+                b.iter(|| {
+                    let mut container = PalettedContainer::new(PalettedContainerKind::BlockStates);
+                    for i in 0..4096 {
+                        let state = (i % unique_states + 1) as BlockStateIntegerRepr;
+                        container.set_at_index(i, state);
+                    }
+                    black_box(container);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_palette_reads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PalettedContainer::get");
+    for unique_states in [1, 16, 200, 4096] {
+        let mut container = PalettedContainer::new(PalettedContainerKind::BlockStates);
+        let values = (0..4096)
+            .map(|i| (i % unique_states + 1) as BlockStateIntegerRepr)
+            .collect::<Vec<_>>();
+        container.set_all(&values);
+
+        group.bench_with_input(
+            BenchmarkId::new("get_at_index", unique_states),
+            &container,
+            |b, container| {
+                b.iter(|| {
+                    for i in 0..4096 {
+                        black_box(container.get_at_index(i));
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get_all", unique_states),
+            &container,
+            |b, container| {
+                let mut out = vec![0; 4096];
+                b.iter(|| {
+                    container.get_all(&mut out);
+                    black_box(&out);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_palette_set_all(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PalettedContainer::set_all");
+    for unique_states in [1, 16, 200, 4096] {
+        let values = (0..4096)
+            .map(|i| (i % unique_states + 1) as BlockStateIntegerRepr)
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(unique_states),
+            &values,
+            |b, values| {
+                b.iter(|| {
+                    let mut container = PalettedContainer::new(PalettedContainerKind::BlockStates);
+                    container.set_all(values);
+                    black_box(container);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_chunks,
+    bench_bitstorage,
+    bench_chunk_decode,
+    bench_palette_writes,
+    bench_palette_reads,
+    bench_palette_set_all
+);
 criterion_main!(benches);
@@ -0,0 +1,994 @@
+//! Load and save chunks in the region (`.mca`) files that vanilla saves worlds
+//! in.
+//!
+//! Loading is useful for running the pathfinder or physics against real
+//! worlds without connecting to a server, and saving makes it possible to
+//! download the worlds that bots see.
+//!
+//! ```no_run
+//! # use azalea_world::{anvil, ChunkStorage, Instance};
+//! # fn example() -> Result<(), anvil::AnvilError> {
+//! let mut instance = Instance::from(ChunkStorage::default());
+//! // the chunks are unloaded when this is dropped
+//! let loaded_chunks = anvil::load_region_dir("world/region", &mut instance)?;
+//! println!("loaded {} chunks", loaded_chunks.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Use [`write_chunk`] and [`write_region_dir`] to save chunks.
+//!
+//! Only worlds from 1.18 and later are supported, and chunks that are
+//! compressed with LZ4 can't be read.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Cursor, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use azalea_block::{block_state::BlockStateIntegerRepr, BlockState};
+use azalea_core::{
+    position::ChunkPos, registry_holder::RegistryHolder, resource_location::ResourceLocation,
+};
+use derive_more::{Deref, DerefMut};
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::ZlibEncoder,
+    Compression,
+};
+use nohash_hasher::IntMap;
+use parking_lot::RwLock;
+use simdnbt::owned::{BaseNbt, Nbt, NbtCompound, NbtList, NbtTag};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{
+    chunk_storage::read_heightmaps,
+    palette::{Palette, PaletteKind, PalettedContainer, PalettedContainerKind},
+    BitStorage, Chunk, Instance,
+};
+
+/// The size of the region file header, which has the locations of the
+/// chunks and then their timestamps.
+pub const HEADER_SIZE: usize = SECTOR_SIZE * 2;
+/// Region files are split into sectors of this many bytes.
+pub const SECTOR_SIZE: usize = 4096;
+/// The number of chunks on each side of a region.
+pub const REGION_WIDTH: i32 = 32;
+
+/// The bit that's set in the compression type if the chunk is too big to fit
+/// in the region file, and is in a separate `.mcc` file instead.
+const EXTERNAL_CHUNK_FLAG: u8 = 0x80;
+
+#[derive(Error, Debug)]
+pub enum AnvilError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid NBT: {0}")]
+    Nbt(#[from] simdnbt::Error),
+    #[error("Invalid region file: {0}")]
+    InvalidRegion(String),
+    #[error("Unsupported compression type {0}")]
+    UnsupportedCompression(u8),
+    #[error("Invalid chunk: {0}")]
+    InvalidChunk(String),
+    #[error("Chunks from before 1.18 aren't supported")]
+    UnsupportedVersion,
+}
+
+/// A region file, which stores a 32x32 area of chunks.
+pub struct RegionFile {
+    data: Vec<u8>,
+    /// Where the region file was read from, which is used for finding chunks
+    /// that are stored in separate files.
+    path: Option<PathBuf>,
+}
+
+impl RegionFile {
+    /// Read the region file at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AnvilError> {
+        let path = path.as_ref();
+        let mut region = Self::from_bytes(fs::read(path)?)?;
+        region.path = Some(path.to_owned());
+        Ok(region)
+    }
+
+    /// Read a region file from memory. Chunks that are stored in separate
+    /// files can't be read from regions that were made with this.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, AnvilError> {
+        // vanilla sometimes leaves empty region files around
+        if !data.is_empty() && data.len() < HEADER_SIZE {
+            return Err(AnvilError::InvalidRegion(format!(
+                "The file is only {} bytes, which isn't enough for the header",
+                data.len()
+            )));
+        }
+        Ok(Self { data, path: None })
+    }
+
+    /// Get where a chunk starts in the file, or `None` if the chunk hasn't
+    /// been saved.
+    fn location(&self, index: usize) -> Option<usize> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let entry = &self.data[index * 4..index * 4 + 4];
+        let sector_offset = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]) as usize;
+        let sector_count = entry[3] as usize;
+        if sector_offset == 0 || sector_count == 0 {
+            return None;
+        }
+        Some(sector_offset * SECTOR_SIZE)
+    }
+
+    /// Whether the chunk was saved in this region. Only the lowest 5 bits of
+    /// the coordinates are used.
+    pub fn contains(&self, pos: &ChunkPos) -> bool {
+        self.location(region_index(pos)).is_some()
+    }
+
+    /// Get the length, compression type, and compressed data of a chunk, as
+    /// it's stored in the file.
+    fn raw_chunk(&self, index: usize) -> Option<&[u8]> {
+        let offset = self.location(index)?;
+        let header = self.data.get(offset..offset + 4)?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        self.data.get(offset..offset + 4 + length)
+    }
+
+    /// Get the time that a chunk was last saved at, in seconds since the Unix
+    /// epoch.
+    fn timestamp(&self, index: usize) -> u32 {
+        if self.data.is_empty() {
+            return 0;
+        }
+        let offset = SECTOR_SIZE + index * 4;
+        let entry = &self.data[offset..offset + 4];
+        u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]])
+    }
+
+    /// Read and decompress the NBT of a chunk, or return `None` if the chunk
+    /// hasn't been saved.
+    pub fn read_chunk_nbt(&self, pos: &ChunkPos) -> Result<Option<BaseNbt>, AnvilError> {
+        let Some(offset) = self.location(region_index(pos)) else {
+            return Ok(None);
+        };
+        let Some(header) = self.data.get(offset..offset + 5) else {
+            return Err(AnvilError::InvalidRegion(format!(
+                "Chunk {pos:?} starts after the end of the file"
+            )));
+        };
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let compression = header[4];
+
+        let external_data;
+        let compressed = if compression & EXTERNAL_CHUNK_FLAG != 0 {
+            let Some(region_path) = &self.path else {
+                return Err(AnvilError::InvalidRegion(format!(
+                    "Chunk {pos:?} is in a separate file, but we don't know where the region is"
+                )));
+            };
+            external_data =
+                fs::read(region_path.with_file_name(format!("c.{}.{}.mcc", pos.x, pos.z)))?;
+            &external_data[..]
+        } else {
+            // the length includes the compression type
+            let Some(compressed) = length
+                .checked_sub(1)
+                .and_then(|length| self.data.get(offset + 5..offset + 5 + length))
+            else {
+                return Err(AnvilError::InvalidRegion(format!(
+                    "Chunk {pos:?} has an invalid length of {length}"
+                )));
+            };
+            compressed
+        };
+
+        let mut data = Vec::new();
+        match compression & !EXTERNAL_CHUNK_FLAG {
+            1 => {
+                GzDecoder::new(compressed).read_to_end(&mut data)?;
+            }
+            2 => {
+                ZlibDecoder::new(compressed).read_to_end(&mut data)?;
+            }
+            3 => data.extend_from_slice(compressed),
+            compression => return Err(AnvilError::UnsupportedCompression(compression)),
+        }
+
+        match simdnbt::owned::read(&mut Cursor::new(data.as_slice()))? {
+            Nbt::Some(nbt) => Ok(Some(nbt)),
+            Nbt::None => Err(AnvilError::InvalidChunk(format!(
+                "Chunk {pos:?} doesn't have any NBT"
+            ))),
+        }
+    }
+
+    /// Read a chunk from the region, or return `None` if it hasn't been saved.
+    ///
+    /// See [`read_chunk`] for what the other arguments are for.
+    pub fn read_chunk(
+        &self,
+        pos: &ChunkPos,
+        dimension_height: u32,
+        min_y: i32,
+        registries: &RegistryHolder,
+    ) -> Result<Option<Chunk>, AnvilError> {
+        let Some(nbt) = self.read_chunk_nbt(pos)? else {
+            return Ok(None);
+        };
+        read_chunk(&nbt, dimension_height, min_y, registries).map(Some)
+    }
+}
+
+/// Get the index of the chunk in the region header.
+fn region_index(pos: &ChunkPos) -> usize {
+    (pos.x.rem_euclid(REGION_WIDTH) + pos.z.rem_euclid(REGION_WIDTH) * REGION_WIDTH) as usize
+}
+
+/// Get the coordinates of a region from the name of its file, like
+/// `r.-1.2.mca`.
+pub fn region_pos_from_path(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((x, z))
+}
+
+/// Get the directory that a dimension's region files are in, relative to the
+/// world's directory.
+pub fn region_dir(world_dir: impl AsRef<Path>, dimension: &ResourceLocation) -> PathBuf {
+    let world_dir = world_dir.as_ref();
+    match dimension.to_string().as_str() {
+        "minecraft:overworld" => world_dir.join("region"),
+        "minecraft:the_nether" => world_dir.join("DIM-1").join("region"),
+        "minecraft:the_end" => world_dir.join("DIM1").join("region"),
+        _ => world_dir
+            .join("dimensions")
+            .join(&dimension.namespace)
+            .join(&dimension.path)
+            .join("region"),
+    }
+}
+
+/// Convert the NBT of a chunk from a region file into a [`Chunk`].
+///
+/// The dimension height and minimum y must be the same as the
+/// [`ChunkStorage`](crate::ChunkStorage) that it'll be added to. The
+/// registries are used for getting the IDs of biomes, and biomes are all set to
+/// the first one if the biome registry is missing.
+pub fn read_chunk(
+    nbt: &NbtCompound,
+    dimension_height: u32,
+    min_y: i32,
+    registries: &RegistryHolder,
+) -> Result<Chunk, AnvilError> {
+    if nbt.compound("Level").is_some() {
+        return Err(AnvilError::UnsupportedVersion);
+    }
+
+    let mut chunk = Chunk::new(dimension_height);
+    let min_section_y = min_y.div_euclid(16);
+
+    let biome_registry = registries
+        .map
+        .get(&ResourceLocation::new("minecraft:worldgen/biome"));
+    let biome_id = |name: &str| {
+        biome_registry
+            .and_then(|biomes| biomes.get_index_of(&ResourceLocation::new(name)))
+            .unwrap_or_default() as BlockStateIntegerRepr
+    };
+
+    let sections = nbt
+        .list("sections")
+        .and_then(|sections| sections.compounds())
+        .unwrap_or_default();
+    for section_nbt in sections {
+        let Some(section_y) = section_nbt.byte("Y") else {
+            return Err(AnvilError::InvalidChunk(
+                "A section is missing its Y".to_owned(),
+            ));
+        };
+        // there's usually an extra section above and below the world for
+        // lighting
+        let Some(section) = usize::try_from(section_y as i32 - min_section_y)
+            .ok()
+            .and_then(|index| chunk.sections.get_mut(index))
+        else {
+            continue;
+        };
+
+        if let Some(block_states) = section_nbt.compound("block_states") {
+            let palette = block_states
+                .list("palette")
+                .and_then(|palette| palette.compounds())
+                .unwrap_or_default()
+                .iter()
+                .map(read_block_state)
+                .collect::<Result<Vec<_>, _>>()?;
+            section.states =
+                read_paletted_container(block_states, palette, PalettedContainerKind::BlockStates)?;
+            section.block_count = count_non_air_blocks(&section.states);
+        }
+        if let Some(biomes) = section_nbt.compound("biomes") {
+            let palette = biomes
+                .list("palette")
+                .and_then(|palette| palette.strings())
+                .unwrap_or_default()
+                .iter()
+                .map(|name| biome_id(&name.to_str()))
+                .collect();
+            section.biomes =
+                read_paletted_container(biomes, palette, PalettedContainerKind::Biomes)?;
+        }
+    }
+
+    if let Some(heightmaps) = nbt.compound("Heightmaps") {
+        chunk.heightmaps = read_heightmaps(heightmaps, dimension_height, min_y);
+    }
+
+    Ok(chunk)
+}
+
+/// Get the ID of a block state from a palette entry like
+/// `{Name: "minecraft:oak_log", Properties: {axis: "x"}}`.
+fn read_block_state(nbt: &NbtCompound) -> Result<BlockStateIntegerRepr, AnvilError> {
+    let Some(name) = nbt.string("Name") else {
+        return Err(AnvilError::InvalidChunk(
+            "A block state is missing its name".to_owned(),
+        ));
+    };
+    let name = name.to_str();
+    let Ok(block) = azalea_registry::Block::from_str(&name) else {
+        warn!("Unknown block {name}, replacing it with air");
+        return Ok(BlockState::AIR.id);
+    };
+
+    let properties = nbt
+        .compound("Properties")
+        .map(|properties| {
+            properties
+                .iter()
+                .filter_map(|(key, value)| {
+                    Some((
+                        key.to_str().into_owned(),
+                        value.string()?.to_str().into_owned(),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let properties = properties
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<Vec<_>>();
+    Ok(BlockState::from_properties(block, &properties).id)
+}
+
+/// Make a [`PalettedContainer`] from the palette and data in a section.
+///
+/// Region files always use a palette, even when there are too many different
+/// values for one to be used over the network, so those are converted to use
+/// global IDs.
+fn read_paletted_container(
+    nbt: &NbtCompound,
+    palette: Vec<BlockStateIntegerRepr>,
+    container_type: PalettedContainerKind,
+) -> Result<PalettedContainer, AnvilError> {
+    let size = container_type.size();
+    if palette.is_empty() {
+        return Err(AnvilError::InvalidChunk(
+            "A section has an empty palette".to_owned(),
+        ));
+    }
+    if palette.len() == 1 {
+        return Ok(PalettedContainer {
+            bits_per_entry: 0,
+            palette: Palette::SingleValue(palette[0]),
+            storage: BitStorage::new(0, size, Some(vec![])).unwrap(),
+            container_type,
+        });
+    }
+
+    let disk_bits = disk_bits_per_entry(palette.len(), container_type);
+
+    let data = nbt
+        .long_array("data")
+        .unwrap_or_default()
+        .iter()
+        .map(|x| *x as u64)
+        .collect::<Vec<_>>();
+    let storage = BitStorage::new(disk_bits as usize, size, Some(data))
+        .map_err(|e| AnvilError::InvalidChunk(e.to_string()))?;
+    if let Some(index) = storage
+        .iter()
+        .find(|&index| index as usize >= palette.len())
+    {
+        return Err(AnvilError::InvalidChunk(format!(
+            "Palette index {index} is out of bounds for a palette with {} entries",
+            palette.len()
+        )));
+    }
+
+    match PaletteKind::from_bits_and_type(disk_bits, &container_type) {
+        PaletteKind::Global => {
+            let max_value = match container_type {
+                PalettedContainerKind::BlockStates => BlockState::MAX_STATE,
+                PalettedContainerKind::Biomes => palette.iter().copied().max().unwrap_or_default(),
+            };
+            let global_bits = bits_for(max_value as usize + 1).max(disk_bits);
+            let mut global_storage = BitStorage::new(global_bits as usize, size, None)
+                .map_err(|e| AnvilError::InvalidChunk(e.to_string()))?;
+            for (i, index) in storage.iter().enumerate() {
+                global_storage.set(i, palette[index as usize] as u64);
+            }
+            Ok(PalettedContainer {
+                bits_per_entry: global_bits,
+                palette: Palette::Global,
+                storage: global_storage,
+                container_type,
+            })
+        }
+        palette_kind => Ok(PalettedContainer {
+            bits_per_entry: disk_bits,
+            palette: match palette_kind {
+                PaletteKind::Linear => Palette::Linear(palette),
+                _ => Palette::Hashmap(palette),
+            },
+            storage,
+            container_type,
+        }),
+    }
+}
+
+/// The number of bits that are needed to store indexes into a palette with
+/// this many entries.
+fn bits_for(count: usize) -> u8 {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()) as u8
+}
+
+/// The number of bits per entry that vanilla uses when saving a section with
+/// a palette of this size.
+fn disk_bits_per_entry(palette_len: usize, container_type: PalettedContainerKind) -> u8 {
+    let bits = bits_for(palette_len);
+    match container_type {
+        // linear palettes always use at least 4 bits for block states
+        PalettedContainerKind::BlockStates if (1..=8).contains(&bits) => bits.max(4),
+        _ => bits,
+    }
+}
+
+fn count_non_air_blocks(states: &PalettedContainer) -> u16 {
+    (0..states.storage.size())
+        .filter(|&i| {
+            !BlockState::try_from(states.get_at_index(i))
+                .unwrap_or(BlockState::AIR)
+                .is_air()
+        })
+        .count() as u16
+}
+
+/// The chunks that were loaded from region files.
+///
+/// [`ChunkStorage`](crate::ChunkStorage) only keeps weak references to
+/// chunks, so this has to be kept around for as long as the chunks should
+/// stay loaded.
+#[derive(Debug, Default, Deref, DerefMut)]
+pub struct LoadedChunks(pub IntMap<ChunkPos, Arc<RwLock<Chunk>>>);
+
+/// Load every chunk in a region file into the instance.
+///
+/// Chunks that can't be read or haven't finished generating are skipped, and
+/// a warning is logged for the ones that can't be read.
+pub fn load_region_file(
+    path: impl AsRef<Path>,
+    instance: &mut Instance,
+) -> Result<LoadedChunks, AnvilError> {
+    let path = path.as_ref();
+    let Some((region_x, region_z)) = region_pos_from_path(path) else {
+        return Err(AnvilError::InvalidRegion(format!(
+            "{} isn't named like a region file",
+            path.display()
+        )));
+    };
+    let region = RegionFile::open(path)?;
+
+    let mut loaded_chunks = LoadedChunks::default();
+    for z in 0..REGION_WIDTH {
+        for x in 0..REGION_WIDTH {
+            let pos = ChunkPos::new(region_x * REGION_WIDTH + x, region_z * REGION_WIDTH + z);
+            let nbt = match region.read_chunk_nbt(&pos) {
+                Ok(Some(nbt)) => nbt,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Couldn't read chunk {pos:?} from {}: {e}", path.display());
+                    continue;
+                }
+            };
+            // chunks that are still generating aren't sent to players
+            if nbt
+                .string("Status")
+                .is_some_and(|status| status.to_str() != "minecraft:full")
+            {
+                continue;
+            }
+            let chunk = match read_chunk(
+                &nbt,
+                instance.chunks.height,
+                instance.chunks.min_y,
+                &instance.registries,
+            ) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    warn!("Couldn't read chunk {pos:?} from {}: {e}", path.display());
+                    continue;
+                }
+            };
+            loaded_chunks.insert(pos, instance.chunks.insert(pos, chunk));
+        }
+    }
+    Ok(loaded_chunks)
+}
+
+/// Load the chunks from every region file in a directory (like `world/region`)
+/// into the instance.
+///
+/// Use [`region_dir`] to get the directory for a dimension.
+pub fn load_region_dir(
+    dir: impl AsRef<Path>,
+    instance: &mut Instance,
+) -> Result<LoadedChunks, AnvilError> {
+    let mut loaded_chunks = LoadedChunks::default();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if region_pos_from_path(&path).is_none() {
+            continue;
+        }
+        loaded_chunks.extend(load_region_file(&path, instance)?.0);
+    }
+    Ok(loaded_chunks)
+}
+
+/// The data version of the chunks that we write, which is the one for the
+/// version of Minecraft that Azalea supports.
+pub const DATA_VERSION: i32 = 4189;
+
+/// The most sectors that a chunk can take up in a region file, since the
+/// number of sectors is stored in one byte. Bigger chunks are saved in
+/// separate files.
+const MAX_CHUNK_SECTORS: usize = 255;
+
+/// Convert a chunk into the NBT that's stored in region files.
+///
+/// The chunk is saved as fully generated but without any light, so vanilla
+/// will calculate the light when it's loaded. Block entities and entities
+/// aren't saved. See [`read_chunk`] for what the registries are used for.
+pub fn write_chunk(
+    chunk: &Chunk,
+    pos: ChunkPos,
+    min_y: i32,
+    registries: &RegistryHolder,
+) -> BaseNbt {
+    let min_section_y = min_y.div_euclid(16);
+
+    let biome_registry = registries
+        .map
+        .get(&ResourceLocation::new("minecraft:worldgen/biome"));
+    let biome_name = |id: BlockStateIntegerRepr| {
+        biome_registry
+            .and_then(|biomes| biomes.get_index(id as usize))
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| "minecraft:plains".to_owned())
+    };
+
+    let sections = chunk
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| {
+            let (block_palette, block_data) = write_paletted_container(&section.states);
+            let block_palette = block_palette
+                .into_iter()
+                .map(|id| write_block_state(BlockState::try_from(id).unwrap_or(BlockState::AIR)))
+                .collect();
+            let (biome_palette, biome_data) = write_paletted_container(&section.biomes);
+            let biome_palette = biome_palette
+                .into_iter()
+                .map(|id| biome_name(id).into())
+                .collect();
+
+            NbtCompound::from_values(vec![
+                (
+                    "Y".into(),
+                    NbtTag::Byte((min_section_y + index as i32) as i8),
+                ),
+                (
+                    "block_states".into(),
+                    NbtTag::Compound(paletted_container_nbt(
+                        NbtList::Compound(block_palette),
+                        block_data,
+                    )),
+                ),
+                (
+                    "biomes".into(),
+                    NbtTag::Compound(paletted_container_nbt(
+                        NbtList::String(biome_palette),
+                        biome_data,
+                    )),
+                ),
+            ])
+        })
+        .collect();
+
+    let heightmaps = chunk
+        .heightmaps
+        .iter()
+        .map(|(kind, heightmap)| {
+            (
+                kind.to_string().into(),
+                NbtTag::LongArray(heightmap.data.data.iter().map(|x| *x as i64).collect()),
+            )
+        })
+        .collect();
+
+    BaseNbt::new(
+        "",
+        NbtCompound::from_values(vec![
+            ("DataVersion".into(), NbtTag::Int(DATA_VERSION)),
+            ("xPos".into(), NbtTag::Int(pos.x)),
+            ("zPos".into(), NbtTag::Int(pos.z)),
+            ("yPos".into(), NbtTag::Int(min_section_y)),
+            ("Status".into(), NbtTag::String("minecraft:full".into())),
+            ("LastUpdate".into(), NbtTag::Long(0)),
+            ("InhabitedTime".into(), NbtTag::Long(0)),
+            ("isLightOn".into(), NbtTag::Byte(0)),
+            ("sections".into(), NbtTag::List(NbtList::Compound(sections))),
+            ("block_entities".into(), NbtTag::List(NbtList::Empty)),
+            (
+                "Heightmaps".into(),
+                NbtTag::Compound(NbtCompound::from_values(heightmaps)),
+            ),
+        ]),
+    )
+}
+
+/// Make a palette entry like `{Name: "minecraft:oak_log", Properties: {axis:
+/// "x"}}` for a block state.
+fn write_block_state(state: BlockState) -> NbtCompound {
+    let block = Box::<dyn azalea_block::Block>::from(state);
+    let mut values = vec![(
+        "Name".into(),
+        NbtTag::String(block.as_registry_block().to_string().into()),
+    )];
+    let properties = state.properties();
+    if !properties.is_empty() {
+        values.push((
+            "Properties".into(),
+            NbtTag::Compound(NbtCompound::from_values(
+                properties
+                    .into_iter()
+                    .map(|(key, value)| (key.into(), NbtTag::String(value.into())))
+                    .collect(),
+            )),
+        ));
+    }
+    NbtCompound::from_values(values)
+}
+
+/// Get the palette and packed data for a section's block states or biomes, in
+/// the format that vanilla saves them in.
+fn write_paletted_container(
+    container: &PalettedContainer,
+) -> (Vec<BlockStateIntegerRepr>, Vec<i64>) {
+    let size = container.container_type.size();
+
+    let mut palette = Vec::new();
+    let mut palette_indexes = HashMap::new();
+    let mut indexes = Vec::with_capacity(size);
+    for i in 0..size {
+        let value = container.get_at_index(i);
+        let index = *palette_indexes.entry(value).or_insert_with(|| {
+            palette.push(value);
+            palette.len() - 1
+        });
+        indexes.push(index as u64);
+    }
+    if palette.len() == 1 {
+        return (palette, Vec::new());
+    }
+
+    let bits = disk_bits_per_entry(palette.len(), container.container_type);
+    let mut storage = BitStorage::new(bits as usize, size, None)
+        .expect("the size of the bit storage should always be valid");
+    for (i, index) in indexes.into_iter().enumerate() {
+        storage.set(i, index);
+    }
+    (
+        palette,
+        storage.data.into_iter().map(|x| x as i64).collect(),
+    )
+}
+
+fn paletted_container_nbt(palette: NbtList, data: Vec<i64>) -> NbtCompound {
+    let mut values = vec![("palette".into(), NbtTag::List(palette))];
+    // the data is left out if there's only one value
+    if !data.is_empty() {
+        values.push(("data".into(), NbtTag::LongArray(data)));
+    }
+    NbtCompound::from_values(values)
+}
+
+/// Write chunks to the region file at the path, and keep the chunks that were
+/// already in it.
+///
+/// The region is written to a temporary file first and then renamed, so it's
+/// never left half-written.
+pub fn write_region_file(
+    path: impl AsRef<Path>,
+    chunks: impl IntoIterator<Item = (ChunkPos, BaseNbt)>,
+) -> Result<(), AnvilError> {
+    let path = path.as_ref();
+
+    // the raw chunks (including their lengths and compression types) and
+    // timestamps for every position in the region
+    let mut raw_chunks = vec![None; (REGION_WIDTH * REGION_WIDTH) as usize];
+    if path.exists() {
+        let old_region = RegionFile::open(path)?;
+        for (index, raw_chunk) in raw_chunks.iter_mut().enumerate() {
+            *raw_chunk = old_region
+                .raw_chunk(index)
+                .map(|data| (data.to_vec(), old_region.timestamp(index)));
+        }
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as u32)
+        .unwrap_or_default();
+    for (pos, nbt) in chunks {
+        let mut nbt_bytes = Vec::new();
+        nbt.write(&mut nbt_bytes);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&nbt_bytes)?;
+        let compressed = encoder.finish()?;
+
+        let mut raw_chunk = Vec::new();
+        if compressed.len() + 5 > MAX_CHUNK_SECTORS * SECTOR_SIZE {
+            // only the compression type is stored in the region
+            fs::write(
+                path.with_file_name(format!("c.{}.{}.mcc", pos.x, pos.z)),
+                &compressed,
+            )?;
+            raw_chunk.extend(1u32.to_be_bytes());
+            raw_chunk.push(2 | EXTERNAL_CHUNK_FLAG);
+        } else {
+            raw_chunk.extend((compressed.len() as u32 + 1).to_be_bytes());
+            raw_chunk.push(2);
+            raw_chunk.extend(compressed);
+        }
+        raw_chunks[region_index(&pos)] = Some((raw_chunk, now));
+    }
+
+    let mut data = vec![0; HEADER_SIZE];
+    for (index, (raw_chunk, timestamp)) in raw_chunks
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, raw_chunk)| Some((index, raw_chunk?)))
+    {
+        let sector_offset = data.len() / SECTOR_SIZE;
+        let sector_count = raw_chunk.len().div_ceil(SECTOR_SIZE);
+        if sector_count > MAX_CHUNK_SECTORS {
+            warn!(
+                "Dropping a chunk from {} since it's too big to be in a region",
+                path.display()
+            );
+            continue;
+        }
+        let location = ((sector_offset as u32) << 8) | sector_count as u32;
+        data[index * 4..index * 4 + 4].copy_from_slice(&location.to_be_bytes());
+        data[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]
+            .copy_from_slice(&timestamp.to_be_bytes());
+
+        data.extend(raw_chunk);
+        // pad the chunk to the end of its last sector
+        data.resize((sector_offset + sector_count) * SECTOR_SIZE, 0);
+    }
+
+    let temp_path = path.with_extension("mca.tmp");
+    fs::write(&temp_path, &data)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Write chunks to the region files in a directory (like `world/region`),
+/// and create the directory if it doesn't exist.
+///
+/// Use [`region_dir`] to get the directory for a dimension.
+pub fn write_region_dir(
+    dir: impl AsRef<Path>,
+    chunks: impl IntoIterator<Item = (ChunkPos, BaseNbt)>,
+) -> Result<(), AnvilError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let mut regions = HashMap::<(i32, i32), Vec<(ChunkPos, BaseNbt)>>::new();
+    for (pos, nbt) in chunks {
+        regions
+            .entry((
+                pos.x.div_euclid(REGION_WIDTH),
+                pos.z.div_euclid(REGION_WIDTH),
+            ))
+            .or_default()
+            .push((pos, nbt));
+    }
+    for ((region_x, region_z), chunks) in regions {
+        write_region_file(dir.join(format!("r.{region_x}.{region_z}.mca")), chunks)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::position::{BlockPos, ChunkBlockPos};
+
+    use super::*;
+    use crate::ChunkStorage;
+
+    fn block_nbt(name: &str) -> NbtCompound {
+        NbtCompound::from_values(vec![("Name".into(), NbtTag::String(name.into()))])
+    }
+
+    /// Make a region with a single chunk at the given position, which has
+    /// stone at the bottom corner and air everywhere else.
+    fn make_region(pos: ChunkPos) -> Vec<u8> {
+        // 4 bits per block, and the first block is the second palette entry
+        let mut data = vec![0i64; 4096 / 16];
+        data[0] = 1;
+        let section = NbtCompound::from_values(vec![
+            ("Y".into(), NbtTag::Byte(-4)),
+            (
+                "block_states".into(),
+                NbtTag::Compound(NbtCompound::from_values(vec![
+                    (
+                        "palette".into(),
+                        NbtTag::List(NbtList::Compound(vec![
+                            block_nbt("minecraft:air"),
+                            block_nbt("minecraft:stone"),
+                        ])),
+                    ),
+                    ("data".into(), NbtTag::LongArray(data)),
+                ])),
+            ),
+        ]);
+        let chunk = BaseNbt::new(
+            "",
+            NbtCompound::from_values(vec![
+                ("xPos".into(), NbtTag::Int(pos.x)),
+                ("zPos".into(), NbtTag::Int(pos.z)),
+                ("Status".into(), NbtTag::String("minecraft:full".into())),
+                (
+                    "sections".into(),
+                    NbtTag::List(NbtList::Compound(vec![section])),
+                ),
+            ]),
+        );
+        let mut nbt_bytes = Vec::new();
+        chunk.write(&mut nbt_bytes);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&nbt_bytes).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut region = vec![0; HEADER_SIZE];
+        let index = region_index(&pos) * 4;
+        let sector_count = (compressed.len() + 5).div_ceil(SECTOR_SIZE);
+        region[index..index + 4].copy_from_slice(&[0, 0, 2, sector_count as u8]);
+        region.extend(((compressed.len() + 1) as u32).to_be_bytes());
+        region.push(2);
+        region.extend(compressed);
+        region.resize(HEADER_SIZE + sector_count * SECTOR_SIZE, 0);
+        region
+    }
+
+    #[test]
+    fn test_read_chunk() {
+        let pos = ChunkPos::new(-31, 2);
+        let region = RegionFile::from_bytes(make_region(pos)).unwrap();
+        assert!(region.contains(&pos));
+        assert!(!region.contains(&ChunkPos::new(-30, 2)));
+
+        let mut chunk_storage = ChunkStorage::default();
+        let chunk = region
+            .read_chunk(
+                &pos,
+                chunk_storage.height,
+                chunk_storage.min_y,
+                &RegistryHolder::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.sections[0].block_count, 1);
+        let _chunk = chunk_storage.insert(pos, chunk);
+
+        assert_eq!(
+            chunk_storage.get_block_state(&BlockPos::new(-31 * 16, -64, 32)),
+            Some(BlockState::from(azalea_registry::Block::Stone))
+        );
+        assert_eq!(
+            chunk_storage.get_block_state(&BlockPos::new(-31 * 16 + 1, -64, 32)),
+            Some(BlockState::AIR)
+        );
+    }
+
+    #[test]
+    fn test_write_and_load_region() {
+        let dir = std::env::temp_dir().join(format!("azalea-anvil-test-{}", std::process::id()));
+        let min_y = -64;
+        let oak_log = BlockState::from_properties(azalea_registry::Block::OakLog, &[("axis", "z")]);
+
+        let mut chunk = Chunk::default();
+        chunk.set(&ChunkBlockPos::new(1, -64, 2), oak_log, min_y);
+        chunk.set(
+            &ChunkBlockPos::new(15, 319, 15),
+            BlockState::from(azalea_registry::Block::Stone),
+            min_y,
+        );
+        let registries = RegistryHolder::default();
+        write_region_dir(
+            &dir,
+            [(
+                ChunkPos::new(-1, 0),
+                write_chunk(&chunk, ChunkPos::new(-1, 0), min_y, &registries),
+            )],
+        )
+        .unwrap();
+        // this is in the same region, so the first chunk has to be kept
+        write_region_dir(
+            &dir,
+            [(
+                ChunkPos::new(-2, 0),
+                write_chunk(&Chunk::default(), ChunkPos::new(-2, 0), min_y, &registries),
+            )],
+        )
+        .unwrap();
+
+        let mut instance = Instance::from(ChunkStorage::default());
+        let loaded_chunks = load_region_dir(&dir, &mut instance).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded_chunks.len(), 2);
+        assert_eq!(
+            instance.get_block_state(&BlockPos::new(-15, -64, 2)),
+            Some(oak_log)
+        );
+        assert_eq!(
+            instance.get_block_state(&BlockPos::new(-1, 319, 15)),
+            Some(BlockState::from(azalea_registry::Block::Stone))
+        );
+        assert_eq!(
+            instance.get_block_state(&BlockPos::new(-16, 0, 0)),
+            Some(BlockState::AIR)
+        );
+        assert_eq!(
+            instance.get_block_state(&BlockPos::new(-32, 0, 0)),
+            Some(BlockState::AIR)
+        );
+    }
+
+    #[test]
+    fn test_region_pos_from_path() {
+        assert_eq!(
+            region_pos_from_path(Path::new("world/region/r.-1.2.mca")),
+            Some((-1, 2))
+        );
+        assert_eq!(region_pos_from_path(Path::new("r.1.2.mcc")), None);
+    }
+}
@@ -209,6 +209,82 @@ impl BitStorage {
         *cell = (*cell & !(self.mask << bit_index)) | ((value & self.mask) << bit_index);
     }
 
+    /// Get every entry in the storage, in order.
+    ///
+    /// This is much faster than calling [`Self::get`] for every index, since
+    /// each long only has to be read once.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `out` isn't the same length as the size of
+    /// this storage.
+    pub fn get_all(&self, out: &mut [u64]) {
+        assert_eq!(
+            out.len(),
+            self.size,
+            "Output length must be the same as the size of the storage"
+        );
+        self.for_each(|index, value| out[index] = value);
+    }
+
+    /// Call `f` with the index and value of every entry, in order.
+    pub fn for_each(&self, mut f: impl FnMut(usize, u64)) {
+        // 0 bit storage
+        if self.data.is_empty() {
+            for index in 0..self.size {
+                f(index, 0);
+            }
+            return;
+        }
+
+        let mut index = 0;
+        for &cell in &self.data {
+            let mut cell = cell;
+            for _ in 0..self.values_per_long {
+                if index >= self.size {
+                    return;
+                }
+                f(index, cell & self.mask);
+                cell >>= self.bits;
+                index += 1;
+            }
+        }
+    }
+
+    /// Replace every entry in the storage.
+    ///
+    /// This is much faster than calling [`Self::set`] for every index, since
+    /// each long only has to be written once.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `values` isn't the same length as the size
+    /// of this storage.
+    pub fn set_all(&mut self, values: &[u64]) {
+        assert_eq!(
+            values.len(),
+            self.size,
+            "Values length must be the same as the size of the storage"
+        );
+        // 0 bit storage
+        if self.data.is_empty() {
+            return;
+        }
+
+        for (cell, values) in self
+            .data
+            .iter_mut()
+            .zip(values.chunks(self.values_per_long))
+        {
+            let mut packed = 0;
+            for (i, &value) in values.iter().enumerate() {
+                debug_assert!(value <= self.mask);
+                packed |= (value & self.mask) << (i * self.bits);
+            }
+            *cell = packed;
+        }
+    }
+
     /// The number of entries.
     #[inline]
     pub fn size(&self) -> usize {
@@ -258,4 +334,25 @@ mod tests {
             assert_eq!(storage.get(i), *expected);
         }
     }
+
+    #[test]
+    fn test_get_all_and_set_all() {
+        // 5 bits per entry doesn't divide 64 evenly, so some bits in every long are
+        // unused
+        let values = (0..100).map(|i| i * 7 % 32).collect::<Vec<u64>>();
+        let mut storage = BitStorage::new(5, values.len(), None).unwrap();
+        storage.set_all(&values);
+
+        for (i, expected) in values.iter().enumerate() {
+            assert_eq!(storage.get(i), *expected);
+        }
+        let mut out = vec![0; values.len()];
+        storage.get_all(&mut out);
+        assert_eq!(out, values);
+
+        let empty_storage = BitStorage::new(0, 10, Some(vec![])).unwrap();
+        let mut out = vec![1; 10];
+        empty_storage.get_all(&mut out);
+        assert_eq!(out, vec![0; 10]);
+    }
 }
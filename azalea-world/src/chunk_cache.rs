@@ -0,0 +1,205 @@
+//! Keep chunks around for a while after they're unloaded, so we don't forget
+//! the terrain as soon as we walk away from it.
+//!
+//! The cache is disabled by default. Give it a memory budget with
+//! [`ChunkCache::set_max_memory`] (or `ChunkCachePlugin` in `azalea_client`)
+//! to enable it.
+
+use std::{collections::BTreeMap, mem};
+
+use azalea_block::block_state::BlockStateIntegerRepr;
+use azalea_core::position::ChunkPos;
+use nohash_hasher::IntMap;
+
+use crate::{
+    light::DATA_LAYER_SIZE,
+    palette::{Palette, PalettedContainer},
+    Chunk, Section,
+};
+
+/// Chunks that were unloaded recently.
+///
+/// These aren't in the world anymore, so they can be out of date, and they
+/// aren't returned by [`ChunkStorage::get`]. When the cache uses more memory
+/// than it's allowed to, the chunks that were unloaded longest ago are
+/// removed first.
+///
+/// [`ChunkStorage::get`]: crate::ChunkStorage::get
+#[derive(Debug, Clone, Default)]
+pub struct ChunkCache {
+    max_memory: usize,
+    memory: usize,
+    chunks: IntMap<ChunkPos, CachedChunk>,
+    /// The positions of the chunks, in the order that they were added.
+    order: BTreeMap<u64, ChunkPos>,
+    next_id: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CachedChunk {
+    chunk: Chunk,
+    memory: usize,
+    /// The key of the chunk in [`ChunkCache::order`].
+    id: u64,
+}
+
+impl ChunkCache {
+    /// Create a cache that can use about `max_memory` bytes.
+    pub fn new(max_memory: usize) -> Self {
+        Self {
+            max_memory,
+            ..Default::default()
+        }
+    }
+
+    /// The most memory that the cache can use, in bytes. If this is 0 then the
+    /// cache is disabled.
+    pub fn max_memory(&self) -> usize {
+        self.max_memory
+    }
+
+    /// Change how much memory the cache can use, in bytes. Setting this to 0
+    /// disables the cache.
+    pub fn set_max_memory(&mut self, max_memory: usize) {
+        self.max_memory = max_memory;
+        self.shrink();
+    }
+
+    /// About how much memory the chunks in the cache use, in bytes.
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get(&self, pos: &ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(pos).map(|cached| &cached.chunk)
+    }
+
+    /// The chunks in the cache and their positions, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.chunks
+            .iter()
+            .map(|(pos, cached)| (*pos, &cached.chunk))
+    }
+
+    /// Add a chunk that was just unloaded to the cache, which might remove
+    /// older chunks to make room for it.
+    pub fn insert(&mut self, pos: ChunkPos, chunk: Chunk) {
+        self.remove(&pos);
+
+        let memory = estimate_memory(&chunk);
+        if memory > self.max_memory {
+            return;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.order.insert(id, pos);
+        self.memory += memory;
+        self.chunks.insert(pos, CachedChunk { chunk, memory, id });
+        self.shrink();
+    }
+
+    pub fn remove(&mut self, pos: &ChunkPos) -> Option<Chunk> {
+        let cached = self.chunks.remove(pos)?;
+        self.order.remove(&cached.id);
+        self.memory -= cached.memory;
+        Some(cached.chunk)
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.order.clear();
+        self.memory = 0;
+    }
+
+    /// Remove the oldest chunks until we're under the memory limit.
+    fn shrink(&mut self) {
+        while self.memory > self.max_memory {
+            let Some((_, pos)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(cached) = self.chunks.remove(&pos) {
+                self.memory -= cached.memory;
+            }
+        }
+    }
+}
+
+/// Guess how many bytes the chunk uses, which doesn't have to be exact.
+pub(crate) fn estimate_memory(chunk: &Chunk) -> usize {
+    fn paletted_container_memory(container: &PalettedContainer) -> usize {
+        let palette_len = match &container.palette {
+            Palette::Linear(ids) | Palette::Hashmap(ids) => ids.len(),
+            Palette::SingleValue(_) | Palette::Global => 0,
+        };
+        container.storage.data.len() * mem::size_of::<u64>()
+            + palette_len * mem::size_of::<BlockStateIntegerRepr>()
+    }
+
+    let sections = chunk
+        .sections
+        .iter()
+        .map(|section| {
+            mem::size_of::<Section>()
+                + paletted_container_memory(&section.states)
+                + paletted_container_memory(&section.biomes)
+        })
+        .sum::<usize>();
+    let light = chunk
+        .light
+        .sky
+        .iter()
+        .chain(&chunk.light.block)
+        .flatten()
+        .count()
+        * DATA_LAYER_SIZE;
+    let heightmaps = chunk
+        .heightmaps
+        .values()
+        .map(|heightmap| heightmap.data.data.len() * mem::size_of::<u64>())
+        .sum::<usize>();
+
+    mem::size_of::<Chunk>() + sections + light + heightmaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_chunks_are_removed_first() {
+        let chunk_memory = estimate_memory(&Chunk::default());
+        let mut cache = ChunkCache::new(chunk_memory * 2);
+
+        cache.insert(ChunkPos::new(0, 0), Chunk::default());
+        cache.insert(ChunkPos::new(1, 0), Chunk::default());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.memory(), chunk_memory * 2);
+
+        // re-adding a chunk makes it the newest one
+        cache.insert(ChunkPos::new(0, 0), Chunk::default());
+        cache.insert(ChunkPos::new(2, 0), Chunk::default());
+        assert!(cache.get(&ChunkPos::new(0, 0)).is_some());
+        assert!(cache.get(&ChunkPos::new(1, 0)).is_none());
+        assert!(cache.get(&ChunkPos::new(2, 0)).is_some());
+
+        cache.set_max_memory(0);
+        assert!(cache.is_empty());
+        assert_eq!(cache.memory(), 0);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let mut cache = ChunkCache::default();
+        cache.insert(ChunkPos::new(0, 0), Chunk::default());
+        assert!(cache.is_empty());
+    }
+}
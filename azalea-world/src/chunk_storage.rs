@@ -10,19 +10,36 @@ use std::{
 use azalea_block::block_state::{BlockState, BlockStateIntegerRepr};
 use azalea_block::fluid_state::FluidState;
 use azalea_buf::{AzaleaRead, AzaleaWrite, BufReadError};
-use azalea_core::position::{BlockPos, ChunkBlockPos, ChunkPos, ChunkSectionBlockPos};
+use azalea_core::position::{
+    BlockPos, ChunkBlockPos, ChunkPos, ChunkSectionBlockPos, ChunkSectionPos,
+};
 use nohash_hasher::IntMap;
 use parking_lot::RwLock;
 use simdnbt::owned::NbtCompound;
 use tracing::{debug, trace, warn};
 
+use crate::chunk_cache::{estimate_memory, ChunkCache};
+use crate::heightmap::highest_in_column;
 use crate::heightmap::Heightmap;
 use crate::heightmap::HeightmapKind;
+use crate::light::{ChunkLight, LightKind};
+use crate::palette::Palette;
 use crate::palette::PalettedContainer;
 use crate::palette::PalettedContainerKind;
 
 const SECTION_HEIGHT: u32 = 16;
 
+/// The height of the overworld in vanilla. This is only used for defaults, the
+/// real height comes from the dimension type that the server sends.
+pub const DEFAULT_HEIGHT: u32 = 384;
+/// The lowest y coordinate of the overworld in vanilla. This is only used for
+/// defaults, like [`DEFAULT_HEIGHT`].
+pub const DEFAULT_MIN_Y: i32 = -64;
+
+/// [`ChunkStorage::map`] can have at least this many entries before the ones
+/// for unloaded chunks are removed automatically.
+const MIN_CLEANUP_LEN: usize = 1024;
+
 /// An efficient storage of chunks for a client that has a limited render
 /// distance. This has support for using a shared [`ChunkStorage`].
 pub struct PartialChunkStorage {
@@ -37,24 +54,64 @@ pub struct PartialChunkStorage {
 /// A storage for chunks where they're only stored weakly, so if they're not
 /// actively being used somewhere else they'll be forgotten. This is used for
 /// shared worlds.
+///
+/// The strong references are in the [`PartialChunkStorage`] of every client
+/// that's in the world. When a client receives a chunk that another client
+/// already has, the existing chunk is replaced in place (see [`Self::insert`])
+/// and both clients keep pointing at it, so there's only one copy of each
+/// chunk no matter how many bots are near it. [`Self::stats`] shows how much
+/// memory that saves. Those `Arc`s are already reference-counted shared chunk
+/// columns, so there's deliberately no separate layer of them on top.
 #[derive(Debug, Clone)]
 pub struct ChunkStorage {
     pub height: u32,
     pub min_y: i32,
     pub map: IntMap<ChunkPos, Weak<RwLock<Chunk>>>,
+    /// Chunks that were unloaded recently. This is disabled unless it's given
+    /// a memory budget.
+    pub cache: ChunkCache,
+    /// When [`Self::map`] gets this big, [`Self::remove_unloaded`] is called
+    /// the next time a chunk is inserted.
+    next_cleanup_len: usize,
+}
+
+/// The numbers from [`ChunkStorage::stats`].
+///
+/// Memory is estimated, and only includes the data in the chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkStorageStats {
+    /// The number of chunks that are loaded.
+    pub chunks: usize,
+    /// The number of references to the loaded chunks. Every client that has a
+    /// chunk loaded counts as a reference, so in a swarm this is usually much
+    /// higher than [`Self::chunks`].
+    pub references: usize,
+    /// The number of chunks that have more than one reference.
+    pub shared_chunks: usize,
+    /// About how many bytes the loaded chunks use.
+    pub memory: usize,
+    /// About how many bytes would be used on top of [`Self::memory`] if every
+    /// reference had its own copy of its chunk.
+    pub memory_saved: usize,
+    /// The number of chunks in the [`ChunkCache`].
+    pub cached_chunks: usize,
+    /// About how many bytes the [`ChunkCache`] uses.
+    pub cache_memory: usize,
 }
 
 /// A single chunk in a world (16*?*16 blocks). This only contains the blocks
 /// and biomes. You can derive the height of the chunk from the number of
 /// sections, but you need a [`ChunkStorage`] to get the minimum Y
 /// coordinate.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Chunk {
     pub sections: Vec<Section>,
     /// Heightmaps are used for identifying the surface blocks in a chunk.
     /// Usually for clients only `WorldSurface` and `MotionBlocking` are
     /// present.
     pub heightmaps: HashMap<HeightmapKind, Heightmap>,
+    /// The sky and block light that the server sent for the chunk.
+    pub light: ChunkLight,
 }
 
 /// A section of a chunk, i.e. a 16*16*16 block area.
@@ -83,10 +140,7 @@ impl Default for Section {
 
 impl Default for Chunk {
     fn default() -> Self {
-        Chunk {
-            sections: vec![Section::default(); (384 / 16) as usize],
-            heightmaps: HashMap::new(),
-        }
+        Chunk::new(DEFAULT_HEIGHT)
     }
 }
 
@@ -233,33 +287,10 @@ impl PartialChunkStorage {
     /// # Panics
     /// If the chunk is not in the render distance.
     pub fn set(&mut self, pos: &ChunkPos, chunk: Option<Chunk>, chunk_storage: &mut ChunkStorage) {
-        let new_chunk;
-
         // add the chunk to the shared storage
-        if let Some(chunk) = chunk {
-            match chunk_storage.map.entry(*pos) {
-                Entry::Occupied(mut e) => {
-                    if let Some(old_chunk) = e.get_mut().upgrade() {
-                        *old_chunk.write() = chunk;
-                        new_chunk = Some(old_chunk);
-                    } else {
-                        let chunk_lock = Arc::new(RwLock::new(chunk));
-                        e.insert(Arc::downgrade(&chunk_lock));
-                        new_chunk = Some(chunk_lock);
-                    }
-                }
-                Entry::Vacant(e) => {
-                    let chunk_lock = Arc::new(RwLock::new(chunk));
-                    e.insert(Arc::downgrade(&chunk_lock));
-                    new_chunk = Some(chunk_lock);
-                }
-            }
-        } else {
-            // don't remove it from the shared storage, since it'll be removed
-            // automatically if this was the last reference
-
-            new_chunk = None;
-        }
+        let new_chunk = chunk.map(|chunk| chunk_storage.insert(*pos, chunk));
+        // if the chunk is None, we don't remove it from the shared storage,
+        // since it'll be removed automatically if this was the last reference
 
         self.limited_set(pos, new_chunk);
     }
@@ -289,6 +320,8 @@ impl ChunkStorage {
             height,
             min_y,
             map: IntMap::default(),
+            cache: ChunkCache::default(),
+            next_cleanup_len: MIN_CLEANUP_LEN,
         }
     }
 
@@ -296,6 +329,117 @@ impl ChunkStorage {
         self.map.get(pos).and_then(|chunk| chunk.upgrade())
     }
 
+    /// Get a chunk that was unloaded recently from the [`ChunkCache`]. This
+    /// returns `None` if the chunk is loaded, use [`Self::get`] for that.
+    pub fn get_cached(&self, pos: &ChunkPos) -> Option<&Chunk> {
+        self.cache.get(pos)
+    }
+
+    /// Drop a reference to a chunk that a client is unloading. If nothing else
+    /// is using the chunk anymore then it's removed from the storage, and
+    /// added to the [`ChunkCache`] if it's enabled.
+    pub fn unload(&mut self, pos: ChunkPos, chunk: Arc<RwLock<Chunk>>) {
+        if let Ok(chunk) = Arc::try_unwrap(chunk) {
+            self.map.remove(&pos);
+            if self.cache.max_memory() > 0 {
+                self.cache.insert(pos, chunk.into_inner());
+            }
+        }
+    }
+
+    /// Forget about the chunks that aren't loaded by anything anymore.
+    ///
+    /// Chunks that are dropped without going through [`Self::unload`] (for
+    /// example when a client's view distance moves away from them) leave an
+    /// empty entry in [`Self::map`], which this removes. Returns the number of
+    /// entries that were removed.
+    ///
+    /// This is called automatically by [`Self::insert`] whenever the map has
+    /// doubled in size since the last time, so you don't usually have to call
+    /// it yourself.
+    pub fn remove_unloaded(&mut self) -> usize {
+        let len = self.map.len();
+        self.map.retain(|_, chunk| chunk.strong_count() > 0);
+        self.next_cleanup_len = usize::max(self.map.len() * 2, MIN_CLEANUP_LEN);
+        len - self.map.len()
+    }
+
+    /// Count how many chunks are loaded and how many clients are sharing
+    /// them.
+    ///
+    /// This locks every chunk to estimate how much memory it uses, so avoid
+    /// calling it too often.
+    ///
+    /// ```
+    /// # fn example(chunks: &azalea_world::ChunkStorage) {
+    /// let stats = chunks.stats();
+    /// println!(
+    ///     "{} chunks loaded by {} clients, using about {} MiB (saving {} MiB)",
+    ///     stats.chunks,
+    ///     stats.references,
+    ///     stats.memory / 1024 / 1024,
+    ///     stats.memory_saved / 1024 / 1024,
+    /// );
+    /// # }
+    /// ```
+    pub fn stats(&self) -> ChunkStorageStats {
+        let mut stats = ChunkStorageStats {
+            cached_chunks: self.cache.len(),
+            cache_memory: self.cache.memory(),
+            ..Default::default()
+        };
+        for chunk in self.map.values() {
+            let Some(chunk) = chunk.upgrade() else {
+                continue;
+            };
+            // don't count the reference we just made
+            let references = Arc::strong_count(&chunk) - 1;
+            let memory = estimate_memory(&chunk.read());
+
+            stats.chunks += 1;
+            stats.references += references;
+            if references > 1 {
+                stats.shared_chunks += 1;
+            }
+            stats.memory += memory;
+            stats.memory_saved += memory * references.saturating_sub(1);
+        }
+        stats
+    }
+
+    /// Add a chunk to the storage, or replace the contents of the chunk that's
+    /// already there.
+    ///
+    /// Since the storage only keeps weak references, the chunk is unloaded as
+    /// soon as the returned `Arc` and all of its clones are dropped.
+    pub fn insert(&mut self, pos: ChunkPos, chunk: Chunk) -> Arc<RwLock<Chunk>> {
+        // the chunk we got is newer than the cached one
+        self.cache.remove(&pos);
+
+        let chunk_lock = match self.map.entry(pos) {
+            Entry::Occupied(mut e) => {
+                if let Some(old_chunk) = e.get_mut().upgrade() {
+                    *old_chunk.write() = chunk;
+                    old_chunk
+                } else {
+                    let chunk_lock = Arc::new(RwLock::new(chunk));
+                    e.insert(Arc::downgrade(&chunk_lock));
+                    chunk_lock
+                }
+            }
+            Entry::Vacant(e) => {
+                let chunk_lock = Arc::new(RwLock::new(chunk));
+                e.insert(Arc::downgrade(&chunk_lock));
+                chunk_lock
+            }
+        };
+        // the map only grows here, and waiting until it doubled keeps this cheap
+        if self.map.len() >= self.next_cleanup_len {
+            self.remove_unloaded();
+        }
+        chunk_lock
+    }
+
     pub fn get_block_state(&self, pos: &BlockPos) -> Option<BlockState> {
         let chunk_pos = ChunkPos::from(pos);
         let chunk = self.get(&chunk_pos)?;
@@ -308,6 +452,52 @@ impl ChunkStorage {
         Some(FluidState::from(block_state))
     }
 
+    /// Get every block in the box between `min` and `max` (inclusive), along
+    /// with its position.
+    ///
+    /// This is much faster than calling [`Self::get_block_state`] for every
+    /// block, since each chunk is only looked up once and each section's
+    /// palette is only resolved once. The blocks are grouped by section, and
+    /// blocks in chunks that aren't loaded are skipped.
+    ///
+    /// ```
+    /// # use azalea_core::position::BlockPos;
+    /// # fn example(chunks: &azalea_world::ChunkStorage) {
+    /// let solid_blocks = chunks
+    ///     .get_block_states_in_aabb(BlockPos::new(-8, 60, -8), BlockPos::new(8, 70, 8))
+    ///     .filter(|(_, state)| !state.is_air())
+    ///     .count();
+    /// # }
+    /// ```
+    pub fn get_block_states_in_aabb(&self, min: BlockPos, max: BlockPos) -> BlockStatesInAabb<'_> {
+        BlockStatesInAabb::new(self, min, max)
+    }
+
+    /// Get the position of the highest block in the column that counts for
+    /// the heightmap kind, or `None` if the chunk isn't loaded or the column
+    /// doesn't have any blocks like that.
+    ///
+    /// See [`Chunk::highest_block_y`].
+    pub fn top_block_at(&self, x: i32, z: i32, kind: HeightmapKind) -> Option<BlockPos> {
+        let chunk = self.get(&ChunkPos::new(x.div_euclid(16), z.div_euclid(16)))?;
+        let chunk = chunk.read();
+        let y = chunk.highest_block_y(
+            x.rem_euclid(16) as u8,
+            z.rem_euclid(16) as u8,
+            kind,
+            self.min_y,
+        )?;
+        Some(BlockPos::new(x, y, z))
+    }
+
+    /// Get the sky or block light level at the position, or `None` if the chunk
+    /// isn't loaded.
+    pub fn get_light(&self, kind: LightKind, pos: &BlockPos) -> Option<u8> {
+        let chunk = self.get(&ChunkPos::from(pos))?;
+        let chunk = chunk.read();
+        Some(chunk.light.get(kind, &ChunkBlockPos::from(pos), self.min_y))
+    }
+
     pub fn set_block_state(&self, pos: &BlockPos, state: BlockState) -> Option<BlockState> {
         if pos.y < self.min_y || pos.y >= (self.min_y + self.height as i32) {
             return None;
@@ -329,6 +519,16 @@ pub fn in_range_for_view_center_and_radius(
 }
 
 impl Chunk {
+    /// Create an empty chunk for a dimension with the given height.
+    pub fn new(dimension_height: u32) -> Self {
+        let section_count = (dimension_height / SECTION_HEIGHT) as usize;
+        Chunk {
+            sections: vec![Section::default(); section_count],
+            heightmaps: HashMap::new(),
+            light: ChunkLight::new(section_count),
+        }
+    }
+
     pub fn read_with_dimension_height(
         buf: &mut Cursor<&[u8]>,
         dimension_height: u32,
@@ -342,24 +542,12 @@ impl Chunk {
             sections.push(section);
         }
 
-        let mut heightmaps = HashMap::new();
-        for (name, heightmap) in heightmaps_nbt.iter() {
-            let Ok(kind) = HeightmapKind::from_str(&name.to_str()) else {
-                warn!("Unknown heightmap kind: {name}");
-                continue;
-            };
-            let Some(data) = heightmap.long_array() else {
-                warn!("Heightmap {name} is not a long array");
-                continue;
-            };
-            let data: Vec<u64> = data.iter().map(|x| *x as u64).collect();
-            let heightmap = Heightmap::new(kind, dimension_height, min_y, data);
-            heightmaps.insert(kind, heightmap);
-        }
+        let heightmaps = read_heightmaps(heightmaps_nbt, dimension_height, min_y);
 
         Ok(Chunk {
             sections,
             heightmaps,
+            light: ChunkLight::new(section_count as usize),
         })
     }
 
@@ -367,6 +555,23 @@ impl Chunk {
         get_block_state_from_sections(&self.sections, pos, min_y)
     }
 
+    /// Get the y coordinate of the highest block in the column that counts for
+    /// the heightmap kind, or `None` if there aren't any.
+    ///
+    /// This uses the heightmap that the server sent if we have it. Vanilla
+    /// servers only send [`HeightmapKind::WorldSurface`] and
+    /// [`HeightmapKind::MotionBlocking`], so the other kinds are calculated by
+    /// checking the blocks in the column.
+    pub fn highest_block_y(&self, x: u8, z: u8, kind: HeightmapKind, min_y: i32) -> Option<i32> {
+        match self.heightmaps.get(&kind) {
+            Some(heightmap) => {
+                let y = heightmap.get_highest_taken(x, z);
+                (y >= min_y).then_some(y)
+            }
+            None => highest_in_column(kind, &self.sections, x, z, min_y),
+        }
+    }
+
     #[must_use = "Use Chunk::set instead if you don't need the previous state"]
     pub fn get_and_set(
         &mut self,
@@ -400,6 +605,37 @@ impl Chunk {
     }
 }
 
+/// Read the heightmaps from the NBT that's sent with chunks (or stored in
+/// them, for world files). Heightmaps that we don't know about or that don't
+/// match the dimension height are skipped.
+pub fn read_heightmaps(
+    heightmaps_nbt: &NbtCompound,
+    dimension_height: u32,
+    min_y: i32,
+) -> HashMap<HeightmapKind, Heightmap> {
+    let mut heightmaps = HashMap::new();
+    for (name, heightmap) in heightmaps_nbt.iter() {
+        let Ok(kind) = HeightmapKind::from_str(&name.to_str()) else {
+            warn!("Unknown heightmap kind: {name}");
+            continue;
+        };
+        let Some(data) = heightmap.long_array() else {
+            warn!("Heightmap {name} is not a long array");
+            continue;
+        };
+        let data: Vec<u64> = data.iter().map(|x| *x as u64).collect();
+        let heightmap = match Heightmap::new(kind, dimension_height, min_y, data) {
+            Ok(heightmap) => heightmap,
+            Err(e) => {
+                warn!("Heightmap {name} doesn't match the dimension height: {e}");
+                continue;
+            }
+        };
+        heightmaps.insert(kind, heightmap);
+    }
+    heightmaps
+}
+
 /// Get the block state at the given position from a list of sections. Returns
 /// `None` if the position is out of bounds.
 #[inline]
@@ -506,6 +742,137 @@ impl Section {
     }
 }
 
+/// An iterator over the blocks in a box, see
+/// [`ChunkStorage::get_block_states_in_aabb`].
+pub struct BlockStatesInAabb<'a> {
+    chunks: &'a ChunkStorage,
+    min: BlockPos,
+    max: BlockPos,
+    section_positions: std::vec::IntoIter<ChunkSectionPos>,
+    /// The last chunk that we looked up, so we don't have to look it up again
+    /// for every section in it.
+    current_chunk: Option<(ChunkPos, Option<Arc<RwLock<Chunk>>>)>,
+    blocks: std::vec::IntoIter<(BlockPos, BlockState)>,
+}
+
+impl<'a> BlockStatesInAabb<'a> {
+    fn new(chunks: &'a ChunkStorage, min: BlockPos, max: BlockPos) -> Self {
+        // only check the blocks that can be in the world
+        let min = BlockPos::new(min.x, i32::max(min.y, chunks.min_y), min.z);
+        let max_world_y = chunks.min_y + chunks.height as i32 - 1;
+        let max = BlockPos::new(max.x, i32::min(max.y, max_world_y), max.z);
+
+        let mut section_positions = Vec::new();
+        if min.x <= max.x && min.y <= max.y && min.z <= max.z {
+            let (min_section, max_section) =
+                (ChunkSectionPos::from(min), ChunkSectionPos::from(max));
+            for x in min_section.x..=max_section.x {
+                for z in min_section.z..=max_section.z {
+                    for y in min_section.y..=max_section.y {
+                        section_positions.push(ChunkSectionPos::new(x, y, z));
+                    }
+                }
+            }
+        }
+
+        Self {
+            chunks,
+            min,
+            max,
+            section_positions: section_positions.into_iter(),
+            current_chunk: None,
+            blocks: Vec::new().into_iter(),
+        }
+    }
+
+    fn chunk(&mut self, chunk_pos: ChunkPos) -> Option<Arc<RwLock<Chunk>>> {
+        match &self.current_chunk {
+            Some((pos, chunk)) if *pos == chunk_pos => chunk.clone(),
+            _ => {
+                let chunk = self.chunks.get(&chunk_pos);
+                self.current_chunk = Some((chunk_pos, chunk.clone()));
+                chunk
+            }
+        }
+    }
+
+    /// Get the blocks in the section that are also in the box.
+    fn read_section(
+        &self,
+        section: &Section,
+        section_pos: ChunkSectionPos,
+    ) -> Vec<(BlockPos, BlockState)> {
+        let section_min = BlockPos::new(section_pos.x * 16, section_pos.y * 16, section_pos.z * 16);
+        let min = self.min.max(&section_min) - section_min;
+        let max = self.max.min(&(section_min + BlockPos::new(15, 15, 15))) - section_min;
+
+        // resolve the palette once instead of for every block
+        let palette_states = match &section.states.palette {
+            Palette::SingleValue(id) => {
+                Some(vec![BlockState::try_from(*id).unwrap_or(BlockState::AIR)])
+            }
+            Palette::Linear(ids) | Palette::Hashmap(ids) => Some(
+                ids.iter()
+                    .map(|&id| BlockState::try_from(id).unwrap_or(BlockState::AIR))
+                    .collect::<Vec<_>>(),
+            ),
+            Palette::Global => None,
+        };
+
+        let mut blocks = Vec::with_capacity(
+            ((max.x - min.x + 1) * (max.y - min.y + 1) * (max.z - min.z + 1)) as usize,
+        );
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    let state = match &palette_states {
+                        Some(states) if states.len() == 1 => states[0],
+                        Some(states) => {
+                            let index = section
+                                .states
+                                .index_from_coords(x as usize, y as usize, z as usize);
+                            let value = section.states.storage.get(index) as usize;
+                            states.get(value).copied().unwrap_or(BlockState::AIR)
+                        }
+                        None => {
+                            let index = section
+                                .states
+                                .index_from_coords(x as usize, y as usize, z as usize);
+                            let value = section.states.storage.get(index) as u32;
+                            BlockState::try_from(value).unwrap_or(BlockState::AIR)
+                        }
+                    };
+                    blocks.push((section_min + BlockPos::new(x, y, z), state));
+                }
+            }
+        }
+        blocks
+    }
+}
+
+impl Iterator for BlockStatesInAabb<'_> {
+    type Item = (BlockPos, BlockState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = self.blocks.next() {
+                return Some(block);
+            }
+
+            let section_pos = self.section_positions.next()?;
+            let Some(chunk) = self.chunk(ChunkPos::from(section_pos)) else {
+                continue;
+            };
+            let chunk = chunk.read();
+            let section_index = section_index(section_pos.y * 16, self.chunks.min_y) as usize;
+            let Some(section) = chunk.sections.get(section_index) else {
+                continue;
+            };
+            self.blocks = self.read_section(section, section_pos).into_iter();
+        }
+    }
+}
+
 impl Default for PartialChunkStorage {
     fn default() -> Self {
         Self::new(8)
@@ -513,7 +880,7 @@ impl Default for PartialChunkStorage {
 }
 impl Default for ChunkStorage {
     fn default() -> Self {
-        Self::new(384, -64)
+        Self::new(DEFAULT_HEIGHT, DEFAULT_MIN_Y)
     }
 }
 
@@ -573,6 +940,248 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn test_tall_dimension() {
+        // the tallest world that data packs can make
+        let (height, min_y) = (4064, -2032);
+        let mut chunk_storage = ChunkStorage::new(height, min_y);
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        partial_chunk_storage.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::new(height)),
+            &mut chunk_storage,
+        );
+
+        let top = BlockPos::new(0, min_y + height as i32 - 1, 0);
+        let bottom = BlockPos::new(0, min_y, 0);
+        chunk_storage.set_block_state(&top, BlockState::from(azalea_registry::Block::Stone));
+        chunk_storage.set_block_state(&bottom, BlockState::from(azalea_registry::Block::Stone));
+        assert_eq!(
+            chunk_storage.get_block_state(&top),
+            Some(BlockState::from(azalea_registry::Block::Stone))
+        );
+        assert_eq!(
+            chunk_storage.get_block_state(&bottom),
+            Some(BlockState::from(azalea_registry::Block::Stone))
+        );
+        assert!(chunk_storage
+            .get_block_state(&BlockPos::new(0, min_y + height as i32, 0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_top_block_at() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        let mut chunk = Chunk::default();
+        chunk.heightmaps.insert(
+            HeightmapKind::MotionBlocking,
+            Heightmap::from_sections(HeightmapKind::MotionBlocking, 384, -64, &chunk.sections),
+        );
+        partial_chunk_storage.set(&ChunkPos::new(-1, 0), Some(chunk), &mut chunk_storage);
+
+        assert_eq!(
+            chunk_storage.top_block_at(-1, 0, HeightmapKind::MotionBlocking),
+            None
+        );
+
+        chunk_storage.set_block_state(
+            &BlockPos::new(-1, 70, 0),
+            azalea_registry::Block::Stone.into(),
+        );
+        chunk_storage.set_block_state(
+            &BlockPos::new(-1, 80, 0),
+            azalea_registry::Block::OakLeaves.into(),
+        );
+        // this one is updated from the heightmap
+        assert_eq!(
+            chunk_storage.top_block_at(-1, 0, HeightmapKind::MotionBlocking),
+            Some(BlockPos::new(-1, 80, 0))
+        );
+        // and this one is calculated, since the chunk doesn't have it
+        assert_eq!(
+            chunk_storage.top_block_at(-1, 0, HeightmapKind::MotionBlockingNoLeaves),
+            Some(BlockPos::new(-1, 70, 0))
+        );
+        assert_eq!(
+            chunk_storage.top_block_at(-2, 0, HeightmapKind::MotionBlocking),
+            None
+        );
+        // not loaded
+        assert_eq!(
+            chunk_storage.top_block_at(0, 0, HeightmapKind::MotionBlocking),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_block_states_in_aabb() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        for pos in [ChunkPos::new(-1, 0), ChunkPos::new(0, 0)] {
+            partial_chunk_storage.set(&pos, Some(Chunk::default()), &mut chunk_storage);
+        }
+        // make the section use a few different palettes
+        for x in 0..16 {
+            chunk_storage.set_block_state(
+                &BlockPos::new(x, 1, 0),
+                azalea_registry::Block::Stone.into(),
+            );
+        }
+        chunk_storage.set_block_state(
+            &BlockPos::new(-1, 2, 0),
+            azalea_registry::Block::Dirt.into(),
+        );
+
+        let blocks = chunk_storage
+            .get_block_states_in_aabb(BlockPos::new(-2, -100, -1), BlockPos::new(2, 15, 20))
+            .collect::<Vec<_>>();
+        // only z 0 to 15 is loaded, and the y is limited to the world's height
+        assert_eq!(blocks.len(), 5 * (15 - -64 + 1) * 16);
+        for (pos, state) in &blocks {
+            assert_eq!(chunk_storage.get_block_state(pos), Some(*state), "{pos:?}");
+        }
+        let non_air = blocks.iter().filter(|(_, state)| !state.is_air()).count();
+        assert_eq!(non_air, 4);
+
+        assert_eq!(
+            chunk_storage
+                .get_block_states_in_aabb(BlockPos::new(0, 0, 0), BlockPos::new(-1, 0, 0))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_unload_into_cache() {
+        let mut chunk_storage = ChunkStorage::default();
+        chunk_storage.cache.set_max_memory(usize::MAX);
+        let pos = ChunkPos::new(0, 0);
+        let chunk = chunk_storage.insert(pos, Chunk::default());
+        chunk_storage.set_block_state(
+            &BlockPos::new(0, 0, 0),
+            azalea_registry::Block::Stone.into(),
+        );
+
+        // something else still has the chunk, so it stays loaded
+        let other_chunk = chunk.clone();
+        chunk_storage.unload(pos, chunk);
+        assert!(chunk_storage.get(&pos).is_some());
+        assert!(chunk_storage.get_cached(&pos).is_none());
+
+        chunk_storage.unload(pos, other_chunk);
+        assert!(chunk_storage.get(&pos).is_none());
+        let cached = chunk_storage.get_cached(&pos).unwrap();
+        assert_eq!(
+            cached.get(&ChunkBlockPos::new(0, 0, 0), chunk_storage.min_y),
+            Some(azalea_registry::Block::Stone.into())
+        );
+
+        // loading the chunk again takes it out of the cache
+        chunk_storage.insert(pos, Chunk::default());
+        assert!(chunk_storage.get_cached(&pos).is_none());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut first_client = PartialChunkStorage::default();
+        let mut second_client = PartialChunkStorage::default();
+        first_client.set(
+            &ChunkPos::new(0, 0),
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        first_client.set(
+            &ChunkPos::new(1, 0),
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        second_client.limited_set(
+            &ChunkPos::new(0, 0),
+            chunk_storage.get(&ChunkPos::new(0, 0)),
+        );
+
+        let chunk_memory = estimate_memory(&Chunk::default());
+        let stats = chunk_storage.stats();
+        assert_eq!(stats.chunks, 2);
+        assert_eq!(stats.references, 3);
+        assert_eq!(stats.shared_chunks, 1);
+        assert_eq!(stats.memory, chunk_memory * 2);
+        assert_eq!(stats.memory_saved, chunk_memory);
+
+        drop(first_client);
+        assert_eq!(chunk_storage.remove_unloaded(), 1);
+        let stats = chunk_storage.stats();
+        assert_eq!(stats.chunks, 1);
+        assert_eq!(stats.shared_chunks, 0);
+        assert_eq!(stats.memory_saved, 0);
+    }
+
+    #[test]
+    fn test_unloaded_chunks_are_removed_automatically() {
+        let mut chunk_storage = ChunkStorage::default();
+        let kept = chunk_storage.insert(ChunkPos::new(0, 0), Chunk::default());
+        for x in 1..MIN_CLEANUP_LEN as i32 {
+            // the returned chunk is dropped right away, like if a client unloaded it
+            chunk_storage.insert(ChunkPos::new(x, 0), Chunk::default());
+        }
+        // the chunk that was being inserted when the map got big enough is still
+        // alive during the cleanup
+        assert_eq!(chunk_storage.map.len(), 2);
+        assert!(chunk_storage.get(&ChunkPos::new(0, 0)).is_some());
+
+        // and the next cleanup waits until the map is big enough again
+        for x in 3..MIN_CLEANUP_LEN as i32 {
+            chunk_storage.insert(ChunkPos::new(x, 1), Chunk::default());
+        }
+        assert_eq!(chunk_storage.map.len(), MIN_CLEANUP_LEN - 1);
+        drop(kept);
+    }
+
+    #[test]
+    fn test_clients_share_chunks() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut first_client = PartialChunkStorage::default();
+        let mut second_client = PartialChunkStorage::default();
+        let pos = ChunkPos::new(0, 0);
+        // both clients get the chunk from the server
+        first_client.set(&pos, Some(Chunk::default()), &mut chunk_storage);
+        second_client.set(&pos, Some(Chunk::default()), &mut chunk_storage);
+
+        assert!(Arc::ptr_eq(
+            first_client.limited_get(&pos).unwrap(),
+            second_client.limited_get(&pos).unwrap()
+        ));
+        let stats = chunk_storage.stats();
+        assert_eq!(stats.chunks, 1);
+        assert_eq!(stats.references, 2);
+        assert_eq!(stats.memory_saved, stats.memory);
+    }
+
+    #[test]
+    fn test_unload_without_cache() {
+        let mut chunk_storage = ChunkStorage::default();
+        let pos = ChunkPos::new(0, 0);
+        let chunk = chunk_storage.insert(pos, Chunk::default());
+        chunk_storage.unload(pos, chunk);
+        assert!(chunk_storage.map.is_empty());
+        assert!(chunk_storage.cache.is_empty());
+    }
+
+    #[test]
+    fn test_tall_dimension_heightmap() {
+        let height = 4064;
+        // 12 bits per entry, so 5 entries fit in a long
+        let data = vec![0; (16 * 16_usize).div_ceil(5)];
+        let heightmap = Heightmap::new(HeightmapKind::MotionBlocking, height, -2032, data).unwrap();
+        assert_eq!(heightmap.get_first_available(0, 0), -2032);
+
+        // data for the default height is the wrong size
+        let data = vec![0; (16 * 16_usize).div_ceil(7)];
+        assert!(Heightmap::new(HeightmapKind::MotionBlocking, height, -2032, data).is_err());
+    }
+
     #[test]
     fn test_chunk_pos_from_index() {
         let mut partial_chunk_storage = PartialChunkStorage::new(5);
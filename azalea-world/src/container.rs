@@ -9,7 +9,7 @@ use derive_more::{Deref, DerefMut};
 use nohash_hasher::IntMap;
 use parking_lot::RwLock;
 use rustc_hash::FxHashMap;
-use tracing::{debug, error};
+use tracing::{debug, warn};
 
 use crate::{ChunkStorage, Instance};
 
@@ -21,7 +21,7 @@ pub struct InstanceContainer {
     // cases where we'd want to get every entity in the world (just getting the entities in chunks
     // should work fine).
 
-    // Entities are garbage collected (by manual reference counting in EntityUuidIndex) so we don't
+    // Entities are garbage collected (by manual reference counting in LoadedBy) so we don't
     // need to worry about them here.
 
     // If it looks like we're relying on the server giving us unique world names, that's because we
@@ -53,18 +53,19 @@ impl InstanceContainer {
         min_y: i32,
     ) -> Arc<RwLock<Instance>> {
         if let Some(existing_lock) = self.instances.get(&name).and_then(|world| world.upgrade()) {
-            let existing = existing_lock.read();
-            if existing.chunks.height != height {
-                error!(
-                    "Shared dimension height mismatch: {} != {height}",
-                    existing.chunks.height
-                );
-            }
-            if existing.chunks.min_y != min_y {
-                error!(
-                    "Shared world min_y mismatch: {} != {min_y}",
-                    existing.chunks.min_y
-                );
+            {
+                let mut existing = existing_lock.write();
+                if existing.chunks.height != height || existing.chunks.min_y != min_y {
+                    // this can happen if the server changed its data packs, and the chunks
+                    // we have are useless since they're the wrong height
+                    warn!(
+                        "Dimension height of {name} changed from {} (min y {}) to {height} (min y {min_y}), forgetting its chunks",
+                        existing.chunks.height, existing.chunks.min_y
+                    );
+                    let cache_max_memory = existing.chunks.cache.max_memory();
+                    existing.chunks = ChunkStorage::new(height, min_y);
+                    existing.chunks.cache.set_max_memory(cache_max_memory);
+                }
             }
             existing_lock.clone()
         } else {
@@ -72,6 +73,7 @@ impl InstanceContainer {
                 chunks: ChunkStorage::new(height, min_y),
                 entities_by_chunk: HashMap::new(),
                 entity_by_id: IntMap::default(),
+                entity_by_uuid: HashMap::new(),
                 registries: RegistryHolder::default(),
             }));
             debug!("Added new instance {name}");
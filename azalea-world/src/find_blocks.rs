@@ -1,17 +1,17 @@
-use azalea_block::{block_state::BlockState, BlockStates};
+use azalea_block::{block_state::BlockState, BlockSetFilter, BlockStates};
 use azalea_core::position::{BlockPos, ChunkPos};
 
 use crate::{iterators::ChunkIterator, palette::Palette, ChunkStorage, Instance};
 
-fn palette_maybe_has_block(palette: &Palette, block_states: &BlockStates) -> bool {
+fn palette_maybe_has_block(palette: &Palette, filter: &impl BlockSetFilter) -> bool {
     match &palette {
-        Palette::SingleValue(id) => block_states.contains(&BlockState { id: *id }),
+        Palette::SingleValue(id) => filter.contains_state(BlockState { id: *id }),
         Palette::Linear(ids) => ids
             .iter()
-            .any(|&id| block_states.contains(&BlockState { id })),
+            .any(|&id| filter.contains_state(BlockState { id })),
         Palette::Hashmap(ids) => ids
             .iter()
-            .any(|&id| block_states.contains(&BlockState { id })),
+            .any(|&id| filter.contains_state(BlockState { id })),
         Palette::Global => true,
     }
 }
@@ -113,49 +113,92 @@ impl Instance {
         }
     }
 
-    /// Find all the coordinates of a block in the world.
+    /// Find the coordinates of every block that matches the filter and is at
+    /// most `radius` blocks away from `nearest_to`.
     ///
-    /// This returns an iterator that yields the [`BlockPos`]s of blocks that
-    /// are in the given block states. It's sorted by `x+y+z`.
-    pub fn find_blocks<'a>(
-        &'a self,
+    /// The filter can be [`BlockStates`], a single [`azalea_registry::Block`],
+    /// or a closure that takes a [`BlockState`] (see [`BlockSetFilter`]).
+    /// Sections are skipped without looking at their blocks if their palette
+    /// doesn't have anything that matches, so this is fast even for big
+    /// radiuses as long as the blocks are rare.
+    ///
+    /// Like [`Self::find_block`], this is sorted by `x+y+z`.
+    ///
+    /// ```
+    /// # fn example(client: &azalea_client::Client) {
+    /// let world = client.world();
+    /// let diamond_ores = world
+    ///     .read()
+    ///     .find_blocks(client.position(), 64, azalea_registry::Block::DiamondOre)
+    ///     .collect::<Vec<_>>();
+    /// # }
+    /// ```
+    pub fn find_blocks<F: BlockSetFilter>(
+        &self,
         nearest_to: impl Into<BlockPos>,
-        block_states: &'a BlockStates,
-    ) -> FindBlocks<'a> {
-        FindBlocks::new(nearest_to.into(), &self.chunks, block_states)
+        radius: u32,
+        filter: F,
+    ) -> FindBlocks<'_, F> {
+        FindBlocks::new(nearest_to.into(), radius, &self.chunks, filter)
     }
 }
 
-pub struct FindBlocks<'a> {
+pub struct FindBlocks<'a, F: BlockSetFilter> {
     nearest_to: BlockPos,
     start_chunk: ChunkPos,
+    radius: u32,
     chunk_iterator: ChunkIterator,
     chunks: &'a ChunkStorage,
-    block_states: &'a BlockStates,
+    filter: F,
 
     queued: Vec<BlockPos>,
 }
 
-impl<'a> FindBlocks<'a> {
-    pub fn new(
-        nearest_to: BlockPos,
-        chunks: &'a ChunkStorage,
-        block_states: &'a BlockStates,
-    ) -> Self {
+impl<'a, F: BlockSetFilter> FindBlocks<'a, F> {
+    pub fn new(nearest_to: BlockPos, radius: u32, chunks: &'a ChunkStorage, filter: F) -> Self {
         let start_chunk: ChunkPos = (&nearest_to).into();
+        // the chunk iterator goes in a diamond, so it has to go twice as far to
+        // cover the corners of the square that the radius is in
+        let chunk_radius = radius.div_ceil(16);
         Self {
             nearest_to,
             start_chunk,
-            chunk_iterator: ChunkIterator::new(start_chunk, 32),
+            radius,
+            chunk_iterator: ChunkIterator::new(start_chunk, chunk_radius * 2 + 1),
             chunks,
-            block_states,
+            filter,
 
             queued: Vec::new(),
         }
     }
+
+    /// Whether any part of the box is in the radius.
+    fn box_in_radius(&self, min: BlockPos, max: BlockPos) -> bool {
+        let closest = BlockPos::new(
+            self.nearest_to.x.clamp(min.x, max.x),
+            self.nearest_to.y.clamp(min.y, max.y),
+            self.nearest_to.z.clamp(min.z, max.z),
+        );
+        self.in_radius(closest)
+    }
+
+    fn in_radius(&self, pos: BlockPos) -> bool {
+        let offset = self.nearest_to - pos;
+        let distance_squared =
+            (offset.x as i64).pow(2) + (offset.y as i64).pow(2) + (offset.z as i64).pow(2);
+        distance_squared <= (self.radius as i64).pow(2)
+    }
+
+    /// Sort the blocks that we found so the nearest is at the end, and queue
+    /// them to be returned.
+    fn queue(&mut self, mut found: Vec<(BlockPos, u32)>) -> Option<BlockPos> {
+        found.sort_unstable_by_key(|(_, distance)| u32::MAX - distance);
+        self.queued = found.into_iter().map(|(pos, _)| pos).collect();
+        self.queued.pop()
+    }
 }
 
-impl Iterator for FindBlocks<'_> {
+impl<F: BlockSetFilter> Iterator for FindBlocks<'_, F> {
     type Item = BlockPos;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -168,7 +211,13 @@ impl Iterator for FindBlocks<'_> {
         let mut nearest_found_pos: Option<BlockPos> = None;
         let mut nearest_found_distance = 0;
 
+        let max_y = self.chunks.min_y + self.chunks.height as i32 - 1;
         while let Some(chunk_pos) = self.chunk_iterator.next() {
+            let chunk_min = BlockPos::new(chunk_pos.x * 16, self.chunks.min_y, chunk_pos.z * 16);
+            let chunk_max = BlockPos::new(chunk_min.x + 15, max_y, chunk_min.z + 15);
+            if !self.box_in_radius(chunk_min, chunk_max) {
+                continue;
+            }
             let Some(chunk) = self.chunks.get(&chunk_pos) else {
                 // if the chunk isn't loaded then we skip it.
                 // we don't just return since it *could* cause issues if there's a random
@@ -179,8 +228,15 @@ impl Iterator for FindBlocks<'_> {
             };
 
             for (section_index, section) in chunk.read().sections.iter().enumerate() {
+                let section_min_y = self.chunks.min_y + (section_index * 16) as i32;
+                let section_min = BlockPos::new(chunk_min.x, section_min_y, chunk_min.z);
+                let section_max = BlockPos::new(chunk_max.x, section_min_y + 15, chunk_max.z);
+                if !self.box_in_radius(section_min, section_max) {
+                    continue;
+                }
+
                 let maybe_has_block =
-                    palette_maybe_has_block(&section.states.palette, self.block_states);
+                    palette_maybe_has_block(&section.states.palette, &self.filter);
                 if !maybe_has_block {
                     continue;
                 }
@@ -189,14 +245,17 @@ impl Iterator for FindBlocks<'_> {
                     let block_state = section.states.get_at_index(i);
                     let block_state = BlockState { id: block_state };
 
-                    if self.block_states.contains(&block_state) {
+                    if self.filter.contains_state(block_state) {
                         let (section_x, section_y, section_z) = section.states.coords_from_index(i);
                         let (x, y, z) = (
                             chunk_pos.x * 16 + (section_x as i32),
-                            self.chunks.min_y + (section_index * 16) as i32 + section_y as i32,
+                            section_min_y + section_y as i32,
                             chunk_pos.z * 16 + (section_z as i32),
                         );
                         let this_block_pos = BlockPos { x, y, z };
+                        if !self.in_radius(this_block_pos) {
+                            continue;
+                        }
                         let this_block_distance =
                             (self.nearest_to - this_block_pos).length_manhattan();
 
@@ -229,16 +288,14 @@ impl Iterator for FindBlocks<'_> {
                 // if we found the position and there's no chance there's something closer,
                 // return it
                 if nearest_chunk_distance > required_chunk_distance {
-                    // sort so nearest is at the end
-                    found.sort_unstable_by_key(|(_, distance)| u32::MAX - distance);
-
-                    self.queued = found.into_iter().map(|(pos, _)| pos).collect();
-                    return self.queued.pop();
+                    return self.queue(found);
                 }
             }
         }
 
-        None
+        // we ran out of chunks to check, so return whatever we found in the
+        // last ones
+        self.queue(found)
     }
 }
 
@@ -302,4 +359,42 @@ mod tests {
         let pos = instance.find_block(BlockPos { x: 0, y: 0, z: 0 }, &Block::Stone.into());
         assert_eq!(pos, Some(BlockPos { x: -1, y: 0, z: 0 }));
     }
+
+    #[test]
+    fn find_blocks_in_radius() {
+        let mut instance = Instance::default();
+
+        let chunk_storage = &mut instance.chunks;
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        for x in -2..=2 {
+            for z in -2..=2 {
+                partial_chunk_storage.set(
+                    &ChunkPos { x, z },
+                    Some(Chunk::default()),
+                    chunk_storage,
+                );
+            }
+        }
+
+        chunk_storage.set_block_state(&BlockPos::new(3, 0, 0), Block::DiamondOre.into());
+        chunk_storage.set_block_state(&BlockPos::new(-20, 5, 0), Block::DiamondOre.into());
+        chunk_storage.set_block_state(&BlockPos::new(20, 0, 20), Block::DiamondOre.into());
+        chunk_storage.set_block_state(&BlockPos::new(0, 40, 0), Block::DiamondOre.into());
+        chunk_storage.set_block_state(&BlockPos::new(1, 1, 1), Block::Stone.into());
+
+        let found = instance
+            .find_blocks(BlockPos::new(0, 0, 0), 25, Block::DiamondOre)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![BlockPos::new(3, 0, 0), BlockPos::new(-20, 5, 0)]
+        );
+
+        let found = instance
+            .find_blocks(BlockPos::new(0, 0, 0), 64, |state: BlockState| {
+                !state.is_air()
+            })
+            .count();
+        assert_eq!(found, 5);
+    }
 }
@@ -0,0 +1,269 @@
+//! Find entities that are close to a position with
+//! [`Instance::entities_by_chunk`], so only the entities in nearby chunks have
+//! to be checked.
+//!
+//! The instance doesn't know where entities are (that's in the ECS), so these
+//! functions take a closure that returns the position of an entity, or `None`
+//! if the entity should be skipped.
+
+use azalea_core::{
+    aabb::AABB,
+    position::{ChunkPos, Vec3},
+};
+use bevy_ecs::entity::Entity;
+
+use crate::Instance;
+
+impl Instance {
+    /// Get the entities in the chunks from `min` to `max` (inclusive) from
+    /// [`Self::entities_by_chunk`]. The entities are in no particular order.
+    pub fn entities_in_chunks(
+        &self,
+        min: ChunkPos,
+        max: ChunkPos,
+    ) -> Box<dyn Iterator<Item = Entity> + '_> {
+        if min.x > max.x || min.z > max.z {
+            return Box::new(std::iter::empty());
+        }
+        let chunk_count =
+            (i64::from(max.x) - i64::from(min.x) + 1) * (i64::from(max.z) - i64::from(min.z) + 1);
+        if chunk_count > self.entities_by_chunk.len() as i64 {
+            // there are fewer chunks with entities than chunks in the area, so it's
+            // faster to check every chunk in the index
+            Box::new(
+                self.entities_by_chunk
+                    .iter()
+                    .filter(move |(pos, _)| {
+                        (min.x..=max.x).contains(&pos.x) && (min.z..=max.z).contains(&pos.z)
+                    })
+                    .flat_map(|(_, entities)| entities.iter().copied()),
+            )
+        } else {
+            Box::new(
+                (min.x..=max.x)
+                    .flat_map(move |x| (min.z..=max.z).map(move |z| ChunkPos::new(x, z)))
+                    .filter_map(|pos| self.entities_by_chunk.get(&pos))
+                    .flat_map(|entities| entities.iter().copied()),
+            )
+        }
+    }
+
+    /// Get the entities whose positions are within `radius` blocks of
+    /// `center`, in no particular order.
+    ///
+    /// `position_of` should return the position of the entity, or `None` to
+    /// leave it out.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use azalea_world::Instance;
+    /// # use azalea_core::position::Vec3;
+    /// # use bevy_ecs::entity::Entity;
+    /// # fn example(instance: &Instance, positions: &HashMap<Entity, Vec3>, center: Vec3) {
+    /// let nearby = instance.entities_in_radius(center, 8., |entity| {
+    ///     positions.get(&entity).copied()
+    /// });
+    /// # }
+    /// ```
+    pub fn entities_in_radius(
+        &self,
+        center: Vec3,
+        radius: f64,
+        mut position_of: impl FnMut(Entity) -> Option<Vec3>,
+    ) -> Vec<Entity> {
+        let min = ChunkPos::from(center - Vec3::new(radius, 0., radius));
+        let max = ChunkPos::from(center + Vec3::new(radius, 0., radius));
+        let radius_squared = radius * radius;
+        self.entities_in_chunks(min, max)
+            .filter(|&entity| {
+                position_of(entity)
+                    .is_some_and(|pos| pos.distance_squared_to(&center) <= radius_squared)
+            })
+            .collect()
+    }
+
+    /// Get the entities whose positions are in the bounding box, in no
+    /// particular order.
+    ///
+    /// Note that this only checks the positions (which are at the bottom
+    /// center of entities), not the entities' bounding boxes.
+    pub fn entities_in_aabb(
+        &self,
+        aabb: &AABB,
+        mut position_of: impl FnMut(Entity) -> Option<Vec3>,
+    ) -> Vec<Entity> {
+        let min = ChunkPos::from(aabb.min);
+        let max = ChunkPos::from(aabb.max);
+        self.entities_in_chunks(min, max)
+            .filter(|&entity| {
+                position_of(entity).is_some_and(|pos| {
+                    (aabb.min.x..=aabb.max.x).contains(&pos.x)
+                        && (aabb.min.y..=aabb.max.y).contains(&pos.y)
+                        && (aabb.min.z..=aabb.max.z).contains(&pos.z)
+                })
+            })
+            .collect()
+    }
+
+    /// Get the closest entity to `center` that's at most `max_distance` blocks
+    /// away.
+    ///
+    /// The chunks around `center` are checked in rings, and it stops as soon
+    /// as the next ring can't have anything closer, so entities that are far
+    /// away usually aren't checked at all. `max_distance` can be
+    /// [`f64::INFINITY`].
+    ///
+    /// `position_of` should return the position of the entity, or `None` to
+    /// leave it out (for example if it's the wrong type of entity).
+    pub fn nearest_entity(
+        &self,
+        center: Vec3,
+        max_distance: f64,
+        mut position_of: impl FnMut(Entity) -> Option<Vec3>,
+    ) -> Option<Entity> {
+        let center_chunk = ChunkPos::from(center);
+        // there's no point in checking rings that are further than every chunk with
+        // entities
+        let last_ring = self
+            .entities_by_chunk
+            .keys()
+            .map(|pos| {
+                i32::max(
+                    (pos.x - center_chunk.x).abs(),
+                    (pos.z - center_chunk.z).abs(),
+                )
+            })
+            .max()?;
+
+        let mut nearest = None;
+        let mut nearest_distance = max_distance;
+        for ring in 0..=last_ring {
+            // the center can be anywhere in its chunk, so everything in this ring is at
+            // least this far away
+            if ring > 0 && f64::from((ring - 1) * 16) > nearest_distance {
+                break;
+            }
+            for chunk_pos in chunk_ring(center_chunk, ring) {
+                let Some(entities) = self.entities_by_chunk.get(&chunk_pos) else {
+                    continue;
+                };
+                for &entity in entities {
+                    let Some(pos) = position_of(entity) else {
+                        continue;
+                    };
+                    let distance = pos.distance_to(&center);
+                    if distance <= nearest_distance {
+                        nearest = Some(entity);
+                        nearest_distance = distance;
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+/// The positions of the chunks that are exactly `ring` chunks away from the
+/// center (in the Chebyshev distance).
+fn chunk_ring(center: ChunkPos, ring: i32) -> impl Iterator<Item = ChunkPos> {
+    let sides = if ring == 0 {
+        vec![ChunkPos::new(0, 0)]
+    } else {
+        let mut offsets = Vec::with_capacity(ring as usize * 8);
+        for x in -ring..=ring {
+            offsets.push(ChunkPos::new(x, -ring));
+            offsets.push(ChunkPos::new(x, ring));
+        }
+        for z in -ring + 1..ring {
+            offsets.push(ChunkPos::new(-ring, z));
+            offsets.push(ChunkPos::new(ring, z));
+        }
+        offsets
+    };
+    sides
+        .into_iter()
+        .map(move |offset| ChunkPos::new(center.x + offset.x, center.z + offset.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashSet};
+
+    use super::*;
+
+    fn instance_with_entities(positions: &[Vec3]) -> Instance {
+        let mut instance = Instance::default();
+        for (i, pos) in positions.iter().enumerate() {
+            instance
+                .entities_by_chunk
+                .entry(ChunkPos::from(pos))
+                .or_default()
+                .insert(Entity::from_raw(i as u32));
+        }
+        instance
+    }
+
+    #[test]
+    fn test_entities_in_radius_and_aabb() {
+        let positions = [
+            Vec3::new(0.5, 64., 0.5),
+            Vec3::new(-3., 64., 2.),
+            Vec3::new(20., 70., 0.),
+            Vec3::new(500., 64., 500.),
+        ];
+        let instance = instance_with_entities(&positions);
+        let position_of = |entity: Entity| positions.get(entity.index() as usize).copied();
+
+        let mut nearby = instance.entities_in_radius(Vec3::new(0., 64., 0.), 10., position_of);
+        nearby.sort();
+        assert_eq!(nearby, vec![Entity::from_raw(0), Entity::from_raw(1)]);
+
+        let mut in_aabb = instance.entities_in_aabb(
+            &AABB {
+                min: Vec3::new(0., 60., -5.),
+                max: Vec3::new(30., 80., 5.),
+            },
+            position_of,
+        );
+        in_aabb.sort();
+        assert_eq!(in_aabb, vec![Entity::from_raw(0), Entity::from_raw(2)]);
+    }
+
+    #[test]
+    fn test_nearest_entity() {
+        let positions = [
+            Vec3::new(500., 64., 500.),
+            Vec3::new(40., 64., 0.),
+            Vec3::new(-17., 64., 0.),
+        ];
+        let instance = instance_with_entities(&positions);
+        let checked = RefCell::new(HashSet::new());
+        let position_of = |entity: Entity| {
+            checked.borrow_mut().insert(entity);
+            positions.get(entity.index() as usize).copied()
+        };
+
+        assert_eq!(
+            instance.nearest_entity(Vec3::new(15., 64., 0.), f64::INFINITY, position_of),
+            Some(Entity::from_raw(1))
+        );
+        // the entity that's far away is never checked
+        assert!(!checked.borrow().contains(&Entity::from_raw(0)));
+
+        assert_eq!(
+            instance.nearest_entity(Vec3::new(15., 64., 0.), 10., position_of),
+            None
+        );
+        // entities can be filtered out
+        assert_eq!(
+            instance.nearest_entity(Vec3::new(15., 64., 0.), f64::INFINITY, |entity| {
+                if entity == Entity::from_raw(1) {
+                    None
+                } else {
+                    position_of(entity)
+                }
+            }),
+            Some(Entity::from_raw(2))
+        );
+    }
+}
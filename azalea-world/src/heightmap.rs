@@ -1,10 +1,16 @@
 use std::{fmt::Display, str::FromStr};
 
 use azalea_block::BlockState;
-use azalea_core::{math, position::ChunkBlockPos};
+use azalea_core::{
+    math,
+    position::{ChunkBlockPos, ChunkSectionBlockPos},
+};
 use azalea_registry::tags::blocks::LEAVES;
 
-use crate::{chunk_storage::get_block_state_from_sections, BitStorage, Section};
+use crate::{
+    bit_storage::BitStorageError, chunk_storage::get_block_state_from_sections, palette::Palette,
+    BitStorage, Section,
+};
 
 // (wg stands for worldgen)
 
@@ -56,10 +62,40 @@ impl HeightmapKind {
 }
 
 impl Heightmap {
-    pub fn new(kind: HeightmapKind, dimension_height: u32, min_y: i32, data: Vec<u64>) -> Self {
+    /// Create a heightmap from the data that the server sent.
+    ///
+    /// The number of bits per entry depends on the height of the dimension, so
+    /// this returns an error if the data is the wrong length for it.
+    pub fn new(
+        kind: HeightmapKind,
+        dimension_height: u32,
+        min_y: i32,
+        data: Vec<u64>,
+    ) -> Result<Self, BitStorageError> {
         let bits = math::ceil_log2(dimension_height + 1);
-        let data = BitStorage::new(bits as usize, 16 * 16, Some(data)).unwrap();
-        Self { kind, data, min_y }
+        let data = BitStorage::new(bits as usize, 16 * 16, Some(data))?;
+        Ok(Self { kind, data, min_y })
+    }
+
+    /// Calculate a heightmap from the blocks in a chunk, which is useful for
+    /// the kinds of heightmaps that the server doesn't send us.
+    pub fn from_sections(
+        kind: HeightmapKind,
+        dimension_height: u32,
+        min_y: i32,
+        sections: &[Section],
+    ) -> Self {
+        let bits = math::ceil_log2(dimension_height + 1);
+        let data = BitStorage::new(bits as usize, 16 * 16, None).unwrap();
+        let mut heightmap = Self { kind, data, min_y };
+        for x in 0..16 {
+            for z in 0..16 {
+                if let Some(y) = highest_in_column(kind, sections, x, z, min_y) {
+                    heightmap.set_height(x, z, y + 1);
+                }
+            }
+        }
+        heightmap
     }
 
     pub fn get_index(x: u8, z: u8) -> usize {
@@ -143,6 +179,35 @@ impl Heightmap {
     }
 }
 
+/// Get the y coordinate of the highest block in the column that's opaque for
+/// the heightmap kind, by checking every block from the top down.
+///
+/// Returns `None` if there aren't any blocks like that in the column.
+pub fn highest_in_column(
+    kind: HeightmapKind,
+    sections: &[Section],
+    x: u8,
+    z: u8,
+    min_y: i32,
+) -> Option<i32> {
+    for (section_index, section) in sections.iter().enumerate().rev() {
+        // sections that are only air can't have anything that's in a heightmap
+        if let Palette::SingleValue(id) = section.states.palette {
+            if BlockState::try_from(id).is_ok_and(|state| !kind.is_opaque(state)) {
+                continue;
+            }
+        }
+        let section_min_y = min_y + section_index as i32 * 16;
+        for y in (0..16).rev() {
+            let state = section.get(ChunkSectionBlockPos::new(x, y, z));
+            if kind.is_opaque(state) {
+                return Some(section_min_y + y as i32);
+            }
+        }
+    }
+    None
+}
+
 impl FromStr for HeightmapKind {
     type Err = ();
 
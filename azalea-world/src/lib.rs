@@ -1,19 +1,26 @@
 #![doc = include_str!("../README.md")]
 #![feature(error_generic_member_access)]
 
+pub mod anvil;
 mod bit_storage;
+pub mod chunk_cache;
 pub mod chunk_storage;
 mod container;
 pub mod find_blocks;
+pub mod find_entities;
 pub mod heightmap;
 pub mod iterators;
+pub mod light;
+pub mod map_render;
 pub mod palette;
+pub mod schematic;
+pub mod snapshot;
 mod world;
 
 use std::backtrace::Backtrace;
 
-pub use bit_storage::BitStorage;
-pub use chunk_storage::{Chunk, ChunkStorage, PartialChunkStorage, Section};
+pub use bit_storage::{BitStorage, BitStorageError};
+pub use chunk_storage::{Chunk, ChunkStorage, ChunkStorageStats, PartialChunkStorage, Section};
 pub use container::*;
 use thiserror::Error;
 pub use world::*;
@@ -0,0 +1,255 @@
+//! The sky and block light that the server sends us.
+//!
+//! We don't calculate light ourselves, so the light is only as up-to-date as
+//! the last light update that the server sent. Vanilla servers send light
+//! updates soon after blocks change, so it's usually close enough.
+
+use azalea_core::{
+    bitset::BitSet,
+    position::{ChunkBlockPos, ChunkSectionBlockPos},
+};
+
+/// The number of bytes in a [`DataLayer`], which stores a 4-bit light level for
+/// each of the 16*16*16 blocks in a section.
+pub const DATA_LAYER_SIZE: usize = 2048;
+
+/// The highest light level.
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightKind {
+    /// Light from the sky. Note that this is the light level as if it's day,
+    /// it doesn't get lower at night.
+    Sky,
+    /// Light from blocks like torches and lava.
+    Block,
+}
+
+/// The light levels for one section, stored like vanilla does (4 bits for each
+/// block).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataLayer {
+    data: Box<[u8; DATA_LAYER_SIZE]>,
+}
+
+impl DataLayer {
+    /// Create a layer where every block has the same light level.
+    pub fn filled(level: u8) -> Self {
+        let level = level & 0xf;
+        Self {
+            data: Box::new([level | (level << 4); DATA_LAYER_SIZE]),
+        }
+    }
+
+    /// Create a layer from the bytes that the server sent. Returns `None` if
+    /// there aren't exactly [`DATA_LAYER_SIZE`] bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let data: [u8; DATA_LAYER_SIZE] = bytes.try_into().ok()?;
+        Some(Self {
+            data: Box::new(data),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; DATA_LAYER_SIZE] {
+        &self.data
+    }
+
+    pub fn get(&self, pos: ChunkSectionBlockPos) -> u8 {
+        let (index, shift) = Self::index(pos);
+        (self.data[index] >> shift) & 0xf
+    }
+
+    pub fn set(&mut self, pos: ChunkSectionBlockPos, level: u8) {
+        let (index, shift) = Self::index(pos);
+        self.data[index] = (self.data[index] & !(0xf << shift)) | ((level & 0xf) << shift);
+    }
+
+    /// The index of the byte that has the light level for the block, and how
+    /// much it has to be shifted by.
+    fn index(pos: ChunkSectionBlockPos) -> (usize, u8) {
+        let index = ((pos.y as usize) << 8) | ((pos.z as usize) << 4) | pos.x as usize;
+        (index >> 1, ((index & 1) << 2) as u8)
+    }
+}
+
+/// The light in a chunk.
+///
+/// There's one more layer below and above the chunk's sections than there are
+/// sections, like in vanilla. Layers are `None` if the server didn't send them.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkLight {
+    pub sky: Vec<Option<DataLayer>>,
+    pub block: Vec<Option<DataLayer>>,
+}
+
+impl ChunkLight {
+    /// Create the light for a chunk with the given number of sections, without
+    /// any light data.
+    pub fn new(section_count: usize) -> Self {
+        Self {
+            sky: vec![None; section_count + 2],
+            block: vec![None; section_count + 2],
+        }
+    }
+
+    pub fn layers(&self, kind: LightKind) -> &[Option<DataLayer>] {
+        match kind {
+            LightKind::Sky => &self.sky,
+            LightKind::Block => &self.block,
+        }
+    }
+
+    pub fn layers_mut(&mut self, kind: LightKind) -> &mut Vec<Option<DataLayer>> {
+        match kind {
+            LightKind::Sky => &mut self.sky,
+            LightKind::Block => &mut self.block,
+        }
+    }
+
+    /// Update the light from the data in a light update or chunk packet.
+    ///
+    /// Layers that are in `mask` are replaced with the next layer in
+    /// `updates`, layers in `empty_mask` are set to 0, and the others aren't
+    /// changed.
+    pub fn update(
+        &mut self,
+        kind: LightKind,
+        mask: &BitSet,
+        empty_mask: &BitSet,
+        updates: &[Vec<u8>],
+    ) {
+        let mut updates = updates.iter();
+        for (index, layer) in self.layers_mut(kind).iter_mut().enumerate() {
+            if mask.index(index) {
+                let Some(bytes) = updates.next() else {
+                    break;
+                };
+                *layer = DataLayer::from_bytes(bytes);
+            } else if empty_mask.index(index) {
+                *layer = Some(DataLayer::filled(0));
+            }
+        }
+    }
+
+    /// Get the sky light level at a position in the chunk.
+    ///
+    /// Like in vanilla, if we don't have the layer for the position then the
+    /// light level from the bottom of the next layer above it is used, and it's
+    /// 15 if there's nothing above it. Chunks without any sky light (like in
+    /// the Nether) always have a sky light level of 0.
+    pub fn get_sky_light(&self, pos: &ChunkBlockPos, min_y: i32) -> u8 {
+        if self.sky.iter().all(Option::is_none) {
+            return 0;
+        }
+        let Some(index) = self.layer_index(pos.y, min_y) else {
+            return if pos.y < min_y { 0 } else { MAX_LIGHT_LEVEL };
+        };
+        let section_pos = ChunkSectionBlockPos::from(pos);
+        if let Some(layer) = &self.sky[index] {
+            return layer.get(section_pos);
+        }
+        self.sky[index + 1..]
+            .iter()
+            .flatten()
+            .next()
+            .map_or(MAX_LIGHT_LEVEL, |layer| {
+                layer.get(ChunkSectionBlockPos::new(section_pos.x, 0, section_pos.z))
+            })
+    }
+
+    /// Get the block light level at a position in the chunk, or 0 if we don't
+    /// know it.
+    pub fn get_block_light(&self, pos: &ChunkBlockPos, min_y: i32) -> u8 {
+        self.layer_index(pos.y, min_y)
+            .and_then(|index| self.block[index].as_ref())
+            .map_or(0, |layer| layer.get(ChunkSectionBlockPos::from(pos)))
+    }
+
+    pub fn get(&self, kind: LightKind, pos: &ChunkBlockPos, min_y: i32) -> u8 {
+        match kind {
+            LightKind::Sky => self.get_sky_light(pos, min_y),
+            LightKind::Block => self.get_block_light(pos, min_y),
+        }
+    }
+
+    /// The index of the layer for the given y coordinate, or `None` if it's
+    /// outside the chunk.
+    fn layer_index(&self, y: i32, min_y: i32) -> Option<usize> {
+        // the first layer is for the section below the chunk
+        let index = (y >> 4) - (min_y >> 4) + 1;
+        usize::try_from(index)
+            .ok()
+            .filter(|&index| index < self.sky.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_layer() {
+        let mut layer = DataLayer::filled(0);
+        layer.set(ChunkSectionBlockPos::new(1, 2, 3), 7);
+        layer.set(ChunkSectionBlockPos::new(0, 2, 3), 15);
+        assert_eq!(layer.get(ChunkSectionBlockPos::new(1, 2, 3)), 7);
+        assert_eq!(layer.get(ChunkSectionBlockPos::new(0, 2, 3)), 15);
+        assert_eq!(layer.get(ChunkSectionBlockPos::new(2, 2, 3)), 0);
+
+        // even indexes are in the low bits, like in vanilla
+        let index = (2 << 8) | (3 << 4);
+        assert_eq!(layer.as_bytes()[index / 2], 0x7f);
+    }
+
+    #[test]
+    fn test_update() {
+        // a chunk that's 2 sections tall, so it has 4 layers
+        let mut light = ChunkLight::new(2);
+        let mut mask = BitSet::new(4);
+        mask.set(1);
+        let mut empty_mask = BitSet::new(4);
+        empty_mask.set(2);
+        light.update(
+            LightKind::Block,
+            &mask,
+            &empty_mask,
+            &[vec![0x55; DATA_LAYER_SIZE]],
+        );
+
+        assert_eq!(light.block[0], None);
+        assert_eq!(light.block[1], Some(DataLayer::filled(5)));
+        assert_eq!(light.block[2], Some(DataLayer::filled(0)));
+        assert_eq!(light.block[3], None);
+        assert_eq!(
+            light.get_block_light(&ChunkBlockPos::new(0, -64, 0), -64),
+            5
+        );
+        assert_eq!(
+            light.get_block_light(&ChunkBlockPos::new(0, -48, 0), -64),
+            0
+        );
+        assert_eq!(
+            light.get_block_light(&ChunkBlockPos::new(0, 100, 0), -64),
+            0
+        );
+    }
+
+    #[test]
+    fn test_sky_light_uses_layer_above() {
+        let mut light = ChunkLight::new(2);
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(0, -64, 0), -64), 0);
+
+        let mut top = DataLayer::filled(15);
+        top.set(ChunkSectionBlockPos::new(4, 0, 5), 3);
+        light.sky[2] = Some(top);
+
+        // the first section doesn't have a layer, so it uses the bottom of the
+        // second one
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(4, -60, 5), -64), 3);
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(0, -60, 0), -64), 15);
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(4, -48, 5), -64), 3);
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(4, -47, 5), -64), 15);
+        // above the chunk
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(4, 200, 5), -64), 15);
+    }
+}
@@ -0,0 +1,688 @@
+//! Autogenerated map colors for every block state
+
+// This file is generated from codegen/lib/code/map_colors.py. If you want to
+// modify it, change that file.
+
+use azalea_block::BlockState;
+
+/// Get the ID of the base map color for the block state, or 0 if it's
+/// invisible on maps (like air and glass).
+///
+/// These are the same as the colors vanilla gives to blocks with
+/// `BlockBehaviour.Properties::mapColor`.
+pub fn block_state_map_color(state: BlockState) -> u8 {
+    match state.id {
+        8..=9 | 11243 => 1,
+        17
+        | 118..=122
+        | 174..=176
+        | 207..=209
+        | 231..=233
+        | 578..=580
+        | 4421..=4452
+        | 4865..=4872
+        | 5057..=5120
+        | 5713..=5720
+        | 5887..=5888
+        | 6032
+        | 6258..=6321
+        | 8189
+        | 8205..=8284
+        | 8520..=8599
+        | 12053..=12058
+        | 12119..=12130
+        | 12192
+        | 12227..=12258
+        | 12515..=12546
+        | 12835..=12898
+        | 13507
+        | 13559..=13561
+        | 13801..=13812
+        | 14375..=14454
+        | 14535..=14614
+        | 15125..=15136
+        | 18413..=19060
+        | 19385..=19416
+        | 21738..=21753
+        | 27576..=27578 => 2,
+        1731..=1986 | 2047 | 6910..=6973 | 21754..=21769 => 3,
+        102..=117 | 2137..=2138 | 2403..=2914 | 10022 => 4,
+        5949 | 11625 | 13552..=13555 | 13954 => 5,
+        2135
+        | 5819..=5882
+        | 8164..=8171
+        | 9906..=9917
+        | 9958..=9973
+        | 11278..=11341
+        | 19451..=19462
+        | 19516..=19523
+        | 20472
+        | 27695..=27696 => 6,
+        29..=38
+        | 41..=42
+        | 45..=84
+        | 168..=170
+        | 252..=391
+        | 420..=447
+        | 476..=559
+        | 2048..=2049
+        | 2118..=2131
+        | 4333..=4340
+        | 5951..=5966
+        | 5968..=5983
+        | 7046..=7101
+        | 7632
+        | 8193..=8204
+        | 9370..=9385
+        | 11626..=11637
+        | 13508..=13525
+        | 13958..=13969
+        | 19588..=19591
+        | 25782..=25838
+        | 25840..=25855
+        | 25857..=25912
+        | 27862..=27863 => 7,
+        2090
+        | 5941..=5948
+        | 5950
+        | 6114
+        | 11607
+        | 13581..=13586
+        | 13677..=13680
+        | 13741
+        | 13757
+        | 23331 => 8,
+        5967 | 6776..=6781 => 9,
+        2..=3
+        | 10..=11
+        | 18
+        | 177..=179
+        | 210..=212
+        | 234..=236
+        | 4341..=4348
+        | 4517..=4548
+        | 4889..=4896
+        | 5249..=5312
+        | 5737..=5744
+        | 5889..=5890
+        | 5984..=5985
+        | 6322..=6385
+        | 6774
+        | 6782..=6845
+        | 8600..=8679
+        | 12059..=12064
+        | 12259..=12290
+        | 12547..=12578
+        | 12899..=12962
+        | 13526
+        | 13975..=14054
+        | 14695..=14774
+        | 15095..=15100
+        | 15143..=15148
+        | 16469..=16792
+        | 25913..=25915 => 10,
+        1
+        | 6..=7
+        | 14
+        | 22..=24
+        | 85
+        | 124..=129
+        | 131
+        | 133
+        | 148..=150
+        | 157..=159
+        | 563
+        | 566..=577
+        | 2035..=2046
+        | 2054..=2089
+        | 2396
+        | 2916
+        | 4329
+        | 4349..=4356
+        | 4769..=4848
+        | 5817..=5818
+        | 5903..=5904
+        | 6770..=6773
+        | 7470..=7549
+        | 8172..=8179
+        | 8285
+        | 8287..=8294
+        | 8693..=9340
+        | 10024..=10033
+        | 10143..=10154
+        | 12107..=12118
+        | 12137..=12142
+        | 12149..=12154
+        | 12191
+        | 13563..=13574
+        | 14135..=14214
+        | 14295..=14374
+        | 14455..=14534
+        | 14775..=14854
+        | 14935..=15014
+        | 15107..=15112
+        | 15119..=15124
+        | 15149..=15154
+        | 15161..=15166
+        | 16145..=16468
+        | 16793..=17116
+        | 17765..=18088
+        | 19433..=19448
+        | 19480..=19483
+        | 23955
+        | 27603..=27694 => 11,
+        86..=101 | 2051..=2053 | 13773..=13799 | 13973..=13974 | 27585 => 12,
+        15
+        | 192..=194
+        | 201..=203
+        | 225..=227
+        | 581..=1730
+        | 2050
+        | 2139..=2395
+        | 2929..=3032
+        | 4332
+        | 4357..=4388
+        | 4677..=4740
+        | 4849..=4856
+        | 4929..=4992
+        | 5697..=5704
+        | 5883..=5884
+        | 5986..=6017
+        | 6130..=6193
+        | 7358..=7389
+        | 9918..=9941
+        | 9990..=10021
+        | 11638..=11957
+        | 12041..=12046
+        | 12131..=12136
+        | 13957
+        | 19417..=19432
+        | 19449..=19450
+        | 19463..=19479
+        | 20385..=20393
+        | 20434..=20457 => 13,
+        4..=5
+        | 25
+        | 43..=44
+        | 142..=144
+        | 189..=191
+        | 246..=248
+        | 4581..=4612
+        | 4905..=4912
+        | 5377..=5440
+        | 5753..=5760
+        | 5897..=5898
+        | 6578..=6641
+        | 10034..=10118
+        | 10923..=11002
+        | 11603
+        | 12083..=12088
+        | 12167..=12172
+        | 12193
+        | 12387..=12418
+        | 12675..=12706
+        | 13155..=13218
+        | 14215..=14294
+        | 14615..=14694
+        | 15015..=15094
+        | 15113..=15118
+        | 15137..=15142
+        | 15167..=15172
+        | 19061..=19384
+        | 20394..=20409
+        | 21737 => 14,
+        19
+        | 123
+        | 180..=182
+        | 237..=239
+        | 2091
+        | 2917..=2928
+        | 4453..=4484
+        | 4873..=4880
+        | 5121..=5184
+        | 5721..=5728
+        | 5891..=5892
+        | 6035..=6042
+        | 6115
+        | 6386..=6449
+        | 7044
+        | 7230..=7357
+        | 7633
+        | 10683..=10762
+        | 11608
+        | 11623
+        | 11958..=12040
+        | 12065..=12070
+        | 12173..=12184
+        | 12194
+        | 12291..=12322
+        | 12579..=12610
+        | 12963..=13026
+        | 13587..=13592
+        | 13681..=13684
+        | 13742
+        | 13758
+        | 14055..=14134
+        | 15101..=15106
+        | 15821..=16144
+        | 20458..=20459
+        | 21770..=21785
+        | 23951
+        | 23960
+        | 23964
+        | 23968
+        | 24209..=24288
+        | 24307..=24313
+        | 24320
+        | 24561..=24640
+        | 24659..=24728
+        | 24921..=24984
+        | 25177..=25240
+        | 25433..=25496
+        | 25689..=25690
+        | 25697..=25698
+        | 25705..=25708
+        | 25721..=25724
+        | 25737..=25760
+        | 27572 => 15,
+        2092
+        | 6116
+        | 11609
+        | 12185..=12190
+        | 13423..=13506
+        | 13593..=13598
+        | 13685..=13688
+        | 13743
+        | 13759
+        | 21786..=21801 => 16,
+        2093
+        | 2915
+        | 6117
+        | 11610
+        | 13599..=13604
+        | 13689..=13692
+        | 13744
+        | 13760
+        | 21802..=21817 => 17,
+        27..=28
+        | 198..=200
+        | 560..=561
+        | 2094
+        | 4645..=4676
+        | 4921..=4928
+        | 5633..=5696
+        | 5785..=5792
+        | 5901..=5902
+        | 6118
+        | 6706..=6769
+        | 11083..=11242
+        | 11604..=11606
+        | 11611
+        | 12095..=12106
+        | 12451..=12482
+        | 12739..=12770
+        | 13283..=13346
+        | 13605..=13610
+        | 13693..=13696
+        | 13745
+        | 13761
+        | 13825
+        | 13844..=13845
+        | 13864..=13865
+        | 13938..=13945
+        | 20410..=20433
+        | 21818..=21833 => 18,
+        2095
+        | 6119
+        | 7045
+        | 11612
+        | 13611..=13616
+        | 13697..=13700
+        | 13746
+        | 13762
+        | 21834..=21849 => 19,
+        39..=40
+        | 392..=419
+        | 2096
+        | 6120
+        | 11613
+        | 13617..=13622
+        | 13701..=13704
+        | 13747
+        | 13763
+        | 13822
+        | 13838..=13839
+        | 13858..=13859
+        | 13914..=13921
+        | 21850..=21865
+        | 27582..=27584 => 20,
+        213..=215
+        | 2097
+        | 6121
+        | 11614
+        | 13623..=13628
+        | 13705..=13708
+        | 13748
+        | 13764
+        | 13816..=13820
+        | 13826..=13835
+        | 13846..=13855
+        | 13866..=13905
+        | 21866..=21881
+        | 23330 => 21,
+        2098
+        | 6122
+        | 11615
+        | 13629..=13634
+        | 13709..=13712
+        | 13749
+        | 13765
+        | 20369..=20384
+        | 21882..=21897
+        | 27697..=27861 => 22,
+        2099
+        | 6123
+        | 11342
+        | 11345..=11424
+        | 11585..=11590
+        | 11616
+        | 13635..=13640
+        | 13713..=13716
+        | 13750
+        | 13766
+        | 15497..=15820
+        | 19605
+        | 19607..=19608
+        | 19651..=19677
+        | 21898..=21913
+        | 23332..=23811 => 23,
+        2100
+        | 6124
+        | 7630..=7631
+        | 11617
+        | 13353..=13422
+        | 13528..=13539
+        | 13575..=13580
+        | 13641..=13646
+        | 13717..=13720
+        | 13751
+        | 13767
+        | 13823
+        | 13840..=13841
+        | 13860..=13861
+        | 13922..=13929
+        | 21914..=21929
+        | 22044..=22093 => 24,
+        2101
+        | 6125
+        | 11618
+        | 13647..=13652
+        | 13721..=13724
+        | 13752
+        | 13768
+        | 13821
+        | 13836..=13837
+        | 13856..=13857
+        | 13906..=13913
+        | 21930..=21945 => 25,
+        21
+        | 139..=141
+        | 154..=156
+        | 186..=188
+        | 219..=221
+        | 243..=245
+        | 2102
+        | 2132
+        | 4549..=4580
+        | 4897..=4904
+        | 5313..=5376
+        | 5745..=5752
+        | 5895..=5896
+        | 6019..=6020
+        | 6126
+        | 6514..=6577
+        | 8680..=8691
+        | 10843..=10922
+        | 11619
+        | 12077..=12082
+        | 12355..=12386
+        | 12643..=12674
+        | 13091..=13154
+        | 13653..=13658
+        | 13725..=13728
+        | 13753
+        | 13769
+        | 21946..=21961 => 26,
+        448..=475
+        | 2103
+        | 6127
+        | 8181..=8188
+        | 11620
+        | 13540..=13551
+        | 13659..=13664
+        | 13729..=13732
+        | 13754
+        | 13770
+        | 13800
+        | 13946..=13953
+        | 21962..=21977
+        | 25839
+        | 25856 => 27,
+        26
+        | 195..=197
+        | 222..=224
+        | 249..=251
+        | 2104
+        | 2133
+        | 2136
+        | 4613..=4644
+        | 4913..=4920
+        | 5569..=5632
+        | 5761..=5768
+        | 5899..=5900
+        | 6128
+        | 6642..=6705
+        | 6846..=6909
+        | 7390..=7469
+        | 8159..=8163
+        | 11003..=11082
+        | 11621
+        | 12089..=12094
+        | 12143..=12148
+        | 12419..=12450
+        | 12707..=12738
+        | 13219..=13282
+        | 13557
+        | 13665..=13670
+        | 13733..=13736
+        | 13755
+        | 13771
+        | 13813..=13815
+        | 13824
+        | 13842..=13843
+        | 13862..=13863
+        | 13930..=13937
+        | 15173..=15496
+        | 19623
+        | 21978..=21993 => 28,
+        2105
+        | 2397
+        | 6021..=6026
+        | 6129
+        | 8180
+        | 8190
+        | 11622
+        | 11624
+        | 13527
+        | 13671..=13676
+        | 13737..=13740
+        | 13756
+        | 13772
+        | 20460..=20467
+        | 20473..=21386
+        | 21411..=21734
+        | 21994..=22009
+        | 23812..=23950
+        | 27570 => 29,
+        2134 | 9942..=9957 | 19484..=19515 | 27573 => 30,
+        4331 | 8692 | 11343..=11344 | 11425..=11584 | 11591..=11602 | 13955..=13956 => 31,
+        565 => 32,
+        8439 => 33,
+        12..=13
+        | 16
+        | 136..=138
+        | 145..=147
+        | 160..=167
+        | 171..=173
+        | 204..=206
+        | 228..=230
+        | 4389..=4420
+        | 4857..=4864
+        | 4993..=5056
+        | 5705..=5712
+        | 5885..=5886
+        | 6194..=6257
+        | 8440..=8519
+        | 12047..=12052
+        | 12195..=12226
+        | 12483..=12514
+        | 12771..=12834
+        | 19524..=19587 => 34,
+        135
+        | 6018
+        | 8046..=8158
+        | 10023
+        | 12161..=12166
+        | 13556
+        | 13558
+        | 14855..=14934
+        | 15155..=15160
+        | 17441..=17764
+        | 18089..=18412
+        | 19622
+        | 19624..=19650
+        | 19678
+        | 21735..=21736 => 35,
+        20
+        | 4485..=4516
+        | 4881..=4888
+        | 5185..=5248
+        | 5729..=5736
+        | 5893..=5894
+        | 6450..=6513
+        | 10155
+        | 10763..=10842
+        | 12071..=12076
+        | 12323..=12354
+        | 12611..=12642
+        | 13027..=13090
+        | 23329 => 36,
+        7634..=8045 | 10156 => 37,
+        10157 => 38,
+        10158 => 39,
+        10159 => 40,
+        10160 => 41,
+        183..=185 | 240..=242 | 10161 => 42,
+        151..=153 | 216..=218 | 10162 | 22094..=23328 => 43,
+        6775
+        | 7550..=7629
+        | 10163
+        | 12155..=12160
+        | 17117..=17440
+        | 23952
+        | 23959
+        | 23963
+        | 23967
+        | 24129..=24208
+        | 24301..=24306
+        | 24315
+        | 24319
+        | 24481..=24560
+        | 24653..=24658
+        | 24729..=24792
+        | 24985..=25048
+        | 25241..=25304
+        | 25497..=25560
+        | 25691..=25692
+        | 25699..=25700
+        | 25709..=25712
+        | 25725..=25728 => 44,
+        10164 | 25916 => 45,
+        10165 => 46,
+        10166 => 47,
+        10167 | 25761..=25781 => 48,
+        10168 => 49,
+        10169 | 27587..=27602 => 50,
+        10170 => 51,
+        19621 => 52,
+        5441..=5504
+        | 5769..=5776
+        | 19609..=19614
+        | 19679
+        | 19681..=19686
+        | 19693..=19694
+        | 19697..=19728
+        | 19761..=19824
+        | 19889..=19920
+        | 19953..=20032
+        | 20161..=20224
+        | 20289..=20320
+        | 20353..=20360 => 53,
+        19615..=19620 => 54,
+        19604
+        | 23954
+        | 23957
+        | 23961
+        | 23965
+        | 23969..=24048
+        | 24289..=24294
+        | 24316..=24317
+        | 24321..=24400
+        | 24641..=24646
+        | 24793..=24856
+        | 25049..=25112
+        | 25305..=25368
+        | 25561..=25624
+        | 25695..=25696
+        | 25703..=25704
+        | 25717..=25720
+        | 25733..=25736 => 55,
+        5505..=5568
+        | 5777..=5784
+        | 19592..=19597
+        | 19680
+        | 19687..=19692
+        | 19695..=19696
+        | 19729..=19760
+        | 19825..=19888
+        | 19921..=19952
+        | 20033..=20112
+        | 20225..=20288
+        | 20321..=20352
+        | 20361..=20368
+        | 23953
+        | 23958
+        | 23962
+        | 23966
+        | 24049..=24128
+        | 24295..=24300
+        | 24314
+        | 24318
+        | 24401..=24480
+        | 24647..=24652
+        | 24857..=24920
+        | 25113..=25176
+        | 25369..=25432
+        | 25625..=25688
+        | 25693..=25694
+        | 25701..=25702
+        | 25713..=25716
+        | 25729..=25732 => 56,
+        19598..=19603 => 57,
+        19606 => 58,
+        130 | 132 | 134 | 564 | 4330 | 5905..=5906 | 8286 | 23956 | 25917..=27569 | 27586 => 59,
+        27571 => 60,
+        7102..=7229 | 27579..=27581 => 61,
+        _ => 0,
+    }
+}
@@ -0,0 +1,390 @@
+//! Render the loaded chunks as a top-down image, like a map (the item) does.
+//!
+//! The colors are the same ones that vanilla maps use, and blocks are shaded
+//! depending on whether they're higher or lower than the block to the north of
+//! them.
+
+mod block_colors;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use azalea_block::fluid_state::{FluidKind, FluidState};
+use azalea_core::position::{ChunkBlockPos, ChunkPos};
+pub use block_colors::block_state_map_color;
+use flate2::{write::ZlibEncoder, Compression, Crc};
+
+use crate::{heightmap::HeightmapKind, Chunk, Instance};
+
+/// The base colors that can be on a map, indexed by their ID. These are the
+/// same as vanilla's `MapColor`s.
+const BASE_COLORS: [u32; 62] = [
+    0x000000, 0x7fb238, 0xf7e9a3, 0xc7c7c7, 0xff0000, 0xa0a0ff, 0xa7a7a7, 0x007c00, 0xffffff,
+    0xa4a8b8, 0x976d4d, 0x707070, 0x4040ff, 0x8f7748, 0xfffcf5, 0xd87f33, 0xb24cd8, 0x6699d8,
+    0xe5e533, 0x7fcc19, 0xf27fa5, 0x4c4c4c, 0x999999, 0x4c7f99, 0x7f3fb2, 0x334cb2, 0x664c33,
+    0x667f33, 0x993333, 0x191919, 0xfaee4d, 0x5cdbd5, 0x4a80ff, 0x00d93a, 0x815631, 0x700200,
+    0xd1b1a1, 0x9f5224, 0x95576c, 0x706c8a, 0xba8524, 0x677535, 0xa04d4e, 0x392923, 0x876b62,
+    0x575c5c, 0x7a4958, 0x4c3e5c, 0x4c3223, 0x4c522a, 0x8e3c2e, 0x251610, 0xbd3031, 0x943f61,
+    0x5c191d, 0x167e86, 0x3a8e8c, 0x562c3e, 0x14b485, 0x646464, 0xd8af93, 0x7fa796,
+];
+
+/// How much each base color is darkened by, depending on the lowest two bits
+/// of the color ID.
+const BRIGHTNESS_MULTIPLIERS: [u32; 4] = [180, 220, 255, 135];
+
+const BRIGHTNESS_LOW: u8 = 0;
+const BRIGHTNESS_NORMAL: u8 = 1;
+const BRIGHTNESS_HIGH: u8 = 2;
+
+// the IDs of the base colors that are used by name below
+const NONE: u8 = 0;
+const FIRE: u8 = 4;
+const WATER: u8 = 12;
+
+/// Convert a color ID from a map to `[r, g, b, a]`.
+///
+/// The top six bits of the ID are the base color and the bottom two bits are
+/// the brightness. Unknown colors and color 0 are transparent.
+pub fn color_id_to_rgba(color_id: u8) -> [u8; 4] {
+    let base_color_id = (color_id >> 2) as usize;
+    if base_color_id == 0 {
+        return [0, 0, 0, 0];
+    }
+    let Some(&base_color) = BASE_COLORS.get(base_color_id) else {
+        return [0, 0, 0, 0];
+    };
+    let multiplier = BRIGHTNESS_MULTIPLIERS[(color_id & 0b11) as usize];
+
+    let r = ((base_color >> 16) & 0xff) * multiplier / 255;
+    let g = ((base_color >> 8) & 0xff) * multiplier / 255;
+    let b = (base_color & 0xff) * multiplier / 255;
+    [r as u8, g as u8, b as u8, 255]
+}
+
+/// An image with 4 bytes (`[r, g, b, a]`) per pixel, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Get the color of the pixel at the given coordinates, as `[r, g, b, a]`.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (x + y * self.width) as usize * 4;
+        self.pixels[index..index + 4]
+            .try_into()
+            .expect("slice is 4 bytes long")
+    }
+
+    /// Encode the image as a PNG file.
+    pub fn to_png(&self) -> Vec<u8> {
+        let row_len = self.width as usize * 4;
+        // every row starts with a byte for the filter type, which is 0 (none)
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for row in self.pixels.chunks(row_len.max(1)) {
+            encoder
+                .write_all(&[0])
+                .and_then(|()| encoder.write_all(row))
+                .expect("writing to a Vec can't fail");
+        }
+        let image_data = encoder.finish().expect("writing to a Vec can't fail");
+
+        let mut header = Vec::with_capacity(13);
+        header.extend(self.width.to_be_bytes());
+        header.extend(self.height.to_be_bytes());
+        // 8 bits per channel, RGBA, default compression and filtering, no interlacing
+        header.extend([8, 6, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_png_chunk(&mut png, b"IHDR", &header);
+        write_png_chunk(&mut png, b"IDAT", &image_data);
+        write_png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Encode the image as a PNG file and write it to `path`.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_png())
+    }
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend(crc.sum().to_be_bytes());
+}
+
+/// The block that's visible from above in a column.
+#[derive(Clone, Copy)]
+struct Surface {
+    base_color: u8,
+    y: i32,
+    /// How many blocks of fluid there are, or 0 if the surface isn't a fluid.
+    fluid_depth: u32,
+}
+
+impl Instance {
+    /// Render the chunks from `min` to `max` (inclusive) as a top-down image
+    /// with one pixel per block, using the same colors as vanilla maps.
+    ///
+    /// North is at the top of the image. Chunks that aren't loaded are
+    /// transparent.
+    ///
+    /// ```no_run
+    /// # use azalea_world::Instance;
+    /// # use azalea_core::position::ChunkPos;
+    /// # fn example(instance: &Instance) -> std::io::Result<()> {
+    /// let image = instance.render_map(ChunkPos::new(-4, -4), ChunkPos::new(3, 3));
+    /// assert_eq!((image.width, image.height), (128, 128));
+    /// image.save_png("map.png")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn render_map(&self, min: ChunkPos, max: ChunkPos) -> RgbaImage {
+        let chunks_x = (max.x - min.x + 1).max(0) as usize;
+        let chunks_z = (max.z - min.z + 1).max(0) as usize;
+        let width = chunks_x * 16;
+        let height = chunks_z * 16;
+
+        // the first row is the row to the north of the image, which is only used for
+        // shading
+        let mut surfaces = vec![None; width * (height + 1)];
+        for chunk_z in min.z - 1..=max.z {
+            for chunk_x in min.x..=max.x {
+                let Some(chunk) = self.chunks.get(&ChunkPos::new(chunk_x, chunk_z)) else {
+                    continue;
+                };
+                let chunk = chunk.read();
+                let start_x = (chunk_x - min.x) as usize * 16;
+                for z in 0..16 {
+                    let row = (chunk_z - min.z) * 16 + z + 1;
+                    if row < 0 {
+                        continue;
+                    }
+                    for x in 0..16 {
+                        surfaces[start_x + x as usize + row as usize * width] =
+                            surface(&chunk, x, z as u8, self.chunks.min_y);
+                    }
+                }
+            }
+        }
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let color_id = match surfaces[x + (y + 1) * width] {
+                    Some(surface) => {
+                        let north = surfaces[x + y * width].map_or(surface.y, |north| north.y);
+                        let checkerboard = ((x + y) & 1) as f64;
+                        let brightness = if surface.base_color == WATER {
+                            let shade = f64::from(surface.fluid_depth) * 0.1 + checkerboard * 0.2;
+                            if shade < 0.5 {
+                                BRIGHTNESS_HIGH
+                            } else if shade > 0.9 {
+                                BRIGHTNESS_LOW
+                            } else {
+                                BRIGHTNESS_NORMAL
+                            }
+                        } else {
+                            let shade =
+                                f64::from(surface.y - north) * 4. / 5. + (checkerboard - 0.5) * 0.4;
+                            if shade > 0.6 {
+                                BRIGHTNESS_HIGH
+                            } else if shade < -0.6 {
+                                BRIGHTNESS_LOW
+                            } else {
+                                BRIGHTNESS_NORMAL
+                            }
+                        };
+                        (surface.base_color << 2) | brightness
+                    }
+                    None => 0,
+                };
+                pixels.extend(color_id_to_rgba(color_id));
+            }
+        }
+
+        RgbaImage {
+            width: width as u32,
+            height: height as u32,
+            pixels,
+        }
+    }
+}
+
+/// Find the highest block in the column that's visible on maps.
+fn surface(chunk: &Chunk, x: u8, z: u8, min_y: i32) -> Option<Surface> {
+    let mut y = chunk.highest_block_y(x, z, HeightmapKind::WorldSurface, min_y)?;
+    while y >= min_y {
+        let state = chunk.get(&ChunkBlockPos::new(x, y, z), min_y)?;
+        let fluid = FluidState::from(state);
+        if fluid.kind != FluidKind::Empty {
+            let mut fluid_depth = 1;
+            while y - fluid_depth as i32 >= min_y
+                && chunk
+                    .get(&ChunkBlockPos::new(x, y - fluid_depth as i32, z), min_y)
+                    .is_some_and(|below| FluidState::from(below).kind != FluidKind::Empty)
+            {
+                fluid_depth += 1;
+            }
+            let base_color = if fluid.kind == FluidKind::Water {
+                WATER
+            } else {
+                FIRE
+            };
+            return Some(Surface {
+                base_color,
+                y,
+                fluid_depth,
+            });
+        }
+
+        let base_color = block_state_map_color(state);
+        if base_color != NONE {
+            return Some(Surface {
+                base_color,
+                y,
+                fluid_depth: 0,
+            });
+        }
+        y -= 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_block::{
+        block_state::BlockState,
+        blocks,
+        properties::{Axis, FacingCardinal, Occupied, Part},
+    };
+    use azalea_core::position::BlockPos;
+    use azalea_registry::Block;
+
+    use super::*;
+    use crate::PartialChunkStorage;
+
+    const GRASS: u8 = 1;
+    const WOOL: u8 = 3;
+    const PLANT: u8 = 7;
+    const SNOW: u8 = 8;
+    const STONE: u8 = 11;
+    const WOOD: u8 = 13;
+    const COLOR_RED: u8 = 28;
+    const PODZOL: u8 = 34;
+
+    #[test]
+    fn test_block_colors() {
+        assert_eq!(block_state_map_color(Block::Air.into()), NONE);
+        assert_eq!(block_state_map_color(Block::Glass.into()), NONE);
+        assert_eq!(block_state_map_color(Block::GrassBlock.into()), GRASS);
+        assert_eq!(block_state_map_color(Block::Stone.into()), STONE);
+        assert_eq!(block_state_map_color(Block::OakPlanks.into()), WOOD);
+        assert_eq!(block_state_map_color(Block::OakLeaves.into()), PLANT);
+        assert_eq!(block_state_map_color(Block::WhiteWool.into()), SNOW);
+        assert_eq!(block_state_map_color(Block::LightGrayWool.into()), 22);
+        assert_eq!(block_state_map_color(Block::RedTerracotta.into()), 50);
+        assert_eq!(
+            block_state_map_color(Block::LightBlueGlazedTerracotta.into()),
+            17
+        );
+        assert_eq!(block_state_map_color(Block::Water.into()), WATER);
+    }
+
+    #[test]
+    fn test_block_state_colors() {
+        // logs are a different color on their ends
+        let log = |axis| BlockState::from(blocks::OakLog { axis });
+        assert_eq!(block_state_map_color(log(Axis::Y)), WOOD);
+        assert_eq!(block_state_map_color(log(Axis::X)), PODZOL);
+
+        // and only the foot of a bed is colored
+        let bed = |part| {
+            BlockState::from(blocks::RedBed {
+                facing: FacingCardinal::North,
+                occupied: Occupied(false),
+                part,
+            })
+        };
+        assert_eq!(block_state_map_color(bed(Part::Foot)), COLOR_RED);
+        assert_eq!(block_state_map_color(bed(Part::Head)), WOOL);
+    }
+
+    #[test]
+    fn test_render_map() {
+        let mut instance = Instance::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        for pos in [ChunkPos::new(0, 0), ChunkPos::new(0, 1)] {
+            partial_chunk_storage.set(&pos, Some(Chunk::default()), &mut instance.chunks);
+        }
+        for x in 0..16 {
+            for z in 0..32 {
+                instance
+                    .chunks
+                    .set_block_state(&BlockPos::new(x, 64, z), Block::GrassBlock.into());
+            }
+        }
+        // a step up, a block of glass that should be invisible, and some water
+        instance
+            .chunks
+            .set_block_state(&BlockPos::new(2, 65, 3), Block::Stone.into());
+        instance
+            .chunks
+            .set_block_state(&BlockPos::new(5, 70, 5), Block::Glass.into());
+        instance
+            .chunks
+            .set_block_state(&BlockPos::new(8, 64, 8), Block::Water.into());
+
+        let image = instance.render_map(ChunkPos::new(0, 0), ChunkPos::new(1, 1));
+        assert_eq!((image.width, image.height), (32, 32));
+
+        // flat ground has the normal brightness
+        assert_eq!(
+            image.pixel(0, 1),
+            color_id_to_rgba((GRASS << 2) | BRIGHTNESS_NORMAL)
+        );
+        assert_eq!(
+            image.pixel(1, 1),
+            color_id_to_rgba((GRASS << 2) | BRIGHTNESS_NORMAL)
+        );
+        assert_eq!(
+            image.pixel(2, 3),
+            color_id_to_rgba((STONE << 2) | BRIGHTNESS_HIGH)
+        );
+        // the block south of the step is lower
+        assert_eq!(
+            image.pixel(2, 4),
+            color_id_to_rgba((GRASS << 2) | BRIGHTNESS_LOW)
+        );
+        assert_eq!(image.pixel(5, 5), image.pixel(7, 5));
+        assert_eq!(
+            image.pixel(8, 8),
+            color_id_to_rgba((WATER << 2) | BRIGHTNESS_HIGH)
+        );
+        // the chunks at x=1 aren't loaded
+        assert_eq!(image.pixel(20, 20), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_png() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let png = image.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+}
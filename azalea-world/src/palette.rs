@@ -3,6 +3,7 @@ use std::io::{Cursor, Write};
 use azalea_block::block_state::BlockStateIntegerRepr;
 use azalea_buf::{AzaleaRead, AzaleaReadVar, AzaleaWrite, AzaleaWriteVar, BufReadError};
 use azalea_core::math;
+use rustc_hash::FxHashMap;
 use tracing::warn;
 
 use crate::BitStorage;
@@ -124,6 +125,17 @@ impl PalettedContainer {
     /// of things in the storage. (So for block states, it must be less than
     /// 4096).
     pub fn get_at_index(&self, index: usize) -> BlockStateIntegerRepr {
+        // fast path for containers that only have one value (like sections that are
+        // all air), since there's no storage to read from
+        if let Palette::SingleValue(value) = self.palette {
+            assert!(
+                index < self.container_type.size(),
+                "Index {index} out of bounds (must be less than {})",
+                self.container_type.size()
+            );
+            return value;
+        }
+
         // first get the palette id
         let paletted_value = self.storage.get(index);
         // and then get the value from that id
@@ -137,6 +149,98 @@ impl PalettedContainer {
         self.get_at_index(self.index_from_coords(x, y, z))
     }
 
+    /// Get every value in the container, ordered by their indexes (see
+    /// [`Self::index_from_coords`]).
+    ///
+    /// This is much faster than calling [`Self::get_at_index`] for every index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `out` doesn't have exactly as many values as the
+    /// container (4096 for block states).
+    pub fn get_all(&self, out: &mut [BlockStateIntegerRepr]) {
+        assert_eq!(
+            out.len(),
+            self.container_type.size(),
+            "Output length must be the same as the size of the container"
+        );
+        match &self.palette {
+            Palette::SingleValue(value) => out.fill(*value),
+            Palette::Global => self
+                .storage
+                .for_each(|index, value| out[index] = value as BlockStateIntegerRepr),
+            palette => self
+                .storage
+                .for_each(|index, id| out[index] = palette.value_for(id as usize)),
+        }
+    }
+
+    /// Replace every value in the container, ordered by their indexes (see
+    /// [`Self::index_from_coords`]).
+    ///
+    /// This is much faster than calling [`Self::set_at_index`] for every index,
+    /// and it always picks the smallest palette that fits the values.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `values` doesn't have exactly as many values as
+    /// the container (4096 for block states).
+    pub fn set_all(&mut self, values: &[BlockStateIntegerRepr]) {
+        let size = self.container_type.size();
+        assert_eq!(
+            values.len(),
+            size,
+            "Values length must be the same as the size of the container"
+        );
+
+        let mut palette_values = Vec::new();
+        let mut ids = FxHashMap::default();
+        let mut paletted_values = values
+            .iter()
+            .map(|&value| {
+                *ids.entry(value).or_insert_with(|| {
+                    palette_values.push(value);
+                    palette_values.len() - 1
+                }) as u64
+            })
+            .collect::<Vec<_>>();
+
+        if palette_values.len() == 1 {
+            *self = PalettedContainer {
+                bits_per_entry: 0,
+                palette: Palette::SingleValue(palette_values[0]),
+                storage: BitStorage::new(0, size, Some(vec![])).unwrap(),
+                container_type: self.container_type,
+            };
+            return;
+        }
+
+        let bits_per_entry = math::ceil_log2(palette_values.len() as u32 - 1) as u8;
+        let (bits_per_entry, palette) =
+            match PaletteKind::from_bits_and_type(bits_per_entry, &self.container_type) {
+                PaletteKind::Linear => (bits_per_entry, Palette::Linear(palette_values)),
+                PaletteKind::Hashmap => (bits_per_entry, Palette::Hashmap(palette_values)),
+                PaletteKind::SingleValue | PaletteKind::Global => {
+                    // the global palette stores the values directly, so there has to be enough
+                    // bits for the biggest one
+                    let max_value = values.iter().copied().max().unwrap_or_default();
+                    let bits_per_entry =
+                        bits_per_entry.max(math::ceil_log2(max_value.into()) as u8);
+                    paletted_values = values.iter().map(|&value| value as u64).collect();
+                    (bits_per_entry, Palette::Global)
+                }
+            };
+
+        let mut storage = BitStorage::new(bits_per_entry.into(), size, None).unwrap();
+        storage.set_all(&paletted_values);
+        *self = PalettedContainer {
+            bits_per_entry,
+            palette,
+            storage,
+            container_type: self.container_type,
+        };
+    }
+
     /// Sets the id at the given coordinates and return the previous id
     pub fn get_and_set(
         &mut self,
@@ -198,11 +302,11 @@ impl PalettedContainer {
     }
 
     fn copy_from(&mut self, palette: &Palette, storage: &BitStorage) {
-        for i in 0..storage.size() {
-            let value = palette.value_for(storage.get(i) as usize);
+        storage.for_each(|i, paletted_value| {
+            let value = palette.value_for(paletted_value as usize);
             let id = self.id_for(value) as u64;
             self.storage.set(i, id);
-        }
+        });
     }
 
     pub fn id_for(&mut self, value: BlockStateIntegerRepr) -> usize {
@@ -365,7 +469,7 @@ impl PalettedContainerKind {
         }
     }
 
-    fn size(&self) -> usize {
+    pub(crate) fn size(&self) -> usize {
         1 << (self.size_bits() * 3)
     }
 }
@@ -427,6 +531,39 @@ mod tests {
         assert_eq!(palette_container.bits_per_entry, 5);
     }
 
+    #[test]
+    fn test_get_all_and_set_all() {
+        let mut palette_container = PalettedContainer::new(PalettedContainerKind::BlockStates);
+        let mut out = vec![1; 4096];
+        palette_container.get_all(&mut out);
+        assert_eq!(out, vec![0; 4096]);
+
+        for (unique_values, palette_kind) in [
+            (1, PaletteKind::SingleValue),
+            (2, PaletteKind::Linear),
+            (16, PaletteKind::Linear),
+            (17, PaletteKind::Hashmap),
+            (256, PaletteKind::Hashmap),
+            (257, PaletteKind::Global),
+        ] {
+            let values = (0..4096)
+                .map(|i| (i % unique_values * 97) as BlockStateIntegerRepr)
+                .collect::<Vec<_>>();
+            palette_container.set_all(&values);
+            assert_eq!(PaletteKind::from(&palette_container.palette), palette_kind);
+
+            palette_container.get_all(&mut out);
+            assert_eq!(out, values);
+            for (i, &value) in values.iter().enumerate() {
+                assert_eq!(palette_container.get_at_index(i), value);
+            }
+        }
+
+        // the container can still be changed normally afterwards
+        palette_container.set_at_index(0, 1);
+        assert_eq!(palette_container.get_at_index(0), 1);
+    }
+
     #[test]
     fn test_coords_from_index() {
         let palette_container = PalettedContainer::new(PalettedContainerKind::BlockStates);
@@ -0,0 +1,752 @@
+//! Load schematics from WorldEdit (`.schem`) and Litematica (`.litematic`)
+//! files, which is useful for bots that build things.
+//!
+//! ```no_run
+//! # use azalea_core::position::BlockPos;
+//! # use azalea_world::{schematic::Structure, Instance};
+//! # fn example(instance: &Instance) -> Result<(), azalea_world::schematic::SchematicError> {
+//! let structure = Structure::load("house.schem")?;
+//! // the blocks that still have to be placed or broken, bottom layer first
+//! for difference in structure.diff(instance, BlockPos::new(0, 64, 0)) {
+//!     println!("{difference:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the blocks are loaded, so block entities (like the items in chests)
+//! and entities are ignored.
+
+use std::{
+    fs,
+    io::{self, Cursor, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use azalea_block::BlockState;
+use azalea_core::position::BlockPos;
+use flate2::read::GzDecoder;
+use simdnbt::owned::{Nbt, NbtCompound};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::Instance;
+
+#[derive(Error, Debug)]
+pub enum SchematicError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid NBT: {0}")]
+    Nbt(#[from] simdnbt::Error),
+    #[error("Invalid schematic: {0}")]
+    Invalid(String),
+    #[error("Unsupported schematic version {0}")]
+    UnsupportedVersion(i32),
+    #[error("Unknown schematic format, the file should end with .schem or .litematic")]
+    UnknownFormat,
+}
+
+/// A box of blocks, like the ones in a schematic.
+///
+/// Positions in the structure are relative to its lowest corner, so they go
+/// from 0 to [`Self::size`] (exclusive).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Structure {
+    size: BlockPos,
+    /// Where the lowest corner of the structure was relative to the origin
+    /// that it was saved with, like the player's position when it was copied
+    /// in WorldEdit.
+    pub offset: BlockPos,
+    /// The blocks, ordered by y, then z, then x.
+    blocks: Vec<BlockState>,
+}
+
+/// A block in the world that doesn't match the [`Structure`], which is
+/// returned by [`Structure::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockDifference {
+    /// The position of the block in the world.
+    pub pos: BlockPos,
+    /// The block that's in the structure.
+    pub expected: BlockState,
+    /// The block that's in the world, or `None` if the chunk isn't loaded.
+    pub actual: Option<BlockState>,
+}
+
+impl Structure {
+    /// Create a structure that's filled with air. Negative sizes are treated
+    /// as 0.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the number of blocks doesn't fit in a `usize`. Use
+    /// [`Self::try_new`] if the size comes from somewhere you don't trust.
+    pub fn new(size: BlockPos) -> Self {
+        match Self::try_new(size) {
+            Ok(structure) => structure,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Like [`Self::new`], but returns [`SchematicError::Invalid`] instead of
+    /// panicking if the structure is too big.
+    pub fn try_new(size: BlockPos) -> Result<Self, SchematicError> {
+        let size = size.max(&BlockPos::new(0, 0, 0));
+        let volume = volume(size)?;
+        Ok(Self {
+            size,
+            offset: BlockPos::default(),
+            blocks: vec![BlockState::AIR; volume],
+        })
+    }
+
+    /// The number of blocks on each side of the structure.
+    pub fn size(&self) -> BlockPos {
+        self.size
+    }
+
+    fn index(&self, pos: BlockPos) -> Option<usize> {
+        if pos.x < 0
+            || pos.y < 0
+            || pos.z < 0
+            || pos.x >= self.size.x
+            || pos.y >= self.size.y
+            || pos.z >= self.size.z
+        {
+            return None;
+        }
+        // this can't overflow since the number of blocks fits in a usize
+        let (x, y, z) = (pos.x as usize, pos.y as usize, pos.z as usize);
+        Some((y * self.size.z as usize + z) * self.size.x as usize + x)
+    }
+
+    /// Get the block at a position in the structure, or `None` if it's
+    /// outside of the structure.
+    pub fn get(&self, pos: BlockPos) -> Option<BlockState> {
+        self.index(pos).map(|index| self.blocks[index])
+    }
+
+    /// Set the block at a position in the structure, and return the block that
+    /// was there. Returns `None` and doesn't do anything if the position is
+    /// outside of the structure.
+    pub fn set(&mut self, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+        let index = self.index(pos)?;
+        Some(std::mem::replace(&mut self.blocks[index], state))
+    }
+
+    /// Iterate over every block in the structure in the order that they
+    /// should usually be built in, which is one layer at a time starting from
+    /// the bottom (so blocks like torches have something to be placed on).
+    pub fn blocks(&self) -> impl Iterator<Item = (BlockPos, BlockState)> + '_ {
+        let (size_x, size_z) = (self.size.x as usize, self.size.z as usize);
+        self.blocks.iter().enumerate().map(move |(index, state)| {
+            // every coordinate is less than the size, so they fit in an i32
+            let pos = BlockPos::new(
+                (index % size_x) as i32,
+                (index / (size_x * size_z)) as i32,
+                ((index / size_x) % size_z) as i32,
+            );
+            (pos, *state)
+        })
+    }
+
+    /// Compare the structure to the world, as if the structure's lowest corner
+    /// was at `origin`, and get the blocks that don't match. They're in the
+    /// same order as [`Self::blocks`].
+    ///
+    /// This includes blocks that should be air, so you'll probably have to
+    /// break some of them.
+    pub fn diff(&self, instance: &Instance, origin: BlockPos) -> Vec<BlockDifference> {
+        if self.blocks.is_empty() {
+            return Vec::new();
+        }
+        let mut actual_blocks = vec![None; self.blocks.len()];
+        let max = origin + self.size - BlockPos::new(1, 1, 1);
+        for (pos, state) in instance.chunks.get_block_states_in_aabb(origin, max) {
+            if let Some(index) = self.index(pos - origin) {
+                actual_blocks[index] = Some(state);
+            }
+        }
+
+        self.blocks()
+            .zip(actual_blocks)
+            .filter(|((_, expected), actual)| Some(*expected) != *actual)
+            .map(|((pos, expected), actual)| BlockDifference {
+                pos: origin + pos,
+                expected,
+                actual,
+            })
+            .collect()
+    }
+
+    /// Load a structure from a `.schem` or `.litematic` file, depending on the
+    /// file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SchematicError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("schem") => Self::read_schem(&fs::read(path)?),
+            Some("litematic") => Self::read_litematic(&fs::read(path)?),
+            _ => Err(SchematicError::UnknownFormat),
+        }
+    }
+
+    /// Read a Sponge schematic (the `.schem` files that WorldEdit uses).
+    /// Versions 2 and 3 are supported, and the data can be gzipped.
+    pub fn read_schem(data: &[u8]) -> Result<Self, SchematicError> {
+        let nbt = read_nbt(data)?;
+        // version 3 puts everything in a Schematic compound
+        let schematic = nbt.compound("Schematic").unwrap_or(&*nbt);
+
+        let version = schematic.int("Version").unwrap_or_default();
+        let (palette, block_data) = match version {
+            2 => (
+                schematic.compound("Palette"),
+                schematic.byte_array("BlockData"),
+            ),
+            3 => {
+                let blocks = schematic.compound("Blocks");
+                (
+                    blocks.and_then(|blocks| blocks.compound("Palette")),
+                    blocks.and_then(|blocks| blocks.byte_array("Data")),
+                )
+            }
+            version => return Err(SchematicError::UnsupportedVersion(version)),
+        };
+        let (Some(palette), Some(block_data)) = (palette, block_data) else {
+            return Err(SchematicError::Invalid(
+                "The schematic doesn't have a palette or block data".to_owned(),
+            ));
+        };
+
+        let dimension = |name: &str| {
+            schematic
+                .short(name)
+                .map(|n| i32::from(n as u16))
+                .ok_or_else(|| SchematicError::Invalid(format!("The schematic is missing {name}")))
+        };
+        let size = BlockPos::new(
+            dimension("Width")?,
+            dimension("Height")?,
+            dimension("Length")?,
+        );
+        // every block is at least one byte, so this stops us from allocating a lot
+        // of memory for a schematic that's lying about its size
+        if block_data.len() < volume(size)? {
+            return Err(SchematicError::Invalid(
+                "The schematic has too little block data".to_owned(),
+            ));
+        }
+        let mut structure = Structure::try_new(size)?;
+        if let Some(&[x, y, z]) = schematic.int_array("Offset") {
+            structure.offset = BlockPos::new(x, y, z);
+        }
+
+        let mut states = Vec::new();
+        for (name, index) in palette.iter() {
+            let Some(index) = index.int() else {
+                continue;
+            };
+            let index = index as usize;
+            if states.len() <= index {
+                states.resize(index + 1, BlockState::AIR);
+            }
+            states[index] = parse_block_state(&name.to_str());
+        }
+
+        let mut data = block_data;
+        for block in &mut structure.blocks {
+            let index = read_varint(&mut data).ok_or_else(|| {
+                SchematicError::Invalid("The schematic has too little block data".to_owned())
+            })?;
+            *block = states.get(index as usize).copied().ok_or_else(|| {
+                SchematicError::Invalid(format!("Palette index {index} is out of bounds"))
+            })?;
+        }
+
+        Ok(structure)
+    }
+
+    /// Read a schematic from Litematica. The data can be gzipped.
+    ///
+    /// If the schematic has several regions, they're put together in one
+    /// structure, and the parts of the structure that aren't in any region are
+    /// air.
+    pub fn read_litematic(data: &[u8]) -> Result<Self, SchematicError> {
+        let nbt = read_nbt(data)?;
+        let version = nbt.int("Version").unwrap_or_default();
+        if !(4..=7).contains(&version) {
+            return Err(SchematicError::UnsupportedVersion(version));
+        }
+        let Some(regions_nbt) = nbt.compound("Regions") else {
+            return Err(SchematicError::Invalid(
+                "The schematic doesn't have any regions".to_owned(),
+            ));
+        };
+
+        let mut regions = Vec::new();
+        for (name, region) in regions_nbt.iter() {
+            let Some(region) = region.compound() else {
+                continue;
+            };
+            let region = read_litematic_region(region).map_err(|e| match e {
+                SchematicError::Invalid(message) => {
+                    SchematicError::Invalid(format!("In region {}: {message}", name.to_str()))
+                }
+                e => e,
+            })?;
+            regions.push(region);
+        }
+
+        let Some(min) = regions
+            .iter()
+            .map(|region| region.offset)
+            .reduce(|a, b| a.min(&b))
+        else {
+            return Ok(Structure::new(BlockPos::default()));
+        };
+        let mut max = min;
+        for region in &regions {
+            let end = checked_add(region.offset, region.size).ok_or_else(|| {
+                SchematicError::Invalid("The regions are too far apart".to_owned())
+            })?;
+            max = max.max(&end);
+        }
+        let size = checked_sub(max, min)
+            .ok_or_else(|| SchematicError::Invalid("The regions are too far apart".to_owned()))?;
+
+        let mut structure = Structure::try_new(size)?;
+        structure.offset = min;
+        for region in regions {
+            let region_offset = region.offset - min;
+            for (pos, state) in region.blocks() {
+                structure.set(region_offset + pos, state);
+            }
+        }
+        Ok(structure)
+    }
+}
+
+/// Read a region from a Litematica schematic as a structure, with the offset
+/// set to the position of its lowest corner.
+fn read_litematic_region(region: &NbtCompound) -> Result<Structure, SchematicError> {
+    let vec3 = |name: &str| {
+        let nbt = region.compound(name)?;
+        Some(BlockPos::new(nbt.int("x")?, nbt.int("y")?, nbt.int("z")?))
+    };
+    let (Some(position), Some(size)) = (vec3("Position"), vec3("Size")) else {
+        return Err(SchematicError::Invalid(
+            "The region doesn't have a position or size".to_owned(),
+        ));
+    };
+    let too_big = || SchematicError::Invalid("The region is too big".to_owned());
+    // the size is negative if the region was selected from the other corner
+    let lowest_corner = |position: i32, size: i32| {
+        if size < 0 {
+            position.checked_add(size)?.checked_add(1)
+        } else {
+            Some(position)
+        }
+    };
+    let abs = |size: i32| i32::try_from(size.unsigned_abs()).ok();
+    let (Some(size_x), Some(size_y), Some(size_z)) = (abs(size.x), abs(size.y), abs(size.z)) else {
+        return Err(too_big());
+    };
+    let (Some(x), Some(y), Some(z)) = (
+        lowest_corner(position.x, size.x),
+        lowest_corner(position.y, size.y),
+        lowest_corner(position.z, size.z),
+    ) else {
+        return Err(too_big());
+    };
+    let size = BlockPos::new(size_x, size_y, size_z);
+
+    let states = region
+        .list("BlockStatePalette")
+        .and_then(|palette| palette.compounds())
+        .unwrap_or_default()
+        .iter()
+        .map(|state| {
+            let name = state
+                .string("Name")
+                .map(|name| name.to_str().into_owned())
+                .unwrap_or_default();
+            let properties = state
+                .compound("Properties")
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .filter_map(|(key, value)| {
+                            Some((
+                                key.to_str().into_owned(),
+                                value.string()?.to_str().into_owned(),
+                            ))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            block_state_from_name_and_properties(&name, &properties)
+        })
+        .collect::<Vec<_>>();
+    if states.is_empty() {
+        return Err(SchematicError::Invalid(
+            "The region has an empty palette".to_owned(),
+        ));
+    }
+
+    // unlike in chunks, the values can be split between two longs
+    let bits = (usize::BITS - (states.len() - 1).leading_zeros()).max(2) as usize;
+    let mask = (1u64 << bits) - 1;
+    let data = region.long_array("BlockStates").unwrap_or_default();
+    // this is checked before the structure is made so we don't allocate a lot of
+    // memory for a region that's lying about its size
+    let needed_bits = volume(size)?.checked_mul(bits).ok_or_else(too_big)?;
+    if data.len().saturating_mul(64) < needed_bits {
+        return Err(SchematicError::Invalid(
+            "The region has too little block data".to_owned(),
+        ));
+    }
+    let mut structure = Structure::try_new(size)?;
+    structure.offset = BlockPos::new(x, y, z);
+    for (i, block) in structure.blocks.iter_mut().enumerate() {
+        let bit_index = i * bits;
+        let long_index = bit_index / 64;
+        let bit_offset = bit_index % 64;
+        let mut value = (data[long_index] as u64) >> bit_offset;
+        if bit_offset + bits > 64 {
+            value |= (data[long_index + 1] as u64) << (64 - bit_offset);
+        }
+        let index = (value & mask) as usize;
+        *block = states.get(index).copied().ok_or_else(|| {
+            SchematicError::Invalid(format!("Palette index {index} is out of bounds"))
+        })?;
+    }
+
+    Ok(structure)
+}
+
+/// The number of blocks in a structure with the given size, which can't be
+/// negative.
+fn volume(size: BlockPos) -> Result<usize, SchematicError> {
+    let [x, y, z] = [size.x, size.y, size.z].map(|n| usize::try_from(n).unwrap_or_default());
+    x.checked_mul(y)
+        .and_then(|xy| xy.checked_mul(z))
+        .ok_or_else(|| {
+            SchematicError::Invalid(format!(
+                "The structure is too big ({} by {} by {})",
+                size.x, size.y, size.z
+            ))
+        })
+}
+
+fn checked_add(a: BlockPos, b: BlockPos) -> Option<BlockPos> {
+    Some(BlockPos::new(
+        a.x.checked_add(b.x)?,
+        a.y.checked_add(b.y)?,
+        a.z.checked_add(b.z)?,
+    ))
+}
+
+fn checked_sub(a: BlockPos, b: BlockPos) -> Option<BlockPos> {
+    Some(BlockPos::new(
+        a.x.checked_sub(b.x)?,
+        a.y.checked_sub(b.y)?,
+        a.z.checked_sub(b.z)?,
+    ))
+}
+
+/// Read NBT that might be gzipped.
+fn read_nbt(data: &[u8]) -> Result<simdnbt::owned::BaseNbt, SchematicError> {
+    let mut decompressed = Vec::new();
+    let data = if data.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        &decompressed
+    } else {
+        data
+    };
+    match simdnbt::owned::read(&mut Cursor::new(data))? {
+        Nbt::Some(nbt) => Ok(nbt),
+        Nbt::None => Err(SchematicError::Invalid(
+            "The schematic doesn't have any NBT".to_owned(),
+        )),
+    }
+}
+
+/// Parse a block state like `minecraft:oak_log[axis=x]`.
+fn parse_block_state(s: &str) -> BlockState {
+    let (name, properties) = match s.split_once('[') {
+        Some((name, properties)) => (name, properties.trim_end_matches(']')),
+        None => (s, ""),
+    };
+    let properties = properties
+        .split(',')
+        .filter_map(|property| property.split_once('='))
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect::<Vec<_>>();
+    block_state_from_name_and_properties(name, &properties)
+}
+
+fn block_state_from_name_and_properties(name: &str, properties: &[(String, String)]) -> BlockState {
+    let Ok(block) = azalea_registry::Block::from_str(name) else {
+        warn!("Unknown block {name}, replacing it with air");
+        return BlockState::AIR;
+    };
+    let properties = properties
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<Vec<_>>();
+    BlockState::from_properties(block, &properties)
+}
+
+fn read_varint(data: &mut &[u8]) -> Option<u32> {
+    let mut value = 0;
+    for i in 0..5 {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= u32::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use azalea_block::properties::Axis;
+    use azalea_registry::Block;
+    use flate2::{write::GzEncoder, Compression};
+    use simdnbt::owned::{BaseNbt, NbtList, NbtTag};
+
+    use super::*;
+    use crate::{ChunkStorage, PartialChunkStorage};
+
+    fn nbt_bytes(nbt: NbtCompound) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        BaseNbt::new("", nbt).write(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_read_schem() {
+        // 2 wide, 2 tall, and 1 long, with a log on top of stone on one side
+        let schematic = NbtCompound::from_values(vec![
+            ("Version".into(), NbtTag::Int(3)),
+            ("Width".into(), NbtTag::Short(2)),
+            ("Height".into(), NbtTag::Short(2)),
+            ("Length".into(), NbtTag::Short(1)),
+            ("Offset".into(), NbtTag::IntArray(vec![-1, 0, 3])),
+            (
+                "Blocks".into(),
+                NbtTag::Compound(NbtCompound::from_values(vec![
+                    (
+                        "Palette".into(),
+                        NbtTag::Compound(NbtCompound::from_values(vec![
+                            ("minecraft:air".into(), NbtTag::Int(0)),
+                            ("minecraft:stone".into(), NbtTag::Int(1)),
+                            ("minecraft:oak_log[axis=x]".into(), NbtTag::Int(2)),
+                        ])),
+                    ),
+                    ("Data".into(), NbtTag::ByteArray(vec![1, 0, 2, 0])),
+                ])),
+            ),
+        ]);
+        let bytes = nbt_bytes(NbtCompound::from_values(vec![(
+            "Schematic".into(),
+            NbtTag::Compound(schematic),
+        )]));
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        for data in [bytes, gzipped] {
+            let structure = Structure::read_schem(&data).unwrap();
+            assert_eq!(structure.size(), BlockPos::new(2, 2, 1));
+            assert_eq!(structure.offset, BlockPos::new(-1, 0, 3));
+            assert_eq!(
+                structure.get(BlockPos::new(0, 0, 0)),
+                Some(Block::Stone.into())
+            );
+            let log = structure.get(BlockPos::new(0, 1, 0)).unwrap();
+            assert_eq!(log.property::<Axis>(), Some(Axis::X));
+            assert_eq!(structure.get(BlockPos::new(1, 1, 0)), Some(BlockState::AIR));
+            assert_eq!(structure.get(BlockPos::new(2, 0, 0)), None);
+        }
+    }
+
+    #[test]
+    fn test_read_litematic() {
+        let block = |name: &str| {
+            NbtCompound::from_values(vec![("Name".into(), NbtTag::String(name.into()))])
+        };
+        // 5 palette entries means 3 bits per block, so some blocks are split
+        // between longs
+        let palette = vec![
+            block("minecraft:air"),
+            block("minecraft:stone"),
+            block("minecraft:dirt"),
+            block("minecraft:sand"),
+            block("minecraft:gravel"),
+        ];
+        let size = BlockPos::new(-4, 2, 3);
+        let indexes = (0..24).map(|i| i % 5).collect::<Vec<u64>>();
+        let mut data = vec![0u64; 2];
+        for (i, index) in indexes.iter().enumerate() {
+            let bit_index = i * 3;
+            data[bit_index / 64] |= index << (bit_index % 64);
+            if bit_index % 64 + 3 > 64 {
+                data[bit_index / 64 + 1] |= index >> (64 - bit_index % 64);
+            }
+        }
+        let vec3 = |pos: BlockPos| {
+            NbtTag::Compound(NbtCompound::from_values(vec![
+                ("x".into(), NbtTag::Int(pos.x)),
+                ("y".into(), NbtTag::Int(pos.y)),
+                ("z".into(), NbtTag::Int(pos.z)),
+            ]))
+        };
+        let region = NbtCompound::from_values(vec![
+            ("Position".into(), vec3(BlockPos::new(10, 0, 0))),
+            ("Size".into(), vec3(size)),
+            (
+                "BlockStatePalette".into(),
+                NbtTag::List(NbtList::Compound(palette)),
+            ),
+            (
+                "BlockStates".into(),
+                NbtTag::LongArray(data.into_iter().map(|x| x as i64).collect()),
+            ),
+        ]);
+        let bytes = nbt_bytes(NbtCompound::from_values(vec![
+            ("Version".into(), NbtTag::Int(6)),
+            (
+                "Regions".into(),
+                NbtTag::Compound(NbtCompound::from_values(vec![(
+                    "house".into(),
+                    NbtTag::Compound(region),
+                )])),
+            ),
+        ]));
+
+        let structure = Structure::read_litematic(&bytes).unwrap();
+        assert_eq!(structure.size(), BlockPos::new(4, 2, 3));
+        // the x size is negative, so the region goes from 7 to 10
+        assert_eq!(structure.offset, BlockPos::new(7, 0, 0));
+        let expected = [
+            Block::Air,
+            Block::Stone,
+            Block::Dirt,
+            Block::Sand,
+            Block::Gravel,
+        ];
+        for (i, (_, state)) in structure.blocks().enumerate() {
+            assert_eq!(state, expected[i % 5].into(), "{i}");
+        }
+    }
+
+    #[test]
+    fn test_too_big() {
+        assert!(matches!(
+            Structure::try_new(BlockPos::new(i32::MAX, i32::MAX, i32::MAX)),
+            Err(SchematicError::Invalid(_))
+        ));
+
+        // the size says there are 65535^3 blocks but there's only one byte of data
+        let schematic = NbtCompound::from_values(vec![
+            ("Version".into(), NbtTag::Int(3)),
+            ("Width".into(), NbtTag::Short(-1)),
+            ("Height".into(), NbtTag::Short(-1)),
+            ("Length".into(), NbtTag::Short(-1)),
+            (
+                "Blocks".into(),
+                NbtTag::Compound(NbtCompound::from_values(vec![
+                    (
+                        "Palette".into(),
+                        NbtTag::Compound(NbtCompound::from_values(vec![(
+                            "minecraft:air".into(),
+                            NbtTag::Int(0),
+                        )])),
+                    ),
+                    ("Data".into(), NbtTag::ByteArray(vec![0])),
+                ])),
+            ),
+        ]);
+        let bytes = nbt_bytes(NbtCompound::from_values(vec![(
+            "Schematic".into(),
+            NbtTag::Compound(schematic),
+        )]));
+        assert!(matches!(
+            Structure::read_schem(&bytes),
+            Err(SchematicError::Invalid(_))
+        ));
+
+        // i32::MIN can't be negated
+        let vec3 = |x: i32, y: i32, z: i32| {
+            NbtTag::Compound(NbtCompound::from_values(vec![
+                ("x".into(), NbtTag::Int(x)),
+                ("y".into(), NbtTag::Int(y)),
+                ("z".into(), NbtTag::Int(z)),
+            ]))
+        };
+        let region = NbtCompound::from_values(vec![
+            ("Position".into(), vec3(0, 0, 0)),
+            ("Size".into(), vec3(i32::MIN, 1, 1)),
+            (
+                "BlockStatePalette".into(),
+                NbtTag::List(NbtList::Compound(vec![NbtCompound::from_values(vec![(
+                    "Name".into(),
+                    NbtTag::String("minecraft:air".into()),
+                )])])),
+            ),
+            ("BlockStates".into(), NbtTag::LongArray(vec![0])),
+        ]);
+        assert!(matches!(
+            read_litematic_region(&region),
+            Err(SchematicError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        partial_chunk_storage.set(
+            &azalea_core::position::ChunkPos::new(0, 0),
+            Some(crate::Chunk::default()),
+            &mut chunk_storage,
+        );
+        chunk_storage.set_block_state(&BlockPos::new(15, 0, 0), Block::Stone.into());
+        chunk_storage.set_block_state(&BlockPos::new(15, 1, 0), Block::Dirt.into());
+        let instance = Instance::from(chunk_storage);
+
+        let mut structure = Structure::new(BlockPos::new(2, 2, 1));
+        structure.set(BlockPos::new(0, 0, 0), Block::Stone.into());
+        structure.set(BlockPos::new(0, 1, 0), Block::OakPlanks.into());
+
+        let diff = structure.diff(&instance, BlockPos::new(15, 0, 0));
+        assert_eq!(
+            diff,
+            vec![
+                BlockDifference {
+                    pos: BlockPos::new(16, 0, 0),
+                    expected: BlockState::AIR,
+                    // this chunk isn't loaded
+                    actual: None,
+                },
+                BlockDifference {
+                    pos: BlockPos::new(15, 1, 0),
+                    expected: Block::OakPlanks.into(),
+                    actual: Some(Block::Dirt.into()),
+                },
+                BlockDifference {
+                    pos: BlockPos::new(16, 1, 0),
+                    expected: BlockState::AIR,
+                    actual: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Copies of the chunks in an [`Instance`] that can be used (and changed)
+//! without locking the instance.
+
+use std::sync::Arc;
+
+use azalea_block::{block_state::BlockState, fluid_state::FluidState};
+use azalea_core::position::{BlockPos, ChunkBlockPos, ChunkPos};
+use nohash_hasher::IntMap;
+use parking_lot::RwLock;
+
+use crate::{Chunk, ChunkStorage, Instance};
+
+/// The chunks in an [`Instance`] at the moment the snapshot was made.
+///
+/// The snapshot doesn't change when the instance does, and it doesn't hold any
+/// locks, so it can be sent to another thread for long computations. Changing
+/// blocks in the snapshot doesn't affect the instance.
+///
+/// Making a snapshot copies every chunk in the instance, so it's about as
+/// expensive as cloning all of the chunks. After that, chunks are shared
+/// between clones of the snapshot and a chunk is only copied again when a
+/// clone changes it, so it's cheap to clone a snapshot for every job that
+/// needs to modify its own version of the world.
+#[derive(Clone, Debug)]
+pub struct InstanceSnapshot {
+    pub height: u32,
+    pub min_y: i32,
+    chunks: IntMap<ChunkPos, Arc<Chunk>>,
+}
+
+impl Instance {
+    /// Copy the loaded chunks so they can be used without holding the
+    /// instance's lock.
+    ///
+    /// The instance has to stay locked until every chunk is copied, so use
+    /// [`InstanceSnapshot::new`] instead if you have the instance's
+    /// [`RwLock`]. See [`InstanceSnapshot`] for more details.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use azalea_world::Instance;
+    /// # use azalea_core::position::BlockPos;
+    /// # use parking_lot::RwLock;
+    /// # fn example(instance: Arc<RwLock<Instance>>) {
+    /// let snapshot = instance.read().snapshot();
+    /// std::thread::spawn(move || {
+    ///     let mut snapshot = snapshot;
+    ///     snapshot.set_block_state(&BlockPos::new(0, 64, 0), azalea_block::BlockState::AIR);
+    ///     // ...
+    /// });
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> InstanceSnapshot {
+        InstanceSnapshot::from(&self.chunks)
+    }
+}
+
+impl From<&ChunkStorage> for InstanceSnapshot {
+    fn from(storage: &ChunkStorage) -> Self {
+        let chunks = storage
+            .map
+            .iter()
+            .filter_map(|(pos, chunk)| {
+                let chunk = chunk.upgrade()?;
+                let chunk = chunk.read().clone();
+                Some((*pos, Arc::new(chunk)))
+            })
+            .collect();
+        Self {
+            height: storage.height,
+            min_y: storage.min_y,
+            chunks,
+        }
+    }
+}
+
+impl InstanceSnapshot {
+    /// Copy the chunks in the instance, only locking it for as long as it
+    /// takes to find them.
+    ///
+    /// Each loaded chunk is only locked while it's being copied. If
+    /// `include_cached_chunks` is true, chunks that are in the instance's
+    /// [`ChunkCache`](crate::chunk_cache::ChunkCache) are also added, and
+    /// those are copied while the instance is still locked.
+    pub fn new(instance: &RwLock<Instance>, include_cached_chunks: bool) -> Self {
+        let (height, min_y, loaded, mut chunks) = {
+            let instance = instance.read();
+            let storage = &instance.chunks;
+            let loaded = storage
+                .map
+                .iter()
+                .filter_map(|(pos, chunk)| Some((*pos, chunk.upgrade()?)))
+                .collect::<Vec<_>>();
+            let mut cached = IntMap::default();
+            if include_cached_chunks {
+                for (pos, chunk) in storage.cache.chunks() {
+                    cached.insert(pos, Arc::new(chunk.clone()));
+                }
+            }
+            (storage.height, storage.min_y, loaded, cached)
+        };
+        for (pos, chunk) in loaded {
+            let chunk = chunk.read().clone();
+            // a loaded chunk is always newer than a cached one
+            chunks.insert(pos, Arc::new(chunk));
+        }
+        Self {
+            height,
+            min_y,
+            chunks,
+        }
+    }
+
+    pub fn get(&self, pos: &ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(pos).map(Arc::as_ref)
+    }
+
+    /// Get a mutable reference to the chunk, which copies it first if it's
+    /// shared with another snapshot.
+    pub fn get_mut(&mut self, pos: &ChunkPos) -> Option<&mut Chunk> {
+        self.chunks.get_mut(pos).map(Arc::make_mut)
+    }
+
+    /// Add a chunk to the snapshot, or replace the chunk that's already there.
+    pub fn insert(&mut self, pos: ChunkPos, chunk: Chunk) {
+        self.chunks.insert(pos, Arc::new(chunk));
+    }
+
+    pub fn remove(&mut self, pos: &ChunkPos) -> Option<Chunk> {
+        self.chunks.remove(pos).map(Arc::unwrap_or_clone)
+    }
+
+    /// The chunks in the snapshot and their positions, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.chunks
+            .iter()
+            .map(|(pos, chunk)| (*pos, chunk.as_ref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get_block_state(&self, pos: &BlockPos) -> Option<BlockState> {
+        let chunk = self.get(&ChunkPos::from(pos))?;
+        chunk.get(&ChunkBlockPos::from(pos), self.min_y)
+    }
+
+    pub fn get_fluid_state(&self, pos: &BlockPos) -> Option<FluidState> {
+        self.get_block_state(pos).map(FluidState::from)
+    }
+
+    /// Change a block in the snapshot and return the state that was there
+    /// before, or `None` if the chunk isn't in the snapshot.
+    pub fn set_block_state(&mut self, pos: &BlockPos, state: BlockState) -> Option<BlockState> {
+        if pos.y < self.min_y || pos.y >= (self.min_y + self.height as i32) {
+            return None;
+        }
+        let min_y = self.min_y;
+        let chunk = self.get_mut(&ChunkPos::from(pos))?;
+        Some(chunk.get_and_set(&ChunkBlockPos::from(pos), state, min_y))
+    }
+
+    /// Make a [`ChunkStorage`] with the chunks in the snapshot, for code that
+    /// needs one (like `azalea`'s physics simulations).
+    ///
+    /// Chunks are moved into the storage, so only the ones that are shared
+    /// with a clone of this snapshot have to be copied.
+    ///
+    /// Since a [`ChunkStorage`] only keeps weak references to its chunks, the
+    /// chunks are unloaded when the returned `Vec` is dropped, so keep it
+    /// alive for as long as you're using the storage.
+    pub fn into_chunk_storage(self) -> (ChunkStorage, Vec<Arc<RwLock<Chunk>>>) {
+        let mut storage = ChunkStorage::new(self.height, self.min_y);
+        let chunks = self
+            .chunks
+            .into_iter()
+            .map(|(pos, chunk)| storage.insert(pos, Arc::unwrap_or_clone(chunk)))
+            .collect();
+        (storage, chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_registry::Block;
+
+    use super::*;
+    use crate::PartialChunkStorage;
+
+    #[test]
+    fn test_snapshot_is_independent() {
+        let mut instance = Instance::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        partial_chunk_storage.set(
+            &ChunkPos::new(0, 0),
+            Some(Chunk::default()),
+            &mut instance.chunks,
+        );
+        let pos = BlockPos::new(1, 64, 1);
+        instance.chunks.set_block_state(&pos, Block::Stone.into());
+
+        let mut snapshot = instance.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot.get_block_state(&pos), Some(Block::Stone.into()));
+
+        // changing the instance doesn't change the snapshot
+        instance.chunks.set_block_state(&pos, Block::Dirt.into());
+        assert_eq!(snapshot.get_block_state(&pos), Some(Block::Stone.into()));
+
+        // and changing a clone of the snapshot doesn't change the original
+        let mut fork = snapshot.clone();
+        fork.set_block_state(&pos, BlockState::AIR);
+        assert_eq!(fork.get_block_state(&pos), Some(BlockState::AIR));
+        assert_eq!(snapshot.get_block_state(&pos), Some(Block::Stone.into()));
+        assert_eq!(instance.get_block_state(&pos), Some(Block::Dirt.into()));
+
+        snapshot.set_block_state(&pos, Block::GrassBlock.into());
+        assert_eq!(
+            snapshot.set_block_state(&BlockPos::new(100, 64, 0), BlockState::AIR),
+            None
+        );
+        let (storage, _chunks) = snapshot.into_chunk_storage();
+        assert_eq!(
+            storage.get_block_state(&pos),
+            Some(Block::GrassBlock.into())
+        );
+        // the fork still has its own copy of the chunk
+        assert_eq!(fork.get_block_state(&pos), Some(BlockState::AIR));
+    }
+
+    #[test]
+    fn test_snapshot_with_cached_chunks() {
+        let instance = RwLock::new(Instance::default());
+        instance.write().chunks.cache.set_max_memory(usize::MAX);
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        let (loaded_pos, cached_pos) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+        for pos in [loaded_pos, cached_pos] {
+            partial_chunk_storage.set(&pos, Some(Chunk::default()), &mut instance.write().chunks);
+        }
+        // unloading the chunk puts it in the cache
+        let chunk = partial_chunk_storage
+            .limited_get_mut(&cached_pos)
+            .and_then(Option::take)
+            .unwrap();
+        instance.write().chunks.unload(cached_pos, chunk);
+        assert!(instance.read().chunks.get_cached(&cached_pos).is_some());
+
+        let snapshot = InstanceSnapshot::new(&instance, false);
+        assert!(snapshot.get(&loaded_pos).is_some());
+        assert!(snapshot.get(&cached_pos).is_none());
+
+        let snapshot = InstanceSnapshot::new(&instance, true);
+        assert!(snapshot.get(&loaded_pos).is_some());
+        assert!(snapshot.get(&cached_pos).is_some());
+    }
+}
@@ -7,13 +7,14 @@ use std::{
 
 use azalea_block::fluid_state::FluidState;
 use azalea_block::BlockState;
-use azalea_core::position::{BlockPos, ChunkPos};
+use azalea_core::position::{BlockPos, ChunkBlockPos, ChunkPos};
 use azalea_core::registry_holder::RegistryHolder;
 use bevy_ecs::{component::Component, entity::Entity};
 use derive_more::{Deref, DerefMut};
 use nohash_hasher::IntMap;
+use uuid::Uuid;
 
-use crate::{ChunkStorage, PartialChunkStorage};
+use crate::{heightmap::HeightmapKind, light::LightKind, ChunkStorage, PartialChunkStorage};
 
 /// PartialInstances are usually owned by clients, and hold strong references to
 /// chunks and entities in [`Instance`]s.
@@ -97,6 +98,12 @@ pub struct Instance {
     /// players.
     pub entity_by_id: IntMap<MinecraftEntityId, Entity>,
 
+    /// An index of UUIDs to Azalea ECS entities.
+    ///
+    /// This is per-instance rather than global since local players that don't
+    /// share an instance each have their own entity for the same player.
+    pub entity_by_uuid: HashMap<Uuid, Entity>,
+
     pub registries: RegistryHolder,
 }
 
@@ -112,6 +119,58 @@ impl Instance {
     pub fn set_block_state(&self, pos: &BlockPos, state: BlockState) -> Option<BlockState> {
         self.chunks.set_block_state(pos, state)
     }
+
+    /// Get the position of the highest block at the x and z coordinates that
+    /// counts for the heightmap kind, like the surface that mobs can stand on
+    /// for [`HeightmapKind::MotionBlocking`].
+    ///
+    /// Returns `None` if the chunk isn't loaded or there aren't any blocks like
+    /// that in the column.
+    ///
+    /// ```
+    /// # use azalea_world::{Instance, heightmap::HeightmapKind};
+    /// # fn example(instance: &Instance) {
+    /// if let Some(surface) = instance.top_block_at(0, 0, HeightmapKind::MotionBlocking) {
+    ///     println!("The surface at 0, 0 is at y={}", surface.y);
+    /// }
+    /// # }
+    /// ```
+    pub fn top_block_at(&self, x: i32, z: i32, kind: HeightmapKind) -> Option<BlockPos> {
+        self.chunks.top_block_at(x, z, kind)
+    }
+
+    /// Get the sky light level at the position, or `None` if the chunk isn't
+    /// loaded. See [`ChunkLight::get_sky_light`] for how this works when the
+    /// server didn't send us the light there.
+    ///
+    /// [`ChunkLight::get_sky_light`]: crate::light::ChunkLight::get_sky_light
+    ///
+    /// This is the light level as if it's day, so it doesn't get lower at
+    /// night.
+    pub fn sky_light(&self, pos: &BlockPos) -> Option<u8> {
+        self.chunks.get_light(LightKind::Sky, pos)
+    }
+
+    /// Get the light level from blocks like torches at the position, or `None`
+    /// if the chunk isn't loaded.
+    pub fn block_light(&self, pos: &BlockPos) -> Option<u8> {
+        self.chunks.get_light(LightKind::Block, pos)
+    }
+
+    /// Get the highest of the sky and block light levels at the position, or
+    /// `None` if the chunk isn't loaded.
+    ///
+    /// Note that since 1.18, whether hostile mobs can spawn only depends on
+    /// the [block light](Self::block_light).
+    pub fn light_level(&self, pos: &BlockPos) -> Option<u8> {
+        let chunk = self.chunks.get(&ChunkPos::from(pos))?;
+        let chunk = chunk.read();
+        let pos = ChunkBlockPos::from(pos);
+        Some(u8::max(
+            chunk.light.get_sky_light(&pos, self.chunks.min_y),
+            chunk.light.get_block_light(&pos, self.chunks.min_y),
+        ))
+    }
 }
 
 impl Debug for PartialInstance {
@@ -144,6 +203,7 @@ impl From<ChunkStorage> for Instance {
             chunks,
             entities_by_chunk: HashMap::new(),
             entity_by_id: IntMap::default(),
+            entity_by_uuid: HashMap::new(),
             registries: RegistryHolder::default(),
         }
     }
@@ -116,6 +116,79 @@ fn generate_mining_world(
     (chunks, start, end)
 }
 
+/// Rolling hills with some stone pillars in the way, which is closer to what
+/// the pathfinder usually has to deal with than a flat world.
+fn generate_terrain_world(
+    partial_chunks: &mut PartialChunkStorage,
+    size: u32,
+) -> (ChunkStorage, BlockPos, BlockPos) {
+    let size = size as i32;
+
+    let mut chunks = ChunkStorage::default();
+    for chunk_x in -size..size {
+        for chunk_z in -size..size {
+            let chunk_pos = ChunkPos::new(chunk_x, chunk_z);
+            partial_chunks.set(&chunk_pos, Some(Chunk::default()), &mut chunks);
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let height_at = |x: i32, z: i32| {
+        let hills = (x as f64 / 9.).sin() * 3. + (z as f64 / 7.).cos() * 3.;
+        8 + hills.round() as i32
+    };
+
+    for chunk_x in -size..size {
+        for chunk_z in -size..size {
+            let chunk_pos = ChunkPos::new(chunk_x, chunk_z);
+            let chunk = chunks.get(&chunk_pos).unwrap();
+            let mut chunk = chunk.write();
+            for x in 0..16_u8 {
+                for z in 0..16_u8 {
+                    let surface_y = height_at(chunk_x * 16 + x as i32, chunk_z * 16 + z as i32);
+                    for y in 0..surface_y {
+                        let block = if y == surface_y - 1 {
+                            azalea_registry::Block::GrassBlock
+                        } else {
+                            azalea_registry::Block::Dirt
+                        };
+                        chunk.set(&ChunkBlockPos::new(x, y, z), block.into(), chunks.min_y);
+                    }
+                    // keep the pillars away from the start and end so they're not on top of one
+                    let near_edge = (chunk_x * 16 + x as i32).abs() > 56
+                        || (chunk_z * 16 + z as i32).abs() > 56;
+                    if rng.gen_bool(0.02) && !near_edge {
+                        for y in surface_y..surface_y + 3 {
+                            chunk.set(
+                                &ChunkBlockPos::new(x, y, z),
+                                azalea_registry::Block::Stone.into(),
+                                chunks.min_y,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut start = BlockPos::new(-60, 20, -60);
+    // move start down until it's on a solid block
+    while chunks.get_block_state(&start).unwrap().is_air() {
+        start = start.down(1);
+    }
+    start = start.up(1);
+
+    let mut end = BlockPos::new(60, 20, 60);
+    // move end down until it's on a solid block
+    while chunks.get_block_state(&end).unwrap().is_air() {
+        end = end.down(1);
+    }
+    end = end.up(1);
+
+    (chunks, start, end)
+}
+
 fn run_pathfinder_benchmark(
     b: &mut Bencher<'_>,
     generate_world: fn(&mut PartialChunkStorage, u32) -> (ChunkStorage, BlockPos, BlockPos),
@@ -159,6 +232,9 @@ fn bench_pathfinder(c: &mut Criterion) {
     // c.bench_function("bedrock", |b| {
     //     run_pathfinder_benchmark(b, generate_bedrock_world);
     // });
+    c.bench_function("terrain", |b| {
+        run_pathfinder_benchmark(b, generate_terrain_world);
+    });
     let mut slow_group = c.benchmark_group("slow");
     slow_group.sample_size(10);
     slow_group.bench_function("mining", |b| {
@@ -0,0 +1,32 @@
+//! A bot that joins a practice server running in the same process, so you
+//! don't need a vanilla server to try it out.
+
+use azalea::{practice_server::PracticeServer, prelude::*};
+
+#[tokio::main]
+async fn main() {
+    let server = PracticeServer::start().await.unwrap();
+
+    ClientBuilder::new()
+        .set_handler(handle)
+        .start(Account::offline("bot"), server.address())
+        .await
+        .unwrap();
+}
+
+#[derive(Default, Clone, Component)]
+pub struct State {}
+
+async fn handle(bot: Client, event: Event, _state: State) -> anyhow::Result<()> {
+    match event {
+        Event::Login => {
+            bot.chat("Hello, world!");
+        }
+        Event::Chat(m) => {
+            println!("{}", m.message().to_ansi());
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Join a server with lots of bots and measure how well the swarm keeps up.
+//!
+//! ## Usage
+//!
+//! ```sh
+//! cargo run --release --example stress_test --features profiling,practice-server -- [bots] [address] [phase seconds]
+//! ```
+//!
+//! - `bots`: how many offline-mode bots to join with. Defaults to 50.
+//! - `address`: the server to join. If it's not given, a practice server is
+//!   started in the same process.
+//! - `phase seconds`: how long each phase lasts. Defaults to 30.
+//!
+//! The bots go through these phases, with each one adding more activity:
+//!
+//! 1. Standing still.
+//! 2. Walking and jumping around randomly.
+//! 3. Chatting every few seconds.
+//! 4. Sprinting in a straight line, so new chunks have to be loaded.
+//!
+//! Every 5 seconds, a line of CSV is printed for every bot with the metrics
+//! from [`azalea::profiling`] since the last report, along with the CPU time
+//! and memory that the whole process is using.
+//!
+//! The lock columns only count the ECS locks that the handler takes with
+//! `BotMetrics::lock_ecs`, not the ones taken inside azalea. The process
+//! columns are for the whole process, so they include the practice server if
+//! it's running in it, and they're left empty on platforms other than Linux.
+
+use std::{
+    env,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use azalea::{
+    entity::Position,
+    practice_server::PracticeServer,
+    prelude::*,
+    profiling::{BotMetrics, ProcessStats},
+    reconnect::ReconnectPolicy,
+    rng::RngClientExt,
+    swarm::prelude::*,
+    SprintDirection, WalkDirection,
+};
+use parking_lot::Mutex;
+use rand::Rng;
+
+/// How often the metrics are printed.
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[tokio::main]
+async fn main() {
+    let mut args = env::args().skip(1);
+    let bot_count = args
+        .next()
+        .map(|bots| bots.parse().expect("bots must be a number"))
+        .unwrap_or(50);
+    let address = args.next();
+    let phase_length = Duration::from_secs(
+        args.next()
+            .map(|seconds| seconds.parse().expect("phase seconds must be a number"))
+            .unwrap_or(30),
+    );
+
+    // the practice server has to be kept alive until we're done
+    let practice_server;
+    let address = match address {
+        Some(address) => address,
+        None => {
+            practice_server = PracticeServer::start().await.unwrap();
+            practice_server.address().to_string()
+        }
+    };
+
+    let start = Instant::now();
+    let mut builder = SwarmBuilder::new()
+        .set_handler(handle)
+        .set_swarm_handler(swarm_handle)
+        // bots keep their state when they reconnect, so their metrics aren't lost
+        .reconnect(ReconnectPolicy::fixed(Duration::from_secs(5)).jitter(Duration::from_secs(1)))
+        .rng_seed(0);
+    let mut all_metrics = Vec::new();
+    for index in 0..bot_count {
+        let username = format!("stress{index}");
+        let state = State {
+            index,
+            metrics: BotMetrics::default(),
+            start: Some(start),
+            phase_length,
+            walk_direction: Arc::new(Mutex::new(WalkDirection::None)),
+        };
+        all_metrics.push((username.clone(), state.metrics.clone()));
+        builder = builder.add_account_with_state(Account::offline(&username), state);
+    }
+
+    tokio::spawn(report_metrics(all_metrics, start, phase_length));
+
+    builder
+        .join_delay(Duration::from_millis(50))
+        .start(address.as_str())
+        .await
+        .unwrap();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    Movement,
+    Chat,
+    ChunkLoading,
+}
+
+impl Phase {
+    fn at(elapsed: Duration, phase_length: Duration) -> Self {
+        match elapsed.as_secs_f64() / phase_length.as_secs_f64().max(1.) {
+            n if n < 1. => Self::Idle,
+            n if n < 2. => Self::Movement,
+            n if n < 3. => Self::Chat,
+            _ => Self::ChunkLoading,
+        }
+    }
+}
+
+#[derive(Component, Clone, Default)]
+struct State {
+    index: usize,
+    metrics: BotMetrics,
+    start: Option<Instant>,
+    phase_length: Duration,
+    walk_direction: Arc<Mutex<WalkDirection>>,
+}
+
+#[derive(Resource, Clone, Default)]
+struct SwarmState;
+
+async fn handle(bot: Client, event: Event, state: State) -> anyhow::Result<()> {
+    let metrics = state.metrics.clone();
+    metrics.time_handler(handle_inner(bot, event, state)).await
+}
+
+async fn handle_inner(mut bot: Client, event: Event, state: State) -> anyhow::Result<()> {
+    let Event::Tick = event else {
+        return Ok(());
+    };
+    let elapsed = state.start.map(|start| start.elapsed()).unwrap_or_default();
+    let phase = Phase::at(elapsed, state.phase_length);
+
+    // this is the kind of thing that handlers usually do, and it's where
+    // we'd have to wait if the ecs is too busy
+    let tick = {
+        let ecs = state.metrics.lock_ecs(&bot);
+        let _position = ecs.get::<Position>(bot.entity).copied();
+        (elapsed.as_millis() / 50) as usize + state.index
+    };
+
+    match phase {
+        Phase::Idle => {}
+        Phase::Movement | Phase::Chat => {
+            if tick % 40 == 0 {
+                // the bots' rngs are seeded, so every run of the stress test moves the same way
+                let (direction, y_rot, jumping) = bot.with_rng(|rng| {
+                    (
+                        rng.gen_range(0..4),
+                        rng.gen_range(-180.0..180.0),
+                        rng.gen_bool(0.5),
+                    )
+                });
+                let direction = match direction {
+                    0 => WalkDirection::Forward,
+                    1 => WalkDirection::Backward,
+                    2 => WalkDirection::Left,
+                    _ => WalkDirection::Right,
+                };
+                *state.walk_direction.lock() = direction;
+                bot.walk(direction);
+                bot.set_direction(y_rot, 0.);
+                bot.set_jumping(jumping);
+            }
+            if phase == Phase::Chat && tick % 100 == 0 {
+                bot.chat(&format!("stress test message from bot {}", state.index));
+            }
+        }
+        Phase::ChunkLoading => {
+            if *state.walk_direction.lock() != WalkDirection::Forward {
+                *state.walk_direction.lock() = WalkDirection::Forward;
+                // spread the bots out so they don't all load the same chunks
+                let y_rot = (state.index as f32 * 137.5) % 360. - 180.;
+                bot.set_direction(y_rot, 0.);
+                bot.set_jumping(false);
+                bot.sprint(SprintDirection::Forward);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn swarm_handle(_swarm: Swarm, event: SwarmEvent, _state: SwarmState) -> anyhow::Result<()> {
+    if let SwarmEvent::Disconnect(account, _join_opts, reason) = event {
+        let reason = reason.map(|reason| reason.to_ansi()).unwrap_or_default();
+        eprintln!("{} got disconnected: {reason}", account.username);
+    }
+    Ok(())
+}
+
+async fn report_metrics(
+    metrics: Vec<(String, BotMetrics)>,
+    start: Instant,
+    phase_length: Duration,
+) {
+    println!(
+        "seconds,phase,bot,handler_calls,handler_ms,handler_lock_count,handler_lock_wait_ms,\
+         max_handler_lock_wait_ms,process_cpu_ms,process_memory_mb"
+    );
+
+    if ProcessStats::current().is_none() {
+        eprintln!("Process stats are only available on Linux, so they'll be left empty");
+    }
+
+    let mut interval = tokio::time::interval(REPORT_INTERVAL);
+    // the first tick finishes immediately
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let elapsed = start.elapsed();
+        let phase = Phase::at(elapsed, phase_length);
+        let (process_cpu, process_memory) = match ProcessStats::current() {
+            Some(process) => (
+                process.cpu_time.as_millis().to_string(),
+                (process.resident_memory / 1024 / 1024).to_string(),
+            ),
+            None => Default::default(),
+        };
+        for (username, metrics) in &metrics {
+            let data = metrics.take();
+            println!(
+                "{},{phase:?},{username},{},{:.3},{},{:.3},{:.3},{},{}",
+                elapsed.as_secs(),
+                data.handler_calls,
+                data.handler_time.as_secs_f64() * 1000.,
+                data.handler_lock_count,
+                data.handler_lock_wait.as_secs_f64() * 1000.,
+                data.max_handler_lock_wait.as_secs_f64() * 1000.,
+                process_cpu,
+                process_memory,
+            );
+        }
+    }
+}
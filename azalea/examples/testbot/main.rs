@@ -189,8 +189,11 @@ async fn handle(bot: Client, event: azalea::Event, state: State) -> anyhow::Resu
 }
 async fn swarm_handle(swarm: Swarm, event: SwarmEvent, _state: SwarmState) -> anyhow::Result<()> {
     match &event {
-        SwarmEvent::Disconnect(account, join_opts) => {
-            println!("bot got kicked! {}", account.username);
+        SwarmEvent::Disconnect(account, join_opts, reason) => {
+            match reason {
+                Some(reason) => println!("{} got kicked: {}", account.username, reason.to_ansi()),
+                None => println!("{} got disconnected!", account.username),
+            }
             tokio::time::sleep(Duration::from_secs(5)).await;
             swarm
                 .add_and_retry_forever_with_opts(account, State::default(), join_opts)
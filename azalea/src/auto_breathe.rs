@@ -0,0 +1,443 @@
+//! Make bots swim up to the surface (or make an air pocket) before they drown.
+
+use azalea_block::fluid_state::{FluidKind, FluidState};
+use azalea_client::{
+    interact::BlockInteractEvent,
+    inventory::{Inventory, SetSelectedHotbarSlotEvent},
+    movement::local_player_ai_step,
+    InstanceHolder, PhysicsState, SwimDirection,
+};
+use azalea_core::{position::BlockPos, tick::GameTick};
+use azalea_entity::{metadata::AirSupply, EyeHeight, FluidOnEyes, LocalEntity, Physics, Position};
+use azalea_registry::tags;
+use azalea_world::Instance;
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::{
+    app::{App, Plugin},
+    pathfinder::{GotoEvent, Pathfinder, StopPathfindingEvent},
+};
+
+/// The maximum amount of air that a player can have, in ticks.
+pub const MAX_AIR_SUPPLY: i32 = 300;
+
+/// A rough (pessimistic) estimate of how many ticks it takes to swim up one
+/// block.
+const TICKS_PER_BLOCK_SWUM: i32 = 10;
+
+/// How far up we look for the surface before giving up and assuming that it's
+/// too far away.
+const MAX_SURFACE_SEARCH_DISTANCE: u32 = 64;
+
+/// A plugin that makes bots swim up when they're about to run out of air, and
+/// then go back to what they were doing once they can breathe again.
+///
+/// If the surface is too far away (or there's a block in the way) and the bot
+/// has a door in its hotbar, it'll place the door where it's standing to make
+/// an air pocket instead.
+///
+/// Bots that were pathfinding stop while they're getting air, and then start
+/// going to their goal again once they're done.
+///
+/// This isn't included in the default plugins, so you have to add it yourself:
+///
+/// ```no_run
+/// # use azalea::{prelude::*, auto_breathe::AutoBreathePlugin};
+/// let client_builder = ClientBuilder::new().add_plugins(AutoBreathePlugin::default());
+/// ```
+#[derive(Clone)]
+pub struct AutoBreathePlugin {
+    /// Start swimming up once our air supply goes below this many ticks.
+    ///
+    /// This defaults to 100 (5 seconds), which is enough to get up through
+    /// around 10 blocks of water.
+    pub min_air_supply: i32,
+}
+impl Default for AutoBreathePlugin {
+    fn default() -> Self {
+        Self {
+            min_air_supply: 100,
+        }
+    }
+}
+impl Plugin for AutoBreathePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MinAirSupply(self.min_air_supply))
+            .add_systems(GameTick, auto_breathe.before(local_player_ai_step));
+    }
+}
+
+/// The amount of air that bots will try to keep. See
+/// [`AutoBreathePlugin::min_air_supply`].
+#[derive(Resource, Clone, Debug)]
+pub struct MinAirSupply(pub i32);
+
+/// A component that's present on bots that are currently getting air. It
+/// contains what the bot was doing before, so we can go back to it once we're
+/// done.
+#[derive(Component, Clone)]
+pub struct Surfacing {
+    pub previous_swim_direction: SwimDirection,
+    pub previous_floating: bool,
+    /// The pathfinder goal that we stopped going to, so it can be resumed.
+    pub previous_goto: Option<GotoEvent>,
+    /// Whether we already tried to place a door to make an air pocket.
+    pub placed_air_pocket: bool,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn auto_breathe(
+    mut query: Query<
+        (
+            Entity,
+            &AirSupply,
+            &FluidOnEyes,
+            &mut PhysicsState,
+            &Position,
+            &EyeHeight,
+            &Physics,
+            &InstanceHolder,
+            Option<&Inventory>,
+            Option<&Pathfinder>,
+            Option<&mut Surfacing>,
+        ),
+        With<LocalEntity>,
+    >,
+    min_air_supply: Res<MinAirSupply>,
+    mut commands: Commands,
+    mut goto_events: EventWriter<GotoEvent>,
+    mut stop_pathfinding_events: EventWriter<StopPathfindingEvent>,
+    mut set_selected_hotbar_slot_events: EventWriter<SetSelectedHotbarSlotEvent>,
+    mut block_interact_events: EventWriter<BlockInteractEvent>,
+) {
+    for (
+        entity,
+        air_supply,
+        fluid_on_eyes,
+        mut physics_state,
+        position,
+        eye_height,
+        physics,
+        instance_holder,
+        inventory,
+        pathfinder,
+        surfacing,
+    ) in &mut query
+    {
+        let underwater = **fluid_on_eyes == FluidKind::Water;
+
+        let Some(mut surfacing) = surfacing else {
+            if underwater && **air_supply < min_air_supply.0 {
+                debug!("{entity:?} is running out of air, swimming up");
+
+                let previous_goto = pathfinder.and_then(|pathfinder| {
+                    let goto = GotoEvent {
+                        entity,
+                        goal: pathfinder.goal.clone()?,
+                        successors_fn: pathfinder.successors_fn?,
+                        allow_mining: pathfinder.allow_mining,
+                        min_timeout: pathfinder.min_timeout?,
+                        max_timeout: pathfinder.max_timeout?,
+                    };
+                    stop_pathfinding_events.send(StopPathfindingEvent {
+                        entity,
+                        force: true,
+                    });
+                    Some(goto)
+                });
+
+                commands.entity(entity).insert(Surfacing {
+                    previous_swim_direction: physics_state.swim_direction,
+                    previous_floating: physics_state.floating,
+                    previous_goto,
+                    placed_air_pocket: false,
+                });
+                physics_state.swim_direction = SwimDirection::Up;
+            }
+            continue;
+        };
+
+        if !underwater {
+            if **air_supply < MAX_AIR_SUPPLY {
+                // our head is out of the water (or we're in an air pocket), so stay
+                // where we are until we've fully caught our breath
+                physics_state.swim_direction = SwimDirection::None;
+                physics_state.floating = true;
+                continue;
+            }
+
+            debug!("{entity:?} got its air back, resuming what it was doing");
+            physics_state.swim_direction = surfacing.previous_swim_direction;
+            physics_state.floating = surfacing.previous_floating;
+            if let Some(goto) = surfacing.previous_goto.take() {
+                goto_events.send(goto);
+            }
+            commands.entity(entity).remove::<Surfacing>();
+            continue;
+        }
+
+        physics_state.swim_direction = SwimDirection::Up;
+
+        if surfacing.placed_air_pocket || !physics.on_ground() {
+            continue;
+        }
+        let Some(inventory) = inventory else {
+            continue;
+        };
+        let eye_block = BlockPos::from(position.up(f64::from(eye_height)));
+        let surface = {
+            let instance = instance_holder.instance.read();
+            distance_to_surface(&instance, eye_block)
+        };
+        let needs_air_pocket = match surface {
+            Some(Surface::Above(blocks)) => blocks as i32 * TICKS_PER_BLOCK_SWUM > **air_supply,
+            Some(Surface::Blocked) => true,
+            None => false,
+        };
+        if !needs_air_pocket {
+            continue;
+        }
+
+        if tags::items::DOORS.contains(&inventory.held_item().kind()) {
+            debug!("{entity:?} can't reach the surface in time, placing a door");
+            // placing a door where we're standing replaces the water in our feet and head
+            // blocks with air
+            block_interact_events.send(BlockInteractEvent {
+                entity,
+                position: BlockPos::from(position).down(1),
+            });
+            surfacing.placed_air_pocket = true;
+        } else if let Some(slot) = door_hotbar_slot(inventory) {
+            // the door will be placed next tick once it's being held
+            set_selected_hotbar_slot_events.send(SetSelectedHotbarSlotEvent { entity, slot });
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Surface {
+    /// There's air this many blocks above the given position.
+    Above(u32),
+    /// There's a block in the way or the surface is too far away, so we can't
+    /// swim up.
+    Blocked,
+}
+
+/// Find how far up we'd have to swim from the given position to get to air.
+///
+/// Returns `None` if we don't know because the chunks above us aren't loaded.
+fn distance_to_surface(instance: &Instance, from: BlockPos) -> Option<Surface> {
+    for distance in 0..MAX_SURFACE_SEARCH_DISTANCE {
+        let block_state = instance.get_block_state(&from.up(distance as i32))?;
+        if block_state.is_air() {
+            return Some(Surface::Above(distance));
+        }
+        if FluidState::from(block_state).kind != FluidKind::Water {
+            return Some(Surface::Blocked);
+        }
+    }
+    Some(Surface::Blocked)
+}
+
+/// Get the index of the first hotbar slot that has a door in it.
+fn door_hotbar_slot(inventory: &Inventory) -> Option<u8> {
+    let menu = &inventory.inventory_menu;
+    menu.slots()[menu.hotbar_slots_range()]
+        .iter()
+        .position(|item| tags::items::DOORS.contains(&item.kind()))
+        .map(|slot| slot as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use azalea_block::BlockState;
+    use azalea_core::{position::ChunkPos, resource_location::ResourceLocation};
+    use azalea_entity::{EntityDimensions, LookDirection};
+    use azalea_inventory::{ItemStack, ItemStackData};
+    use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
+    use bevy_ecs::schedule::Schedule;
+    use parking_lot::RwLock;
+
+    use super::*;
+    use crate::pathfinder::{astar::PathfinderTimeout, goals::BlockPosGoal, moves};
+
+    /// Make a world where the given range of y levels is filled with water and
+    /// everything above it is air. The partial chunk storage has to be kept
+    /// alive since the instance only has a weak reference to the chunk.
+    fn water_world(
+        water: std::ops::Range<i32>,
+        ceiling: Option<i32>,
+    ) -> (Arc<RwLock<Instance>>, PartialChunkStorage) {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut instance = Instance::from(ChunkStorage::default());
+        partial_chunks.set(
+            &ChunkPos::new(0, 0),
+            Some(Chunk::default()),
+            &mut instance.chunks,
+        );
+        let water_state = BlockState::from(azalea_registry::Block::Water);
+        for y in water {
+            instance.set_block_state(&BlockPos::new(0, y, 0), water_state);
+        }
+        if let Some(y) = ceiling {
+            instance.set_block_state(
+                &BlockPos::new(0, y, 0),
+                azalea_block::blocks::Stone {}.into(),
+            );
+        }
+        (Arc::new(RwLock::new(instance)), partial_chunks)
+    }
+
+    fn spawn_player(world: &mut World, instance: Arc<RwLock<Instance>>, air: i32) -> Entity {
+        let position = azalea_core::position::Vec3::new(0.5, 1., 0.5);
+        let mut physics = Physics::new(
+            EntityDimensions::from(azalea_registry::EntityKind::Player),
+            position,
+        );
+        physics.set_on_ground(true);
+        let entity = world
+            .spawn((
+                LocalEntity,
+                AirSupply(air),
+                FluidOnEyes::new(FluidKind::Water),
+                PhysicsState::default(),
+                Position::new(position),
+                EyeHeight::new(1.62),
+                physics,
+                LookDirection::default(),
+            ))
+            .id();
+        world
+            .entity_mut(entity)
+            .insert(InstanceHolder::new(entity, instance));
+        entity
+    }
+
+    fn world_with_events() -> World {
+        let mut world = World::new();
+        world.insert_resource(MinAirSupply(100));
+        world.init_resource::<Events<GotoEvent>>();
+        world.init_resource::<Events<StopPathfindingEvent>>();
+        world.init_resource::<Events<SetSelectedHotbarSlotEvent>>();
+        world.init_resource::<Events<BlockInteractEvent>>();
+        world
+    }
+
+    fn run(world: &mut World) {
+        let mut schedule = Schedule::default();
+        schedule.add_systems(auto_breathe);
+        schedule.run(world);
+    }
+
+    #[test]
+    fn test_distance_to_surface() {
+        let (instance, _partial_chunks) = water_world(0..10, None);
+        let instance = instance.read();
+        assert_eq!(
+            distance_to_surface(&instance, BlockPos::new(0, 2, 0)),
+            Some(Surface::Above(8))
+        );
+        assert_eq!(
+            distance_to_surface(&instance, BlockPos::new(0, 10, 0)),
+            Some(Surface::Above(0))
+        );
+        assert_eq!(
+            distance_to_surface(&instance, BlockPos::new(16, 2, 0)),
+            None
+        );
+
+        let (instance, _partial_chunks) = water_world(0..10, Some(5));
+        assert_eq!(
+            distance_to_surface(&instance.read(), BlockPos::new(0, 2, 0)),
+            Some(Surface::Blocked)
+        );
+    }
+
+    #[test]
+    fn test_surface_and_resume_pathfinding() {
+        let (instance, _partial_chunks) = water_world(0..10, None);
+        let mut world = world_with_events();
+        let entity = spawn_player(&mut world, instance, 50);
+        world.entity_mut(entity).insert(Pathfinder {
+            goal: Some(Arc::new(BlockPosGoal(BlockPos::new(10, 0, 0)))),
+            successors_fn: Some(moves::default_move),
+            allow_mining: false,
+            min_timeout: Some(PathfinderTimeout::Time(Duration::from_secs(1))),
+            max_timeout: Some(PathfinderTimeout::Time(Duration::from_secs(5))),
+            ..Default::default()
+        });
+
+        run(&mut world);
+        assert_eq!(
+            world.get::<PhysicsState>(entity).unwrap().swim_direction,
+            SwimDirection::Up
+        );
+        let stop_events = world.resource::<Events<StopPathfindingEvent>>();
+        assert_eq!(stop_events.iter_current_update_events().count(), 1);
+
+        // our head is out of the water, but we're still catching our breath
+        *world.get_mut::<FluidOnEyes>(entity).unwrap() = FluidOnEyes::new(FluidKind::Empty);
+        run(&mut world);
+        let physics_state = world.get::<PhysicsState>(entity).unwrap();
+        assert_eq!(physics_state.swim_direction, SwimDirection::None);
+        assert!(physics_state.floating);
+        assert!(world.get::<Surfacing>(entity).is_some());
+
+        world.get_mut::<AirSupply>(entity).unwrap().0 = MAX_AIR_SUPPLY;
+        run(&mut world);
+        let physics_state = world.get::<PhysicsState>(entity).unwrap();
+        assert_eq!(physics_state.swim_direction, SwimDirection::None);
+        assert!(!physics_state.floating);
+        assert!(world.get::<Surfacing>(entity).is_none());
+        let goto_events = world.resource::<Events<GotoEvent>>();
+        let goto = goto_events.iter_current_update_events().next().unwrap();
+        assert_eq!(goto.entity, entity);
+        assert!(!goto.allow_mining);
+    }
+
+    #[test]
+    fn test_place_door_when_surface_is_blocked() {
+        let (instance, _partial_chunks) = water_world(0..10, Some(5));
+        let mut world = world_with_events();
+        let entity = spawn_player(&mut world, instance, 50);
+        let mut inventory = Inventory::default();
+        let hotbar_start = inventory.inventory_menu.hotbar_slots_range().start;
+        *inventory.inventory_menu.slot_mut(hotbar_start + 3).unwrap() =
+            ItemStack::Present(ItemStackData {
+                kind: azalea_registry::Item::OakDoor,
+                count: 1,
+                components: Default::default(),
+            });
+        world.entity_mut(entity).insert(inventory);
+
+        // start surfacing
+        run(&mut world);
+        // select the door
+        run(&mut world);
+        let hotbar_events = world.resource::<Events<SetSelectedHotbarSlotEvent>>();
+        assert_eq!(
+            hotbar_events
+                .iter_current_update_events()
+                .map(|e| e.slot)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        world
+            .get_mut::<Inventory>(entity)
+            .unwrap()
+            .selected_hotbar_slot = 3;
+
+        // place it
+        run(&mut world);
+        let interact_events = world.resource::<Events<BlockInteractEvent>>();
+        let positions = interact_events
+            .iter_current_update_events()
+            .map(|e| e.position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![BlockPos::new(0, 0, 0)]);
+        assert!(world.get::<Surfacing>(entity).unwrap().placed_air_pocket);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use azalea_client::{
     packet_handling::{death_event_on_0_health, game::DeathEvent},
     respawn::{perform_respawn, PerformRespawnEvent},
@@ -8,26 +10,73 @@ use bevy_ecs::prelude::*;
 use crate::app::{App, Plugin};
 
 /// A plugin that makes [`DeathEvent`]s send [`PerformRespawnEvent`]s.
+///
+/// You can make bots wait before respawning by setting [`Self::delay`]:
+///
+/// ```
+/// # use std::time::Duration;
+/// # use azalea::{auto_respawn::AutoRespawnPlugin, DefaultBotPlugins};
+/// # use azalea::app::PluginGroup;
+/// DefaultBotPlugins.build().set(AutoRespawnPlugin {
+///     delay: Duration::from_secs(5),
+/// });
+/// ```
 #[derive(Clone, Default)]
-pub struct AutoRespawnPlugin;
+pub struct AutoRespawnPlugin {
+    /// How long to wait after dying before respawning.
+    pub delay: Duration,
+}
 impl Plugin for AutoRespawnPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            auto_respawn
-                .before(perform_respawn)
-                .after(death_event_on_0_health),
-        );
+        app.insert_resource(AutoRespawnDelay(self.delay))
+            .add_systems(
+                Update,
+                (auto_respawn, respawn_when_ready)
+                    .chain()
+                    .before(perform_respawn)
+                    .after(death_event_on_0_health),
+            );
     }
 }
 
+/// The resource that contains how long bots wait after dying before
+/// respawning. You can change this at runtime.
+#[derive(Resource, Clone, Debug)]
+pub struct AutoRespawnDelay(pub Duration);
+
+/// A component that's present on bots that are dead and are going to respawn
+/// at the given time.
+#[derive(Component, Clone, Debug)]
+pub struct RespawnAt(pub Instant);
+
 fn auto_respawn(
     mut events: EventReader<DeathEvent>,
-    mut perform_respawn_events: EventWriter<PerformRespawnEvent>,
+    query: Query<(), With<RespawnAt>>,
+    delay: Res<AutoRespawnDelay>,
+    mut commands: Commands,
 ) {
     for event in events.read() {
-        perform_respawn_events.send(PerformRespawnEvent {
-            entity: event.entity,
-        });
+        // we might get multiple death events for the same death
+        if query.get(event.entity).is_ok() {
+            continue;
+        }
+        commands
+            .entity(event.entity)
+            .insert(RespawnAt(Instant::now() + delay.0));
+    }
+}
+
+fn respawn_when_ready(
+    query: Query<(Entity, &RespawnAt)>,
+    mut perform_respawn_events: EventWriter<PerformRespawnEvent>,
+    mut commands: Commands,
+) {
+    let now = Instant::now();
+    for (entity, respawn_at) in &query {
+        if now < respawn_at.0 {
+            continue;
+        }
+        perform_respawn_events.send(PerformRespawnEvent { entity });
+        commands.entity(entity).remove::<RespawnAt>();
     }
 }
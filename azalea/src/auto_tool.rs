@@ -1,7 +1,7 @@
 use azalea_block::{fluid_state::FluidKind, Block, BlockState};
 use azalea_client::{inventory::Inventory, Client};
-use azalea_entity::{FluidOnEyes, Physics};
-use azalea_inventory::{components, ItemStack, Menu};
+use azalea_entity::{effects::ActiveEffects, Attributes, FluidOnEyes, Physics};
+use azalea_inventory::{ItemMatcher, ItemStack, Menu};
 
 #[derive(Debug)]
 pub struct BestToolResult {
@@ -11,24 +11,67 @@ pub struct BestToolResult {
 
 pub trait AutoToolClientExt {
     fn best_tool_in_hotbar_for_block(&self, block: BlockState) -> BestToolResult;
+    fn best_matching_tool_in_hotbar_for_block(
+        &self,
+        block: BlockState,
+        matcher: &ItemMatcher,
+    ) -> BestToolResult;
 }
 
 impl AutoToolClientExt for Client {
     fn best_tool_in_hotbar_for_block(&self, block: BlockState) -> BestToolResult {
+        self.best_matching_tool_in_hotbar_for_block(block, &ItemMatcher::new())
+    }
+
+    /// Like [`Self::best_tool_in_hotbar_for_block`], but only items that match
+    /// are considered. Mining with an empty hand is always an option.
+    ///
+    /// ```
+    /// # use azalea::{prelude::*, auto_tool::AutoToolClientExt, BlockPos};
+    /// # use azalea_inventory::ItemMatcher;
+    /// # fn example(bot: &Client, pos: BlockPos) {
+    /// let block = bot.world().read().get_block_state(&pos).unwrap_or_default();
+    /// // don't wear out tools that are about to break
+    /// let best = bot.best_matching_tool_in_hotbar_for_block(
+    ///     block,
+    ///     &ItemMatcher::new().min_durability(10),
+    /// );
+    /// # }
+    /// ```
+    fn best_matching_tool_in_hotbar_for_block(
+        &self,
+        block: BlockState,
+        matcher: &ItemMatcher,
+    ) -> BestToolResult {
         let mut ecs = self.ecs.lock();
-        let (inventory, physics, fluid_on_eyes) =
-            self.query::<(&Inventory, &Physics, &FluidOnEyes)>(&mut ecs);
+        let (inventory, physics, fluid_on_eyes, attributes, active_effects) =
+            self.query::<(
+                &Inventory,
+                &Physics,
+                &FluidOnEyes,
+                &Attributes,
+                &ActiveEffects,
+            )>(&mut ecs);
         let menu = &inventory.inventory_menu;
 
-        accurate_best_tool_in_hotbar_for_block(block, menu, physics, fluid_on_eyes)
+        accurate_best_matching_tool_in_hotbar_for_block(
+            block,
+            menu,
+            matcher,
+            physics,
+            fluid_on_eyes,
+            attributes,
+            active_effects,
+        )
     }
 }
 
 /// Returns the best tool in the hotbar for the given block.
 ///
 /// Note that this doesn't take into account whether the player is on the ground
-/// or in water, use [`accurate_best_tool_in_hotbar_for_block`] instead if you
-/// care about those things.
+/// or in water or has effects like haste, use
+/// [`accurate_best_tool_in_hotbar_for_block`] instead if you care about those
+/// things.
 pub fn best_tool_in_hotbar_for_block(block: BlockState, menu: &Menu) -> BestToolResult {
     let mut physics = Physics::default();
     physics.set_on_ground(true);
@@ -38,6 +81,8 @@ pub fn best_tool_in_hotbar_for_block(block: BlockState, menu: &Menu) -> BestTool
         menu,
         &physics,
         &FluidOnEyes::new(FluidKind::Empty),
+        &Attributes::default(),
+        &ActiveEffects::default(),
     )
 }
 
@@ -46,7 +91,35 @@ pub fn accurate_best_tool_in_hotbar_for_block(
     menu: &Menu,
     physics: &Physics,
     fluid_on_eyes: &FluidOnEyes,
+    attributes: &Attributes,
+    active_effects: &ActiveEffects,
+) -> BestToolResult {
+    accurate_best_matching_tool_in_hotbar_for_block(
+        block,
+        menu,
+        &ItemMatcher::new(),
+        physics,
+        fluid_on_eyes,
+        attributes,
+        active_effects,
+    )
+}
+
+/// Like [`accurate_best_tool_in_hotbar_for_block`], but items that don't match
+/// are treated like empty slots.
+pub fn accurate_best_matching_tool_in_hotbar_for_block(
+    block: BlockState,
+    menu: &Menu,
+    matcher: &ItemMatcher,
+    physics: &Physics,
+    fluid_on_eyes: &FluidOnEyes,
+    attributes: &Attributes,
+    active_effects: &ActiveEffects,
 ) -> BestToolResult {
+    // tools that will break the next time they're used are only picked if
+    // they're faster than everything else
+    let not_about_to_break = ItemMatcher::new().min_durability(2);
+
     let hotbar_slots = &menu.slots()[menu.hotbar_slots_range()];
 
     let mut best_speed = 0.;
@@ -66,7 +139,7 @@ pub fn accurate_best_tool_in_hotbar_for_block(
         };
     }
 
-    // find the first slot that has an item without durability
+    // find the first slot that has an item that isn't about to break
     for (i, item_slot) in hotbar_slots.iter().enumerate() {
         let this_item_speed;
         match item_slot {
@@ -77,18 +150,32 @@ pub fn accurate_best_tool_in_hotbar_for_block(
                     menu,
                     fluid_on_eyes,
                     physics,
+                    attributes,
+                    active_effects,
+                ));
+            }
+            ItemStack::Present(item_stack) if !matcher.matches_data(item_stack) => {
+                // items we aren't allowed to use are the same as an empty hand
+                this_item_speed = Some(azalea_entity::mining::get_mine_progress(
+                    block.as_ref(),
+                    azalea_registry::Item::Air,
+                    menu,
+                    fluid_on_eyes,
+                    physics,
+                    attributes,
+                    active_effects,
                 ));
             }
             ItemStack::Present(item_stack) => {
-                // lazy way to avoid checking durability since azalea doesn't have durability
-                // data yet
-                if !item_stack.components.has::<components::Damage>() {
+                if not_about_to_break.matches_data(item_stack) {
                     this_item_speed = Some(azalea_entity::mining::get_mine_progress(
                         block.as_ref(),
                         item_stack.kind,
                         menu,
                         fluid_on_eyes,
                         physics,
+                        attributes,
+                        active_effects,
                     ));
                 } else {
                     this_item_speed = None;
@@ -105,6 +192,9 @@ pub fn accurate_best_tool_in_hotbar_for_block(
 
     // now check every item
     for (i, item_slot) in hotbar_slots.iter().enumerate() {
+        if !matcher.matches(item_slot) {
+            continue;
+        }
         if let ItemStack::Present(item_slot) = item_slot {
             let this_item_speed = azalea_entity::mining::get_mine_progress(
                 block.as_ref(),
@@ -112,6 +202,8 @@ pub fn accurate_best_tool_in_hotbar_for_block(
                 menu,
                 fluid_on_eyes,
                 physics,
+                attributes,
+                active_effects,
             );
             if this_item_speed > best_speed {
                 best_slot = Some(i);
@@ -1,17 +1,16 @@
-use std::f64::consts::PI;
-
 use azalea_client::interact::SwingArmEvent;
 use azalea_client::mining::Mining;
-use azalea_client::TickBroadcast;
+use azalea_client::movement_arbiter::{MovementArbiter, MovementPriority, MovementRequest};
+use azalea_client::{InstanceHolder, SprintDirection, TickBroadcast, WalkDirection};
 use azalea_core::position::{BlockPos, Vec3};
+use azalea_core::rotation;
 use azalea_core::tick::GameTick;
 use azalea_entity::{
-    clamp_look_direction, metadata::Player, EyeHeight, Jumping, LocalEntity, LookDirection,
-    Position,
+    clamp_look_direction, metadata::Player, Jumping, LocalEntity, LookDirection, Physics, Position,
 };
-use azalea_physics::PhysicsSet;
+use azalea_physics::{collision::BlockWithShape, PhysicsSet};
 use bevy_app::Update;
-use bevy_ecs::prelude::Event;
+use bevy_ecs::prelude::{Event, EventWriter, ResMut};
 use bevy_ecs::schedule::IntoSystemConfigs;
 use futures_lite::Future;
 use tracing::trace;
@@ -19,7 +18,9 @@ use tracing::trace;
 use crate::accept_resource_packs::AcceptResourcePacksPlugin;
 use crate::app::{App, Plugin, PluginGroup, PluginGroupBuilder};
 use crate::auto_respawn::AutoRespawnPlugin;
+use crate::chat_triggers::ChatTriggersPlugin;
 use crate::container::ContainerPlugin;
+use crate::death_recovery::DeathRecoveryPlugin;
 use crate::ecs::{
     component::Component,
     entity::Entity,
@@ -27,7 +28,13 @@ use crate::ecs::{
     query::{With, Without},
     system::{Commands, Query},
 };
+use crate::inventory_layout::InventoryLayoutPlugin;
+use crate::look_arbiter::{resolve_look_requests, LookArbiter, LookPriority, LookTarget};
+use crate::movement_safety::MovementSafetyPlugin;
 use crate::pathfinder::PathfinderPlugin;
+use crate::pets::PetsPlugin;
+use crate::redstone::RedstonePlugin;
+use crate::rng::RngPlugin;
 
 #[derive(Clone, Default)]
 pub struct BotPlugin;
@@ -35,19 +42,34 @@ impl Plugin for BotPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LookAtEvent>()
             .add_event::<JumpEvent>()
+            .add_event::<WalkTowardsEvent>()
+            .add_event::<JumpTowardsEvent>()
+            .init_resource::<LookArbiter>()
             .add_systems(
                 Update,
                 (
                     insert_bot,
-                    look_at_listener.before(clamp_look_direction),
+                    (look_at_listener, resolve_look_requests)
+                        .chain()
+                        .before(clamp_look_direction),
                     jump_listener,
+                    walk_towards_listener,
+                    jump_towards_listener,
                 ),
             )
             .add_systems(
                 GameTick,
-                stop_jumping
-                    .after(PhysicsSet)
-                    .after(azalea_client::movement::send_player_input_packet),
+                (
+                    stop_jumping
+                        .after(PhysicsSet)
+                        .after(azalea_client::movement::send_player_input_packet),
+                    tick_walk_towards
+                        .after(PhysicsSet)
+                        .after(azalea_client::movement::send_position),
+                    tick_jump_towards
+                        .after(PhysicsSet)
+                        .after(azalea_client::movement::send_position),
+                ),
             );
     }
 }
@@ -91,6 +113,45 @@ pub trait BotClientExt {
     ///
     /// [`look_at`]: crate::prelude::BotClientExt::look_at
     fn mine(&mut self, position: BlockPos) -> impl Future<Output = ()> + Send;
+    /// Look towards and walk to a nearby position in a straight line, jumping
+    /// over any obstacles in the way.
+    ///
+    /// This doesn't do any pathfinding, so it's cheap but can easily get stuck.
+    /// Use [`PathfinderClientExt::goto`] if you need to go somewhere that's
+    /// not in a straight line.
+    ///
+    /// [`PathfinderClientExt::goto`]: crate::pathfinder::PathfinderClientExt::goto
+    fn walk_towards(&mut self, position: Vec3);
+    /// Same as [`walk_towards`](Self::walk_towards), but sprint instead of
+    /// walking.
+    fn sprint_towards(&mut self, position: Vec3);
+    /// Stop walking towards the position that was given to
+    /// [`walk_towards`](Self::walk_towards) or
+    /// [`sprint_towards`](Self::sprint_towards).
+    fn stop_walking_towards(&mut self);
+    /// Turn to face the given direction and immediately jump in it, walking
+    /// forward until we land.
+    ///
+    /// Only the horizontal part of the direction is used, and it doesn't have
+    /// to be normalized.
+    ///
+    /// ```
+    /// # use azalea::{prelude::*, Vec3};
+    /// # fn example(bot: &mut Client) {
+    /// // jump east
+    /// bot.jump_towards(Vec3::new(1., 0., 0.));
+    /// # }
+    /// ```
+    fn jump_towards(&mut self, direction: Vec3);
+    /// Sprint towards the given position and jump at the last moment before
+    /// walking off the block we're standing on, which is how you'd jump
+    /// across a gap.
+    ///
+    /// You can check whether a jump like this would work with
+    /// [`simulation::can_sprint_jump_across`].
+    ///
+    /// [`simulation::can_sprint_jump_across`]: crate::pathfinder::simulation::can_sprint_jump_across
+    fn sprint_jump_towards(&mut self, position: Vec3);
 }
 
 impl BotClientExt for azalea_client::Client {
@@ -148,6 +209,47 @@ impl BotClientExt for azalea_client::Client {
             }
         }
     }
+
+    fn walk_towards(&mut self, position: Vec3) {
+        self.ecs.lock().send_event(WalkTowardsEvent {
+            entity: self.entity,
+            target: Some(WalkingTowards {
+                position,
+                sprint: false,
+            }),
+        });
+    }
+
+    fn sprint_towards(&mut self, position: Vec3) {
+        self.ecs.lock().send_event(WalkTowardsEvent {
+            entity: self.entity,
+            target: Some(WalkingTowards {
+                position,
+                sprint: true,
+            }),
+        });
+    }
+
+    fn stop_walking_towards(&mut self) {
+        self.ecs.lock().send_event(WalkTowardsEvent {
+            entity: self.entity,
+            target: None,
+        });
+    }
+
+    fn jump_towards(&mut self, direction: Vec3) {
+        self.ecs.lock().send_event(JumpTowardsEvent {
+            entity: self.entity,
+            jump: JumpingTowards::in_direction(direction, false),
+        });
+    }
+
+    fn sprint_jump_towards(&mut self, position: Vec3) {
+        self.ecs.lock().send_event(JumpTowardsEvent {
+            entity: self.entity,
+            jump: JumpingTowards::to_position(position, true),
+        });
+    }
 }
 
 /// Event to jump once.
@@ -169,47 +271,270 @@ pub fn jump_listener(
 }
 
 /// Make an entity look towards a certain position in the world.
+///
+/// This is submitted to the [`LookArbiter`] with [`LookPriority::DEFAULT`].
 #[derive(Event)]
 pub struct LookAtEvent {
     pub entity: Entity,
     /// The position we want the entity to be looking at.
     pub position: Vec3,
 }
-fn look_at_listener(
-    mut events: EventReader<LookAtEvent>,
-    mut query: Query<(&Position, &EyeHeight, &mut LookDirection)>,
+fn look_at_listener(mut events: EventReader<LookAtEvent>, mut look_arbiter: ResMut<LookArbiter>) {
+    for event in events.read() {
+        trace!("look at {:?}", event.position);
+        look_arbiter.submit(
+            event.entity,
+            LookPriority::DEFAULT,
+            LookTarget::Position(event.position),
+        );
+    }
+}
+
+/// How close we have to get to the target of [`WalkingTowards`] before we stop.
+const WALK_TOWARDS_REACHED_DISTANCE: f64 = 0.3;
+
+/// A component that's present on bots that are walking towards a position
+/// with [`BotClientExt::walk_towards`].
+#[derive(Component, Clone, Debug)]
+pub struct WalkingTowards {
+    pub position: Vec3,
+    /// Whether we should sprint instead of walking.
+    pub sprint: bool,
+}
+
+/// Start or stop walking towards a position in a straight line.
+#[derive(Event)]
+pub struct WalkTowardsEvent {
+    pub entity: Entity,
+    /// The position to walk towards, or `None` to stop walking.
+    pub target: Option<WalkingTowards>,
+}
+
+fn walk_towards_listener(
+    mut commands: Commands,
+    mut events: EventReader<WalkTowardsEvent>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
+) {
+    for event in events.read() {
+        if let Some(target) = &event.target {
+            commands.entity(event.entity).insert(target.clone());
+        } else {
+            commands.entity(event.entity).remove::<WalkingTowards>();
+            movement_arbiter.withdraw(event.entity, MovementPriority::PATHFINDER);
+        }
+    }
+}
+
+/// Turn towards the target of [`WalkingTowards`] and keep walking, jumping if
+/// something's in the way.
+#[allow(clippy::type_complexity)]
+pub fn tick_walk_towards(
+    mut commands: Commands,
+    query: Query<(Entity, &WalkingTowards, &Position, &Physics)>,
+    mut look_arbiter: ResMut<LookArbiter>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
+    mut jump_events: EventWriter<JumpEvent>,
 ) {
+    for (entity, walking_towards, position, physics) in &query {
+        let target = walking_towards.position;
+        if position.horizontal_distance_squared_to(&target) < WALK_TOWARDS_REACHED_DISTANCE.powi(2)
+        {
+            commands.entity(entity).remove::<WalkingTowards>();
+            continue;
+        }
+
+        // only change the yaw so we're not staring at the ground
+        look_arbiter.submit(
+            entity,
+            LookPriority::MOVEMENT,
+            LookTarget::YRot(direction_looking_at(position, &target).y_rot),
+        );
+
+        movement_arbiter.submit(
+            entity,
+            MovementPriority::PATHFINDER,
+            forward_request(walking_towards.sprint),
+        );
+
+        if physics.horizontal_collision && physics.on_ground() {
+            jump_events.send(JumpEvent { entity });
+        }
+    }
+}
+
+/// A component that's present on bots that are doing a single jump with
+/// [`BotClientExt::jump_towards`] or [`BotClientExt::sprint_jump_towards`].
+///
+/// It's removed when the bot lands.
+#[derive(Component, Clone, Debug)]
+pub struct JumpingTowards {
+    /// The horizontal direction that we're jumping in. This is ignored if
+    /// there's a `target`.
+    pub direction: Vec3,
+    /// The position that we're trying to land on, if there is one. We turn
+    /// towards it every tick and stop accelerating once we're above it.
+    pub target: Option<Vec3>,
+    /// If this is true, we sprint until we're at the edge of the block we're
+    /// standing on before jumping. Otherwise, we jump immediately.
+    pub sprint: bool,
+    /// Whether we've already left the ground.
+    pub jumped: bool,
+}
+impl JumpingTowards {
+    /// Jump in a direction without a specific place to land.
+    pub fn in_direction(direction: Vec3, sprint: bool) -> Self {
+        Self {
+            direction: direction.xz().normalize(),
+            target: None,
+            sprint,
+            jumped: false,
+        }
+    }
+
+    /// Jump towards a position and try to land on it.
+    pub fn to_position(target: Vec3, sprint: bool) -> Self {
+        Self {
+            direction: Vec3::default(),
+            target: Some(target),
+            sprint,
+            jumped: false,
+        }
+    }
+
+    /// The horizontal direction that a player at `position` should be moving
+    /// in, which is normalized unless it's zero.
+    pub fn direction_from(&self, position: &Vec3) -> Vec3 {
+        match self.target {
+            Some(target) => (&target - position).xz().normalize(),
+            None => self.direction,
+        }
+    }
+}
+
+/// Start a single jump towards a position.
+#[derive(Event)]
+pub struct JumpTowardsEvent {
+    pub entity: Entity,
+    pub jump: JumpingTowards,
+}
+
+fn jump_towards_listener(mut commands: Commands, mut events: EventReader<JumpTowardsEvent>) {
     for event in events.read() {
-        if let Ok((position, eye_height, mut look_direction)) = query.get_mut(event.entity) {
-            let new_look_direction =
-                direction_looking_at(&position.up(eye_height.into()), &event.position);
-            trace!(
-                "look at {:?} (currently at {:?})",
-                event.position,
-                **position
+        commands
+            .entity(event.entity)
+            .insert(event.jump.clone())
+            .remove::<WalkingTowards>();
+    }
+}
+
+/// Run the [`JumpingTowards`] jump for bots that have it.
+#[allow(clippy::type_complexity)]
+pub fn tick_jump_towards(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut JumpingTowards,
+        &Position,
+        &Physics,
+        &InstanceHolder,
+    )>,
+    mut look_arbiter: ResMut<LookArbiter>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
+    mut jump_events: EventWriter<JumpEvent>,
+) {
+    for (entity, mut jumping_towards, position, physics, instance_holder) in &mut query {
+        let direction = jumping_towards.direction_from(position);
+
+        if jumping_towards.jumped {
+            if physics.on_ground() {
+                // we landed
+                commands.entity(entity).remove::<JumpingTowards>();
+            } else if jumping_towards.target.is_some_and(|target| {
+                position.horizontal_distance_squared_to(&target) < 0.5_f64.powi(2)
+            }) {
+                // stop accelerating so we don't overshoot
+                movement_arbiter.submit(
+                    entity,
+                    MovementPriority::PATHFINDER,
+                    MovementRequest::walk(WalkDirection::None),
+                );
+            } else {
+                movement_arbiter.submit(
+                    entity,
+                    MovementPriority::PATHFINDER,
+                    forward_request(jumping_towards.sprint),
+                );
+            }
+            continue;
+        }
+
+        if direction != Vec3::default() {
+            look_arbiter.submit(
+                entity,
+                LookPriority::MOVEMENT,
+                LookTarget::YRot(rotation::rotation_of(&direction).0),
             );
-            *look_direction = new_look_direction;
         }
+
+        movement_arbiter.submit(
+            entity,
+            MovementPriority::PATHFINDER,
+            forward_request(jumping_towards.sprint),
+        );
+
+        if !physics.on_ground() {
+            // we must've been knocked off the ground, so it's too late to jump
+            if !jumping_towards.sprint {
+                jumping_towards.jumped = true;
+            }
+            continue;
+        }
+
+        let should_jump = if jumping_towards.sprint {
+            is_about_to_walk_off_edge(
+                position,
+                physics,
+                direction,
+                &instance_holder.instance.read(),
+            ) || physics.horizontal_collision
+        } else {
+            true
+        };
+        if should_jump {
+            jump_events.send(JumpEvent { entity });
+            jumping_towards.jumped = true;
+        }
+    }
+}
+
+fn forward_request(sprint: bool) -> MovementRequest {
+    if sprint {
+        MovementRequest::sprint(SprintDirection::Forward)
+    } else {
+        MovementRequest::walk(WalkDirection::Forward)
     }
 }
 
+/// Whether the edge of the player's hitbox will stop being above a solid block
+/// next tick if they keep moving in the (normalized, horizontal) `direction`.
+pub fn is_about_to_walk_off_edge(
+    position: &Vec3,
+    physics: &Physics,
+    direction: Vec3,
+    instance: &azalea_world::Instance,
+) -> bool {
+    let half_width = physics.dimensions.width as f64 / 2.;
+    let next_edge = position + &(physics.velocity.xz() + direction * half_width);
+    let block_below = instance
+        .get_block_state(&BlockPos::from(next_edge.down(0.5)))
+        .unwrap_or_default();
+    block_below.is_collision_shape_empty()
+}
+
 /// Return the look direction that would make a client at `current` be
 /// looking at `target`.
 pub fn direction_looking_at(current: &Vec3, target: &Vec3) -> LookDirection {
-    // borrowed from mineflayer's Bot.lookAt because i didn't want to do math
-    let delta = target - current;
-    let y_rot = (PI - f64::atan2(-delta.x, -delta.z)) * (180.0 / PI);
-    let ground_distance = f64::sqrt(delta.x * delta.x + delta.z * delta.z);
-    let x_rot = f64::atan2(delta.y, ground_distance) * -(180.0 / PI);
-
-    // clamp
-    let y_rot = y_rot.rem_euclid(360.0);
-    let x_rot = x_rot.clamp(-90.0, 90.0) % 360.0;
-
-    LookDirection {
-        x_rot: x_rot as f32,
-        y_rot: y_rot as f32,
-    }
+    LookDirection::from(rotation::look_at(current, target))
 }
 
 /// A [`PluginGroup`] for the plugins that add extra bot functionality to the
@@ -222,7 +547,123 @@ impl PluginGroup for DefaultBotPlugins {
             .add(BotPlugin)
             .add(PathfinderPlugin)
             .add(ContainerPlugin)
-            .add(AutoRespawnPlugin)
+            .add(AutoRespawnPlugin::default())
             .add(AcceptResourcePacksPlugin)
+            .add(MovementSafetyPlugin)
+            .add(PetsPlugin)
+            .add(RngPlugin::default())
+            .add(ChatTriggersPlugin)
+            .add(InventoryLayoutPlugin)
+            .add(DeathRecoveryPlugin)
+            .add(RedstonePlugin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::position::ChunkPos;
+    use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
+
+    use super::*;
+    use crate::pathfinder::simulation::{
+        can_sprint_jump_across, SimulatedPlayerBundle, Simulation,
+    };
+
+    fn chunks_with_blocks(blocks: &[BlockPos]) -> (PartialChunkStorage, ChunkStorage) {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut chunks = ChunkStorage::default();
+        partial_chunks.set(&ChunkPos::new(0, 0), Some(Chunk::default()), &mut chunks);
+        for pos in blocks {
+            chunks.set_block_state(pos, azalea_registry::Block::Stone.into());
+        }
+        (partial_chunks, chunks)
+    }
+
+    #[test]
+    fn test_walk_towards_jumps_over_step() {
+        let (_partial_chunks, chunks) = chunks_with_blocks(&[
+            BlockPos::new(0, 70, 0),
+            BlockPos::new(0, 70, 1),
+            BlockPos::new(0, 70, 2),
+            BlockPos::new(0, 71, 3),
+            BlockPos::new(0, 71, 4),
+            BlockPos::new(0, 71, 5),
+        ]);
+        let mut simulation =
+            Simulation::new(chunks, SimulatedPlayerBundle::new(Vec3::new(0.5, 71., 0.5)));
+        let entity = simulation.entity;
+        simulation.app.world_mut().send_event(WalkTowardsEvent {
+            entity,
+            target: Some(WalkingTowards {
+                position: Vec3::new(0.5, 72., 5.5),
+                sprint: false,
+            }),
+        });
+
+        for _ in 0..100 {
+            simulation.tick();
+        }
+        assert_eq!(
+            BlockPos::from(simulation.position()),
+            BlockPos::new(0, 72, 5)
+        );
+        assert!(simulation.get_component::<WalkingTowards>().is_none());
+    }
+
+    #[test]
+    fn test_jump_towards_direction() {
+        let (_partial_chunks, chunks) = chunks_with_blocks(&[
+            BlockPos::new(0, 70, 0),
+            BlockPos::new(1, 70, 0),
+            BlockPos::new(2, 70, 0),
+            BlockPos::new(3, 70, 0),
+        ]);
+        let mut simulation =
+            Simulation::new(chunks, SimulatedPlayerBundle::new(Vec3::new(0.5, 71., 0.5)));
+        let entity = simulation.entity;
+        // let the player land first
+        simulation.tick();
+        simulation.app.world_mut().send_event(JumpTowardsEvent {
+            entity,
+            jump: JumpingTowards::in_direction(Vec3::new(2., 0., 0.), false),
+        });
+
+        let mut left_ground = false;
+        for _ in 0..40 {
+            simulation.tick();
+            left_ground |= simulation.position().y > 71.;
+            if simulation.get_component::<JumpingTowards>().is_none() {
+                break;
+            }
+        }
+        assert!(left_ground);
+        assert!(simulation.get_component::<JumpingTowards>().is_none());
+        let position = simulation.position();
+        assert!((position.y - 71.).abs() < 1e-6, "{position}");
+        // we should've turned east and moved that way
+        assert!(position.x > 1., "{position}");
+        assert!((position.z - 0.5).abs() < 0.01, "{position}");
+    }
+
+    #[test]
+    fn test_can_sprint_jump_across() {
+        let (_partial_chunks, chunks) = chunks_with_blocks(&[
+            BlockPos::new(0, 70, 0),
+            BlockPos::new(0, 70, 3),
+            BlockPos::new(0, 70, 9),
+        ]);
+        let start = Vec3::new(0.5, 71., 0.5);
+        // a gap of two blocks is easy
+        assert!(can_sprint_jump_across(
+            chunks.clone(),
+            start,
+            BlockPos::new(0, 71, 3)
+        ));
+        // but eight blocks is too far
+        assert!(!can_sprint_jump_across(
+            chunks,
+            start,
+            BlockPos::new(0, 71, 9)
+        ));
     }
 }
@@ -0,0 +1,292 @@
+//! Run systems when a chat message matches a pattern.
+//!
+//! Instead of checking every chat message against a long `match`, you can
+//! register a pattern and a handler with [`ChatTriggersAppExt::on_chat`], and
+//! the handler will be run with the captures whenever a bot receives a
+//! matching message.
+//!
+//! ```
+//! # use azalea::{prelude::*, chat_triggers::ChatTrigger};
+//! # use azalea::app::{App, Plugin};
+//! # use azalea::ecs::prelude::*;
+//! struct GreeterPlugin;
+//! impl Plugin for GreeterPlugin {
+//!     fn build(&self, app: &mut App) {
+//!         app.on_chat(r"^(\w+) joined the game$", greet)
+//!             .expect("the pattern is a valid regex");
+//!     }
+//! }
+//!
+//! fn greet(In(trigger): In<ChatTrigger>) {
+//!     let username = trigger.get(1).unwrap();
+//!     println!("{username} joined!");
+//! }
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use azalea_client::chat::{ChatPacket, ChatReceivedEvent};
+use bevy_ecs::{prelude::*, system::SystemId};
+use regex::Regex;
+
+use crate::app::{App, Plugin, Update};
+
+/// A plugin that runs the handlers that were registered with
+/// [`ChatTriggersAppExt::on_chat`].
+#[derive(Clone, Default)]
+pub struct ChatTriggersPlugin;
+impl Plugin for ChatTriggersPlugin {
+    fn build(&self, app: &mut App) {
+        // triggers might've already been registered
+        app.init_resource::<ChatTriggers>()
+            .add_systems(Update, run_chat_triggers);
+    }
+}
+
+/// The patterns and handlers that were registered with
+/// [`ChatTriggersAppExt::on_chat`], in the order that they were registered.
+#[derive(Resource, Default)]
+pub struct ChatTriggers {
+    pub triggers: Vec<(ChatMatcher, SystemId<In<ChatTrigger>>)>,
+}
+
+/// Something that decides whether a chat message should run a trigger.
+pub enum ChatMatcher {
+    /// Match the plain text of the whole message, including the sender's name
+    /// for player chat messages.
+    Regex(Regex),
+    /// A function that returns the captures if the message matches.
+    Custom(Box<CustomChatMatcher>),
+}
+
+pub type CustomChatMatcher = dyn Fn(&ChatPacket) -> Option<Vec<String>> + Send + Sync;
+
+impl ChatMatcher {
+    /// Create a matcher from a function that returns the captures if the
+    /// message matches, or `None` if it doesn't.
+    pub fn custom(f: impl Fn(&ChatPacket) -> Option<Vec<String>> + Send + Sync + 'static) -> Self {
+        Self::Custom(Box::new(f))
+    }
+
+    /// Check whether the message matches, and return the captures if it does.
+    pub fn captures(&self, packet: &ChatPacket) -> Option<ChatCaptures> {
+        match self {
+            ChatMatcher::Regex(regex) => {
+                let message = packet.message().to_string();
+                let captures = regex.captures(&message)?;
+                let values = captures
+                    .iter()
+                    .map(|m| m.map(|m| m.as_str().to_owned()))
+                    .collect();
+                let names = regex
+                    .capture_names()
+                    .enumerate()
+                    .filter_map(|(i, name)| Some((name?.to_owned(), i)))
+                    .collect();
+                Some(ChatCaptures { values, names })
+            }
+            ChatMatcher::Custom(f) => {
+                let values = f(packet)?.into_iter().map(Some).collect();
+                Some(ChatCaptures {
+                    values,
+                    names: HashMap::new(),
+                })
+            }
+        }
+    }
+}
+
+impl From<Regex> for ChatMatcher {
+    fn from(regex: Regex) -> Self {
+        Self::Regex(regex)
+    }
+}
+
+impl TryFrom<&str> for ChatMatcher {
+    type Error = regex::Error;
+
+    fn try_from(pattern: &str) -> Result<Self, Self::Error> {
+        Ok(Self::Regex(Regex::new(pattern)?))
+    }
+}
+
+impl fmt::Debug for ChatMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatMatcher::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            ChatMatcher::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// The groups that a [`ChatMatcher`] captured.
+#[derive(Clone, Debug, Default)]
+pub struct ChatCaptures {
+    /// The captured text for each group, or `None` if the group didn't
+    /// participate in the match. For regexes, the first item is the whole
+    /// match.
+    pub values: Vec<Option<String>>,
+    names: HashMap<String, usize>,
+}
+
+impl ChatCaptures {
+    /// Get the text captured by the group with the given index.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.values.get(index)?.as_deref()
+    }
+
+    /// Get the text captured by the group with the given name, like
+    /// `(?<username>\w+)`.
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.get(*self.names.get(name)?)
+    }
+}
+
+/// The input for a chat trigger's handler.
+#[derive(Clone, Debug)]
+pub struct ChatTrigger {
+    /// The bot that received the message.
+    pub entity: Entity,
+    pub packet: ChatPacket,
+    pub captures: ChatCaptures,
+}
+
+impl ChatTrigger {
+    /// Get the text captured by the group with the given index. This is the
+    /// same as `trigger.captures.get(index)`.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.captures.get(index)
+    }
+
+    /// Get the text captured by the group with the given name. This is the
+    /// same as `trigger.captures.name(name)`.
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.captures.name(name)
+    }
+}
+
+pub trait ChatTriggersAppExt {
+    /// Run the handler every time a bot receives a chat message that matches.
+    ///
+    /// The matcher can be a regex (as a string or a [`Regex`]) or a
+    /// [`ChatMatcher::custom`] function. The handler is a system that takes
+    /// an [`In<ChatTrigger>`], and if a message matches several triggers then
+    /// all of them are run.
+    ///
+    /// Note that in a swarm, every bot that receives the message will run the
+    /// handler.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without registering anything if the matcher is a
+    /// string that isn't a valid regex.
+    fn on_chat<T: TryInto<ChatMatcher>, M>(
+        &mut self,
+        matcher: T,
+        handler: impl IntoSystem<In<ChatTrigger>, (), M> + 'static,
+    ) -> Result<&mut Self, T::Error>;
+}
+
+impl ChatTriggersAppExt for App {
+    fn on_chat<T: TryInto<ChatMatcher>, M>(
+        &mut self,
+        matcher: T,
+        handler: impl IntoSystem<In<ChatTrigger>, (), M> + 'static,
+    ) -> Result<&mut Self, T::Error> {
+        let matcher = matcher.try_into()?;
+        let handler = self.world_mut().register_system(handler);
+        self.init_resource::<ChatTriggers>();
+        self.world_mut()
+            .resource_mut::<ChatTriggers>()
+            .triggers
+            .push((matcher, handler));
+        Ok(self)
+    }
+}
+
+pub fn run_chat_triggers(
+    mut commands: Commands,
+    mut events: EventReader<ChatReceivedEvent>,
+    triggers: Res<ChatTriggers>,
+) {
+    for event in events.read() {
+        for (matcher, handler) in &triggers.triggers {
+            let Some(captures) = matcher.captures(&event.packet) else {
+                continue;
+            };
+            commands.run_system_with_input(
+                *handler,
+                ChatTrigger {
+                    entity: event.entity,
+                    packet: event.packet.clone(),
+                    captures,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Joined(Vec<String>);
+
+    fn record_join(In(trigger): In<ChatTrigger>, mut joined: ResMut<Joined>) {
+        joined.0.push(trigger.name("username").unwrap().to_owned());
+    }
+
+    #[test]
+    fn test_regex_captures() {
+        let matcher = ChatMatcher::try_from(r"^(\w+) joined the game$").unwrap();
+        let captures = matcher
+            .captures(&ChatPacket::new("py5 joined the game"))
+            .unwrap();
+        assert_eq!(captures.get(0), Some("py5 joined the game"));
+        assert_eq!(captures.get(1), Some("py5"));
+        assert!(matcher.captures(&ChatPacket::new("hello")).is_none());
+    }
+
+    #[test]
+    fn test_custom_matcher() {
+        let matcher = ChatMatcher::custom(|packet| {
+            let (sender, content) = packet.split_sender_and_content();
+            (content == "ping").then(|| vec![sender.unwrap_or_default()])
+        });
+        let captures = matcher.captures(&ChatPacket::new("<bot0> ping")).unwrap();
+        assert_eq!(captures.get(0), Some("bot0"));
+    }
+
+    #[test]
+    fn test_handlers_run() {
+        let mut app = App::new();
+        app.add_event::<ChatReceivedEvent>()
+            .init_resource::<Joined>()
+            .add_plugins(ChatTriggersPlugin)
+            .on_chat(r"^(?<username>\w+) joined the game$", record_join)
+            .unwrap();
+
+        let entity = app.world_mut().spawn_empty().id();
+        for message in ["bot0 joined the game", "<bot0> hi", "bot1 joined the game"] {
+            app.world_mut().send_event(ChatReceivedEvent {
+                entity,
+                packet: ChatPacket::new(message),
+            });
+        }
+        app.update();
+
+        assert_eq!(app.world().resource::<Joined>().0, ["bot0", "bot1"]);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let mut app = App::new();
+        app.add_event::<ChatReceivedEvent>()
+            .init_resource::<Joined>()
+            .add_plugins(ChatTriggersPlugin);
+
+        assert!(app.on_chat(r"^(\w+ joined the game$", record_join).is_err());
+        assert!(app.world().resource::<ChatTriggers>().triggers.is_empty());
+    }
+}
@@ -7,10 +7,13 @@ use azalea_client::{
     Client,
 };
 use azalea_core::position::BlockPos;
-use azalea_inventory::{operations::ClickOperation, ItemStack, Menu};
-use azalea_protocol::packets::game::ClientboundGamePacket;
+use azalea_inventory::{
+    operations::{ClickOperation, QuickMoveClick, ThrowClick},
+    ItemMatcher, ItemStack, Menu,
+};
+use azalea_protocol::packets::game::{s_interact::InteractionHand, ClientboundGamePacket};
 use bevy_app::{App, Plugin, Update};
-use bevy_ecs::{component::Component, prelude::EventReader, system::Commands};
+use bevy_ecs::{component::Component, entity::Entity, prelude::EventReader, system::Commands};
 use futures_lite::Future;
 
 use crate::bot::BotClientExt;
@@ -27,6 +30,10 @@ pub trait ContainerClientExt {
         &mut self,
         pos: BlockPos,
     ) -> impl Future<Output = Option<ContainerHandle>> + Send;
+    fn open_entity_container(
+        &mut self,
+        entity: Entity,
+    ) -> impl Future<Output = Option<ContainerHandle>> + Send;
     fn open_inventory(&mut self) -> Option<ContainerHandle>;
     fn get_open_container(&self) -> Option<ContainerHandleRef>;
 }
@@ -73,6 +80,43 @@ impl ContainerClientExt for Client {
         }
     }
 
+    /// Open a container by right clicking an entity, like a villager's trading
+    /// menu or a chest minecart.
+    ///
+    /// This returns `None` if the entity didn't open a container.
+    async fn open_entity_container(&mut self, entity: Entity) -> Option<ContainerHandle> {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .insert(WaitingForInventoryOpen);
+        self.interact_entity(entity, InteractionHand::MainHand);
+
+        // entities that don't have a menu won't send anything back, so we don't wait
+        // forever
+        let mut receiver = self.get_tick_broadcaster();
+        let mut ticks = 0;
+        while receiver.recv().await.is_ok() {
+            let mut ecs = self.ecs.lock();
+            if ecs.get::<WaitingForInventoryOpen>(self.entity).is_none() {
+                break;
+            }
+            ticks += 1;
+            if ticks > 40 {
+                ecs.entity_mut(self.entity)
+                    .remove::<WaitingForInventoryOpen>();
+                return None;
+            }
+        }
+
+        let ecs = self.ecs.lock();
+        let inventory = ecs.get::<Inventory>(self.entity)?;
+        if inventory.id == 0 {
+            None
+        } else {
+            Some(ContainerHandle::new(inventory.id, self.clone()))
+        }
+    }
+
     /// Open the player's inventory. This will return None if another
     /// container is open.
     ///
@@ -180,6 +224,46 @@ impl ContainerHandleRef {
             operation,
         });
     }
+
+    /// Shift-click every matching item in our inventory into the container.
+    ///
+    /// Items that don't fit stay in our inventory.
+    pub fn deposit(&self, matcher: &ItemMatcher) {
+        let Some(menu) = self.menu() else {
+            return;
+        };
+        for slot in menu.find_all_in(menu.player_slots_range(), matcher) {
+            self.click(QuickMoveClick::Left { slot: slot as u16 });
+        }
+    }
+
+    /// Shift-click every matching item in the container into our inventory.
+    ///
+    /// Items that don't fit stay in the container.
+    pub fn withdraw(&self, matcher: &ItemMatcher) {
+        let Some(menu) = self.menu() else {
+            return;
+        };
+        for slot in menu.find_all_in(container_slots_range(&menu), matcher) {
+            self.click(QuickMoveClick::Left { slot: slot as u16 });
+        }
+    }
+
+    /// Drop every matching item in our inventory on the ground.
+    pub fn drop_items(&self, matcher: &ItemMatcher) {
+        let Some(menu) = self.menu() else {
+            return;
+        };
+        for slot in menu.find_all_in(menu.player_slots_range(), matcher) {
+            self.click(ThrowClick::All { slot: slot as u16 });
+        }
+    }
+}
+
+/// The slots that are part of the container, not including the player's
+/// inventory.
+fn container_slots_range(menu: &Menu) -> std::ops::RangeInclusive<usize> {
+    0..=menu.player_slots_range().start().saturating_sub(1)
 }
 
 /// A handle to the open container. The container will be closed once this is
@@ -229,6 +313,23 @@ impl ContainerHandle {
     pub fn click(&self, operation: impl Into<ClickOperation>) {
         self.0.click(operation);
     }
+
+    /// Shift-click every matching item in our inventory into the container.
+    /// See [`ContainerHandleRef::deposit`].
+    pub fn deposit(&self, matcher: &ItemMatcher) {
+        self.0.deposit(matcher);
+    }
+
+    /// Shift-click every matching item in the container into our inventory.
+    /// See [`ContainerHandleRef::withdraw`].
+    pub fn withdraw(&self, matcher: &ItemMatcher) {
+        self.0.withdraw(matcher);
+    }
+
+    /// Drop every matching item in our inventory on the ground.
+    pub fn drop_items(&self, matcher: &ItemMatcher) {
+        self.0.drop_items(matcher);
+    }
 }
 
 #[derive(Component, Debug)]
@@ -0,0 +1,393 @@
+//! Remember where bots died, and go back to pick up the items that they
+//! dropped.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use azalea_client::{
+    packet_handling::{
+        death_event_on_0_health,
+        game::{DeathEvent, ItemPickupEvent},
+    },
+    Client,
+};
+use azalea_core::{position::Vec3, resource_location::ResourceLocation};
+use azalea_entity::{metadata, LocalEntity, Position};
+use azalea_world::InstanceName;
+use bevy_app::Update;
+use bevy_ecs::prelude::*;
+use futures_lite::Future;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    app::{App, Plugin},
+    bot::BotClientExt,
+    pathfinder::{goals::RadiusGoal, PathfinderClientExt},
+};
+
+/// How long items stay on the ground before they despawn.
+pub const ITEM_DESPAWN_TIME: Duration = Duration::from_secs(5 * 60);
+/// How far from where we died that dropped items are assumed to be ours.
+const DEATH_ITEM_RADIUS: f64 = 8.;
+/// How many times we'll try to walk to an item before giving up on it.
+const MAX_PICKUP_ATTEMPTS: u32 = 3;
+/// How long we'll try to walk to a single item before giving up on it.
+const ITEM_GOTO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A plugin that keeps track of where bots died in [`LastDeath`], and which
+/// items are lying around there.
+#[derive(Clone, Default)]
+pub struct DeathRecoveryPlugin;
+impl Plugin for DeathRecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                record_death_position,
+                record_item_pickups,
+                track_dropped_items,
+            )
+                .chain()
+                .after(death_event_on_0_health),
+        );
+    }
+}
+
+/// A component that's present on bots that have died, with where they died
+/// and the items that they dropped.
+///
+/// It's removed after the items are recovered with
+/// [`DeathRecoveryClientExt::recover_items`].
+#[derive(Component, Clone, Debug)]
+pub struct LastDeath {
+    pub position: Vec3,
+    /// The dimension that we died in.
+    pub instance: ResourceLocation,
+    pub time: Instant,
+    /// The item entities near where we died. This only has the items that are
+    /// loaded right now, so it'll be empty while we're far away.
+    pub items: HashSet<Entity>,
+    /// The item entities from [`Self::items`] that we picked up ourselves.
+    /// Items that despawned or were picked up by someone else aren't in here.
+    pub picked_up: HashSet<Entity>,
+}
+
+impl LastDeath {
+    /// Whether the items that we dropped have probably despawned by now.
+    pub fn is_expired(&self) -> bool {
+        self.time.elapsed() >= ITEM_DESPAWN_TIME
+    }
+}
+
+fn record_death_position(
+    mut events: EventReader<DeathEvent>,
+    query: Query<(&Position, &InstanceName, Option<&LastDeath>), With<LocalEntity>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok((position, instance_name, last_death)) = query.get(event.entity) else {
+            continue;
+        };
+        // we might get multiple death events for the same death
+        if last_death.is_some_and(|d| d.position == **position && !d.is_expired()) {
+            continue;
+        }
+        debug!("{:?} died at {position:?}", event.entity);
+        commands.entity(event.entity).insert(LastDeath {
+            position: **position,
+            instance: (**instance_name).clone(),
+            time: Instant::now(),
+            items: HashSet::new(),
+            picked_up: HashSet::new(),
+        });
+    }
+}
+
+fn record_item_pickups(
+    mut events: EventReader<ItemPickupEvent>,
+    mut query: Query<&mut LastDeath, With<LocalEntity>>,
+) {
+    for event in events.read() {
+        if event.collector != event.entity {
+            continue;
+        }
+        let Ok(mut last_death) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if last_death.items.contains(&event.item_entity) {
+            last_death.picked_up.insert(event.item_entity);
+        }
+    }
+}
+
+fn track_dropped_items(
+    mut query: Query<&mut LastDeath, With<LocalEntity>>,
+    items: Query<(Entity, &Position, &InstanceName), With<metadata::Item>>,
+) {
+    for mut last_death in &mut query {
+        // items get despawned when their chunk is unloaded and spawned again
+        // as new entities when we come back, so this is updated every time
+        let nearby = items
+            .iter()
+            .filter(|(_, position, instance_name)| {
+                ***instance_name == last_death.instance
+                    && position.distance_to(&last_death.position) <= DEATH_ITEM_RADIUS
+            })
+            .map(|(entity, _, _)| entity)
+            .collect::<HashSet<_>>();
+        if nearby != last_death.items {
+            last_death.items = nearby;
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RecoverItemsError {
+    #[error("We haven't died")]
+    NotDead,
+    #[error("It's been too long since we died, so the items have despawned")]
+    Expired,
+    #[error("We died in {0}, which isn't the dimension that we're in")]
+    WrongDimension(ResourceLocation),
+}
+
+pub trait DeathRecoveryClientExt {
+    fn last_death(&self) -> Option<LastDeath>;
+    fn recover_items(&mut self) -> impl Future<Output = Result<usize, RecoverItemsError>> + Send;
+}
+
+impl DeathRecoveryClientExt for Client {
+    /// Where we last died, if we haven't recovered our items since then.
+    fn last_death(&self) -> Option<LastDeath> {
+        self.get_component::<LastDeath>()
+    }
+
+    /// Walk back to where we last died and pick up every item that's lying
+    /// around there.
+    ///
+    /// This has to be done before the items despawn, which is 5 minutes after
+    /// dying. Returns the number of item entities that were picked up.
+    ///
+    /// ```no_run
+    /// # use azalea::prelude::*;
+    /// # use azalea::death_recovery::DeathRecoveryClientExt;
+    /// # async fn example(mut bot: Client) {
+    /// match bot.recover_items().await {
+    ///     Ok(count) => println!("picked up {count} stacks"),
+    ///     Err(e) => println!("couldn't get our items back: {e}"),
+    /// }
+    /// # }
+    /// ```
+    async fn recover_items(&mut self) -> Result<usize, RecoverItemsError> {
+        let last_death = self.last_death().ok_or(RecoverItemsError::NotDead)?;
+        if last_death.is_expired() {
+            return Err(RecoverItemsError::Expired);
+        }
+        let instance_name = self.component::<InstanceName>();
+        if *instance_name != last_death.instance {
+            return Err(RecoverItemsError::WrongDimension(last_death.instance));
+        }
+
+        self.goto(RadiusGoal {
+            pos: last_death.position,
+            radius: 2.,
+        });
+        // there's no point in getting there after the items despawned
+        let time_left = ITEM_DESPAWN_TIME.saturating_sub(last_death.time.elapsed());
+        if !self
+            .wait_until_goto_target_reached_with_timeout(time_left)
+            .await
+        {
+            self.stop_pathfinding();
+            return Err(RecoverItemsError::Expired);
+        }
+        // give the items some time to be sent to us
+        wait_ticks(self, 20).await;
+
+        let mut attempts = HashMap::<Entity, u32>::new();
+        loop {
+            let Some(last_death) = self.last_death() else {
+                break;
+            };
+            if last_death.is_expired() {
+                break;
+            }
+
+            let position = self.position();
+            let target = {
+                let ecs = self.ecs.lock();
+                last_death
+                    .items
+                    .iter()
+                    .filter(|item| {
+                        attempts.get(item).copied().unwrap_or_default() < MAX_PICKUP_ATTEMPTS
+                    })
+                    .filter_map(|item| Some((*item, **ecs.get::<Position>(*item)?)))
+                    .min_by(|(_, a), (_, b)| {
+                        a.distance_to(&position)
+                            .total_cmp(&b.distance_to(&position))
+                    })
+            };
+            let Some((item, item_position)) = target else {
+                break;
+            };
+            *attempts.entry(item).or_default() += 1;
+
+            self.goto(RadiusGoal {
+                pos: item_position,
+                radius: 0.5,
+            });
+            if !self
+                .wait_until_goto_target_reached_with_timeout(ITEM_GOTO_TIMEOUT)
+                .await
+            {
+                self.stop_pathfinding();
+                continue;
+            }
+            // items are picked up the tick after we touch them
+            wait_ticks(self, 2).await;
+        }
+
+        // this also counts items that we happened to walk over on the way
+        let picked_up = self
+            .last_death()
+            .map(|last_death| last_death.picked_up.len())
+            .unwrap_or_default();
+        debug!("Recovered {picked_up} items from where we died");
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .remove::<LastDeath>();
+        Ok(picked_up)
+    }
+}
+
+async fn wait_ticks(bot: &Client, ticks: usize) {
+    let mut tick_broadcaster = bot.get_tick_broadcaster();
+    for _ in 0..ticks {
+        if tick_broadcaster.recv().await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_inventory::ItemStack;
+    use bevy_ecs::{event::Events, schedule::Schedule};
+
+    use super::*;
+
+    fn setup() -> (World, Schedule, Entity) {
+        let mut ecs = World::new();
+        ecs.init_resource::<Events<DeathEvent>>();
+        ecs.init_resource::<Events<ItemPickupEvent>>();
+        let bot = ecs
+            .spawn((
+                LocalEntity,
+                Position::new(Vec3::new(0., 64., 0.)),
+                InstanceName(ResourceLocation::new("overworld")),
+            ))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                record_death_position,
+                record_item_pickups,
+                track_dropped_items,
+            )
+                .chain(),
+        );
+        (ecs, schedule, bot)
+    }
+
+    fn spawn_item(ecs: &mut World, pos: Vec3, instance: &str) -> Entity {
+        ecs.spawn((
+            metadata::Item,
+            Position::new(pos),
+            InstanceName(ResourceLocation::new(instance)),
+        ))
+        .id()
+    }
+
+    fn die(ecs: &mut World, schedule: &mut Schedule, bot: Entity) {
+        ecs.send_event(DeathEvent {
+            entity: bot,
+            packet: None,
+        });
+        schedule.run(ecs);
+    }
+
+    fn pick_up(
+        ecs: &mut World,
+        schedule: &mut Schedule,
+        bot: Entity,
+        collector: Entity,
+        item: Entity,
+    ) {
+        ecs.send_event(ItemPickupEvent {
+            entity: bot,
+            collector,
+            item_entity: item,
+            item: ItemStack::Empty,
+        });
+        schedule.run(ecs);
+    }
+
+    #[test]
+    fn test_records_death_position() {
+        let (mut ecs, mut schedule, bot) = setup();
+        die(&mut ecs, &mut schedule, bot);
+
+        let last_death = ecs.get::<LastDeath>(bot).unwrap().clone();
+        assert_eq!(last_death.position, Vec3::new(0., 64., 0.));
+        assert_eq!(last_death.instance, ResourceLocation::new("overworld"));
+
+        // another event for the same death doesn't reset the time
+        die(&mut ecs, &mut schedule, bot);
+        assert_eq!(ecs.get::<LastDeath>(bot).unwrap().time, last_death.time);
+    }
+
+    #[test]
+    fn test_tracks_nearby_items() {
+        let (mut ecs, mut schedule, bot) = setup();
+        let near = spawn_item(&mut ecs, Vec3::new(2., 64., 0.), "overworld");
+        let far = spawn_item(&mut ecs, Vec3::new(100., 64., 0.), "overworld");
+        let other_dimension = spawn_item(&mut ecs, Vec3::new(2., 64., 0.), "the_nether");
+        die(&mut ecs, &mut schedule, bot);
+        schedule.run(&mut ecs);
+
+        let items = &ecs.get::<LastDeath>(bot).unwrap().items;
+        assert!(items.contains(&near));
+        assert!(!items.contains(&far));
+        assert!(!items.contains(&other_dimension));
+
+        ecs.despawn(near);
+        schedule.run(&mut ecs);
+        assert!(ecs.get::<LastDeath>(bot).unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_only_counts_our_pickups() {
+        let (mut ecs, mut schedule, bot) = setup();
+        let ours = spawn_item(&mut ecs, Vec3::new(1., 64., 0.), "overworld");
+        let stolen = spawn_item(&mut ecs, Vec3::new(-1., 64., 0.), "overworld");
+        let unrelated = spawn_item(&mut ecs, Vec3::new(100., 64., 0.), "overworld");
+        let other_player = ecs.spawn_empty().id();
+        die(&mut ecs, &mut schedule, bot);
+        schedule.run(&mut ecs);
+
+        pick_up(&mut ecs, &mut schedule, bot, bot, ours);
+        pick_up(&mut ecs, &mut schedule, bot, other_player, stolen);
+        pick_up(&mut ecs, &mut schedule, bot, bot, unrelated);
+        // items that despawn on their own aren't picked up either
+        ecs.despawn(stolen);
+        schedule.run(&mut ecs);
+
+        let picked_up = &ecs.get::<LastDeath>(bot).unwrap().picked_up;
+        assert_eq!(picked_up, &HashSet::from([ours]));
+    }
+}
@@ -0,0 +1,388 @@
+//! Keep specific items in specific slots of a bot's inventory, like a kit.
+//!
+//! ```
+//! # use azalea::prelude::*;
+//! # use azalea::inventory_layout::{InventoryLayout, InventoryLayoutClientExt};
+//! # use azalea::registry::Item;
+//! # use azalea_inventory::ItemMatcher;
+//! # fn example(bot: &mut Client) {
+//! bot.set_inventory_layout(
+//!     InventoryLayout::new()
+//!         .hotbar(0, Item::DiamondSword)
+//!         .hotbar(1, ItemMatcher::new().name("*_pickaxe"))
+//!         .hotbar(8, ItemMatcher::new().tag(&azalea::registry::tags::items::LOGS))
+//!         .offhand(Item::Shield),
+//! );
+//! # }
+//! ```
+
+use std::collections::HashSet;
+
+use azalea_client::{
+    inventory::{ContainerClickEvent, Inventory, InventorySet},
+    Client,
+};
+use azalea_core::tick::GameTick;
+use azalea_entity::LocalEntity;
+use azalea_inventory::{
+    operations::{ClickOperation, PickupClick, SwapClick},
+    ItemMatcher, ItemStack, Menu,
+};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::app::{App, Plugin};
+
+/// The index of the first hotbar slot in the player's inventory menu.
+const HOTBAR_START: usize = 36;
+/// The index of the offhand slot in the player's inventory menu.
+const OFFHAND_SLOT: usize = 45;
+/// The button that swaps with the offhand in a [`SwapClick`].
+const OFFHAND_BUTTON: u8 = 40;
+/// The slots that items can be taken from to fill the layout. This is the
+/// main inventory, the hotbar, and the offhand.
+const SOURCE_SLOTS: std::ops::RangeInclusive<usize> = 9..=OFFHAND_SLOT;
+
+/// How many ticks to wait after clicking before we plan again, so the server
+/// has time to tell us what our inventory looks like now.
+const CLICK_COOLDOWN_TICKS: u32 = 5;
+
+/// A plugin that rearranges the inventories of bots that have an
+/// [`InventoryLayout`] to match it.
+#[derive(Clone, Default)]
+pub struct InventoryLayoutPlugin;
+impl Plugin for InventoryLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LayoutSatisfiedEvent>()
+            .add_event::<MissingLayoutItemsEvent>()
+            .add_systems(GameTick, arrange_inventory.before(InventorySet));
+    }
+}
+
+/// A component for the items that a bot should keep in each slot of its
+/// inventory.
+///
+/// While this is present, the bot will move items around whenever its
+/// inventory stops matching the layout, as long as it doesn't have a container
+/// open. Slots that aren't in the layout can hold anything.
+#[derive(Component, Clone, Debug, Default)]
+pub struct InventoryLayout {
+    /// The slot indexes in [`Menu::Player`] and the items that should be in
+    /// them, in order of priority.
+    pub slots: Vec<(usize, ItemMatcher)>,
+
+    cooldown: u32,
+    /// The slots that were missing items last time we checked, or `None` if we
+    /// haven't checked yet.
+    last_missing: Option<Vec<usize>>,
+}
+
+impl InventoryLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep an item that matches in the given slot of the [`Menu::Player`].
+    ///
+    /// Slots that are added first get priority if an item matches multiple
+    /// slots.
+    #[must_use]
+    pub fn slot(mut self, index: usize, matcher: impl Into<ItemMatcher>) -> Self {
+        self.slots.retain(|(i, _)| *i != index);
+        self.slots.push((index, matcher.into()));
+        self
+    }
+
+    /// Keep an item that matches in the given hotbar slot, from 0 to 8.
+    #[must_use]
+    pub fn hotbar(self, hotbar_slot: u8, matcher: impl Into<ItemMatcher>) -> Self {
+        assert!(hotbar_slot < 9, "hotbar slots are from 0 to 8");
+        self.slot(HOTBAR_START + hotbar_slot as usize, matcher)
+    }
+
+    /// Keep an item that matches in our offhand.
+    #[must_use]
+    pub fn offhand(self, matcher: impl Into<ItemMatcher>) -> Self {
+        self.slot(OFFHAND_SLOT, matcher)
+    }
+}
+
+/// Sent when a bot's inventory matches its [`InventoryLayout`], either after
+/// rearranging it or when the layout is first set.
+#[derive(Event, Debug, Clone)]
+pub struct LayoutSatisfiedEvent {
+    pub entity: Entity,
+}
+
+/// Sent when a bot doesn't have the items to fill some slots in its
+/// [`InventoryLayout`].
+///
+/// This is only sent again if the slots that are missing items change.
+#[derive(Event, Debug, Clone)]
+pub struct MissingLayoutItemsEvent {
+    pub entity: Entity,
+    /// The slot indexes in [`Menu::Player`] that couldn't be filled.
+    pub slots: Vec<usize>,
+}
+
+/// The clicks that would make an inventory match an [`InventoryLayout`].
+///
+/// Get this with [`plan_layout`].
+#[derive(Debug, Clone, Default)]
+pub struct LayoutPlan {
+    pub clicks: Vec<ClickOperation>,
+    /// The slots that don't have a matching item anywhere in the inventory.
+    pub missing: Vec<usize>,
+}
+
+/// Figure out which clicks will make the player's inventory menu match the
+/// layout.
+///
+/// Items are moved with a single swap click whenever one of the two slots is in
+/// the hotbar or offhand, and by picking them up and putting them down
+/// otherwise. Slots that already match are never touched.
+pub fn plan_layout(menu: &Menu, layout: &InventoryLayout) -> LayoutPlan {
+    let mut slots = menu.slots();
+    let mut plan = LayoutPlan::default();
+
+    // slots that already have the right item shouldn't be taken from
+    let mut done = layout
+        .slots
+        .iter()
+        .filter(|(index, matcher)| slots.get(*index).is_some_and(|item| matcher.matches(item)))
+        .map(|(index, _)| *index)
+        .collect::<HashSet<_>>();
+
+    for (target, matcher) in &layout.slots {
+        let target = *target;
+        if done.contains(&target) || target >= slots.len() {
+            continue;
+        }
+
+        let Some(source) = SOURCE_SLOTS
+            .filter(|i| *i != target && !done.contains(i) && matcher.matches(&slots[*i]))
+            .min_by_key(|source| click_cost(*source, target))
+        else {
+            plan.missing.push(target);
+            continue;
+        };
+
+        if let Some(button) = swap_button(target) {
+            plan.clicks.push(
+                SwapClick {
+                    source_slot: source as u16,
+                    target_slot: button,
+                }
+                .into(),
+            );
+        } else if let Some(button) = swap_button(source) {
+            plan.clicks.push(
+                SwapClick {
+                    source_slot: target as u16,
+                    target_slot: button,
+                }
+                .into(),
+            );
+        } else {
+            plan.clicks.push(
+                PickupClick::Left {
+                    slot: Some(source as u16),
+                }
+                .into(),
+            );
+            plan.clicks.push(
+                PickupClick::Left {
+                    slot: Some(target as u16),
+                }
+                .into(),
+            );
+            if let (ItemStack::Present(source_item), ItemStack::Present(target_item)) =
+                (&slots[source], &slots[target])
+            {
+                // put the item that was in the target slot where ours was
+                plan.clicks.push(
+                    PickupClick::Left {
+                        slot: Some(source as u16),
+                    }
+                    .into(),
+                );
+                // if they were the same item then the stacks got merged
+                // instead of swapped, so plan the rest once we know how that
+                // went
+                if source_item.is_same_item_and_components(target_item) {
+                    break;
+                }
+            }
+        }
+
+        slots.swap(source, target);
+        done.insert(target);
+    }
+
+    plan
+}
+
+/// The number of clicks it takes to swap the items in two slots.
+fn click_cost(source: usize, target: usize) -> u32 {
+    if swap_button(source).is_some() || swap_button(target).is_some() {
+        1
+    } else {
+        3
+    }
+}
+
+/// The button for a [`SwapClick`] that swaps with the given slot, if there is
+/// one.
+fn swap_button(slot: usize) -> Option<u8> {
+    match slot {
+        HOTBAR_START..OFFHAND_SLOT => Some((slot - HOTBAR_START) as u8),
+        OFFHAND_SLOT => Some(OFFHAND_BUTTON),
+        _ => None,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn arrange_inventory(
+    mut query: Query<(Entity, &Inventory, &mut InventoryLayout), With<LocalEntity>>,
+    mut container_click_events: EventWriter<ContainerClickEvent>,
+    mut satisfied_events: EventWriter<LayoutSatisfiedEvent>,
+    mut missing_events: EventWriter<MissingLayoutItemsEvent>,
+) {
+    for (entity, inventory, mut layout) in &mut query {
+        if layout.cooldown > 0 {
+            layout.cooldown -= 1;
+            continue;
+        }
+        // don't mess with containers that were opened for something else, or
+        // with an item that's being moved
+        if inventory.id != 0 || inventory.carried.is_present() {
+            continue;
+        }
+
+        let plan = plan_layout(&inventory.inventory_menu, &layout);
+        if !plan.clicks.is_empty() {
+            debug!(
+                "Rearranging inventory of {entity:?} with {} clicks",
+                plan.clicks.len()
+            );
+            for operation in plan.clicks {
+                container_click_events.send(ContainerClickEvent {
+                    entity,
+                    window_id: 0,
+                    operation,
+                });
+            }
+            layout.cooldown = CLICK_COOLDOWN_TICKS;
+            continue;
+        }
+
+        if layout.last_missing.as_ref() == Some(&plan.missing) {
+            continue;
+        }
+        if plan.missing.is_empty() {
+            satisfied_events.send(LayoutSatisfiedEvent { entity });
+        } else {
+            missing_events.send(MissingLayoutItemsEvent {
+                entity,
+                slots: plan.missing.clone(),
+            });
+        }
+        layout.last_missing = Some(plan.missing);
+    }
+}
+
+pub trait InventoryLayoutClientExt {
+    fn set_inventory_layout(&mut self, layout: InventoryLayout);
+    fn clear_inventory_layout(&mut self);
+}
+
+impl InventoryLayoutClientExt for Client {
+    /// Start rearranging our inventory to match the layout, and keep it that
+    /// way until [`Self::clear_inventory_layout`] is called.
+    ///
+    /// A [`LayoutSatisfiedEvent`] or [`MissingLayoutItemsEvent`] is sent once
+    /// we've done everything we can.
+    fn set_inventory_layout(&mut self, layout: InventoryLayout) {
+        self.ecs.lock().entity_mut(self.entity).insert(layout);
+    }
+
+    /// Stop keeping our inventory in the layout that was set with
+    /// [`Self::set_inventory_layout`].
+    fn clear_inventory_layout(&mut self) {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .remove::<InventoryLayout>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_inventory::{ItemStackData, Player};
+    use azalea_registry::Item;
+
+    use super::*;
+
+    fn item(kind: Item, count: i32) -> ItemStack {
+        ItemStack::Present(ItemStackData {
+            kind,
+            count,
+            components: Default::default(),
+        })
+    }
+
+    fn menu_with(items: &[(usize, ItemStack)]) -> Menu {
+        let mut menu = Menu::Player(Player::default());
+        for (index, item) in items {
+            *menu.slot_mut(*index).unwrap() = item.clone();
+        }
+        menu
+    }
+
+    #[test]
+    fn test_already_satisfied() {
+        let menu = menu_with(&[(36, item(Item::DiamondSword, 1))]);
+        let plan = plan_layout(&menu, &InventoryLayout::new().hotbar(0, Item::DiamondSword));
+        assert!(plan.clicks.is_empty());
+        assert!(plan.missing.is_empty());
+    }
+
+    #[test]
+    fn test_swap_into_hotbar() {
+        let menu = menu_with(&[(12, item(Item::DiamondSword, 1))]);
+        let plan = plan_layout(&menu, &InventoryLayout::new().hotbar(2, Item::DiamondSword));
+        assert_eq!(plan.clicks.len(), 1);
+        assert!(matches!(
+            plan.clicks[0],
+            ClickOperation::Swap(SwapClick {
+                source_slot: 12,
+                target_slot: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_missing_and_priority() {
+        let menu = menu_with(&[(9, item(Item::Bread, 16))]);
+        let layout = InventoryLayout::new()
+            .hotbar(0, Item::Bread)
+            .hotbar(1, Item::Bread)
+            .offhand(Item::Shield);
+        let plan = plan_layout(&menu, &layout);
+        assert_eq!(plan.clicks.len(), 1);
+        assert_eq!(plan.missing, vec![37, 45]);
+    }
+
+    #[test]
+    fn test_does_not_take_from_satisfied_slots() {
+        let menu = menu_with(&[(36, item(Item::Bread, 16)), (10, item(Item::Stone, 1))]);
+        let layout = InventoryLayout::new()
+            .hotbar(0, Item::Bread)
+            .slot(9, ItemMatcher::new());
+        let plan = plan_layout(&menu, &layout);
+        // the stone is the only thing that can go in slot 9, and it takes two
+        // clicks since neither slot is in the hotbar and slot 9 is empty
+        assert_eq!(plan.clicks.len(), 2);
+        assert!(plan.missing.is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+//! Put items in item frames, rotate them, and take them out again.
+
+use azalea_client::{interact::EntityInteractEvent, Client};
+use azalea_entity::metadata::{GlowItemFrame, ItemFrame, ItemFrameItem, Rotation};
+use azalea_protocol::packets::game::s_interact::InteractionHand;
+use bevy_ecs::{
+    entity::Entity,
+    query::{Or, With},
+};
+
+pub trait ItemFrameClientExt {
+    fn item_frame_item(&self, frame: Entity) -> Option<azalea_inventory::ItemStack>;
+    fn item_frame_rotation(&self, frame: Entity) -> Option<i32>;
+    fn put_item_in_frame(&mut self, frame: Entity) -> bool;
+    fn rotate_item_frame(&mut self, frame: Entity) -> bool;
+    fn remove_item_from_frame(&mut self, frame: Entity) -> bool;
+}
+
+impl ItemFrameClientExt for Client {
+    /// Get the item that's in the given item frame, or `None` if the entity
+    /// isn't an item frame.
+    fn item_frame_item(&self, frame: Entity) -> Option<azalea_inventory::ItemStack> {
+        let mut ecs = self.ecs.lock();
+        let mut query =
+            ecs.query_filtered::<&ItemFrameItem, Or<(With<ItemFrame>, With<GlowItemFrame>)>>();
+        query.get(&ecs, frame).ok().map(|item| (**item).clone())
+    }
+
+    /// Get the rotation of the item in the given item frame, from 0 to 7 in
+    /// 45 degree steps.
+    fn item_frame_rotation(&self, frame: Entity) -> Option<i32> {
+        let mut ecs = self.ecs.lock();
+        let mut query =
+            ecs.query_filtered::<&Rotation, Or<(With<ItemFrame>, With<GlowItemFrame>)>>();
+        query.get(&ecs, frame).ok().map(|rotation| **rotation)
+    }
+
+    /// Put the item that we're holding into the given empty item frame.
+    ///
+    /// Returns false if the entity isn't an item frame or if it already has an
+    /// item in it.
+    fn put_item_in_frame(&mut self, frame: Entity) -> bool {
+        if !self
+            .item_frame_item(frame)
+            .is_some_and(|item| item.is_empty())
+        {
+            return false;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: frame,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+
+    /// Rotate the item in the given item frame by 45 degrees clockwise.
+    ///
+    /// Returns false if the entity isn't an item frame or if it's empty, since
+    /// right clicking an empty frame would put our held item in it instead.
+    fn rotate_item_frame(&mut self, frame: Entity) -> bool {
+        if !self
+            .item_frame_item(frame)
+            .is_some_and(|item| item.is_present())
+        {
+            return false;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: frame,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+
+    /// Take the item out of the given item frame by punching it. The item will
+    /// be dropped on the ground.
+    ///
+    /// Returns false if the entity isn't an item frame or if it's empty, since
+    /// punching an empty frame would break it.
+    fn remove_item_from_frame(&mut self, frame: Entity) -> bool {
+        if !self
+            .item_frame_item(frame)
+            .is_some_and(|item| item.is_present())
+        {
+            return false;
+        }
+        let Some(frame_ref) = self.entity_id_ref(frame) else {
+            return false;
+        };
+        self.attack_ref(frame_ref).is_ok()
+    }
+}
@@ -0,0 +1,269 @@
+//! Use buckets, glass bottles, and shears on blocks and entities.
+//!
+//! Picking up fluids doesn't work with [`Client::block_interact`], since the
+//! server decides which fluid we're filling from based on where we're looking.
+//! These helpers look at the right spot and send the right packet for you.
+
+use azalea_client::{
+    interact::{EntityInteractEvent, StartUseItemEvent},
+    inventory::{Inventory, SetSelectedHotbarSlotEvent},
+    Client,
+};
+use azalea_core::position::{BlockPos, Vec3};
+use azalea_entity::{
+    metadata::{AbstractAgeableBaby, BoggedSheared, HasPumpkin, SheepSheared},
+    EntityKind, EyeHeight, LookDirection, Position,
+};
+use azalea_inventory::{ItemMatcher, ItemStack};
+use azalea_protocol::packets::game::s_interact::InteractionHand;
+use azalea_registry::Item;
+use bevy_ecs::entity::Entity;
+use tracing::warn;
+
+use crate::direction_looking_at;
+
+pub trait ItemUseClientExt {
+    #[deprecated(
+        since = "0.12.0",
+        note = "use `select_matching_hotbar_item` with an `ItemMatcher` instead"
+    )]
+    fn select_hotbar_item(&mut self, predicate: impl Fn(Item) -> bool) -> bool;
+    fn select_matching_hotbar_item(&mut self, matcher: &ItemMatcher) -> bool;
+    fn fill_bucket(&mut self, fluid: BlockPos) -> bool;
+    fn empty_bucket(&mut self, position: BlockPos) -> bool;
+    fn fill_bottle(&mut self, water: BlockPos) -> bool;
+    fn shear(&mut self, entity: Entity) -> bool;
+    fn milk(&mut self, entity: Entity) -> bool;
+}
+
+impl ItemUseClientExt for Client {
+    /// Hold the first item in our hotbar that matches the predicate.
+    ///
+    /// This is the same as [`Self::select_matching_hotbar_item`], except empty
+    /// slots are passed to the predicate as [`Item::Air`].
+    fn select_hotbar_item(&mut self, predicate: impl Fn(Item) -> bool) -> bool {
+        select_hotbar_slot(self, |item| predicate(item.kind()))
+    }
+
+    /// Hold the first item in our hotbar that matches.
+    ///
+    /// If the item we're already holding matches, this does nothing. Returns
+    /// false if there's no matching item in our hotbar.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::item_use::ItemUseClientExt;
+    /// # use azalea::registry::Item;
+    /// # use azalea_inventory::ItemMatcher;
+    /// # fn example(bot: &mut Client) {
+    /// bot.select_matching_hotbar_item(&Item::Bucket.into());
+    /// bot.select_matching_hotbar_item(&ItemMatcher::new().name("*_sword"));
+    /// # }
+    /// ```
+    fn select_matching_hotbar_item(&mut self, matcher: &ItemMatcher) -> bool {
+        select_hotbar_slot(self, |item| matcher.matches(item))
+    }
+
+    /// Pick up the water or lava source block at the given position with an
+    /// empty bucket from our hotbar.
+    ///
+    /// This will turn our head to look at the fluid. Returns false if we don't
+    /// have an empty bucket in our hotbar.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::item_use::ItemUseClientExt;
+    /// # use azalea::BlockPos;
+    /// # fn example(bot: &mut Client) {
+    /// bot.fill_bucket(BlockPos::new(0, 63, 0));
+    /// # }
+    /// ```
+    fn fill_bucket(&mut self, fluid: BlockPos) -> bool {
+        if !self.select_matching_hotbar_item(&Item::Bucket.into()) {
+            warn!("Tried to fill a bucket without having an empty one in our hotbar");
+            return false;
+        }
+        use_item_looking_at(self, fluid.center());
+        true
+    }
+
+    /// Place the fluid from a filled bucket in our hotbar at the given
+    /// position.
+    ///
+    /// We aim at the top of the block below the position, so there has to be
+    /// a block there. If the position contains a block that can be
+    /// waterlogged (like a slab), the water will be put in that block instead.
+    /// Returns false if we don't have a filled bucket in our hotbar.
+    fn empty_bucket(&mut self, position: BlockPos) -> bool {
+        if !self.select_matching_hotbar_item(&ItemMatcher::new().kinds(FILLED_BUCKETS)) {
+            warn!("Tried to empty a bucket without having a filled one in our hotbar");
+            return false;
+        }
+        // aim slightly below the top of the block under the target so the server's
+        // raycast definitely hits it
+        let target = position.center() - Vec3::new(0., 0.6, 0.);
+        use_item_looking_at(self, target);
+        true
+    }
+
+    /// Fill a glass bottle from our hotbar with the water source block at the
+    /// given position.
+    ///
+    /// To fill a bottle from a cauldron, use [`Client::block_interact`] while
+    /// holding the bottle instead. Returns false if we don't have a glass
+    /// bottle in our hotbar.
+    fn fill_bottle(&mut self, water: BlockPos) -> bool {
+        if !self.select_matching_hotbar_item(&Item::GlassBottle.into()) {
+            warn!("Tried to fill a bottle without having one in our hotbar");
+            return false;
+        }
+        use_item_looking_at(self, water.center());
+        true
+    }
+
+    /// Shear the given sheep, mooshroom, snow golem, or bogged with shears
+    /// from our hotbar.
+    ///
+    /// Returns false if we don't have shears, or if the entity can't be
+    /// sheared right now (for example, because it's a lamb or it's already
+    /// been sheared).
+    fn shear(&mut self, entity: Entity) -> bool {
+        if !can_be_sheared(self, entity) {
+            return false;
+        }
+        if !self.select_matching_hotbar_item(&Item::Shears.into()) {
+            warn!("Tried to shear an entity without having shears in our hotbar");
+            return false;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+
+    /// Milk the given cow, goat, or mooshroom with an empty bucket from our
+    /// hotbar.
+    ///
+    /// Returns false if we don't have an empty bucket or if the entity can't be
+    /// milked.
+    fn milk(&mut self, entity: Entity) -> bool {
+        {
+            let ecs = self.ecs.lock();
+            let Some(kind) = ecs.get::<EntityKind>(entity) else {
+                return false;
+            };
+            if !matches!(
+                **kind,
+                azalea_registry::EntityKind::Cow
+                    | azalea_registry::EntityKind::Goat
+                    | azalea_registry::EntityKind::Mooshroom
+            ) || is_baby(&ecs, entity)
+            {
+                return false;
+            }
+        }
+        if !self.select_matching_hotbar_item(&Item::Bucket.into()) {
+            warn!("Tried to milk an entity without having an empty bucket in our hotbar");
+            return false;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+}
+
+/// Hold the first item in our hotbar that the function returns true for,
+/// starting with the one we're already holding.
+fn select_hotbar_slot(bot: &mut Client, matches: impl Fn(&ItemStack) -> bool) -> bool {
+    let slot = {
+        let mut ecs = bot.ecs.lock();
+        let inventory = bot.query::<&Inventory>(&mut ecs);
+        if matches(&inventory.held_item()) {
+            return true;
+        }
+        let menu = &inventory.inventory_menu;
+        menu.slots()[menu.hotbar_slots_range()]
+            .iter()
+            .position(matches)
+    };
+    let Some(slot) = slot else {
+        return false;
+    };
+    bot.ecs.lock().send_event(SetSelectedHotbarSlotEvent {
+        entity: bot.entity,
+        slot: slot as u8,
+    });
+    true
+}
+
+/// The buckets that place a fluid when used, including buckets with fish or
+/// axolotls in them.
+pub const FILLED_BUCKETS: [Item; 8] = [
+    Item::WaterBucket,
+    Item::LavaBucket,
+    Item::PufferfishBucket,
+    Item::SalmonBucket,
+    Item::CodBucket,
+    Item::TropicalFishBucket,
+    Item::AxolotlBucket,
+    Item::TadpoleBucket,
+];
+
+/// Returns whether the item is a bucket that places a fluid when used,
+/// including buckets with fish or axolotls in them.
+pub fn is_filled_bucket(item: Item) -> bool {
+    FILLED_BUCKETS.contains(&item)
+}
+
+/// Turn our head to look at the given position and use the item in our main
+/// hand.
+///
+/// The look direction is set directly instead of through the `LookArbiter` so
+/// the use item packet is guaranteed to contain our new rotation, which the
+/// server uses to decide what we clicked.
+fn use_item_looking_at(client: &Client, target: Vec3) {
+    let mut ecs = client.ecs.lock();
+    let (position, eye_height, mut look_direction) =
+        client.query::<(&Position, &EyeHeight, &mut LookDirection)>(&mut ecs);
+    *look_direction = direction_looking_at(&position.up(eye_height.into()), &target);
+
+    ecs.send_event(StartUseItemEvent {
+        entity: client.entity,
+        hand: InteractionHand::MainHand,
+    });
+}
+
+fn is_baby(ecs: &bevy_ecs::world::World, entity: Entity) -> bool {
+    ecs.get::<AbstractAgeableBaby>(entity)
+        .is_some_and(|baby| **baby)
+}
+
+fn can_be_sheared(client: &Client, entity: Entity) -> bool {
+    let ecs = client.ecs.lock();
+    let Some(kind) = ecs.get::<EntityKind>(entity) else {
+        return false;
+    };
+    match **kind {
+        azalea_registry::EntityKind::Sheep => {
+            !is_baby(&ecs, entity)
+                && !ecs
+                    .get::<SheepSheared>(entity)
+                    .is_some_and(|sheared| **sheared)
+        }
+        azalea_registry::EntityKind::Mooshroom => !is_baby(&ecs, entity),
+        azalea_registry::EntityKind::SnowGolem => ecs
+            .get::<HasPumpkin>(entity)
+            .is_some_and(|has_pumpkin| **has_pumpkin),
+        azalea_registry::EntityKind::Bogged => !ecs
+            .get::<BoggedSheared>(entity)
+            .is_some_and(|sheared| **sheared),
+        _ => false,
+    }
+}
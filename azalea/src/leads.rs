@@ -0,0 +1,87 @@
+//! Attach leads to mobs, tie them to fences, and take them off again.
+
+use azalea_client::{interact::EntityInteractEvent, Client};
+use azalea_core::position::BlockPos;
+use azalea_entity::{Dead, LeashHolder};
+use azalea_protocol::packets::game::s_interact::InteractionHand;
+use azalea_registry::Item;
+use bevy_ecs::{entity::Entity, query::Without};
+use tracing::warn;
+
+use crate::item_use::ItemUseClientExt;
+
+pub trait LeadClientExt {
+    fn attach_lead(&mut self, mob: Entity) -> bool;
+    fn detach_lead(&mut self, mob: Entity) -> bool;
+    fn tie_lead_to_fence(&mut self, fence: BlockPos);
+    fn leashed_mobs(&self) -> Vec<Entity>;
+}
+
+impl LeadClientExt for Client {
+    /// Put a lead on the given mob.
+    ///
+    /// If we're not already holding a lead, this will switch to one in our
+    /// hotbar first. Returns false if we don't have a lead in our hotbar.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::leads::LeadClientExt;
+    /// # use azalea::BlockPos;
+    /// # fn example(bot: &mut Client, cow: azalea::ecs::entity::Entity) {
+    /// bot.attach_lead(cow);
+    /// // the cow will follow us until we tie it to a fence
+    /// bot.tie_lead_to_fence(BlockPos::new(0, 64, 0));
+    /// # }
+    /// ```
+    fn attach_lead(&mut self, mob: Entity) -> bool {
+        if !self.select_matching_hotbar_item(&Item::Lead.into()) {
+            warn!("Tried to attach a lead without having one in our hotbar");
+            return false;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: mob,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+
+    /// Take the lead off of the given mob, dropping it as an item.
+    ///
+    /// This works for mobs that are leashed to us or to a fence. Returns false
+    /// if the mob isn't leashed.
+    fn detach_lead(&mut self, mob: Entity) -> bool {
+        let mut ecs = self.ecs.lock();
+        if ecs.get::<LeashHolder>(mob).is_none() {
+            return false;
+        }
+        ecs.send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: mob,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+        true
+    }
+
+    /// Tie all of the mobs that are leashed to us to the fence at the given
+    /// position.
+    ///
+    /// This is the same as right clicking the fence, so you should make sure
+    /// that we're close enough to it.
+    fn tie_lead_to_fence(&mut self, fence: BlockPos) {
+        self.block_interact(fence);
+    }
+
+    /// Get the mobs that are currently leashed to us.
+    fn leashed_mobs(&self) -> Vec<Entity> {
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query_filtered::<(Entity, &LeashHolder), Without<Dead>>();
+        query
+            .iter(&ecs)
+            .filter(|(_, leash_holder)| ***leash_holder == self.entity)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+}
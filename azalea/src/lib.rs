@@ -5,14 +5,33 @@
 #![feature(never_type)]
 
 pub mod accept_resource_packs;
+pub mod auto_breathe;
 pub mod auto_respawn;
 pub mod auto_tool;
 mod bot;
+pub mod chat_triggers;
 pub mod container;
+pub mod death_recovery;
+pub mod inventory_layout;
+pub mod item_frames;
+pub mod item_use;
+pub mod leads;
+pub mod look_arbiter;
+pub mod movement_safety;
 pub mod nearest_entity;
 pub mod pathfinder;
+pub mod pets;
+#[cfg(feature = "practice-server")]
+pub mod practice_server;
 pub mod prelude;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod reconnect;
+pub mod redstone;
+pub mod rng;
 pub mod swarm;
+pub mod trading;
+pub mod villagers;
 
 use std::net::SocketAddr;
 
@@ -22,7 +41,13 @@ pub use azalea_block as blocks;
 pub use azalea_brigadier as brigadier;
 pub use azalea_buf as buf;
 pub use azalea_chat::FormattedText;
+// functions are in a different namespace than modules, so this doesn't conflict
+// with azalea_client's `ping` module
+pub use azalea_client::ping::ping;
 pub use azalea_client::*;
+use azalea_client::{
+    bungeecord_forwarding::BungeeCordForwarding, proxy_protocol::ProxyProtocolHeader,
+};
 pub use azalea_core as core;
 // these are re-exported on this level because they're very common
 pub use azalea_core::{
@@ -39,8 +64,9 @@ pub use bevy_ecs as ecs;
 pub use bot::*;
 use ecs::component::Component;
 use futures::{future::BoxFuture, Future};
-use protocol::connect::Proxy;
+use protocol::connect::{Proxy, SocketOptions};
 use protocol::{resolver::ResolverError, ServerAddress};
+use reconnect::ReconnectPolicy;
 use swarm::SwarmBuilder;
 use thiserror::Error;
 
@@ -168,6 +194,44 @@ where
         self
     }
 
+    /// Automatically rejoin the server when the client is kicked or loses its
+    /// connection, instead of staying disconnected. The client keeps its state
+    /// and receives an [`Event::Reconnecting`] before every attempt.
+    ///
+    /// ```no_run
+    /// # use azalea::{prelude::*, reconnect::ReconnectPolicy};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// ClientBuilder::new()
+    ///     .set_handler(handle)
+    ///     .reconnect(ReconnectPolicy::exponential(
+    ///         Duration::from_secs(5),
+    ///         Duration::from_secs(60),
+    ///     ))
+    ///     .start(Account::offline("bot"), "localhost")
+    ///     .await;
+    /// # }
+    /// # #[derive(Component, Clone, Default)]
+    /// # pub struct State;
+    /// # async fn handle(mut bot: Client, event: Event, state: State) -> anyhow::Result<()> {
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.swarm = self.swarm.reconnect(policy);
+        self
+    }
+
+    /// Seed the bot's random number generator, so any random behavior is the
+    /// same every time it's run. See the [`rng`] module for more details.
+    #[must_use]
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.swarm = self.swarm.rng_seed(seed);
+        self
+    }
+
     /// Build this `ClientBuilder` into an actual [`Client`] and join the given
     /// server. If the client can't join, it'll keep retrying forever until it
     /// can.
@@ -226,7 +290,7 @@ pub struct NoState;
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub struct JoinOpts {
-    /// The Socks5 proxy that this bot will use.
+    /// The Socks5 or HTTP proxy that this bot will use.
     pub proxy: Option<Proxy>,
     /// Override the server address that this specific bot will send in the
     /// handshake packet.
@@ -234,6 +298,16 @@ pub struct JoinOpts {
     /// Override the socket address that this specific bot will use to connect
     /// to the server.
     pub custom_resolved_address: Option<SocketAddr>,
+    /// Add BungeeCord's forwarded player info to the handshake, so we can join
+    /// backend servers that have `bungeecord: true` without going through the
+    /// proxy.
+    pub bungeecord_forwarding: Option<BungeeCordForwarding>,
+    /// Send a PROXY protocol header when connecting, so we can join backend
+    /// servers that are behind HAProxy without going through it.
+    pub proxy_protocol: Option<ProxyProtocolHeader>,
+    /// The options for this bot's TCP socket, like the local address that it
+    /// connects from.
+    pub socket_options: Option<SocketOptions>,
 }
 
 impl JoinOpts {
@@ -251,9 +325,27 @@ impl JoinOpts {
         if let Some(custom_resolved_address) = other.custom_resolved_address {
             self.custom_resolved_address = Some(custom_resolved_address);
         }
+        if let Some(bungeecord_forwarding) = other.bungeecord_forwarding.clone() {
+            self.bungeecord_forwarding = Some(bungeecord_forwarding);
+        }
+        if let Some(proxy_protocol) = other.proxy_protocol.clone() {
+            self.proxy_protocol = Some(proxy_protocol);
+        }
+        if let Some(socket_options) = other.socket_options.clone() {
+            self.socket_options = Some(socket_options);
+        }
     }
 
     /// Set the proxy that this bot will use.
+    ///
+    /// Giving each bot in a swarm its own proxy makes them connect from
+    /// different IP addresses, so the server won't throttle them for joining
+    /// from the same one.
+    ///
+    /// ```
+    /// # use azalea::{protocol::connect::Proxy, JoinOpts};
+    /// let opts = JoinOpts::new().proxy(Proxy::socks5("127.0.0.1:1080".parse().unwrap(), None));
+    /// ```
     #[must_use]
     pub fn proxy(mut self, proxy: Proxy) -> Self {
         self.proxy = Some(proxy);
@@ -272,4 +364,49 @@ impl JoinOpts {
         self.custom_resolved_address = Some(custom_resolved_address);
         self
     }
+    /// Pretend that we're connecting through a BungeeCord proxy, which lets
+    /// us join backend servers directly.
+    ///
+    /// This only works on servers that trust anyone who can connect to them,
+    /// so it's mostly useful for private networks and testing. See
+    /// [`BungeeCordForwarding`] for more details.
+    #[must_use]
+    pub fn bungeecord_forwarding(mut self, forwarding: BungeeCordForwarding) -> Self {
+        self.bungeecord_forwarding = Some(forwarding);
+        self
+    }
+    /// Send a PROXY protocol header as soon as we connect, which makes
+    /// servers that are behind HAProxy think that we're coming from the
+    /// header's source address.
+    ///
+    /// ```
+    /// # use azalea::{proxy_protocol::ProxyProtocolHeader, JoinOpts};
+    /// let opts = JoinOpts::new()
+    ///     .proxy_protocol(ProxyProtocolHeader::new("10.0.0.2:51000".parse().unwrap()));
+    /// ```
+    #[must_use]
+    pub fn proxy_protocol(mut self, header: ProxyProtocolHeader) -> Self {
+        self.proxy_protocol = Some(header);
+        self
+    }
+    /// Set the options for this bot's TCP socket.
+    ///
+    /// Binding each bot to a different local address lets a host with more
+    /// than one IP spread the bots across them, and a read timeout makes
+    /// dead connections get noticed sooner.
+    ///
+    /// ```
+    /// # use azalea::{protocol::connect::SocketOptions, JoinOpts};
+    /// # use std::time::Duration;
+    /// let opts = JoinOpts::new().socket_options(SocketOptions {
+    ///     bind_address: Some("192.0.2.10:0".parse().unwrap()),
+    ///     read_timeout: Some(Duration::from_secs(30)),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    #[must_use]
+    pub fn socket_options(mut self, socket_options: SocketOptions) -> Self {
+        self.socket_options = Some(socket_options);
+        self
+    }
 }
@@ -0,0 +1,189 @@
+//! Decide where bots look when more than one thing wants to turn their head.
+//!
+//! Instead of setting [`LookDirection`] directly, systems submit requests to
+//! the [`LookArbiter`] for every tick that they want to be looking somewhere.
+//! Only the request with the highest [`LookPriority`] for each bot is used, so
+//! for example a plugin that's attacking something can keep looking at its
+//! target while the pathfinder is moving the bot, without the order that their
+//! systems run in mattering.
+//!
+//! ```
+//! # use azalea::prelude::*;
+//! # use azalea::ecs::prelude::*;
+//! # use azalea::look_arbiter::{LookArbiter, LookPriority, LookTarget};
+//! # use azalea::Vec3;
+//! #[derive(Component)]
+//! struct AttackTarget(Vec3);
+//!
+//! fn look_at_attack_target(
+//!     query: Query<(Entity, &AttackTarget)>,
+//!     mut look_arbiter: ResMut<LookArbiter>,
+//! ) {
+//!     for (entity, target) in &query {
+//!         look_arbiter.submit(entity, LookPriority::COMBAT, LookTarget::Position(target.0));
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use azalea_client::Client;
+use azalea_core::position::Vec3;
+use azalea_entity::{EyeHeight, LookDirection, Position};
+use bevy_ecs::prelude::*;
+use tracing::trace;
+
+use crate::bot::direction_looking_at;
+
+/// How important a look request is. When multiple requests are submitted for
+/// the same bot in a tick, the one with the highest priority wins.
+///
+/// You can make your own priorities in between the constants here if you need
+/// to, like `LookPriority(LookPriority::COMBAT.0 + 1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LookPriority(pub u8);
+
+impl LookPriority {
+    /// For looking around when there's nothing else to do.
+    pub const IDLE: Self = Self(0);
+    /// Used by the pathfinder, and for walking and jumping towards positions.
+    pub const MOVEMENT: Self = Self(64);
+    /// Used by [`BotClientExt::look_at`] and [`LookAtEvent`].
+    ///
+    /// [`BotClientExt::look_at`]: crate::prelude::BotClientExt::look_at
+    /// [`LookAtEvent`]: crate::LookAtEvent
+    pub const DEFAULT: Self = Self(128);
+    /// For things that only work if we're looking at what we're interacting
+    /// with, like attacking.
+    pub const COMBAT: Self = Self(192);
+    pub const HIGHEST: Self = Self(u8::MAX);
+}
+
+impl Default for LookPriority {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Where a [`LookRequest`] wants the bot to look.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LookTarget {
+    /// Look at a position in the world from the bot's eyes.
+    Position(Vec3),
+    /// Look in an exact direction.
+    Direction(LookDirection),
+    /// Only change the yaw (looking to the side), and keep looking up or down
+    /// as much as we already were.
+    YRot(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookRequest {
+    pub priority: LookPriority,
+    pub target: LookTarget,
+}
+
+/// A resource with the look requests that were submitted since they were last
+/// resolved.
+///
+/// Requests only last until [`resolve_look_requests`] runs in the next
+/// `Update`, so systems that want to keep looking somewhere should submit a
+/// request every tick.
+#[derive(Resource, Debug, Default)]
+pub struct LookArbiter {
+    requests: HashMap<Entity, LookRequest>,
+}
+
+impl LookArbiter {
+    /// Ask for the entity to look at the target. This is ignored if a request
+    /// with a higher priority was already submitted for the entity, and
+    /// replaces any request with the same or a lower priority.
+    pub fn submit(&mut self, entity: Entity, priority: LookPriority, target: LookTarget) {
+        if let Some(existing) = self.requests.get(&entity) {
+            if existing.priority > priority {
+                trace!(
+                    "Ignoring look request for {entity:?} with priority {priority:?} because \
+                     there's already one with priority {:?}",
+                    existing.priority
+                );
+                return;
+            }
+        }
+        self.requests
+            .insert(entity, LookRequest { priority, target });
+    }
+
+    /// The request that will be used for the entity if nothing with a higher
+    /// priority is submitted before they're resolved.
+    pub fn get(&self, entity: Entity) -> Option<&LookRequest> {
+        self.requests.get(&entity)
+    }
+}
+
+/// Apply the winning look request for every entity to its [`LookDirection`].
+pub fn resolve_look_requests(
+    mut look_arbiter: ResMut<LookArbiter>,
+    mut query: Query<(&Position, &EyeHeight, &mut LookDirection)>,
+) {
+    if look_arbiter.requests.is_empty() {
+        return;
+    }
+    for (entity, request) in look_arbiter.requests.drain() {
+        let Ok((position, eye_height, mut look_direction)) = query.get_mut(entity) else {
+            continue;
+        };
+        match request.target {
+            LookTarget::Position(target) => {
+                *look_direction = direction_looking_at(&position.up(eye_height.into()), &target);
+            }
+            LookTarget::Direction(direction) => {
+                *look_direction = direction;
+            }
+            LookTarget::YRot(y_rot) => {
+                look_direction.y_rot = y_rot;
+            }
+        }
+    }
+}
+
+pub trait LookArbiterClientExt {
+    fn look_at_with_priority(&mut self, position: Vec3, priority: LookPriority);
+}
+
+impl LookArbiterClientExt for Client {
+    /// Same as [`BotClientExt::look_at`], but with a different priority than
+    /// [`LookPriority::DEFAULT`].
+    ///
+    /// [`BotClientExt::look_at`]: crate::prelude::BotClientExt::look_at
+    fn look_at_with_priority(&mut self, position: Vec3, priority: LookPriority) {
+        self.ecs.lock().resource_mut::<LookArbiter>().submit(
+            self.entity,
+            priority,
+            LookTarget::Position(position),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highest_priority_wins() {
+        let entity = Entity::from_raw(0);
+        let mut look_arbiter = LookArbiter::default();
+        look_arbiter.submit(entity, LookPriority::COMBAT, LookTarget::YRot(1.));
+        look_arbiter.submit(entity, LookPriority::MOVEMENT, LookTarget::YRot(2.));
+        assert_eq!(
+            look_arbiter.get(entity).unwrap().target,
+            LookTarget::YRot(1.)
+        );
+
+        // the last request wins if they have the same priority
+        look_arbiter.submit(entity, LookPriority::COMBAT, LookTarget::YRot(3.));
+        assert_eq!(
+            look_arbiter.get(entity).unwrap().target,
+            LookTarget::YRot(3.)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Stop bots from walking into the void, into unloaded chunks, or outside of
+//! the world border.
+
+use azalea_client::{
+    movement::{local_player_ai_step, tick_controls},
+    InstanceHolder, PhysicsState, WalkDirection, WorldBorder,
+};
+use azalea_core::{
+    position::{BlockPos, ChunkPos, ChunkSectionBlockPos, Vec3},
+    tick::GameTick,
+};
+use azalea_entity::{input_vector, InLoadedChunk, Jumping, LookDirection, Physics, Position};
+use azalea_world::{
+    chunk_storage::section_index, heightmap::HeightmapKind, palette::Palette, Instance,
+};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::app::{App, Plugin};
+use crate::pathfinder::ExecutingPath;
+
+/// How far ahead of the player we check for unsafe positions.
+const LOOK_AHEAD_DISTANCE: f64 = 1.;
+
+/// A plugin that makes bots refuse to walk somewhere that would probably get
+/// them killed or stuck, and sends a [`MovementRefusedEvent`] when that
+/// happens.
+///
+/// Bots that are following a path from the pathfinder are still stopped at the
+/// world border and at unloaded chunks, but they're allowed to walk over the
+/// void since the pathfinder already knows which blocks are safe to walk on
+/// (and parkouring over gaps would be impossible otherwise).
+///
+/// Movement is only stopped for the ticks where it's unsafe, so anything that
+/// was [held](azalea_client::movement_arbiter::MovementArbiter::hold) (like
+/// [`Client::walk`](azalea_client::Client::walk)) keeps being held and works
+/// again once the bot turns away from the danger.
+#[derive(Clone, Default)]
+pub struct MovementSafetyPlugin;
+impl Plugin for MovementSafetyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MovementRefusedEvent>().add_systems(
+            GameTick,
+            refuse_unsafe_movement
+                .after(tick_controls)
+                .before(local_player_ai_step),
+        );
+    }
+}
+
+/// An event sent when a local player tried to walk somewhere unsafe and their
+/// movement was stopped.
+#[derive(Event, Debug, Clone)]
+pub struct MovementRefusedEvent {
+    pub entity: Entity,
+    /// The position that we refused to walk into.
+    pub position: Vec3,
+    pub reason: MovementRefusedReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementRefusedReason {
+    /// There are no blocks below the position, so we'd fall into the void.
+    Void,
+    /// The chunk at the position isn't loaded, so we don't know what's there.
+    UnloadedChunk,
+    /// The position is outside the world border.
+    WorldBorder,
+}
+
+/// A marker component that disables the checks from [`MovementSafetyPlugin`]
+/// for a bot.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AllowUnsafeMovement;
+
+/// A component that's present on bots while their movement is being refused,
+/// so [`MovementRefusedEvent`] is only sent when they start trying to walk
+/// somewhere unsafe instead of on every tick.
+#[derive(Component, Clone, Debug)]
+pub struct RefusingMovement(pub MovementRefusedReason);
+
+/// Stop bots from walking somewhere unsafe.
+///
+/// This only happens while the bot is on the ground and not jumping, since
+/// there's nothing we can do if we're already in the air.
+#[allow(clippy::type_complexity)]
+pub fn refuse_unsafe_movement(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut PhysicsState,
+            &Position,
+            &LookDirection,
+            &Physics,
+            &Jumping,
+            &InstanceHolder,
+            Option<&WorldBorder>,
+            Option<&RefusingMovement>,
+            Has<ExecutingPath>,
+        ),
+        (With<InLoadedChunk>, Without<AllowUnsafeMovement>),
+    >,
+    mut movement_refused_events: EventWriter<MovementRefusedEvent>,
+) {
+    for (
+        entity,
+        mut physics_state,
+        position,
+        look_direction,
+        physics,
+        jumping,
+        instance_holder,
+        world_border,
+        refusing_movement,
+        is_pathfinding,
+    ) in &mut query
+    {
+        let reason = if (physics_state.forward_impulse == 0. && physics_state.left_impulse == 0.)
+            || !physics.on_ground()
+            || **jumping
+        {
+            None
+        } else {
+            let direction = input_vector(
+                look_direction,
+                1.,
+                &Vec3::new(
+                    physics_state.left_impulse as f64,
+                    0.,
+                    physics_state.forward_impulse as f64,
+                ),
+            );
+            let target = **position + direction * LOOK_AHEAD_DISTANCE;
+            unsafe_movement_reason(&instance_holder.instance.read(), target, world_border)
+                .filter(|reason| !is_pathfinding || *reason != MovementRefusedReason::Void)
+                .map(|reason| (reason, target))
+        };
+
+        let Some((reason, target)) = reason else {
+            if refusing_movement.is_some() {
+                commands.entity(entity).remove::<RefusingMovement>();
+            }
+            continue;
+        };
+
+        // the keys stay held so we'll start moving again when it's safe, so only send
+        // the event when this starts
+        if refusing_movement.is_none_or(|refusing| refusing.0 != reason) {
+            debug!("Refusing to move {entity:?} to {target} because of {reason:?}");
+            commands.entity(entity).insert(RefusingMovement(reason));
+            movement_refused_events.send(MovementRefusedEvent {
+                entity,
+                position: target,
+                reason,
+            });
+        }
+        physics_state.move_direction = WalkDirection::None;
+        physics_state.trying_to_sprint = false;
+        physics_state.forward_impulse = 0.;
+        physics_state.left_impulse = 0.;
+    }
+}
+
+/// Returns why it'd be unsafe for a player to walk to the given position, or
+/// `None` if it's safe.
+pub fn unsafe_movement_reason(
+    world: &Instance,
+    position: Vec3,
+    world_border: Option<&WorldBorder>,
+) -> Option<MovementRefusedReason> {
+    if let Some(world_border) = world_border {
+        if !world_border.contains(position) {
+            return Some(MovementRefusedReason::WorldBorder);
+        }
+    }
+
+    let block_pos = BlockPos::from(position);
+    if world.chunks.get(&ChunkPos::from(&block_pos)).is_none() {
+        return Some(MovementRefusedReason::UnloadedChunk);
+    }
+
+    // if there's any block below us then we won't fall into the void
+    if !has_block_below(world, block_pos) {
+        return Some(MovementRefusedReason::Void);
+    }
+    None
+}
+
+/// Whether there's a block that isn't air at or below the position.
+///
+/// The heightmap usually answers this right away, and when there's something
+/// above us we skip the sections that are only air instead of checking every
+/// block in the column.
+fn has_block_below(world: &Instance, pos: BlockPos) -> bool {
+    let Some(top) = world
+        .chunks
+        .top_block_at(pos.x, pos.z, HeightmapKind::WorldSurface)
+    else {
+        // there are no blocks in the whole column
+        return false;
+    };
+    if top.y <= pos.y {
+        return true;
+    }
+
+    let Some(chunk) = world.chunks.get(&ChunkPos::from(&pos)) else {
+        return false;
+    };
+    let chunk = chunk.read();
+    let min_y = world.chunks.min_y;
+    let (x, z) = (pos.x.rem_euclid(16) as u8, pos.z.rem_euclid(16) as u8);
+    let mut y = pos.y;
+    while y >= min_y {
+        let index = section_index(y, min_y) as usize;
+        let section_min_y = min_y + index as i32 * 16;
+        let Some(section) = chunk.sections.get(index) else {
+            y = section_min_y - 1;
+            continue;
+        };
+        if matches!(section.states.palette, Palette::SingleValue(0)) {
+            // the section is only air
+            y = section_min_y - 1;
+            continue;
+        }
+        let block_state = section.get(ChunkSectionBlockPos::new(x, (y - section_min_y) as u8, z));
+        if !block_state.is_air() {
+            return true;
+        }
+        y -= 1;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_client::movement_arbiter::{MovementArbiter, MovementPriority, MovementRequest};
+    use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
+
+    use super::*;
+    use crate::pathfinder::simulation::{SimulatedPlayerBundle, Simulation};
+
+    fn chunks_with_blocks(blocks: &[BlockPos]) -> (PartialChunkStorage, ChunkStorage) {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut chunks = ChunkStorage::default();
+        partial_chunks.set(&ChunkPos::new(0, 0), Some(Chunk::default()), &mut chunks);
+        for pos in blocks {
+            chunks.set_block_state(pos, azalea_registry::Block::Stone.into());
+        }
+        (partial_chunks, chunks)
+    }
+
+    #[test]
+    fn test_unsafe_movement_reason() {
+        let (_partial_chunks, chunks) = chunks_with_blocks(&[
+            BlockPos::new(0, 60, 0),
+            // a roof with nothing below it
+            BlockPos::new(2, 80, 0),
+        ]);
+        let world = Instance::from(chunks);
+
+        assert_eq!(
+            unsafe_movement_reason(&world, Vec3::new(0.5, 70., 0.5), None),
+            None
+        );
+        assert_eq!(
+            unsafe_movement_reason(&world, Vec3::new(1.5, 70., 0.5), None),
+            Some(MovementRefusedReason::Void)
+        );
+        assert_eq!(
+            unsafe_movement_reason(&world, Vec3::new(2.5, 70., 0.5), None),
+            Some(MovementRefusedReason::Void)
+        );
+        assert_eq!(
+            unsafe_movement_reason(&world, Vec3::new(20.5, 70., 0.5), None),
+            Some(MovementRefusedReason::UnloadedChunk)
+        );
+        let world_border = WorldBorder {
+            center_x: 0.,
+            center_z: 0.,
+            size: 1.,
+        };
+        assert_eq!(
+            unsafe_movement_reason(&world, Vec3::new(0.6, 70., 0.5), Some(&world_border)),
+            Some(MovementRefusedReason::WorldBorder)
+        );
+    }
+
+    #[test]
+    fn test_refuse_walking_off_edge() {
+        let (_partial_chunks, chunks) = chunks_with_blocks(&[BlockPos::new(0, 70, 0)]);
+        let mut simulation =
+            Simulation::new(chunks, SimulatedPlayerBundle::new(Vec3::new(0.5, 71., 0.5)));
+        simulation.app.add_plugins(MovementSafetyPlugin);
+        let entity = simulation.entity;
+        simulation
+            .app
+            .world_mut()
+            .resource_mut::<MovementArbiter>()
+            .hold(
+                entity,
+                MovementPriority::DEFAULT,
+                MovementRequest::walk(WalkDirection::Forward),
+            );
+
+        for _ in 0..40 {
+            simulation.tick();
+        }
+        assert_eq!(
+            BlockPos::from(simulation.position()),
+            BlockPos::new(0, 71, 0)
+        );
+        assert_eq!(
+            simulation.get_component::<RefusingMovement>().map(|r| r.0),
+            Some(MovementRefusedReason::Void)
+        );
+        // we're still trying to walk, so we'll move again if we turn around
+        assert_eq!(
+            simulation
+                .app
+                .world()
+                .resource::<MovementArbiter>()
+                .resolve(entity),
+            (WalkDirection::Forward, false)
+        );
+    }
+}
@@ -1,9 +1,10 @@
+use azalea_core::position::Vec3;
 use azalea_entity::Position;
-use azalea_world::{InstanceName, MinecraftEntityId};
+use azalea_world::{InstanceContainer, InstanceName, MinecraftEntityId};
 use bevy_ecs::{
     prelude::Entity,
     query::{QueryFilter, With},
-    system::{Query, SystemParam},
+    system::{Query, Res, SystemParam},
 };
 
 /// This system parameter can be used as a shorthand for quickly finding an
@@ -55,6 +56,8 @@ where
         (Entity, &'static InstanceName, &'static Position),
         (With<MinecraftEntityId>, F),
     >,
+
+    instance_container: Res<'w, InstanceContainer>,
 }
 
 impl<'a, F> EntityFinder<'_, '_, F>
@@ -64,28 +67,16 @@ where
     /// Gets the nearest entity to the given position and world instance name.
     /// This method will return `None` if there are no entities within range. If
     /// multiple entities are within range, only the closest one is returned.
+    ///
+    /// Only the entities in the chunks around the position are checked, see
+    /// [`Instance::nearest_entity`](azalea_world::Instance::nearest_entity).
     pub fn nearest_to_position(
         &'a self,
         position: &Position,
         instance_name: &InstanceName,
         max_distance: f64,
     ) -> Option<Entity> {
-        let mut nearest_entity = None;
-        let mut min_distance = max_distance;
-
-        for (target_entity, e_instance, e_pos) in self.filtered_entities.iter() {
-            if e_instance != instance_name {
-                continue;
-            }
-
-            let target_distance = position.distance_to(e_pos);
-            if target_distance < min_distance {
-                nearest_entity = Some(target_entity);
-                min_distance = target_distance;
-            }
-        }
-
-        nearest_entity
+        self.nearest_excluding(position, instance_name, max_distance, None)
     }
 
     /// Gets the nearest entity to the given entity. This method will return
@@ -96,26 +87,24 @@ where
             return None;
         };
 
-        let mut nearest_entity = None;
-        let mut min_distance = max_distance;
-
-        for (target_entity, e_instance, e_pos) in self.filtered_entities.iter() {
-            if entity == target_entity {
-                continue;
-            };
-
-            if e_instance != instance_name {
-                continue;
-            }
+        self.nearest_excluding(position, instance_name, max_distance, Some(entity))
+    }
 
-            let target_distance = position.distance_to(e_pos);
-            if target_distance < min_distance {
-                nearest_entity = Some(target_entity);
-                min_distance = target_distance;
+    fn nearest_excluding(
+        &self,
+        position: &Position,
+        instance_name: &InstanceName,
+        max_distance: f64,
+        excluded: Option<Entity>,
+    ) -> Option<Entity> {
+        let instance = self.instance_container.get(instance_name)?;
+        let instance = instance.read();
+        instance.nearest_entity(**position, max_distance, |target_entity| {
+            if Some(target_entity) == excluded {
+                return None;
             }
-        }
-
-        nearest_entity
+            self.position_of(target_entity, instance_name)
+        })
     }
 
     /// This function get an iterator over all nearby entities to the given
@@ -130,20 +119,8 @@ where
         instance_name: &'a InstanceName,
         max_distance: f64,
     ) -> impl Iterator<Item = (Entity, f64)> + 'a {
-        self.filtered_entities
-            .iter()
-            .filter_map(move |(target_entity, e_instance, e_pos)| {
-                if e_instance != instance_name {
-                    return None;
-                }
-
-                let distance = position.distance_to(e_pos);
-                if distance < max_distance {
-                    Some((target_entity, distance))
-                } else {
-                    None
-                }
-            })
+        self.nearby_excluding(position, instance_name, max_distance, None)
+            .into_iter()
     }
 
     /// This function get an iterator over all nearby entities to the given
@@ -157,33 +134,46 @@ where
         entity: Entity,
         max_distance: f64,
     ) -> impl Iterator<Item = (Entity, f64)> + 'a {
-        let position;
-        let instance_name;
-        if let Ok((pos, instance)) = self.all_entities.get(entity) {
-            position = *pos;
-            instance_name = Some(instance);
+        let nearby = if let Ok((position, instance_name)) = self.all_entities.get(entity) {
+            self.nearby_excluding(position, instance_name, max_distance, Some(entity))
         } else {
-            position = Position::default();
-            instance_name = None;
+            Vec::new()
         };
+        nearby.into_iter()
+    }
 
-        self.filtered_entities
-            .iter()
-            .filter_map(move |(target_entity, e_instance, e_pos)| {
-                if entity == target_entity {
-                    return None;
-                }
-
-                if Some(e_instance) != instance_name {
+    fn nearby_excluding(
+        &self,
+        position: &Position,
+        instance_name: &InstanceName,
+        max_distance: f64,
+        excluded: Option<Entity>,
+    ) -> Vec<(Entity, f64)> {
+        let Some(instance) = self.instance_container.get(instance_name) else {
+            return Vec::new();
+        };
+        let instance = instance.read();
+        instance
+            .entities_in_radius(**position, max_distance, |target_entity| {
+                if Some(target_entity) == excluded {
                     return None;
                 }
-
-                let distance = position.distance_to(e_pos);
-                if distance < max_distance {
-                    Some((target_entity, distance))
-                } else {
-                    None
-                }
+                self.position_of(target_entity, instance_name)
+            })
+            .into_iter()
+            .filter_map(|target_entity| {
+                let target_position = self.position_of(target_entity, instance_name)?;
+                let distance = position.distance_to(&target_position);
+                // entities_in_radius includes entities that are exactly max_distance away
+                (distance < max_distance).then_some((target_entity, distance))
             })
+            .collect()
+    }
+
+    /// The position of the entity if it matches the filter and is in the
+    /// instance.
+    fn position_of(&self, entity: Entity, instance_name: &InstanceName) -> Option<Vec3> {
+        let (_, e_instance, e_pos) = self.filtered_entities.get(entity).ok()?;
+        (e_instance == instance_name).then_some(**e_pos)
     }
 }
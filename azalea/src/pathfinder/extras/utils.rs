@@ -9,31 +9,18 @@ pub fn get_reachable_blocks_around_player(
     player_position: BlockPos,
     chunk_storage: &ChunkStorage,
 ) -> Vec<BlockPos> {
-    // check a 12x12x12 area around the player
-    let mut blocks = Vec::new();
-
-    for x in -6..=6 {
-        // y is 1 up to somewhat offset for the eye height
-        for y in -5..=7 {
-            for z in -6..=6 {
-                let block_pos = player_position + BlockPos::new(x, y, z);
-                let block_state = chunk_storage
-                    .get_block_state(&block_pos)
-                    .unwrap_or_default();
-
-                if block_state.is_air() {
-                    // fast path, skip if it's air
-                    continue;
-                }
-
-                if can_reach_block(chunk_storage, player_position, block_pos) {
-                    blocks.push(block_pos);
-                }
-            }
-        }
-    }
-
-    blocks
+    // check a 12x12x12 area around the player.
+    // y is 1 up to somewhat offset for the eye height
+    chunk_storage
+        .get_block_states_in_aabb(
+            player_position + BlockPos::new(-6, -5, -6),
+            player_position + BlockPos::new(6, 7, 6),
+        )
+        // fast path, skip if it's air
+        .filter(|(_, block_state)| !block_state.is_air())
+        .map(|(block_pos, _)| block_pos)
+        .filter(|&block_pos| can_reach_block(chunk_storage, player_position, block_pos))
+        .collect()
 }
 
 pub fn pick_closest_block(position: BlockPos, blocks: &[BlockPos]) -> Option<BlockPos> {
@@ -1,17 +1,23 @@
 use std::{cell::UnsafeCell, ops::RangeInclusive};
 
+use azalea_block::fluid_state::FluidKind;
 use azalea_block::{
     block_state::BlockStateIntegerRepr, properties::Waterlogged, BlockState, BlockStates,
 };
+use azalea_entity::{effects::ActiveEffects, Attributes, FluidOnEyes, Physics};
 use azalea_inventory::Menu;
 use nohash_hasher::IntMap;
 
 use super::costs::BLOCK_BREAK_ADDITIONAL_PENALTY;
-use crate::auto_tool::best_tool_in_hotbar_for_block;
+use crate::auto_tool::accurate_best_tool_in_hotbar_for_block;
 
 pub struct MiningCache {
     block_state_id_costs: UnsafeCell<IntMap<BlockStateIntegerRepr, f32>>,
     inventory_menu: Option<Menu>,
+    /// The player's attributes and effects, since things like haste and mining
+    /// fatigue change how long it takes to break blocks.
+    attributes: Attributes,
+    active_effects: ActiveEffects,
 
     water_block_state_range: RangeInclusive<BlockStateIntegerRepr>,
     lava_block_state_range: RangeInclusive<BlockStateIntegerRepr>,
@@ -70,12 +76,27 @@ impl MiningCache {
         Self {
             block_state_id_costs: UnsafeCell::new(IntMap::default()),
             inventory_menu,
+            attributes: Attributes::default(),
+            active_effects: ActiveEffects::default(),
             water_block_state_range,
             lava_block_state_range,
             falling_blocks,
         }
     }
 
+    /// Use the given attributes and effects when calculating how long it takes
+    /// to mine blocks, instead of the defaults for a player without any
+    /// effects.
+    pub fn with_mining_modifiers(
+        mut self,
+        attributes: Attributes,
+        active_effects: ActiveEffects,
+    ) -> Self {
+        self.attributes = attributes;
+        self.active_effects = active_effects;
+        self
+    }
+
     pub fn cost_for(&self, block: BlockState) -> f32 {
         let Some(inventory_menu) = &self.inventory_menu else {
             return f32::INFINITY;
@@ -87,7 +108,17 @@ impl MiningCache {
         if let Some(cost) = block_state_id_costs.get(&block.id) {
             *cost
         } else {
-            let best_tool_result = best_tool_in_hotbar_for_block(block, inventory_menu);
+            // paths never make us mine while we're in the air or underwater
+            let mut physics = Physics::default();
+            physics.set_on_ground(true);
+            let best_tool_result = accurate_best_tool_in_hotbar_for_block(
+                block,
+                inventory_menu,
+                &physics,
+                &FluidOnEyes::new(FluidKind::Empty),
+                &self.attributes,
+                &self.active_effects,
+            );
             let mut cost = 1. / best_tool_result.percentage_per_tick;
 
             cost += BLOCK_BREAK_ADDITIONAL_PENALTY;
@@ -116,3 +147,49 @@ impl MiningCache {
 pub fn is_waterlogged(block: BlockState) -> bool {
     block.property::<Waterlogged>().unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use azalea_entity::effects::MobEffectData;
+    use azalea_registry::MobEffect;
+
+    use super::*;
+
+    fn effects(effect: MobEffect, amplifier: u32) -> ActiveEffects {
+        let mut active_effects = ActiveEffects::default();
+        active_effects.insert(
+            effect,
+            MobEffectData {
+                amplifier,
+                duration_ticks: -1,
+            },
+        );
+        active_effects
+    }
+
+    #[test]
+    fn test_effects_change_mining_cost() {
+        let menu = Menu::Player(azalea_inventory::Player::default());
+        let stone = BlockState::from(azalea_registry::Block::Stone);
+
+        let normal = MiningCache::new(Some(menu.clone())).cost_for(stone);
+        let haste = MiningCache::new(Some(menu.clone()))
+            .with_mining_modifiers(Attributes::default(), effects(MobEffect::Haste, 1))
+            .cost_for(stone);
+        let mining_fatigue = MiningCache::new(Some(menu))
+            .with_mining_modifiers(Attributes::default(), effects(MobEffect::MiningFatigue, 0))
+            .cost_for(stone);
+
+        assert!(haste < normal, "{haste} < {normal}");
+        assert!(mining_fatigue > normal, "{mining_fatigue} > {normal}");
+    }
+
+    #[test]
+    fn test_cant_mine_without_inventory() {
+        let mining_cache = MiningCache::new(None);
+        assert_eq!(
+            mining_cache.cost_for(azalea_registry::Block::Stone.into()),
+            f32::INFINITY
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Noticing when chunks that the pathfinder needs aren't loaded, and asking the
+//! server to send them again.
+//!
+//! This can happen when the server forgets to send a chunk or while we're
+//! switching worlds, and without it the bot would wait forever for a path.
+
+use std::{
+    collections::HashMap,
+    iter,
+    time::{Duration, Instant},
+};
+
+use azalea_client::chunks::{ChunkResend, RequestChunkResendEvent};
+use azalea_core::position::{BlockPos, ChunkPos};
+use azalea_entity::Position;
+use azalea_world::{InstanceContainer, InstanceName};
+use bevy_ecs::prelude::*;
+use tracing::warn;
+
+use super::{ExecutingPath, Pathfinder};
+
+/// How long a chunk has to be missing before we ask for it, since chunks
+/// usually arrive soon after we teleport or change worlds.
+const MISSING_CHUNK_GRACE_PERIOD: Duration = Duration::from_secs(2);
+/// How long to wait after a resend finished before asking the server to resend
+/// chunks again.
+const RESEND_COOLDOWN: Duration = Duration::from_secs(5);
+/// How many nodes ahead in the path are checked for missing chunks.
+const NODES_TO_CHECK: usize = 20;
+
+#[derive(Default)]
+pub struct MissingChunkState {
+    missing_since: Option<Instant>,
+    last_request: Option<Instant>,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn request_missing_chunks(
+    query: Query<
+        (
+            Entity,
+            &Pathfinder,
+            Option<&ExecutingPath>,
+            &Position,
+            &InstanceName,
+        ),
+        Without<ChunkResend>,
+    >,
+    instance_container: Res<InstanceContainer>,
+    mut states: Local<HashMap<Entity, MissingChunkState>>,
+    mut request_chunk_resend_events: EventWriter<RequestChunkResendEvent>,
+) {
+    for (entity, pathfinder, executing_path, position, instance_name) in &query {
+        if pathfinder.goal.is_none() {
+            states.remove(&entity);
+            continue;
+        }
+        let Some(world_lock) = instance_container.get(instance_name) else {
+            continue;
+        };
+        let world = world_lock.read();
+
+        let path_targets = executing_path
+            .into_iter()
+            .flat_map(|executing_path| executing_path.path.iter().take(NODES_TO_CHECK))
+            .map(|movement| movement.target);
+        let mut missing = Vec::new();
+        for pos in iter::once(BlockPos::from(position)).chain(path_targets) {
+            let chunk_pos = ChunkPos::from(&pos);
+            if !missing.contains(&chunk_pos) && world.chunks.get(&chunk_pos).is_none() {
+                missing.push(chunk_pos);
+            }
+        }
+        if missing.is_empty() {
+            states.remove(&entity);
+            continue;
+        }
+
+        let state = states.entry(entity).or_default();
+        let missing_since = *state.missing_since.get_or_insert_with(Instant::now);
+        if missing_since.elapsed() < MISSING_CHUNK_GRACE_PERIOD
+            || state
+                .last_request
+                .is_some_and(|last_request| last_request.elapsed() < RESEND_COOLDOWN)
+        {
+            continue;
+        }
+
+        warn!(
+            "The chunks at {missing:?} are needed for pathfinding but aren't loaded, asking the server to resend them"
+        );
+        state.last_request = Some(Instant::now());
+        request_chunk_resend_events.send(RequestChunkResendEvent {
+            entity,
+            chunks: missing,
+        });
+    }
+}
@@ -7,6 +7,7 @@ pub mod costs;
 mod debug;
 pub mod goals;
 pub mod mining;
+mod missing_chunks;
 pub mod moves;
 pub mod rel_block_pos;
 pub mod simulation;
@@ -23,20 +24,22 @@ use astar::PathfinderTimeout;
 use azalea_client::inventory::{Inventory, InventorySet, SetSelectedHotbarSlotEvent};
 use azalea_client::mining::{Mining, StartMiningBlockEvent};
 use azalea_client::movement::MoveEventsSet;
-use azalea_client::{InstanceHolder, StartSprintEvent, StartWalkEvent};
-use azalea_core::position::BlockPos;
+use azalea_client::movement_arbiter::{MovementArbiter, MovementPriority};
+use azalea_client::InstanceHolder;
+use azalea_core::position::{BlockPos, ChunkPos};
 use azalea_core::tick::GameTick;
+use azalea_entity::effects::ActiveEffects;
 use azalea_entity::metadata::Player;
-use azalea_entity::LocalEntity;
+use azalea_entity::{Attributes, LocalEntity};
 use azalea_entity::{Physics, Position};
 use azalea_physics::PhysicsSet;
-use azalea_world::{InstanceContainer, InstanceName};
+use azalea_world::{snapshot::InstanceSnapshot, InstanceContainer, InstanceName};
 use bevy_app::{PreUpdate, Update};
 use bevy_ecs::prelude::Event;
 use bevy_ecs::query::Changed;
 use bevy_ecs::schedule::IntoSystemConfigs;
 use bevy_tasks::{AsyncComputeTaskPool, Task};
-use futures_lite::future;
+use futures_lite::{future, Future};
 use goals::BlockPosGoal;
 use parking_lot::RwLock;
 use rel_block_pos::RelBlockPos;
@@ -48,16 +51,16 @@ use self::goals::Goal;
 use self::mining::MiningCache;
 use self::moves::{ExecuteCtx, IsReachedCtx, SuccessorsFn};
 use crate::app::{App, Plugin};
-use crate::bot::{JumpEvent, LookAtEvent};
+use crate::bot::{BotClientExt, JumpEvent};
 use crate::ecs::{
     component::Component,
     entity::Entity,
     event::{EventReader, EventWriter},
     query::{With, Without},
-    system::{Commands, Query, Res},
+    system::{Commands, Query, Res, ResMut},
 };
+use crate::look_arbiter::LookArbiter;
 use crate::pathfinder::{astar::a_star, moves::PathfinderCtx, world::CachedWorld};
-use crate::WalkDirection;
 
 #[derive(Clone, Default)]
 pub struct PathfinderPlugin;
@@ -83,6 +86,10 @@ impl Plugin for PathfinderPlugin {
                     .after(PhysicsSet)
                     .after(azalea_client::movement::send_position),
             )
+            .add_systems(
+                GameTick,
+                missing_chunks::request_missing_chunks.after(PhysicsSet),
+            )
             .add_systems(PreUpdate, add_default_pathfinder)
             .add_systems(
                 Update,
@@ -107,6 +114,15 @@ pub struct Pathfinder {
     pub successors_fn: Option<SuccessorsFn>,
     pub is_calculating: bool,
     pub allow_mining: bool,
+    /// Whether paths can go through chunks that were unloaded recently but are
+    /// still in the world's chunk cache, which is useful for planning long
+    /// paths. The chunk cache has to be enabled with
+    /// [`ChunkCachePlugin`](azalea_client::chunks::ChunkCachePlugin) for this
+    /// to do anything.
+    ///
+    /// The cached chunks might be out of date, so paths through them can be
+    /// wrong. They're recalculated once the chunks are loaded again.
+    pub use_cached_chunks: bool,
 
     pub min_timeout: Option<PathfinderTimeout>,
     pub max_timeout: Option<PathfinderTimeout>,
@@ -130,7 +146,7 @@ pub struct ExecutingPath {
 /// Also see [`PathfinderClientExt::goto`].
 ///
 /// This event is read by [`goto_listener`].
-#[derive(Event)]
+#[derive(Event, Clone)]
 pub struct GotoEvent {
     /// The local bot entity that will do the pathfinding and execute the path.
     pub entity: Entity,
@@ -167,6 +183,9 @@ pub struct PathFoundEvent {
     pub is_partial: bool,
     pub successors_fn: SuccessorsFn,
     pub allow_mining: bool,
+    /// Whether the path was allowed to go through cached chunks, see
+    /// [`Pathfinder::use_cached_chunks`].
+    pub use_cached_chunks: bool,
 }
 
 #[allow(clippy::type_complexity)]
@@ -183,6 +202,11 @@ pub trait PathfinderClientExt {
     fn goto(&self, goal: impl Goal + Send + Sync + 'static);
     fn goto_without_mining(&self, goal: impl Goal + Send + Sync + 'static);
     fn stop_pathfinding(&self);
+    fn wait_until_goto_target_reached(&self) -> impl Future<Output = ()> + Send;
+    fn wait_until_goto_target_reached_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = bool> + Send;
 }
 
 impl PathfinderClientExt for azalea_client::Client {
@@ -225,6 +249,79 @@ impl PathfinderClientExt for azalea_client::Client {
             force: false,
         });
     }
+
+    /// Wait until we've stopped pathfinding, either because we reached our
+    /// goal or because the pathfinder gave up.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::{BlockPos, pathfinder::goals::BlockPosGoal};
+    /// # async fn example(bot: &Client) {
+    /// bot.goto(BlockPosGoal(BlockPos::new(0, 70, 0)));
+    /// bot.wait_until_goto_target_reached().await;
+    /// # }
+    /// ```
+    async fn wait_until_goto_target_reached(&self) {
+        wait_for_pathfinder(&self.ecs, self.entity, self.get_tick_broadcaster(), None).await;
+    }
+
+    /// Same as [`Self::wait_until_goto_target_reached`], but give up after
+    /// the timeout. Returns whether the pathfinder stopped before then.
+    ///
+    /// The pathfinder keeps going after a timeout, so you'll usually want to
+    /// call [`Self::stop_pathfinding`] if this returns false.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use azalea::prelude::*;
+    /// # use azalea::{BlockPos, pathfinder::goals::BlockPosGoal};
+    /// # async fn example(bot: &Client) {
+    /// bot.goto(BlockPosGoal(BlockPos::new(0, 70, 0)));
+    /// if !bot
+    ///     .wait_until_goto_target_reached_with_timeout(Duration::from_secs(30))
+    ///     .await
+    /// {
+    ///     bot.stop_pathfinding();
+    /// }
+    /// # }
+    /// ```
+    async fn wait_until_goto_target_reached_with_timeout(&self, timeout: Duration) -> bool {
+        wait_for_pathfinder(
+            &self.ecs,
+            self.entity,
+            self.get_tick_broadcaster(),
+            Some(timeout),
+        )
+        .await
+    }
+}
+
+/// Wait until the entity's pathfinder has no goal and isn't executing a path,
+/// checking every tick. Returns false if the timeout passed first.
+async fn wait_for_pathfinder(
+    ecs: &parking_lot::Mutex<bevy_ecs::world::World>,
+    entity: Entity,
+    mut tick_broadcaster: tokio::sync::broadcast::Receiver<()>,
+    timeout: Option<Duration>,
+) -> bool {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    // wait a tick so the goto event has time to be processed
+    let _ = tick_broadcaster.recv().await;
+    while tick_broadcaster.recv().await.is_ok() {
+        {
+            let ecs = ecs.lock();
+            let Some(pathfinder) = ecs.get::<Pathfinder>(entity) else {
+                break;
+            };
+            if pathfinder.goal.is_none() && ecs.get::<ExecutingPath>(entity).is_none() {
+                break;
+            }
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return false;
+        }
+    }
+    true
 }
 
 #[derive(Component)]
@@ -239,19 +336,33 @@ pub fn goto_listener(
         &Position,
         &InstanceName,
         &Inventory,
+        &Attributes,
+        &ActiveEffects,
     )>,
     instance_container: Res<InstanceContainer>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
 
     for event in events.read() {
-        let Ok((mut pathfinder, executing_path, position, instance_name, inventory)) =
-            query.get_mut(event.entity)
+        let Ok((
+            mut pathfinder,
+            executing_path,
+            position,
+            instance_name,
+            inventory,
+            attributes,
+            active_effects,
+        )) = query.get_mut(event.entity)
         else {
             warn!("got goto event for an entity that can't pathfind");
             continue;
         };
 
+        // a walk that was never stopped would have a higher priority than the
+        // pathfinder and keep us from following the path
+        movement_arbiter.release(event.entity, MovementPriority::DEFAULT);
+
         if event.goal.success(BlockPos::from(position)) {
             // we're already at the goal, nothing to do
             pathfinder.goal = None;
@@ -290,9 +401,25 @@ pub fn goto_listener(
 
         let successors_fn: moves::SuccessorsFn = event.successors_fn;
 
-        let world_lock = instance_container
-            .get(instance_name)
-            .expect("Entity tried to pathfind but the entity isn't in a valid world");
+        let Some(world_lock) = instance_container.get(instance_name) else {
+            // this can happen while we're switching worlds, the path will be recalculated
+            // once we're in the new one
+            warn!("Tried to pathfind but the entity isn't in a valid world");
+            pathfinder.is_calculating = false;
+            continue;
+        };
+        if world_lock
+            .read()
+            .chunks
+            .get(&ChunkPos::from(&start))
+            .is_none()
+        {
+            // the path will be recalculated once the chunk arrives, and
+            // request_missing_chunks will ask for it if it takes too long
+            debug!("The chunk that we're starting from isn't loaded yet, not pathfinding");
+            pathfinder.is_calculating = false;
+            continue;
+        }
 
         let goal = event.goal.clone();
         let entity = event.entity;
@@ -300,14 +427,11 @@ pub fn goto_listener(
         let goto_id_atomic = pathfinder.goto_id.clone();
 
         let allow_mining = event.allow_mining;
-        let mining_cache = MiningCache::new(if allow_mining {
-            Some(inventory.inventory_menu.clone())
-        } else {
-            None
-        });
+        let mining_cache = mining_cache_for(allow_mining, inventory, attributes, active_effects);
 
         let min_timeout = event.min_timeout;
         let max_timeout = event.max_timeout;
+        let use_cached_chunks = pathfinder.use_cached_chunks;
 
         let task = thread_pool.spawn(async move {
             calculate_path(CalculatePathOpts {
@@ -318,6 +442,7 @@ pub fn goto_listener(
                 world_lock,
                 goto_id_atomic,
                 allow_mining,
+                use_cached_chunks,
                 mining_cache,
                 min_timeout,
                 max_timeout,
@@ -336,6 +461,8 @@ pub struct CalculatePathOpts {
     pub world_lock: Arc<RwLock<azalea_world::Instance>>,
     pub goto_id_atomic: Arc<AtomicUsize>,
     pub allow_mining: bool,
+    /// Also see [`Pathfinder::use_cached_chunks`].
+    pub use_cached_chunks: bool,
     pub mining_cache: MiningCache,
     /// Also see [`GotoEvent::min_timeout`].
     pub min_timeout: PathfinderTimeout,
@@ -356,7 +483,10 @@ pub fn calculate_path(opts: CalculatePathOpts) -> Option<PathFoundEvent> {
     let goto_id = opts.goto_id_atomic.fetch_add(1, atomic::Ordering::SeqCst) + 1;
 
     let origin = opts.start;
-    let cached_world = CachedWorld::new(opts.world_lock, origin);
+    // the search can take a while, so use a snapshot to avoid holding the world's
+    // lock the whole time
+    let snapshot = InstanceSnapshot::new(&opts.world_lock, opts.use_cached_chunks);
+    let cached_world = CachedWorld::from_snapshot(snapshot, origin);
     let successors = |pos: RelBlockPos| {
         call_successors_fn(&cached_world, &opts.mining_cache, opts.successors_fn, pos)
     };
@@ -423,6 +553,7 @@ pub fn calculate_path(opts: CalculatePathOpts) -> Option<PathFoundEvent> {
         is_partial,
         successors_fn: opts.successors_fn,
         allow_mining: opts.allow_mining,
+        use_cached_chunks: opts.use_cached_chunks,
     })
 }
 
@@ -452,32 +583,40 @@ pub fn path_found_listener(
         Option<&mut ExecutingPath>,
         &InstanceName,
         &Inventory,
+        &Attributes,
+        &ActiveEffects,
     )>,
     instance_container: Res<InstanceContainer>,
     mut commands: Commands,
 ) {
     for event in events.read() {
-        let (mut pathfinder, executing_path, instance_name, inventory) = query
-            .get_mut(event.entity)
-            .expect("Path found for an entity that doesn't have a pathfinder");
+        let Ok((
+            mut pathfinder,
+            executing_path,
+            instance_name,
+            inventory,
+            attributes,
+            active_effects,
+        )) = query.get_mut(event.entity)
+        else {
+            warn!("Path found for an entity that doesn't have a pathfinder");
+            continue;
+        };
         if let Some(path) = &event.path {
             if let Some(mut executing_path) = executing_path {
                 let mut new_path = VecDeque::new();
 
                 // combine the old and new paths if the first node of the new path is a
                 // successor of the last node of the old path
-                if let Some(last_node_of_current_path) = executing_path.path.back() {
-                    let world_lock = instance_container
-                        .get(instance_name)
-                        .expect("Entity tried to pathfind but the entity isn't in a valid world");
+                if let Some(last_node_of_current_path) = executing_path.path.back()
+                    && let Some(world_lock) = instance_container.get(instance_name)
+                {
                     let origin = event.start;
                     let successors_fn: moves::SuccessorsFn = event.successors_fn;
-                    let cached_world = CachedWorld::new(world_lock, origin);
-                    let mining_cache = MiningCache::new(if event.allow_mining {
-                        Some(inventory.inventory_menu.clone())
-                    } else {
-                        None
-                    });
+                    let cached_world = CachedWorld::new(world_lock, origin)
+                        .with_cached_chunks(event.use_cached_chunks);
+                    let mining_cache =
+                        mining_cache_for(event.allow_mining, inventory, attributes, active_effects);
                     let successors = |pos: RelBlockPos| {
                         call_successors_fn(&cached_world, &mining_cache, successors_fn, pos)
                     };
@@ -549,11 +688,22 @@ pub fn timeout_movement(
         Option<&Mining>,
         &InstanceName,
         &Inventory,
+        &Attributes,
+        &ActiveEffects,
     )>,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (entity, mut pathfinder, mut executing_path, position, mining, instance_name, inventory) in
-        &mut query
+    for (
+        entity,
+        mut pathfinder,
+        mut executing_path,
+        position,
+        mining,
+        instance_name,
+        inventory,
+        attributes,
+        active_effects,
+    ) in &mut query
     {
         // don't timeout if we're mining
         if let Some(mining) = mining {
@@ -574,19 +724,27 @@ pub fn timeout_movement(
             executing_path.queued_path = None;
             executing_path.last_reached_node = BlockPos::from(position);
 
-            let world_lock = instance_container
-                .get(instance_name)
-                .expect("Entity tried to pathfind but the entity isn't in a valid world");
-            let successors_fn: moves::SuccessorsFn = pathfinder.successors_fn.unwrap();
+            let Some(world_lock) = instance_container.get(instance_name) else {
+                continue;
+            };
+            let Some(successors_fn) = pathfinder.successors_fn else {
+                continue;
+            };
 
             // try to fix the path without recalculating everything.
             // (though, it'll still get fully recalculated by `recalculate_near_end_of_path`
             // if the new path is too short)
+            let mining_cache = mining_cache_for(
+                pathfinder.allow_mining,
+                inventory,
+                attributes,
+                active_effects,
+            );
             patch_path(
                 0..=cmp::min(20, executing_path.path.len() - 1),
                 &mut executing_path,
                 &mut pathfinder,
-                inventory,
+                mining_cache,
                 entity,
                 successors_fn,
                 world_lock,
@@ -605,7 +763,6 @@ pub fn check_node_reached(
         &Position,
         &Physics,
     )>,
-    mut walk_events: EventWriter<StartWalkEvent>,
     mut commands: Commands,
 ) {
     for (entity, mut pathfinder, mut executing_path, position, physics) in &mut query {
@@ -656,10 +813,6 @@ pub fn check_node_reached(
 
                         if executing_path.path.is_empty() {
                             info!("the path we just swapped to was empty, so reached end of path");
-                            walk_events.send(StartWalkEvent {
-                                entity,
-                                direction: WalkDirection::None,
-                            });
                             commands.entity(entity).remove::<ExecutingPath>();
                             break;
                         }
@@ -670,10 +823,6 @@ pub fn check_node_reached(
 
                     if executing_path.path.is_empty() {
                         debug!("pathfinder path is now empty");
-                        walk_events.send(StartWalkEvent {
-                            entity,
-                            direction: WalkDirection::None,
-                        });
                         commands.entity(entity).remove::<ExecutingPath>();
                         if let Some(goal) = pathfinder.goal.clone() {
                             if goal.success(movement.target) {
@@ -699,26 +848,39 @@ pub fn check_for_path_obstruction(
         &mut ExecutingPath,
         &InstanceName,
         &Inventory,
+        &Attributes,
+        &ActiveEffects,
     )>,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (entity, mut pathfinder, mut executing_path, instance_name, inventory) in &mut query {
+    for (
+        entity,
+        mut pathfinder,
+        mut executing_path,
+        instance_name,
+        inventory,
+        attributes,
+        active_effects,
+    ) in &mut query
+    {
         let Some(successors_fn) = pathfinder.successors_fn else {
             continue;
         };
 
-        let world_lock = instance_container
-            .get(instance_name)
-            .expect("Entity tried to pathfind but the entity isn't in a valid world");
+        let Some(world_lock) = instance_container.get(instance_name) else {
+            continue;
+        };
 
         // obstruction check (the path we're executing isn't possible anymore)
         let origin = executing_path.last_reached_node;
-        let cached_world = CachedWorld::new(world_lock, origin);
-        let mining_cache = MiningCache::new(if pathfinder.allow_mining {
-            Some(inventory.inventory_menu.clone())
-        } else {
-            None
-        });
+        let cached_world =
+            CachedWorld::new(world_lock, origin).with_cached_chunks(pathfinder.use_cached_chunks);
+        let mining_cache = mining_cache_for(
+            pathfinder.allow_mining,
+            inventory,
+            attributes,
+            active_effects,
+        );
         let successors =
             |pos: RelBlockPos| call_successors_fn(&cached_world, &mining_cache, successors_fn, pos);
 
@@ -748,18 +910,24 @@ pub fn check_for_path_obstruction(
                 continue;
             };
 
-            let world_lock = instance_container
-                .get(instance_name)
-                .expect("Entity tried to pathfind but the entity isn't in a valid world");
+            let Some(world_lock) = instance_container.get(instance_name) else {
+                continue;
+            };
 
             // patch up to 20 nodes
             let patch_end_index = cmp::min(obstructed_index + 20, executing_path.path.len() - 1);
 
+            let mining_cache = mining_cache_for(
+                pathfinder.allow_mining,
+                inventory,
+                attributes,
+                active_effects,
+            );
             patch_path(
                 obstructed_index..=patch_end_index,
                 &mut executing_path,
                 &mut pathfinder,
-                inventory,
+                mining_cache,
                 entity,
                 successors_fn,
                 world_lock,
@@ -768,6 +936,23 @@ pub fn check_for_path_obstruction(
     }
 }
 
+/// Make a [`MiningCache`] that takes into account the player's items,
+/// attributes, and effects, or one that doesn't allow mining if `allow_mining`
+/// is false.
+fn mining_cache_for(
+    allow_mining: bool,
+    inventory: &Inventory,
+    attributes: &Attributes,
+    active_effects: &ActiveEffects,
+) -> MiningCache {
+    if allow_mining {
+        MiningCache::new(Some(inventory.inventory_menu.clone()))
+            .with_mining_modifiers(attributes.clone(), active_effects.clone())
+    } else {
+        MiningCache::new(None)
+    }
+}
+
 /// update the given [`ExecutingPath`] to recalculate the path of the nodes in
 /// the given index range.
 ///
@@ -777,7 +962,7 @@ fn patch_path(
     patch_nodes: RangeInclusive<usize>,
     executing_path: &mut ExecutingPath,
     pathfinder: &mut Pathfinder,
-    inventory: &Inventory,
+    mining_cache: MiningCache,
     entity: Entity,
     successors_fn: SuccessorsFn,
     world_lock: Arc<RwLock<azalea_world::Instance>>,
@@ -797,11 +982,6 @@ fn patch_path(
     let goto_id_atomic = pathfinder.goto_id.clone();
 
     let allow_mining = pathfinder.allow_mining;
-    let mining_cache = MiningCache::new(if allow_mining {
-        Some(inventory.inventory_menu.clone())
-    } else {
-        None
-    });
 
     // the timeout is small enough that this doesn't need to be async
     let path_found_event = calculate_path(CalculatePathOpts {
@@ -812,6 +992,7 @@ fn patch_path(
         world_lock,
         goto_id_atomic,
         allow_mining,
+        use_cached_chunks: pathfinder.use_cached_chunks,
         mining_cache,
         min_timeout: PathfinderTimeout::Nodes(10_000),
         max_timeout: PathfinderTimeout::Nodes(10_000),
@@ -860,7 +1041,6 @@ fn patch_path(
 
 pub fn recalculate_near_end_of_path(
     mut query: Query<(Entity, &mut Pathfinder, &mut ExecutingPath)>,
-    mut walk_events: EventWriter<StartWalkEvent>,
     mut goto_events: EventWriter<GotoEvent>,
     mut commands: Commands,
 ) {
@@ -901,27 +1081,13 @@ pub fn recalculate_near_end_of_path(
                         executing_path.path = new_path;
                         if executing_path.path.is_empty() {
                             info!("the path we just swapped to was empty, so reached end of path");
-                            walk_events.send(StartWalkEvent {
-                                entity,
-                                direction: WalkDirection::None,
-                            });
                             commands.entity(entity).remove::<ExecutingPath>();
                             break;
                         }
                     } else {
-                        walk_events.send(StartWalkEvent {
-                            entity,
-                            direction: WalkDirection::None,
-                        });
                         commands.entity(entity).remove::<ExecutingPath>();
                     }
                 }
-            } else if executing_path.path.is_empty() {
-                // idk when this can happen but stop moving just in case
-                walk_events.send(StartWalkEvent {
-                    entity,
-                    direction: WalkDirection::None,
-                });
             }
         }
     }
@@ -938,9 +1104,8 @@ pub fn tick_execute_path(
         &InstanceHolder,
         &Inventory,
     )>,
-    mut look_at_events: EventWriter<LookAtEvent>,
-    mut sprint_events: EventWriter<StartSprintEvent>,
-    mut walk_events: EventWriter<StartWalkEvent>,
+    mut look_arbiter: ResMut<LookArbiter>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
     mut jump_events: EventWriter<JumpEvent>,
     mut start_mining_events: EventWriter<StartMiningBlockEvent>,
     mut set_selected_hotbar_slot_events: EventWriter<SetSelectedHotbarSlotEvent>,
@@ -959,9 +1124,8 @@ pub fn tick_execute_path(
                 instance: instance_holder.instance.clone(),
                 menu: inventory_component.inventory_menu.clone(),
 
-                look_at_events: &mut look_at_events,
-                sprint_events: &mut sprint_events,
-                walk_events: &mut walk_events,
+                look_arbiter: &mut look_arbiter,
+                movement_arbiter: &mut movement_arbiter,
                 jump_events: &mut jump_events,
                 start_mining_events: &mut start_mining_events,
                 set_selected_hotbar_slot_events: &mut set_selected_hotbar_slot_events,
@@ -981,20 +1145,27 @@ pub fn recalculate_if_has_goal_but_no_path(
     mut goto_events: EventWriter<GotoEvent>,
 ) {
     for (entity, mut pathfinder) in &mut query {
-        if pathfinder.goal.is_some() && !pathfinder.is_calculating {
-            if let Some(goal) = pathfinder.goal.as_ref().cloned() {
-                debug!("Recalculating path because it has a goal but no ExecutingPath");
-                goto_events.send(GotoEvent {
-                    entity,
-                    goal,
-                    successors_fn: pathfinder.successors_fn.unwrap(),
-                    allow_mining: pathfinder.allow_mining,
-                    min_timeout: pathfinder.min_timeout.expect("min_timeout should be set"),
-                    max_timeout: pathfinder.max_timeout.expect("max_timeout should be set"),
-                });
-                pathfinder.is_calculating = true;
-            }
+        if pathfinder.is_calculating {
+            continue;
         }
+        let (Some(goal), Some(successors_fn), Some(min_timeout), Some(max_timeout)) = (
+            pathfinder.goal.clone(),
+            pathfinder.successors_fn,
+            pathfinder.min_timeout,
+            pathfinder.max_timeout,
+        ) else {
+            continue;
+        };
+        debug!("Recalculating path because it has a goal but no ExecutingPath");
+        goto_events.send(GotoEvent {
+            entity,
+            goal,
+            successors_fn,
+            allow_mining: pathfinder.allow_mining,
+            min_timeout,
+            max_timeout,
+        });
+        pathfinder.is_calculating = true;
     }
 }
 
@@ -1010,7 +1181,7 @@ pub struct StopPathfindingEvent {
 pub fn handle_stop_pathfinding_event(
     mut events: EventReader<StopPathfindingEvent>,
     mut query: Query<(&mut Pathfinder, &mut ExecutingPath)>,
-    mut walk_events: EventWriter<StartWalkEvent>,
+    mut movement_arbiter: ResMut<MovementArbiter>,
     mut commands: Commands,
 ) {
     for event in events.read() {
@@ -1032,10 +1203,8 @@ pub fn handle_stop_pathfinding_event(
         }
 
         if executing_path.path.is_empty() {
-            walk_events.send(StartWalkEvent {
-                entity: event.entity,
-                direction: WalkDirection::None,
-            });
+            // so we don't keep moving for another tick
+            movement_arbiter.withdraw(event.entity, MovementPriority::PATHFINDER);
             commands.entity(event.entity).remove::<ExecutingPath>();
         }
     }
@@ -1113,6 +1282,10 @@ mod tests {
         time::{Duration, Instant},
     };
 
+    use azalea_client::{
+        movement_arbiter::{MovementArbiter, MovementPriority, MovementRequest},
+        WalkDirection,
+    };
     use azalea_core::position::{BlockPos, ChunkPos, Vec3};
     use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
 
@@ -1207,6 +1380,39 @@ mod tests {
         assert_simulation_reaches(&mut simulation, 20, BlockPos::new(0, 71, 1));
     }
 
+    #[test]
+    fn test_goto_overrides_walk() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut simulation = setup_simulation_world(
+            &mut partial_chunks,
+            BlockPos::new(0, 71, 0),
+            vec![
+                BlockPos::new(0, 70, 0),
+                BlockPos::new(0, 70, 1),
+                BlockPos::new(0, 70, 2),
+            ],
+        );
+        // like calling bot.walk and never stopping
+        simulation
+            .app
+            .world_mut()
+            .resource_mut::<MovementArbiter>()
+            .hold(
+                simulation.entity,
+                MovementPriority::DEFAULT,
+                MovementRequest::walk(WalkDirection::Left),
+            );
+        simulation.app.world_mut().send_event(GotoEvent {
+            entity: simulation.entity,
+            goal: Arc::new(BlockPosGoal(BlockPos::new(0, 71, 2))),
+            successors_fn: moves::default_move,
+            allow_mining: false,
+            min_timeout: PathfinderTimeout::Nodes(1_000_000),
+            max_timeout: PathfinderTimeout::Nodes(5_000_000),
+        });
+        assert_simulation_reaches(&mut simulation, 30, BlockPos::new(0, 71, 2));
+    }
+
     #[test]
     fn test_double_diagonal_with_walls() {
         let mut partial_chunks = PartialChunkStorage::default();
@@ -1368,4 +1574,153 @@ mod tests {
         );
         assert_simulation_reaches(&mut simulation, 80, BlockPos::new(4, 74, 9));
     }
+
+    /// A world with an entity whose pathfinder has a goal, and a tick
+    /// broadcaster that has already been sent some ticks.
+    fn pathfinding_world(
+        ticks: usize,
+    ) -> (
+        parking_lot::Mutex<bevy_ecs::world::World>,
+        bevy_ecs::entity::Entity,
+        tokio::sync::broadcast::Receiver<()>,
+    ) {
+        let mut ecs = bevy_ecs::world::World::new();
+        let entity = ecs
+            .spawn(super::Pathfinder {
+                goal: Some(Arc::new(BlockPosGoal(BlockPos::new(0, 70, 0)))),
+                ..Default::default()
+            })
+            .id();
+        let (tick_sender, tick_receiver) = tokio::sync::broadcast::channel(ticks);
+        for _ in 0..ticks {
+            tick_sender.send(()).unwrap();
+        }
+        (parking_lot::Mutex::new(ecs), entity, tick_receiver)
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_pathfinder_times_out() {
+        let (ecs, entity, ticks) = pathfinding_world(3);
+        assert!(!super::wait_for_pathfinder(&ecs, entity, ticks, Some(Duration::ZERO)).await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_pathfinder_without_goal() {
+        let (ecs, entity, ticks) = pathfinding_world(3);
+        ecs.lock()
+            .get_mut::<super::Pathfinder>(entity)
+            .unwrap()
+            .goal = None;
+        assert!(super::wait_for_pathfinder(&ecs, entity, ticks, Some(Duration::ZERO)).await);
+    }
+
+    /// A world where the floor goes from z=0 to z=19, and the chunk with the
+    /// end of it was unloaded but is still in the chunk cache.
+    ///
+    /// The partial chunk storage has to be kept around for the first chunk to
+    /// stay loaded.
+    fn world_with_cached_chunk() -> (
+        Arc<parking_lot::RwLock<azalea_world::Instance>>,
+        PartialChunkStorage,
+    ) {
+        let mut instance = azalea_world::Instance::from(ChunkStorage::default());
+        instance.chunks.cache.set_max_memory(usize::MAX);
+        let mut partial_chunks = PartialChunkStorage::default();
+        for z in 0..2 {
+            partial_chunks.set(
+                &ChunkPos::new(0, z),
+                Some(Chunk::default()),
+                &mut instance.chunks,
+            );
+        }
+        for z in 0..20 {
+            instance.chunks.set_block_state(
+                &BlockPos::new(0, 70, z),
+                azalea_registry::Block::Stone.into(),
+            );
+        }
+        let chunk = partial_chunks
+            .limited_get_mut(&ChunkPos::new(0, 1))
+            .and_then(Option::take)
+            .unwrap();
+        instance.chunks.unload(ChunkPos::new(0, 1), chunk);
+        assert!(instance.chunks.get(&ChunkPos::new(0, 1)).is_none());
+        assert!(instance.chunks.get_cached(&ChunkPos::new(0, 1)).is_some());
+        (Arc::new(parking_lot::RwLock::new(instance)), partial_chunks)
+    }
+
+    fn calculate_path_to_cached_chunk(
+        world_lock: Arc<parking_lot::RwLock<azalea_world::Instance>>,
+        use_cached_chunks: bool,
+    ) -> super::PathFoundEvent {
+        super::calculate_path(super::CalculatePathOpts {
+            entity: bevy_ecs::entity::Entity::PLACEHOLDER,
+            start: BlockPos::new(0, 71, 0),
+            goal: Arc::new(BlockPosGoal(BlockPos::new(0, 71, 19))),
+            successors_fn: moves::default_move,
+            world_lock,
+            goto_id_atomic: Default::default(),
+            allow_mining: false,
+            use_cached_chunks,
+            mining_cache: super::mining::MiningCache::new(None),
+            min_timeout: PathfinderTimeout::Nodes(10_000),
+            max_timeout: PathfinderTimeout::Nodes(50_000),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_path_across_cached_chunk() {
+        let (world_lock, _partial_chunks) = world_with_cached_chunk();
+
+        let path_found = calculate_path_to_cached_chunk(world_lock.clone(), false);
+        assert!(path_found.is_partial);
+
+        let path_found = calculate_path_to_cached_chunk(world_lock.clone(), true);
+        assert!(!path_found.is_partial);
+        assert!(path_found.use_cached_chunks);
+        let path = path_found.path.unwrap();
+        assert_eq!(path.back().unwrap().target, BlockPos::new(0, 71, 19));
+
+        // following the path doesn't make it look obstructed, as long as the cached
+        // chunks are still allowed
+        for use_cached_chunks in [true, false] {
+            let mut ecs = bevy_ecs::world::World::new();
+            let mut instance_container = azalea_world::InstanceContainer::new();
+            let instance_name = azalea_world::InstanceName(
+                azalea_core::resource_location::ResourceLocation::new("minecraft:overworld"),
+            );
+            instance_container
+                .instances
+                .insert(instance_name.0.clone(), Arc::downgrade(&world_lock));
+            ecs.insert_resource(instance_container);
+            let entity = ecs
+                .spawn((
+                    super::Pathfinder {
+                        successors_fn: Some(moves::default_move),
+                        use_cached_chunks,
+                        ..Default::default()
+                    },
+                    super::ExecutingPath {
+                        path: path.clone(),
+                        queued_path: None,
+                        last_reached_node: BlockPos::new(0, 71, 0),
+                        last_node_reached_at: Instant::now(),
+                        is_path_partial: false,
+                    },
+                    instance_name,
+                    azalea_client::inventory::Inventory::default(),
+                    azalea_entity::Attributes::default(),
+                    azalea_entity::effects::ActiveEffects::default(),
+                ))
+                .id();
+            bevy_ecs::schedule::Schedule::default()
+                .add_systems(super::check_for_path_obstruction)
+                .run(&mut ecs);
+
+            let executing_path = ecs.get::<super::ExecutingPath>(entity).unwrap();
+            assert_eq!(executing_path.path.len() == path.len(), use_cached_chunks);
+            assert_eq!(executing_path.is_path_partial, !use_cached_chunks);
+        }
+    }
 }
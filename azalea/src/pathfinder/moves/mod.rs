@@ -4,8 +4,10 @@ pub mod parkour;
 use std::{fmt::Debug, sync::Arc};
 
 use azalea_client::{
-    inventory::SetSelectedHotbarSlotEvent, mining::StartMiningBlockEvent, SprintDirection,
-    StartSprintEvent, StartWalkEvent, WalkDirection,
+    inventory::SetSelectedHotbarSlotEvent,
+    mining::StartMiningBlockEvent,
+    movement_arbiter::{MovementArbiter, MovementPriority, MovementRequest},
+    SprintDirection, WalkDirection,
 };
 use azalea_core::position::{BlockPos, Vec3};
 use azalea_inventory::Menu;
@@ -19,7 +21,11 @@ use super::{
     rel_block_pos::RelBlockPos,
     world::{is_block_state_passable, CachedWorld},
 };
-use crate::{auto_tool::best_tool_in_hotbar_for_block, JumpEvent, LookAtEvent};
+use crate::{
+    auto_tool::best_tool_in_hotbar_for_block,
+    look_arbiter::{LookArbiter, LookPriority, LookTarget},
+    JumpEvent,
+};
 
 type Edge = astar::Edge<RelBlockPos, MoveData>;
 
@@ -46,7 +52,7 @@ impl Debug for MoveData {
     }
 }
 
-pub struct ExecuteCtx<'w1, 'w2, 'w3, 'w4, 'w5, 'w6, 'a> {
+pub struct ExecuteCtx<'w1, 'w2, 'w3, 'a> {
     pub entity: Entity,
     /// The node that we're trying to reach.
     pub target: BlockPos,
@@ -58,46 +64,49 @@ pub struct ExecuteCtx<'w1, 'w2, 'w3, 'w4, 'w5, 'w6, 'a> {
     pub instance: Arc<RwLock<Instance>>,
     pub menu: Menu,
 
-    pub look_at_events: &'a mut EventWriter<'w1, LookAtEvent>,
-    pub sprint_events: &'a mut EventWriter<'w2, StartSprintEvent>,
-    pub walk_events: &'a mut EventWriter<'w3, StartWalkEvent>,
-    pub jump_events: &'a mut EventWriter<'w4, JumpEvent>,
-    pub start_mining_events: &'a mut EventWriter<'w5, StartMiningBlockEvent>,
-    pub set_selected_hotbar_slot_events: &'a mut EventWriter<'w6, SetSelectedHotbarSlotEvent>,
+    /// Looking is done with [`LookPriority::MOVEMENT`], so anything with a
+    /// higher priority can look somewhere else while we're moving.
+    pub look_arbiter: &'a mut LookArbiter,
+    /// Movement is submitted with [`MovementPriority::PATHFINDER`], so it has
+    /// to be done again every tick.
+    pub movement_arbiter: &'a mut MovementArbiter,
+    pub jump_events: &'a mut EventWriter<'w1, JumpEvent>,
+    pub start_mining_events: &'a mut EventWriter<'w2, StartMiningBlockEvent>,
+    pub set_selected_hotbar_slot_events: &'a mut EventWriter<'w3, SetSelectedHotbarSlotEvent>,
 }
 
-impl ExecuteCtx<'_, '_, '_, '_, '_, '_, '_> {
+impl ExecuteCtx<'_, '_, '_, '_> {
     pub fn look_at(&mut self, position: Vec3) {
-        self.look_at_events.send(LookAtEvent {
-            entity: self.entity,
-            position: Vec3 {
-                x: position.x,
-                // look forward
-                y: self.position.up(1.53).y,
-                z: position.z,
-            },
+        self.look_at_exact(Vec3 {
+            x: position.x,
+            // look forward
+            y: self.position.up(1.53).y,
+            z: position.z,
         });
     }
 
     pub fn look_at_exact(&mut self, position: Vec3) {
-        self.look_at_events.send(LookAtEvent {
-            entity: self.entity,
-            position,
-        });
+        self.look_arbiter.submit(
+            self.entity,
+            LookPriority::MOVEMENT,
+            LookTarget::Position(position),
+        );
     }
 
     pub fn sprint(&mut self, direction: SprintDirection) {
-        self.sprint_events.send(StartSprintEvent {
-            entity: self.entity,
-            direction,
-        });
+        self.movement_arbiter.submit(
+            self.entity,
+            MovementPriority::PATHFINDER,
+            MovementRequest::sprint(direction),
+        );
     }
 
     pub fn walk(&mut self, direction: WalkDirection) {
-        self.walk_events.send(StartWalkEvent {
-            entity: self.entity,
-            direction,
-        });
+        self.movement_arbiter.submit(
+            self.entity,
+            MovementPriority::PATHFINDER,
+            MovementRequest::walk(direction),
+        );
     }
 
     pub fn jump(&mut self) {
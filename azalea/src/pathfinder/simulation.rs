@@ -3,12 +3,20 @@
 use std::sync::Arc;
 
 use azalea_client::{inventory::Inventory, packet_handling::game::SendPacketEvent, PhysicsState};
-use azalea_core::{position::Vec3, resource_location::ResourceLocation, tick::GameTick};
+use azalea_core::{
+    position::{BlockPos, Vec3},
+    resource_location::ResourceLocation,
+    tick::GameTick,
+};
 use azalea_entity::{
-    attributes::AttributeInstance, Attributes, EntityDimensions, LookDirection, Physics, Position,
+    attributes::AttributeInstance, effects::ActiveEffects, Attributes, EntityDimensions,
+    LookDirection, Physics, Position,
 };
 use azalea_registry::EntityKind;
-use azalea_world::{ChunkStorage, Instance, InstanceContainer, MinecraftEntityId, PartialInstance};
+use azalea_world::{
+    snapshot::InstanceSnapshot, Chunk, ChunkStorage, Instance, InstanceContainer,
+    MinecraftEntityId, PartialInstance,
+};
 use bevy_app::App;
 use bevy_ecs::prelude::*;
 use parking_lot::RwLock;
@@ -21,6 +29,7 @@ pub struct SimulatedPlayerBundle {
     pub physics_state: PhysicsState,
     pub look_direction: LookDirection,
     pub attributes: Attributes,
+    pub active_effects: ActiveEffects,
     pub inventory: Inventory,
 }
 
@@ -37,7 +46,10 @@ impl SimulatedPlayerBundle {
                 speed: AttributeInstance::new(0.1),
                 attack_speed: AttributeInstance::new(4.0),
                 water_movement_efficiency: AttributeInstance::new(0.0),
+                block_break_speed: AttributeInstance::new(1.0),
+                submerged_mining_speed: AttributeInstance::new(0.2),
             },
+            active_effects: ActiveEffects::default(),
             inventory: Inventory::default(),
         }
     }
@@ -124,6 +136,9 @@ pub struct Simulation {
     pub app: App,
     pub entity: Entity,
     _instance: Arc<RwLock<Instance>>,
+    /// The chunks from [`Self::from_snapshot`], which have to be kept alive
+    /// since the instance only has weak references to them.
+    _chunks: Vec<Arc<RwLock<Chunk>>>,
 }
 
 impl Simulation {
@@ -134,9 +149,24 @@ impl Simulation {
             app,
             entity,
             _instance: instance,
+            _chunks: Vec::new(),
         }
     }
 
+    /// Create a simulation that uses the chunks in the snapshot, so the real
+    /// world doesn't have to be locked and changes that happen in the
+    /// simulation (like mining blocks) don't affect it.
+    ///
+    /// The chunks are moved out of the snapshot, so clone it first if you want
+    /// to use it for more than one simulation. [`Self::new`] shares chunks
+    /// with the [`ChunkStorage`] instead.
+    pub fn from_snapshot(snapshot: InstanceSnapshot, player: SimulatedPlayerBundle) -> Self {
+        let (chunks, chunk_holders) = snapshot.into_chunk_storage();
+        let mut simulation = Self::new(chunks, player);
+        simulation._chunks = chunk_holders;
+        simulation
+    }
+
     pub fn tick(&mut self) {
         self.app.update();
         self.app.world_mut().run_schedule(GameTick);
@@ -158,15 +188,61 @@ impl Simulation {
     }
 }
 
+/// Simulate a [`BotClientExt::sprint_jump_towards`] from the `start` position
+/// and return whether we'd land on the `target` block.
+///
+/// The chunks are shared with the given [`ChunkStorage`], so this is cheap to
+/// call with an [`Instance`]'s chunks.
+///
+/// [`BotClientExt::sprint_jump_towards`]: crate::BotClientExt::sprint_jump_towards
+pub fn can_sprint_jump_across(chunks: ChunkStorage, start: Vec3, target: BlockPos) -> bool {
+    let mut simulation = Simulation::new(chunks, SimulatedPlayerBundle::new(start));
+    simulation
+        .app
+        .world_mut()
+        .entity_mut(simulation.entity)
+        .insert(crate::bot::JumpingTowards::to_position(
+            target.center(),
+            true,
+        ));
+
+    // a jump shouldn't take longer than this
+    for _ in 0..40 {
+        simulation.tick();
+        if simulation
+            .get_component::<crate::bot::JumpingTowards>()
+            .is_none()
+        {
+            break;
+        }
+    }
+
+    let position = simulation.position();
+    BlockPos::from(position) == target && (position.y - target.y as f64).abs() < 0.094
+}
+
 /// A set of simulations, useful for efficiently doing multiple simulations.
 pub struct SimulationSet {
     pub app: App,
     instance: Arc<RwLock<Instance>>,
+    _chunks: Vec<Arc<RwLock<Chunk>>>,
 }
 impl SimulationSet {
     pub fn new(chunks: ChunkStorage) -> Self {
         let (app, instance) = create_simulation_instance(chunks);
-        Self { app, instance }
+        Self {
+            app,
+            instance,
+            _chunks: Vec::new(),
+        }
+    }
+    /// Create a set of simulations that use the chunks in the snapshot. See
+    /// [`Simulation::from_snapshot`].
+    pub fn from_snapshot(snapshot: InstanceSnapshot) -> Self {
+        let (chunks, chunk_holders) = snapshot.into_chunk_storage();
+        let mut simulation_set = Self::new(chunks);
+        simulation_set._chunks = chunk_holders;
+        simulation_set
     }
     pub fn tick(&mut self) {
         self.app.update();
@@ -9,7 +9,7 @@ use azalea_core::{
     position::{BlockPos, ChunkPos, ChunkSectionBlockPos, ChunkSectionPos},
 };
 use azalea_physics::collision::BlockWithShape;
-use azalea_world::Instance;
+use azalea_world::{snapshot::InstanceSnapshot, Instance};
 use parking_lot::RwLock;
 
 use super::{mining::MiningCache, rel_block_pos::RelBlockPos};
@@ -22,7 +22,12 @@ pub struct CachedWorld {
     origin: BlockPos,
 
     min_y: i32,
-    world_lock: Arc<RwLock<Instance>>,
+    world: WorldSource,
+    /// Whether chunks in the world's [`ChunkCache`] can be used when the chunk
+    /// isn't loaded.
+    ///
+    /// [`ChunkCache`]: azalea_world::chunk_cache::ChunkCache
+    use_cached_chunks: bool,
 
     // we store `PalettedContainer`s instead of `Chunk`s or `Section`s because it doesn't contain
     // any unnecessary data like heightmaps or biomes.
@@ -79,6 +84,14 @@ impl CachedSections {
     }
 }
 
+/// Where a [`CachedWorld`] gets its chunks from.
+enum WorldSource {
+    /// The live world, which is locked whenever we need a chunk that we don't
+    /// have yet.
+    Instance(Arc<RwLock<Instance>>),
+    Snapshot(InstanceSnapshot),
+}
+
 pub struct CachedSection {
     pub pos: ChunkSectionPos,
     pub passable_bitset: FixedBitSet<{ 4096_usize.div_ceil(8) }>,
@@ -88,10 +101,26 @@ pub struct CachedSection {
 impl CachedWorld {
     pub fn new(world_lock: Arc<RwLock<Instance>>, origin: BlockPos) -> Self {
         let min_y = world_lock.read().chunks.min_y;
+        Self::with_source(WorldSource::Instance(world_lock), min_y, origin)
+    }
+
+    /// Create a [`CachedWorld`] that uses the chunks from a snapshot instead of
+    /// the live world, so the world never has to be locked. Blocks that change
+    /// after the snapshot was made won't be noticed.
+    ///
+    /// [`Self::with_cached_chunks`] does nothing here, since the snapshot
+    /// decides whether it has cached chunks (see [`InstanceSnapshot::new`]).
+    pub fn from_snapshot(snapshot: InstanceSnapshot, origin: BlockPos) -> Self {
+        let min_y = snapshot.min_y;
+        Self::with_source(WorldSource::Snapshot(snapshot), min_y, origin)
+    }
+
+    fn with_source(world: WorldSource, min_y: i32, origin: BlockPos) -> Self {
         Self {
             origin,
             min_y,
-            world_lock,
+            world,
+            use_cached_chunks: false,
             cached_chunks: Default::default(),
             last_chunk_cache_index: Default::default(),
             cached_blocks: Default::default(),
@@ -103,6 +132,15 @@ impl CachedWorld {
         }
     }
 
+    /// Also use chunks that were unloaded recently (but are still in the
+    /// world's chunk cache) when the chunk isn't loaded. These might be out of
+    /// date, so the path could go through blocks that changed since then.
+    #[must_use]
+    pub fn with_cached_chunks(mut self, use_cached_chunks: bool) -> Self {
+        self.use_cached_chunks = use_cached_chunks;
+        self
+    }
+
     // ```
     // fn get_block_state(&self, pos: BlockPos) -> Option<BlockState> {
     //     self.with_section(ChunkSectionPos::from(pos), |section| {
@@ -165,15 +203,36 @@ impl CachedWorld {
             return Some(f(section));
         }
 
-        let world = self.world_lock.read();
-        let chunk = world.chunks.get(&chunk_pos)?;
-        let chunk = chunk.read();
-
-        let sections: Vec<azalea_world::palette::PalettedContainer> = chunk
-            .sections
-            .iter()
-            .map(|section| section.states.clone())
-            .collect();
+        let sections: Vec<azalea_world::palette::PalettedContainer> = match &self.world {
+            WorldSource::Instance(world_lock) => {
+                let world = world_lock.read();
+                if let Some(chunk) = world.chunks.get(&chunk_pos) {
+                    let chunk = chunk.read();
+                    chunk
+                        .sections
+                        .iter()
+                        .map(|section| section.states.clone())
+                        .collect()
+                } else if self.use_cached_chunks {
+                    let chunk = world.chunks.get_cached(&chunk_pos)?;
+                    chunk
+                        .sections
+                        .iter()
+                        .map(|section| section.states.clone())
+                        .collect()
+                } else {
+                    return None;
+                }
+            }
+            WorldSource::Snapshot(snapshot) => {
+                let chunk = snapshot.get(&chunk_pos)?;
+                chunk
+                    .sections
+                    .iter()
+                    .map(|section| section.states.clone())
+                    .collect()
+            }
+        };
 
         if section_index >= sections.len() {
             // y position is out of bounds
@@ -193,8 +252,9 @@ impl CachedWorld {
         self.with_section(section_pos, |section| {
             let mut passable_bitset = FixedBitSet::<{ 4096_usize.div_ceil(8) }>::new();
             let mut solid_bitset = FixedBitSet::<{ 4096_usize.div_ceil(8) }>::new();
-            for i in 0..4096 {
-                let block_state_id = section.get_at_index(i);
+            let mut block_state_ids = [0; 4096];
+            section.get_all(&mut block_state_ids);
+            for (i, block_state_id) in block_state_ids.into_iter().enumerate() {
                 let block_state = BlockState::try_from(block_state_id).unwrap_or(BlockState::AIR);
                 if is_block_state_passable(block_state) {
                     passable_bitset.set(i);
@@ -527,6 +587,82 @@ pub fn is_block_state_solid(block: BlockState) -> bool {
     block.is_collision_shape_full()
 }
 
+/// Find places to stand in an [`Instance`], using the same rules as the
+/// pathfinder.
+pub trait FindStandableExt {
+    /// Find the closest position within `radius` blocks of `pos` that a player
+    /// could stand at, or `None` if there isn't one.
+    ///
+    /// A position is standable if the block below it is solid and the block
+    /// at it and the one above are passable (so not water, lava, or fire).
+    /// Blocks in unloaded chunks are never standable. This is useful for
+    /// picking a safe place to teleport to or walk towards.
+    ///
+    /// ```
+    /// # use azalea::{pathfinder::world::FindStandableExt, world::Instance, BlockPos};
+    /// # fn example(instance: &Instance, spawn: BlockPos) {
+    /// if let Some(pos) = instance.find_standable_near(spawn, 8) {
+    ///     println!("we can stand at {pos}");
+    /// }
+    /// # }
+    /// ```
+    fn find_standable_near(&self, pos: BlockPos, radius: u32) -> Option<BlockPos>;
+}
+
+impl FindStandableExt for Instance {
+    fn find_standable_near(&self, pos: BlockPos, radius: u32) -> Option<BlockPos> {
+        let radius = radius as i32;
+        let radius_squared = radius * radius;
+
+        let mut nearest: Option<(BlockPos, i32)> = None;
+        // check the cubes around the position from the inside out, since everything
+        // in a cube is at least `ring` blocks away and we can stop once that's
+        // further than the nearest position we found
+        for ring in 0..=radius {
+            if nearest.is_some_and(|(_, distance)| ring * ring > distance) {
+                break;
+            }
+            for x in -ring..=ring {
+                for y in -ring..=ring {
+                    // only the faces of the cube are new, so skip the inside
+                    let z_step = if x.abs() == ring || y.abs() == ring {
+                        1
+                    } else {
+                        2 * ring as usize
+                    };
+                    for z in (-ring..=ring).step_by(z_step) {
+                        let offset = BlockPos::new(x, y, z);
+                        let distance = offset.length_squared();
+                        if distance > radius_squared
+                            || nearest.is_some_and(|(_, nearest)| distance >= nearest)
+                        {
+                            continue;
+                        }
+                        let candidate = pos + offset;
+                        if is_block_pos_standable(self, candidate) {
+                            nearest = Some((candidate, distance));
+                        }
+                    }
+                }
+            }
+        }
+        nearest.map(|(pos, _)| pos)
+    }
+}
+
+fn is_block_pos_standable(instance: &Instance, pos: BlockPos) -> bool {
+    let is_passable = |pos| {
+        instance
+            .get_block_state(&pos)
+            .is_some_and(is_block_state_passable)
+    };
+    instance
+        .get_block_state(&pos.down(1))
+        .is_some_and(is_block_state_solid)
+        && is_passable(pos)
+        && is_passable(pos.up(1))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -556,6 +692,36 @@ mod tests {
         assert!(ctx.is_block_pos_passable(BlockPos::new(0, 1, 0),));
     }
 
+    #[test]
+    fn test_from_snapshot() {
+        let mut partial_world = PartialInstance::default();
+        let mut world = ChunkStorage::default();
+        partial_world
+            .chunks
+            .set(&ChunkPos { x: 0, z: 0 }, Some(Chunk::default()), &mut world);
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(0, 0, 0),
+            azalea_registry::Block::Stone.into(),
+            &world,
+        );
+        let instance = Instance::from(world);
+
+        let mut snapshot = instance.snapshot();
+        snapshot.set_block_state(
+            &BlockPos::new(0, 1, 0),
+            azalea_registry::Block::Stone.into(),
+        );
+
+        let ctx = CachedWorld::from_snapshot(snapshot, BlockPos::default());
+        assert!(ctx.is_block_pos_solid(BlockPos::new(0, 0, 0)));
+        assert!(ctx.is_block_pos_solid(BlockPos::new(0, 1, 0)));
+        // the block that was changed in the snapshot is still air in the world
+        assert_eq!(
+            instance.get_block_state(&BlockPos::new(0, 1, 0)),
+            Some(BlockState::AIR)
+        );
+    }
+
     #[test]
     fn test_is_solid() {
         let mut partial_world = PartialInstance::default();
@@ -604,4 +770,44 @@ mod tests {
         assert!(!ctx.is_standable_at_block_pos(BlockPos::new(0, 0, 0)));
         assert!(!ctx.is_standable_at_block_pos(BlockPos::new(0, 2, 0)));
     }
+
+    #[test]
+    fn test_find_standable_near() {
+        let mut partial_world = PartialInstance::default();
+        let mut world = ChunkStorage::default();
+        partial_world
+            .chunks
+            .set(&ChunkPos { x: 0, z: 0 }, Some(Chunk::default()), &mut world);
+        let blocks = [
+            // standing in lava
+            (BlockPos::new(9, 0, 8), azalea_registry::Block::Stone),
+            (BlockPos::new(9, 1, 8), azalea_registry::Block::Lava),
+            // standing in fire
+            (BlockPos::new(8, 0, 10), azalea_registry::Block::Stone),
+            (BlockPos::new(8, 1, 10), azalea_registry::Block::Fire),
+            // no room for our head
+            (BlockPos::new(6, 0, 8), azalea_registry::Block::Stone),
+            (BlockPos::new(6, 2, 8), azalea_registry::Block::OakFence),
+            (BlockPos::new(11, 0, 8), azalea_registry::Block::Stone),
+        ];
+        for (pos, block) in blocks {
+            partial_world
+                .chunks
+                .set_block_state(&pos, block.into(), &world);
+        }
+        let instance = Instance::from(world);
+
+        assert_eq!(
+            instance.find_standable_near(BlockPos::new(8, 1, 8), 8),
+            Some(BlockPos::new(11, 1, 8))
+        );
+        assert_eq!(
+            instance.find_standable_near(BlockPos::new(8, 1, 8), 2),
+            None
+        );
+        assert_eq!(
+            instance.find_standable_near(BlockPos::new(11, 1, 8), 0),
+            Some(BlockPos::new(11, 1, 8))
+        );
+    }
 }
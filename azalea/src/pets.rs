@@ -0,0 +1,120 @@
+//! Keep track of the tamed animals (like wolves, cats, and parrots) that
+//! belong to our bots.
+
+use azalea_client::{
+    interact::EntityInteractEvent, packet_handling::game::PacketEvent, Client, GameProfileComponent,
+};
+use azalea_entity::{
+    indexing::EntityIdIndex,
+    metadata::{InSittingPose, Owneruuid, Tame},
+    Dead, LocalEntity,
+};
+use azalea_protocol::packets::game::{s_interact::InteractionHand, ClientboundGamePacket};
+use azalea_world::MinecraftEntityId;
+use bevy_app::Update;
+use bevy_ecs::prelude::*;
+
+use crate::app::{App, Plugin};
+
+/// A plugin that sends [`PetHurtEvent`]s when our pets take damage.
+#[derive(Clone, Default)]
+pub struct PetsPlugin;
+impl Plugin for PetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PetHurtEvent>()
+            .add_systems(Update, send_pet_hurt_events);
+    }
+}
+
+/// An event sent when one of a bot's tamed animals takes damage.
+#[derive(Event, Debug, Clone)]
+pub struct PetHurtEvent {
+    /// The local player that owns the pet.
+    pub entity: Entity,
+    /// The pet that was hurt.
+    pub pet: Entity,
+    /// The entity that was responsible for the damage, if any.
+    pub attacker: Option<Entity>,
+}
+
+pub fn send_pet_hurt_events(
+    mut events: EventReader<PacketEvent>,
+    local_players: Query<(&GameProfileComponent, &EntityIdIndex), With<LocalEntity>>,
+    pets: Query<(&Tame, &Owneruuid)>,
+    mut pet_hurt_events: EventWriter<PetHurtEvent>,
+) {
+    for event in events.read() {
+        let ClientboundGamePacket::DamageEvent(p) = event.packet.as_ref() else {
+            continue;
+        };
+        let Ok((game_profile, entity_id_index)) = local_players.get(event.entity) else {
+            continue;
+        };
+        let Some(pet) = entity_id_index.get(MinecraftEntityId(p.entity_id)) else {
+            continue;
+        };
+        let Ok((tame, owner_uuid)) = pets.get(pet) else {
+            continue;
+        };
+        if !**tame || **owner_uuid != Some(game_profile.uuid) {
+            continue;
+        }
+
+        let attacker = p
+            .source_cause_id
+            .0
+            .and_then(|id| entity_id_index.get(MinecraftEntityId(id)));
+        pet_hurt_events.send(PetHurtEvent {
+            entity: event.entity,
+            pet,
+            attacker,
+        });
+    }
+}
+
+pub trait PetClientExt {
+    fn pets(&self) -> Vec<Entity>;
+    fn is_pet_sitting(&self, pet: Entity) -> bool;
+    fn set_pet_sitting(&mut self, pet: Entity, sitting: bool);
+}
+
+impl PetClientExt for Client {
+    /// Get all of the tamed animals that belong to us and that we can see.
+    fn pets(&self) -> Vec<Entity> {
+        let mut ecs = self.ecs.lock();
+        let uuid = self.query::<&GameProfileComponent>(&mut ecs).uuid;
+
+        let mut query = ecs.query_filtered::<(Entity, &Tame, &Owneruuid), Without<Dead>>();
+        query
+            .iter(&ecs)
+            .filter(|(_, tame, owner_uuid)| ***tame && ***owner_uuid == Some(uuid))
+            .map(|(entity, _, _)| entity)
+            .collect()
+    }
+
+    /// Whether the given pet is currently sitting. This returns false if the
+    /// entity isn't a tameable animal.
+    fn is_pet_sitting(&self, pet: Entity) -> bool {
+        self.ecs
+            .lock()
+            .get::<InSittingPose>(pet)
+            .is_some_and(|sitting| **sitting)
+    }
+
+    /// Make our pet sit or stand up by right clicking it.
+    ///
+    /// Make sure that you're not holding anything that the pet could eat (like
+    /// meat for a wolf), since that'll feed it instead. This does nothing if
+    /// the pet is already in the right pose.
+    fn set_pet_sitting(&mut self, pet: Entity, sitting: bool) {
+        if self.is_pet_sitting(pet) == sitting {
+            return;
+        }
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: pet,
+            hand: InteractionHand::MainHand,
+            location: None,
+        });
+    }
+}
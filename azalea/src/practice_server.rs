@@ -0,0 +1,441 @@
+//! A tiny server that runs in the same process as your bots, so you can try
+//! things out without downloading and running a vanilla server.
+//!
+//! The world is a flat grass plain that bots can walk around on, and chat
+//! messages are sent back to every connected player. Nothing else is
+//! simulated, so blocks can't be broken and there are no other entities.
+//!
+//! The server only speaks the subset of the protocol that Azalea needs and
+//! doesn't do any authentication, so it's not meant for vanilla clients.
+//!
+//! This module is only available with the `practice-server` feature.
+//!
+//! ```no_run
+//! # use azalea::{prelude::*, practice_server::PracticeServer};
+//! # #[tokio::main]
+//! # async fn main() {
+//! let server = PracticeServer::start().await.unwrap();
+//!
+//! ClientBuilder::new()
+//!     .start(Account::offline("bot"), server.address())
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use azalea_auth::game_profile::GameProfile;
+use azalea_buf::AzaleaWrite;
+use azalea_chat::FormattedText;
+use azalea_core::{
+    bitset::BitSet,
+    game_type::{GameMode, OptionalGameType},
+    position::{ChunkBlockPos, Vec3},
+    resource_location::ResourceLocation,
+};
+use azalea_entity::LookDirection;
+use azalea_protocol::{
+    common::movements::{PositionMoveRotation, RelativeMovements},
+    connect::Connection,
+    packets::{
+        common::CommonPlayerSpawnInfo,
+        config::{
+            ClientboundFinishConfiguration, ClientboundRegistryData, ServerboundConfigPacket,
+        },
+        game::{
+            c_game_event::EventType, c_level_chunk_with_light::ClientboundLevelChunkPacketData,
+            c_light_update::ClientboundLightUpdatePacketData, ClientboundGameEvent,
+            ClientboundGamePacket, ClientboundKeepAlive, ClientboundLevelChunkWithLight,
+            ClientboundLogin, ClientboundPlayerPosition, ClientboundSetChunkCacheCenter,
+            ClientboundSystemChat, ServerboundGamePacket,
+        },
+        handshake::{ClientboundHandshakePacket, ServerboundHandshakePacket},
+        login::{ClientboundLoginPacket, ServerboundLoginPacket},
+        status::{
+            c_status_response::{Players, Version},
+            ClientboundStatusResponse,
+        },
+        Packet, PROTOCOL_VERSION, VERSION_NAME,
+    },
+    read::ReadPacketError,
+    server::{Listener, ServerConnection},
+};
+use azalea_registry::Block;
+use azalea_world::Chunk;
+use parking_lot::Mutex;
+use simdnbt::owned::{Nbt, NbtCompound, NbtTag};
+use tokio::{
+    net::ToSocketAddrs,
+    sync::mpsc,
+    task::{JoinHandle, JoinSet},
+    time::interval,
+};
+use tracing::{debug, info, warn};
+
+/// How many chunks around the spawn point are sent to players.
+const VIEW_DISTANCE: i32 = 4;
+/// The y coordinate of the bottom of the world.
+const MIN_Y: i32 = -64;
+/// The height of the world.
+const HEIGHT: i32 = 384;
+/// The blocks that the world is made of, starting at [`MIN_Y`].
+const LAYERS: [Block; 4] = [Block::Bedrock, Block::Dirt, Block::Dirt, Block::GrassBlock];
+
+/// A local server with a flat world, mostly useful for examples and tests.
+///
+/// The server stops when this is dropped. See the [module-level
+/// documentation](self) for more.
+pub struct PracticeServer {
+    address: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct ServerState {
+    /// The players that are in the game state, by their username.
+    players: HashMap<String, mpsc::UnboundedSender<ClientboundGamePacket>>,
+}
+
+impl ServerState {
+    fn broadcast(&self, packet: ClientboundGamePacket) {
+        for sender in self.players.values() {
+            let _ = sender.send(packet.clone());
+        }
+    }
+}
+
+impl PracticeServer {
+    /// Start a server on a random port on localhost.
+    ///
+    /// Use [`Self::address`] to get the address that bots should join.
+    pub async fn start() -> io::Result<Self> {
+        Self::bind("127.0.0.1:0").await
+    }
+
+    /// Start a server that's listening on the given address.
+    pub async fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = Listener::bind(address).await?;
+        let address = listener.local_addr()?;
+        info!("Practice server listening on {address}");
+
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let task = tokio::spawn(accept_connections(listener, state.clone()));
+
+        Ok(Self {
+            address,
+            state,
+            task,
+        })
+    }
+
+    /// The address that the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The usernames of the players that are currently in the world.
+    pub fn players(&self) -> Vec<String> {
+        self.state.lock().players.keys().cloned().collect()
+    }
+
+    /// Send a system message to every player in the world.
+    pub fn broadcast(&self, message: impl Into<FormattedText>) {
+        self.state.lock().broadcast(
+            ClientboundSystemChat {
+                content: message.into(),
+                overlay: false,
+            }
+            .into_variant(),
+        );
+    }
+}
+
+impl Drop for PracticeServer {
+    fn drop(&mut self) {
+        // the connections are in a JoinSet owned by this task, so they get
+        // aborted too
+        self.task.abort();
+    }
+}
+
+async fn accept_connections(listener: Listener, state: Arc<Mutex<ServerState>>) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let conn = match accepted {
+                    Ok((conn, _)) => conn,
+                    Err(e) => {
+                        warn!("Practice server couldn't accept a connection: {e}");
+                        continue;
+                    }
+                };
+                let state = state.clone();
+                connections.spawn(async move {
+                    if let Err(e) = handle_connection(conn, state).await {
+                        warn!("Practice server connection ended with an error: {e}");
+                    }
+                });
+            }
+            // clean up the connections that already finished
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
+
+async fn handle_connection(
+    conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket>,
+    state: Arc<Mutex<ServerState>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match conn.handshake().await?.1 {
+        ServerConnection::Status(conn) => {
+            conn.respond_to_status(ClientboundStatusResponse {
+                description: "An Azalea practice server".into(),
+                favicon: None,
+                players: Players {
+                    max: 100,
+                    online: 0,
+                    sample: Vec::new(),
+                },
+                version: Version {
+                    name: VERSION_NAME.to_string(),
+                    protocol: PROTOCOL_VERSION,
+                },
+                enforces_secure_chat: Some(false),
+                forge_data: None,
+                mod_info: None,
+            })
+            .await?;
+            Ok(())
+        }
+        ServerConnection::Login(conn) => handle_login(conn, state).await,
+    }
+}
+
+async fn handle_login(
+    mut conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    state: Arc<Mutex<ServerState>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let name = conn.read_hello().await?.name;
+    debug!("{name} is logging in to the practice server");
+
+    let mut conn = conn
+        .finish_login(GameProfile::new(
+            azalea_auth::offline::generate_uuid(&name),
+            name.clone(),
+        ))
+        .await?;
+    conn.write(overworld_registry_data()).await?;
+    conn.write(ClientboundFinishConfiguration).await?;
+    loop {
+        if let ServerboundConfigPacket::FinishConfiguration(_) = conn.read().await? {
+            break;
+        }
+    }
+
+    play(conn.game(), name, state).await
+}
+
+async fn play(
+    mut conn: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+    name: String,
+    state: Arc<Mutex<ServerState>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    conn.write(ClientboundLogin {
+        player_id: 1,
+        hardcore: false,
+        levels: vec![ResourceLocation::new("minecraft:overworld")],
+        max_players: 100,
+        chunk_radius: VIEW_DISTANCE as u32,
+        simulation_distance: VIEW_DISTANCE as u32,
+        reduced_debug_info: false,
+        show_death_screen: true,
+        do_limited_crafting: false,
+        common: CommonPlayerSpawnInfo {
+            dimension_type: 0,
+            dimension: ResourceLocation::new("minecraft:overworld"),
+            seed: 0,
+            game_type: GameMode::Survival,
+            previous_game_type: OptionalGameType(None),
+            is_debug: false,
+            is_flat: true,
+            last_death_location: None,
+            portal_cooldown: 0,
+            sea_level: 63,
+        },
+        enforces_secure_chat: false,
+    })
+    .await?;
+    conn.write(ClientboundGameEvent {
+        event: EventType::WaitForLevelChunks,
+        param: 0.,
+    })
+    .await?;
+    conn.write(ClientboundSetChunkCacheCenter { x: 0, z: 0 })
+        .await?;
+
+    let chunk_data = flat_chunk_data();
+    for x in -VIEW_DISTANCE..=VIEW_DISTANCE {
+        for z in -VIEW_DISTANCE..=VIEW_DISTANCE {
+            conn.write(ClientboundLevelChunkWithLight {
+                x,
+                z,
+                chunk_data: chunk_data.clone(),
+                light_data: ClientboundLightUpdatePacketData {
+                    sky_y_mask: BitSet::default(),
+                    block_y_mask: BitSet::default(),
+                    empty_sky_y_mask: BitSet::default(),
+                    empty_block_y_mask: BitSet::default(),
+                    sky_updates: Vec::new(),
+                    block_updates: Vec::new(),
+                },
+            })
+            .await?;
+        }
+    }
+
+    conn.write(ClientboundPlayerPosition {
+        id: 1,
+        change: PositionMoveRotation {
+            pos: Vec3::new(0.5, (MIN_Y + LAYERS.len() as i32) as f64, 0.5),
+            delta: Vec3::ZERO,
+            look_direction: LookDirection::default(),
+        },
+        relative: RelativeMovements {
+            x: false,
+            y: false,
+            z: false,
+            y_rot: false,
+            x_rot: false,
+            delta_x: false,
+            delta_y: false,
+            delta_z: false,
+            rotate_delta: false,
+        },
+    })
+    .await?;
+
+    let (mut read_conn, mut write_conn) = conn.into_split();
+    let (packet_sender, mut packet_receiver) = mpsc::unbounded_channel();
+    state.lock().players.insert(name.clone(), packet_sender);
+    info!("{name} joined the practice server");
+
+    let write_task = tokio::spawn(async move {
+        let mut keep_alive = interval(Duration::from_secs(15));
+        loop {
+            let packet = tokio::select! {
+                Some(packet) = packet_receiver.recv() => packet,
+                _ = keep_alive.tick() => {
+                    let id = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64;
+                    ClientboundKeepAlive { id }.into_variant()
+                }
+                else => break,
+            };
+            if write_conn.write(packet).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = loop {
+        match read_conn.read().await {
+            Ok(ServerboundGamePacket::Chat(p)) => {
+                state.lock().broadcast(
+                    ClientboundSystemChat {
+                        content: format!("<{name}> {}", p.message).into(),
+                        overlay: false,
+                    }
+                    .into_variant(),
+                );
+            }
+            Ok(_) => {}
+            Err(e) => match *e {
+                ReadPacketError::ConnectionClosed => break Ok(()),
+                ReadPacketError::IoError { source } => break Err(source.into()),
+                e => warn!("Practice server couldn't read a packet from {name}: {e}"),
+            },
+        }
+    };
+
+    write_task.abort();
+    state.lock().players.remove(&name);
+    info!("{name} left the practice server");
+    result
+}
+
+/// The registry data for an overworld-like dimension type. This is the only
+/// registry that Azalea needs to join.
+fn overworld_registry_data() -> ClientboundRegistryData {
+    let overworld = NbtCompound::from_values(vec![
+        ("ambient_light".into(), NbtTag::Float(0.)),
+        ("bed_works".into(), NbtTag::Byte(1)),
+        ("coordinate_scale".into(), NbtTag::Double(1.)),
+        (
+            "effects".into(),
+            NbtTag::String("minecraft:overworld".into()),
+        ),
+        ("has_ceiling".into(), NbtTag::Byte(0)),
+        ("has_raids".into(), NbtTag::Byte(1)),
+        ("has_skylight".into(), NbtTag::Byte(1)),
+        ("height".into(), NbtTag::Int(HEIGHT)),
+        (
+            "infiniburn".into(),
+            NbtTag::String("#minecraft:infiniburn_overworld".into()),
+        ),
+        ("logical_height".into(), NbtTag::Int(HEIGHT)),
+        ("min_y".into(), NbtTag::Int(MIN_Y)),
+        ("monster_spawn_block_light_limit".into(), NbtTag::Int(0)),
+        ("monster_spawn_light_level".into(), NbtTag::Int(0)),
+        ("natural".into(), NbtTag::Byte(1)),
+        ("piglin_safe".into(), NbtTag::Byte(0)),
+        ("respawn_anchor_works".into(), NbtTag::Byte(0)),
+        ("ultrawarm".into(), NbtTag::Byte(0)),
+    ]);
+    ClientboundRegistryData {
+        registry_id: ResourceLocation::new("minecraft:dimension_type"),
+        entries: vec![(
+            ResourceLocation::new("minecraft:overworld"),
+            Some(overworld),
+        )],
+    }
+}
+
+/// The data for a chunk in the flat world. Every chunk is the same, so this
+/// can be sent for any chunk position.
+fn flat_chunk_data() -> ClientboundLevelChunkPacketData {
+    let mut chunk = Chunk::default();
+    for (i, block) in LAYERS.into_iter().enumerate() {
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set(
+                    &ChunkBlockPos::new(x, MIN_Y + i as i32, z),
+                    block.into(),
+                    MIN_Y,
+                );
+            }
+        }
+    }
+    chunk.sections[0].block_count = (LAYERS.len() * 16 * 16) as u16;
+
+    let mut data = Vec::new();
+    chunk
+        .azalea_write(&mut data)
+        .expect("writing to a Vec can't fail");
+
+    ClientboundLevelChunkPacketData {
+        heightmaps: Nbt::None,
+        data,
+        block_entities: Vec::new(),
+    }
+}
@@ -8,6 +8,8 @@ pub use azalea_core::tick::GameTick;
 pub use crate::ecs as bevy_ecs;
 pub use crate::ecs::{component::Component, system::Resource};
 pub use crate::{
-    bot::BotClientExt, container::ContainerClientExt, pathfinder::PathfinderClientExt,
-    ClientBuilder,
+    bot::BotClientExt, chat_triggers::ChatTriggersAppExt, container::ContainerClientExt,
+    item_frames::ItemFrameClientExt, item_use::ItemUseClientExt, leads::LeadClientExt,
+    pathfinder::PathfinderClientExt, pets::PetClientExt, redstone::RedstoneClientExt,
+    rng::RngClientExt, trading::TradeClientExt, villagers::VillagerClientExt, ClientBuilder,
 };
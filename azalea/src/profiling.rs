@@ -0,0 +1,218 @@
+//! Measure how much time bots spend on their own work and waiting for the ECS,
+//! so you can tell how well a swarm scales.
+//!
+//! This is enabled with the `profiling` feature. See the `stress_test` example
+//! for how it's meant to be used.
+//!
+//! Every bot shares the same ECS, so the time that's spent running systems
+//! can't be split up between bots. What we can measure per bot is how long its
+//! handler takes and how long the handler has to wait when it locks the ECS
+//! with [`BotMetrics::lock_ecs`], which is where contention shows up when
+//! there are too many bots.
+//!
+//! Note that only the locks that go through [`BotMetrics::lock_ecs`] are
+//! timed. The ECS is also locked by the methods on [`Client`] (like
+//! [`Client::walk`]) and by the loop that runs the schedules, and those aren't
+//! counted in the metrics.
+
+use std::{
+    fmt::{self, Display},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use azalea_client::Client;
+use bevy_ecs::world::World;
+use parking_lot::{Mutex, MutexGuard};
+
+/// Metrics for a single bot. This is cheap to clone, and every clone updates
+/// the same metrics.
+#[derive(Clone, Default)]
+pub struct BotMetrics(Arc<Mutex<BotMetricsData>>);
+
+#[derive(Clone, Debug, Default)]
+pub struct BotMetricsData {
+    /// How many times the bot's handler was called.
+    pub handler_calls: u64,
+    /// The total time that was spent in the bot's handler, including time
+    /// spent waiting for the ECS lock.
+    pub handler_time: Duration,
+    /// How many times the handler locked the ECS with
+    /// [`BotMetrics::lock_ecs`].
+    pub handler_lock_count: u64,
+    /// The total time that was spent waiting for the ECS lock in
+    /// [`BotMetrics::lock_ecs`].
+    pub handler_lock_wait: Duration,
+    /// The longest that we had to wait for the ECS lock at once in
+    /// [`BotMetrics::lock_ecs`].
+    pub max_handler_lock_wait: Duration,
+}
+
+impl BotMetrics {
+    /// Get a copy of the metrics as they are right now.
+    pub fn get(&self) -> BotMetricsData {
+        self.0.lock().clone()
+    }
+
+    /// Reset every metric to zero, and return what they were before.
+    pub fn take(&self) -> BotMetricsData {
+        std::mem::take(&mut *self.0.lock())
+    }
+
+    /// Lock the bot's ECS and record how long it took.
+    ///
+    /// This is the only lock that's timed, so use it instead of
+    /// `bot.ecs.lock()` in the handler that's being measured.
+    pub fn lock_ecs<'a>(&self, bot: &'a Client) -> MutexGuard<'a, World> {
+        let start = Instant::now();
+        let ecs = bot.ecs.lock();
+        let wait = start.elapsed();
+
+        let mut data = self.0.lock();
+        data.handler_lock_count += 1;
+        data.handler_lock_wait += wait;
+        data.max_handler_lock_wait = data.max_handler_lock_wait.max(wait);
+        ecs
+    }
+
+    /// Run the bot's handler and record how long it took.
+    pub async fn time_handler<T>(&self, handler: impl std::future::Future<Output = T>) -> T {
+        let start = Instant::now();
+        let result = handler.await;
+        let elapsed = start.elapsed();
+
+        let mut data = self.0.lock();
+        data.handler_calls += 1;
+        data.handler_time += elapsed;
+        result
+    }
+}
+
+impl Display for BotMetricsData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let average_lock_wait = self
+            .handler_lock_wait
+            .checked_div(self.handler_lock_count as u32)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} handler calls in {:?}, {} handler locks with {:?} average and {:?} max wait",
+            self.handler_calls,
+            self.handler_time,
+            self.handler_lock_count,
+            average_lock_wait,
+            self.max_handler_lock_wait
+        )
+    }
+}
+
+/// The CPU time and memory that the whole process is using.
+///
+/// These are for every thread in the process, so they include anything else
+/// that's running in it (like a practice server) and can't be split up
+/// between bots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessStats {
+    /// The CPU time that the process has used in user and kernel mode, across
+    /// all threads.
+    pub cpu_time: Duration,
+    /// The amount of memory that the process has in RAM, in bytes.
+    pub resident_memory: u64,
+}
+
+/// The entries in the auxiliary vector for the clock tick rate and page size,
+/// see `man getauxval`.
+const AT_PAGESZ: u64 = 6;
+const AT_CLKTCK: u64 = 17;
+
+impl ProcessStats {
+    /// Read the stats for the current process from `/proc`.
+    ///
+    /// This only works on Linux, and returns `None` everywhere else.
+    pub fn current() -> Option<Self> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        // the kernel tells us the clock tick rate and page size in the
+        // auxiliary vector, so we don't need libc to get them
+        let auxv = std::fs::read("/proc/self/auxv").ok()?;
+        let clock_ticks_per_second = auxv_value(&auxv, AT_CLKTCK)?;
+        let page_size = auxv_value(&auxv, AT_PAGESZ)?;
+
+        let cpu_ticks = parse_stat_cpu_ticks(&std::fs::read_to_string("/proc/self/stat").ok()?)?;
+
+        let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+        let resident_pages = statm.split(' ').nth(1)?.parse::<u64>().ok()?;
+
+        Some(Self {
+            cpu_time: Duration::from_secs_f64(cpu_ticks as f64 / clock_ticks_per_second as f64),
+            resident_memory: resident_pages * page_size,
+        })
+    }
+}
+
+/// Find a value in the contents of `/proc/self/auxv`, which is a list of
+/// native-endian `usize` key and value pairs.
+fn auxv_value(auxv: &[u8], key: u64) -> Option<u64> {
+    const SIZE: usize = std::mem::size_of::<usize>();
+    auxv.chunks_exact(SIZE * 2)
+        .map(|pair| {
+            let read = |bytes: &[u8]| usize::from_ne_bytes(bytes.try_into().unwrap()) as u64;
+            (read(&pair[..SIZE]), read(&pair[SIZE..]))
+        })
+        .find(|&(k, value)| k == key && value != 0)
+        .map(|(_, value)| value)
+}
+
+/// Get the user and kernel mode CPU time from the contents of
+/// `/proc/self/stat`, in clock ticks.
+fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    // the process name can have spaces in it, so skip past it first
+    let fields = stat[stat.rfind(')')? + 2..].split(' ').collect::<Vec<_>>();
+    // utime and stime are fields 14 and 15, and we skipped the first two
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auxv_value() {
+        let mut auxv = Vec::new();
+        for (key, value) in [(AT_PAGESZ, 16384), (AT_CLKTCK, 250), (0, 0)] {
+            auxv.extend((key as usize).to_ne_bytes());
+            auxv.extend((value as usize).to_ne_bytes());
+        }
+        assert_eq!(auxv_value(&auxv, AT_PAGESZ), Some(16384));
+        assert_eq!(auxv_value(&auxv, AT_CLKTCK), Some(250));
+        assert_eq!(auxv_value(&auxv, 3), None);
+    }
+
+    #[test]
+    fn test_parse_stat_cpu_ticks() {
+        let stat = "1234 (my (weird) bot) S 1 1234 1234 0 -1 4194560 100 0 0 0 \
+                    25 17 0 0 20 0 8 0 100 0 0";
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(42));
+        assert_eq!(parse_stat_cpu_ticks("1234 (bot) S"), None);
+    }
+
+    #[test]
+    fn test_handler_lock_wait() {
+        let metrics = BotMetrics::default();
+        {
+            let mut data = metrics.0.lock();
+            data.handler_lock_count = 2;
+            data.handler_lock_wait = Duration::from_millis(10);
+        }
+        assert!(metrics
+            .get()
+            .to_string()
+            .contains("2 handler locks with 5ms average"));
+        assert_eq!(metrics.take().handler_lock_count, 2);
+        assert_eq!(metrics.get().handler_lock_count, 0);
+    }
+}
@@ -0,0 +1,335 @@
+//! Automatically rejoin the server when a bot gets disconnected.
+//!
+//! See [`ClientBuilder::reconnect`] and [`SwarmBuilder::reconnect`].
+//!
+//! [`ClientBuilder::reconnect`]: crate::ClientBuilder::reconnect
+//! [`SwarmBuilder::reconnect`]: crate::swarm::SwarmBuilder::reconnect
+
+use std::{future::Future, time::Duration};
+
+use azalea_client::{Account, Client, Event};
+use bevy_ecs::component::Component;
+use rand::Rng;
+use tracing::{error, info};
+
+use crate::{rng::BotRng, swarm::Swarm, JoinOpts};
+
+/// How long to wait before trying to rejoin the server after a bot was kicked
+/// or lost its connection.
+///
+/// Bots that were disconnected with [`Client::disconnect`] are never
+/// reconnected.
+///
+/// ```
+/// # use azalea::reconnect::ReconnectPolicy;
+/// # use std::time::Duration;
+/// // wait 5 seconds, then 10, then 20, and so on until it's 5 minutes
+/// let policy = ReconnectPolicy::exponential(Duration::from_secs(5), Duration::from_secs(300))
+///     // give up after failing to rejoin 10 times in a row
+///     .max_attempts(10)
+///     // and wait up to 2 extra seconds so the bots don't all rejoin at once
+///     .jitter(Duration::from_secs(2));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// How long to wait before the first attempt to rejoin.
+    pub initial_delay: Duration,
+    /// The longest we'll ever wait between attempts.
+    pub max_delay: Duration,
+    /// What the delay is multiplied by every time an attempt fails.
+    pub multiplier: u32,
+    /// The number of attempts to make before giving up, or `None` to keep
+    /// trying forever.
+    pub max_attempts: Option<u32>,
+    /// A random amount of time up to this is added to every delay, so bots
+    /// that were disconnected at the same time don't all try to rejoin at the
+    /// same moment.
+    ///
+    /// The randomness comes from the bot's [`BotRng`], so it's reproducible
+    /// if the swarm has a seed.
+    pub jitter: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Wait `initial_delay` before the first attempt, and double the delay
+    /// after every failed attempt until it reaches `max_delay`.
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            multiplier: 2,
+            max_attempts: None,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// Always wait the same amount of time between attempts.
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            initial_delay: delay,
+            max_delay: delay,
+            multiplier: 1,
+            max_attempts: None,
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// Give up after failing to rejoin this many times in a row.
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Add a random amount of time up to `jitter` to every delay. See
+    /// [`Self::jitter`](#structfield.jitter).
+    #[must_use]
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// How long to wait before the given attempt, starting at 0. Returns
+    /// `None` if we shouldn't try again.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Option<Duration> {
+        if let Some(max_attempts) = self.max_attempts {
+            if attempt >= max_attempts {
+                return None;
+            }
+        }
+        let factor = self.multiplier.saturating_pow(attempt);
+        Some(
+            self.initial_delay
+                .saturating_mul(factor)
+                .min(self.max_delay),
+        )
+    }
+
+    /// Like [`Self::delay_for_attempt`], but with a random amount of time up
+    /// to [`Self::jitter`](#structfield.jitter) added.
+    pub fn jittered_delay_for_attempt(&self, attempt: u32, rng: &mut impl Rng) -> Option<Duration> {
+        let delay = self.delay_for_attempt(attempt)?;
+        if self.jitter.is_zero() {
+            return Some(delay);
+        }
+        Some(delay + self.jitter.mul_f64(rng.gen::<f64>()))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    /// The same delays that are used for retrying when the initial join fails.
+    fn default() -> Self {
+        Self::exponential(Duration::from_secs(5), Duration::from_secs(15))
+    }
+}
+
+/// Keep trying to rejoin the server with the given bot until it works or the
+/// policy says to give up. The bot's state is kept from before it was
+/// disconnected.
+pub(crate) async fn reconnect<S: Component + Clone>(
+    swarm: Swarm,
+    bot: Client,
+    join_opts: JoinOpts,
+    policy: ReconnectPolicy,
+) {
+    let (Some(account), Some(state)) = (bot.get_component::<Account>(), bot.get_component::<S>())
+    else {
+        error!(
+            "Couldn't reconnect {:?} because it's missing its account or state",
+            bot.entity
+        );
+        return;
+    };
+    let username = account.username.clone();
+
+    // clone the rng so the bot's future random choices don't depend on how many
+    // times it had to try reconnecting
+    let mut rng = bot
+        .get_component::<BotRng>()
+        .unwrap_or_else(|| BotRng::new(None, &username));
+
+    let (swarm, account, state, join_opts, username) =
+        (&swarm, &account, &state, &join_opts, username.as_str());
+    let result = retry_join(
+        &policy,
+        &mut *rng,
+        |attempt, delay| {
+            info!(
+                "Reconnecting as {username} in {delay:?} (attempt {})",
+                attempt + 1
+            );
+            let _ = swarm
+                .bots_tx
+                .send((Some(Event::Reconnecting { attempt, delay }), bot.clone()));
+        },
+        move || async move {
+            match swarm.add_with_opts(account, state.clone(), join_opts).await {
+                Ok(_) => true,
+                Err(e) => {
+                    error!("Error reconnecting as {username}: {e}");
+                    false
+                }
+            }
+        },
+    )
+    .await;
+    if let Err(attempts) = result {
+        error!("Giving up on reconnecting as {username} after {attempts} attempts");
+    }
+}
+
+/// Wait for the delay that the policy gives and then call `join`, until it
+/// returns `true` or the policy says to give up. `on_retry` is called with the
+/// attempt number and the delay before every wait.
+///
+/// Attempts are counted from 0 every time this is called, so a bot that
+/// rejoined successfully starts with the initial delay again the next time
+/// it's disconnected. Returns the number of attempts if it gave up.
+async fn retry_join<Fut: Future<Output = bool>>(
+    policy: &ReconnectPolicy,
+    rng: &mut impl Rng,
+    mut on_retry: impl FnMut(u32, Duration),
+    mut join: impl FnMut() -> Fut,
+) -> Result<(), u32> {
+    let mut attempt = 0;
+    while let Some(delay) = policy.jittered_delay_for_attempt(attempt, &mut *rng) {
+        on_retry(attempt, delay);
+        tokio::time::sleep(delay).await;
+        if join().await {
+            return Ok(());
+        }
+        attempt += 1;
+    }
+    Err(attempt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_for_attempt() {
+        let policy = ReconnectPolicy::exponential(Duration::from_secs(5), Duration::from_secs(30))
+            .max_attempts(5);
+        let delays: Vec<_> = (0..6).map(|i| policy.delay_for_attempt(i)).collect();
+        assert_eq!(
+            delays,
+            [5, 10, 20, 30, 30]
+                .map(|secs| Some(Duration::from_secs(secs)))
+                .into_iter()
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_initial_delay_and_multiplier() {
+        let policy = ReconnectPolicy {
+            multiplier: 3,
+            ..ReconnectPolicy::exponential(Duration::from_millis(100), Duration::from_secs(2))
+        };
+        let delays: Vec<_> = (0..5)
+            .map(|i| policy.delay_for_attempt(i).unwrap().as_millis())
+            .collect();
+        // the last two are capped at the max delay
+        assert_eq!(delays, [100, 300, 900, 2000, 2000]);
+
+        // huge attempt numbers don't overflow
+        assert_eq!(
+            policy.delay_for_attempt(u32::MAX),
+            Some(Duration::from_secs(2))
+        );
+
+        let fixed = ReconnectPolicy::fixed(Duration::from_secs(3));
+        assert!((0..5).all(|i| fixed.delay_for_attempt(i) == Some(Duration::from_secs(3))));
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let jitter = Duration::from_millis(500);
+        let policy = ReconnectPolicy::exponential(Duration::from_secs(1), Duration::from_secs(4))
+            .jitter(jitter);
+        let mut rng = BotRng::new(Some(0), "bot0");
+        for _ in 0..100 {
+            for attempt in 0..4 {
+                let base = policy.delay_for_attempt(attempt).unwrap();
+                let delay = policy
+                    .jittered_delay_for_attempt(attempt, &mut *rng)
+                    .unwrap();
+                assert!(delay >= base && delay <= base + jitter, "{delay:?}");
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_join_resets_after_success() {
+        let policy = ReconnectPolicy::exponential(Duration::from_secs(1), Duration::from_secs(10));
+        let mut rng = BotRng::new(Some(0), "bot0");
+
+        // fail twice, then join
+        let mut delays = Vec::new();
+        let mut joins = 0;
+        let result = retry_join(
+            &policy,
+            &mut *rng,
+            |_, delay| delays.push(delay.as_secs()),
+            || {
+                joins += 1;
+                let joined = joins == 3;
+                async move { joined }
+            },
+        )
+        .await;
+        assert_eq!(result, Ok(()));
+        assert_eq!(delays, [1, 2, 4]);
+
+        // the next time the bot is disconnected, it starts from the initial delay
+        let mut delays = Vec::new();
+        let result = retry_join(
+            &policy,
+            &mut *rng,
+            |_, delay| delays.push(delay.as_secs()),
+            || async { true },
+        )
+        .await;
+        assert_eq!(result, Ok(()));
+        assert_eq!(delays, [1]);
+
+        // and it gives up after the max attempts
+        let policy = policy.max_attempts(3);
+        let mut attempts = Vec::new();
+        let result = retry_join(
+            &policy,
+            &mut *rng,
+            |attempt, _| attempts.push(attempt),
+            || async { false },
+        )
+        .await;
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_jitter_uses_bot_rng() {
+        let policy = ReconnectPolicy::fixed(Duration::from_secs(5)).jitter(Duration::from_secs(2));
+        let delays = |seed| {
+            let mut rng = BotRng::new(Some(seed), "bot0");
+            (0..8)
+                .map(|i| policy.jittered_delay_for_attempt(i, &mut *rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let first = delays(1234);
+        assert_eq!(first, delays(1234));
+        assert_ne!(first, delays(1235));
+        for delay in first {
+            assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(7));
+        }
+
+        // no jitter means no randomness
+        let mut rng = BotRng::new(None, "bot0");
+        assert_eq!(
+            ReconnectPolicy::fixed(Duration::from_secs(5)).jittered_delay_for_attempt(0, &mut *rng),
+            Some(Duration::from_secs(5))
+        );
+    }
+}
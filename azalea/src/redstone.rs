@@ -0,0 +1,227 @@
+//! Read the state of redstone components like levers and repeaters, and flip
+//! levers and press buttons.
+
+use azalea_block::{
+    properties::{
+        ComparatorType, HeavyWeightedPressurePlatePower, LightWeightedPressurePlatePower, Locked,
+        Powered, RepeaterDelay,
+    },
+    BlockState,
+};
+use azalea_client::{packet_handling::game::PacketEvent, Client};
+use azalea_core::position::BlockPos;
+use azalea_protocol::packets::game::ClientboundGamePacket;
+use azalea_registry::{tags, Block};
+use bevy_app::Update;
+use bevy_ecs::prelude::*;
+use futures_lite::Future;
+
+use crate::{
+    app::{App, Plugin},
+    bot::BotClientExt,
+};
+
+/// How many ticks we wait for the server to update a block after we interact
+/// with it.
+const INTERACT_TIMEOUT_TICKS: usize = 20;
+
+/// A plugin that sends [`RedstoneUpdateEvent`]s.
+#[derive(Clone, Default)]
+pub struct RedstonePlugin;
+impl Plugin for RedstonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RedstoneUpdateEvent>()
+            .add_systems(Update, send_redstone_update_events);
+    }
+}
+
+/// The state of a block that's used for building redstone circuits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedstoneComponent {
+    Lever {
+        powered: bool,
+    },
+    Button {
+        powered: bool,
+    },
+    /// A pressure plate. Plates that aren't weighted have a power of either 0
+    /// or 15.
+    PressurePlate {
+        power: u8,
+    },
+    Repeater {
+        powered: bool,
+        /// The delay in redstone ticks, from 1 to 4.
+        delay: u8,
+        locked: bool,
+    },
+    Comparator {
+        powered: bool,
+        /// Whether the comparator is in subtraction mode (with its front torch
+        /// lit).
+        subtract: bool,
+    },
+}
+
+impl RedstoneComponent {
+    /// Get the redstone component for a block state, or `None` if it's not a
+    /// block that we know about.
+    ///
+    /// ```
+    /// # use azalea::redstone::RedstoneComponent;
+    /// let lever = azalea::blocks::blocks::Lever {
+    ///     face: azalea::blocks::properties::Face::Floor,
+    ///     facing: azalea::blocks::properties::FacingCardinal::North,
+    ///     powered: true,
+    /// };
+    /// assert_eq!(
+    ///     RedstoneComponent::from_block_state(lever.into()),
+    ///     Some(RedstoneComponent::Lever { powered: true })
+    /// );
+    /// ```
+    pub fn from_block_state(state: BlockState) -> Option<Self> {
+        let block = Block::from(state);
+        let powered = || state.property::<Powered>().unwrap_or_default();
+        let component = match block {
+            Block::Lever => Self::Lever { powered: powered() },
+            Block::Repeater => Self::Repeater {
+                powered: powered(),
+                delay: state.property::<RepeaterDelay>()? as u8 + 1,
+                locked: state.property::<Locked>().unwrap_or_default(),
+            },
+            Block::Comparator => Self::Comparator {
+                powered: powered(),
+                subtract: state.property::<ComparatorType>()? == ComparatorType::Subtract,
+            },
+            Block::LightWeightedPressurePlate => Self::PressurePlate {
+                power: state.property::<LightWeightedPressurePlatePower>()? as u8,
+            },
+            Block::HeavyWeightedPressurePlate => Self::PressurePlate {
+                power: state.property::<HeavyWeightedPressurePlatePower>()? as u8,
+            },
+            _ if tags::blocks::BUTTONS.contains(&block) => Self::Button { powered: powered() },
+            _ if tags::blocks::PRESSURE_PLATES.contains(&block) => Self::PressurePlate {
+                power: if powered() { 15 } else { 0 },
+            },
+            _ => return None,
+        };
+        Some(component)
+    }
+
+    /// Whether the component is giving out a redstone signal.
+    pub fn is_powered(&self) -> bool {
+        match *self {
+            Self::Lever { powered }
+            | Self::Button { powered }
+            | Self::Repeater { powered, .. }
+            | Self::Comparator { powered, .. } => powered,
+            Self::PressurePlate { power } => power > 0,
+        }
+    }
+}
+
+/// An event sent when the server changes a block to a [`RedstoneComponent`],
+/// like when someone flips a lever or a repeater turns on.
+///
+/// Components that are changed because a chunk was loaded don't send this.
+#[derive(Event, Debug, Clone)]
+pub struct RedstoneUpdateEvent {
+    /// The local player that got the update.
+    pub entity: Entity,
+    pub pos: BlockPos,
+    pub component: RedstoneComponent,
+}
+
+pub fn send_redstone_update_events(
+    mut events: EventReader<PacketEvent>,
+    mut redstone_update_events: EventWriter<RedstoneUpdateEvent>,
+) {
+    for event in events.read() {
+        let updates = match event.packet.as_ref() {
+            ClientboundGamePacket::BlockUpdate(p) => vec![(p.pos, p.block_state)],
+            ClientboundGamePacket::SectionBlocksUpdate(p) => p
+                .states
+                .iter()
+                .map(|state| (p.section_pos + state.pos, state.state))
+                .collect(),
+            _ => continue,
+        };
+        for (pos, state) in updates {
+            if let Some(component) = RedstoneComponent::from_block_state(state) {
+                redstone_update_events.send(RedstoneUpdateEvent {
+                    entity: event.entity,
+                    pos,
+                    component,
+                });
+            }
+        }
+    }
+}
+
+pub trait RedstoneClientExt {
+    fn redstone_component(&self, pos: BlockPos) -> Option<RedstoneComponent>;
+    fn toggle_lever(&mut self, pos: BlockPos) -> impl Future<Output = bool> + Send;
+    fn press_button(&mut self, pos: BlockPos) -> impl Future<Output = bool> + Send;
+}
+
+impl RedstoneClientExt for Client {
+    /// Get the state of the redstone component at the given position, or
+    /// `None` if there isn't one or the chunk isn't loaded.
+    fn redstone_component(&self, pos: BlockPos) -> Option<RedstoneComponent> {
+        let state = self.world().read().get_block_state(&pos)?;
+        RedstoneComponent::from_block_state(state)
+    }
+
+    /// Flip the lever at the given position and wait for the server to tell
+    /// us that it changed.
+    ///
+    /// Returns false if there's no lever there or if it didn't change, for
+    /// example because we're too far away from it.
+    async fn toggle_lever(&mut self, pos: BlockPos) -> bool {
+        let Some(RedstoneComponent::Lever { powered }) = self.redstone_component(pos) else {
+            return false;
+        };
+        self.block_interact(pos);
+        wait_for_component(self, pos, |component| {
+            component == RedstoneComponent::Lever { powered: !powered }
+        })
+        .await
+    }
+
+    /// Press the button at the given position and wait for the server to tell
+    /// us that it's powered.
+    ///
+    /// Returns false if there's no button there, if it was already pressed,
+    /// or if it didn't get pressed, for example because we're too far away
+    /// from it.
+    async fn press_button(&mut self, pos: BlockPos) -> bool {
+        let Some(RedstoneComponent::Button { powered: false }) = self.redstone_component(pos)
+        else {
+            return false;
+        };
+        self.block_interact(pos);
+        wait_for_component(self, pos, |component| {
+            component == RedstoneComponent::Button { powered: true }
+        })
+        .await
+    }
+}
+
+/// Wait until the component at the position matches the predicate, or return
+/// false if it doesn't after [`INTERACT_TIMEOUT_TICKS`].
+async fn wait_for_component(
+    bot: &Client,
+    pos: BlockPos,
+    predicate: impl Fn(RedstoneComponent) -> bool,
+) -> bool {
+    let mut ticks = bot.get_tick_broadcaster();
+    for _ in 0..INTERACT_TIMEOUT_TICKS {
+        if ticks.recv().await.is_err() {
+            return false;
+        }
+        if bot.redstone_component(pos).is_some_and(&predicate) {
+            return true;
+        }
+    }
+    false
+}
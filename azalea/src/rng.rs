@@ -0,0 +1,146 @@
+//! A random number generator for each bot, so randomized behavior can be
+//! reproduced.
+//!
+//! Anything that makes bots act randomly (like looking around to avoid being
+//! kicked for being AFK, breaking ties between equally good paths, or adding
+//! human-like delays) should get its randomness from [`BotRng`] instead of
+//! [`rand::thread_rng`]. If a seed is set with [`SwarmBuilder::rng_seed`],
+//! every run with the same accounts will then make the same random choices,
+//! which is useful for tests and for debugging a replay of what a bot did.
+//!
+//! [`SwarmBuilder::rng_seed`]: crate::swarm::SwarmBuilder::rng_seed
+
+use azalea_client::{Account, Client};
+use azalea_entity::LocalEntity;
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::app::{App, Plugin, Update};
+
+/// A plugin that gives every bot a [`BotRng`].
+#[derive(Clone, Default)]
+pub struct RngPlugin {
+    /// The seed that every bot's random number generator is derived from, or
+    /// `None` to seed them from the OS.
+    ///
+    /// This can also be set with [`SwarmBuilder::rng_seed`].
+    ///
+    /// [`SwarmBuilder::rng_seed`]: crate::swarm::SwarmBuilder::rng_seed
+    pub seed: Option<u64>,
+}
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        // don't overwrite a seed that was set with SwarmBuilder::rng_seed
+        if self.seed.is_some() || !app.world().contains_resource::<RngSeed>() {
+            app.insert_resource(RngSeed(self.seed));
+        }
+        app.add_systems(Update, insert_bot_rng);
+    }
+}
+
+/// The seed that bots' random number generators are derived from. See
+/// [`RngPlugin::seed`].
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct RngSeed(pub Option<u64>);
+
+/// The random number generator for a bot.
+///
+/// If an [`RngSeed`] is set, this is seeded from it and the bot's username, so
+/// bots in the same swarm don't all make the same choices but each bot makes
+/// the same choices every time it's run. Note that the sequence of numbers can
+/// still change between versions of Azalea.
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct BotRng(pub StdRng);
+
+impl BotRng {
+    /// Create a random number generator for the bot with the given username.
+    /// If `seed` is `None`, it's seeded from the OS instead.
+    pub fn new(seed: Option<u64>, username: &str) -> Self {
+        match seed {
+            Some(seed) => Self(StdRng::seed_from_u64(derive_seed(seed, username))),
+            None => Self(StdRng::from_entropy()),
+        }
+    }
+}
+
+/// Mix the username into the seed.
+///
+/// This doesn't use std's hasher since its output isn't guaranteed to stay the
+/// same between Rust versions.
+fn derive_seed(seed: u64, username: &str) -> u64 {
+    // fnv-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in username.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    // splitmix64 finalizer so similar seeds don't give similar results
+    let mut z = seed ^ hash;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[allow(clippy::type_complexity)]
+fn insert_bot_rng(
+    mut commands: Commands,
+    query: Query<(Entity, &Account), (With<LocalEntity>, Without<BotRng>)>,
+    seed: Res<RngSeed>,
+) {
+    for (entity, account) in &query {
+        commands
+            .entity(entity)
+            .insert(BotRng::new(**seed, &account.username));
+    }
+}
+
+pub trait RngClientExt {
+    /// Run a function with the bot's random number generator.
+    ///
+    /// ```
+    /// # use azalea::{prelude::*, rng::RngClientExt};
+    /// # use rand::Rng;
+    /// # fn example(bot: &Client) {
+    /// let delay_ticks = bot.with_rng(|rng| rng.gen_range(5..20));
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the bot doesn't have a [`BotRng`] yet, which can
+    /// happen if it's called before the bot has been in the world for a tick.
+    fn with_rng<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T;
+}
+
+impl RngClientExt for Client {
+    fn with_rng<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        let mut ecs = self.ecs.lock();
+        let mut rng = ecs
+            .get_mut::<BotRng>(self.entity)
+            .expect("Our client is missing a required component: BotRng");
+        f(&mut rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_same_seed_is_reproducible() {
+        let mut a = BotRng::new(Some(1234), "bot0");
+        let mut b = BotRng::new(Some(1234), "bot0");
+        let a: Vec<u32> = (0..16).map(|_| a.gen()).collect();
+        let b: Vec<u32> = (0..16).map(|_| b.gen()).collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_bots_get_different_seeds() {
+        assert_ne!(derive_seed(1234, "bot0"), derive_seed(1234, "bot1"));
+        assert_ne!(derive_seed(1234, "bot0"), derive_seed(1235, "bot0"));
+    }
+}
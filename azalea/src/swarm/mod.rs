@@ -5,12 +5,14 @@
 mod chat;
 mod events;
 pub mod prelude;
+pub mod sleep;
 
 use std::{collections::HashMap, future::Future, net::SocketAddr, sync::Arc, time::Duration};
 
+use azalea_chat::FormattedText;
 use azalea_client::{
-    chat::ChatPacket, start_ecs_runner, Account, Client, DefaultPlugins, Event, JoinError,
-    StartClientOpts,
+    chat::ChatPacket, disconnect::ManualDisconnect, start_ecs_runner, transfer::PendingTransfer,
+    Account, Client, DefaultPlugins, Event, JoinError, ShareInstances, StartClientOpts,
 };
 use azalea_protocol::{resolver, ServerAddress};
 use azalea_world::InstanceContainer;
@@ -21,7 +23,11 @@ use parking_lot::{Mutex, RwLock};
 use tokio::sync::mpsc;
 use tracing::error;
 
-use crate::{BoxHandleFn, DefaultBotPlugins, HandleFn, JoinOpts, NoState, StartError};
+use crate::{
+    reconnect::{self, ReconnectPolicy},
+    rng::RngSeed,
+    BoxHandleFn, DefaultBotPlugins, HandleFn, JoinOpts, NoState, StartError,
+};
 
 /// A swarm is a way to conveniently control many bots at once, while also
 /// being able to control bots at an individual level when desired.
@@ -42,8 +48,14 @@ pub struct Swarm {
 
     pub instance_container: Arc<RwLock<InstanceContainer>>,
 
-    bots_tx: mpsc::UnboundedSender<(Option<Event>, Client)>,
+    pub(crate) bots_tx: mpsc::UnboundedSender<(Option<Event>, Client)>,
     swarm_tx: mpsc::UnboundedSender<SwarmEvent>,
+    /// Bots that got kicked or lost connection are sent here if a
+    /// [`ReconnectPolicy`] was set.
+    reconnect_tx: Option<mpsc::UnboundedSender<(Client, JoinOpts)>>,
+    /// Bots that a server sent to another server are sent here, along with
+    /// the address of the new server.
+    transfer_tx: mpsc::UnboundedSender<(Client, JoinOpts, ServerAddress)>,
 
     run_schedule_sender: mpsc::UnboundedSender<()>,
 }
@@ -81,6 +93,9 @@ where
     /// a duration of 0, since if a duration is present the bots will wait for
     /// the previous one to be ready.
     pub(crate) join_delay: Option<std::time::Duration>,
+    /// How bots should rejoin the server after getting disconnected, or `None`
+    /// if they shouldn't.
+    pub(crate) reconnect_policy: Option<ReconnectPolicy>,
 }
 impl SwarmBuilder<NoState, NoSwarmState, (), ()> {
     /// Start creating the swarm.
@@ -129,6 +144,7 @@ impl SwarmBuilder<NoState, NoSwarmState, (), ()> {
             handler: None,
             swarm_handler: None,
             join_delay: None,
+            reconnect_policy: None,
         }
     }
 }
@@ -231,6 +247,7 @@ where
                 Box::pin(handler(swarm, event, state))
             })),
             join_delay: self.join_delay,
+            reconnect_policy: self.reconnect_policy,
         }
     }
 }
@@ -331,6 +348,54 @@ where
         self
     }
 
+    /// Automatically rejoin the server when a bot is kicked or loses its
+    /// connection. The bot keeps the state that it had before it was
+    /// disconnected, and receives an [`Event::Reconnecting`] before every
+    /// attempt.
+    ///
+    /// [`SwarmEvent::Disconnect`] is still sent, so you shouldn't also
+    /// reconnect from your swarm handler if you use this.
+    ///
+    /// ```
+    /// # use azalea::{prelude::*, swarm::prelude::*, reconnect::ReconnectPolicy};
+    /// # use std::time::Duration;
+    /// # let swarm_builder = SwarmBuilder::new();
+    /// swarm_builder.reconnect(ReconnectPolicy::exponential(
+    ///     Duration::from_secs(5),
+    ///     Duration::from_secs(60),
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// Seed the random number generators that the bots use, so any random
+    /// behavior is the same every time the swarm is run. See the [`rng`]
+    /// module for more details.
+    ///
+    /// [`rng`]: crate::rng
+    #[must_use]
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.app.insert_resource(RngSeed(Some(seed)));
+        self
+    }
+
+    /// Set whether bots that are in the same world share their view of it,
+    /// which is the default.
+    ///
+    /// Sharing means the chunks and entities that one bot was sent can be seen
+    /// by every other bot in the same world, which saves memory. If you turn it
+    /// off, every bot only knows about what the server sent to it, which can be
+    /// useful for things like testing desync detection. See
+    /// [`ShareInstances`] for more details.
+    #[must_use]
+    pub fn share_world(mut self, share: bool) -> Self {
+        self.app.insert_resource(ShareInstances(share));
+        self
+    }
+
     /// Build this `SwarmBuilder` into an actual [`Swarm`] and join the given
     /// server.
     ///
@@ -380,6 +445,8 @@ where
         // we can't modify the swarm plugins after this
         let (bots_tx, mut bots_rx) = mpsc::unbounded_channel();
         let (swarm_tx, mut swarm_rx) = mpsc::unbounded_channel();
+        let (reconnect_tx, mut reconnect_rx) = mpsc::unbounded_channel();
+        let (transfer_tx, mut transfer_rx) = mpsc::unbounded_channel();
 
         swarm_tx.send(SwarmEvent::Init).unwrap();
 
@@ -401,6 +468,8 @@ where
             bots_tx,
 
             swarm_tx: swarm_tx.clone(),
+            reconnect_tx: self.reconnect_policy.is_some().then_some(reconnect_tx),
+            transfer_tx,
 
             run_schedule_sender,
         };
@@ -450,6 +519,30 @@ where
             swarm_tx.send(SwarmEvent::Login).unwrap();
         });
 
+        if let Some(policy) = self.reconnect_policy.clone() {
+            let swarm_clone = swarm.clone();
+            tokio::spawn(async move {
+                while let Some((bot, join_opts)) = reconnect_rx.recv().await {
+                    tokio::spawn(reconnect::reconnect::<S>(
+                        swarm_clone.clone(),
+                        bot,
+                        join_opts,
+                        policy.clone(),
+                    ));
+                }
+            });
+        }
+
+        let swarm_clone = swarm.clone();
+        tokio::spawn(async move {
+            while let Some((bot, join_opts, address)) = transfer_rx.recv().await {
+                let swarm = swarm_clone.clone();
+                tokio::spawn(async move {
+                    swarm.follow_transfer::<S>(bot, join_opts, address).await;
+                });
+            }
+        });
+
         let swarm_state = self.swarm_state;
 
         // Watch swarm_rx and send those events to the swarm_handle.
@@ -508,9 +601,12 @@ pub enum SwarmEvent {
     Init,
     /// A bot got disconnected from the server.
     ///
+    /// The last field is the reason that the server gave for kicking the bot,
+    /// like in [`Event::Disconnect`].
+    ///
     /// You can implement an auto-reconnect by calling [`Swarm::add_with_opts`]
     /// with the account and options from this event.
-    Disconnect(Box<Account>, JoinOpts),
+    Disconnect(Box<Account>, JoinOpts, Option<FormattedText>),
     /// At least one bot received a chat message.
     Chat(ChatPacket),
 }
@@ -567,7 +663,7 @@ pub type BoxSwarmHandleFn<SS, R> =
 ///     _state: SwarmState,
 /// ) -> anyhow::Result<()> {
 ///     match &event {
-///         SwarmEvent::Disconnect(account, join_opts) => {
+///         SwarmEvent::Disconnect(account, join_opts, _reason) => {
 ///             // automatically reconnect after 5 seconds
 ///             tokio::time::sleep(Duration::from_secs(5)).await;
 ///             swarm.add_with_opts(account, State::default(), join_opts).await?;
@@ -621,6 +717,9 @@ impl Swarm {
             address: &address,
             resolved_address: &resolved_address,
             proxy: join_opts.proxy.clone(),
+            socket_options: join_opts.socket_options.clone().unwrap_or_default(),
+            bungeecord_forwarding: join_opts.bungeecord_forwarding.clone(),
+            proxy_protocol: join_opts.proxy_protocol.clone(),
             run_schedule_sender: self.run_schedule_sender.clone(),
         })
         .await?;
@@ -636,9 +735,15 @@ impl Swarm {
         let cloned_bots_tx = self.bots_tx.clone();
         let cloned_bot = bot.clone();
         let swarm_tx = self.swarm_tx.clone();
+        let reconnect_tx = self.reconnect_tx.clone();
+        let transfer_tx = self.transfer_tx.clone();
         let join_opts = join_opts.clone();
         tokio::spawn(async move {
+            let mut disconnect_reason = None;
             while let Some(event) = rx.recv().await {
+                if let Event::Disconnect(reason) = &event {
+                    disconnect_reason.clone_from(reason);
+                }
                 // we can't handle events here (since we can't copy the handler),
                 // they're handled above in SwarmBuilder::start
                 if let Err(e) = cloned_bots_tx.send((Some(event), cloned_bot.clone())) {
@@ -646,17 +751,88 @@ impl Swarm {
                 }
             }
             cloned_bots.lock().remove(&bot.entity);
+            // the server sent us somewhere else, so this isn't really a disconnect
+            if let Some(transfer) = cloned_bot.get_component::<PendingTransfer>() {
+                let _ = transfer_tx.send((cloned_bot, join_opts, transfer.address));
+                return;
+            }
             let account = cloned_bot
                 .get_component::<Account>()
                 .expect("bot is missing required Account component");
+            if let Some(reconnect_tx) = reconnect_tx
+                && cloned_bot.get_component::<ManualDisconnect>().is_none()
+            {
+                let _ = reconnect_tx.send((cloned_bot, join_opts.clone()));
+            }
             swarm_tx
-                .send(SwarmEvent::Disconnect(Box::new(account), join_opts))
+                .send(SwarmEvent::Disconnect(
+                    Box::new(account),
+                    join_opts,
+                    disconnect_reason,
+                ))
                 .unwrap();
         });
 
         Ok(bot)
     }
 
+    /// Join the server that a bot was transferred to, keeping its state.
+    ///
+    /// If that doesn't work, it's treated like the bot was disconnected from
+    /// the original server.
+    async fn follow_transfer<S: Component + Clone>(
+        &self,
+        bot: Client,
+        join_opts: JoinOpts,
+        address: ServerAddress,
+    ) {
+        let (Some(account), Some(state)) =
+            (bot.get_component::<Account>(), bot.get_component::<S>())
+        else {
+            error!(
+                "Couldn't follow transfer for {:?} because it's missing its account or state",
+                bot.entity
+            );
+            return;
+        };
+
+        let mut transfer_join_opts = join_opts.clone();
+        transfer_join_opts.custom_address = Some(address.clone());
+        let result = match resolver::resolve_address(&address).await {
+            Ok(resolved_address) => {
+                transfer_join_opts.custom_resolved_address = Some(resolved_address);
+                self.add_with_opts(&account, state, &transfer_join_opts)
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+
+        match result {
+            Ok(new_bot) => {
+                let _ = self
+                    .bots_tx
+                    .send((Some(Event::Transferred { address }), new_bot));
+            }
+            Err(e) => {
+                error!(
+                    "Error following transfer to {}:{} as {}: {e}",
+                    address.host, address.port, account.username
+                );
+                // so we don't say it's a transfer if we reconnect to the original server
+                bot.ecs
+                    .lock()
+                    .entity_mut(bot.entity)
+                    .remove::<PendingTransfer>();
+                if let Some(reconnect_tx) = &self.reconnect_tx {
+                    let _ = reconnect_tx.send((bot, join_opts.clone()));
+                }
+                let _ =
+                    self.swarm_tx
+                        .send(SwarmEvent::Disconnect(Box::new(account), join_opts, None));
+            }
+        }
+    }
+
     /// Add a new account to the swarm, retrying if it couldn't join. This will
     /// run forever until the bot joins or the task is aborted.
     ///
@@ -0,0 +1,524 @@
+//! Make bots in a swarm sleep together so the night gets skipped.
+//!
+//! This isn't part of the default plugins, so you have to add
+//! [`SwarmSleepPlugin`] to your swarm yourself.
+//!
+//! ```no_run
+//! # use azalea::{prelude::*, swarm::{prelude::*, sleep::{SwarmSleepConfig, SwarmSleepPlugin}}};
+//! # use azalea::BlockPos;
+//! # async fn example(accounts: Vec<Account>) {
+//! SwarmBuilder::new()
+//!     .add_accounts(accounts)
+//!     .add_plugins(SwarmSleepPlugin {
+//!         config: SwarmSleepConfig {
+//!             // the server's playersSleepingPercentage gamerule
+//!             sleep_percentage: 50,
+//!             beds: vec![BlockPos::new(0, 64, 0), BlockPos::new(2, 64, 0)],
+//!             ..Default::default()
+//!         },
+//!     })
+//!     .start("localhost")
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! When it's night (or thundering), enough bots to reach the sleeping
+//! percentage are sent to the closest free beds. Beds from
+//! [`SwarmSleepConfig::beds`] are used, as well as any beds that a bot in the
+//! swarm has seen before (see [`KnownBeds`]), even if they're in chunks that
+//! aren't loaded anymore. When it's day again, the bots get out of bed.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use azalea_block::{
+    properties::{Occupied, Part},
+    BlockState, BlockStates,
+};
+use azalea_client::{
+    interact::BlockInteractEvent, packet_handling::game::SendPacketEvent, GameTime, InstanceHolder,
+    TabList, Weather,
+};
+use azalea_core::{
+    game_type::GameMode, position::BlockPos, resource_location::ResourceLocation, tick::GameTick,
+};
+use azalea_entity::{metadata::SleepingPos, EyeHeight, LocalEntity, Position};
+use azalea_protocol::packets::game::s_player_command::{self, ServerboundPlayerCommand};
+use azalea_registry::tags;
+use azalea_world::{Instance, InstanceName, MinecraftEntityId};
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use tracing::debug;
+
+use crate::pathfinder::{goals::ReachBlockPosGoal, moves, GotoEvent, PathfinderTimeout};
+
+/// How often we look for beds for the bots that need one, in ticks.
+const ASSIGN_INTERVAL_TICKS: u32 = 20;
+/// How often a bot that's next to its bed tries to get in it, in ticks.
+const INTERACT_INTERVAL_TICKS: u32 = 10;
+/// How long a bot tries to get to its bed before giving up on it, in ticks.
+const GIVE_UP_TICKS: u32 = 20 * 30;
+
+#[derive(Clone, Default)]
+pub struct SwarmSleepPlugin {
+    pub config: SwarmSleepConfig,
+}
+impl Plugin for SwarmSleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .init_resource::<KnownBeds>()
+            .add_systems(
+                GameTick,
+                (
+                    remember_beds,
+                    assign_beds,
+                    sleep_in_assigned_beds,
+                    leave_beds_at_dawn,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The settings for the [`SwarmSleepPlugin`]. This is a resource, so it can be
+/// changed while the swarm is running.
+#[derive(Resource, Clone, Debug)]
+pub struct SwarmSleepConfig {
+    /// The server's `playersSleepingPercentage` gamerule, which is the
+    /// percentage of players that have to be sleeping to skip the night. The
+    /// default is 100, like in vanilla.
+    pub sleep_percentage: u8,
+    /// Beds that the bots are allowed to use, even if they're far away.
+    pub beds: Vec<BlockPos>,
+    /// How far away from a bot that we look for beds to remember in loaded
+    /// chunks.
+    pub search_radius: u32,
+}
+
+impl Default for SwarmSleepConfig {
+    fn default() -> Self {
+        Self {
+            sleep_percentage: 100,
+            beds: Vec::new(),
+            search_radius: 32,
+        }
+    }
+}
+
+impl SwarmSleepConfig {
+    /// How many players have to be sleeping to skip the night, using the same
+    /// formula as vanilla.
+    ///
+    /// ```
+    /// # use azalea::swarm::sleep::SwarmSleepConfig;
+    /// let config = SwarmSleepConfig {
+    ///     sleep_percentage: 50,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(config.sleepers_needed(5), 3);
+    /// assert_eq!(config.sleepers_needed(0), 1);
+    /// ```
+    pub fn sleepers_needed(&self, players: usize) -> usize {
+        (players * self.sleep_percentage as usize)
+            .div_ceil(100)
+            .max(1)
+    }
+}
+
+/// A component for bots that were told to sleep in a bed by the
+/// [`SwarmSleepPlugin`].
+#[derive(Component, Clone, Debug)]
+pub struct AssignedBed {
+    pub pos: BlockPos,
+    /// The number of ticks since the bed was assigned, which resets when we
+    /// get in it.
+    pub ticks: u32,
+}
+
+/// The beds that bots in the swarm have seen, so they can be used again
+/// after the chunks they're in get unloaded.
+///
+/// This is a resource that's shared by the whole swarm, so a bed that one bot
+/// found can be used by any other bot in the same world. Beds are forgotten
+/// when a bot sees that they were broken.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct KnownBeds {
+    beds: HashMap<ResourceLocation, HashSet<BlockPos>>,
+}
+
+impl KnownBeds {
+    /// The positions of the heads of the beds that we know about in the
+    /// world.
+    pub fn in_world(&self, world: &ResourceLocation) -> impl Iterator<Item = BlockPos> + '_ {
+        self.beds.get(world).into_iter().flatten().copied()
+    }
+
+    pub fn insert(&mut self, world: ResourceLocation, pos: BlockPos) {
+        self.beds.entry(world).or_default().insert(pos);
+    }
+
+    pub fn remove(&mut self, world: &ResourceLocation, pos: &BlockPos) -> bool {
+        self.beds
+            .get_mut(world)
+            .is_some_and(|beds| beds.remove(pos))
+    }
+
+    /// Remember the beds within the radius of the position, and forget the
+    /// ones that are loaded but aren't beds anymore.
+    pub fn remember_near(
+        &mut self,
+        instance: &Instance,
+        world: &ResourceLocation,
+        position: BlockPos,
+        radius: u32,
+    ) {
+        let bed_states = BlockStates::from(&*tags::blocks::BEDS);
+        let seen = instance
+            .find_blocks(position, radius, &bed_states)
+            .filter(|pos| instance.get_block_state(pos).is_some_and(is_bed_head))
+            .collect::<Vec<_>>();
+
+        if let Some(beds) = self.beds.get_mut(world) {
+            let radius_squared = radius as i32 * radius as i32;
+            beds.retain(|bed| {
+                bed.distance_squared_to(&position) > radius_squared
+                    || instance.get_block_state(bed).map_or(true, is_bed_head)
+            });
+        }
+        for pos in seen {
+            self.insert(world.clone(), pos);
+        }
+    }
+}
+
+fn is_night(game_time: Option<&GameTime>, weather: Option<&Weather>) -> bool {
+    game_time.is_some_and(|time| time.can_sleep())
+        || weather.is_some_and(|weather| weather.is_thundering())
+}
+
+fn is_bed_head(state: BlockState) -> bool {
+    state.property::<Part>() == Some(Part::Head)
+}
+
+/// Whether the block is the head of a bed that nobody is sleeping in.
+fn is_free_bed(state: BlockState) -> bool {
+    is_bed_head(state) && state.property::<Occupied>() == Some(false)
+}
+
+pub fn remember_beds(
+    config: Res<SwarmSleepConfig>,
+    mut known_beds: ResMut<KnownBeds>,
+    query: Query<(&Position, &InstanceName, &InstanceHolder), With<LocalEntity>>,
+    mut ticks: Local<u32>,
+) {
+    *ticks += 1;
+    if *ticks % ASSIGN_INTERVAL_TICKS != 0 {
+        return;
+    }
+
+    for (position, instance_name, instance_holder) in &query {
+        let instance = instance_holder.instance.read();
+        known_beds.remember_near(
+            &instance,
+            instance_name,
+            BlockPos::from(position),
+            config.search_radius,
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn assign_beds(
+    mut commands: Commands,
+    config: Res<SwarmSleepConfig>,
+    known_beds: Res<KnownBeds>,
+    query: Query<
+        (
+            Entity,
+            &Position,
+            &InstanceName,
+            &InstanceHolder,
+            &TabList,
+            Option<&GameTime>,
+            Option<&Weather>,
+            Option<&AssignedBed>,
+        ),
+        With<LocalEntity>,
+    >,
+    mut goto_events: EventWriter<GotoEvent>,
+    mut ticks: Local<u32>,
+) {
+    *ticks += 1;
+    if *ticks % ASSIGN_INTERVAL_TICKS != 0 {
+        return;
+    }
+
+    let overworld = ResourceLocation::new("minecraft:overworld");
+    let mut bots_by_world = HashMap::<&ResourceLocation, Vec<_>>::new();
+    for bot in &query {
+        // beds explode in other dimensions
+        if **bot.2 == overworld {
+            bots_by_world.entry(&**bot.2).or_default().push(bot);
+        }
+    }
+
+    for (world, bots) in &bots_by_world {
+        if !bots.iter().any(|bot| is_night(bot.5, bot.6)) {
+            continue;
+        }
+        let players = bots[0]
+            .4
+            .values()
+            .filter(|player| player.gamemode != GameMode::Spectator)
+            .count();
+        let needed = config.sleepers_needed(players);
+
+        let mut taken = bots
+            .iter()
+            .filter_map(|bot| bot.7.map(|bed| bed.pos))
+            .collect::<HashSet<_>>();
+        let mut candidates = config
+            .beds
+            .iter()
+            .copied()
+            .chain(known_beds.in_world(world))
+            .collect::<HashSet<_>>();
+        for &(entity, position, _, instance_holder, _, _, _, assigned_bed) in bots {
+            if taken.len() >= needed {
+                break;
+            }
+            if assigned_bed.is_some() {
+                continue;
+            }
+
+            let instance = instance_holder.instance.read();
+            let Some(bed) = find_bed(&instance, position, &candidates, &taken) else {
+                continue;
+            };
+            debug!("Sending {entity:?} to the bed at {bed}");
+            taken.insert(bed);
+            candidates.remove(&bed);
+            commands
+                .entity(entity)
+                .insert(AssignedBed { pos: bed, ticks: 0 });
+            goto_events.send(GotoEvent {
+                entity,
+                goal: Arc::new(ReachBlockPosGoal {
+                    pos: bed,
+                    chunk_storage: instance.chunks.clone(),
+                }),
+                successors_fn: moves::default_move,
+                allow_mining: false,
+                min_timeout: PathfinderTimeout::Time(Duration::from_secs(1)),
+                max_timeout: PathfinderTimeout::Time(Duration::from_secs(5)),
+            });
+        }
+    }
+}
+
+/// Find the closest free bed out of the candidates to the position that isn't
+/// already taken by another bot.
+///
+/// Beds in chunks that aren't loaded are assumed to be free, since we can't
+/// know until we get there.
+fn find_bed(
+    instance: &Instance,
+    position: &Position,
+    candidates: &HashSet<BlockPos>,
+    taken: &HashSet<BlockPos>,
+) -> Option<BlockPos> {
+    let position = BlockPos::from(position);
+    candidates
+        .iter()
+        .copied()
+        .filter(|bed| !taken.contains(bed))
+        .filter(|bed| instance.get_block_state(bed).map_or(true, is_free_bed))
+        .min_by_key(|bed| bed.distance_squared_to(&position))
+}
+
+pub fn sleep_in_assigned_beds(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut AssignedBed,
+        &Position,
+        &EyeHeight,
+        &SleepingPos,
+        &InstanceHolder,
+    )>,
+    mut block_interact_events: EventWriter<BlockInteractEvent>,
+) {
+    for (entity, mut bed, position, eye_height, sleeping_pos, instance_holder) in &mut query {
+        if sleeping_pos.is_some() {
+            bed.ticks = 0;
+            continue;
+        }
+
+        bed.ticks += 1;
+        // the bed might not be loaded yet if we're still walking to it
+        let is_free = instance_holder
+            .instance
+            .read()
+            .get_block_state(&bed.pos)
+            .map_or(true, is_free_bed);
+        if !is_free || bed.ticks > GIVE_UP_TICKS {
+            // another bot will be sent to a different bed
+            debug!("{entity:?} couldn't get into the bed at {}", bed.pos);
+            commands.entity(entity).remove::<AssignedBed>();
+            continue;
+        }
+
+        let eye_position = position.up(**eye_height as f64);
+        let in_reach = eye_position.distance_squared_to(&bed.pos.center()) <= 4.5 * 4.5;
+        if in_reach && bed.ticks % INTERACT_INTERVAL_TICKS == 0 {
+            block_interact_events.send(BlockInteractEvent {
+                entity,
+                position: bed.pos,
+            });
+        }
+    }
+}
+
+pub fn leave_beds_at_dawn(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            &SleepingPos,
+            &MinecraftEntityId,
+            Option<&GameTime>,
+            Option<&Weather>,
+        ),
+        With<AssignedBed>,
+    >,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, sleeping_pos, entity_id, game_time, weather) in &query {
+        if is_night(game_time, weather) {
+            continue;
+        }
+        // the server usually wakes us up by itself, but it doesn't hurt to
+        // make sure
+        if sleeping_pos.is_some() {
+            send_packet_events.send(SendPacketEvent::new(
+                entity,
+                ServerboundPlayerCommand {
+                    id: **entity_id,
+                    action: s_player_command::Action::StopSleeping,
+                    data: 0,
+                },
+            ));
+        }
+        commands.entity(entity).remove::<AssignedBed>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_block::properties::FacingCardinal;
+    use azalea_core::position::ChunkPos;
+    use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
+
+    use super::*;
+
+    fn bed(part: Part) -> BlockState {
+        azalea_block::blocks::RedBed {
+            facing: FacingCardinal::North,
+            occupied: false,
+            part,
+        }
+        .into()
+    }
+
+    /// Make a world with a bed in the chunk at 0,0. The partial chunk storage
+    /// has to be kept alive for the chunk to stay loaded.
+    fn world_with_bed(head: BlockPos) -> (Instance, PartialChunkStorage) {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut instance = Instance::from(ChunkStorage::default());
+        partial_chunks.set(
+            &ChunkPos::new(0, 0),
+            Some(Chunk::default()),
+            &mut instance.chunks,
+        );
+        instance.set_block_state(&head, bed(Part::Head));
+        instance.set_block_state(&head.south(1), bed(Part::Foot));
+        (instance, partial_chunks)
+    }
+
+    #[test]
+    fn test_remembers_beds_after_unloading() {
+        let overworld = ResourceLocation::new("minecraft:overworld");
+        let head = BlockPos::new(2, 64, 2);
+        let (instance, partial_chunks) = world_with_bed(head);
+
+        let mut known_beds = KnownBeds::default();
+        known_beds.remember_near(&instance, &overworld, BlockPos::new(0, 64, 0), 32);
+        // only the head of the bed is remembered
+        assert_eq!(known_beds.in_world(&overworld).collect::<Vec<_>>(), [head]);
+
+        drop(partial_chunks);
+        assert!(instance.get_block_state(&head).is_none());
+        // the chunk isn't loaded anymore, so there's nothing to forget
+        known_beds.remember_near(&instance, &overworld, BlockPos::new(0, 64, 0), 32);
+
+        let candidates = known_beds.in_world(&overworld).collect::<HashSet<_>>();
+        let position = Position::new(azalea_core::position::Vec3::new(100., 64., 100.));
+        assert_eq!(
+            find_bed(&instance, &position, &candidates, &HashSet::new()),
+            Some(head)
+        );
+        assert_eq!(
+            find_bed(&instance, &position, &candidates, &HashSet::from([head])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_forgets_broken_beds() {
+        let overworld = ResourceLocation::new("minecraft:overworld");
+        let head = BlockPos::new(2, 64, 2);
+        let (instance, _partial_chunks) = world_with_bed(head);
+
+        let mut known_beds = KnownBeds::default();
+        known_beds.remember_near(&instance, &overworld, BlockPos::new(0, 64, 0), 32);
+        // beds in other worlds are kept separately
+        let nether = ResourceLocation::new("minecraft:the_nether");
+        known_beds.insert(nether.clone(), head);
+
+        instance.set_block_state(&head, BlockState::AIR);
+        instance.set_block_state(&head.south(1), BlockState::AIR);
+        known_beds.remember_near(&instance, &overworld, BlockPos::new(0, 64, 0), 32);
+        assert_eq!(known_beds.in_world(&overworld).count(), 0);
+        assert_eq!(known_beds.in_world(&nether).collect::<Vec<_>>(), [head]);
+    }
+
+    #[test]
+    fn test_occupied_beds_arent_free() {
+        let head = BlockPos::new(2, 64, 2);
+        let (instance, _partial_chunks) = world_with_bed(head);
+        instance.set_block_state(
+            &head,
+            azalea_block::blocks::RedBed {
+                facing: FacingCardinal::North,
+                occupied: true,
+                part: Part::Head,
+            }
+            .into(),
+        );
+
+        let position = Position::new(azalea_core::position::Vec3::new(0., 64., 0.));
+        assert_eq!(
+            find_bed(
+                &instance,
+                &position,
+                &HashSet::from([head]),
+                &HashSet::new()
+            ),
+            None
+        );
+    }
+}
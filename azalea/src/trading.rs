@@ -0,0 +1,468 @@
+//! Trade with villagers, and automatically run trading halls.
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use azalea_client::{
+    inventory::{Inventory, MerchantOffers, SelectTradeEvent},
+    Client,
+};
+use azalea_core::position::BlockPos;
+use azalea_entity::Position;
+use azalea_inventory::{
+    item::MaxStackSizeExt, operations::QuickMoveClick, ItemMatcher, ItemStack, Menu,
+};
+use azalea_protocol::packets::game::c_merchant_offers::MerchantOffer;
+use azalea_registry::Item;
+use bevy_ecs::entity::Entity;
+use futures_lite::Future;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::{
+    bot::BotClientExt,
+    container::{ContainerClientExt, ContainerHandle},
+    pathfinder::{
+        goals::{RadiusGoal, ReachBlockPosGoal},
+        PathfinderClientExt,
+    },
+};
+
+/// The index of the result slot in the merchant menu.
+const RESULT_SLOT: u16 = 2;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OpenTradesError {
+    #[error("The merchant didn't open a menu")]
+    NoMenu,
+    #[error("We don't have an inventory")]
+    NoInventory,
+    #[error("The menu that was opened isn't a merchant menu")]
+    NotMerchant,
+    #[error("The merchant menu was opened but we didn't get any offers")]
+    NoOffers,
+}
+
+pub trait TradeClientExt {
+    fn open_trades(
+        &mut self,
+        merchant: Entity,
+    ) -> impl Future<Output = Result<TradingMenu, OpenTradesError>> + Send;
+    fn run_trading_hall(
+        &mut self,
+        hall: &TradingHall,
+    ) -> impl Future<Output = TradingHallReport> + Send;
+}
+
+impl TradeClientExt for Client {
+    /// Open the trading menu of a villager or wandering trader.
+    ///
+    /// This doesn't walk to the merchant, so you should make sure that we're
+    /// close enough to it first. Returns an error if the menu couldn't be
+    /// opened (for example, because the villager doesn't have a profession).
+    async fn open_trades(&mut self, merchant: Entity) -> Result<TradingMenu, OpenTradesError> {
+        let container = self
+            .open_entity_container(merchant)
+            .await
+            .ok_or(OpenTradesError::NoMenu)?;
+
+        // the offers are sent right after the menu is opened
+        let mut ticks = self.get_tick_broadcaster();
+        for _ in 0..20 {
+            {
+                let ecs = self.ecs.lock();
+                let inventory = ecs
+                    .get::<Inventory>(self.entity)
+                    .ok_or(OpenTradesError::NoInventory)?;
+                if !matches!(inventory.container_menu, Some(Menu::Merchant { .. })) {
+                    return Err(OpenTradesError::NotMerchant);
+                }
+                if ecs
+                    .get::<MerchantOffers>(self.entity)
+                    .is_some_and(|offers| offers.container_id == container.id())
+                {
+                    return Ok(TradingMenu {
+                        container,
+                        client: self.clone(),
+                    });
+                }
+            }
+            if ticks.recv().await.is_err() {
+                break;
+            }
+        }
+        Err(OpenTradesError::NoOffers)
+    }
+
+    /// Go through every villager in the trading hall once, making every trade
+    /// that the hall's filter accepts until the villager runs out of stock or
+    /// we run out of items to pay with.
+    ///
+    /// If we don't have enough items for a trade, we'll go to the hall's
+    /// supply chests to get more first. Villagers restock twice a day, so you
+    /// probably want to call this in a loop.
+    ///
+    /// ```no_run
+    /// # use azalea::prelude::*;
+    /// # use azalea::trading::{TradeClientExt, TradingHall};
+    /// # use azalea::villagers::VillagerClientExt;
+    /// # use azalea::registry::{Item, VillagerProfession};
+    /// # use azalea::BlockPos;
+    /// # async fn example(mut bot: Client) {
+    /// let hall = TradingHall::new(bot.villagers(VillagerProfession::Librarian))
+    ///     .with_supply_chests(vec![BlockPos::new(0, 64, 0)])
+    ///     .with_wanted_items(vec![Item::EnchantedBook]);
+    /// loop {
+    ///     let report = bot.run_trading_hall(&hall).await;
+    ///     println!("got {:?}", report.gained);
+    /// }
+    /// # }
+    /// ```
+    async fn run_trading_hall(&mut self, hall: &TradingHall) -> TradingHallReport {
+        let mut report = TradingHallReport::default();
+
+        for &villager in &hall.villagers {
+            let villager_position = self.ecs.lock().get::<Position>(villager).copied();
+            let Some(villager_position) = villager_position else {
+                debug!("Villager {villager:?} isn't loaded anymore, skipping");
+                continue;
+            };
+            self.goto(RadiusGoal {
+                pos: *villager_position,
+                radius: 2.,
+            });
+            self.wait_until_goto_target_reached().await;
+
+            let mut restocked = false;
+            'reopen: loop {
+                let menu = match self.open_trades(villager).await {
+                    Ok(menu) => menu,
+                    Err(err) => {
+                        warn!("Couldn't open the trades of villager {villager:?}: {err}");
+                        break;
+                    }
+                };
+                report.villagers_visited += 1;
+
+                for (index, offer) in menu.offers().iter().enumerate() {
+                    if offer.out_of_stock || !(hall.should_trade)(offer) {
+                        continue;
+                    }
+
+                    if !can_afford(&self.menu(), offer) {
+                        if restocked || hall.supply_chests.is_empty() {
+                            continue;
+                        }
+                        // we close the menu by dropping it
+                        drop(menu);
+                        restock_for(self, hall, offer).await;
+                        restocked = true;
+
+                        self.goto(RadiusGoal {
+                            pos: *villager_position,
+                            radius: 2.,
+                        });
+                        self.wait_until_goto_target_reached().await;
+                        continue 'reopen;
+                    }
+
+                    while let Some((kind, gained)) = menu.trade(index).await {
+                        *report.gained.entry(kind).or_default() += gained;
+                    }
+                }
+                break;
+            }
+        }
+
+        report
+    }
+}
+
+/// Take the items that we need to pay for the offer from the trading hall's
+/// supply chests.
+async fn restock_for(bot: &mut Client, hall: &TradingHall, offer: &MerchantOffer) {
+    let mut wanted = HashMap::<Item, i32>::new();
+    for (kind, _) in costs(offer) {
+        // get a full stack so we don't have to come back as often
+        *wanted.entry(kind).or_default() += kind.max_stack_size();
+    }
+
+    for &chest in &hall.supply_chests {
+        if wanted.values().all(|&count| count <= 0) {
+            break;
+        }
+        let chunk_storage = bot.world().read().chunks.clone();
+        bot.goto(ReachBlockPosGoal {
+            pos: chest,
+            chunk_storage,
+        });
+        bot.wait_until_goto_target_reached().await;
+
+        let Some(container) = bot.open_container_at(chest).await else {
+            warn!("Couldn't open supply chest at {chest}");
+            continue;
+        };
+        let Some(contents) = container.contents() else {
+            continue;
+        };
+        for (slot, item) in contents.iter().enumerate() {
+            let Some(remaining) = wanted.get_mut(&item.kind()) else {
+                continue;
+            };
+            if *remaining <= 0 {
+                continue;
+            }
+            container.click(QuickMoveClick::Left { slot: slot as u16 });
+            *remaining -= item.count();
+        }
+    }
+}
+
+/// A trading menu that we have open with a villager or wandering trader. The
+/// menu is closed when this is dropped.
+pub struct TradingMenu {
+    container: ContainerHandle,
+    client: Client,
+}
+impl Debug for TradingMenu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradingMenu")
+            .field("id", &self.container.id())
+            .finish()
+    }
+}
+impl TradingMenu {
+    /// The trades that the merchant is offering.
+    pub fn offers(&self) -> Vec<MerchantOffer> {
+        self.client
+            .get_component::<MerchantOffers>()
+            .map(|offers| offers.offers)
+            .unwrap_or_default()
+    }
+
+    /// Make the trade at the given index as many times as we can, and return
+    /// what we got from it.
+    ///
+    /// This returns `None` if no trade happened, which usually means that
+    /// we don't have the items to pay for it or that the trade is locked.
+    pub async fn trade(&self, index: usize) -> Option<(Item, i32)> {
+        let offer = self.offers().get(index)?.clone();
+        let result_kind = offer.result.kind();
+        let count_before = count_items(&self.container.menu()?, result_kind);
+
+        self.client.ecs.lock().send_event(SelectTradeEvent {
+            entity: self.client.entity,
+            index: index as u32,
+        });
+
+        // wait for the server to put the result in the result slot
+        let mut ticks = self.client.get_tick_broadcaster();
+        let mut has_result = false;
+        for _ in 0..5 {
+            let _ = ticks.recv().await;
+            let menu = self.container.menu()?;
+            if menu
+                .slot(RESULT_SLOT as usize)
+                .is_some_and(|result| result.is_present())
+            {
+                has_result = true;
+                break;
+            }
+        }
+        if !has_result {
+            return None;
+        }
+
+        // shift clicking the result makes the trade repeatedly until we run out
+        // of items or the trade gets locked
+        self.container
+            .click(QuickMoveClick::Left { slot: RESULT_SLOT });
+        let _ = ticks.recv().await;
+        let _ = ticks.recv().await;
+
+        let gained = count_items(&self.container.menu()?, result_kind) - count_before;
+        if gained <= 0 {
+            return None;
+        }
+        debug!("Traded for {gained}x {result_kind}");
+        Some((result_kind, gained))
+    }
+}
+
+/// A trading hall that can be run with [`TradeClientExt::run_trading_hall`].
+#[derive(Clone)]
+pub struct TradingHall {
+    /// The villagers that we're going to trade with, in order. You can get
+    /// these with [`VillagerClientExt::villagers`].
+    ///
+    /// [`VillagerClientExt::villagers`]: crate::villagers::VillagerClientExt::villagers
+    pub villagers: Vec<Entity>,
+    /// Chests that contain the items we pay with, like emeralds.
+    pub supply_chests: Vec<BlockPos>,
+    /// Decides whether we should make a trade. By default, we only make trades
+    /// that give us emeralds.
+    pub should_trade: Arc<dyn Fn(&MerchantOffer) -> bool + Send + Sync>,
+}
+impl TradingHall {
+    pub fn new(villagers: Vec<Entity>) -> Self {
+        Self {
+            villagers,
+            supply_chests: Vec::new(),
+            should_trade: Arc::new(|offer| offer.result.kind() == Item::Emerald),
+        }
+    }
+
+    pub fn with_supply_chests(mut self, supply_chests: Vec<BlockPos>) -> Self {
+        self.supply_chests = supply_chests;
+        self
+    }
+
+    /// Only make trades that give us one of the given items.
+    pub fn with_wanted_items(mut self, items: Vec<Item>) -> Self {
+        self.should_trade = Arc::new(move |offer| items.contains(&offer.result.kind()));
+        self
+    }
+
+    /// Use a custom function to decide which trades we should make.
+    pub fn with_filter(
+        mut self,
+        should_trade: impl Fn(&MerchantOffer) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.should_trade = Arc::new(should_trade);
+        self
+    }
+}
+
+/// What happened while running a trading hall.
+#[derive(Clone, Debug, Default)]
+pub struct TradingHallReport {
+    /// The number of villagers whose trading menu we opened.
+    pub villagers_visited: usize,
+    /// The items that we got from trading.
+    pub gained: HashMap<Item, i32>,
+}
+
+/// Count how many of the given item are in the player's inventory slots of the
+/// menu.
+fn count_items(menu: &Menu, kind: Item) -> i32 {
+    menu.count_in(menu.player_slots_range(), &ItemMatcher::from(kind))
+}
+
+/// The number of items that the first cost of the offer actually requires,
+/// after the price multiplier, demand, and special price are applied.
+///
+/// This is the same as `MerchantOffer.getCostA` in vanilla.
+fn adjusted_cost_a(offer: &MerchantOffer) -> i32 {
+    let ItemStack::Present(base_cost) = &offer.base_cost_a else {
+        return 0;
+    };
+    let count = base_cost.count;
+    let demand_diff =
+        ((count as f32 * offer.demand as f32 * offer.price_multiplier).floor() as i32).max(0);
+    (count + demand_diff + offer.special_price_diff).clamp(1, base_cost.kind.max_stack_size())
+}
+
+/// The items (and how many of them) that we have to pay to make the trade
+/// once.
+fn costs(offer: &MerchantOffer) -> Vec<(Item, i32)> {
+    let mut costs = Vec::new();
+    if let ItemStack::Present(cost_a) = &offer.base_cost_a {
+        costs.push((cost_a.kind, adjusted_cost_a(offer)));
+    }
+    if let ItemStack::Present(cost_b) = &offer.cost_b {
+        costs.push((cost_b.kind, cost_b.count));
+    }
+    costs
+}
+
+/// Whether we have enough items in our inventory to make the trade once.
+fn can_afford(menu: &Menu, offer: &MerchantOffer) -> bool {
+    let mut needed = HashMap::<Item, i32>::new();
+    for (kind, count) in costs(offer) {
+        *needed.entry(kind).or_default() += count;
+    }
+    needed
+        .into_iter()
+        .all(|(kind, count)| count_items(menu, kind) >= count)
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_inventory::{ItemStackData, Player};
+
+    use super::*;
+
+    fn item(kind: Item, count: i32) -> ItemStack {
+        ItemStack::Present(ItemStackData {
+            kind,
+            count,
+            components: Default::default(),
+        })
+    }
+
+    fn offer(cost_a: ItemStack, cost_b: ItemStack) -> MerchantOffer {
+        MerchantOffer {
+            base_cost_a: cost_a,
+            result: item(Item::EnchantedBook, 1),
+            cost_b,
+            out_of_stock: false,
+            uses: 0,
+            max_uses: 12,
+            xp: 1,
+            special_price_diff: 0,
+            price_multiplier: 0.2,
+            demand: 0,
+        }
+    }
+
+    fn menu_with(items: &[(usize, ItemStack)]) -> Menu {
+        let mut menu = Menu::Player(Player::default());
+        for (index, item) in items {
+            *menu.slot_mut(*index).unwrap() = item.clone();
+        }
+        menu
+    }
+
+    #[test]
+    fn test_adjusted_cost_a() {
+        let mut offer = offer(item(Item::Emerald, 10), ItemStack::Empty);
+        assert_eq!(adjusted_cost_a(&offer), 10);
+
+        // demand makes it more expensive
+        offer.demand = 5;
+        assert_eq!(adjusted_cost_a(&offer), 20);
+
+        // and curing the villager (or hero of the village) makes it cheaper
+        offer.special_price_diff = -15;
+        assert_eq!(adjusted_cost_a(&offer), 5);
+
+        // but it always costs at least one
+        offer.special_price_diff = -100;
+        assert_eq!(adjusted_cost_a(&offer), 1);
+
+        // and at most a stack
+        offer.special_price_diff = 0;
+        offer.demand = 100;
+        assert_eq!(adjusted_cost_a(&offer), 64);
+    }
+
+    #[test]
+    fn test_can_afford() {
+        let mut offer = offer(item(Item::Emerald, 10), item(Item::Book, 1));
+        let menu = menu_with(&[
+            (9, item(Item::Emerald, 8)),
+            (10, item(Item::Emerald, 4)),
+            (36, item(Item::Book, 1)),
+        ]);
+        assert!(can_afford(&menu, &offer));
+
+        offer.demand = 2;
+        // 10 + floor(10 * 2 * 0.2) = 14
+        assert!(!can_afford(&menu, &offer));
+
+        offer.special_price_diff = -2;
+        assert!(can_afford(&menu, &offer));
+
+        let no_books = menu_with(&[(9, item(Item::Emerald, 64))]);
+        assert!(!can_afford(&no_books, &offer));
+    }
+}
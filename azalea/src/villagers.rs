@@ -0,0 +1,156 @@
+//! Find villagers and wandering traders, optionally filtered by their
+//! profession and level.
+
+use azalea_client::Client;
+use azalea_entity::{
+    metadata::{AbstractAgeableBaby, Villager, VillagerVillagerData, WanderingTrader},
+    Dead, Position,
+};
+use azalea_registry::VillagerProfession;
+use azalea_world::InstanceName;
+use bevy_ecs::{
+    entity::Entity,
+    query::{With, Without},
+};
+
+use crate::pathfinder::{goals::RadiusGoal, PathfinderClientExt};
+
+/// Which villagers we're looking for.
+///
+/// You can also pass a [`VillagerProfession`] anywhere this is accepted to only
+/// filter by profession.
+#[derive(Clone, Debug, Default)]
+pub struct VillagerFilter {
+    pub profession: Option<VillagerProfession>,
+    /// The minimum level of the villager, from 1 (novice) to 5 (master).
+    pub min_level: Option<u32>,
+    /// The maximum level of the villager, from 1 (novice) to 5 (master).
+    pub max_level: Option<u32>,
+}
+impl VillagerFilter {
+    pub fn matches(&self, profession: VillagerProfession, level: u32) -> bool {
+        self.profession.is_none_or(|p| p == profession)
+            && self.min_level.is_none_or(|min| level >= min)
+            && self.max_level.is_none_or(|max| level <= max)
+    }
+}
+impl From<VillagerProfession> for VillagerFilter {
+    fn from(profession: VillagerProfession) -> Self {
+        Self {
+            profession: Some(profession),
+            ..Default::default()
+        }
+    }
+}
+
+pub trait VillagerClientExt {
+    fn villagers(&self, filter: impl Into<VillagerFilter>) -> Vec<Entity>;
+    fn nearest_villager(&self, filter: impl Into<VillagerFilter>) -> Option<Entity>;
+    fn nearest_wandering_trader(&self) -> Option<Entity>;
+    fn goto_nearest_villager(&self, filter: impl Into<VillagerFilter>) -> Option<Entity>;
+}
+
+impl VillagerClientExt for Client {
+    /// Get all of the adult villagers that match the filter in our world,
+    /// sorted by their distance to us.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::villagers::{VillagerClientExt, VillagerFilter};
+    /// # use azalea::registry::VillagerProfession;
+    /// # fn example(bot: &Client) {
+    /// let master_librarians = bot.villagers(VillagerFilter {
+    ///     profession: Some(VillagerProfession::Librarian),
+    ///     min_level: Some(5),
+    ///     ..Default::default()
+    /// });
+    /// # }
+    /// ```
+    fn villagers(&self, filter: impl Into<VillagerFilter>) -> Vec<Entity> {
+        let filter = filter.into();
+
+        let mut ecs = self.ecs.lock();
+        let (position, instance_name) = self.query::<(&Position, &InstanceName)>(&mut ecs);
+        let (position, instance_name) = (**position, instance_name.clone());
+
+        let mut query = ecs.query_filtered::<(
+            Entity,
+            &VillagerVillagerData,
+            &Position,
+            &InstanceName,
+            Option<&AbstractAgeableBaby>,
+        ), (With<Villager>, Without<Dead>)>();
+        let mut villagers = query
+            .iter(&ecs)
+            .filter(|(_, data, _, villager_instance_name, baby)| {
+                **villager_instance_name == instance_name
+                    && !baby.is_some_and(|b| **b)
+                    && filter.matches(data.profession, data.level)
+            })
+            .map(|(entity, _, villager_position, _, _)| {
+                (entity, villager_position.distance_squared_to(&position))
+            })
+            .collect::<Vec<_>>();
+        villagers.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        villagers.into_iter().map(|(entity, _)| entity).collect()
+    }
+
+    /// Get the closest adult villager that matches the filter.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::villagers::VillagerClientExt;
+    /// # use azalea::registry::VillagerProfession;
+    /// # fn example(bot: &Client) {
+    /// let librarian = bot.nearest_villager(VillagerProfession::Librarian);
+    /// # }
+    /// ```
+    fn nearest_villager(&self, filter: impl Into<VillagerFilter>) -> Option<Entity> {
+        self.villagers(filter).into_iter().next()
+    }
+
+    /// Get the closest wandering trader in our world.
+    fn nearest_wandering_trader(&self) -> Option<Entity> {
+        let mut ecs = self.ecs.lock();
+        let (position, instance_name) = self.query::<(&Position, &InstanceName)>(&mut ecs);
+        let (position, instance_name) = (**position, instance_name.clone());
+
+        let mut query = ecs.query_filtered::<(Entity, &Position, &InstanceName), (
+            With<WanderingTrader>,
+            Without<Dead>,
+        )>();
+        query
+            .iter(&ecs)
+            .filter(|(_, _, trader_instance_name)| **trader_instance_name == instance_name)
+            .min_by(|(_, a, _), (_, b, _)| {
+                a.distance_squared_to(&position)
+                    .total_cmp(&b.distance_squared_to(&position))
+            })
+            .map(|(entity, _, _)| entity)
+    }
+
+    /// Start pathfinding to the closest adult villager that matches the
+    /// filter, and return the villager that we're going to.
+    ///
+    /// This returns `None` and doesn't do anything if there are no matching
+    /// villagers.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::villagers::VillagerClientExt;
+    /// # use azalea::registry::VillagerProfession;
+    /// # fn example(bot: &Client) {
+    /// bot.goto_nearest_villager(VillagerProfession::Librarian);
+    /// # }
+    /// ```
+    fn goto_nearest_villager(&self, filter: impl Into<VillagerFilter>) -> Option<Entity> {
+        let villager = self.nearest_villager(filter)?;
+        let villager_position = **self.ecs.lock().get::<Position>(villager)?;
+        self.goto(RadiusGoal {
+            pos: villager_position,
+            radius: 2.,
+        });
+        Some(villager)
+    }
+}
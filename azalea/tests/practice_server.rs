@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use azalea::{practice_server::PracticeServer, prelude::*, registry::Block, BlockPos};
+use tokio::time::{sleep, timeout};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn join_practice_server() {
+    let server = PracticeServer::start().await.unwrap();
+    let (bot, mut events) = Client::join(&Account::offline("bot"), server.address())
+        .await
+        .unwrap();
+
+    timeout(Duration::from_secs(10), async {
+        while server.players().is_empty() {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the bot should have joined the server");
+    assert_eq!(server.players(), vec!["bot".to_string()]);
+
+    server.broadcast("hello");
+    timeout(Duration::from_secs(10), async {
+        while let Some(event) = events.recv().await {
+            if let Event::Chat(m) = event {
+                if m.message().to_string() == "hello" {
+                    return;
+                }
+            }
+        }
+        panic!("the bot disconnected before getting the message");
+    })
+    .await
+    .expect("the bot should have received the message");
+
+    // the chunks are sent before the bot is added to the list of players, so
+    // they should be loaded by now
+    assert_eq!(
+        bot.world()
+            .read()
+            .get_block_state(&BlockPos::new(0, -61, 0)),
+        Some(Block::GrassBlock.into())
+    );
+
+    bot.disconnect();
+}
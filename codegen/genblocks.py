@@ -1,5 +1,6 @@
 import lib.code.version
 import lib.code.shapes
+import lib.code.map_colors
 import lib.code.packet
 import lib.code.blocks
 import lib.code.utils
@@ -24,6 +25,9 @@ def generate(version_id):
     lib.code.shapes.generate_block_shapes(
         pixlyzer_block_datas, shape_datas['shapes'], shape_datas['aabbs'], block_states_report)
 
+    lib.code.map_colors.generate_map_colors(
+        pixlyzer_block_datas, block_states_report)
+
     lib.code.utils.fmt()
 
     print('Done!')
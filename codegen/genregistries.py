@@ -1,4 +1,5 @@
 import lib.code.inventory
+import lib.code.items
 import lib.code.registry
 import lib.code.version
 import lib.code.packet
@@ -14,6 +15,9 @@ def generate(version_id: str):
     lib.code.registry.generate_registries(registries)
     lib.code.inventory.update_menus(registries['minecraft:menu']['entries'])
 
+    items = lib.extract.get_items_report(version_id)
+    lib.code.items.generate_item_max_damage(items, registries)
+
     block_tags = lib.extract.get_registry_tags(version_id, 'block')
     item_tags = lib.extract.get_registry_tags(version_id, 'item')
     fluid_tags = lib.extract.get_registry_tags(version_id, 'fluid')
@@ -0,0 +1,38 @@
+from lib.utils import to_camel_case, upper_first_letter, get_dir_location
+
+MAX_DAMAGE_DIR = get_dir_location('../azalea-inventory/src/item/max_damage.rs')
+
+
+def generate_item_max_damage(items_report: dict, registries: dict):
+    '''
+    Generate a function that returns the default `max_damage` component of
+    every item, from the vanilla items report.
+    '''
+    item_registry = registries['minecraft:item']['entries']
+    ordered_items = sorted(item_registry.keys(),
+                           key=lambda item: item_registry[item]['protocol_id'])
+
+    generated = '''// This file was generated by codegen/lib/code/items.py, don't edit it manually!
+
+use azalea_registry::Item;
+
+/// The durability of the item when it's new, or `None` if it can't be damaged.
+///
+/// This is the default value of the `max_damage` component for the item.
+pub fn default_max_damage(item: Item) -> Option<i32> {
+    Some(match item {
+'''
+    for item_id in ordered_items:
+        components = items_report.get(item_id, {}).get('components', {})
+        max_damage = components.get('minecraft:max_damage')
+        if max_damage is None:
+            continue
+        item_name = item_id.split(':')[-1]
+        generated += f'        Item::{upper_first_letter(to_camel_case(item_name))} => {max_damage},\n'
+    generated += '''        _ => return None,
+    })
+}
+'''
+
+    with open(MAX_DAMAGE_DIR, 'w') as f:
+        f.write(generated)
@@ -0,0 +1,56 @@
+from lib.utils import get_dir_location
+from lib.code.shapes import convert_ints_to_rust_ranges
+
+MAP_COLORS_RS_DIR = get_dir_location(
+    '../azalea-world/src/map_render/block_colors.rs')
+
+
+def generate_map_colors(blocks_pixlyzer: dict, block_states_report: dict):
+    # { map color id: [block state ids] }
+    state_ids_by_color = {}
+    for block_id, block_report_data in block_states_report.items():
+        block_data_pixlyzer = blocks_pixlyzer.get(block_id)
+        if block_data_pixlyzer is None:
+            print(f'Warning: no map color for {block_id}, it\'ll be invisible on maps')
+            continue
+        pixlyzer_states = block_data_pixlyzer['states'].values()
+        for possible_state, state_data in zip(block_report_data['states'], pixlyzer_states):
+            # some blocks (like beds and logs) have a different color depending on
+            # their state
+            map_color = state_data.get(
+                'map_color', block_data_pixlyzer.get('map_color', 0))
+            state_ids_by_color.setdefault(map_color, []).append(
+                possible_state['id'])
+
+    code = generate_map_colors_code(state_ids_by_color)
+    with open(MAP_COLORS_RS_DIR, 'w') as f:
+        f.write(code)
+
+
+def generate_map_colors_code(state_ids_by_color: dict):
+    match_arms_code = ''
+    for map_color, block_state_ids in sorted(state_ids_by_color.items()):
+        # 0 (none) is the default, so it doesn't need an arm
+        if map_color == 0:
+            continue
+        match_arms_code += f'        {convert_ints_to_rust_ranges(block_state_ids)} => {map_color},\n'
+
+    return f'''
+//! Autogenerated map colors for every block state
+
+// This file is generated from codegen/lib/code/map_colors.py. If you want to
+// modify it, change that file.
+
+use azalea_block::BlockState;
+
+/// Get the ID of the base map color for the block state, or 0 if it's
+/// invisible on maps (like air and glass).
+///
+/// These are the same as the colors vanilla gives to blocks with
+/// `BlockBehaviour.Properties::mapColor`.
+pub fn block_state_map_color(state: BlockState) -> u8 {{
+    match state.id {{
+{match_arms_code}        _ => 0,
+    }}
+}}
+'''
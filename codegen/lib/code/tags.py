@@ -30,5 +30,76 @@ use crate::{struct_name};
             generated += f'{struct_name}::{upper_first_letter(to_camel_case(item_name))},\n'
         generated += ']));\n'
 
+    generated += generate_tag_enum(
+        sorted(registries.keys()), struct_name)
+
     with open(tags_dir, 'w') as f:
-        f.write(generated)
\ No newline at end of file
+        f.write(generated)
+
+
+def generate_tag_enum(tag_names: list[str], struct_name: str):
+    '''
+    Generate an enum with a variant for every tag, so tags can be checked like
+    `Block::Stone.is_in(BlockTag::MineablePickaxe)`.
+    '''
+    enum_name = f'{struct_name}Tag'
+    variants = [(tag_name, to_camel_case(tag_name),
+                 to_snake_case(tag_name.replace('/', '_')).upper()) for tag_name in tag_names]
+
+    generated = f'''
+/// A vanilla {struct_name.lower()} tag, which can be checked with [`{struct_name}::is_in`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum {enum_name} {{
+'''
+    for _, variant, _ in variants:
+        generated += f'{variant},\n'
+    generated += f'''}}
+
+impl {enum_name} {{
+    /// Every vanilla {struct_name.lower()} tag.
+    pub const ALL: &'static [{enum_name}] = &[
+'''
+    for _, variant, _ in variants:
+        generated += f'{enum_name}::{variant},\n'
+    generated += f'''];
+
+    /// The name of the tag without the `minecraft:` namespace, like
+    /// `mineable/pickaxe`.
+    pub fn name(self) -> &'static str {{
+        match self {{
+'''
+    for tag_name, variant, _ in variants:
+        generated += f'{enum_name}::{variant} => "{tag_name}",\n'
+    generated += f'''}}
+    }}
+
+    /// Get the tag from its name, which can start with `minecraft:`.
+    pub fn from_name(name: &str) -> Option<Self> {{
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {{
+'''
+    for tag_name, variant, _ in variants:
+        generated += f'"{tag_name}" => {enum_name}::{variant},\n'
+    generated += f'''_ => return None,
+        }})
+    }}
+
+    /// Everything that's in the tag.
+    pub fn values(self) -> &'static HashSet<{struct_name}> {{
+        match self {{
+'''
+    for _, variant, static_set_name in variants:
+        generated += f'{enum_name}::{variant} => &{static_set_name},\n'
+    generated += f'''}}
+    }}
+}}
+
+impl {struct_name} {{
+    /// Whether this is in the given vanilla tag. Servers can change tags with
+    /// data packs, but this only knows about the vanilla ones.
+    pub fn is_in(self, tag: {enum_name}) -> bool {{
+        tag.values().contains(&self)
+    }}
+}}
+'''
+    return generated
\ No newline at end of file
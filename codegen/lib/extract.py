@@ -28,6 +28,8 @@ def get_registries_report(version_id: str):
     return get_report(version_id, 'registries')
 def get_packets_report(version_id: str):
     return get_report(version_id, 'packets')
+def get_items_report(version_id: str):
+    return get_report(version_id, 'items')
 def get_report(version_id: str, name: str):
     generate_data_from_server_jar(version_id)
     with open(get_dir_location(f'__cache__/generated-{version_id}/reports/{name}.json'), 'r') as f: